@@ -0,0 +1,66 @@
+// Copyright © 2025 Nipun Kumar
+
+// Renders LaTeX math emitted by md2rsx using KaTeX.
+//
+// md2rsx outputs `.math` elements carrying their TeX source in `data-tex`.
+// This script loads KaTeX, renders every such element, and keeps watching the
+// document so streamed and newly opened messages are rendered as they appear.
+(function () {
+    if (window.__mcmcpcpMath) {
+        return;
+    }
+    window.__mcmcpcpMath = true;
+
+    const KATEX_JS = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist/katex.min.js";
+
+    function render(el) {
+        const tex = el.getAttribute("data-tex");
+        if (tex === null || el.getAttribute("data-rendered") === tex) {
+            return;
+        }
+        try {
+            window.katex.render(tex, el, {
+                displayMode: el.classList.contains("math-display"),
+                throwOnError: false,
+            });
+            el.setAttribute("data-rendered", tex);
+        } catch (e) {
+            console.warn("KaTeX render failed", e);
+        }
+    }
+
+    function renderAll(root) {
+        if (root.matches && root.matches(".math[data-tex]")) {
+            render(root);
+        }
+        if (root.querySelectorAll) {
+            root.querySelectorAll(".math[data-tex]").forEach(render);
+        }
+    }
+
+    function start() {
+        renderAll(document.body);
+        new MutationObserver(function (mutations) {
+            for (const m of mutations) {
+                if (m.type === "attributes") {
+                    render(m.target);
+                } else {
+                    m.addedNodes.forEach(renderAll);
+                }
+            }
+        }).observe(document.body, {
+            childList: true,
+            subtree: true,
+            attributes: true,
+            attributeFilter: ["data-tex"],
+        });
+    }
+
+    const script = document.createElement("script");
+    script.src = KATEX_JS;
+    script.onload = start;
+    script.onerror = function () {
+        console.warn("Could not load KaTeX; math is shown as TeX source");
+    };
+    document.head.appendChild(script);
+})();
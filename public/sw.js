@@ -0,0 +1,68 @@
+// Copyright © 2025 Nipun Kumar
+
+// Service worker of the web build. It keeps the app shell and the assets it
+// loads cached, so the installed app opens without a connection. Chats and
+// settings live in IndexedDB and need no caching here.
+
+const CACHE = "mcmcpcp-v1";
+const SHELL = ["./", "./manifest.webmanifest", "./icon.svg"];
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    caches
+      .open(CACHE)
+      .then((cache) => cache.addAll(SHELL))
+      .then(() => self.skipWaiting())
+  );
+});
+
+// Drop the caches of older versions
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) =>
+        Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key)))
+      )
+      .then(() => self.clients.claim())
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  // Provider and MCP requests go straight to the network
+  if (request.method !== "GET" || url.origin !== self.location.origin) {
+    return;
+  }
+  if (request.mode === "navigate") {
+    // Every route is the same page: the network first so updates arrive,
+    // the cached shell when offline
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          if (response.ok) {
+            const copy = response.clone();
+            caches.open(CACHE).then((cache) => cache.put("./", copy));
+          }
+          return response;
+        })
+        .catch(() => caches.match("./"))
+    );
+    return;
+  }
+  // Bundled assets have hashed names, so a cached copy is never out of date
+  event.respondWith(
+    caches.match(request).then(
+      (cached) =>
+        cached ||
+        fetch(request).then((response) => {
+          if (response.ok) {
+            const copy = response.clone();
+            caches.open(CACHE).then((cache) => cache.put(request, copy));
+          }
+          return response;
+        })
+    )
+  );
+});
@@ -0,0 +1,165 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Prompt A/B testing: one user prompt answered under two system prompts or
+//! toolsets, side by side.
+//!
+//! Each variant runs in a fresh conversation with its own toolset, so the two
+//! runs share nothing but the prompt and the model. The user votes on the
+//! outputs, and votes are kept in storage so variants can be compared over
+//! many prompts.
+
+use std::sync::Arc;
+
+use crate::{
+    app_settings::{AbTrial, AbVariant, AbVote},
+    core::{ToolsetHooks, final_answer, run_tools_loop},
+    llm::{ContentPart, LlmClient, Message},
+    mcp::host::MCPHost,
+    storage::{Storage, get_storage},
+    toolset::registry,
+};
+
+/// Votes for the variants of trials that used the same pair of labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbTally {
+    /// Labels of the variants, A then B
+    pub labels: [String; 2],
+    /// Trials won by each variant, A then B
+    pub wins: [usize; 2],
+    pub ties: usize,
+}
+
+/// Runs a prompt under one variant, with the tool calls the model makes.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to answer
+/// * `host` - Global MCP host
+/// * `variant` - System prompt and toolset to run with
+/// * `prompt` - The user prompt
+/// * `max_failures` - Consecutive failed tool calls at which the run stops
+///
+/// # Returns
+/// The final answer, or an error if the run failed
+pub async fn run_variant(
+    client: &LlmClient,
+    model: &str,
+    host: Arc<MCPHost>,
+    variant: &AbVariant,
+    prompt: &str,
+    max_failures: u8,
+) -> anyhow::Result<String> {
+    let (toolset, _) = registry::create_new(&variant.toolset, host);
+    let mut messages = vec![
+        Message::System {
+            content: variant.system_prompt.clone(),
+        },
+        Message::User {
+            content: vec![ContentPart::Text {
+                text: prompt.to_string(),
+            }],
+        },
+    ];
+    run_tools_loop(
+        client,
+        model,
+        toolset.get_mcp_host(),
+        &mut messages,
+        toolset.max_tool_rounds(),
+        max_failures,
+        &mut ToolsetHooks(toolset.as_ref()),
+    )
+    .await?;
+    let answer = final_answer(&messages);
+    if answer.is_empty() {
+        anyhow::bail!("The model gave no answer");
+    }
+    Ok(answer)
+}
+
+/// Runs a prompt under both variants at once.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to answer
+/// * `host` - Global MCP host
+/// * `variants` - The two variants, A then B
+/// * `prompt` - The user prompt
+/// * `max_failures` - Consecutive failed tool calls at which a run stops
+///
+/// # Returns
+/// The final answer of each variant, or why it failed, A then B
+pub async fn run_trial(
+    client: &LlmClient,
+    model: &str,
+    host: Arc<MCPHost>,
+    variants: &[AbVariant; 2],
+    prompt: &str,
+    max_failures: u8,
+) -> [Result<String, String>; 2] {
+    let [a, b] = variants;
+    let (a, b) = futures::join!(
+        run_variant(client, model, host.clone(), a, prompt, max_failures),
+        run_variant(client, model, host, b, prompt, max_failures),
+    );
+    [a, b].map(|r| r.map_err(|e| e.to_string()))
+}
+
+/// Lists the recorded trials.
+///
+/// # Returns
+/// The trials, newest first
+pub async fn list_trials() -> anyhow::Result<Vec<AbTrial>> {
+    let mut trials = get_storage().await?.load_ab_trials().await?;
+    trials.reverse();
+    Ok(trials)
+}
+
+/// Records a trial and the user's vote.
+///
+/// # Arguments
+/// * `trial` - The trial
+pub async fn record_trial(trial: AbTrial) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let mut trials = storage.load_ab_trials().await?;
+    trials.push(trial);
+    storage.save_ab_trials(&trials).await
+}
+
+/// Deletes all recorded trials.
+pub async fn clear_trials() -> anyhow::Result<()> {
+    get_storage().await?.save_ab_trials(&[]).await
+}
+
+/// Counts the votes of trials by the labels of their variants, so the same
+/// pair of prompts can be compared over many trials.
+///
+/// # Arguments
+/// * `trials` - The recorded trials
+///
+/// # Returns
+/// One tally per pair of labels, in the order the pairs first appear
+pub fn tally(trials: &[AbTrial]) -> Vec<AbTally> {
+    let mut tallies: Vec<AbTally> = vec![];
+    for trial in trials {
+        let labels = trial.variants.clone().map(|v| v.label);
+        let index = match tallies.iter().position(|t| t.labels == labels) {
+            Some(i) => i,
+            None => {
+                tallies.push(AbTally {
+                    labels,
+                    wins: [0, 0],
+                    ties: 0,
+                });
+                tallies.len() - 1
+            }
+        };
+        let t = &mut tallies[index];
+        match trial.vote {
+            AbVote::A => t.wins[0] += 1,
+            AbVote::B => t.wins[1] += 1,
+            AbVote::Tie => t.ties += 1,
+        }
+    }
+    tallies
+}
@@ -1,9 +1,30 @@
 // Copyright © 2025 Nipun Kumar
 
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use dioxus::logger::tracing::warn;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{llm::Message, mcp::ServerSpec};
+use crate::{
+    core::{MAX_TOOL_FAILURES, RemovedTurn, estimate_message_tokens, remove_turn, replace_turn, restore_turn, turn_range},
+    llm::{ContentPart, DEFAULT_STREAM_IDLE_TIMEOUT, LlmClient, Message, ProviderCheck},
+    mcp::{
+        ServerSpec, openapi::OpenApiSettings, permissions::Permissions, plugin::PluginSpec,
+        scripted::ScriptedTool, shell::ShellSettings,
+    },
+};
+
+/// Base URL of the OpenRouter API
+pub const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1";
+
+/// Recently used models remembered for the model switcher
+const MAX_RECENT_MODELS: usize = 12;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AppSettings {
@@ -11,6 +32,251 @@ pub struct AppSettings {
     pub provider: ProviderSettings,
     pub last_chat_id: Option<u32>,
     pub mcp_servers: Option<Vec<ServerSpec>>,
+    #[serde(default)]
+    pub display: DisplaySettings,
+    /// Remembered answers to MCP permission prompts, by server ID
+    #[serde(default)]
+    pub permissions: Permissions,
+    /// Seconds a streaming response may go without data before it is aborted
+    #[serde(default = "default_stream_idle_timeout")]
+    pub stream_idle_timeout_secs: u64,
+    /// Record raw LLM requests and responses for the debug panel
+    #[serde(default)]
+    pub debug_logging: bool,
+    /// Whether models accept tools, overriding the provider's model metadata,
+    /// by model ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_support: BTreeMap<String, bool>,
+    /// Consecutive failed tool calls at which a turn stops with the error;
+    /// fewer are reported to the model so it can retry
+    #[serde(default = "default_max_tool_failures")]
+    pub max_tool_failures: u8,
+    /// WASM plugins serving builtin tools (native only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PluginSpec>,
+    /// Tools defined as Rhai scripts (native only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripted_tools: Vec<ScriptedTool>,
+    /// Builtin server running allowlisted commands (native only)
+    #[serde(default)]
+    pub shell: ShellSettings,
+    /// Builtin server calling REST APIs described by OpenAPI documents (native only)
+    #[serde(default)]
+    pub openapi: OpenApiSettings,
+    /// Abbreviations expanded in the message input with Tab
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippets: Vec<Snippet>,
+    /// Whether story chapters that changed are summarized in the background
+    /// after each turn
+    #[serde(default = "default_true")]
+    pub auto_summarize_chapters: bool,
+    /// Providers of recently used models, most recent first, each with its
+    /// model set. The model switcher lists them, and switching to a model of
+    /// another provider takes that provider's settings from here
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_models: Vec<ProviderSettings>,
+    /// Result of the last connection test of each kind of provider, by
+    /// [`ProviderSettings::kind`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_checks: BTreeMap<String, ProviderCheck>,
+    /// Named sets of MCP servers, switched between from the tools panel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub server_groups: Vec<ServerGroup>,
+}
+
+impl AppSettings {
+    /// How long a streaming response may stall before it is aborted.
+    pub fn stream_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.stream_idle_timeout_secs.max(1))
+    }
+
+    /// Creates a client for the provider's API with the configured timeout and
+    /// tool support overrides.
+    pub fn client(&self) -> LlmClient {
+        self.provider
+            .client()
+            .with_idle_timeout(self.stream_idle_timeout())
+            .with_tool_support(self.tool_support.clone())
+    }
+
+    /// Settings of the provider serving a model: the current provider if it
+    /// is of the same kind, else the most recently used one of that kind.
+    ///
+    /// # Arguments
+    /// * `choice` - The model and the kind of provider serving it
+    ///
+    /// # Returns
+    /// The provider with the model set, or `None` if no provider of that kind
+    /// was configured
+    pub fn provider_for(&self, choice: &ModelChoice) -> Option<ProviderSettings> {
+        let mut provider = std::iter::once(&self.provider)
+            .chain(&self.recent_models)
+            .find(|p| p.kind() == choice.provider)?
+            .clone();
+        provider.set_model(choice.model.clone());
+        Some(provider)
+    }
+
+    /// The settings a chat runs with: these, with the chat's own model if it
+    /// has one whose provider is still known.
+    ///
+    /// # Arguments
+    /// * `choice` - The chat's model, if it overrides the one in the settings
+    pub fn for_chat(mut self, choice: Option<&ModelChoice>) -> Self {
+        if let Some(provider) = choice.and_then(|c| self.provider_for(c)) {
+            self.provider = provider;
+        }
+        self
+    }
+
+    /// Records a model as the most recently used one.
+    ///
+    /// A model of the current provider's kind is recorded with the current
+    /// provider's settings, so values from config file and environment
+    /// overrides are not stored with it.
+    ///
+    /// # Arguments
+    /// * `used` - The provider the model was used with, with its model set
+    ///
+    /// # Returns
+    /// Whether the list changed and should be saved
+    pub fn remember_model(&mut self, used: &ProviderSettings) -> bool {
+        let Some(model) = used.get_model() else {
+            return false;
+        };
+        let entry = if used.kind() == self.provider.kind() {
+            let mut provider = self.provider.clone();
+            provider.set_model(model.clone());
+            provider
+        } else {
+            used.clone()
+        };
+        if self.recent_models.first() == Some(&entry) {
+            return false;
+        }
+        self.recent_models.retain(|p| {
+            p.kind() != entry.kind() || p.get_model().as_deref() != Some(model.as_str())
+        });
+        self.recent_models.insert(0, entry);
+        self.recent_models.truncate(MAX_RECENT_MODELS);
+        true
+    }
+
+    /// IDs of the enabled MCP servers.
+    pub fn enabled_servers(&self) -> Vec<String> {
+        self.mcp_servers
+            .iter()
+            .flatten()
+            .filter(|s| s.enabled)
+            .map(|s| s.id.clone())
+            .collect()
+    }
+
+    /// The server group whose servers are exactly the enabled ones, if any.
+    /// Servers of the group that were removed since are not counted.
+    pub fn active_server_group(&self) -> Option<&ServerGroup> {
+        let configured: Vec<&String> = self.mcp_servers.iter().flatten().map(|s| &s.id).collect();
+        let mut enabled = self.enabled_servers();
+        enabled.sort();
+        self.server_groups.iter().find(|g| {
+            let mut servers: Vec<String> = g
+                .servers
+                .iter()
+                .filter(|id| configured.contains(id))
+                .cloned()
+                .collect();
+            servers.sort();
+            servers == enabled
+        })
+    }
+
+    /// Enables the servers of a group and disables all others.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the group
+    ///
+    /// # Returns
+    /// Whether the group exists
+    pub fn switch_server_group(&mut self, name: &str) -> bool {
+        let Some(group) = self.server_groups.iter().find(|g| g.name == name) else {
+            return false;
+        };
+        for server in self.mcp_servers.iter_mut().flatten() {
+            server.enabled = group.servers.contains(&server.id);
+        }
+        true
+    }
+}
+
+/// A named set of MCP servers, such as "Research" or "Coding". Switching to
+/// it enables its servers and disables the others.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerGroup {
+    /// Name shown in the tools panel
+    pub name: String,
+    /// IDs of the servers in the group
+    pub servers: Vec<String>,
+}
+
+/// A model, and the kind of provider serving it. Chats can choose one
+/// instead of the model in the settings; the provider's settings are looked
+/// up when the chat runs, so keys are not stored with chats.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelChoice {
+    /// Kind of provider, as in [`ProviderSettings::kind`]
+    pub provider: String,
+    /// ID of the model
+    pub model: String,
+}
+
+fn default_stream_idle_timeout() -> u64 {
+    DEFAULT_STREAM_IDLE_TIMEOUT.as_secs()
+}
+
+fn default_max_tool_failures() -> u8 {
+    MAX_TOOL_FAILURES
+}
+
+/// Preferences controlling how chat content is rendered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DisplaySettings {
+    /// Render `$...$` and `$$...$$` spans as LaTeX math using KaTeX
+    #[serde(default = "default_true")]
+    pub render_math: bool,
+    /// Send messages with Enter, using Shift+Enter for line breaks; otherwise
+    /// Enter inserts line breaks and Ctrl+Enter sends
+    #[serde(default = "default_true")]
+    pub enter_sends: bool,
+    /// Render HTML in messages, keeping only formatting, tables and links;
+    /// otherwise it is shown as text
+    #[serde(default = "default_true")]
+    pub raw_html: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            render_math: true,
+            enter_sends: true,
+            raw_html: true,
+        }
+    }
+}
+
+/// Text inserted in place of an abbreviation typed in the message input.
+///
+/// The expansion may contain placeholders like `{{tone}}`; after expanding,
+/// Tab selects each one in turn so it can be typed over.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Snippet {
+    /// What is typed, e.g. "sty"
+    pub abbreviation: String,
+    /// What it is replaced with
+    pub expansion: String,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,26 +285,140 @@ pub enum ProviderSettings {
     OpenRouter {
         api_key: String,
         model: Option<String>,
+        #[serde(default, skip_serializing_if = "ProviderOptions::is_empty")]
+        options: ProviderOptions,
     },
     Ollama {
         api_url: String,
         model: Option<String>,
+        #[serde(default, skip_serializing_if = "ProviderOptions::is_empty")]
+        options: ProviderOptions,
+        /// How long the model stays loaded after a request, e.g. "30m" or
+        /// "-1" for until the server stops; the server's default if `None`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        keep_alive: Option<String>,
     },
 }
 
+/// Extra headers and body fields sent with every request to a provider.
+///
+/// These pass through options the client has no settings for, such as
+/// OpenRouter's attribution headers (`HTTP-Referer`, `X-Title`) and its
+/// `provider`, `transforms` and `route` fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProviderOptions {
+    /// HTTP headers, by name
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Fields added to chat completion request bodies
+    #[serde(default)]
+    pub body: serde_json::Map<String, Value>,
+}
+
+impl ProviderOptions {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.body.is_empty()
+    }
+
+    /// Checks that the headers can be sent.
+    ///
+    /// # Returns
+    /// An error naming the first invalid header name or value
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, value) in &self.headers {
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name {name:?}"))?;
+            reqwest::header::HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header {name}"))?;
+        }
+        Ok(())
+    }
+}
+
 impl ProviderSettings {
+    /// Kind of provider, as stored: "openrouter" or "ollama".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProviderSettings::OpenRouter { .. } => "openrouter",
+            ProviderSettings::Ollama { .. } => "ollama",
+        }
+    }
+
+    /// Name of the kind of provider, for display.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProviderSettings::OpenRouter { .. } => "OpenRouter",
+            ProviderSettings::Ollama { .. } => "Ollama",
+        }
+    }
+
+    /// The provider's model, as a choice chats can keep.
+    pub fn model_choice(&self) -> Option<ModelChoice> {
+        Some(ModelChoice {
+            provider: self.kind().to_string(),
+            model: self.get_model()?,
+        })
+    }
+
     pub fn is_configured(&self) -> bool {
         match &self {
-            ProviderSettings::OpenRouter { api_key, model } => {
+            ProviderSettings::OpenRouter { api_key, model, .. } => {
                 !api_key.is_empty() && model.is_some()
             }
-            ProviderSettings::Ollama { api_url, model } => !api_url.is_empty() && model.is_some(),
+            ProviderSettings::Ollama { api_url, model, .. } => {
+                !api_url.is_empty() && model.is_some()
+            }
         }
     }
 
+    /// Extra headers and body fields for requests to this provider.
+    pub fn get_options(&self) -> &ProviderOptions {
+        match &self {
+            ProviderSettings::OpenRouter { options, .. } => options,
+            ProviderSettings::Ollama { options, .. } => options,
+        }
+    }
+
+    /// Replaces the extra headers and body fields.
+    ///
+    /// # Arguments
+    /// * `new_options` - Options to send from now on
+    pub fn with_options(mut self, new_options: ProviderOptions) -> Self {
+        match &mut self {
+            ProviderSettings::OpenRouter { options, .. } => *options = new_options,
+            ProviderSettings::Ollama { options, .. } => *options = new_options,
+        }
+        self
+    }
+
+    /// Creates a client for this provider's API, sending its extra options.
+    pub fn client(&self) -> LlmClient {
+        let options = self.get_options();
+        let mut body = options.body.clone();
+        if let Some(keep_alive) = self.get_keep_alive() {
+            body.entry("keep_alive").or_insert(keep_alive.into());
+        }
+        LlmClient::new(self.get_api_url(), self.get_api_key().unwrap_or_default())
+            .with_headers(options.headers.clone())
+            .with_extra_body(body)
+    }
+
+    /// How long an Ollama server should keep the model loaded after a request.
+    pub fn get_keep_alive(&self) -> Option<String> {
+        match &self {
+            ProviderSettings::OpenRouter { .. } => None,
+            ProviderSettings::Ollama { keep_alive, .. } => keep_alive.clone(),
+        }
+    }
+
+    /// Whether the provider is a local backend, such as Ollama or llama.cpp.
+    pub fn is_local(&self) -> bool {
+        matches!(self, ProviderSettings::Ollama { .. })
+    }
+
     pub fn get_api_url(&self) -> String {
         match &self {
-            ProviderSettings::OpenRouter { .. } => "https://openrouter.ai/api/v1".to_string(),
+            ProviderSettings::OpenRouter { .. } => OPENROUTER_API_URL.to_string(),
             ProviderSettings::Ollama { api_url, .. } => api_url.clone(),
         }
     }
@@ -56,8 +436,349 @@ impl ProviderSettings {
             ProviderSettings::Ollama { model, .. } => model.clone(),
         }
     }
+
+    pub fn set_model(&mut self, new_model: String) {
+        match self {
+            ProviderSettings::OpenRouter { model, .. } => *model = Some(new_model),
+            ProviderSettings::Ollama { model, .. } => *model = Some(new_model),
+        }
+    }
+}
+
+/// Settings overrides read from a config file and environment variables.
+///
+/// Every field is optional, and only fields that are set replace stored values.
+/// Precedence, from highest to lowest:
+/// 1. Environment variables (`MCMCPCP_PROVIDER`, `MCMCPCP_API_KEY`, `MCMCPCP_API_URL`,
+///    `MCMCPCP_MODEL`, `MCMCPCP_MCP_SERVERS`)
+/// 2. `config.toml` or `config.json` in the project config directory
+/// 3. Settings saved from the Settings page
+///
+/// The sources are read once, at startup; see [`SettingsOverrides::current`].
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+pub struct SettingsOverrides {
+    /// Provider kind, `openrouter` or `ollama`
+    pub provider: Option<String>,
+    /// API key (OpenRouter)
+    pub api_key: Option<String>,
+    /// API URL (Ollama)
+    pub api_url: Option<String>,
+    /// Model to use
+    pub model: Option<String>,
+    /// MCP servers, replacing the stored list (a JSON array in `MCMCPCP_MCP_SERVERS`)
+    pub mcp_servers: Option<Vec<ServerSpec>>,
+    /// Display preferences
+    pub display: Option<DisplaySettings>,
+}
+
+/// Overrides in effect, read on first use
+static OVERRIDES: LazyLock<SettingsOverrides> = LazyLock::new(SettingsOverrides::load);
+
+impl SettingsOverrides {
+    /// Returns the overrides in effect, loading them the first time.
+    ///
+    /// Changes to the config file or environment apply after a restart.
+    pub fn current() -> &'static Self {
+        &OVERRIDES
+    }
+
+    /// Loads overrides from the config file and environment, in precedence order.
+    ///
+    /// Invalid sources are logged and skipped rather than failing startup.
+    ///
+    /// # Returns
+    /// The combined overrides
+    pub fn load() -> Self {
+        let file = Self::from_config_file().unwrap_or_else(|e| {
+            warn!("Ignoring invalid config file: {e:?}");
+            Self::default()
+        });
+        let env = Self::from_env().unwrap_or_else(|e| {
+            warn!("Ignoring invalid environment overrides: {e:?}");
+            Self::default()
+        });
+        file.merge(env)
+    }
+
+    /// Returns whether no setting is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combines two sets of overrides, with fields set in `other` taking precedence.
+    ///
+    /// # Arguments
+    /// * `other` - Overrides with higher precedence
+    ///
+    /// # Returns
+    /// The combined overrides
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            provider: other.provider.or(self.provider),
+            api_key: other.api_key.or(self.api_key),
+            api_url: other.api_url.or(self.api_url),
+            model: other.model.or(self.model),
+            mcp_servers: other.mcp_servers.or(self.mcp_servers),
+            display: other.display.or(self.display),
+        }
+    }
+
+    /// Reads overrides from `MCMCPCP_*` environment variables.
+    ///
+    /// # Returns
+    /// The overrides, or an error if `MCMCPCP_MCP_SERVERS` is not valid JSON
+    pub fn from_env() -> anyhow::Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let mcp_servers = var("MCMCPCP_MCP_SERVERS")
+            .map(|v| serde_json::from_str(&v).context("parsing MCMCPCP_MCP_SERVERS"))
+            .transpose()?;
+        Ok(Self {
+            provider: var("MCMCPCP_PROVIDER"),
+            api_key: var("MCMCPCP_API_KEY"),
+            api_url: var("MCMCPCP_API_URL"),
+            model: var("MCMCPCP_MODEL"),
+            mcp_servers,
+            display: None,
+        })
+    }
+
+    /// Reads overrides from `config.toml` or `config.json` in the config directory.
+    ///
+    /// # Returns
+    /// The overrides, or defaults if there is no config file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_config_file() -> anyhow::Result<Self> {
+        let dir = crate::storage::config_dir();
+        let toml_path = dir.join("config.toml");
+        if toml_path.exists() {
+            let data = std::fs::read_to_string(&toml_path)?;
+            return toml::from_str(&data).with_context(|| format!("parsing {toml_path:?}"));
+        }
+        let json_path = dir.join("config.json");
+        if json_path.exists() {
+            let data = std::fs::read_to_string(&json_path)?;
+            return serde_json::from_str(&data).with_context(|| format!("parsing {json_path:?}"));
+        }
+        Ok(Self::default())
+    }
+
+    /// Config files are not available in the browser.
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_config_file() -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    /// Applies these overrides over stored settings.
+    ///
+    /// # Arguments
+    /// * `settings` - Stored settings, if any
+    ///
+    /// # Returns
+    /// The effective settings. Overrides alone can produce settings when none are
+    /// stored, as long as they specify a provider.
+    pub fn apply(&self, settings: Option<AppSettings>) -> anyhow::Result<Option<AppSettings>> {
+        if self.is_empty() {
+            return Ok(settings);
+        }
+        let mut settings = match settings {
+            Some(s) => s,
+            None if self.provider.is_some() => AppSettings {
+                id: Some(1),
+                provider: ProviderSettings::OpenRouter {
+                    api_key: "".to_string(),
+                    model: None,
+                    options: Default::default(),
+                },
+                last_chat_id: None,
+                mcp_servers: None,
+                display: Default::default(),
+                permissions: Default::default(),
+                stream_idle_timeout_secs: default_stream_idle_timeout(),
+                debug_logging: false,
+                tool_support: Default::default(),
+                max_tool_failures: default_max_tool_failures(),
+                plugins: vec![],
+                scripted_tools: vec![],
+                shell: Default::default(),
+                openapi: Default::default(),
+                snippets: vec![],
+                auto_summarize_chapters: true,
+                recent_models: vec![],
+                provider_checks: Default::default(),
+                server_groups: vec![],
+            },
+            None => return Ok(None),
+        };
+
+        // Switch provider kind if requested, keeping nothing from the old provider
+        match self.provider.as_deref() {
+            Some("openrouter")
+                if !matches!(settings.provider, ProviderSettings::OpenRouter { .. }) =>
+            {
+                settings.provider = ProviderSettings::OpenRouter {
+                    api_key: "".to_string(),
+                    model: None,
+                    options: Default::default(),
+                };
+            }
+            Some("ollama") if !matches!(settings.provider, ProviderSettings::Ollama { .. }) => {
+                settings.provider = ProviderSettings::Ollama {
+                    api_url: "http://localhost:11434/v1".to_string(),
+                    model: None,
+                    options: Default::default(),
+                    keep_alive: None,
+                };
+            }
+            None | Some("openrouter") | Some("ollama") => {}
+            Some(p) => bail!("Unknown provider {p:?}, expected \"openrouter\" or \"ollama\""),
+        }
+
+        match &mut settings.provider {
+            ProviderSettings::OpenRouter { api_key, model, .. } => {
+                if let Some(k) = &self.api_key {
+                    *api_key = k.clone();
+                }
+                if self.model.is_some() {
+                    *model = self.model.clone();
+                }
+            }
+            ProviderSettings::Ollama { api_url, model, .. } => {
+                if let Some(u) = &self.api_url {
+                    *api_url = u.clone();
+                }
+                if self.model.is_some() {
+                    *model = self.model.clone();
+                }
+            }
+        }
+        if self.mcp_servers.is_some() {
+            settings.mcp_servers = self.mcp_servers.clone();
+        }
+        if let Some(d) = &self.display {
+            settings.display = d.clone();
+        }
+        Ok(Some(settings))
+    }
 }
 
+/// Applies config file and environment overrides over stored settings.
+///
+/// Invalid overrides are logged and the stored settings are used unchanged.
+///
+/// # Arguments
+/// * `settings` - Stored settings, if any
+///
+/// # Returns
+/// The effective settings
+pub fn with_overrides(settings: Option<AppSettings>) -> Option<AppSettings> {
+    match SettingsOverrides::current().apply(settings.clone()) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Ignoring settings overrides: {e:?}");
+            settings
+        }
+    }
+}
+
+/// Name of the profile that exists before any others are created.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Named profiles, each with its own settings, MCP servers and chat history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Profiles {
+    /// Profile in use
+    pub active: String,
+    /// All profile names, in creation order
+    pub names: Vec<String>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            names: vec![DEFAULT_PROFILE.to_string()],
+        }
+    }
+}
+
+/// A prompt that is run on a recurring schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledTask {
+    /// Unique ID, the creation time in milliseconds
+    pub id: u64,
+    /// Name shown in the schedule list and notifications
+    pub name: String,
+    /// Prompt sent as the first user message of each run
+    pub prompt: String,
+    /// Toolset the prompt runs in
+    pub toolset: Toolsets,
+    /// Five-field cron expression: minute, hour, day of month, month, day of week
+    pub cron: String,
+    pub enabled: bool,
+    /// When the last run started, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub last_run: Option<u64>,
+    /// Chat created by the last run
+    #[serde(default)]
+    pub last_chat_id: Option<u32>,
+    /// Error from the last run, if it failed
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// One side of a prompt A/B test: the system prompt and toolset a prompt is
+/// run with.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AbVariant {
+    /// Name shown with the variant's output and in the results
+    pub label: String,
+    /// Toolset whose tools the model can call
+    pub toolset: Toolsets,
+    /// System prompt replacing the toolset's own
+    pub system_prompt: String,
+}
+
+/// Which output of an A/B trial the user preferred.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AbVote {
+    A,
+    B,
+    Tie,
+}
+
+/// A prompt run with two variants, and the user's vote on the outputs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AbTrial {
+    /// Unique ID, the time of the vote in milliseconds
+    pub id: u64,
+    /// Model that answered both times
+    pub model: String,
+    /// The user prompt
+    pub prompt: String,
+    /// The two variants, A then B
+    pub variants: [AbVariant; 2],
+    /// Final answers of the two variants, A then B
+    pub outputs: [String; 2],
+    pub vote: AbVote,
+}
+
+/// A saved version of the story of a story chat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorySnapshot {
+    /// Unique ID within the chat, the creation time in milliseconds
+    pub id: u64,
+    /// Name shown in the snapshot list
+    pub name: String,
+    /// Whether the snapshot was taken automatically rather than by the user
+    #[serde(default)]
+    pub auto: bool,
+    /// The story, as stored in the chat's value
+    pub story: Value,
+}
+
+/// Text that takes the place of images removed with [`Chat::strip_images`].
+pub const STRIPPED_IMAGE: &str = "[Image removed to save space]";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Chat {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -65,10 +786,546 @@ pub struct Chat {
     pub chat_type: Toolsets,
     pub messages: Vec<Message>,
     pub value: Value,
+    /// Turns replaced by summaries, kept for reference
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archive: Vec<RemovedTurn>,
+    /// Global MCP servers whose tools this chat does not see
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub disabled_servers: BTreeSet<String>,
+    /// Who wrote the messages, by stable message ID, for those that record it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub authors: BTreeMap<u32, MessageAuthor>,
+    /// Stable IDs of messages always sent to the model, however the
+    /// conversation is shortened
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub pinned: BTreeSet<u32>,
+    /// Files attached to the chat, which the model searches with the
+    /// knowledge tools rather than reading in full
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<ChatDocument>,
+    /// Stable IDs of the messages, by position, for links to them. Messages
+    /// past the end of the list have none yet; see [`Chat::message_id`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub message_ids: Vec<u32>,
+    /// ID of the next message without one
+    #[serde(default)]
+    pub next_message_id: u32,
+    /// When messages were added, in milliseconds since the Unix epoch, by
+    /// stable message ID. Messages of chats from before times were kept
+    /// have none
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message_times: BTreeMap<u32, u64>,
+    /// Labels for finding the chat in the chat history
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Whether the chat is hidden from the chat history unless asked for
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Facts about the chat for sorting and filtering the chat history,
+    /// kept up to date when it is saved
+    #[serde(default)]
+    pub metadata: ChatMetadata,
+    /// Values filled into `{{name}}` placeholders of the system prompt and
+    /// the user's messages when they are sent, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Model the chat uses instead of the one in the settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelChoice>,
+}
+
+/// A chat saved in the middle of a turn, for recovery after a crash.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    /// The chat, with the turn as far as it got and the toolset's state
+    pub chat: Chat,
+    /// State of the global MCP servers, as from `MCPHost::export_states`
+    #[serde(default)]
+    pub server_states: HashMap<String, Value>,
+}
+
+/// Facts about a chat, worked out when it is saved so the chat history can
+/// sort and filter chats without going through their messages.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatMetadata {
+    /// When the chat was first saved, in milliseconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// When the conversation last changed, in milliseconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(default)]
+    pub message_count: usize,
+    /// Estimated tokens of the conversation, as sent to a model
+    #[serde(default)]
+    pub tokens: usize,
+    /// Models that wrote the replies
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub models: BTreeSet<String>,
+    /// Whether the model called any tools
+    #[serde(default)]
+    pub has_tool_calls: bool,
+}
+
+/// A file attached to a chat, as text.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatDocument {
+    /// The file's name, by which the model refers to it
+    pub name: String,
+    /// The text extracted from the file
+    pub text: String,
+    /// Short description of the contents, written by the model once the
+    /// file is attached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Who wrote a message, such as the model that answered or a persona it spoke as.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageAuthor {
+    /// Name shown above the message
+    pub name: String,
+    /// Model that wrote the message, if not the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Emoji or image URL shown next to the name; the name's initial otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+impl MessageAuthor {
+    /// An author that is a model, shown by its name.
+    pub fn model(model: &str) -> Self {
+        Self {
+            name: model.to_string(),
+            model: None,
+            avatar: None,
+        }
+    }
+}
+
+impl Chat {
+    /// Works out the metadata that comes from the messages again.
+    ///
+    /// # Returns
+    /// Whether the conversation changed since the metadata was last worked out
+    pub fn refresh_metadata(&mut self) -> bool {
+        let tokens = self.messages.iter().map(estimate_message_tokens).sum();
+        let changed =
+            tokens != self.metadata.tokens || self.messages.len() != self.metadata.message_count;
+        self.metadata.tokens = tokens;
+        self.metadata.message_count = self.messages.len();
+        self.metadata.models = self
+            .authors
+            .values()
+            .map(|a| a.model.clone().unwrap_or_else(|| a.name.clone()))
+            .collect();
+        self.metadata.has_tool_calls = self.messages.iter().any(|m| {
+            matches!(m, Message::Assistant { tool_calls: Some(calls), .. } if !calls.is_empty())
+        });
+        changed
+    }
+
+    /// Brings the metadata up to date as the chat is saved, noting when it
+    /// was created and when the conversation last changed.
+    ///
+    /// # Arguments
+    /// * `now` - Time of the save, in milliseconds since the Unix epoch
+    pub fn touch(&mut self, now: u64) {
+        let changed = self.refresh_metadata();
+        self.metadata.created_at.get_or_insert(now);
+        if changed || self.metadata.updated_at.is_none() {
+            self.metadata.updated_at = Some(now);
+        }
+    }
+
+    /// Records the author of the assistant messages from `from` on.
+    ///
+    /// # Arguments
+    /// * `from` - Position of the first message to consider
+    /// * `author` - Who wrote them
+    pub fn set_author(&mut self, from: usize, author: &MessageAuthor) {
+        for i in from..self.messages.len() {
+            if matches!(self.messages[i], Message::Assistant { .. }) {
+                self.authors.insert(self.message_id(i), author.clone());
+            }
+        }
+    }
+
+    /// Who wrote the message at a position, if that is recorded.
+    pub fn author(&self, index: usize) -> Option<&MessageAuthor> {
+        self.authors.get(&self.message_id(index))
+    }
+
+    /// Pins or unpins a message. A pinned assistant message brings the
+    /// results of its tool calls with it.
+    ///
+    /// # Arguments
+    /// * `index` - Position of the message
+    /// * `pinned` - Whether it is pinned
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) {
+        if !pinned {
+            self.pinned.remove(&self.message_id(index));
+        } else if index < self.messages.len() {
+            self.pinned.insert(self.message_id(index));
+        }
+    }
+
+    /// Whether the message at a position is pinned.
+    pub fn is_pinned(&self, index: usize) -> bool {
+        self.pinned.contains(&self.message_id(index))
+    }
+
+    /// Unpins a message of a turn that was replaced by a summary.
+    ///
+    /// # Arguments
+    /// * `archived` - Position of the turn in the archive
+    /// * `offset` - Position of the message in the turn
+    pub fn unpin_archived(&mut self, archived: usize, offset: usize) {
+        if let Some(turn) = self.archive.get_mut(archived) {
+            let id = turn.message_id(offset);
+            turn.pinned.remove(&id);
+        }
+    }
+
+    /// The pinned messages to send to the model: those of turns replaced by
+    /// summaries, then those in the conversation.
+    pub fn pinned_messages(&self) -> Vec<Message> {
+        let mut pinned = vec![];
+        for turn in &self.archive {
+            let positions = (0..turn.messages.len()).filter(|&i| turn.is_pinned(i));
+            pinned.extend(pinned_of(&turn.messages, positions));
+        }
+        let positions = (0..self.messages.len()).filter(|&i| self.is_pinned(i));
+        pinned.extend(pinned_of(&self.messages, positions));
+        pinned
+    }
+
+    /// Removes the turn starting with an assistant message, with its authors
+    /// and pins.
+    ///
+    /// # Arguments
+    /// * `index` - Position of the assistant message
+    ///
+    /// # Returns
+    /// What was removed, to pass to [`Chat::restore_turn`], or `None` if
+    /// `index` is not an assistant message
+    pub fn remove_turn(&mut self, index: usize) -> Option<RemovedTurn> {
+        let range = turn_range(&self.messages, index)?;
+        self.assign_message_ids();
+        let ids: Vec<u32> = self.message_ids.drain(range).collect();
+        let mut removed = remove_turn(&mut self.messages, index)?;
+        self.take_marks(&ids, &mut removed);
+        removed.ids = ids;
+        Some(removed)
+    }
+
+    /// Puts back a turn removed with [`Chat::remove_turn`], with its authors
+    /// and pins.
+    ///
+    /// # Arguments
+    /// * `removed` - The removed turn
+    pub fn restore_turn(&mut self, mut removed: RemovedTurn) {
+        let index = removed.index.min(self.messages.len());
+        self.assign_message_ids();
+        // Turns removed before messages had IDs get new ones, and kept their
+        // authors and pins by position in the turn
+        let mut ids = Vec::with_capacity(removed.messages.len());
+        for offset in 0..removed.messages.len() {
+            if let Some(&id) = removed.ids.get(offset) {
+                ids.push(id);
+                continue;
+            }
+            let id = self.next_message_id;
+            self.next_message_id += 1;
+            let old = offset as u32;
+            if let Some(author) = removed.authors.remove(&old) {
+                self.authors.insert(id, author);
+            }
+            if removed.pinned.remove(&old) {
+                self.pinned.insert(id);
+            }
+            ids.push(id);
+        }
+        self.message_ids.splice(index..index, ids);
+        self.authors.append(&mut removed.authors);
+        self.pinned.append(&mut removed.pinned);
+        restore_turn(&mut self.messages, removed);
+    }
+
+    /// Replaces the turn starting with an assistant message with a summary,
+    /// which keeps the author of the assistant message. Pinned messages of
+    /// the turn stay pinned in the archive, and are still sent to the model.
+    ///
+    /// # Arguments
+    /// * `index` - Position of the assistant message
+    /// * `summary` - Text of the replacement message
+    ///
+    /// # Returns
+    /// The replaced messages, for archiving, or `None` if `index` is not an
+    /// assistant message
+    pub fn replace_turn(&mut self, index: usize, summary: String) -> Option<RemovedTurn> {
+        let range = turn_range(&self.messages, index)?;
+        self.assign_message_ids();
+        // The summary takes the place, and the ID, of the assistant message
+        let summary_id = self.message_id(index);
+        let ids: Vec<u32> = self
+            .message_ids
+            .splice(range.clone(), [summary_id])
+            .collect();
+        let mut removed = replace_turn(&mut self.messages, index, summary)?;
+        self.take_marks(&ids, &mut removed);
+        if let Some(author) = removed.authors.get(&summary_id) {
+            self.authors.insert(summary_id, author.clone());
+        }
+        removed.ids = ids;
+        Some(removed)
+    }
+
+    /// Stable ID of the message at a position, which stays the same when
+    /// turns before it are removed or summarized.
+    ///
+    /// Messages added since the IDs were last assigned get the IDs
+    /// [`Chat::assign_message_ids`] will give them, so chats saved before
+    /// messages had IDs number them by position.
+    ///
+    /// # Arguments
+    /// * `index` - Position of the message
+    pub fn message_id(&self, index: usize) -> u32 {
+        match self.message_ids.get(index) {
+            Some(&id) => id,
+            None => self.next_message_id + (index - self.message_ids.len()) as u32,
+        }
+    }
+
+    /// Notes the time of the messages from a position on, as they are added,
+    /// and that the conversation changed then.
+    ///
+    /// # Arguments
+    /// * `from` - Position of the first message added
+    /// * `now` - Time they were added, in milliseconds since the Unix epoch
+    pub fn stamp_messages(&mut self, from: usize, now: u64) {
+        self.assign_message_ids();
+        for &id in self.message_ids.iter().skip(from) {
+            self.message_times.entry(id).or_insert(now);
+        }
+        self.metadata.created_at.get_or_insert(now);
+        self.metadata.updated_at = Some(now);
+    }
+
+    /// When the message at a position was added, if that is known.
+    pub fn message_time(&self, index: usize) -> Option<u64> {
+        let id = self.message_ids.get(index)?;
+        self.message_times.get(id).copied()
+    }
+
+    /// Position of the message with a stable ID, if it is in the conversation.
+    pub fn message_index(&self, id: u32) -> Option<usize> {
+        (0..self.messages.len()).find(|&i| self.message_id(i) == id)
+    }
+
+    /// Gives IDs to the messages added since the last call, and drops those
+    /// of messages removed from the end.
+    pub fn assign_message_ids(&mut self) {
+        let len = self.messages.len();
+        self.message_ids.truncate(len);
+        while self.message_ids.len() < len {
+            self.message_ids.push(self.next_message_id);
+            self.next_message_id += 1;
+        }
+    }
+
+    /// Bytes of the images embedded in the chat, including those of turns
+    /// replaced by summaries. Images linked by URL take no space.
+    pub fn image_bytes(&self) -> u64 {
+        self.all_messages()
+            .flat_map(|m| match m {
+                Message::User { content } => content.as_slice(),
+                _ => &[],
+            })
+            .map(|part| match part {
+                ContentPart::ImageUrl { image_url } if image_url.url.starts_with("data:") => {
+                    image_url.url.len() as u64
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Replaces the images embedded in the chat with a note, to save space.
+    ///
+    /// # Returns
+    /// The number of images removed
+    pub fn strip_images(&mut self) -> usize {
+        let mut removed = 0;
+        let archived = self.archive.iter_mut().flat_map(|t| t.messages.iter_mut());
+        for message in self.messages.iter_mut().chain(archived) {
+            let Message::User { content } = message else {
+                continue;
+            };
+            for part in content.iter_mut() {
+                if matches!(part, ContentPart::ImageUrl { image_url } if image_url.url.starts_with("data:"))
+                {
+                    *part = ContentPart::Text {
+                        text: STRIPPED_IMAGE.to_string(),
+                    };
+                    removed += 1;
+                }
+            }
+        }
+        removed
+    }
+
+    /// The messages of the chat and of the turns replaced by summaries.
+    fn all_messages(&self) -> impl Iterator<Item = &Message> {
+        self.messages
+            .iter()
+            .chain(self.archive.iter().flat_map(|t| t.messages.iter()))
+    }
+
+    /// Moves the authors and pins of messages taken out of the conversation
+    /// to what was removed.
+    ///
+    /// # Arguments
+    /// * `ids` - Stable IDs of the messages taken out
+    /// * `removed` - Where their authors and pins go
+    fn take_marks(&mut self, ids: &[u32], removed: &mut RemovedTurn) {
+        for id in ids {
+            if let Some(author) = self.authors.remove(id) {
+                removed.authors.insert(*id, author);
+            }
+            if self.pinned.remove(id) {
+                removed.pinned.insert(*id);
+            }
+        }
+    }
+}
+
+/// The messages at the pinned positions, with the tool results of pinned
+/// assistant messages.
+fn pinned_of(messages: &[Message], pinned: impl Iterator<Item = usize>) -> Vec<Message> {
+    let mut out = vec![];
+    for i in pinned {
+        match turn_range(messages, i) {
+            Some(range) => out.extend_from_slice(&messages[range]),
+            None => out.extend(messages.get(i).cloned()),
+        }
+    }
+    out
 }
 
-#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+/// ID of the toolset a chat runs in. The built-in toolsets have their own
+/// variants; others are registered by ID (see [`crate::toolset::registry`]).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Toolsets {
     Chat,
     Story,
+    Agent,
+    Coding,
+    Roleplay,
+    /// A toolset registered at runtime, stored as its bare ID
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl std::fmt::Display for Toolsets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Toolsets::Chat => write!(f, "Chat"),
+            Toolsets::Story => write!(f, "Story"),
+            Toolsets::Agent => write!(f, "Agent"),
+            Toolsets::Coding => write!(f, "Coding"),
+            Toolsets::Roleplay => write!(f, "Roleplay"),
+            Toolsets::Custom(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Toolsets {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Chat" => Toolsets::Chat,
+            "Story" => Toolsets::Story,
+            "Agent" => Toolsets::Agent,
+            "Coding" => Toolsets::Coding,
+            "Roleplay" => Toolsets::Roleplay,
+            id => Toolsets::Custom(id.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(text: &str) -> Message {
+        Message::User {
+            content: vec![ContentPart::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn assistant(text: &str) -> Message {
+        Message::Assistant {
+            content: Some(text.to_string()),
+            tool_calls: None,
+        }
+    }
+
+    fn chat(messages: Vec<Message>) -> Chat {
+        Chat {
+            id: None,
+            chat_type: Toolsets::Chat,
+            messages,
+            value: Value::Null,
+            archive: vec![],
+            disabled_servers: Default::default(),
+            authors: Default::default(),
+            pinned: Default::default(),
+            documents: vec![],
+            message_ids: vec![],
+            next_message_id: 0,
+            message_times: Default::default(),
+            tags: Default::default(),
+            archived: false,
+            metadata: Default::default(),
+            variables: Default::default(),
+            model: None,
+        }
+    }
+
+    #[test]
+    fn pins_and_authors_follow_messages_when_a_turn_is_removed() {
+        let mut chat = chat(vec![user("a"), assistant("b"), user("c"), assistant("d")]);
+        chat.set_author(0, &MessageAuthor::model("m"));
+        chat.set_pinned(3, true);
+
+        let removed = chat.remove_turn(1).unwrap();
+        assert!(chat.is_pinned(2));
+        assert_eq!(chat.author(2).unwrap().name, "m");
+        assert!(!chat.is_pinned(1));
+
+        chat.restore_turn(removed);
+        assert!(chat.is_pinned(3));
+        assert!(!chat.is_pinned(2));
+        assert_eq!(chat.author(1).unwrap().name, "m");
+        assert_eq!(chat.author(3).unwrap().name, "m");
+    }
+
+    #[test]
+    fn pins_of_a_summarized_turn_stay_in_the_archive() {
+        let mut chat = chat(vec![user("a"), assistant("b"), user("c"), assistant("d")]);
+        chat.set_pinned(1, true);
+        chat.set_pinned(3, true);
+
+        let removed = chat.replace_turn(1, "summary".into()).unwrap();
+        chat.archive.push(removed);
+        assert!(!chat.is_pinned(1));
+        assert!(chat.is_pinned(3));
+        assert!(chat.archive[0].is_pinned(0));
+        assert_eq!(chat.pinned_messages(), [assistant("b"), assistant("d")]);
+    }
 }
@@ -0,0 +1,253 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Headless command-line interface for MCMCPCP.
+//!
+//! Reads a prompt (or a JSON array of messages) from the command line or stdin,
+//! runs the LLM and MCP tool loop using the provider and servers configured in the
+//! app, and prints the final answer or the full transcript as JSON.
+
+#[cfg(not(target_arch = "wasm32"))]
+const USAGE: &str = "Usage: cli [OPTIONS] [PROMPT...]
+
+Runs a conversation using the provider and MCP servers configured in the app.
+If PROMPT is omitted it is read from stdin. Input that parses as a JSON array
+of messages is continued as an existing conversation.
+
+Options:
+    --model <MODEL>   Use this model instead of the configured one
+    --profile <NAME>  Use this profile instead of the one active in the app
+    --no-servers      Do not start the configured MCP servers
+    --agent           Plan the task and run longer without pausing (agent mode)
+    --stop <TEXT>     Stop generating at TEXT; may be given several times
+    --prefill <TEXT>  Start the assistant's reply with TEXT
+    --grammar <FILE>  Constrain the output to the GBNF grammar in FILE (llama.cpp)
+    --schema <FILE>   Constrain the output to the JSON schema in FILE (local backends)
+    --transcript      Print the full transcript as JSON instead of the answer
+    --capture <DIR>   Save each raw response stream to a file in DIR
+    --replay <PATH>   Answer from captured streams in PATH (a file or a directory)
+                      instead of the provider, one per request
+    -h, --help        Print this help
+
+Environment:
+    MCMCPCP_PASSPHRASE  Passphrase of the app's storage, if it is encrypted";
+
+/// Command-line options for the headless runner.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Args {
+    /// Model overriding the one in settings
+    model: Option<String>,
+    /// Profile overriding the active one
+    profile: Option<String>,
+    /// Skip starting configured MCP servers
+    no_servers: bool,
+    /// Run in agent mode
+    agent: bool,
+    /// Stop sequences sent with each request
+    stop: Vec<String>,
+    /// Start of the assistant's reply
+    prefill: Option<String>,
+    /// File holding a GBNF grammar for the output
+    grammar: Option<String>,
+    /// File holding a JSON schema for the output
+    schema: Option<String>,
+    /// Print the whole transcript instead of the final answer
+    transcript: bool,
+    /// Directory raw response streams are captured to
+    capture: Option<String>,
+    /// Captured streams answered from instead of the provider
+    replay: Option<String>,
+    /// Prompt given on the command line
+    prompt: Vec<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Args {
+    /// Parses options from the process arguments.
+    ///
+    /// # Returns
+    /// The parsed options, or `None` if help was requested
+    fn parse() -> anyhow::Result<Option<Self>> {
+        let mut args = Self::default();
+        let mut it = std::env::args().skip(1);
+        while let Some(a) = it.next() {
+            match a.as_str() {
+                "--model" => {
+                    let m = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--model needs a value"))?;
+                    args.model = Some(m);
+                }
+                "--profile" => {
+                    let p = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--profile needs a value"))?;
+                    args.profile = Some(p);
+                }
+                "--stop" => {
+                    let s = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--stop needs a value"))?;
+                    args.stop.push(s);
+                }
+                "--prefill" => {
+                    let p = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--prefill needs a value"))?;
+                    args.prefill = Some(p);
+                }
+                "--grammar" => {
+                    let g = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--grammar needs a value"))?;
+                    args.grammar = Some(g);
+                }
+                "--schema" => {
+                    let s = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--schema needs a value"))?;
+                    args.schema = Some(s);
+                }
+                "--capture" => {
+                    let c = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--capture needs a value"))?;
+                    args.capture = Some(c);
+                }
+                "--replay" => {
+                    let r = it
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("--replay needs a value"))?;
+                    args.replay = Some(r);
+                }
+                "--no-servers" => args.no_servers = true,
+                "--agent" => args.agent = true,
+                "--transcript" => args.transcript = true,
+                "-h" | "--help" => return Ok(None),
+                s if s.starts_with("--") => anyhow::bail!("Unknown option {s}\n\n{USAGE}"),
+                _ => args.prompt.push(a),
+            }
+        }
+        Ok(Some(args))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use std::{io::Read, sync::Arc};
+
+    use anyhow::Context;
+    use mcmcpcp::{
+        app_settings::Toolsets,
+        core::{
+            AgentTools, ChatSession, ChatTools, Constraint, MCPHost, Message, Plan, Toolset,
+            final_answer, load_replay, load_settings, new_conversation, select_profile,
+            set_capture_dir, set_request_logging, unlock_storage,
+        },
+    };
+
+    let Some(args) = Args::parse()? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let passphrase = std::env::var("MCMCPCP_PASSPHRASE").ok();
+    unlock_storage(passphrase.as_deref())
+        .await
+        .context("Could not unlock storage; set MCMCPCP_PASSPHRASE to its passphrase")?;
+    select_profile(args.profile.as_deref()).await?;
+    let settings = load_settings()
+        .await?
+        .context("No settings found; configure a provider in the app first")?;
+    set_request_logging(settings.debug_logging);
+    if let Some(dir) = &args.capture {
+        set_capture_dir(Some(dir.into()));
+    }
+    let provider = &settings.provider;
+    let model = args
+        .model
+        .or_else(|| provider.get_model())
+        .context("No model configured; pass --model")?;
+
+    let host = Arc::new(MCPHost::new());
+    // Permissions denied in the app stay denied; there is no prompt here
+    host.set_permissions(settings.permissions.clone()).await;
+    if !args.no_servers {
+        host.sync_servers(settings.mcp_servers.clone().unwrap_or_default())
+            .await?;
+        host.sync_plugins(&settings.plugins).await?;
+        host.sync_scripted_tools(&settings.scripted_tools).await;
+        // Without a prompt, shell commands and API calls other than reads are
+        // refused; see check_permissions
+        host.sync_shell(&settings.shell).await;
+        host.sync_openapi(&settings.openapi).await;
+    }
+    let (toolset, chat_type): (Box<dyn Toolset>, _) = if args.agent {
+        (
+            Box::new(AgentTools::new(host, Plan::default())),
+            Toolsets::Agent,
+        )
+    } else {
+        (Box::new(ChatTools::new(host)), Toolsets::Chat)
+    };
+
+    let input = if args.prompt.is_empty() {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s)?;
+        s
+    } else {
+        args.prompt.join(" ")
+    };
+    let messages = match serde_json::from_str::<Vec<Message>>(&input) {
+        Ok(m) => m,
+        Err(_) => {
+            let prompt = input.trim();
+            if prompt.is_empty() {
+                anyhow::bail!("No prompt given\n\n{USAGE}");
+            }
+            new_conversation(toolset.as_ref(), prompt.to_string())
+        }
+    };
+
+    let constraint = match (&args.grammar, &args.schema) {
+        (Some(_), Some(_)) => anyhow::bail!("Pass either --grammar or --schema, not both"),
+        (Some(path), None) => Some(Constraint::Grammar(
+            std::fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?,
+        )),
+        (None, Some(path)) => {
+            let schema =
+                std::fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+            Some(Constraint::JsonSchema(
+                serde_json::from_str(&schema)
+                    .with_context(|| format!("{path} is not valid JSON"))?,
+            ))
+        }
+        (None, None) => toolset.output_constraint().filter(|_| provider.is_local()),
+    };
+    let mut client = settings
+        .client()
+        .with_stop(args.stop)
+        .with_prefill(args.prefill.unwrap_or_default())
+        .with_constraint(constraint);
+    if let Some(path) = &args.replay {
+        client = client.with_replay(load_replay(path.as_ref())?);
+    }
+    let mut session = ChatSession::from_messages(chat_type, messages, toolset.as_ref())
+        .with_max_failures(settings.max_tool_failures);
+    let result = session.run(&client, &model).await;
+    let messages = session.chat.messages;
+    // Let servers finish what they are doing instead of dying with the process
+    toolset.get_mcp_host().shutdown().await;
+    result?;
+
+    if args.transcript {
+        println!("{}", serde_json::to_string_pretty(&messages)?);
+    } else {
+        println!("{}", final_answer(&messages));
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
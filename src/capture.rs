@@ -0,0 +1,236 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Capture of raw provider streams, and their replay, for reproducing streaming
+//! bugs.
+//!
+//! When a capture directory is set (with [`set_capture_dir`] or the
+//! `MCMCPCP_CAPTURE_DIR` environment variable), the body of every streaming
+//! response is written to its own file in that directory, chunk by chunk as it
+//! arrived, so frames split across chunks are kept as they were. Files are JSON
+//! lines: a header with the model and URL, then one line per chunk holding its
+//! bytes as `text`, or as `base64` if they are not valid UTF-8 on their own.
+//! Each line is sealed when stored data is encrypted.
+//!
+//! A client made with [`crate::llm::LlmClient::with_replay`] answers requests
+//! from such files instead of the network, one file per request. Plain `.sse`
+//! files holding a response body are accepted too, as a single chunk.
+//! Capturing and loading are native only.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Context;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::encryption::{reseal_lines, seal, unseal};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+
+/// Environment variable naming the capture directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CAPTURE_DIR_VAR: &str = "MCMCPCP_CAPTURE_DIR";
+
+/// Directory set with [`set_capture_dir`], taking precedence over the environment
+#[cfg(not(target_arch = "wasm32"))]
+static CAPTURE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+/// Counter keeping file names of captures in the same millisecond apart
+#[cfg(not(target_arch = "wasm32"))]
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The chunks of a captured response body, in the order they arrived.
+pub type CapturedResponse = Vec<Vec<u8>>;
+
+/// A line of a capture file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CaptureLine {
+    /// The first line, describing the request
+    Header { model: String, url: String },
+    /// A chunk that is valid UTF-8
+    Text { text: String },
+    /// A chunk that is not valid UTF-8 by itself
+    Binary { base64: String },
+}
+
+/// Sets the directory streams are captured to, or `None` to fall back to
+/// `MCMCPCP_CAPTURE_DIR`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_capture_dir(dir: Option<PathBuf>) {
+    if let Ok(mut d) = CAPTURE_DIR.lock() {
+        *d = dir;
+    }
+}
+
+/// The directory streams are captured to, if capturing is on.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn capture_dir() -> Option<PathBuf> {
+    let set = CAPTURE_DIR.lock().ok().and_then(|d| d.clone());
+    set.or_else(|| {
+        std::env::var_os(CAPTURE_DIR_VAR)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// A streaming response being written to a capture file.
+pub struct Capture {
+    #[cfg(not(target_arch = "wasm32"))]
+    file: File,
+}
+
+impl Capture {
+    /// Starts capturing a response, if capturing is on.
+    ///
+    /// # Arguments
+    /// * `model` - Model the request was for
+    /// * `url` - URL the request was sent to
+    ///
+    /// # Returns
+    /// The capture, or `None` if capturing is off or the file could not be created
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start(model: &str, url: &str) -> Option<Self> {
+        let dir = capture_dir()?;
+        let res = Self::create(&dir, model, url);
+        res.map_err(|e| dioxus::logger::tracing::warn!("Could not start capture: {e:?}"))
+            .ok()
+    }
+
+    /// Starts capturing a response, if capturing is on. Never on wasm.
+    #[cfg(target_arch = "wasm32")]
+    pub fn start(_model: &str, _url: &str) -> Option<Self> {
+        None
+    }
+
+    /// Creates the capture file and writes its header.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn create(dir: &Path, model: &str, url: &str) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        let safe_model: String = model
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let name = format!(
+            "{}-{}-{safe_model}.jsonl",
+            crate::core::now_millis(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut capture = Self {
+            file: File::create(dir.join(name))?,
+        };
+        capture.write(&CaptureLine::Header {
+            model: model.to_string(),
+            url: url.to_string(),
+        });
+        Ok(capture)
+    }
+
+    /// Appends a chunk of the response body.
+    pub fn chunk(&mut self, bytes: &[u8]) {
+        let line = match std::str::from_utf8(bytes) {
+            Ok(text) => CaptureLine::Text {
+                text: text.to_string(),
+            },
+            Err(_) => CaptureLine::Binary {
+                base64: STANDARD.encode(bytes),
+            },
+        };
+        self.write(&line);
+    }
+
+    /// Writes a line, logging failures instead of disturbing the response.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&mut self, line: &CaptureLine) {
+        use std::io::Write as _;
+
+        let res = serde_json::to_string(line)
+            .map_err(anyhow::Error::from)
+            .and_then(|l| seal(&l))
+            .and_then(|l| Ok(writeln!(self.file, "{l}")?));
+        if let Err(e) = res {
+            dioxus::logger::tracing::warn!("Could not write capture: {e:?}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&mut self, _line: &CaptureLine) {}
+}
+
+/// Seals the lines of the captures in the capture directory again after
+/// encryption was turned on or off.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reseal_captures() -> anyhow::Result<()> {
+    let Some(dir) = capture_dir().filter(|d| d.is_dir()) else {
+        return Ok(());
+    };
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "jsonl") {
+            reseal_lines(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads captured responses for replay.
+///
+/// # Arguments
+/// * `path` - A capture or `.sse` file, or a directory of them, replayed in
+///   file name order
+///
+/// # Returns
+/// The chunks of each response, in order
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_replay(path: &Path) -> anyhow::Result<Vec<CapturedResponse>> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .with_context(|| format!("reading {}", path.display()))?
+            .filter_map(|e| Some(e.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "jsonl" || e == "sse"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    if files.is_empty() {
+        anyhow::bail!("No .jsonl or .sse captures in {}", path.display());
+    }
+    files.iter().map(|f| load_file(f)).collect()
+}
+
+/// Loads the chunks of one captured response.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_file(path: &Path) -> anyhow::Result<CapturedResponse> {
+    let content = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    if path.extension().is_some_and(|e| e == "sse") {
+        return Ok(vec![content]);
+    }
+    let text = String::from_utf8(content).with_context(|| format!("reading {}", path.display()))?;
+    let mut chunks = vec![];
+    for (i, line) in text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+    {
+        let line: CaptureLine = serde_json::from_str(&unseal(line)?)
+            .with_context(|| format!("{}:{}: not a capture line", path.display(), i + 1))?;
+        match line {
+            CaptureLine::Header { .. } => {}
+            CaptureLine::Text { text } => chunks.push(text.into_bytes()),
+            CaptureLine::Binary { base64 } => chunks.push(
+                STANDARD
+                    .decode(base64)
+                    .with_context(|| format!("{}:{}: invalid base64", path.display(), i + 1))?,
+            ),
+        }
+    }
+    Ok(chunks)
+}
@@ -0,0 +1,514 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Editing of conversations that keeps them valid for the API.
+//!
+//! An assistant message that calls tools must be followed by one tool message
+//! per call, so removing it alone, or removing one of its results, leaves a
+//! conversation providers reject. These helpers remove a whole turn, and keep
+//! what they removed so it can be put back. A turn can also be replaced by a
+//! summary of its tool results, to keep long conversations short, and pinned
+//! messages are kept in the requests that shortening would leave them out of.
+//! Documents attached to a chat are listed in its system prompt the same way,
+//! and the chat's variables are filled into the messages sent.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_settings::{ChatDocument, MessageAuthor};
+use crate::core::{TOOL_IMAGES_INTRO, stream_response};
+use crate::llm::{ContentPart, LlmClient, Message};
+
+/// Instructions for summarizing the tool calls of a turn.
+const SUMMARY_PROMPT: &str = "You condense tool output in a conversation. Summarize the tool \
+calls below and their results in a few sentences or bullet points, keeping every fact, figure, \
+name, path and URL the rest of the conversation may rely on. Reply with the summary only.";
+
+/// Introduces pinned messages missing from a request.
+const PINNED_INTRO: &str = "The user pinned these messages from earlier in the conversation. \
+Keep them in mind even though they are no longer shown in full:";
+
+/// Instructions for summarizing a document attached to a chat.
+const DOCUMENT_SUMMARY_PROMPT: &str = "You describe documents for a reader who has not seen \
+them. In two or three sentences, say what the document below is and what it covers, naming \
+its main topics. Reply with the description only.";
+
+/// Introduces the documents attached to a chat.
+const DOCUMENTS_INTRO: &str = "The user attached these documents to the conversation. Use the \
+search_documents and read_document tools to look up what they say instead of guessing:";
+
+/// Characters of a document sent to be summarized; longer ones are cut.
+const DOCUMENT_SUMMARY_CHARS: usize = 24_000;
+
+/// Messages taken out of a conversation, kept so the removal can be undone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemovedTurn {
+    /// Position of the first removed message
+    pub index: usize,
+    /// The removed messages, in order
+    pub messages: Vec<Message>,
+    /// Authors of the removed messages, by stable message ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub authors: BTreeMap<u32, MessageAuthor>,
+    /// Stable IDs of the pinned messages among the removed ones
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub pinned: BTreeSet<u32>,
+    /// Stable IDs of the removed messages, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<u32>,
+}
+
+impl RemovedTurn {
+    /// Stable ID of a removed message. Turns removed before messages had
+    /// IDs number them by position in the turn.
+    ///
+    /// # Arguments
+    /// * `offset` - Position of the message in the turn
+    pub fn message_id(&self, offset: usize) -> u32 {
+        self.ids.get(offset).copied().unwrap_or(offset as u32)
+    }
+
+    /// Whether a removed message is pinned.
+    ///
+    /// # Arguments
+    /// * `offset` - Position of the message in the turn
+    pub fn is_pinned(&self, offset: usize) -> bool {
+        self.pinned.contains(&self.message_id(offset))
+    }
+}
+
+/// Returns the messages forming the turn that starts with an assistant message.
+///
+/// The turn is the assistant message, the tool results following it, and the
+/// message carrying images returned by those tools, if any.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `index` - Position of the assistant message
+///
+/// # Returns
+/// The range of the turn, or `None` if `index` is not an assistant message
+pub fn turn_range(messages: &[Message], index: usize) -> Option<Range<usize>> {
+    if !matches!(messages.get(index), Some(Message::Assistant { .. })) {
+        return None;
+    }
+    let mut end = index + 1;
+    while matches!(messages.get(end), Some(Message::Tool { .. })) {
+        end += 1;
+    }
+    if end > index + 1 && messages.get(end).is_some_and(is_tool_images) {
+        end += 1;
+    }
+    Some(index..end)
+}
+
+/// Sets the system prompt of a conversation.
+///
+/// Replaces the leading system message, or inserts one if there is none.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `prompt` - The new system prompt
+pub fn set_system_prompt(messages: &mut Vec<Message>, prompt: String) {
+    match messages.first_mut() {
+        Some(Message::System { content }) => *content = prompt,
+        _ => messages.insert(0, Message::System { content: prompt }),
+    }
+}
+
+/// Drops tool calls and results from turns before the current one.
+///
+/// What remains of earlier turns is the dialogue: system, user and assistant
+/// text. Assistant messages that only called tools are dropped, as are images
+/// returned by tools. The current turn, which may be waiting on tool results,
+/// is kept whole.
+///
+/// # Arguments
+/// * `messages` - The conversation
+///
+/// # Returns
+/// The shortened conversation
+pub fn drop_old_tool_results(messages: &[Message]) -> Vec<Message> {
+    let current = turn_start(messages, messages.len());
+    let mut kept = vec![];
+    for message in &messages[..current] {
+        match message {
+            Message::Tool { .. } => {}
+            m if is_tool_images(m) => {}
+            Message::Assistant { content, .. } => {
+                if let Some(content) = content.as_deref().filter(|c| !c.trim().is_empty()) {
+                    kept.push(Message::Assistant {
+                        content: Some(content.to_string()),
+                        tool_calls: None,
+                    });
+                }
+            }
+            _ => kept.push(message.clone()),
+        }
+    }
+    kept.extend_from_slice(&messages[current..]);
+    kept
+}
+
+/// Finds where the turn containing a message starts.
+///
+/// A turn starts with a message from the user; messages carrying images
+/// returned by tools do not count.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `index` - Position of a message in the turn
+///
+/// # Returns
+/// Position of the user message starting the turn, or 0 if there is none
+pub fn turn_start(messages: &[Message], index: usize) -> usize {
+    messages[..index.min(messages.len())]
+        .iter()
+        .rposition(|m| matches!(m, Message::User { .. }) && !is_tool_images(m))
+        .unwrap_or(0)
+}
+
+/// Whether a message carries the images returned by tool calls.
+fn is_tool_images(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::User { content }
+            if matches!(content.first(), Some(ContentPart::Text { text }) if text == TOOL_IMAGES_INTRO)
+    )
+}
+
+/// Removes an assistant message together with its tool results.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `index` - Position of the assistant message
+///
+/// # Returns
+/// What was removed, to pass to [`restore_turn`], or `None` if `index` is not
+/// an assistant message
+pub fn remove_turn(messages: &mut Vec<Message>, index: usize) -> Option<RemovedTurn> {
+    let range = turn_range(messages, index)?;
+    Some(RemovedTurn {
+        index,
+        messages: messages.drain(range).collect(),
+        authors: BTreeMap::new(),
+        pinned: BTreeSet::new(),
+        ids: vec![],
+    })
+}
+
+/// Puts back a turn removed with [`remove_turn`].
+///
+/// The turn is reinserted where it was, or at the end if the conversation has
+/// since become shorter.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `removed` - The removed turn
+pub fn restore_turn(messages: &mut Vec<Message>, removed: RemovedTurn) {
+    let index = removed.index.min(messages.len());
+    messages.splice(index..index, removed.messages);
+}
+
+/// Asks the model to summarize the tool calls and results of a turn.
+///
+/// Stop sequences, prefill and output constraints set on the client are not
+/// applied to this request.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to write the summary
+/// * `messages` - The conversation
+/// * `index` - Position of the assistant message starting the turn
+///
+/// # Returns
+/// The summary, or an error if `index` is not an assistant message or the request fails
+pub async fn summarize_turn(
+    client: &LlmClient,
+    model: &str,
+    messages: &[Message],
+    index: usize,
+) -> anyhow::Result<String> {
+    let range =
+        turn_range(messages, index).ok_or_else(|| anyhow::anyhow!("Not an assistant message"))?;
+    let transcript = transcript(&messages[range]);
+    let client = client
+        .clone()
+        .with_stop(vec![])
+        .with_prefill(String::new())
+        .with_constraint(None);
+    let prompt = [
+        Message::System {
+            content: SUMMARY_PROMPT.to_string(),
+        },
+        Message::User {
+            content: vec![ContentPart::Text { text: transcript }],
+        },
+    ];
+    let (summary, _) = stream_response(&client, model, &prompt, &[], &mut ()).await?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        anyhow::bail!("The model returned an empty summary");
+    }
+    Ok(summary.to_string())
+}
+
+/// Asks the model to describe a document attached to a chat, from its start.
+///
+/// Stop sequences, prefill and output constraints set on the client are not
+/// applied to this request.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to write the summary
+/// * `document` - The document
+///
+/// # Returns
+/// The summary, or an error if the request fails
+pub async fn summarize_document(
+    client: &LlmClient,
+    model: &str,
+    document: &ChatDocument,
+) -> anyhow::Result<String> {
+    let text: String = document.text.chars().take(DOCUMENT_SUMMARY_CHARS).collect();
+    let client = client
+        .clone()
+        .with_stop(vec![])
+        .with_prefill(String::new())
+        .with_constraint(None);
+    let prompt = [
+        Message::System {
+            content: DOCUMENT_SUMMARY_PROMPT.to_string(),
+        },
+        Message::User {
+            content: vec![ContentPart::Text {
+                text: format!("# {}\n\n{text}", document.name),
+            }],
+        },
+    ];
+    let (summary, _) = stream_response(&client, model, &prompt, &[], &mut ()).await?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        anyhow::bail!("The model returned an empty summary");
+    }
+    Ok(summary.to_string())
+}
+
+/// Writes messages out as plain text, one paragraph per message, with tool
+/// calls and their arguments. Images and audio are left out.
+///
+/// # Arguments
+/// * `messages` - The messages
+pub fn transcript(messages: &[Message]) -> String {
+    let mut transcript = String::new();
+    for message in messages {
+        match message {
+            Message::System { content } => {
+                transcript.push_str(&format!("System: {content}\n\n"));
+            }
+            Message::User { .. } if is_tool_images(message) => {}
+            Message::User { content } => {
+                let text = content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentPart::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                transcript.push_str(&format!("User: {text}\n\n"));
+            }
+            Message::Assistant {
+                content,
+                tool_calls,
+            } => {
+                if let Some(content) = content.as_deref().filter(|c| !c.is_empty()) {
+                    transcript.push_str(&format!("Assistant: {content}\n\n"));
+                }
+                for f in tool_calls
+                    .iter()
+                    .flatten()
+                    .filter_map(|c| c.function.as_ref())
+                {
+                    transcript.push_str(&format!(
+                        "Called `{}` with {}\n\n",
+                        f.name.as_deref().unwrap_or_default(),
+                        f.arguments.as_deref().unwrap_or("{}"),
+                    ));
+                }
+            }
+            Message::Tool { content, .. } => {
+                transcript.push_str(&format!("Result:\n{content}\n\n"));
+            }
+        }
+    }
+    transcript
+}
+
+/// Adds pinned messages that a shortened request leaves out to its system
+/// prompt, so the model sees them however the conversation was shortened.
+///
+/// # Arguments
+/// * `messages` - The messages to send
+/// * `pinned` - The pinned messages, in order
+///
+/// # Returns
+/// The messages to send, with the missing pinned messages in the system prompt
+pub fn with_pinned(mut messages: Vec<Message>, pinned: &[Message]) -> Vec<Message> {
+    let missing: Vec<Message> = pinned
+        .iter()
+        .filter(|p| !messages.contains(p))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        return messages;
+    }
+    let section = format!("{PINNED_INTRO}\n\n{}", transcript(&missing).trim_end());
+    match messages.first_mut() {
+        Some(Message::System { content }) => content.push_str(&format!("\n\n{section}")),
+        _ => messages.insert(0, Message::System { content: section }),
+    }
+    messages
+}
+
+/// Lists the documents attached to a chat in the system prompt of a request,
+/// with their summaries, so the model knows to search them.
+///
+/// # Arguments
+/// * `messages` - The messages to send
+/// * `documents` - The chat's documents
+///
+/// # Returns
+/// The messages to send, with the documents in the system prompt
+pub fn with_documents(mut messages: Vec<Message>, documents: &[ChatDocument]) -> Vec<Message> {
+    if documents.is_empty() {
+        return messages;
+    }
+    let list = documents
+        .iter()
+        .map(|d| match &d.summary {
+            Some(summary) => format!("- {}: {summary}", d.name),
+            None => format!("- {}", d.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let section = format!("{DOCUMENTS_INTRO}\n\n{list}");
+    match messages.first_mut() {
+        Some(Message::System { content }) => content.push_str(&format!("\n\n{section}")),
+        _ => messages.insert(0, Message::System { content: section }),
+    }
+    messages
+}
+
+/// Fills a chat's variables into the system prompt and the user's messages
+/// of a request, so prompts written with `{{name}}` placeholders can be
+/// reused with other values. The stored conversation keeps the placeholders.
+///
+/// # Arguments
+/// * `messages` - The messages to send
+/// * `variables` - Values of the chat's variables, by name
+///
+/// # Returns
+/// The messages to send, with the placeholders of defined variables replaced
+pub fn with_variables(
+    mut messages: Vec<Message>,
+    variables: &BTreeMap<String, String>,
+) -> Vec<Message> {
+    if variables.is_empty() {
+        return messages;
+    }
+    for message in &mut messages {
+        match message {
+            Message::System { content } => *content = substitute_variables(content, variables),
+            Message::User { content } => {
+                for part in content {
+                    if let ContentPart::Text { text } = part {
+                        *text = substitute_variables(text, variables);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    messages
+}
+
+/// Replaces the `{{name}}` placeholders of defined variables in a text.
+/// Placeholders of other names are left as they are.
+///
+/// # Arguments
+/// * `text` - Text with placeholders
+/// * `variables` - Values of the variables, by name
+pub fn substitute_variables(text: &str, variables: &BTreeMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        result.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..start + len + 4]),
+        }
+        rest = &rest[start + len + 4..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Names of the `{{name}}` placeholders in a text, in order of appearance,
+/// each once.
+///
+/// # Arguments
+/// * `text` - Text with placeholders
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        if is_variable_name(name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + len + 4..];
+    }
+    names
+}
+
+/// Whether a name can be used for a variable: letters, digits and `_`.
+pub fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Replaces a turn with a single assistant message holding a summary of it.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `index` - Position of the assistant message starting the turn
+/// * `summary` - Text of the replacement message
+///
+/// # Returns
+/// The replaced messages, for archiving, or `None` if `index` is not an
+/// assistant message
+pub fn replace_turn(
+    messages: &mut Vec<Message>,
+    index: usize,
+    summary: String,
+) -> Option<RemovedTurn> {
+    let removed = remove_turn(messages, index)?;
+    messages.insert(
+        index,
+        Message::Assistant {
+            content: Some(summary),
+            tool_calls: None,
+        },
+    );
+    Some(removed)
+}
@@ -0,0 +1,204 @@
+// Copyright © 2025 Nipun Kumar
+
+//! UI-free core of MCMCPCP, for embedding the MCP-augmented chat loop.
+//!
+//! This module gathers everything needed to run a conversation outside the Dioxus
+//! app: the LLM client, the MCP host, toolsets, and the tool loop itself. None of
+//! it requires a Dioxus runtime or signals.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use mcmcpcp::core::{
+//!     ChatTools, LlmClient, MAX_TOOL_FAILURES, MAX_TOOL_ROUNDS, MCPHost, final_answer,
+//!     new_conversation, run_tools_loop,
+//! };
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let host = Arc::new(MCPHost::new());
+//! let client = LlmClient::new("http://localhost:11434/v1".into(), String::new());
+//! let mut messages = new_conversation(&ChatTools::new(host.clone()), "Hello!".into());
+//! let (rounds, failures) = (MAX_TOOL_ROUNDS, MAX_TOOL_FAILURES);
+//! run_tools_loop(&client, "llama3.2", host, &mut messages, rounds, failures, &mut ()).await?;
+//! println!("{}", final_answer(&messages));
+//! # Ok(())
+//! # }
+//! ```
+
+mod edit;
+mod session;
+mod tokens;
+mod tool_loop;
+
+pub use edit::{
+    RemovedTurn, drop_old_tool_results, is_variable_name, placeholders, remove_turn, replace_turn,
+    restore_turn, set_system_prompt, substitute_variables, summarize_document, summarize_turn,
+    transcript, turn_range, turn_start, with_documents, with_pinned, with_variables,
+};
+pub use session::{CANCELLED_TOOL_CALL, CancelHandle, ChatSession, SessionEvent, TurnTiming};
+pub use tokens::{
+    MEDIA_TOKENS, estimate_message_tokens, estimate_part_tokens, estimate_request_tokens,
+    estimate_tokens,
+};
+pub use tool_loop::{
+    CHECKPOINT_INTERVAL_MILLIS, Candidate, DECLINED_TOOL_CALL, MAX_TOOL_FAILURES, MAX_TOOL_ROUNDS,
+    TOOL_IMAGES_INTRO, ToolResults, ToolsetHooks, TurnHooks, accept_candidate, call_tools,
+    compare_responses, extract_wierd_tool_calls, propose_tool_calls, run_selected_tools,
+    run_tools_loop, stream_response, tools_to_message_objects,
+};
+
+pub use crate::app_settings::{AppSettings, Chat, ProviderSettings};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::capture::{CAPTURE_DIR_VAR, load_replay, set_capture_dir};
+pub use crate::llm::{Constraint, ContentPart, LlmClient, Message, Tool, ToolCallDelta};
+pub use crate::mcp::host::{MCPHost, MCPServer};
+pub use crate::mcp::{McpTool, ServerSpec, ToolDescriptor, ToolResult, ToolResultContent};
+pub use crate::toolset::{
+    Toolset,
+    agent::{AGENT_MAX_TOOL_ROUNDS, AgentTools, Plan, PlanStep},
+    chat::ChatTools,
+};
+
+use crate::storage::{Storage, get_storage};
+
+/// Returns the current wall-clock time in milliseconds since the Unix epoch.
+///
+/// `std::time::Instant` is unavailable on wasm, so this uses `Date.now()` there.
+#[cfg(target_arch = "wasm32")]
+pub fn now_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Describes how long ago a time was, such as "5m ago" or "2h ago".
+///
+/// # Arguments
+/// * `then` - The time, in milliseconds since the Unix epoch
+/// * `now` - The current time, as from [`now_millis`]
+pub fn time_ago(then: u64, now: u64) -> String {
+    let minutes = now.saturating_sub(then) / 60_000;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if minutes < 1 {
+        "just now".to_string()
+    } else if hours < 1 {
+        format!("{minutes}m ago")
+    } else if days < 1 {
+        format!("{hours}h ago")
+    } else if days < 7 {
+        format!("{days}d ago")
+    } else if days < 30 {
+        format!("{}w ago", days / 7)
+    } else if days < 365 {
+        format!("{}mo ago", days / 30)
+    } else {
+        format!("{}y ago", days / 365)
+    }
+}
+
+/// Waits for a duration without blocking the async runtime.
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: std::time::Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Waits for a duration without blocking the async runtime.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: std::time::Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Selects the profile whose settings and chats later calls use.
+///
+/// # Arguments
+/// * `profile` - Profile name, or `None` for the profile last active in the app
+pub async fn select_profile(profile: Option<&str>) -> anyhow::Result<()> {
+    let profiles = crate::storage::load_profiles().await?;
+    let name = match profile {
+        Some(p) if profiles.names.iter().any(|n| n == p) => p,
+        Some(p) => anyhow::bail!("No profile named {p}"),
+        None => &profiles.active,
+    };
+    crate::storage::set_active_profile(name);
+    Ok(())
+}
+
+/// Unlocks storage encrypted in the app's settings, so later calls can read
+/// and write it. Does nothing if storage is not encrypted.
+///
+/// # Arguments
+/// * `passphrase` - The storage passphrase, if one was given
+pub async fn unlock_storage(passphrase: Option<&str>) -> anyhow::Result<()> {
+    match (crate::storage::load_encryption().await?, passphrase) {
+        (None, _) => Ok(()),
+        (Some(info), Some(p)) => crate::storage::encryption::unlock(&info, p),
+        (Some(_), None) => anyhow::bail!("Storage is encrypted and no passphrase was given"),
+    }
+}
+
+/// Loads the settings saved by the app, with config file and environment
+/// overrides applied.
+///
+/// # Returns
+/// The effective settings, or `None` if the app has never been configured
+pub async fn load_settings() -> anyhow::Result<Option<AppSettings>> {
+    let stored = get_storage().await?.load_settings().await?;
+    Ok(crate::app_settings::with_overrides(stored))
+}
+
+/// Turns recording of raw LLM requests and responses on or off.
+///
+/// On native platforms recorded exchanges are appended to `requests.log` in the
+/// config directory, with API keys redacted.
+///
+/// # Arguments
+/// * `enabled` - Whether to record
+pub fn set_request_logging(enabled: bool) {
+    crate::request_log::set_enabled(enabled);
+}
+
+/// Starts a new conversation with a toolset's system prompt.
+///
+/// # Arguments
+/// * `toolset` - Toolset providing the system prompt
+/// * `prompt` - The first user message
+///
+/// # Returns
+/// The system and user messages to send to the model
+pub fn new_conversation(toolset: &dyn Toolset, prompt: String) -> Vec<Message> {
+    vec![
+        Message::System {
+            content: toolset.get_system_prompt(),
+        },
+        Message::User {
+            content: vec![ContentPart::Text { text: prompt }],
+        },
+    ]
+}
+
+/// Returns the text of the last assistant message in a conversation.
+///
+/// # Arguments
+/// * `messages` - The conversation
+///
+/// # Returns
+/// The final answer, or an empty string if the assistant has not replied
+pub fn final_answer(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .rev()
+        .find_map(|m| match m {
+            Message::Assistant {
+                content: Some(c), ..
+            } if !c.is_empty() => Some(c.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
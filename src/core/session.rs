@@ -0,0 +1,749 @@
+// Copyright © 2025 Nipun Kumar
+
+//! A conversation and the turns run on it, independent of any UI.
+//!
+//! [`ChatSession`] owns a [`Chat`] and runs turns on it with the tool loop,
+//! keeping the state a UI shows: whether a turn is running, the text streaming
+//! in, and the last error. Changes are announced as [`SessionEvent`]s to a
+//! listener, which the chat UI uses to update its signals; the `cli` binary
+//! and headless tests read the session's state directly instead.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use dioxus::logger::tracing::warn;
+use tokio::sync::watch;
+
+use crate::{
+    app_settings::{Chat, ChatDocument, Checkpoint, MessageAuthor, Toolsets},
+    core::{
+        MAX_TOOL_FAILURES, TurnHooks, estimate_tokens, now_millis, propose_tool_calls,
+        run_tools_loop, with_documents, with_pinned, with_variables,
+    },
+    llm::{ContentPart, LlmClient, Message, ToolCallDelta},
+    mcp::{
+        knowledge::serve_documents,
+        scratchpad::{notes, serve_scratchpad, with_notes},
+        tool_ref::ToolRef,
+    },
+    storage::{Storage, get_storage},
+    toolset::Toolset,
+};
+
+/// Result recorded for a tool call that was cancelled before it ran.
+pub const CANCELLED_TOOL_CALL: &str = "The user stopped the turn before this tool call ran.";
+
+/// A change in a [`ChatSession`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// A turn started or finished
+    Busy(bool),
+    /// The text of the response streaming in so far, or `None` once it is complete
+    Streaming(Option<String>),
+    /// Messages were appended to the chat
+    Appended {
+        messages: Vec<Message>,
+        /// Who wrote the assistant messages among them, if the model did
+        author: Option<MessageAuthor>,
+        /// When they were added, in milliseconds since the Unix epoch
+        at: u64,
+    },
+    /// The toolset's Markdown view changed while tools ran
+    Display(Option<String>),
+    /// The turn failed, or the error was cleared
+    Error(Option<String>),
+    /// The turn stopped after the toolset's maximum number of tool rounds
+    RoundLimit,
+    /// The turn was stopped with [`CancelHandle::cancel`]
+    Cancelled,
+    /// Estimated tokens per second of the response streaming in, or `None`
+    /// once it is complete
+    Throughput(Option<f64>),
+    /// How long the parts of the turn took, once it is over
+    Timing(TurnTiming),
+}
+
+/// How long the parts of a turn took, in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TurnTiming {
+    /// From the start of the turn to its end
+    pub total: u64,
+    /// From the start of the turn to the first text streamed in
+    pub first_token: Option<u64>,
+    /// Spent receiving text, from the first text of each response to its end
+    pub streaming: u64,
+    /// Estimated tokens of text received
+    pub tokens: usize,
+    /// Each round of tool calls: the tools called and how long they ran
+    pub tool_rounds: Vec<(Vec<String>, u64)>,
+}
+
+impl TurnTiming {
+    /// Time spent running tools.
+    pub fn tools(&self) -> u64 {
+        self.tool_rounds.iter().map(|(_, millis)| millis).sum()
+    }
+
+    /// Estimated tokens per second while text streamed in, if any did.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        (self.streaming > 0 && self.tokens > 0)
+            .then(|| self.tokens as f64 * 1000.0 / self.streaming as f64)
+    }
+}
+
+/// Stops the turn running in a session, from outside it.
+#[derive(Debug, Clone)]
+pub struct CancelHandle(Arc<watch::Sender<bool>>);
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+}
+
+impl PartialEq for CancelHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl CancelHandle {
+    /// Stops the running turn. Messages already received are kept, and tool
+    /// calls that did not run are recorded as cancelled.
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Resolves once the turn is cancelled.
+    async fn cancelled(&self) {
+        let mut rx = self.0.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+
+    fn reset(&self) {
+        self.0.send_replace(false);
+    }
+}
+
+/// A conversation with a toolset, and the state of the turn running on it.
+pub struct ChatSession<'a> {
+    /// The conversation
+    pub chat: Chat,
+    toolset: &'a dyn Toolset,
+    busy: bool,
+    streaming: Option<String>,
+    error: Option<String>,
+    max_failures: u8,
+    checkpoints: bool,
+    cancel: CancelHandle,
+    listener: Box<dyn FnMut(SessionEvent) + 'a>,
+    /// Timing of the turn running or last run
+    timing: TurnTiming,
+    /// When the turn started, in milliseconds since the Unix epoch
+    started: u64,
+}
+
+impl<'a> ChatSession<'a> {
+    /// Creates a session on a chat.
+    ///
+    /// # Arguments
+    /// * `chat` - The conversation
+    /// * `toolset` - Toolset whose tools the model can call
+    pub fn new(chat: Chat, toolset: &'a dyn Toolset) -> Self {
+        Self {
+            chat,
+            toolset,
+            busy: false,
+            streaming: None,
+            error: None,
+            max_failures: MAX_TOOL_FAILURES,
+            checkpoints: false,
+            cancel: CancelHandle::default(),
+            listener: Box::new(|_| {}),
+            timing: TurnTiming::default(),
+            started: 0,
+        }
+    }
+
+    /// Creates a session on a new chat holding the given messages.
+    ///
+    /// # Arguments
+    /// * `chat_type` - Kind of chat
+    /// * `messages` - The conversation so far
+    /// * `toolset` - Toolset whose tools the model can call
+    pub fn from_messages(
+        chat_type: Toolsets,
+        messages: Vec<Message>,
+        toolset: &'a dyn Toolset,
+    ) -> Self {
+        let chat = Chat {
+            id: None,
+            chat_type,
+            messages,
+            value: serde_json::Value::Null,
+            archive: vec![],
+            disabled_servers: Default::default(),
+            authors: Default::default(),
+            pinned: Default::default(),
+            documents: vec![],
+            message_ids: vec![],
+            next_message_id: 0,
+            message_times: Default::default(),
+            tags: Default::default(),
+            archived: false,
+            metadata: Default::default(),
+            variables: Default::default(),
+            model: None,
+        };
+        Self::new(chat, toolset)
+    }
+
+    /// Sets the number of consecutive failed tool calls at which a turn stops.
+    pub fn with_max_failures(mut self, max_failures: u8) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Saves a checkpoint of the chat during turns, for crash recovery.
+    pub fn with_checkpoints(mut self, checkpoints: bool) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    /// Uses a cancel handle shared with the caller, to stop turns from outside.
+    pub fn with_cancel(mut self, cancel: CancelHandle) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sets the function told of every change in the session.
+    pub fn on_event(mut self, listener: impl FnMut(SessionEvent) + 'a) -> Self {
+        self.listener = Box::new(listener);
+        self
+    }
+
+    /// Whether a turn is running.
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// The text of the response streaming in, if any.
+    pub fn streaming(&self) -> Option<&str> {
+        self.streaming.as_deref()
+    }
+
+    /// Why the last turn failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns a handle that stops the running turn.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Adds a user message and runs the turn answering it.
+    ///
+    /// # Arguments
+    /// * `client` - LLM client for making API calls
+    /// * `model` - Model to answer
+    /// * `content` - The user's message
+    ///
+    /// # Returns
+    /// The number of tool rounds made
+    pub async fn send_message(
+        &mut self,
+        client: &LlmClient,
+        model: &str,
+        content: Vec<ContentPart>,
+    ) -> anyhow::Result<u8> {
+        if self.busy {
+            anyhow::bail!("A turn is already running");
+        }
+        let message = Message::User { content };
+        let at = now_millis();
+        self.chat.messages.push(message.clone());
+        self.chat.stamp_messages(self.chat.messages.len() - 1, at);
+        self.emit(SessionEvent::Appended {
+            messages: vec![message],
+            author: None,
+            at,
+        });
+        self.run(client, model).await
+    }
+
+    /// Clears the error of a failed turn and continues the conversation.
+    ///
+    /// # Arguments
+    /// * `client` - LLM client for making API calls
+    /// * `model` - Model to answer
+    ///
+    /// # Returns
+    /// The number of tool rounds made
+    pub async fn retry(&mut self, client: &LlmClient, model: &str) -> anyhow::Result<u8> {
+        self.set_error(None);
+        self.run(client, model).await
+    }
+
+    /// Continues the conversation as it is, running the tools the model calls.
+    ///
+    /// # Arguments
+    /// * `client` - LLM client for making API calls
+    /// * `model` - Model to answer
+    ///
+    /// # Returns
+    /// The number of tool rounds made, or 0 if the turn was cancelled
+    pub async fn run(&mut self, client: &LlmClient, model: &str) -> anyhow::Result<u8> {
+        self.start()?;
+        let host = self.toolset.get_mcp_host();
+        serve_documents(&host, &self.chat.documents).await;
+        serve_scratchpad(&host, &self.chat.messages).await;
+        let max_rounds = self.toolset.max_tool_rounds();
+        let max_failures = self.max_failures;
+        let cancel = self.cancel.clone();
+        let mut messages = self.chat.messages.clone();
+        let outcome = {
+            let mut hooks = SessionHooks::new(self, model);
+            let turn = run_tools_loop(
+                client,
+                model,
+                host,
+                &mut messages,
+                max_rounds,
+                max_failures,
+                &mut hooks,
+            );
+            tokio::select! {
+                result = turn => Some(result),
+                _ = cancel.cancelled() => None,
+            }
+        };
+        let result = self.finish(outcome, 0);
+        if result.as_ref().is_ok_and(|count| *count >= max_rounds) {
+            self.emit(SessionEvent::RoundLimit);
+        }
+        result
+    }
+
+    /// Streams one response without running the tools it calls, for the
+    /// user to review.
+    ///
+    /// # Arguments
+    /// * `client` - LLM client for making API calls
+    /// * `model` - Model to answer
+    ///
+    /// # Returns
+    /// The tool calls the model wants to make, empty if the turn was cancelled
+    pub async fn propose(
+        &mut self,
+        client: &LlmClient,
+        model: &str,
+    ) -> anyhow::Result<Vec<ToolCallDelta>> {
+        self.start()?;
+        let host = self.toolset.get_mcp_host();
+        serve_documents(&host, &self.chat.documents).await;
+        serve_scratchpad(&host, &self.chat.messages).await;
+        let cancel = self.cancel.clone();
+        let mut messages = self.chat.messages.clone();
+        let outcome = {
+            let mut hooks = SessionHooks::new(self, model);
+            let turn = propose_tool_calls(client, model, host, &mut messages, &mut hooks);
+            tokio::select! {
+                result = turn => Some(result),
+                _ = cancel.cancelled() => None,
+            }
+        };
+        self.finish(outcome, vec![])
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        if self.busy {
+            anyhow::bail!("A turn is already running");
+        }
+        self.cancel.reset();
+        self.set_error(None);
+        self.timing = TurnTiming::default();
+        self.started = now_millis();
+        self.busy = true;
+        self.emit(SessionEvent::Busy(true));
+        Ok(())
+    }
+
+    /// Ends a turn, recording its error or closing the tool calls a
+    /// cancelled turn left without results.
+    fn finish<T>(&mut self, outcome: Option<anyhow::Result<T>>, cancelled: T) -> anyhow::Result<T> {
+        if self.streaming.take().is_some() {
+            self.emit(SessionEvent::Streaming(None));
+        }
+        let result = match outcome {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(e)) => {
+                self.set_error(Some(format!("{e:#}")));
+                Err(e)
+            }
+            None => {
+                let results = unanswered_calls(&self.chat.messages)
+                    .into_iter()
+                    .map(|id| Message::Tool {
+                        tool_call_id: id,
+                        content: CANCELLED_TOOL_CALL.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                if !results.is_empty() {
+                    let at = now_millis();
+                    let from = self.chat.messages.len();
+                    self.chat.messages.extend(results.iter().cloned());
+                    self.chat.stamp_messages(from, at);
+                    self.emit(SessionEvent::Appended {
+                        messages: results,
+                        author: None,
+                        at,
+                    });
+                }
+                self.emit(SessionEvent::Cancelled);
+                Ok(cancelled)
+            }
+        };
+        self.timing.total = now_millis().saturating_sub(self.started);
+        self.emit(SessionEvent::Timing(self.timing.clone()));
+        self.busy = false;
+        self.emit(SessionEvent::Busy(false));
+        result
+    }
+
+    fn set_error(&mut self, error: Option<String>) {
+        if self.error != error {
+            self.error = error.clone();
+            self.emit(SessionEvent::Error(error));
+        }
+    }
+
+    fn emit(&mut self, event: SessionEvent) {
+        (self.listener)(event);
+    }
+}
+
+/// Connects the tool loop to a session's chat and listener.
+struct SessionHooks<'s, 'a> {
+    session: &'s mut ChatSession<'a>,
+    author: MessageAuthor,
+    /// Pinned messages, sent with every request
+    pinned: Vec<Message>,
+    /// Documents attached to the chat, listed in every request
+    documents: Vec<ChatDocument>,
+    /// Variables filled into every request
+    variables: BTreeMap<String, String>,
+    /// The toolset's Markdown view as last announced
+    display: Option<Option<String>>,
+    /// When the response streaming in started, and its estimated tokens
+    response: Option<(u64, usize)>,
+    /// Tools of the round running, and when it started
+    tools: Option<(Vec<String>, u64)>,
+    /// State of the global MCP servers as of the last tool round, for checkpoints
+    server_states: HashMap<String, serde_json::Value>,
+}
+
+impl<'s, 'a> SessionHooks<'s, 'a> {
+    fn new(session: &'s mut ChatSession<'a>, model: &str) -> Self {
+        let pinned = session.chat.pinned_messages();
+        let documents = session.chat.documents.clone();
+        let variables = session.chat.variables.clone();
+        let author = match session.toolset.speaker() {
+            Some(speaker) => MessageAuthor {
+                model: Some(model.to_string()),
+                ..speaker
+            },
+            None => MessageAuthor::model(model),
+        };
+        Self {
+            session,
+            author,
+            pinned,
+            documents,
+            variables,
+            display: None,
+            response: None,
+            tools: None,
+            server_states: HashMap::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl TurnHooks for SessionHooks<'_, '_> {
+    fn on_stream(&mut self, text: &str) {
+        self.session.streaming = Some(text.to_string());
+        self.session
+            .emit(SessionEvent::Streaming(Some(text.to_string())));
+
+        let now = now_millis();
+        let timing = &mut self.session.timing;
+        timing
+            .first_token
+            .get_or_insert(now.saturating_sub(self.session.started));
+        let (start, _) = *self.response.get_or_insert((now, 0));
+        let tokens = estimate_tokens(text);
+        self.response = Some((start, tokens));
+        if now > start {
+            let rate = tokens as f64 * 1000.0 / (now - start) as f64;
+            self.session.emit(SessionEvent::Throughput(Some(rate)));
+        }
+    }
+
+    fn on_stream_end(&mut self) {
+        if self.session.streaming.take().is_some() {
+            self.session.emit(SessionEvent::Streaming(None));
+        }
+        if let Some((start, tokens)) = self.response.take() {
+            let timing = &mut self.session.timing;
+            timing.streaming += now_millis().saturating_sub(start);
+            timing.tokens += tokens;
+            self.session.emit(SessionEvent::Throughput(None));
+        }
+    }
+
+    fn on_messages(&mut self, messages: &[Message]) {
+        // A response calling tools starts a round; their results end it
+        let now = now_millis();
+        for message in messages {
+            match message {
+                Message::Assistant {
+                    tool_calls: Some(calls),
+                    ..
+                } if !calls.is_empty() => {
+                    let names = calls
+                        .iter()
+                        .filter_map(|c| c.function.as_ref()?.name.as_deref())
+                        .map(|name| ToolRef::decode(name).map_or(name.to_string(), |t| t.tool))
+                        .collect();
+                    self.tools = Some((names, now));
+                }
+                Message::Tool { .. } => {
+                    if let Some((names, start)) = self.tools.take() {
+                        let millis = now.saturating_sub(start);
+                        self.session.timing.tool_rounds.push((names, millis));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let chat = &mut self.session.chat;
+        let at = now_millis();
+        let from = chat.messages.len();
+        chat.messages.extend_from_slice(messages);
+        chat.set_author(from, &self.author);
+        chat.stamp_messages(from, at);
+        self.session.emit(SessionEvent::Appended {
+            messages: messages.to_vec(),
+            author: Some(self.author.clone()),
+            at,
+        });
+    }
+
+    async fn checkpoint(&mut self, messages: &[Message], partial: Option<&str>) {
+        if self.session.checkpoints {
+            let toolset = self.session.toolset;
+            let mut chat = self.session.chat.clone();
+            chat.messages = messages.to_vec();
+            chat.value = toolset.get_state().await;
+            if let Some(text) = partial {
+                chat.messages.push(Message::Assistant {
+                    content: Some(text.to_string()),
+                    tool_calls: None,
+                });
+            } else {
+                // Servers only change state when tools are called, so this is
+                // not repeated while a response streams in
+                self.server_states = toolset.get_mcp_host().root().export_states().await;
+            }
+            let checkpoint = Checkpoint {
+                chat,
+                server_states: self.server_states.clone(),
+            };
+            let res = match get_storage().await {
+                Ok(stg) => stg.save_checkpoint(&checkpoint).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                warn!("Could not save checkpoint: {e:?}");
+            }
+        }
+        // Keep views of the toolset's state live while tools change it
+        let md = self.session.toolset.get_markdown_repr().await;
+        if self.display.as_ref() != Some(&md) {
+            self.display = Some(md.clone());
+            self.session.emit(SessionEvent::Display(md));
+        }
+    }
+
+    async fn request_messages(&mut self, messages: &[Message]) -> Option<Vec<Message>> {
+        let pruned = self.session.toolset.prune_messages(messages).await;
+        // Notes are taken from the whole conversation, as pruning may drop them
+        let notes = notes(messages);
+        if self.pinned.is_empty()
+            && self.documents.is_empty()
+            && notes.is_empty()
+            && self.variables.is_empty()
+        {
+            return pruned;
+        }
+        let pruned = pruned.unwrap_or_else(|| messages.to_vec());
+        let pruned = with_pinned(pruned, &self.pinned);
+        let pruned = with_documents(pruned, &self.documents);
+        let pruned = with_notes(pruned, &notes);
+        Some(with_variables(pruned, &self.variables))
+    }
+}
+
+/// IDs of the tool calls of the last assistant message that have no result.
+fn unanswered_calls(messages: &[Message]) -> Vec<String> {
+    let Some(index) = messages
+        .iter()
+        .rposition(|m| matches!(m, Message::Assistant { .. }))
+    else {
+        return vec![];
+    };
+    let Message::Assistant {
+        tool_calls: Some(calls),
+        ..
+    } = &messages[index]
+    else {
+        return vec![];
+    };
+    calls
+        .iter()
+        .filter_map(|c| c.id.clone())
+        .filter(|id| {
+            !messages[index + 1..]
+                .iter()
+                .any(|m| matches!(m, Message::Tool { tool_call_id, .. } if tool_call_id == id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{mcp::host::MCPHost, toolset::chat::ChatTools};
+
+    /// A captured response body streaming the given deltas, one event each.
+    fn response(deltas: &[serde_json::Value], finish_reason: &str) -> Vec<Vec<u8>> {
+        let mut chunks: Vec<Vec<u8>> = deltas
+            .iter()
+            .map(|delta| {
+                let event = json!({
+                    "id": "r",
+                    "object": "chat.completion.chunk",
+                    "choices": [{"index": 0, "delta": delta, "finish_reason": null}],
+                });
+                format!("data: {event}\n\n").into_bytes()
+            })
+            .collect();
+        let last = json!({
+            "id": "r",
+            "object": "chat.completion.chunk",
+            "choices": [{"index": 0, "delta": {}, "finish_reason": finish_reason}],
+        });
+        chunks.push(format!("data: {last}\n\ndata: [DONE]\n\n").into_bytes());
+        chunks
+    }
+
+    fn user(text: &str) -> Vec<ContentPart> {
+        vec![ContentPart::Text {
+            text: text.to_string(),
+        }]
+    }
+
+    #[tokio::test]
+    async fn runs_a_turn_with_a_tool_call() {
+        let toolset = ChatTools::new(Arc::new(MCPHost::new()));
+        let write_note = ToolRef::new("scratchpad", "write_note").encode();
+        let client = LlmClient::new("http://localhost".into(), String::new()).with_replay(vec![
+            response(
+                &[json!({"tool_calls": [{
+                    "id": "call-1",
+                    "type": "function",
+                    "function": {"name": write_note, "arguments": "{\"note\": \"remember\"}"},
+                }]})],
+                "tool_calls",
+            ),
+            response(
+                &[json!({"content": "Do"}), json!({"content": "ne"})],
+                "stop",
+            ),
+        ]);
+        let mut events = vec![];
+        let mut session = ChatSession::from_messages(Toolsets::Chat, vec![], &toolset)
+            .on_event(|e| events.push(e));
+
+        let rounds = session
+            .send_message(&client, "model", user("Take a note"))
+            .await
+            .unwrap();
+
+        assert_eq!(rounds, 1);
+        assert!(!session.is_busy());
+        assert_eq!(session.error(), None);
+        assert_eq!(session.streaming(), None);
+        let messages = &session.chat.messages;
+        assert_eq!(messages.len(), 4);
+        assert!(
+            matches!(&messages[1], Message::Assistant { tool_calls: Some(calls), .. }
+            if calls[0].id.as_deref() == Some("call-1"))
+        );
+        assert!(
+            matches!(&messages[2], Message::Tool { tool_call_id, content }
+            if tool_call_id == "call-1" && content.contains("Noted"))
+        );
+        assert!(matches!(
+            &messages[3],
+            Message::Assistant { content: Some(text), tool_calls }
+            if text == "Done" && tool_calls.as_ref().is_none_or(|c| c.is_empty())
+        ));
+        assert_eq!(notes(messages), ["remember"]);
+        assert_eq!(session.chat.message_ids.len(), 4);
+        assert_eq!(
+            session.chat.author(3).map(|a| a.name.as_str()),
+            Some("model")
+        );
+        drop(session);
+
+        assert!(matches!(
+            events.first(),
+            Some(SessionEvent::Appended { author: None, .. })
+        ));
+        assert_eq!(events.get(1), Some(&SessionEvent::Busy(true)));
+        assert_eq!(events.last(), Some(&SessionEvent::Busy(false)));
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, SessionEvent::Streaming(Some(_))))
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_error_of_a_failed_turn() {
+        let toolset = ChatTools::new(Arc::new(MCPHost::new()));
+        let client = LlmClient::new("http://localhost".into(), String::new()).with_replay(vec![]);
+        let mut session = ChatSession::from_messages(Toolsets::Chat, vec![], &toolset);
+
+        assert!(
+            session
+                .send_message(&client, "model", user("Hi"))
+                .await
+                .is_err()
+        );
+        assert!(!session.is_busy());
+        assert!(
+            session
+                .error()
+                .is_some_and(|e| e.contains("No captured responses"))
+        );
+        assert_eq!(session.chat.messages.len(), 1);
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Rough token counts, for warning about requests that will not fit a model's
+//! context window.
+//!
+//! Tokenizers differ between models, so this approximates a typical BPE
+//! tokenizer instead of running one: a token per five letters or digits of a
+//! word, one per punctuation mark, and one per character of scripts written
+//! without spaces. That is rough, and errs on the high side for English prose,
+//! but close enough to tell when a request will not fit.
+
+use crate::llm::{ContentPart, Message, Tool};
+
+/// Tokens counted for each image or audio clip, whose real cost depends on
+/// its size and the model.
+pub const MEDIA_TOKENS: usize = 1000;
+
+/// Letters or digits of a word counted as one token.
+const LETTERS_PER_TOKEN: usize = 5;
+
+/// Tokens of formatting around each message.
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// Estimates the number of tokens in a text.
+///
+/// # Arguments
+/// * `text` - The text
+///
+/// # Returns
+/// The approximate token count
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    // Letters and digits in the current word
+    let mut word: usize = 0;
+    for c in text.chars() {
+        if c.is_alphanumeric() && c.len_utf8() < 3 {
+            word += 1;
+            continue;
+        }
+        tokens += word.div_ceil(LETTERS_PER_TOKEN);
+        word = 0;
+        if !c.is_whitespace() {
+            // Punctuation, symbols, and CJK and similar characters
+            tokens += 1;
+        }
+    }
+    tokens + word.div_ceil(LETTERS_PER_TOKEN)
+}
+
+/// Estimates the number of tokens in a chat completion request.
+///
+/// # Arguments
+/// * `messages` - The messages sent
+/// * `tools` - The tools offered
+///
+/// # Returns
+/// The approximate prompt token count
+pub fn estimate_request_tokens(messages: &[Message], tools: &[Tool]) -> usize {
+    let messages: usize = messages.iter().map(estimate_message_tokens).sum();
+    let tools = if tools.is_empty() {
+        0
+    } else {
+        estimate_tokens(&serde_json::to_string(tools).unwrap_or_default())
+    };
+    messages + tools
+}
+
+/// Estimates the number of tokens in a message.
+pub fn estimate_message_tokens(message: &Message) -> usize {
+    let content = match message {
+        Message::System { content } | Message::Tool { content, .. } => estimate_tokens(content),
+        Message::User { content } => content.iter().map(estimate_part_tokens).sum(),
+        Message::Assistant {
+            content,
+            tool_calls,
+        } => {
+            let calls = tool_calls
+                .as_ref()
+                .map(|c| estimate_tokens(&serde_json::to_string(c).unwrap_or_default()))
+                .unwrap_or_default();
+            estimate_tokens(content.as_deref().unwrap_or_default()) + calls
+        }
+    };
+    content + MESSAGE_OVERHEAD
+}
+
+/// Estimates the number of tokens in part of a user message.
+pub fn estimate_part_tokens(part: &ContentPart) -> usize {
+    match part {
+        ContentPart::Text { text } => estimate_tokens(text),
+        ContentPart::ImageUrl { .. } | ContentPart::InputAudio { .. } => MEDIA_TOKENS,
+    }
+}
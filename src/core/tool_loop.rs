@@ -0,0 +1,715 @@
+// Copyright © 2025 Nipun Kumar
+
+//! The LLM and MCP tool loop, operating on plain message lists.
+//!
+//! Nothing here depends on Dioxus signals: progress is reported through
+//! [`TurnHooks`], so the loop can drive the chat UI, the `cli` binary, or any
+//! other program embedding MCMCPCP.
+
+use std::{collections::HashSet, sync::Arc};
+
+use dioxus::logger::tracing::{info, warn};
+use serde_json::Value;
+
+use crate::core::now_millis;
+use crate::llm::{
+    ContentPart, Function, FunctionDelta, ImageUrl, LlmClient, Message, Tool, ToolCallDelta,
+};
+use crate::mcp::content::describe_content;
+use crate::mcp::host::MCPHost;
+use crate::mcp::results::{MAX_RESULT_CHARS, limit_result};
+use crate::mcp::tool_ref::ToolRef;
+use crate::mcp::{ToolDescriptor, ToolResult};
+use crate::toolset::Toolset;
+
+/// Maximum number of tool rounds in a single turn, to prevent runaway tool execution.
+pub const MAX_TOOL_ROUNDS: u8 = 10;
+
+/// Consecutive failed tool calls at which a turn stops with the error, by default.
+pub const MAX_TOOL_FAILURES: u8 = 3;
+
+/// Result recorded for a proposed tool call the user chose not to run.
+pub const DECLINED_TOOL_CALL: &str = "The user chose not to run this tool call.";
+
+/// Text introducing the images returned by tool calls, sent to the model with them.
+pub const TOOL_IMAGES_INTRO: &str = "Images returned by the tool calls above:";
+
+/// How often a checkpoint is taken while a response is streaming.
+pub const CHECKPOINT_INTERVAL_MILLIS: u64 = 5_000;
+
+/// Callbacks invoked by [`run_tools_loop`] as a turn progresses.
+///
+/// All methods have empty default implementations, and `()` implements this
+/// trait for callers that only need the final messages.
+#[async_trait::async_trait(?Send)]
+pub trait TurnHooks {
+    /// Called with the accumulated text each time more of a response streams in.
+    fn on_stream(&mut self, _text: &str) {}
+
+    /// Called when a streamed response is complete.
+    fn on_stream_end(&mut self) {}
+
+    /// Called with messages just appended to the conversation.
+    fn on_messages(&mut self, _messages: &[Message]) {}
+
+    /// Called when the turn reaches a point worth saving for crash recovery:
+    /// at the start of the turn, after each tool batch, and periodically while
+    /// a response is streaming.
+    ///
+    /// # Arguments
+    /// * `messages` - The conversation so far
+    /// * `partial` - Text of the response currently streaming, if any
+    async fn checkpoint(&mut self, _messages: &[Message], _partial: Option<&str>) {}
+
+    /// Called before each request to shorten the conversation sent to the model,
+    /// e.g. with [`Toolset::prune_messages`]. The conversation itself is kept whole.
+    ///
+    /// # Returns
+    /// The messages to send instead, or `None` to send the conversation as is
+    async fn request_messages(&mut self, _messages: &[Message]) -> Option<Vec<Message>> {
+        None
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl TurnHooks for () {}
+
+/// Hooks for callers without a UI that only apply a toolset's pruning policy.
+pub struct ToolsetHooks<'a>(pub &'a dyn Toolset);
+
+#[async_trait::async_trait(?Send)]
+impl TurnHooks for ToolsetHooks<'_> {
+    async fn request_messages(&mut self, messages: &[Message]) -> Option<Vec<Message>> {
+        self.0.prune_messages(messages).await
+    }
+}
+
+/// Converts MCP tool descriptors to LLM tool objects.
+///
+/// This function transforms tool descriptors from MCP servers into the format
+/// expected by LLM APIs. Each tool is prefixed with its server ID to ensure
+/// unique naming and proper routing when the tool is called.
+///
+/// # Arguments
+/// * `tools` - Vector of tool descriptors from MCP servers
+///
+/// # Returns
+/// Vector of `Tool` objects formatted for LLM API requests
+pub fn tools_to_message_objects(tools: Vec<ToolDescriptor>) -> Vec<Tool> {
+    let mut seen = HashSet::new();
+    tools
+        .iter()
+        .filter_map(move |t| {
+            let t = t.clone();
+            // Prefix tool name with server ID for unique identification
+            let name = ToolRef::new(&t.server_id, &t.tool.name).encode();
+            if !seen.insert(name.clone()) {
+                warn!("Skipping duplicate tool {name}");
+                return None;
+            }
+            Some(Tool {
+                r#type: "function".into(),
+                function: Function {
+                    name,
+                    description: t.tool.description,
+                    parameters: Some(t.tool.input_schema),
+                    strict: Some(true), // Enable strict parameter validation
+                },
+            })
+        })
+        .collect()
+}
+
+/// Messages produced by [`call_tools`], and how each call went.
+pub struct ToolResults {
+    /// Tool messages, in call order, followed by a user message with any images
+    pub messages: Vec<Message>,
+    /// Outcome of each call, in call order, with the error of failed calls
+    pub outcomes: Vec<Result<(), String>>,
+}
+
+/// Executes tool calls and converts results to chat messages.
+///
+/// This function processes tool call deltas from the LLM, extracts the server ID
+/// and tool name, executes the tools on the appropriate MCP servers, and formats
+/// the results as tool messages that can be added to the chat conversation.
+///
+/// # Arguments
+/// * `tool_calls` - Vector of tool call deltas from the LLM response
+/// * `host` - MCP host for executing tool calls
+/// * `images` - Whether the model accepts images
+///
+/// # Returns
+/// Tool result messages to add to the conversation, and the outcome of each call
+///
+/// A call that fails, for instance because its arguments are not valid JSON,
+/// the server timed out or the server is gone, gets a tool message describing
+/// the error, so the model can retry or work around it.
+///
+/// Results longer than [`MAX_RESULT_CHARS`] are truncated, and the model is
+/// told how to page through the rest with the `read_more` tool if the host
+/// provides it.
+///
+/// Tool messages can only hold text, so images in the results, such as browser
+/// screenshots, follow the tool messages in a user message when `images` is
+/// set. Otherwise the tool message notes that they were left out.
+pub async fn call_tools(
+    tool_calls: Vec<ToolCallDelta>,
+    host: Arc<MCPHost>,
+    images: bool,
+) -> ToolResults {
+    let mut new_chat: Vec<Message> = vec![];
+    let mut outcomes = vec![];
+    let mut image_parts: Vec<ContentPart> = vec![];
+    // Looked up the first time a result needs truncating
+    let mut read_more_tool: Option<Option<String>> = None;
+
+    // Process each tool call from the LLM
+    for tc in tool_calls.into_iter() {
+        let Some(f) = tc.function.as_ref() else {
+            continue; // Skip tool calls without function information
+        };
+
+        // Parse the tool name to extract server ID and tool name
+        if let Some(tool_ref) = ToolRef::decode(f.name.as_deref().unwrap_or("")) {
+            let server_id = tool_ref.server_id.as_str();
+            let tool_name = tool_ref.tool.as_str();
+            let params_str = f.arguments.as_deref().unwrap_or("{}");
+
+            let text = match call_tool(&host, server_id, tool_name, params_str).await {
+                Ok(result) => {
+                    outcomes.push(Ok(()));
+                    let (mut text, result_images) = result_text(result, server_id);
+                    if text.chars().count() > MAX_RESULT_CHARS {
+                        if read_more_tool.is_none() {
+                            read_more_tool = Some(
+                                host.list_tools()
+                                    .await
+                                    .into_iter()
+                                    .find(|t| t.tool.name == "read_more")
+                                    .map(|t| ToolRef::new(t.server_id, t.tool.name).encode()),
+                            );
+                        }
+                        text = limit_result(text, read_more_tool.clone().flatten().as_deref());
+                    }
+                    if !result_images.is_empty() {
+                        let count = result_images.len();
+                        text.push_str(&if images {
+                            format!("\n[{count} image(s) attached in the next message]")
+                        } else {
+                            format!(
+                                "\n[{count} image(s) left out: the model does not accept images]"
+                            )
+                        });
+                        if images {
+                            image_parts.extend(result_images);
+                        }
+                    }
+                    text
+                }
+                Err(e) => {
+                    warn!("Tool call {server_id}/{tool_name} failed: {e:?}");
+                    outcomes.push(Err(format!("{server_id}/{tool_name}: {e:#}")));
+                    format!("Error: the tool call failed: {e:#}")
+                }
+            };
+
+            // Create a tool message with the result
+            let tcm = Message::Tool {
+                tool_call_id: tc.id.unwrap_or_else(|| "".into()),
+                content: text,
+            };
+            new_chat.push(tcm);
+        }
+    }
+
+    if !image_parts.is_empty() {
+        let mut content = vec![ContentPart::Text {
+            text: TOOL_IMAGES_INTRO.to_string(),
+        }];
+        content.extend(image_parts);
+        new_chat.push(Message::User { content });
+    }
+
+    ToolResults {
+        messages: new_chat,
+        outcomes,
+    }
+}
+
+/// Parses the arguments of a tool call and executes it.
+async fn call_tool(
+    host: &MCPHost,
+    server_id: &str,
+    tool_name: &str,
+    params_str: &str,
+) -> anyhow::Result<ToolResult> {
+    // Parse the function arguments from JSON string
+    let arguments: Value = serde_json::from_str(params_str)
+        .map_err(|e| anyhow::anyhow!("the arguments are not valid JSON ({e})"))?;
+
+    // Log the tool call for debugging
+    info!("Calling {server_id}/{tool_name}({arguments:?})");
+
+    // Execute the tool call on the MCP server
+    host.tool_call(server_id, tool_name, arguments).await
+}
+
+/// Converts a tool result to the text of a tool message.
+///
+/// # Returns
+/// The text, and the images the result contained
+fn result_text(result: ToolResult, server_id: &str) -> (String, Vec<ContentPart>) {
+    // Filter for text content and combine into a single message
+    let (texts, others): (Vec<_>, Vec<_>) =
+        result.content.into_iter().partition(|c| c.r#type == "text");
+    let messages: Vec<String> = texts
+        .into_iter()
+        .map(|c| c.text.unwrap_or_default())
+        .collect();
+    let (images_content, others): (Vec<_>, Vec<_>) =
+        others.into_iter().partition(|c| c.r#type == "image");
+    // Resources and binary data are written into the text as markers
+    let messages: Vec<String> = messages
+        .into_iter()
+        .chain(others.iter().filter_map(|c| describe_content(c, server_id)))
+        .collect();
+    let images = images_content
+        .into_iter()
+        .filter_map(|c| {
+            let data = c.data?;
+            let mime = c.mime_type.unwrap_or_else(|| "image/png".into());
+            Some(ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: format!("data:{mime};base64,{data}"),
+                },
+            })
+        })
+        .collect();
+    (messages.join("\n"), images)
+}
+
+/// Extracts tool calls from text that uses non-standard formats.
+///
+/// Some LLM models may return tool calls in custom formats rather than the
+/// standard streaming format. This function attempts to parse these alternative
+/// formats and convert them to standard ToolCallDelta objects.
+///
+/// # Arguments
+/// * `text` - The text content to parse for tool calls
+///
+/// # Returns
+/// An optional ToolCallDelta if a tool call was successfully extracted
+pub fn extract_wierd_tool_calls(text: &str) -> anyhow::Result<Option<ToolCallDelta>> {
+    if text.starts_with("[TOOL_CALLS]") {
+        let t = text.replace("[TOOL_CALLS]", "");
+        let parts: Vec<String> = t.split("<SPECIAL_32>").map(|s| s.into()).collect();
+        if parts.len() < 2 {
+            return Ok(None);
+        }
+        return Ok(Some(ToolCallDelta {
+            id: Some("...".into()),
+            kind: Some("function".into()),
+            function: Some(FunctionDelta {
+                name: Some(parts[0].to_string()),
+                arguments: Some(parts[1].clone()),
+            }),
+        }));
+    }
+
+    // Models asked to reply with JSON often wrap it in a code block
+    let json = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .unwrap_or(text);
+    if let Ok(Value::Object(m)) = serde_json::from_str(json.trim())
+        && let Some(name) = m.get("name").and_then(|x| x.as_str())
+        && let Some(args) = m.get("arguments")
+    {
+        let arguments = if let Some(s) = args.as_str() {
+            Some(s.to_string())
+        } else if let Some(m) = args.as_object() {
+            let args_str = serde_json::to_string(&Value::Object(m.clone()))?;
+            Some(args_str)
+        } else {
+            None
+        };
+
+        return Ok(Some(ToolCallDelta {
+            id: Some("...".into()),
+            kind: Some("function".into()),
+            function: Some(FunctionDelta {
+                name: Some(name.to_string()),
+                arguments,
+            }),
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Streams a single assistant response and collects its text and tool calls.
+///
+/// Tool call deltas are accumulated into complete calls, and tool calls emitted
+/// as plain text by some models are recognised as well.
+///
+/// # Arguments
+/// * `client` - LLM client for making API calls
+/// * `model` - Model name to use for the response
+/// * `messages` - Conversation so far
+/// * `tools` - Tools the model may call
+/// * `hooks` - Notified as text arrives, and checkpointed periodically
+///
+/// # Returns
+/// The trimmed response text and the tool calls requested by the model
+pub async fn stream_response(
+    client: &LlmClient,
+    model: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    hooks: &mut impl TurnHooks,
+) -> anyhow::Result<(String, Vec<ToolCallDelta>)> {
+    // Start streaming response from LLM
+    let pruned = hooks.request_messages(messages).await;
+    let request = pruned.as_deref().unwrap_or(messages);
+    let mut stream = client.stream(model, request, tools).await?;
+    let mut text = "".to_string();
+    let mut tool_calls = vec![];
+    let mut current_tool_call: Option<ToolCallDelta> = None;
+    let mut last_checkpoint = now_millis();
+
+    // Process streaming response chunks
+    while let Some(e) = stream.recv().await {
+        let e = match e {
+            Ok(e) => e,
+            Err(err) => {
+                // Drop the partial response; the turn can be retried from the last message
+                hooks.on_stream_end();
+                let received = text.chars().count();
+                return Err(if received > 0 {
+                    anyhow::anyhow!("{err} ({received} characters had arrived)")
+                } else {
+                    err
+                });
+            }
+        };
+        let Some(ch) = e.choices.first() else { break };
+
+        // Handle text content (assistant response)
+        if let Some(t) = &ch.delta.content
+            && !t.is_empty()
+        {
+            text = format!("{}{}", &text, t);
+            hooks.on_stream(&text);
+
+            // Periodically save the partial response
+            if now_millis().saturating_sub(last_checkpoint) >= CHECKPOINT_INTERVAL_MILLIS {
+                hooks.checkpoint(messages, Some(&text)).await;
+                last_checkpoint = now_millis();
+            }
+        }
+
+        // Handle tool calls
+        if let Some(tools) = &ch.delta.tool_calls {
+            info!("{:?}", tools);
+            for t in tools {
+                if current_tool_call.is_none() {
+                    current_tool_call = Some(t.clone());
+                } else if let Some(c) = current_tool_call.as_mut() {
+                    let existing = c
+                        .clone()
+                        .function
+                        .map(|fd| fd.arguments.clone().unwrap_or_default())
+                        .unwrap_or_default();
+                    let delta = t
+                        .clone()
+                        .function
+                        .map(|fd| fd.arguments.clone().unwrap_or_default())
+                        .unwrap_or_else(|| "".to_string());
+                    if let Some(fd) = c.function.as_mut() {
+                        fd.arguments = Some(format!("{existing}{delta}"));
+                    }
+                }
+            }
+        }
+
+        if ch.finish_reason.is_some() {
+            // flush tools
+            if let Some(tc) = current_tool_call.take() {
+                tool_calls.push(tc);
+            }
+        }
+    }
+
+    if let Some(tc) = current_tool_call.take() {
+        tool_calls.push(tc);
+    }
+
+    text = text.trim().to_string();
+
+    // Process the final response
+    if !text.is_empty() {
+        // Handle special tool call format (fallback for some models)
+        if let Ok(Some(tcd)) = extract_wierd_tool_calls(&text) {
+            tool_calls.push(tcd);
+            text = "".to_string();
+        }
+    }
+
+    Ok((text, tool_calls))
+}
+
+/// Main loop for handling LLM responses and tool execution.
+///
+/// This function manages the conversation flow:
+/// 1. Sends the current conversation to the LLM
+/// 2. Processes streaming responses (text and tool calls)
+/// 3. Executes any requested tools
+/// 4. Continues the loop until no more tools are called
+/// 5. Stops after `max_rounds` tool rounds to prevent runaway tool execution
+///
+/// Assistant responses and tool results are appended to `messages` as they are
+/// produced, so the conversation is complete up to the point of any error.
+///
+/// # Arguments
+/// * `client` - LLM client for making API calls
+/// * `model` - Model name to use for the conversation
+/// * `host` - MCP host used to list and call tools
+/// * `messages` - Conversation to continue
+/// * `max_rounds` - Tool rounds to run before returning, usually [`MAX_TOOL_ROUNDS`]
+/// * `max_failures` - Consecutive failed tool calls at which the turn stops
+///   with the last error, usually [`MAX_TOOL_FAILURES`]; fewer failures are only
+///   reported to the model, so it can retry. 1 stops at the first failure
+/// * `hooks` - Callbacks notified of streaming text and new messages, and
+///   asked to checkpoint the conversation for crash recovery
+///
+/// # Returns
+/// Result indicating success or failure, and the number of tool rounds run
+pub async fn run_tools_loop(
+    client: &LlmClient,
+    model: &str,
+    host: Arc<MCPHost>,
+    messages: &mut Vec<Message>,
+    max_rounds: u8,
+    max_failures: u8,
+    hooks: &mut impl TurnHooks,
+) -> anyhow::Result<u8> {
+    // Get available tools
+    let tools = tools_to_message_objects(host.list_tools().await);
+    let images = client.supports_images(model).await;
+
+    // Save the new user message before anything can go wrong
+    hooks.checkpoint(messages, None).await;
+
+    let mut count = 0u8; // Safety counter to prevent infinite loops
+    let mut failures = 0u8; // Tool calls failed since the last one that worked
+    loop {
+        let (text, tool_calls) = stream_response(client, model, messages, &tools, hooks).await?;
+        hooks.on_stream_end();
+        record_response(messages, text, &tool_calls, hooks);
+
+        // If no tools were called, we're done
+        if tool_calls.is_empty() {
+            warn!("No tool calls, exit loop");
+            return Ok(count);
+        }
+
+        // Execute the requested tools
+        let results = call_tools(tool_calls, host.clone(), images).await;
+        warn!("Got {} messages after tool call", results.messages.len());
+        hooks.on_messages(&results.messages);
+        messages.extend(results.messages);
+        hooks.checkpoint(messages, None).await;
+
+        for outcome in results.outcomes {
+            match outcome {
+                Ok(()) => failures = 0,
+                Err(e) => {
+                    failures = failures.saturating_add(1);
+                    if failures >= max_failures {
+                        anyhow::bail!("Tool call failed: {e}");
+                    }
+                }
+            }
+        }
+
+        count += 1;
+        if count >= max_rounds {
+            warn!("Count exceeded, exit loop");
+            return Ok(count);
+        }
+    }
+}
+
+/// Appends a streamed response to the conversation, unless it is empty.
+fn record_response(
+    messages: &mut Vec<Message>,
+    text: String,
+    tool_calls: &[ToolCallDelta],
+    hooks: &mut impl TurnHooks,
+) {
+    if !text.is_empty() || !tool_calls.is_empty() {
+        messages.push(Message::Assistant {
+            content: Some(text),
+            tool_calls: Some(tool_calls.to_vec()),
+        });
+        hooks.on_messages(&messages[messages.len() - 1..]);
+    }
+}
+
+/// Streams a single response without executing the tool calls it requests.
+///
+/// This is the "plan only" counterpart of [`run_tools_loop`]: the model sees the
+/// available tools, and its response is appended to `messages`, but the tool
+/// calls are returned for review instead of run. Pass them to
+/// [`run_selected_tools`] to execute some of them and record results for all.
+///
+/// # Arguments
+/// * `client` - LLM client for making API calls
+/// * `model` - Model name to use for the response
+/// * `host` - MCP host used to list tools
+/// * `messages` - Conversation to continue
+/// * `hooks` - Callbacks notified of streaming text and new messages
+///
+/// # Returns
+/// The tool calls the model wants to make, empty if it answered directly
+pub async fn propose_tool_calls(
+    client: &LlmClient,
+    model: &str,
+    host: Arc<MCPHost>,
+    messages: &mut Vec<Message>,
+    hooks: &mut impl TurnHooks,
+) -> anyhow::Result<Vec<ToolCallDelta>> {
+    let tools = tools_to_message_objects(host.list_tools().await);
+    hooks.checkpoint(messages, None).await;
+
+    let (text, tool_calls) = stream_response(client, model, messages, &tools, hooks).await?;
+    hooks.on_stream_end();
+    record_response(messages, text, &tool_calls, hooks);
+    Ok(tool_calls)
+}
+
+/// A response from one of several models asked the same question.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// Model that produced the response
+    pub model: String,
+    /// Response text
+    pub text: String,
+    /// Tool calls the model requested
+    pub tool_calls: Vec<ToolCallDelta>,
+    /// Why the model did not respond, if it failed
+    pub error: Option<String>,
+}
+
+/// Forwards the text of one of several concurrent streams to a shared callback.
+struct CandidateHooks<'a, F: Fn(usize, &str)> {
+    index: usize,
+    on_stream: &'a F,
+}
+
+#[async_trait::async_trait(?Send)]
+impl<F: Fn(usize, &str)> TurnHooks for CandidateHooks<'_, F> {
+    fn on_stream(&mut self, text: &str) {
+        (self.on_stream)(self.index, text);
+    }
+}
+
+/// Streams responses to the same conversation from several models at once.
+///
+/// Nothing is appended to the conversation and no tools are run; pass the
+/// chosen candidate to [`accept_candidate`] to continue with it.
+///
+/// # Arguments
+/// * `client` - LLM client for making API calls
+/// * `models` - Models to ask
+/// * `host` - MCP host used to list tools
+/// * `messages` - Conversation to respond to
+/// * `on_stream` - Called with a model's index and its text so far as each response streams in
+///
+/// # Returns
+/// One candidate per model, in the order of `models`; failed models have an error
+pub async fn compare_responses(
+    client: &LlmClient,
+    models: &[String],
+    host: Arc<MCPHost>,
+    messages: &[Message],
+    on_stream: impl Fn(usize, &str),
+) -> Vec<Candidate> {
+    let tools = tools_to_message_objects(host.list_tools().await);
+    let on_stream = &on_stream;
+    let tools = &tools;
+    futures::future::join_all(models.iter().enumerate().map(|(index, model)| async move {
+        let mut hooks = CandidateHooks { index, on_stream };
+        match stream_response(client, model, messages, tools, &mut hooks).await {
+            Ok((text, tool_calls)) => Candidate {
+                model: model.clone(),
+                text,
+                tool_calls,
+                error: None,
+            },
+            Err(e) => Candidate {
+                model: model.clone(),
+                text: String::new(),
+                tool_calls: vec![],
+                error: Some(e.to_string()),
+            },
+        }
+    }))
+    .await
+}
+
+/// Appends the chosen candidate to the conversation as the assistant's response.
+///
+/// # Arguments
+/// * `messages` - Conversation the candidates responded to
+/// * `candidate` - The response to keep
+/// * `hooks` - Notified of the new message
+///
+/// # Returns
+/// The tool calls the candidate requested, to run with [`run_selected_tools`]
+pub fn accept_candidate(
+    messages: &mut Vec<Message>,
+    candidate: Candidate,
+    hooks: &mut impl TurnHooks,
+) -> Vec<ToolCallDelta> {
+    record_response(messages, candidate.text, &candidate.tool_calls, hooks);
+    candidate.tool_calls
+}
+
+/// Executes the selected calls among those returned by [`propose_tool_calls`].
+///
+/// Every call gets a tool message, so the conversation stays valid: selected
+/// calls get their results and the others get [`DECLINED_TOOL_CALL`].
+///
+/// # Arguments
+/// * `tool_calls` - Proposed tool calls, in the order the model made them
+/// * `selected` - Whether to run each call; missing entries are not run
+/// * `host` - MCP host for executing tool calls
+/// * `images` - Whether the model accepts images returned by the tools
+///
+/// # Returns
+/// Tool messages to append to the conversation, in call order, followed by
+/// any images the tools returned
+pub async fn run_selected_tools(
+    tool_calls: Vec<ToolCallDelta>,
+    selected: &[bool],
+    host: Arc<MCPHost>,
+    images: bool,
+) -> anyhow::Result<Vec<Message>> {
+    let mut messages = vec![];
+    for (i, tc) in tool_calls.into_iter().enumerate() {
+        if selected.get(i).copied().unwrap_or(false) {
+            messages.extend(call_tools(vec![tc], host.clone(), images).await.messages);
+        } else {
+            messages.push(Message::Tool {
+                tool_call_id: tc.id.unwrap_or_default(),
+                content: DECLINED_TOOL_CALL.to_string(),
+            });
+        }
+    }
+    // Tool messages must directly follow the call, so images go last
+    messages.sort_by_key(|m| matches!(m, Message::User { .. }));
+    Ok(messages)
+}
@@ -0,0 +1,405 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Export of conversations as standalone, read-only HTML pages.
+//!
+//! The conversation is rendered with the same Markdown pipeline as the chat view
+//! (see [`crate::md2rsx`]) into a virtual DOM, which is then serialized with
+//! dioxus-ssr. The resulting page has all CSS inlined and loads nothing from the
+//! network, so it can be emailed or published as a single file.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::{
+    app_settings::Chat,
+    llm::{ContentPart, FunctionDelta, Message},
+    mcp::host::MCPHost,
+    md2rsx::{MarkdownOptions, markdown_to_rsx_with},
+    toolset::registry,
+};
+
+/// Stylesheet inlined into exported pages.
+const EXPORT_CSS: &str = r#"
+body {
+    font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+    line-height: 1.5;
+    color: #222;
+    background: #f5f5f5;
+    margin: 0;
+    padding: 2em 1em;
+}
+main { max-width: 48em; margin: 0 auto; }
+h1.title { font-size: 1.4em; margin: 0 0 1.5em 0; color: #444; }
+.message {
+    border-radius: 8px;
+    padding: 0.75em 1em;
+    margin-bottom: 1em;
+    overflow-x: auto;
+}
+.user-message { background: #007bff; color: white; margin-left: 15%; }
+.ai-message { background: white; border: 1px solid #ddd; margin-right: 15%; }
+.role { font-size: 0.75em; font-weight: 600; opacity: 0.7; margin-bottom: 0.25em; }
+details {
+    margin-top: 0.5em;
+    border: 1px solid #ddd;
+    border-radius: 6px;
+    padding: 0.25em 0.75em;
+    background: #fafafa;
+    color: #222;
+}
+summary { cursor: pointer; font-weight: 600; font-size: 0.9em; }
+pre {
+    font-family: "Fira Code", "JetBrains Mono", "Courier New", monospace;
+    font-size: 0.85em;
+    white-space: pre-wrap;
+    word-break: break-word;
+}
+code { font-family: "Fira Code", "JetBrains Mono", "Courier New", monospace; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; }
+th { background: #f0f0f0; }
+li:has(> .task-list-item-checkbox), li:has(> p > .task-list-item-checkbox) { list-style: none; }
+.markdown-alert-title { font-weight: 600; margin: 0.25em 0; }
+.footnotes { font-size: 0.85em; color: #555; }
+blockquote { border-left: 3px solid #ccc; margin-left: 0; padding-left: 1em; color: #555; }
+.story {
+    background: white;
+    border: 1px solid #ddd;
+    border-radius: 8px;
+    padding: 1em 2em;
+    margin-top: 2em;
+}
+.math { font-family: "Times New Roman", serif; font-style: italic; }
+.math-display { display: block; text-align: center; margin: 0.5em 0; }
+"#;
+
+/// Renders a chat as a standalone HTML document.
+///
+/// For stories, agent chats and other toolsets with a side panel, the panel's
+/// content, such as the story text or plan, is included after the conversation.
+///
+/// # Arguments
+/// * `chat` - The chat to export
+///
+/// # Returns
+/// A complete HTML document with inlined CSS
+pub async fn chat_to_html(chat: &Chat) -> String {
+    // The panel only reads the toolset's state, so no MCP servers are needed
+    let host = Arc::new(MCPHost::new_with_tools(
+        HashMap::new(),
+        Duration::ZERO,
+        Duration::ZERO,
+    ));
+    let story = registry::create(&chat.chat_type, host, &chat.value)
+        .get_markdown_repr()
+        .await;
+    let title = match chat.id {
+        Some(id) => format!("Chat #{id}"),
+        None => "Unnamed chat".to_string(),
+    };
+
+    let mut dom = VirtualDom::new_with_props(
+        ExportedChat,
+        ExportedChatProps {
+            title: title.clone(),
+            messages: chat.messages.clone(),
+            story,
+        },
+    );
+    dom.rebuild_in_place();
+    let body = dioxus_ssr::render(&dom);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{EXPORT_CSS}</style>\n</head>\n\
+         <body>\n{body}\n</body>\n</html>\n"
+    )
+}
+
+/// Returns a file name for an exported chat.
+pub fn export_file_name(chat: &Chat) -> String {
+    match chat.id {
+        Some(id) => format!("chat-{id}.html"),
+        None => "chat.html".to_string(),
+    }
+}
+
+/// Saves an exported page to the user's downloads folder.
+///
+/// # Arguments
+/// * `file_name` - Name of the file to create
+/// * `html` - Page contents
+///
+/// # Returns
+/// Where the page went: its path on desktop, its file name in the browser
+pub async fn save_html(file_name: &str, html: &str) -> anyhow::Result<String> {
+    save_download(file_name, "text/html", html.as_bytes()).await
+}
+
+/// Exports several chats as standalone HTML pages in one zip file, saved
+/// to the user's downloads folder.
+///
+/// # Arguments
+/// * `chats` - Chats to export
+///
+/// # Returns
+/// Where the zip file went: its path on desktop, its file name in the browser
+pub async fn export_chats(chats: &[Chat]) -> anyhow::Result<String> {
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for chat in chats {
+        let html = chat_to_html(chat).await;
+        zip.start_file(
+            export_file_name(chat),
+            zip::write::SimpleFileOptions::default(),
+        )?;
+        zip.write_all(html.as_bytes())?;
+    }
+    let bytes = zip.finish()?.into_inner();
+    save_download("chats.zip", "application/zip", &bytes).await
+}
+
+/// Saves a file to the user's downloads folder.
+///
+/// An existing file is never replaced: like browsers do, the name gets a
+/// number, as in `chat-3 (2).html`, until it is free.
+///
+/// # Arguments
+/// * `file_name` - Name of the file to create
+/// * `_mime_type` - MIME type of the file, used by the browser version
+/// * `bytes` - File contents
+///
+/// # Returns
+/// The path the file was written to
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn save_download(
+    file_name: &str,
+    _mime_type: &str,
+    bytes: &[u8],
+) -> anyhow::Result<String> {
+    use tokio::io::AsyncWriteExt;
+
+    let dir = directories_next::UserDirs::new()
+        .and_then(|d| d.download_dir().map(|p| p.to_path_buf()))
+        .unwrap_or_else(crate::storage::config_dir);
+    tokio::fs::create_dir_all(&dir).await?;
+    for n in 1..=1000 {
+        let path = dir.join(numbered_file_name(file_name, n));
+        let mut file = match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(bytes).await?;
+        file.flush().await?;
+        return Ok(path.display().to_string());
+    }
+    anyhow::bail!("No free name for {file_name} in {}", dir.display())
+}
+
+/// Numbers a file name to tell it from existing files.
+///
+/// # Arguments
+/// * `file_name` - Name of the file
+/// * `n` - Number of the copy; the first keeps the name as it is
+///
+/// # Returns
+/// The name with ` (n)` before its extension
+#[cfg(not(target_arch = "wasm32"))]
+fn numbered_file_name(file_name: &str, n: u32) -> String {
+    if n <= 1 {
+        return file_name.to_string();
+    }
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem} ({n}).{ext}"),
+        _ => format!("{file_name} ({n})"),
+    }
+}
+
+/// Offers a file to the browser as a download.
+///
+/// # Arguments
+/// * `file_name` - Suggested name of the downloaded file
+/// * `mime_type` - MIME type of the file
+/// * `bytes` - File contents
+///
+/// # Returns
+/// The suggested file name
+#[cfg(target_arch = "wasm32")]
+pub async fn save_download(
+    file_name: &str,
+    mime_type: &str,
+    bytes: &[u8],
+) -> anyhow::Result<String> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    let eval = document::eval(
+        r#"
+        const name = await dioxus.recv();
+        const type = await dioxus.recv();
+        const data = await dioxus.recv();
+        const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+        const url = URL.createObjectURL(new Blob([bytes], { type }));
+        const a = document.createElement("a");
+        a.href = url;
+        a.download = name;
+        a.click();
+        URL.revokeObjectURL(url);
+        "#,
+    );
+    eval.send(file_name)
+        .and_then(|_| eval.send(mime_type))
+        .and_then(|_| eval.send(STANDARD.encode(bytes)))
+        .map_err(|e| anyhow::anyhow!("Could not start download: {e:?}"))?;
+    Ok(file_name.to_string())
+}
+
+/// Saves a file where the user picks in a save dialog.
+///
+/// # Arguments
+/// * `file_name` - Name suggested in the dialog
+/// * `_mime_type` - MIME type of the file, used by the browser version
+/// * `bytes` - File contents
+///
+/// # Returns
+/// The path the file was written to, or `None` if the user cancelled
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+pub async fn save_file_as(
+    file_name: &str,
+    _mime_type: &str,
+    bytes: &[u8],
+) -> anyhow::Result<Option<String>> {
+    let dir = directories_next::UserDirs::new()
+        .and_then(|d| d.download_dir().map(|p| p.to_path_buf()))
+        .unwrap_or_else(crate::storage::config_dir);
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_directory(dir)
+        .set_file_name(file_name)
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    tokio::fs::write(file.path(), bytes).await?;
+    Ok(Some(file.path().display().to_string()))
+}
+
+/// Saves a file as a download, on platforms without a save dialog.
+///
+/// # Arguments
+/// * `file_name` - Name of the file to create
+/// * `mime_type` - MIME type of the file
+/// * `bytes` - File contents
+///
+/// # Returns
+/// Where the file went, as from [`save_download`]
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub async fn save_file_as(
+    file_name: &str,
+    mime_type: &str,
+    bytes: &[u8],
+) -> anyhow::Result<Option<String>> {
+    save_download(file_name, mime_type, bytes).await.map(Some)
+}
+
+/// Body of the exported page.
+#[component]
+fn ExportedChat(title: String, messages: Vec<Message>, story: Option<String>) -> Element {
+    rsx! {
+        main {
+            h1 { class: "title", "{title}" }
+            for (i , msg) in messages.into_iter().enumerate() {
+                ExportedMessage { key: "{i}", msg }
+            }
+            if let Some(story) = story {
+                section { class: "story", {markdown_to_rsx_with(&story, MarkdownOptions::default())} }
+            }
+        }
+    }
+}
+
+/// A single message; tool calls, tool results and the system prompt are collapsed.
+///
+/// KaTeX is not bundled into exports, so math is left as its TeX source.
+#[component]
+fn ExportedMessage(msg: Message) -> Element {
+    let options = MarkdownOptions::default();
+    match msg {
+        Message::System { content } => rsx! {
+            details {
+                summary { "System prompt" }
+                pre { "{content}" }
+            }
+        },
+        Message::User { content } => rsx! {
+            div { class: "message user-message",
+                div { class: "role", "You" }
+                for part in content {
+                    match part {
+                        ContentPart::Text { text } => markdown_to_rsx_with(&text, options),
+                        ContentPart::ImageUrl { image_url } => rsx! {
+                            a { href: "{image_url.url}", "[image]" }
+                        },
+                        ContentPart::InputAudio { input_audio } => rsx! {
+                            audio {
+                                controls: true,
+                                src: "data:audio/{input_audio.format};base64,{input_audio.data}",
+                            }
+                        },
+                    }
+                }
+            }
+        },
+        Message::Assistant {
+            content,
+            tool_calls,
+        } => {
+            let fns: Vec<FunctionDelta> = tool_calls
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|tc| tc.function)
+                .collect();
+            rsx! {
+                div { class: "message ai-message",
+                    div { class: "role", "Assistant" }
+                    if let Some(c) = content {
+                        {markdown_to_rsx_with(&c, options)}
+                    }
+                    for f in fns {
+                        details {
+                            summary { "🔧 {f.name.clone().unwrap_or_default()}" }
+                            pre { "{f.arguments.clone().unwrap_or_default()}" }
+                        }
+                    }
+                }
+            }
+        }
+        Message::Tool { content, .. } => rsx! {
+            details {
+                summary { "🔧 Tool Result" }
+                {markdown_to_rsx_with(&content, options)}
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_file_names_before_the_extension() {
+        assert_eq!(numbered_file_name("chat-3.html", 1), "chat-3.html");
+        assert_eq!(numbered_file_name("chat-3.html", 2), "chat-3 (2).html");
+        assert_eq!(numbered_file_name("chats.zip", 10), "chats (10).zip");
+        assert_eq!(numbered_file_name("notes", 2), "notes (2)");
+        assert_eq!(numbered_file_name(".env", 2), ".env (2)");
+    }
+}
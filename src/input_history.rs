@@ -0,0 +1,127 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Messages the user sent before, recalled in the chat input.
+//!
+//! The input cycles through the current chat's messages with the arrow keys,
+//! like a shell, and searches the messages of all stored chats with Ctrl+R.
+//! Nothing is stored apart from the chats: the history is read from them when
+//! the search opens.
+
+use crate::{
+    llm::{ContentPart, Message},
+    storage::{Storage, get_storage},
+};
+
+/// Most messages offered by the search across chats.
+const MAX_PROMPTS: usize = 5_000;
+
+/// Text of a message sent by the user, without attachments.
+fn user_text(message: &Message) -> Option<String> {
+    let Message::User { content } = message else {
+        return None;
+    };
+    let text = content
+        .iter()
+        .filter_map(|c| match c {
+            ContentPart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Messages the user sent in a conversation, for recall with the arrow keys.
+///
+/// # Arguments
+/// * `messages` - The conversation
+///
+/// # Returns
+/// The messages' text, oldest first, with repeats in a row kept once
+pub fn chat_prompts(messages: &[Message]) -> Vec<String> {
+    let mut prompts: Vec<String> = messages.iter().filter_map(user_text).collect();
+    prompts.dedup();
+    prompts
+}
+
+/// Messages the user sent in all stored chats of the profile.
+///
+/// # Returns
+/// The messages' text, newest chat first and each chat's last message first,
+/// with each text kept once
+pub async fn all_prompts() -> anyhow::Result<Vec<String>> {
+    let mut chats = get_storage().await?.list_chats().await?;
+    // Chat IDs grow as chats are created
+    chats.sort_by_key(|c| std::cmp::Reverse(c.id));
+    let mut seen = std::collections::HashSet::new();
+    let mut prompts = Vec::new();
+    for chat in chats {
+        for text in chat.messages.iter().rev().filter_map(user_text) {
+            if prompts.len() == MAX_PROMPTS {
+                return Ok(prompts);
+            }
+            if seen.insert(text.clone()) {
+                prompts.push(text);
+            }
+        }
+    }
+    Ok(prompts)
+}
+
+/// Scores how well a text matches a fuzzy query: the query's characters must
+/// appear in the text in order, ignoring case. Matches at the start of words
+/// and runs of consecutive characters score higher, gaps lower.
+///
+/// # Arguments
+/// * `query` - What was typed
+/// * `text` - A past message
+///
+/// # Returns
+/// The score, or `None` if the text does not match
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    let mut previous = ' ';
+    for (i, c) in text.chars().enumerate() {
+        if next < query.len() && c.to_lowercase().eq(std::iter::once(query[next])) {
+            score += 1;
+            if !previous.is_alphanumeric() {
+                score += 8;
+            }
+            match last_match {
+                Some(last) if last + 1 == i => score += 5,
+                Some(last) => score -= ((i - last - 1) as i64).min(5),
+                None => {}
+            }
+            last_match = Some(i);
+            next += 1;
+        }
+        previous = c;
+    }
+    (next == query.len()).then_some(score)
+}
+
+/// Past messages matching a fuzzy query, best first; equal matches keep
+/// their order, so newer messages come first.
+///
+/// # Arguments
+/// * `prompts` - Past messages, newest first
+/// * `query` - What was typed
+/// * `limit` - Most messages returned
+pub fn search<'a>(prompts: &'a [String], query: &str, limit: usize) -> Vec<&'a String> {
+    let mut matches: Vec<(i64, &String)> = prompts
+        .iter()
+        .filter_map(|p| fuzzy_score(query, p).map(|s| (s, p)))
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().take(limit).map(|(_, p)| p).collect()
+}
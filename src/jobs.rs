@@ -0,0 +1,142 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Background jobs: work that follows a chat turn without holding it up.
+//!
+//! [`spawn_job`] runs a future on the app's executor, which is driven by tokio
+//! on native platforms and by the browser's event loop on wasm, and records
+//! its progress in a small in-memory list. Storage futures are not `Send`, so
+//! jobs stay on the UI thread; they only wait on I/O there.
+//!
+//! The list feeds the activity indicator in the toolbar, which
+//! [`subscribe`]s to be woken whenever a job starts or ends. Failures are
+//! logged and kept in the list until [`clear_finished`] is called; jobs that
+//! succeed drop out of the indicator by themselves.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use dioxus::{core::spawn_forever, logger::tracing::warn};
+use tokio::sync::watch;
+
+use crate::core::now_millis;
+
+/// Number of finished jobs kept in the list.
+const MAX_FINISHED: usize = 20;
+
+/// Jobs, oldest first
+static JOBS: Mutex<VecDeque<Job>> = Mutex::new(VecDeque::new());
+/// Counter used to generate job IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+/// Bumped whenever the list changes
+static CHANGED: LazyLock<watch::Sender<u64>> = LazyLock::new(|| watch::Sender::new(0));
+
+/// Progress of a job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Done,
+    Failed(String),
+}
+
+/// A background job and its progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    pub id: u64,
+    /// What the job does, such as "Saving the daily story snapshot"
+    pub label: String,
+    /// When the job started, in milliseconds since the Unix epoch
+    pub started_at: u64,
+    pub status: JobStatus,
+}
+
+/// Runs a job in the background, outliving the component that started it.
+///
+/// # Arguments
+/// * `label` - What the job does, shown in the activity indicator
+/// * `job` - The work; an error is logged and shown as a failure
+pub fn spawn_job<F>(label: impl Into<String>, job: F)
+where
+    F: Future<Output = anyhow::Result<()>> + 'static,
+{
+    let id = start(label.into());
+    if spawn_forever(async move { finish(id, job.await) }).is_none() {
+        finish(id, Err(anyhow::anyhow!("The app is shutting down")));
+    }
+}
+
+/// Returns the jobs that are running or have failed, oldest first.
+pub fn jobs() -> Vec<Job> {
+    JOBS.lock()
+        .map(|jobs| {
+            jobs.iter()
+                .filter(|j| j.status != JobStatus::Done)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Forgets the jobs that have finished, including failed ones.
+pub fn clear_finished() {
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.retain(|j| j.status == JobStatus::Running);
+    }
+    changed();
+}
+
+/// Returns a receiver that is notified whenever a job starts or ends.
+pub fn subscribe() -> watch::Receiver<u64> {
+    CHANGED.subscribe()
+}
+
+/// Adds a running job to the list.
+fn start(label: String) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.push_back(Job {
+            id,
+            label,
+            started_at: now_millis(),
+            status: JobStatus::Running,
+        });
+    }
+    changed();
+    id
+}
+
+/// Records the outcome of a job, dropping the oldest finished jobs.
+fn finish(id: u64, outcome: anyhow::Result<()>) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            job.status = match outcome {
+                Ok(()) => JobStatus::Done,
+                Err(e) => {
+                    warn!("{} failed: {e:?}", job.label);
+                    JobStatus::Failed(format!("{e:#}"))
+                }
+            };
+        }
+        let mut finished = jobs
+            .iter()
+            .filter(|j| j.status != JobStatus::Running)
+            .count();
+        while finished > MAX_FINISHED {
+            let Some(i) = jobs.iter().position(|j| j.status != JobStatus::Running) else {
+                break;
+            };
+            jobs.remove(i);
+            finished -= 1;
+        }
+    }
+    changed();
+}
+
+fn changed() {
+    CHANGED.send_modify(|n| *n += 1);
+}
@@ -19,32 +19,63 @@ use dioxus::prelude::*;
 
 // Public modules - exposed for external use
 pub mod app_settings; // Settings for the application
+pub mod core; // UI-free chat loop for embedding
 pub mod llm; // LLM client and message handling
 pub mod mcp; // Model Context Protocol implementation
+pub mod toolset; // Toolsets chats run in, and their registry
 
 // Private modules - internal implementation details
+mod ab_test; // Prompt A/B tests: one prompt under two system prompts
+mod capture; // Capture and replay of raw provider streams
+mod export; // Standalone HTML export of chats
+mod input_history; // Past user messages, recalled in the chat input
+mod jobs; // Background jobs that follow a chat turn
 mod md2rsx; // Markdown to RSX conversion utilities
+mod notifications; // Notifications when long turns finish in the background
+mod request_log; // Optional recording of raw LLM requests and responses
+#[cfg(not(target_arch = "wasm32"))]
+mod scheduler; // Recurring prompts run in the background
+mod snapshots; // Named versions of stories
+mod sse; // Incremental parsing of server-sent events
 mod storage; // DB for settings, chats etc
-mod toolset;
+mod tool_prompt; // Tools described in the prompt for models without tool support
+mod tts; // Text to speech for listening to stories
 mod ui; // User interface components
 mod utils; // Utility functions for tool handling // specialised toolsets like storywriting, RP, coding ...
 
-use app_settings::AppSettings;
+use app_settings::{AppSettings, SettingsOverrides, Toolsets};
+use ui::ab_test::AbTest;
+use ui::activity::ActivityIndicator;
+use ui::debug_panel::DebugPanel;
 use ui::home::ChatEl;
 use ui::home::NewChat;
+use ui::home::NewToolsetChat;
 use ui::home::NewStory;
 use ui::mcp_tools::McpTools;
+use ui::nav_guard::{LeaveDialog, NavGuard};
+use ui::offline::{OfflineBanner, use_online_provider, use_service_worker};
+use ui::permissions::PermissionDialog;
+use ui::reading_view::ReadStory;
+use ui::recovery::RecoveryPrompt;
+#[cfg(not(target_arch = "wasm32"))]
+use ui::schedules::Schedules;
 use ui::settings::Settings;
 use ui::slideout::Slideout;
+use ui::startup::{StartupScreen, WarmupStep, warm_up};
+use ui::unlock::UnlockScreen;
 
 use crate::mcp::host::MCPHost;
-use crate::storage::Storage;
-use crate::storage::get_storage;
 use crate::ui::chat_log::ChatLog;
 
 /// Main CSS stylesheet for application styling
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 
+/// Client-side KaTeX loader that renders math emitted by md2rsx
+const MATH_JS: Asset = asset!("/assets/math.js");
+
+/// KaTeX stylesheet, loaded only when math rendering is enabled
+const KATEX_CSS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist/katex.min.css";
+
 /// Application favicon - SVG format for scalability
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 // Chat log icon
@@ -57,49 +88,126 @@ const CHATS_ICON: Asset = asset!("/assets/chat_list.png");
 const SETTINGS_ICON: Asset = asset!("/assets/settings.png");
 // Tools icon
 const TOOLS_ICON: Asset = asset!("/assets/tools.png");
+// Schedules icon
+const SCHEDULE_ICON: Asset = asset!("/assets/schedule.png");
 
 /// Root application component that sets up routing and global resources.
 ///
 /// This component:
 /// - Loads the favicon and main CSS stylesheet
-/// - Shows a loading state during initialization
+/// - Shows warm-up progress while MCP servers start and models are fetched
 /// - Sets up the router for navigation between pages
 #[component]
 pub fn App() -> Element {
     let mut settings: Signal<Option<AppSettings>> = use_signal(|| None);
     use_context_provider(|| Arc::new(MCPHost::new()));
     use_context_provider(|| settings);
+    // Config file and environment overrides, read once at startup
+    use_context_provider(|| SettingsOverrides::current().clone());
+    use_online_provider();
+    use_service_worker();
+    #[cfg(feature = "desktop")]
+    use_graceful_shutdown();
+    let warmup: Signal<Vec<WarmupStep>> = use_signal(Vec::new);
+    let mut skip_warmup = use_signal(|| false);
+    let mut lock: Signal<Option<storage::encryption::EncryptionInfo>> = use_signal(|| None);
+    let mut unlocked = use_signal(|| false);
 
     let init = use_resource(move || async move {
-        let storage = match get_storage().await {
-            Ok(s) => s,
-            Err(e) => {
-                warn!("Could not get storage: {e:?}");
-                bail!("Could not get storage: {e:?}");
+        // Encrypted storage is unlocked before anything is loaded from it;
+        // entering the passphrase reruns initialization
+        let _ = unlocked();
+        match storage::load_encryption().await {
+            Ok(Some(info)) if storage::encryption::is_locked() => {
+                lock.set(Some(info));
+                std::future::pending::<()>().await;
             }
-        };
-        let s = storage.load_settings().await.unwrap();
-        settings.set(s);
+            Ok(_) => {}
+            Err(e) => warn!("Could not check storage encryption: {e:?}"),
+        }
+        match storage::load_profiles().await {
+            Ok(profiles) => storage::set_active_profile(&profiles.active),
+            Err(e) => warn!("Could not load profiles: {e:?}"),
+        }
+        let host = consume_context::<Arc<MCPHost>>();
+        if let Err(e) = utils::load_active_profile(&mut settings, &host).await {
+            warn!("Could not get storage: {e:?}");
+            bail!("Could not get storage: {e:?}");
+        }
+        // Start servers and fetch models now rather than on first use
+        let st = settings.peek().clone();
+        if let Some(st) = st {
+            warm_up(&st, &host, warmup).await;
+        }
         anyhow::Ok(())
     });
     let _ = use_resource(move || async move {
+        // Servers are started by the warm-up until initialization is done
+        if init.read().is_none() {
+            return Ok(());
+        }
         let st = settings();
         // sync MCP servers with settings
         let host = consume_context::<Arc<MCPHost>>();
+        if let Some(st) = &st {
+            host.set_permissions(st.permissions.clone()).await;
+            request_log::set_enabled(st.debug_logging);
+        }
+        let plugins = st.as_ref().map(|st| st.plugins.clone()).unwrap_or_default();
+        let scripted_tools = st.as_ref().map(|st| st.scripted_tools.clone()).unwrap_or_default();
+        let shell = st.as_ref().map(|st| st.shell.clone()).unwrap_or_default();
+        let openapi = st.as_ref().map(|st| st.openapi.clone()).unwrap_or_default();
         let specs = st.and_then(|st| st.mcp_servers).unwrap_or_default();
         host.sync_servers(specs).await?;
+        host.sync_plugins(&plugins).await?;
+        host.sync_scripted_tools(&scripted_tools).await;
+        host.sync_shell(&shell).await;
+        host.sync_openapi(&openapi).await;
 
         anyhow::Ok(())
     });
+    // Restart servers under development when their files change; rerunning on a
+    // settings change drops the previous watcher
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = use_resource(move || async move {
+        if init.read().is_none() {
+            return;
+        }
+        let specs = settings().and_then(|st| st.mcp_servers).unwrap_or_default();
+        mcp::watch::watch_servers(consume_context::<Arc<MCPHost>>(), specs).await;
+    });
+    let render_math = settings
+        .read()
+        .as_ref()
+        .map(|s| s.display.render_math)
+        .unwrap_or(true);
 
     rsx! {
         // Set up document head with favicon and stylesheet
         document::Link { rel: "icon", href: FAVICON }
         document::Link { rel: "stylesheet", href: MAIN_CSS }
+        // Lay out for the device width on phones, up to the screen edges
+        document::Meta {
+            name: "viewport",
+            content: "width=device-width, initial-scale=1, viewport-fit=cover",
+        }
+        // Installable as an app from the browser
+        if cfg!(target_arch = "wasm32") {
+            document::Link { rel: "manifest", href: "./manifest.webmanifest" }
+            document::Meta { name: "theme-color", content: "#8b9dc3" }
+        }
 
-        // Show loading state until initialization is complete
-        if init.read().is_none() {
-            "Loading..."
+        // KaTeX for LaTeX math in messages (can be disabled in settings)
+        if render_math {
+            document::Link { rel: "stylesheet", href: KATEX_CSS }
+            document::Script { src: MATH_JS }
+        }
+
+        if let Some(info) = lock().filter(|_| !unlocked()) {
+            UnlockScreen { info, on_unlock: move |_| unlocked.set(true) }
+        } else if init.read().is_none() && !skip_warmup() {
+            // Show warm-up progress until initialization is complete
+            StartupScreen { steps: warmup(), on_skip: move |_| skip_warmup.set(true) }
         } else {
             // Render the main router once initialization is done
             Router::<Route> {}
@@ -107,11 +215,55 @@ pub fn App() -> Element {
     }
 }
 
+/// Stops MCP servers gracefully when the window is closed.
+///
+/// The window is hidden while the servers shut down and closed once they have,
+/// so servers are not killed in the middle of a write when the app exits.
+#[cfg(feature = "desktop")]
+fn use_graceful_shutdown() {
+    use dioxus::desktop::{
+        WindowCloseBehaviour, WindowEvent, tao::event::Event, use_wry_event_handler, window,
+    };
+
+    let desktop = window();
+    let host = consume_context::<Arc<MCPHost>>();
+    let mut closing = use_signal(|| false);
+    use_wry_event_handler(move |event, _| {
+        let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } = event
+        else {
+            return;
+        };
+        if closing() {
+            return;
+        }
+        closing.set(true);
+        // Hide instead of closing until the servers have stopped
+        desktop.set_close_behavior(WindowCloseBehaviour::WindowHides);
+        let desktop = desktop.clone();
+        let host = host.clone();
+        spawn(async move {
+            // Server states are otherwise only saved a while after each chat
+            if let Err(e) = utils::save_server_states(&host).await {
+                warn!("Could not save MCP server states: {e:?}");
+            }
+            host.shutdown().await;
+            desktop.close();
+        });
+    });
+}
+
 /// Application routes defining the available pages and their URL patterns.
 /// 
 /// The application has two main routes:
 /// - `/` - Home page with the main chat interface
-/// - `/chats/:id` - Individual chat pages
+/// - `/new/:toolset` - New chat in a registered toolset, by its ID
+/// - `/chats/:id` - Individual chat pages; `#msg-42` links to a message
+/// - `/chats/:id/read` - Reading view of a story
+/// - `/debug` - Log of raw LLM requests, for debugging providers
+/// - `/ab` - A/B tests of system prompts
 /// - `/*` - Catch-all for 404 pages
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -121,8 +273,17 @@ enum Route {
     NewChat { },
     #[route("/story")]
     NewStory { },
-    #[route("/chats/:id")]
-    ChatEl { id: u32 },
+    #[route("/new/:toolset")]
+    NewToolsetChat { toolset: Toolsets },
+    // The anchor links to a message, as in /chats/3#msg-42
+    #[route("/chats/:id#:anchor")]
+    ChatEl { id: u32, anchor: String },
+    #[route("/chats/:id/read")]
+    ReadStory { id: u32 },
+    #[route("/debug")]
+    DebugPanel { },
+    #[route("/ab")]
+    AbTest { },
     #[route("/:..segments")]
     PageNotFound { segments: Vec<String> },
 }
@@ -135,23 +296,26 @@ enum Route {
 fn Layout() -> Element {
     let mut slideout = use_signal(|| false);
     let mut slideout_content = use_signal(|| SlideoutContent::ChatLog);
+    // Links away from a chat ask first while a turn runs or a message is unsent
+    let guard = NavGuard::provide();
+    let guarded = guard.has_work();
+
+    // Run scheduled prompts in the background while the app is open
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let nav = navigator();
+        use_future(move || {
+            scheduler::run_scheduler(consume_context::<Arc<MCPHost>>(), nav)
+        });
+    }
 
     rsx! {
-        div {
-            class: "tool-icons", 
-            style: "
-                position: fixed;
-                top: 2px;
-                left: 2px;
-                z-index: 9;
-                width: calc(100% - 3em);
-                padding-left: 1.5em;
-                padding-right: 1.5em;
-                display: flex;
-                flex-direction: row;
-            ",
+        // Row of icons at the top, or a bottom navigation bar on narrow screens
+        div { class: "tool-icons",
             Link {
                 to: crate::Route::NewChat {},
+                onclick_only: guarded,
+                onclick: move |_| if guarded { guard.ask(crate::Route::NewChat {}) },
                 img { src: NEW_CHAT_ICON }
                 "New Chat",
             },
@@ -163,6 +327,8 @@ fn Layout() -> Element {
             // }
             Link {
                 to: crate::Route::NewStory {},
+                onclick_only: guarded,
+                onclick: move |_| if guarded { guard.ask(crate::Route::NewStory {}) },
                 img { src: NEW_STORY_ICON }
                 "New Article",
             },
@@ -172,12 +338,32 @@ fn Layout() -> Element {
             //     },
             //     img { src: NEW_STORY_ICON }
             // }
+            // Other toolsets, including those registered at runtime, get a link of their own
+            for entry in toolset::registry::entries() {
+                if let Toolsets::Coding | Toolsets::Roleplay | Toolsets::Custom(_) = &entry.id {
+                    Link {
+                        key: "{entry.id}",
+                        to: crate::Route::NewToolsetChat { toolset: entry.id.clone() },
+                        onclick_only: guarded,
+                        onclick: {
+                            let toolset = entry.id.clone();
+                            move |_| {
+                                if guarded {
+                                    guard.ask(crate::Route::NewToolsetChat { toolset: toolset.clone() });
+                                }
+                            }
+                        },
+                        "{entry.icon} New {entry.name}",
+                    }
+                }
+            }
             // Link {
             //     to: crate::Route::NewStory {},
             //     img { src: CHATS_ICON }
             //     "Your Chats",
             // },
-            div {style: "flex-grow: 1;", ""}
+            div { class: "nav-spacer", "" }
+            ActivityIndicator {}
             span {
                 onclick: move |_e: Event<MouseData>| {
                     slideout_content.set(SlideoutContent::ChatLog);
@@ -202,6 +388,16 @@ fn Layout() -> Element {
                 img { src: TOOLS_ICON },
                 "Tools"
             }
+            if cfg!(not(target_arch = "wasm32")) {
+                span {
+                    onclick: move |_e: Event<MouseData>| {
+                        slideout_content.set(SlideoutContent::Schedules);
+                        slideout.set(true);
+                    },
+                    img { src: SCHEDULE_ICON },
+                    "Schedules"
+                }
+            }
         }
         Slideout {
             open: slideout,
@@ -228,9 +424,23 @@ fn Layout() -> Element {
                             },
                         }
                     },
+                    #[cfg(not(target_arch = "wasm32"))]
+                    SlideoutContent::Schedules => rsx! {
+                        Schedules {
+                            on_close: move |_| {
+                                slideout.set(false);
+                            },
+                        }
+                    },
+                    #[cfg(target_arch = "wasm32")]
+                    SlideoutContent::Schedules => rsx! {},
                 }
             },
         }
+        OfflineBanner {}
+        RecoveryPrompt {}
+        LeaveDialog {}
+        PermissionDialog {}
         Outlet::<Route> {}
     }
 }
@@ -240,6 +450,7 @@ enum SlideoutContent {
     ChatLog,
     Settings,
     McpTools,
+    Schedules,
 }
 
 /// 404 page component shown when a user navigates to an invalid route.
@@ -9,16 +9,62 @@
 //! The client supports both native and WASM targets, with appropriate async runtime
 //! handling for each platform.
 
-use anyhow::bail;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::Infallible,
+    fmt::{Debug, Display},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail};
 use dioxus::logger::tracing::{info, warn};
-use futures::StreamExt as _;
+use futures::{Stream, StreamExt as _};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tokio::{
     spawn,
     sync::mpsc::{self, Receiver},
 };
 
+use crate::{
+    capture::{Capture, CapturedResponse},
+    core::{now_millis, sleep},
+    request_log,
+    sse::SseDecoder,
+    tool_prompt,
+};
+
+/// How long a streaming response may go without data before it is aborted, by default.
+pub const DEFAULT_STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Body fields of chat completion requests that extra fields may not replace.
+pub const RESERVED_BODY_FIELDS: [&str; 4] = ["model", "messages", "stream", "tools"];
+
+/// How long a connection test may take before the provider is reported down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool offered in connection tests, to see whether the model calls it.
+const CHECK_TOOL: &str = "report_status";
+
+/// Model names last fetched from each API URL
+static MODEL_CACHE: Mutex<BTreeMap<String, Vec<String>>> = Mutex::new(BTreeMap::new());
+/// Metadata of each model, per API URL, as of the last fetch
+static MODEL_META: Mutex<BTreeMap<String, BTreeMap<String, ModelMeta>>> =
+    Mutex::new(BTreeMap::new());
+
+/// What an API's model list says about a model.
+#[derive(Clone, Debug, Default)]
+struct ModelMeta {
+    /// Kinds of input the model accepts
+    input_modalities: Option<Vec<String>>,
+    /// Whether the model accepts tools
+    supports_tools: Option<bool>,
+    /// Most tokens the model can take in and put out per request
+    context_length: Option<u64>,
+}
+
 /// HTTP client for communicating with LLM APIs.
 ///
 /// Supports OpenAI-compatible APIs and handles authentication, request formatting,
@@ -32,6 +78,34 @@ pub struct LlmClient {
     api_key: String,
     /// HTTP client for making requests
     client: Client,
+    /// Longest gap allowed between chunks of a streaming response
+    idle_timeout: Duration,
+    /// Sequences at which the model stops generating
+    stop: Vec<String>,
+    /// Text the assistant's reply is made to start with
+    prefill: String,
+    /// Constraint on the generated text, for backends that support one
+    constraint: Option<Constraint>,
+    /// Extra headers sent with every request
+    headers: BTreeMap<String, String>,
+    /// Extra fields added to chat completion request bodies
+    extra_body: serde_json::Map<String, Value>,
+    /// Whether models accept tools, overriding the model metadata, by model ID
+    tool_support: BTreeMap<String, bool>,
+    /// Captured responses streams are answered from instead of the API, if replaying
+    replay: Option<Arc<Mutex<VecDeque<CapturedResponse>>>>,
+}
+
+/// A constraint on the text a model may generate.
+///
+/// Supported by local backends: llama.cpp's server accepts both kinds, Ollama
+/// only JSON schemas. Hosted providers may reject or ignore them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    /// A GBNF grammar, sent as llama.cpp's `grammar` parameter
+    Grammar(String),
+    /// A JSON schema the response must match, sent as `response_format`
+    JsonSchema(serde_json::Value),
 }
 
 impl LlmClient {
@@ -45,9 +119,138 @@ impl LlmClient {
             api_url,
             api_key,
             client: Client::new(),
+            idle_timeout: DEFAULT_STREAM_IDLE_TIMEOUT,
+            stop: vec![],
+            prefill: String::new(),
+            constraint: None,
+            headers: BTreeMap::new(),
+            extra_body: serde_json::Map::new(),
+            tool_support: BTreeMap::new(),
+            replay: None,
+        }
+    }
+
+    /// Sets how long a streaming response may go without data before it is aborted.
+    ///
+    /// # Arguments
+    /// * `timeout` - Longest gap allowed between chunks
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Sets sequences at which the model stops generating, e.g. `"\nUser:"`.
+    ///
+    /// Empty sequences are ignored. Providers limit how many are accepted;
+    /// OpenAI allows four.
+    ///
+    /// # Arguments
+    /// * `stop` - Stop sequences sent with every chat completion request
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = stop.into_iter().filter(|s| !s.is_empty()).collect();
+        self
+    }
+
+    /// Sets text the assistant's reply is made to start with.
+    ///
+    /// The prefill is sent as a trailing assistant message for the model to
+    /// continue, and streamed back as the first chunk of the response, so the
+    /// reply includes it. It only applies to responses to a user message, not
+    /// to responses following tool results. Not every provider continues a
+    /// trailing assistant message; others answer afresh after it.
+    ///
+    /// # Arguments
+    /// * `prefill` - Start of the reply, or an empty string for none
+    pub fn with_prefill(mut self, prefill: String) -> Self {
+        self.prefill = prefill;
+        self
+    }
+
+    /// Sets a grammar or JSON schema the generated text must follow.
+    ///
+    /// Some servers, llama.cpp among them, refuse a custom grammar in requests
+    /// that also offer tools.
+    ///
+    /// # Arguments
+    /// * `constraint` - The constraint, or `None` for free-form output
+    pub fn with_constraint(mut self, constraint: Option<Constraint>) -> Self {
+        self.constraint = constraint;
+        self
+    }
+
+    /// Sets headers sent with every request, e.g. OpenRouter's `HTTP-Referer`
+    /// and `X-Title` attribution headers.
+    ///
+    /// # Arguments
+    /// * `headers` - Header values by name
+    pub fn with_headers(mut self, headers: BTreeMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Sets fields added to every chat completion request body, e.g. OpenRouter's
+    /// `provider` routing preferences.
+    ///
+    /// The fields may replace parameters like `max_tokens`, but not
+    /// [`RESERVED_BODY_FIELDS`], which the client fills in itself.
+    ///
+    /// # Arguments
+    /// * `body` - Fields by name
+    pub fn with_extra_body(mut self, body: serde_json::Map<String, Value>) -> Self {
+        self.extra_body = body;
+        self
+    }
+
+    /// Sets which models accept tools, overriding the model metadata.
+    ///
+    /// Tools are described in the system prompt for models that do not accept
+    /// them; see [`crate::tool_prompt`].
+    ///
+    /// # Arguments
+    /// * `tool_support` - Whether each model accepts tools, by model ID
+    pub fn with_tool_support(mut self, tool_support: BTreeMap<String, bool>) -> Self {
+        self.tool_support = tool_support;
+        self
+    }
+
+    /// Answers streaming requests from captured responses instead of the API.
+    ///
+    /// Each request takes the next response, in order, and fails once they
+    /// run out; clones of the client share them. Model metadata is not
+    /// fetched while replaying. See [`crate::capture`].
+    ///
+    /// # Arguments
+    /// * `responses` - The chunks of each response body, as loaded by
+    ///   [`crate::core::load_replay`]
+    pub fn with_replay(mut self, responses: Vec<CapturedResponse>) -> Self {
+        self.replay = Some(Arc::new(Mutex::new(responses.into())));
+        self
+    }
+
+    /// Takes the next captured response, if replaying.
+    ///
+    /// # Returns
+    /// The response's chunks, `None` if not replaying, or an error if no
+    /// responses are left
+    fn next_replay(&self) -> anyhow::Result<Option<CapturedResponse>> {
+        let Some(replay) = &self.replay else {
+            return Ok(None);
+        };
+        let next = replay.lock().map_err(|e| anyhow!("{e}"))?.pop_front();
+        match next {
+            Some(chunks) => Ok(Some(chunks)),
+            None => bail!("No captured responses left to replay"),
         }
     }
 
+    /// Adds the extra headers to a request.
+    fn add_headers(&self, mut req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        for (name, value) in &self.headers {
+            req = req.header(name, value);
+        }
+        req
+    }
+
     /// Retrieves the list of available models from the LLM API.
     ///
     /// Makes a GET request to the `/models` endpoint to fetch all available
@@ -57,22 +260,350 @@ impl LlmClient {
     /// A `ModelsResponse` containing the list of available models, or an error
     /// if the request fails or the API returns an error status.
     pub async fn models(&self) -> anyhow::Result<ModelsResponse> {
-        let res = self
+        self.get_json("models").await
+    }
+
+    /// Retrieves the API key's label, spending limit and rate limit.
+    ///
+    /// Only OpenRouter offers this, at its `/key` endpoint.
+    ///
+    /// # Returns
+    /// What OpenRouter reports about the key, or an error if the request fails
+    pub async fn key_info(&self) -> anyhow::Result<KeyInfo> {
+        Ok(self.get_json::<DataResponse<KeyInfo>>("key").await?.data)
+    }
+
+    /// Retrieves the credits bought and used on the account of the API key.
+    ///
+    /// Only OpenRouter offers this, at its `/credits` endpoint.
+    ///
+    /// # Returns
+    /// The account's credits, or an error if the request fails
+    pub async fn credits(&self) -> anyhow::Result<Credits> {
+        Ok(self.get_json::<DataResponse<Credits>>("credits").await?.data)
+    }
+
+    /// Loads a model into memory on an Ollama server, so the next request
+    /// does not wait for it to load.
+    ///
+    /// Uses Ollama's native `/api/generate` endpoint, which loads the model
+    /// without generating anything when no prompt is sent.
+    ///
+    /// # Arguments
+    /// * `model` - Model to load
+    /// * `keep_alive` - How long to keep it loaded, e.g. "30m" or "-1" for
+    ///   until the server stops; the server's default if `None`
+    ///
+    /// # Returns
+    /// An error if the request fails
+    pub async fn warm_up(&self, model: &str, keep_alive: Option<&str>) -> anyhow::Result<()> {
+        let mut body = serde_json::json!({ "model": model, "stream": false });
+        if let Some(keep_alive) = keep_alive {
+            body["keep_alive"] = keep_alive.into();
+        }
+        let url = format!("{}/api/generate", self.native_api_url());
+        self.send_json::<Value>(reqwest::Method::POST, &url, Some(&body)).await?;
+        Ok(())
+    }
+
+    /// Retrieves the models an Ollama server has loaded in memory, from its
+    /// native `/api/ps` endpoint.
+    ///
+    /// # Returns
+    /// The loaded models, or an error if the request fails
+    pub async fn loaded_models(&self) -> anyhow::Result<Vec<LoadedModel>> {
+        let url = format!("{}/api/ps", self.native_api_url());
+        let res: LoadedModels = self.send_json(reqwest::Method::GET, &url, None).await?;
+        Ok(res.models)
+    }
+
+    /// Tests the provider: lists its models, then streams a tiny completion
+    /// that offers the model a tool, timing both. Gives up after 30 seconds.
+    ///
+    /// # Arguments
+    /// * `model` - Model to ask for the completion
+    ///
+    /// # Returns
+    /// What was found, with the error if the provider could not be reached
+    pub async fn check(&self, model: &str) -> ProviderCheck {
+        let checked_at = now_millis();
+        let check = Box::pin(self.run_check(model, checked_at));
+        let timeout = Box::pin(sleep(CHECK_TIMEOUT));
+        match futures::future::select(check, timeout).await {
+            futures::future::Either::Left((check, _)) => check,
+            futures::future::Either::Right(_) => ProviderCheck {
+                checked_at,
+                model: model.to_string(),
+                error: Some(format!("No answer within {} s", CHECK_TIMEOUT.as_secs())),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Runs the steps of [`Self::check`], stopping at the first that fails.
+    async fn run_check(&self, model: &str, checked_at: u64) -> ProviderCheck {
+        let mut check = ProviderCheck {
+            checked_at,
+            model: model.to_string(),
+            ..Default::default()
+        };
+        match self.model_names().await {
+            Ok(names) => {
+                check.models_ms = Some(now_millis().saturating_sub(checked_at));
+                check.model_listed = Some(names.iter().any(|n| n == model));
+            }
+            Err(e) => {
+                check.error = Some(format!("Listing models failed: {e:#}"));
+                return check;
+            }
+        }
+
+        let messages = [Message::User {
+            content: vec![ContentPart::Text {
+                text: format!(
+                    "This is a connection test. Call the {CHECK_TOOL} tool with the status \"ok\"."
+                ),
+            }],
+        }];
+        let tools = [Tool {
+            r#type: "function".to_string(),
+            function: Function {
+                name: CHECK_TOOL.to_string(),
+                description: Some("Reports that the connection works.".to_string()),
+                parameters: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": { "status": { "type": "string" } },
+                    "required": ["status"]
+                })),
+                strict: None,
+            },
+        }];
+        let native_tools = self.supports_tools(model).await;
+        let started = now_millis();
+        let mut rx = match self.stream(model, &messages, &tools).await {
+            Ok(rx) => rx,
+            Err(e) => {
+                check.error = Some(format!("Completion failed: {e:#}"));
+                return check;
+            }
+        };
+        let mut events = 0;
+        let mut called = false;
+        while let Some(event) = rx.recv().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    check.error = Some(format!("Completion broke off: {e:#}"));
+                    return check;
+                }
+            };
+            if events == 0 {
+                check.first_token_ms = Some(now_millis().saturating_sub(started));
+            }
+            events += 1;
+            called |= event
+                .choices
+                .iter()
+                .any(|c| c.delta.tool_calls.as_ref().is_some_and(|t| !t.is_empty()));
+        }
+        if events == 0 {
+            check.error = Some("The completion was empty".to_string());
+            return check;
+        }
+        check.completion_ms = Some(now_millis().saturating_sub(started));
+        check.streaming = Some(events > 1);
+        check.tool_calls = Some(native_tools && called);
+        check
+    }
+
+    /// The API URL without the `/v1` of the OpenAI-compatible API, where
+    /// Ollama's native API is served.
+    fn native_api_url(&self) -> &str {
+        let url = self.api_url.trim_end_matches('/');
+        url.strip_suffix("/v1").unwrap_or(url)
+    }
+
+    /// Makes a GET request to an API endpoint and parses the JSON response.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the endpoint, relative to the API URL
+    ///
+    /// # Returns
+    /// The parsed response, or an error if the request fails or the API
+    /// returns an error status
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let url = format!("{}/{path}", &self.api_url);
+        self.send_json(reqwest::Method::GET, &url, None).await
+    }
+
+    /// Makes a request with an optional JSON body and parses the JSON response.
+    ///
+    /// # Arguments
+    /// * `method` - HTTP method
+    /// * `url` - Full URL of the endpoint
+    /// * `body` - Request body, if any
+    ///
+    /// # Returns
+    /// The parsed response, or an error if the request fails or the API
+    /// returns an error status
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<&Value>,
+    ) -> anyhow::Result<T> {
+        let log_id = request_log::start(method.as_str(), url, body, &self.api_key);
+        let mut req = self
             .client
-            .get(format!("{}/models", &self.api_url))
+            .request(method, url)
             .bearer_auth(format!("Bearer {}", &self.api_key))
-            .header("Content-Type", "application/json")
-            .send()
-            .await?;
+            .header("Content-Type", "application/json");
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        let res = self.add_headers(req).send().await;
+        let res = match res {
+            Ok(res) => res,
+            Err(e) => {
+                request_log::finish(log_id, Some(e.to_string()));
+                return Err(e.into());
+            }
+        };
+        let status = res.status();
+        request_log::set_status(log_id, status.as_u16());
+        let body = res.text().await?;
+        request_log::append_response(log_id, &body);
 
         // Check for HTTP error status and provide detailed error information
-        if !res.status().is_success() {
-            let status = res.status();
-            let body = res.text().await?;
+        if !status.is_success() {
+            request_log::finish(log_id, Some(format!("HTTP {status}")));
             bail!("Request failed: {} - {}", status, body);
         }
+        request_log::finish(log_id, None);
 
-        Ok(res.json().await?)
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Retrieves the names of the available models and caches them.
+    ///
+    /// # Returns
+    /// Model IDs in the order the API returned them
+    pub async fn model_names(&self) -> anyhow::Result<Vec<String>> {
+        let models = self.models().await?.data;
+        let meta = models
+            .iter()
+            .map(|m| {
+                let meta = ModelMeta {
+                    input_modalities: m.architecture.as_ref().map(|a| a.input_modalities.clone()),
+                    supports_tools: m
+                        .supported_parameters
+                        .as_ref()
+                        .map(|p| p.iter().any(|p| p == "tools")),
+                    context_length: m.context_length,
+                };
+                (m.id.clone(), meta)
+            })
+            .collect();
+        let names: Vec<String> = models.into_iter().map(|m| m.id).collect();
+        if let Ok(mut cache) = MODEL_CACHE.lock() {
+            cache.insert(self.api_url.clone(), names.clone());
+        }
+        if let Ok(mut cache) = MODEL_META.lock() {
+            cache.insert(self.api_url.clone(), meta);
+        }
+        Ok(names)
+    }
+
+    /// Returns what the API's model list says about a model.
+    ///
+    /// Uses the metadata cached by [`Self::model_names`], fetching it first if
+    /// needed.
+    ///
+    /// # Returns
+    /// The metadata, or `None` if the model is not listed
+    async fn model_meta(&self, model: &str) -> Option<ModelMeta> {
+        if self.replay.is_some() {
+            return None;
+        }
+        let cached = || {
+            MODEL_META
+                .lock()
+                .ok()
+                .and_then(|c| c.get(&self.api_url).map(|m| m.get(model).cloned()))
+        };
+        if let Some(meta) = cached() {
+            return meta;
+        }
+        if let Err(e) = self.model_names().await {
+            warn!("Could not fetch model metadata: {e:?}");
+        }
+        cached().flatten()
+    }
+
+    /// Returns the kinds of input a model accepts, according to the API's model metadata.
+    ///
+    /// Uses the metadata cached by [`Self::model_names`], fetching it first if
+    /// needed.
+    ///
+    /// # Arguments
+    /// * `model` - The model ID
+    ///
+    /// # Returns
+    /// Modalities such as "text", "image" and "audio", or `None` if the API
+    /// says nothing about the model, as Ollama does
+    pub async fn input_modalities(&self, model: &str) -> Option<Vec<String>> {
+        self.model_meta(model).await?.input_modalities
+    }
+
+    /// Returns the size of a model's context window, according to the API's
+    /// model metadata.
+    ///
+    /// # Arguments
+    /// * `model` - The model ID
+    ///
+    /// # Returns
+    /// The number of tokens, or `None` if the API does not say
+    pub async fn context_length(&self, model: &str) -> Option<u64> {
+        self.model_meta(model).await?.context_length
+    }
+
+    /// Whether a model accepts tools in requests.
+    ///
+    /// A per-model override set with [`Self::with_tool_support`] wins; otherwise
+    /// the API's model metadata decides, and models it says nothing about are
+    /// assumed to accept tools.
+    ///
+    /// # Arguments
+    /// * `model` - The model ID
+    pub async fn supports_tools(&self, model: &str) -> bool {
+        if let Some(supported) = self.tool_support.get(model) {
+            return *supported;
+        }
+        self.model_meta(model)
+            .await
+            .and_then(|m| m.supports_tools)
+            .unwrap_or(true)
+    }
+
+    /// Whether a model accepts images. Models without metadata are assumed not to.
+    ///
+    /// # Arguments
+    /// * `model` - The model ID
+    pub async fn supports_images(&self, model: &str) -> bool {
+        self.input_modalities(model)
+            .await
+            .is_some_and(|m| m.iter().any(|m| m == "image"))
+    }
+
+    /// Returns the model names last fetched from an API, without making a request.
+    ///
+    /// # Arguments
+    /// * `api_url` - Base URL of the LLM API
+    ///
+    /// # Returns
+    /// The cached names, or `None` if the list has not been fetched yet
+    pub fn cached_model_names(api_url: &str) -> Option<Vec<String>> {
+        MODEL_CACHE.lock().ok()?.get(api_url).cloned()
     }
 
     /// Creates a streaming chat completion request (native platforms only).
@@ -88,77 +619,36 @@ impl LlmClient {
     ///
     /// # Returns
     /// A receiver channel that yields `StreamEvent`s as the response is generated,
-    /// or an error if the request fails.
+    /// or an error if the request fails. If the response stalls for longer than the
+    /// idle timeout or breaks off, the request is aborted and the channel yields an
+    /// error before closing.
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn stream(
         &self,
         model: &str,
         messages: &[Message],
         tools: &[Tool],
-    ) -> anyhow::Result<Receiver<StreamEvent>> {
-        // Send the streaming chat completion request
-        let res = self
-            .client
-            .post(format!("{}/chat/completions", &self.api_url))
-            .bearer_auth(&self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "model": model,
-                "stream": true,        // Enable streaming response
-                "messages": messages,
-                "tools": tools,
-                "max_tokens": 2048,    // Limit response length
-            }))
-            .send()
-            .await?;
-
-        // Check for HTTP error status
-        if !res.status().is_success() {
-            let status = res.status();
-            let body = res.text().await?;
-            bail!("Request failed: {} - {}", status, body);
+    ) -> anyhow::Result<Receiver<anyhow::Result<StreamEvent>>> {
+        // Create a channel for streaming events with a buffer of 32 items
+        let (tx, rx) = mpsc::channel::<anyhow::Result<StreamEvent>>(32);
+        if let Some(chunks) = self.next_replay()? {
+            if let Some(prefill) = self.prefill_for(messages) {
+                let _ = tx.send(Ok(StreamEvent::text(prefill))).await;
+            }
+            let body = replay_stream(chunks);
+            spawn(pump_events(body, tx, self.idle_timeout, None, None));
+            return Ok(rx);
         }
 
-        // Create a channel for streaming events with a buffer of 32 items
-        let (tx, rx) = mpsc::channel::<StreamEvent>(32);
+        let (res, log_id) = self.send_stream_request(model, messages, tools).await?;
+        if let Some(prefill) = self.prefill_for(messages) {
+            let _ = tx.send(Ok(StreamEvent::text(prefill))).await;
+        }
 
         // Spawn a task to process the streaming response
-        spawn(async move {
-            let mut stream = res.bytes_stream();
-            while let Some(item) = stream.next().await {
-                let chunk = match item {
-                    Ok(x) => x,
-                    Err(e) => {
-                        warn!("Response stream error: {e:?}");
-                        return;
-                    }
-                };
-
-                // Convert bytes to text and process line by line
-                let text = String::from_utf8_lossy(&chunk);
-                for line in text.lines() {
-                    // Skip lines that don't start with "data: " (SSE format)
-                    if !line.starts_with("data: ") {
-                        continue;
-                    }
-                    let data = &line[6..]; // Remove "data: " prefix
-
-                    // Check for stream completion marker
-                    if data == "[DONE]" {
-                        info!("\n-- Stream complete --");
-                        return;
-                    }
-
-                    // Parse and send the stream event
-                    if let Ok(event) = serde_json::from_str::<StreamEvent>(data)
-                        && let Err(e) = tx.send(event).await
-                    {
-                        warn!("Could not send response event: {e:?}");
-                        return;
-                    }
-                }
-            }
-        });
+        let capture = Capture::start(model, res.url().as_str());
+        let body = res.bytes_stream();
+        spawn(pump_events(body, tx, self.idle_timeout, log_id, capture));
 
         Ok(rx)
     }
@@ -166,8 +656,6 @@ impl LlmClient {
     /// Creates a streaming chat completion request (WASM platforms only).
     ///
     /// Similar to the native version but uses `spawn_local` for WASM compatibility.
-    /// This version omits the `max_tokens` parameter as it may not be supported
-    /// by all WASM-compatible LLM providers.
     ///
     /// # Arguments
     /// * `model` - The model ID to use for completion
@@ -176,82 +664,327 @@ impl LlmClient {
     ///
     /// # Returns
     /// A receiver channel that yields `StreamEvent`s as the response is generated,
-    /// or an error if the request fails.
+    /// or an error if the request fails. If the response stalls for longer than the
+    /// idle timeout or breaks off, the request is aborted and the channel yields an
+    /// error before closing.
     #[cfg(target_arch = "wasm32")]
     pub async fn stream(
         &self,
         model: &str,
         messages: &[Message],
         tools: &[Tool],
-    ) -> anyhow::Result<Receiver<StreamEvent>> {
+    ) -> anyhow::Result<Receiver<anyhow::Result<StreamEvent>>> {
         use wasm_bindgen_futures::spawn_local;
 
+        // Create a channel for streaming events with a buffer of 32 items
+        let (tx, rx) = mpsc::channel::<anyhow::Result<StreamEvent>>(32);
+        if let Some(chunks) = self.next_replay()? {
+            if let Some(prefill) = self.prefill_for(messages) {
+                let _ = tx.send(Ok(StreamEvent::text(prefill))).await;
+            }
+            let body = replay_stream(chunks);
+            spawn_local(pump_events(body, tx, self.idle_timeout, None, None));
+            return Ok(rx);
+        }
+
+        let (res, log_id) = self.send_stream_request(model, messages, tools).await?;
+        if let Some(prefill) = self.prefill_for(messages) {
+            let _ = tx.send(Ok(StreamEvent::text(prefill))).await;
+        }
+
+        // Spawn a local task to process the streaming response (WASM-compatible)
+        let capture = Capture::start(model, res.url().as_str());
+        let body = res.bytes_stream();
+        spawn_local(pump_events(body, tx, self.idle_timeout, log_id, capture));
+
+        Ok(rx)
+    }
+
+    /// Sends a streaming chat completion request and checks its status.
+    ///
+    /// # Returns
+    /// The response, whose body is the event stream, and its request log ID
+    async fn send_stream_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> anyhow::Result<(reqwest::Response, Option<u64>)> {
+        let url = format!("{}/chat/completions", &self.api_url);
+        let mut messages = messages.to_vec();
+        if has_audio(&messages)
+            && self
+                .input_modalities(model)
+                .await
+                .is_some_and(|m| !m.iter().any(|m| m == "audio"))
+        {
+            warn!("{model} does not accept audio; leaving audio out of the request");
+            remove_audio(&mut messages);
+        }
+        let tools = if !tools.is_empty() && !self.supports_tools(model).await {
+            info!("{model} does not accept tools; describing them in the prompt");
+            messages = tool_prompt::describe_tools(&messages, tools);
+            &[]
+        } else {
+            tools
+        };
+        if let Some(prefill) = self.prefill_for(&messages) {
+            messages.push(Message::Assistant {
+                content: Some(prefill.to_string()),
+                tool_calls: None,
+            });
+        }
+        let mut body = serde_json::json!({
+            "model": model,
+            "stream": true,        // Enable streaming response
+            "messages": messages,
+            "max_tokens": 2048,    // Limit response length
+        });
+        if !tools.is_empty() {
+            body["tools"] = serde_json::json!(tools);
+        }
+        if !self.stop.is_empty() {
+            body["stop"] = serde_json::json!(self.stop);
+        }
+        match &self.constraint {
+            Some(Constraint::Grammar(grammar)) => body["grammar"] = serde_json::json!(grammar),
+            Some(Constraint::JsonSchema(schema)) => {
+                body["response_format"] = serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "response", "strict": true, "schema": schema },
+                });
+            }
+            None => {}
+        }
+        for (key, value) in &self.extra_body {
+            if RESERVED_BODY_FIELDS.contains(&key.as_str()) {
+                warn!("Ignoring extra body field {key:?}, which is set by the client");
+            } else {
+                body[key] = value.clone();
+            }
+        }
+        let log_id = request_log::start("POST", &url, Some(&body), &self.api_key);
+
         // Send the streaming chat completion request
-        let res = self
+        let req = self
             .client
-            .post(format!("{}/chat/completions", &self.api_url))
+            .post(&url)
             .bearer_auth(&self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "model": model,
-                "stream": true,        // Enable streaming response
-                "messages": messages,
-                "tools": tools,
-                "max_tokens": 2048,
-            }))
-            .send()
-            .await?;
+            .header("Content-Type", "application/json");
+        let res = self.add_headers(req).json(&body).send().await;
+        let res = match res {
+            Ok(res) => res,
+            Err(e) => {
+                request_log::finish(log_id, Some(e.to_string()));
+                return Err(e.into());
+            }
+        };
+        request_log::set_status(log_id, res.status().as_u16());
 
         // Check for HTTP error status
         if !res.status().is_success() {
-            let status = res.status().clone();
+            let status = res.status();
             let body = res.text().await?;
+            request_log::append_response(log_id, &body);
+            request_log::finish(log_id, Some(format!("HTTP {status}")));
             bail!("Request failed: {} - {}", status, body);
         }
+        Ok((res, log_id))
+    }
 
-        // Create a channel for streaming events with a buffer of 32 items
-        let (tx, rx) = mpsc::channel::<StreamEvent>(32);
+    /// Returns the prefill to use for a request, if any.
+    ///
+    /// # Arguments
+    /// * `messages` - Conversation the request continues
+    ///
+    /// # Returns
+    /// The prefill, when one is set and the conversation ends with a user message
+    fn prefill_for(&self, messages: &[Message]) -> Option<&str> {
+        if self.prefill.is_empty() || !matches!(messages.last(), Some(Message::User { .. })) {
+            return None;
+        }
+        Some(&self.prefill)
+    }
+}
 
-        // Spawn a local task to process the streaming response (WASM-compatible)
-        spawn_local(async move {
-            let mut stream = res.bytes_stream();
-            while let Some(item) = stream.next().await {
-                let chunk = match item {
-                    Ok(x) => x,
-                    Err(e) => {
-                        warn!("Response stream error: {e:?}");
-                        return;
-                    }
-                };
+/// Whether any user message has an audio part.
+fn has_audio(messages: &[Message]) -> bool {
+    messages.iter().any(|m| match m {
+        Message::User { content } => content
+            .iter()
+            .any(|p| matches!(p, ContentPart::InputAudio { .. })),
+        _ => false,
+    })
+}
 
-                // Convert bytes to text and process line by line
-                let text = String::from_utf8_lossy(&chunk);
-                for line in text.lines() {
-                    // Skip lines that don't start with "data: " (SSE format)
-                    if !line.starts_with("data: ") {
-                        continue;
-                    }
-                    let data = &line[6..]; // Remove "data: " prefix
-
-                    // Check for stream completion marker
-                    if data == "[DONE]" {
-                        info!("\n-- Stream complete --");
-                        return;
-                    }
-
-                    // Parse and send the stream event
-                    // warn!("Stream event: {data}");
-                    if let Ok(event) = serde_json::from_str::<StreamEvent>(data) {
-                        if let Err(e) = tx.send(event).await {
-                            warn!("Could not send response event: {e:?}");
-                            return;
-                        }
-                    }
+/// Replaces audio parts with a note, for models that do not accept audio.
+fn remove_audio(messages: &mut [Message]) {
+    for m in messages {
+        if let Message::User { content } = m {
+            for part in content.iter_mut() {
+                if matches!(part, ContentPart::InputAudio { .. }) {
+                    *part = ContentPart::Text {
+                        text: "[An audio recording was left out: this model does not accept audio]"
+                            .to_string(),
+                    };
                 }
             }
-        });
+        }
+    }
+}
 
-        Ok(rx)
+/// Reads the SSE events of a streaming response and forwards them to a channel.
+///
+/// Stops at the `[DONE]` marker, at the end of the body, or when the receiver
+/// is dropped. If no data arrives for `idle_timeout`, or the body breaks off, an
+/// error is sent instead and the request is aborted by dropping the response.
+///
+/// # Arguments
+/// * `stream` - Chunks of the response body, which is the event stream
+/// * `tx` - Channel receiving the parsed events
+/// * `idle_timeout` - Longest gap allowed between chunks
+/// * `log_id` - Request log entry the raw events are recorded in
+/// * `capture` - Capture file the raw chunks are written to
+async fn pump_events<S, B, E>(
+    mut stream: S,
+    tx: mpsc::Sender<anyhow::Result<StreamEvent>>,
+    idle_timeout: Duration,
+    log_id: Option<u64>,
+    mut capture: Option<Capture>,
+) where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Display + Debug,
+{
+    let mut decoder = SseDecoder::new();
+    let error = loop {
+        let item = match next_chunk(&mut stream, idle_timeout).await {
+            Some(Some(item)) => item,
+            Some(None) => break None,
+            None => {
+                warn!("Response stream stalled for {idle_timeout:?}");
+                break Some(format!(
+                    "The provider sent nothing for {} seconds, so the response was aborted",
+                    idle_timeout.as_secs()
+                ));
+            }
+        };
+        let chunk = match item {
+            Ok(x) => x,
+            Err(e) => {
+                warn!("Response stream error: {e:?}");
+                break Some(format!("The response broke off: {e}"));
+            }
+        };
+
+        if let Some(capture) = &mut capture {
+            capture.chunk(chunk.as_ref());
+        }
+
+        let mut done = false;
+        for event in decoder.push(chunk.as_ref()) {
+            if !forward_event(&event.data, &tx, log_id).await {
+                done = true;
+                break;
+            }
+        }
+        if done {
+            break None;
+        }
+    };
+    // Providers may leave out the blank line after the last event
+    if error.is_none()
+        && let Some(event) = decoder.finish()
+    {
+        forward_event(&event.data, &tx, log_id).await;
+    }
+    if let Some(e) = &error {
+        let _ = tx.send(Err(anyhow!("{e}"))).await;
+    }
+    request_log::finish(log_id, error);
+}
+
+/// Turns a captured response into a body stream for [`pump_events`].
+fn replay_stream(
+    chunks: CapturedResponse,
+) -> impl Stream<Item = Result<Vec<u8>, Infallible>> + Unpin {
+    futures::stream::iter(chunks.into_iter().map(Ok))
+}
+
+/// Parses the data of one SSE event and sends it on.
+///
+/// Data that is not a stream event is skipped, except error objects, which
+/// some providers send mid-stream and which are forwarded as errors. Some
+/// servers omit the blank lines between events, so data spanning several lines
+/// that does not parse as a whole is parsed line by line.
+///
+/// # Returns
+/// Whether to keep reading: `false` at the `[DONE]` marker, after an error, or
+/// when the receiver is gone
+async fn forward_event(
+    data: &str,
+    tx: &mpsc::Sender<anyhow::Result<StreamEvent>>,
+    log_id: Option<u64>,
+) -> bool {
+    request_log::append_response(log_id, data);
+    let parts: Vec<&str> = if data.contains('\n') && serde_json::from_str::<Value>(data).is_err() {
+        data.lines().collect()
+    } else {
+        vec![data]
+    };
+    for data in parts {
+        // Check for stream completion marker
+        if data == "[DONE]" {
+            info!("\n-- Stream complete --");
+            return false;
+        }
+        let event = match serde_json::from_str::<StreamEvent>(data) {
+            Ok(event) => Ok(event),
+            Err(_) => match serde_json::from_str::<Value>(data) {
+                Ok(Value::Object(o)) if o.contains_key("error") => {
+                    let error = &o["error"];
+                    let message = error
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| error.to_string());
+                    Err(anyhow!("The provider reported an error: {message}"))
+                }
+                _ => continue,
+            },
+        };
+        let failed = event.is_err();
+        if let Err(e) = tx.send(event).await {
+            warn!("Could not send response event: {e:?}");
+            return false;
+        }
+        if failed {
+            return false;
+        }
+    }
+    true
+}
+
+/// Waits for the next chunk of a response body, giving up after `idle`.
+///
+/// # Returns
+/// `Some` with the next item (`None` at the end of the body), or `None` on timeout
+#[cfg(not(target_arch = "wasm32"))]
+async fn next_chunk<S: Stream + Unpin>(stream: &mut S, idle: Duration) -> Option<Option<S::Item>> {
+    tokio::time::timeout(idle, stream.next()).await.ok()
+}
+
+/// Waits for the next chunk of a response body, giving up after `idle`.
+///
+/// # Returns
+/// `Some` with the next item (`None` at the end of the body), or `None` on timeout
+#[cfg(target_arch = "wasm32")]
+async fn next_chunk<S: Stream + Unpin>(stream: &mut S, idle: Duration) -> Option<Option<S::Item>> {
+    use futures::future::{Either, select};
+    let sleep = gloo_timers::future::sleep(idle);
+    match select(stream.next(), sleep).await {
+        Either::Left((item, _)) => Some(item),
+        Either::Right(_) => None,
     }
 }
 
@@ -271,6 +1004,141 @@ pub struct ModelsResponse {
 pub struct Model {
     /// Unique identifier for the model (e.g., "gpt-4", "claude-3-sonnet")
     pub id: String,
+    /// Input and output modalities, reported by OpenRouter
+    #[serde(default)]
+    pub architecture: Option<Architecture>,
+    /// Request parameters the model accepts, e.g. "tools", reported by OpenRouter
+    #[serde(default)]
+    pub supported_parameters: Option<Vec<String>>,
+    /// Size of the model's context window in tokens, reported by OpenRouter
+    #[serde(default)]
+    pub context_length: Option<u64>,
+}
+
+/// Response of OpenRouter endpoints that wrap their result in `data`.
+#[derive(Debug, Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// An API key as reported by OpenRouter.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct KeyInfo {
+    /// Name of the key, or a redacted form of it
+    #[serde(default)]
+    pub label: String,
+    /// Most the key may spend in USD; `None` if unlimited
+    #[serde(default)]
+    pub limit: Option<f64>,
+    /// What the key has spent in USD
+    #[serde(default)]
+    pub usage: f64,
+    /// What the key may still spend in USD; `None` if unlimited
+    #[serde(default)]
+    pub limit_remaining: Option<f64>,
+    /// Whether the account has never bought credits
+    #[serde(default)]
+    pub is_free_tier: bool,
+    /// Requests allowed per interval
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// A rate limit as reported by OpenRouter.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimit {
+    /// Requests allowed per interval
+    pub requests: i64,
+    /// The interval, e.g. "10s"
+    pub interval: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadedModels {
+    #[serde(default)]
+    models: Vec<LoadedModel>,
+}
+
+/// A model loaded in memory, as reported by Ollama.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LoadedModel {
+    /// Model name, e.g. "llama3.2:latest"
+    #[serde(default)]
+    pub name: String,
+    /// Model ID, usually the same as the name
+    #[serde(default)]
+    pub model: String,
+    /// When the model will be unloaded, as an RFC 3339 timestamp
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl LoadedModel {
+    /// Whether this is the model with the name given, where a name without
+    /// a tag means the "latest" tag.
+    pub fn is(&self, model: &str) -> bool {
+        let tagged = if model.contains(':') {
+            model.to_string()
+        } else {
+            format!("{model}:latest")
+        };
+        [&self.name, &self.model]
+            .iter()
+            .any(|name| *name == model || **name == tagged)
+    }
+}
+
+/// Result of testing a provider with [`LlmClient::check`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCheck {
+    /// When the test ran, in milliseconds since the Unix epoch
+    pub checked_at: u64,
+    /// Model the completion was asked of
+    pub model: String,
+    /// How long listing the models took, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models_ms: Option<u64>,
+    /// Whether the model is among those listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_listed: Option<bool>,
+    /// Time until the first event of the completion, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_token_ms: Option<u64>,
+    /// Time until the completion ended, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_ms: Option<u64>,
+    /// Whether the completion arrived in several events rather than at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    /// Whether the model called the tool it was offered, natively rather
+    /// than from a description in the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<bool>,
+    /// Why the test failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProviderCheck {
+    /// Whether the provider answered the test.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Credits of an OpenRouter account, in USD.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Credits {
+    pub total_credits: f64,
+    pub total_usage: f64,
+}
+
+/// Modalities of a model, as reported by OpenRouter.
+#[derive(Debug, Deserialize)]
+pub struct Architecture {
+    /// Kinds of input the model accepts, e.g. "text" and "image"
+    #[serde(default)]
+    pub input_modalities: Vec<String>,
 }
 
 /// Represents a message in a conversation with an LLM.
@@ -307,7 +1175,7 @@ pub enum Message {
 
 /// Represents different types of content that can be included in a user message.
 ///
-/// Supports text content and image URLs for multimodal interactions.
+/// Supports text content, image URLs and audio for multimodal interactions.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, Hash)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ContentPart {
@@ -321,6 +1189,22 @@ pub enum ContentPart {
         /// Image URL and metadata
         image_url: ImageUrl,
     },
+    /// Audio content, such as a voice memo
+    InputAudio {
+        /// Encoded audio data
+        input_audio: InputAudio,
+    },
+}
+
+/// Audio sent inline with a user message.
+///
+/// Used for models that accept audio input.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, Hash)]
+pub struct InputAudio {
+    /// Base64-encoded audio data
+    pub data: String,
+    /// Audio format, e.g. "wav" or "mp3"
+    pub format: String,
 }
 
 /// Represents an image URL for multimodal content.
@@ -377,6 +1261,28 @@ pub struct StreamEvent {
     pub choices: Vec<Choice>,
 }
 
+impl StreamEvent {
+    /// Creates an event carrying a piece of assistant text.
+    ///
+    /// # Arguments
+    /// * `content` - The text
+    pub fn text(content: &str) -> Self {
+        Self {
+            id: String::new(),
+            object: "chat.completion.chunk".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                delta: Delta {
+                    role: Some("assistant".to_string()),
+                    content: Some(content.to_string()),
+                    tool_calls: None,
+                },
+                finish_reason: None,
+            }],
+        }
+    }
+}
+
 /// Represents a choice delta in a streaming response.
 ///
 /// Contains incremental updates to the response content and metadata
@@ -0,0 +1,199 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Tool result content other than text and images.
+//!
+//! Tool messages in the conversation are plain text, so resources and binary
+//! data returned by tools are written into the text: embedded text resources as
+//! their contents, and resource links and binary data as one-line markers the
+//! chat renders as "open" and "download" buttons. Binary data is kept in memory
+//! for downloading and does not survive a restart; only the most recent
+//! [`MAX_STORED_BLOBS`] are kept.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::mcp::ToolResultContent;
+
+/// Number of binary results kept for downloading.
+pub const MAX_STORED_BLOBS: usize = 16;
+
+/// Prefix of the marker for a resource link.
+const RESOURCE_PREFIX: &str = "[Resource ";
+/// Prefix of the marker for binary data.
+const BINARY_PREFIX: &str = "[Binary data ";
+
+/// Binary data returned by tools, oldest first
+static STORED_BLOBS: Mutex<VecDeque<(String, Vec<u8>)>> = Mutex::new(VecDeque::new());
+/// Counter used to generate blob IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One of the contents returned by `resources/read`.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    /// URI of the resource
+    pub uri: String,
+    /// MIME type of the resource, if known
+    pub mime_type: Option<String>,
+    /// Contents of a text resource
+    pub text: Option<String>,
+    /// Base64-encoded contents of a binary resource
+    pub blob: Option<String>,
+}
+
+/// Reference to non-text content, found in a tool message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentMarker {
+    /// A resource that can be read from the server that returned it
+    Resource {
+        server_id: String,
+        uri: String,
+        mime_type: Option<String>,
+    },
+    /// Binary data stored for downloading
+    Binary {
+        id: String,
+        mime_type: String,
+        size: usize,
+    },
+}
+
+/// Describes content that is neither text nor an image for the conversation.
+///
+/// # Arguments
+/// * `content` - Content returned by a tool
+/// * `server_id` - Server that returned it, which can resolve its resources
+///
+/// # Returns
+/// Text standing for the content, or `None` if it has nothing to show
+pub fn describe_content(content: &ToolResultContent, server_id: &str) -> Option<String> {
+    match content.r#type.as_str() {
+        "resource" => {
+            let resource: ResourceContents =
+                serde_json::from_value(content.resource.clone()?).ok()?;
+            match (resource.text, resource.blob) {
+                (Some(text), _) => Some(format!(
+                    "{}\n{text}",
+                    resource_marker(server_id, &resource.uri, None)
+                )),
+                (None, Some(blob)) => {
+                    let mime = resource
+                        .mime_type
+                        .unwrap_or_else(|| "application/octet-stream".into());
+                    Some(binary_marker(&mime, STANDARD.decode(blob).ok()?))
+                }
+                (None, None) => Some(resource_marker(
+                    server_id,
+                    &resource.uri,
+                    resource.mime_type.as_deref(),
+                )),
+            }
+        }
+        "resource_link" => {
+            let uri = content.uri.as_deref()?;
+            Some(resource_marker(
+                server_id,
+                uri,
+                content.mime_type.as_deref(),
+            ))
+        }
+        _ => {
+            let bytes = STANDARD.decode(content.data.as_deref()?).ok()?;
+            let mime = content
+                .mime_type
+                .clone()
+                .unwrap_or_else(|| "application/octet-stream".into());
+            Some(binary_marker(&mime, bytes))
+        }
+    }
+}
+
+/// Formats the marker for a resource link.
+fn resource_marker(server_id: &str, uri: &str, mime_type: Option<&str>) -> String {
+    match mime_type {
+        Some(mime) => format!("{RESOURCE_PREFIX}{server_id} {uri} ({mime})]"),
+        None => format!("{RESOURCE_PREFIX}{server_id} {uri}]"),
+    }
+}
+
+/// Stores binary data and formats its marker.
+fn binary_marker(mime_type: &str, bytes: Vec<u8>) -> String {
+    let id = format!("b{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let size = bytes.len();
+    if let Ok(mut blobs) = STORED_BLOBS.lock() {
+        if blobs.len() >= MAX_STORED_BLOBS {
+            blobs.pop_front();
+        }
+        blobs.push_back((id.clone(), bytes));
+    }
+    format!("{BINARY_PREFIX}{id} ({mime_type}, {size} bytes)]")
+}
+
+/// Finds the markers of resources and binary data in a tool message.
+///
+/// # Arguments
+/// * `text` - Content of a tool message
+///
+/// # Returns
+/// The markers, in order
+pub fn find_markers(text: &str) -> Vec<ContentMarker> {
+    text.lines().filter_map(parse_marker).collect()
+}
+
+/// Parses a line written by [`resource_marker`] or [`binary_marker`].
+fn parse_marker(line: &str) -> Option<ContentMarker> {
+    if let Some(rest) = line.strip_prefix(RESOURCE_PREFIX) {
+        let rest = rest.strip_suffix(']')?;
+        let (server_id, rest) = rest.split_once(' ')?;
+        let (uri, mime_type) = match rest.split_once(" (") {
+            Some((uri, mime)) => (uri, mime.strip_suffix(')').map(str::to_string)),
+            None => (rest, None),
+        };
+        return Some(ContentMarker::Resource {
+            server_id: server_id.to_string(),
+            uri: uri.to_string(),
+            mime_type,
+        });
+    }
+    let rest = line.strip_prefix(BINARY_PREFIX)?.strip_suffix(" bytes)]")?;
+    let (id, rest) = rest.split_once(" (")?;
+    let (mime_type, size) = rest.rsplit_once(", ")?;
+    Some(ContentMarker::Binary {
+        id: id.to_string(),
+        mime_type: mime_type.to_string(),
+        size: size.parse().ok()?,
+    })
+}
+
+/// Returns stored binary data.
+///
+/// # Arguments
+/// * `id` - ID from the marker
+///
+/// # Returns
+/// The data, or `None` if it has expired
+pub fn stored_blob(id: &str) -> Option<Vec<u8>> {
+    let blobs = STORED_BLOBS.lock().ok()?;
+    blobs
+        .iter()
+        .find(|(i, _)| i == id)
+        .map(|(_, bytes)| bytes.clone())
+}
+
+/// Parses the result of a `resources/read` call.
+pub fn parse_resource_contents(result: Value) -> anyhow::Result<Vec<ResourceContents>> {
+    let contents = result
+        .get("contents")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("The server returned no resource contents"))?;
+    Ok(serde_json::from_value(contents)?)
+}
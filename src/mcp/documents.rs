@@ -0,0 +1,128 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Text extraction from fetched documents, for the builtin `fetch_document` tool.
+//!
+//! The kind of document is detected from the `Content-Type` header, the URL's
+//! extension and the first bytes of the body. PDFs are extracted with
+//! `pdf-extract` (native platforms only), DOCX files by reading the paragraphs of
+//! `word/document.xml`, HTML is converted to Markdown and anything else that is
+//! valid UTF-8 is returned as is.
+
+use std::io::Read as _;
+
+use anyhow::{Context as _, bail};
+
+/// Kinds of documents `fetch_document` can read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Html,
+    Text,
+}
+
+impl DocumentKind {
+    /// Detects the kind of a fetched document.
+    ///
+    /// # Arguments
+    /// * `content_type` - Value of the `Content-Type` header, if any
+    /// * `url` - URL the document was fetched from
+    /// * `bytes` - The document
+    pub fn detect(content_type: Option<&str>, url: &str, bytes: &[u8]) -> Self {
+        let content_type = content_type.unwrap_or_default().to_lowercase();
+        let path = url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if bytes.starts_with(b"%PDF") || content_type.contains("application/pdf") {
+            DocumentKind::Pdf
+        } else if content_type.contains("wordprocessingml")
+            || (bytes.starts_with(b"PK") && path.ends_with(".docx"))
+        {
+            DocumentKind::Docx
+        } else if content_type.contains("html") || looks_like_html(bytes) {
+            DocumentKind::Html
+        } else {
+            DocumentKind::Text
+        }
+    }
+}
+
+/// Whether a body without a useful content type starts like an HTML page.
+fn looks_like_html(bytes: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+    let start = start.trim_start();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Extracts the text of a PDF.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pdf_to_text(bytes: &[u8]) -> anyhow::Result<String> {
+    let text = pdf_extract::extract_text_from_mem(bytes).context("Could not read the PDF")?;
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("The PDF has no extractable text; it may be a scan");
+    }
+    Ok(text.to_string())
+}
+
+/// Extracts the text of a PDF (not available on WASM).
+#[cfg(target_arch = "wasm32")]
+pub fn pdf_to_text(_bytes: &[u8]) -> anyhow::Result<String> {
+    bail!("Reading PDFs is only supported in the desktop app")
+}
+
+/// Extracts the text of a DOCX file, one line per paragraph.
+pub fn docx_to_text(bytes: &[u8]) -> anyhow::Result<String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .context("Could not open the DOCX file")?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .context("The DOCX file has no document body")?
+        .read_to_string(&mut xml)?;
+    Ok(document_xml_to_text(&xml))
+}
+
+/// Collects the text runs of a WordprocessingML body.
+///
+/// Text is taken from `<w:t>` elements; paragraph ends, breaks and tabs become
+/// line breaks and tabs. Everything else, such as formatting, is skipped.
+fn document_xml_to_text(xml: &str) -> String {
+    let mut text = String::new();
+    let mut rest = xml;
+    let mut in_text = false;
+    while let Some(start) = rest.find('<') {
+        if in_text {
+            text.push_str(&unescape_xml(&rest[..start]));
+        }
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        let name = tag
+            .trim_start_matches('/')
+            .split([' ', '/'])
+            .next()
+            .unwrap_or_default();
+        match name {
+            "w:t" => in_text = !tag.starts_with('/') && !tag.ends_with('/'),
+            "w:p" if tag.starts_with('/') => text.push('\n'),
+            "w:br" | "w:cr" => text.push('\n'),
+            "w:tab" => text.push('\t'),
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.trim().to_string()
+}
+
+/// Replaces the predefined XML entities.
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
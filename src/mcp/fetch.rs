@@ -6,20 +6,27 @@ use anyhow::{anyhow, bail};
 use html2md::{TagHandler, TagHandlerFactory, parse_html_custom};
 use serde_json::{Value, json};
 
-use crate::mcp::{McpTool, ToolResult, ToolResultContent, host::MCPServer};
+use crate::mcp::{
+    McpTool, ToolResult, ToolResultContent,
+    documents::{self, DocumentKind},
+    host::MCPServer,
+    results,
+};
 
 /// Built-in MCP server that provides web fetching functionality.
 ///
 /// This server is always available and provides a "fetch" tool that can
 /// retrieve content from URLs. It's implemented as a built-in server to
 /// provide basic web access without requiring external MCP server setup.
+/// It also serves the `read_more` tool for paging through truncated tool
+/// results (see [`crate::mcp::results`]).
 pub struct FetchMcpServer {}
 
 #[async_trait::async_trait]
 impl MCPServer for FetchMcpServer {
     /// Returns the fetch tool definition.
     ///
-    /// Provides a single "fetch" tool that can retrieve content from URLs.
+    /// Provides tools that retrieve web pages and documents from URLs.
     async fn list_tools(&self) -> Vec<McpTool> {
         vec![
             McpTool {
@@ -35,6 +42,7 @@ impl MCPServer for FetchMcpServer {
                     },
                     "required": ["url"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "fetch".into(),
@@ -49,14 +57,36 @@ impl MCPServer for FetchMcpServer {
                     },
                     "required": ["url"]
                 }),
+                annotations: None,
             },
+            McpTool {
+                name: "fetch_document".into(),
+                description: Some(
+                    "Fetch a document from a URL and return its text. Reads PDFs, Word (DOCX) \
+                     files and web pages, so use it for papers and other linked files."
+                        .into(),
+                ),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "url": {
+                            "type": "string",
+                            "description": "The URL of the document"
+                        }
+                    },
+                    "required": ["url"]
+                }),
+                annotations: None,
+            },
+            results::read_more_tool(),
         ]
     }
 
     /// Handles RPC calls for the fetch server.
     ///
-    /// Currently only supports the "tools/call" method with the "fetch" tool.
-    /// The fetch tool retrieves content from the specified URL and returns it as text.
+    /// Currently only supports the "tools/call" method. The fetch tools retrieve
+    /// content from the specified URL and return it as text; `fetch_document`
+    /// also extracts the text of PDF and DOCX files.
     async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
         // Only support tool calls for this built-in server
         if method != "tools/call" {
@@ -66,6 +96,45 @@ impl MCPServer for FetchMcpServer {
         // Extract the tool name from parameters
         let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
 
+        if name == "read_more" {
+            let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+            let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let length = args.get("length").and_then(|v| v.as_u64()).unwrap_or(8_000) as usize;
+            let (text, is_error) = match results::read_more(id, offset, length) {
+                Ok(text) => (text, None),
+                Err(e) => (e.to_string(), Some(true)),
+            };
+            return Ok(serde_json::to_value(ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".into(),
+                    text: Some(text),
+                    ..Default::default()
+                }],
+                is_error,
+            })?);
+        }
+
+        if name == "fetch_document" {
+            let url = params
+                .get("arguments")
+                .and_then(|a| a.get("url"))
+                .and_then(|u| u.as_str())
+                .unwrap_or_default();
+            let (text, is_error) = match fetch_document(url).await {
+                Ok(text) => (text, None),
+                Err(e) => (format!("Fetch error: {e:#}"), Some(true)),
+            };
+            return Ok(serde_json::to_value(ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".into(),
+                    text: Some(text),
+                    ..Default::default()
+                }],
+                is_error,
+            })?);
+        }
+
         // Only support the "fetch" tool
         if name != "fetch" && name != "fetch_raw_html" {
             bail!("Unknown tool: {name}")
@@ -85,15 +154,7 @@ impl MCPServer for FetchMcpServer {
             };
 
             let text = if name == "fetch" {
-                let mut handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::new();
-                handlers.insert("style".to_string(), Box::new(CustomFactory));
-                handlers.insert("script".to_string(), Box::new(CustomFactory));
-                handlers.insert("link".to_string(), Box::new(CustomFactory));
-                handlers.insert("a".to_string(), Box::new(CustomFactory));
-                handlers.insert("img".to_string(), Box::new(CustomFactory));
-                handlers.insert("noscript".to_string(), Box::new(CustomFactory));
-
-                parse_html_custom(&text, &handlers)
+                html_to_markdown(&text)
             } else {
                 text
             };
@@ -103,9 +164,7 @@ impl MCPServer for FetchMcpServer {
                 content: vec![ToolResultContent {
                     r#type: "text".into(),
                     text: Some(text),
-                    mime_type: None,
-                    data: None,
-                    resource: None,
+                    ..Default::default()
                 }],
                 is_error: None,
             })?);
@@ -115,6 +174,91 @@ impl MCPServer for FetchMcpServer {
     }
 }
 
+/// Converts HTML to Markdown, leaving out scripts, styles, links and images.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::new();
+    handlers.insert("style".to_string(), Box::new(CustomFactory));
+    handlers.insert("script".to_string(), Box::new(CustomFactory));
+    handlers.insert("link".to_string(), Box::new(CustomFactory));
+    handlers.insert("a".to_string(), Box::new(CustomFactory));
+    handlers.insert("img".to_string(), Box::new(CustomFactory));
+    handlers.insert("noscript".to_string(), Box::new(CustomFactory));
+
+    parse_html_custom(html, &handlers)
+}
+
+/// Fetches a document and extracts its text according to its kind.
+///
+/// # Arguments
+/// * `url` - The URL of the document
+///
+/// # Returns
+/// The document's text, or an error if it could not be fetched or read
+async fn fetch_document(url: &str) -> anyhow::Result<String> {
+    if url.is_empty() {
+        bail!("No URL given");
+    }
+    let (content_type, bytes) = _fetch_bytes(url.to_string()).await?;
+    match DocumentKind::detect(content_type.as_deref(), url, &bytes) {
+        DocumentKind::Pdf => documents::pdf_to_text(&bytes),
+        DocumentKind::Docx => documents::docx_to_text(&bytes),
+        DocumentKind::Html => Ok(html_to_markdown(&String::from_utf8_lossy(&bytes))),
+        DocumentKind::Text => String::from_utf8(bytes).map_err(|_| {
+            anyhow!(
+                "Unsupported binary document ({})",
+                content_type.as_deref().unwrap_or("unknown type")
+            )
+        }),
+    }
+}
+
+/// Fetches the raw bytes of a URL (WASM version), through the same CORS proxy as [`_fetch`].
+///
+/// # Returns
+/// The `Content-Type` of the response, if known, and its body
+#[cfg(target_arch = "wasm32")]
+async fn _fetch_bytes(url: String) -> anyhow::Result<(Option<String>, Vec<u8>)> {
+    use gloo_net::http::Request;
+    use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel::<anyhow::Result<(Option<String>, Vec<u8>)>>();
+    wasm_bindgen_futures::spawn_local(async move {
+        let encoded = utf8_percent_encode(&url, NON_ALPHANUMERIC).to_string();
+        let _url = format!("https://api.allorigins.win/raw?url={encoded}");
+        let result = async {
+            let res = Request::get(&_url).send().await?;
+            if !res.ok() {
+                bail!("HTTP {}", res.status());
+            }
+            let content_type = res.headers().get("content-type");
+            Ok((content_type, res.binary().await?))
+        }
+        .await;
+        let _ = tx.send(result);
+    });
+    rx.await
+        .unwrap_or_else(|_| Err(anyhow!("Error fetching data during tool call!")))
+}
+
+/// Fetches the raw bytes of a URL (native version).
+///
+/// # Returns
+/// The `Content-Type` of the response, if known, and its body
+#[cfg(not(target_arch = "wasm32"))]
+async fn _fetch_bytes(url: String) -> anyhow::Result<(Option<String>, Vec<u8>)> {
+    let res = reqwest::Client::new().get(&url).send().await?;
+    if !res.status().is_success() {
+        bail!("HTTP {}", res.status());
+    }
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    Ok((content_type, res.bytes().await?.to_vec()))
+}
+
 /// Fetches content from a URL (WASM version).
 ///
 /// Uses a CORS proxy service to bypass browser CORS restrictions when running
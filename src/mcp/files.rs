@@ -0,0 +1,351 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin server reading and editing the files of a coding chat's project
+//! (native only).
+//!
+//! Files are never written straight from a tool call: each edit becomes a
+//! [`Patch`] the user reviews in the chat, accepting or rejecting each hunk.
+//! Accepted hunks are written at once, through a temporary file, and the edit
+//! is added to the chat's [`EditHistory`] so it can be undone.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, bail};
+use serde_json::{Value, json};
+
+use crate::mcp::{
+    McpTool, ToolAnnotations, ToolResult, ToolResultContent,
+    host::MCPServer,
+    patch::{self, Patch},
+};
+
+/// ID of the files server in coding chats.
+pub const FILES_SERVER: &str = "files";
+
+/// Lines returned by `read_file` when the model does not ask for a range.
+const MAX_READ_LINES: usize = 2_000;
+
+/// An edit written to a file.
+#[derive(Debug, Clone)]
+pub struct AppliedEdit {
+    /// Path of the file, relative to the project
+    pub path: String,
+    /// The file
+    pub file: PathBuf,
+    /// Text before the edit; `None` if the edit created the file
+    pub before: Option<String>,
+    /// Text written by the edit
+    pub after: String,
+}
+
+/// Edits applied in a chat, oldest first.
+pub type EditHistory = Arc<Mutex<Vec<AppliedEdit>>>;
+
+/// Builtin MCP server for the files of a project directory.
+pub struct FilesMcpServer {
+    /// The project directory
+    root: PathBuf,
+    history: EditHistory,
+    /// Review session the chat's patches are shown in
+    session: u64,
+}
+
+impl FilesMcpServer {
+    /// Creates a server for a project directory.
+    ///
+    /// # Arguments
+    /// * `root` - The project directory
+    /// * `history` - Where applied edits are recorded
+    /// * `session` - Review session of the chat, from [`patch::new_session`]
+    pub fn new(root: impl Into<PathBuf>, history: EditHistory, session: u64) -> Self {
+        Self {
+            root: root.into(),
+            history,
+            session,
+        }
+    }
+
+    /// Resolves a path given by the model, which must be inside the project.
+    fn resolve(&self, path: &str) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(path);
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!("{path} must be a path inside the project, relative to its root");
+        }
+        let root = self.root.canonicalize().with_context(|| {
+            format!(
+                "The project directory {} is not available",
+                self.root.display()
+            )
+        })?;
+        let file = root.join(relative);
+        // The file, or the nearest of its directories that exists, may still be
+        // a link pointing out of the project; a new file would be created there
+        let existing = file
+            .ancestors()
+            .find(|a| a.symlink_metadata().is_ok())
+            .unwrap_or(&root);
+        let target = existing
+            .canonicalize()
+            .with_context(|| format!("{path} leads to a link to nowhere"))?;
+        if !target.starts_with(&root) {
+            bail!("{path} is outside the project");
+        }
+        Ok(file)
+    }
+
+    fn read_file(&self, args: &Value) -> anyhow::Result<String> {
+        let path = required_str(args, "path")?;
+        let text = fs::read_to_string(self.resolve(path)?)
+            .with_context(|| format!("Cannot read {path}"))?;
+        let start = args
+            .get("start_line")
+            .and_then(|v| v.as_u64())
+            .map_or(1, |n| n.max(1) as usize);
+        let end = args.get("end_line").and_then(|v| v.as_u64());
+        let total = text.lines().count();
+        let end = end
+            .map_or(start + MAX_READ_LINES - 1, |n| n as usize)
+            .min(total);
+        let mut out = String::new();
+        for (i, line) in text.lines().enumerate().take(end).skip(start - 1) {
+            out.push_str(&format!("{:>5}  {line}\n", i + 1));
+        }
+        if end < total {
+            out.push_str(&format!(
+                "[lines {start}-{end} of {total}; read more with start_line]\n"
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Proposes an edit, waits for the user's review and applies the
+    /// accepted hunks.
+    async fn propose(&self, path: &str, summary: &str, after: String) -> anyhow::Result<String> {
+        let file = self.resolve(path)?;
+        let before = match fs::read_to_string(&file) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Cannot read {path}")),
+        };
+        if before.as_deref() == Some(after.as_str()) {
+            return Ok(format!(
+                "{path} already has this content; nothing to change"
+            ));
+        }
+        let patch = Patch::new(path, summary, before.clone(), after);
+        let hunks = patch.hunks.len();
+        let Some(accepted) = patch::review(self.session, patch.clone()).await else {
+            return Ok(format!(
+                "The user rejected the edit of {path}; it was not applied"
+            ));
+        };
+        let applied = accepted.iter().filter(|a| **a).count();
+        if applied == 0 {
+            return Ok(format!(
+                "The user rejected all hunks of the edit of {path}; nothing was applied"
+            ));
+        }
+
+        // The file may have changed while the user was reviewing
+        let current = fs::read_to_string(&file).ok();
+        if current != before {
+            bail!(
+                "{path} changed while the edit was being reviewed; read it again and propose the edit anew"
+            );
+        }
+        let text = patch.apply(&accepted);
+        write_atomically(&file, &text)?;
+        if let Ok(mut history) = self.history.lock() {
+            history.push(AppliedEdit {
+                path: path.to_string(),
+                file,
+                before,
+                after: text,
+            });
+        }
+        if applied == hunks {
+            Ok(format!("The user accepted the edit; {path} was updated"))
+        } else {
+            let rejected: Vec<String> = accepted
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| !**a)
+                .map(|(i, _)| (i + 1).to_string())
+                .collect();
+            Ok(format!(
+                "The user accepted {applied} of {hunks} hunks; {path} was updated without hunks {}. Read the file before editing it again.",
+                rejected.join(", ")
+            ))
+        }
+    }
+}
+
+/// Undoes the last edit applied in a chat, unless the file changed since.
+///
+/// # Arguments
+/// * `history` - Edits applied in the chat
+///
+/// # Returns
+/// What was undone
+pub fn undo_last(history: &EditHistory) -> anyhow::Result<String> {
+    let mut history = history
+        .lock()
+        .map_err(|_| anyhow::anyhow!("The edit history is unavailable"))?;
+    let Some(edit) = history.last() else {
+        bail!("There is no edit to undo");
+    };
+    let current = fs::read_to_string(&edit.file).ok();
+    if current.as_deref() != Some(edit.after.as_str()) {
+        bail!(
+            "{} changed after the last edit, so it cannot be undone",
+            edit.path
+        );
+    }
+    match &edit.before {
+        Some(before) => write_atomically(&edit.file, before)?,
+        None => {
+            fs::remove_file(&edit.file).with_context(|| format!("Cannot remove {}", edit.path))?
+        }
+    }
+    let edit = history.pop().expect("checked above");
+    Ok(match edit.before {
+        Some(_) => format!("Undid the last edit of `{}`", edit.path),
+        None => format!("Removed `{}`, which the last edit created", edit.path),
+    })
+}
+
+/// Writes a file through a temporary file in the same directory, so it is
+/// never left half written.
+fn write_atomically(file: &Path, text: &str) -> anyhow::Result<()> {
+    let dir = file.parent().context("The file has no directory")?;
+    fs::create_dir_all(dir)?;
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let temp = dir.join(format!(".{name}.mcmcpcp-edit"));
+    fs::write(&temp, text).with_context(|| format!("Cannot write {}", temp.display()))?;
+    if let Ok(metadata) = fs::metadata(file) {
+        let _ = fs::set_permissions(&temp, metadata.permissions());
+    }
+    fs::rename(&temp, file).with_context(|| format!("Cannot replace {}", file.display()))?;
+    Ok(())
+}
+
+/// Reads a required string argument.
+fn required_str<'a>(args: &'a Value, name: &str) -> anyhow::Result<&'a str> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .with_context(|| format!("Missing required argument '{name}'"))
+}
+
+#[async_trait::async_trait]
+impl MCPServer for FilesMcpServer {
+    async fn list_tools(&self) -> Vec<McpTool> {
+        vec![
+            McpTool {
+                name: "read_file".into(),
+                description: Some("Read a file of the project, with line numbers. Long files are returned in parts; use start_line and end_line to read a range.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path of the file, relative to the project root"},
+                        "start_line": {"type": "number", "description": "First line to read (default 1)"},
+                        "end_line": {"type": "number", "description": "Last line to read"}
+                    },
+                    "required": ["path"]
+                }),
+                annotations: Some(ToolAnnotations {
+                    read_only_hint: Some(true),
+                    ..Default::default()
+                }),
+            },
+            McpTool {
+                name: "edit_file".into(),
+                description: Some("Propose replacing a passage of a file. `old_text` must appear exactly once in the file; include enough surrounding lines to make it unique. The user reviews the change before it is applied and may accept only some of its hunks.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path of the file, relative to the project root"},
+                        "old_text": {"type": "string", "description": "The exact text to replace"},
+                        "new_text": {"type": "string", "description": "The text to put in its place"},
+                        "summary": {"type": "string", "description": "One line on why, shown to the user"}
+                    },
+                    "required": ["path", "old_text", "new_text", "summary"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "write_file".into(),
+                description: Some("Propose creating a file, or replacing all of its content. Prefer edit_file for changes to existing files. The user reviews the change before it is applied.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path of the file, relative to the project root"},
+                        "content": {"type": "string", "description": "The whole content of the file"},
+                        "summary": {"type": "string", "description": "One line on why, shown to the user"}
+                    },
+                    "required": ["path", "content", "summary"]
+                }),
+                annotations: None,
+            },
+        ]
+    }
+
+    /// Handles tool calls; edits wait for the user's review.
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let result = match name {
+            "read_file" => self.read_file(&args),
+            "edit_file" => async {
+                let path = required_str(&args, "path")?;
+                let old_text = required_str(&args, "old_text")?;
+                let new_text = args.get("new_text").and_then(|v| v.as_str()).unwrap_or("");
+                let summary = args.get("summary").and_then(|v| v.as_str()).unwrap_or("");
+                let text = fs::read_to_string(self.resolve(path)?)
+                    .with_context(|| format!("Cannot read {path}"))?;
+                match text.matches(old_text).count() {
+                    0 => bail!("old_text was not found in {path}; read the file and copy the passage exactly"),
+                    1 => {}
+                    n => bail!("old_text appears {n} times in {path}; include more surrounding lines"),
+                }
+                self.propose(path, summary, text.replacen(old_text, new_text, 1))
+                    .await
+            }
+            .await,
+            "write_file" => async {
+                let path = required_str(&args, "path")?;
+                let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                let summary = args.get("summary").and_then(|v| v.as_str()).unwrap_or("");
+                self.propose(path, summary, content.to_string()).await
+            }
+            .await,
+            _ => bail!("Unknown tool: {name}"),
+        };
+
+        let (text, is_error) = match result {
+            Ok(text) => (text, None),
+            Err(e) => (format!("{e:#}"), Some(true)),
+        };
+        Ok(serde_json::to_value(ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".into(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error,
+        })?)
+    }
+}
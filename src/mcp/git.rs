@@ -0,0 +1,489 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin server giving coding chats access to the git repository of their
+//! project (native only).
+//!
+//! Reading tools (status, diff, log, files at a revision) run freely. Creating
+//! branches and committing change the repository, so the server is not trusted
+//! and the host asks the user before each such call, showing the branch, the
+//! commit message and the files committed (see [`crate::mcp::permissions`]).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use git2::{BranchType, DiffFormat, DiffOptions, Repository, Sort, StatusOptions};
+use serde_json::{Value, json};
+
+use crate::mcp::{McpTool, ToolAnnotations, ToolResult, ToolResultContent, host::MCPServer};
+
+/// ID of the git server in coding chats.
+pub const GIT_SERVER: &str = "git";
+
+/// Commits listed by `log` when the model does not say how many.
+const DEFAULT_LOG_COUNT: usize = 20;
+
+/// Builtin MCP server for the git repository containing a project directory.
+pub struct GitMcpServer {
+    /// Directory the repository is discovered from
+    root: PathBuf,
+}
+
+impl GitMcpServer {
+    /// Creates a server for the repository containing a directory.
+    ///
+    /// # Arguments
+    /// * `root` - The project directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+/// Name of the branch checked out in the repository containing a directory.
+///
+/// # Returns
+/// The branch name, or `None` if the directory is not in a repository or the
+/// head is detached
+pub fn current_branch(root: &Path) -> Option<String> {
+    let repo = Repository::discover(root).ok()?;
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    head.shorthand().map(|s| s.to_string())
+}
+
+#[async_trait::async_trait]
+impl MCPServer for GitMcpServer {
+    async fn list_tools(&self) -> Vec<McpTool> {
+        let read_only = Some(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        });
+        vec![
+            McpTool {
+                name: "status".into(),
+                description: Some("List changed, staged and untracked files of the project's git repository, and the current branch.".into()),
+                input_schema: json!({ "type": "object", "properties": {} }),
+                annotations: read_only.clone(),
+            },
+            McpTool {
+                name: "diff".into(),
+                description: Some("Show a unified diff of uncommitted changes. By default shows changes not yet staged; set `staged` for changes staged for the next commit.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Limit the diff to this file or directory (optional)"},
+                        "staged": {"type": "boolean", "description": "Show staged changes instead of unstaged ones"}
+                    }
+                }),
+                annotations: read_only.clone(),
+            },
+            McpTool {
+                name: "log".into(),
+                description: Some("List recent commits on the current branch, newest first.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "max_count": {"type": "number", "description": "Number of commits to list (default 20)"},
+                        "rev": {"type": "string", "description": "Revision to start from (default HEAD)"}
+                    }
+                }),
+                annotations: read_only.clone(),
+            },
+            McpTool {
+                name: "show_file_at_rev".into(),
+                description: Some("Show the contents of a file as it was at a revision, e.g. HEAD~1 or a commit id.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path of the file, relative to the repository root"},
+                        "rev": {"type": "string", "description": "The revision"}
+                    },
+                    "required": ["path", "rev"]
+                }),
+                annotations: read_only,
+            },
+            McpTool {
+                name: "create_branch".into(),
+                description: Some("Create a branch at the current commit, optionally switching to it. Needs the user's approval.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Name of the new branch"},
+                        "checkout": {"type": "boolean", "description": "Switch to the branch after creating it"}
+                    },
+                    "required": ["name"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "commit".into(),
+                description: Some("Stage files and commit them. Without `paths`, commits what is already staged. Needs the user's approval.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "message": {"type": "string", "description": "The commit message"},
+                        "paths": {"type": "array", "items": {"type": "string"}, "description": "Files to stage before committing, relative to the repository root"}
+                    },
+                    "required": ["message"]
+                }),
+                annotations: None,
+            },
+        ]
+    }
+
+    /// Handles tool calls on a blocking thread, since git2 is synchronous.
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let root = self.root.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let repo = Repository::discover(&root)
+                .with_context(|| format!("{} is not in a git repository", root.display()))?;
+            match name.as_str() {
+                "status" => status(&repo),
+                "diff" => diff(&repo, &args),
+                "log" => log(&repo, &args),
+                "show_file_at_rev" => show_file_at_rev(&repo, &args),
+                "create_branch" => create_branch(&repo, &args),
+                "commit" => commit(&repo, &args),
+                _ => bail!("Unknown tool: {name}"),
+            }
+        })
+        .await?;
+
+        let (text, is_error) = match result {
+            Ok(text) => (text, None),
+            Err(e) => (format!("{e:#}"), Some(true)),
+        };
+        Ok(serde_json::to_value(ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".into(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error,
+        })?)
+    }
+
+    fn is_trusted(&self) -> bool {
+        false
+    }
+
+    /// Creating branches and committing ask every time, showing what would
+    /// change.
+    fn approval_details(&self, tool_name: &str, arguments: &Value) -> Option<String> {
+        let branch = current_branch(&self.root).unwrap_or_else(|| "(detached HEAD)".into());
+        let text = |name: &str| arguments.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        match tool_name {
+            "create_branch" => {
+                let checkout = arguments
+                    .get("checkout")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                Some(format!(
+                    "Create branch {} from {branch}{}",
+                    text("name"),
+                    if checkout { " and switch to it" } else { "" }
+                ))
+            }
+            "commit" => {
+                let mut details = format!("Commit on {branch}:\n\n{}\n\nFiles:", text("message"));
+                let paths = staged_paths(&self.root, arguments);
+                if paths.is_empty() {
+                    details.push_str(" none staged");
+                }
+                for path in paths {
+                    details.push_str(&format!("\n- {path}"));
+                }
+                Some(details)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Paths a commit would include: those already staged and those the call
+/// stages.
+///
+/// # Arguments
+/// * `root` - The project directory
+/// * `args` - Arguments of the `commit` call
+fn staged_paths(root: &Path, args: &Value) -> Vec<String> {
+    let mut paths: Vec<String> = args
+        .get("paths")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str().map(|p| p.to_string()))
+        .collect();
+    if let Ok(repo) = Repository::discover(root)
+        && let Ok(statuses) = repo.statuses(None)
+    {
+        for entry in statuses.iter() {
+            let s = entry.status();
+            let staged = s.is_index_new()
+                || s.is_index_modified()
+                || s.is_index_deleted()
+                || s.is_index_renamed();
+            if staged && let Some(path) = entry.path() {
+                paths.push(path.to_string());
+            }
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Reads a required string argument.
+fn required_str<'a>(args: &'a Value, name: &str) -> anyhow::Result<&'a str> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .with_context(|| format!("Missing required argument '{name}'"))
+}
+
+/// Lists changed files with two-letter codes like `git status --short`.
+fn status(repo: &Repository) -> anyhow::Result<String> {
+    let branch = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().unwrap_or("?").to_string(),
+        Ok(_) => "(detached HEAD)".to_string(),
+        Err(_) => "(no commits yet)".to_string(),
+    };
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+
+    let mut out = format!("On branch {branch}\n");
+    if statuses.is_empty() {
+        out.push_str("Nothing to commit, working tree clean\n");
+        return Ok(out);
+    }
+    for entry in statuses.iter() {
+        let s = entry.status();
+        if s.is_ignored() {
+            continue;
+        }
+        let code = if s.is_wt_new() {
+            "??".to_string()
+        } else {
+            let index = match () {
+                _ if s.is_index_new() => 'A',
+                _ if s.is_index_modified() => 'M',
+                _ if s.is_index_deleted() => 'D',
+                _ if s.is_index_renamed() => 'R',
+                _ => ' ',
+            };
+            let worktree = match () {
+                _ if s.is_wt_modified() => 'M',
+                _ if s.is_wt_deleted() => 'D',
+                _ if s.is_wt_renamed() => 'R',
+                _ => ' ',
+            };
+            format!("{index}{worktree}")
+        };
+        out.push_str(&format!("{code} {}\n", entry.path().unwrap_or("?")));
+    }
+    Ok(out)
+}
+
+/// Shows unstaged or staged changes as a unified diff.
+fn diff(repo: &Repository, args: &Value) -> anyhow::Result<String> {
+    let mut options = DiffOptions::new();
+    if let Some(path) = args.get("path").and_then(|v| v.as_str()) {
+        options.pathspec(path);
+    }
+    let staged = args
+        .get("staged")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let diff = if staged {
+        let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        repo.diff_tree_to_index(head.as_ref(), None, Some(&mut options))?
+    } else {
+        repo.diff_index_to_workdir(None, Some(&mut options))?
+    };
+
+    let mut out = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            out.push(line.origin());
+        }
+        out.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    if out.is_empty() {
+        let which = if staged { "staged" } else { "unstaged" };
+        return Ok(format!("No {which} changes"));
+    }
+    Ok(out)
+}
+
+/// Lists commits, one per line, newest first.
+fn log(repo: &Repository, args: &Value) -> anyhow::Result<String> {
+    let count = args
+        .get("max_count")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_LOG_COUNT);
+    let mut walk = repo.revwalk()?;
+    match args.get("rev").and_then(|v| v.as_str()) {
+        Some(rev) => walk.push(repo.revparse_single(rev)?.peel_to_commit()?.id())?,
+        None => walk.push_head()?,
+    }
+    walk.set_sorting(Sort::TIME)?;
+
+    let mut out = String::new();
+    for oid in walk.take(count) {
+        let commit = repo.find_commit(oid?)?;
+        let date = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+            .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let id = commit.id().to_string();
+        out.push_str(&format!(
+            "{} {date} {}: {}\n",
+            &id[..8],
+            commit.author().name().unwrap_or("?"),
+            commit.summary().unwrap_or_default()
+        ));
+    }
+    Ok(out)
+}
+
+/// Returns the text of a file at a revision.
+fn show_file_at_rev(repo: &Repository, args: &Value) -> anyhow::Result<String> {
+    let path = required_str(args, "path")?;
+    let rev = required_str(args, "rev")?;
+    let blob = repo
+        .revparse_single(&format!("{rev}:{path}"))
+        .with_context(|| format!("{path} does not exist at {rev}"))?
+        .peel_to_blob()?;
+    if blob.is_binary() {
+        bail!("{path} is a binary file of {} bytes", blob.size());
+    }
+    Ok(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// Creates a branch at HEAD, optionally switching to it.
+fn create_branch(repo: &Repository, args: &Value) -> anyhow::Result<String> {
+    let name = required_str(args, "name")?;
+    if repo.find_branch(name, BranchType::Local).is_ok() {
+        bail!("A branch named {name} already exists");
+    }
+    let commit = repo.head()?.peel_to_commit()?;
+    repo.branch(name, &commit, false)?;
+    // The branch starts at the current commit, so the working tree stays as it is
+    if args
+        .get("checkout")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        repo.set_head(&format!("refs/heads/{name}"))?;
+        return Ok(format!("Created branch {name} and switched to it"));
+    }
+    Ok(format!("Created branch {name}"))
+}
+
+/// Stages the given paths and commits the index on top of HEAD.
+fn commit(repo: &Repository, args: &Value) -> anyhow::Result<String> {
+    let message = required_str(args, "message")?;
+    let workdir = repo
+        .workdir()
+        .context("The repository has no working directory")?
+        .to_path_buf();
+    let mut index = repo.index()?;
+    let paths = args.get("paths").and_then(|v| v.as_array());
+    for path in paths.into_iter().flatten().filter_map(|p| p.as_str()) {
+        if workdir.join(path).exists() {
+            index.add_path(Path::new(path))?;
+        } else {
+            index.remove_path(Path::new(path))?;
+        }
+    }
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+        bail!("Nothing to commit; stage changes by passing their paths");
+    }
+    let signature = repo
+        .signature()
+        .context("Set user.name and user.email in the git config to commit")?;
+    let parents: Vec<_> = parent.iter().collect();
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )?;
+    let id = oid.to_string();
+    Ok(format!(
+        "Committed {}: {}",
+        &id[..8],
+        message.lines().next().unwrap_or_default()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new repository with one staged file, in a fresh temporary directory.
+    fn repository(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mcmcpcp-git-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let repo = Repository::init(&root).unwrap();
+        std::fs::write(root.join("staged.txt"), "text").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        root
+    }
+
+    #[test]
+    fn commits_show_the_message_and_files() {
+        let root = repository("commit");
+        let server = GitMcpServer::new(&root);
+        let details = server
+            .approval_details(
+                "commit",
+                &json!({"message": "Fix the parser", "paths": ["src/lib.rs"]}),
+            )
+            .unwrap();
+        assert!(details.contains("Fix the parser"));
+        assert!(details.contains("- src/lib.rs"));
+        assert!(details.contains("- staged.txt"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn branches_ask_every_time_and_reads_do_not() {
+        let root = repository("branch");
+        let server = GitMcpServer::new(&root);
+        let details = server
+            .approval_details(
+                "create_branch",
+                &json!({"name": "feature", "checkout": true}),
+            )
+            .unwrap();
+        assert!(details.contains("feature"));
+        assert!(details.contains("switch to it"));
+        assert!(server.approval_details("status", &json!({})).is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
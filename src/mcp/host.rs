@@ -8,11 +8,27 @@
 //! and built-in functionality like web fetching.
 
 use serde_json::{Value, json};
-use std::{collections::{HashMap, HashSet}, time::Duration};
+use dioxus::logger::tracing::warn;
+use std::{collections::{BTreeMap, HashMap, HashSet}, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 
 use crate::mcp::{
-    McpTool, ServerSpec, ToolDescriptor, ToolResult, fetch::FetchMcpServer, server::_McpServer,
+    McpTool, ServerSpec, ToolDescriptor, ToolResult,
+    content::{ResourceContents, parse_resource_contents},
+    fetch::FetchMcpServer,
+    openapi::{OPENAPI_SERVER, OpenApiSettings, openapi_server},
+    permissions::{
+        Capability, Decision, PermissionChoice, PermissionPrompter, PermissionRequest,
+        Permissions, infer_capabilities,
+    },
+    plugin::{PluginSpec, load_plugin},
+    prompts::{PromptDescriptor, PromptMessage, parse_prompt_list, parse_prompt_messages},
+    result_format::{ResultFormat, ResultFormats, convert_result, request_format},
+    scripted::{SCRIPTS_SERVER, ScriptedTool, scripted_tools_server},
+    secrets::load_secrets,
+    shell::{SHELL_SERVER, ShellSettings, shell_server},
+    server::_McpServer,
+    tool_ref::validate_server_id,
 };
 
 /// Trait defining the interface for MCP servers.
@@ -37,6 +53,67 @@ pub trait MCPServer: Send + Sync {
     /// # Returns
     /// The result of the RPC call as a JSON value
     async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value>;
+
+    /// Exports this server's state so it can be persisted across restarts.
+    ///
+    /// Servers without persistent state keep the default, which exports nothing.
+    ///
+    /// # Returns
+    /// The server's state, or `None` if it does not support state persistence
+    async fn export_state(&mut self) -> Option<Value> {
+        None
+    }
+
+    /// Restores state previously returned by [`MCPServer::export_state`].
+    ///
+    /// # Arguments
+    /// * `state` - State to restore
+    ///
+    /// # Returns
+    /// Ok(()) if the state was restored or the server has no state to restore
+    async fn import_state(&mut self, _state: Value) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Whether this server's tools may run without permission prompts.
+    ///
+    /// Builtin servers are trusted; external server processes are not.
+    fn is_trusted(&self) -> bool {
+        true
+    }
+
+    /// Describes a call that needs the user's approval every time, even if they
+    /// allowed the tool's capabilities before. Only asked of untrusted servers.
+    ///
+    /// # Arguments
+    /// * `tool_name` - Name of the tool
+    /// * `arguments` - Arguments of the call
+    ///
+    /// # Returns
+    /// What the call does, shown to the user, or `None` if remembered
+    /// decisions apply
+    fn approval_details(&self, _tool_name: &str, _arguments: &Value) -> Option<String> {
+        None
+    }
+
+    /// Stops this server, giving it a chance to finish in-flight work.
+    ///
+    /// Builtin servers have nothing to stop and keep the default.
+    async fn shutdown(&mut self) {}
+}
+
+/// A server shared between the host's map and the calls running on it.
+///
+/// Each server has its own lock, so a long call, such as a shell command or an
+/// edit waiting for review, only holds up that server.
+type SharedServer = Arc<RwLock<Box<dyn MCPServer>>>;
+
+/// Wraps servers for the host's map.
+fn share(servers: HashMap<String, Box<dyn MCPServer>>) -> HashMap<String, SharedServer> {
+    servers
+        .into_iter()
+        .map(|(id, s)| (id, Arc::new(RwLock::new(s))))
+        .collect()
 }
 
 /// Main MCP Host that manages multiple MCP servers and provides a unified interface.
@@ -45,8 +122,28 @@ pub trait MCPServer: Send + Sync {
 /// handles tool discovery across all servers, and routes tool calls to the
 /// appropriate server. It provides timeout configuration for server operations.
 pub struct MCPHost {
-    /// Map of server ID to server implementation, protected by RwLock for concurrent access
-    servers: RwLock<HashMap<String, Box<dyn MCPServer>>>,
+    /// Map of server ID to server implementation, protected by RwLock for concurrent access.
+    /// The map is only locked to look servers up, never while a server is awaited.
+    servers: RwLock<HashMap<String, SharedServer>>,
+    /// Tools each server listed last, for listing a server that is busy with a call
+    listed_tools: RwLock<HashMap<String, Vec<McpTool>>>,
+    /// Last known state of each server, restored when the server is (re)spawned
+    saved_states: RwLock<HashMap<String, Value>>,
+    /// Loaded plugins, by the ID of the server serving their tools
+    plugins: RwLock<HashMap<String, PluginSpec>>,
+    /// Host whose servers are also available through this one, for toolsets that
+    /// add their own servers on top of the globally configured ones
+    parent: Option<Arc<MCPHost>>,
+    /// Servers whose tools are neither listed nor callable through this host,
+    /// such as those turned off for one chat
+    hidden_servers: RwLock<HashSet<String>>,
+    /// Remembered permission decisions for untrusted servers
+    permissions: RwLock<Permissions>,
+    /// Format the results of tools called through this host are given in
+    result_formats: ResultFormats,
+    /// Asks the user about capabilities without a decision; without one, such
+    /// calls are allowed
+    prompter: RwLock<Option<Arc<dyn PermissionPrompter>>>,
     /// Timeout for individual RPC requests to servers
     #[allow(unused)]
     pub request_timeout: Duration,
@@ -107,21 +204,73 @@ impl MCPHost {
         startup_timeout: Duration,
     ) -> Self {
         Self {
-            servers: RwLock::new(servers),
+            servers: RwLock::new(share(servers)),
+            listed_tools: RwLock::new(HashMap::new()),
+            saved_states: RwLock::new(HashMap::new()),
+            plugins: RwLock::new(HashMap::new()),
+            parent: None,
+            hidden_servers: RwLock::new(HashSet::new()),
+            permissions: RwLock::new(Permissions::new()),
+            prompter: RwLock::new(None),
+            result_formats: ResultFormats::default(),
             request_timeout,
             startup_timeout,
         }
     }
 
+    /// Creates a host that layers its own servers over those of another host.
+    ///
+    /// Tools of both hosts are listed, and calls to servers this host does not have
+    /// are forwarded to the parent. Local servers shadow parent servers with the
+    /// same ID.
+    ///
+    /// # Arguments
+    /// * `servers` - MCP servers owned by this host
+    /// * `parent` - Host providing the remaining servers
+    ///
+    /// # Returns
+    /// A new Host with the parent's timeouts
+    pub fn new_layered(
+        servers: HashMap<String, Box<dyn MCPServer>>,
+        parent: Arc<MCPHost>,
+    ) -> Self {
+        Self {
+            servers: RwLock::new(share(servers)),
+            listed_tools: RwLock::new(HashMap::new()),
+            saved_states: RwLock::new(HashMap::new()),
+            plugins: RwLock::new(HashMap::new()),
+            hidden_servers: RwLock::new(HashSet::new()),
+            permissions: RwLock::new(Permissions::new()),
+            prompter: RwLock::new(None),
+            result_formats: ResultFormats::default(),
+            request_timeout: parent.request_timeout,
+            startup_timeout: parent.startup_timeout,
+            parent: Some(parent),
+        }
+    }
+
+    /// Sets the format the results of tools called through this host are
+    /// given to the model in, such as compact JSON for a toolset whose results
+    /// only the model reads.
+    ///
+    /// # Arguments
+    /// * `formats` - Formats, by tool
+    pub fn with_result_formats(mut self, formats: ResultFormats) -> Self {
+        self.result_formats = formats;
+        self
+    }
+
     /// Syncs this host's servers with the list of servers in settings.
     ///
     /// # Arguments
     /// * `specs` - Server specifications including command, arguments, and ID
     ///
     /// # Returns
-    /// Ok(()) if the servers was successfully synced, or an error if spawning failed
+    /// Ok(()) if the servers was successfully synced, or the first error if spawning
+    /// any of them failed
     pub async fn sync_servers(&self, specs: Vec<ServerSpec>) -> anyhow::Result<()> {
-        // add any specs which are enabled and not running
+        // add any specs which are enabled and not running, spawning them concurrently
+        let mut missing = vec![];
         for spec in &specs {
             // Skip disabled servers
             if !spec.enabled {
@@ -129,15 +278,17 @@ impl MCPHost {
             }
             
             let exists = { self.servers.read().await.contains_key(&spec.id) };
-            if exists {
-                continue;
+            if !exists {
+                missing.push(spec.clone());
+            }
+        }
+        let results =
+            futures::future::join_all(missing.into_iter().map(|spec| self.add_server(spec))).await;
+        let mut first_error = None;
+        for res in results {
+            if let Err(e) = res {
+                first_error.get_or_insert(e);
             }
-            let server =
-                _McpServer::spawn(spec.clone(), self.request_timeout, self.startup_timeout).await?;
-            self.servers
-                .write()
-                .await
-                .insert(spec.id.clone(), Box::new(server));
         }
         
         // Create set of enabled server IDs that should be running
@@ -146,6 +297,14 @@ impl MCPHost {
             .map(|s| s.id.clone())
             .collect();
         enabled_spec_ids.insert("builtin".into());
+        // Scripted tools are synced separately, by sync_scripted_tools
+        enabled_spec_ids.insert(SCRIPTS_SERVER.into());
+        // The shell server is synced separately, by sync_shell
+        enabled_spec_ids.insert(SHELL_SERVER.into());
+        // The OpenAPI server is synced separately, by sync_openapi
+        enabled_spec_ids.insert(OPENAPI_SERVER.into());
+        // Plugins are synced separately, by sync_plugins
+        enabled_spec_ids.extend(self.plugins.read().await.keys().cloned());
 
         let ids: Vec<String> = {
             self.servers.read().await.keys()
@@ -153,14 +312,244 @@ impl MCPHost {
                 .collect()
         };
         
-        // Remove servers that are running but should not be (disabled or removed from config)
+        // Stop servers that are running but should not be (disabled or removed from config)
         for id in &ids {
             if !enabled_spec_ids.contains(id) {
-                let _ = self.servers.write().await.remove(id);
+                let removed = self.servers.write().await.remove(id);
+                if let Some(server) = removed {
+                    server.write().await.shutdown().await;
+                }
             }
         }
 
-        Ok(())
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Syncs this host's plugins with the list of plugins in settings.
+    ///
+    /// Plugins that were removed, disabled or changed are unloaded, keeping their
+    /// state, and enabled plugins that are not loaded are loaded.
+    ///
+    /// # Arguments
+    /// * `specs` - Plugins in settings
+    ///
+    /// # Returns
+    /// Ok(()) if the plugins were synced, or the first error if loading any of
+    /// them failed
+    pub async fn sync_plugins(&self, specs: &[PluginSpec]) -> anyhow::Result<()> {
+        let loaded: Vec<PluginSpec> = self.plugins.read().await.values().cloned().collect();
+        for spec in loaded {
+            if specs.iter().any(|s| s.enabled && *s == spec) {
+                continue;
+            }
+            self.plugins.write().await.remove(&spec.id);
+            let removed = self.servers.write().await.remove(&spec.id);
+            if let Some(server) = removed {
+                let mut server = server.write().await;
+                if let Some(state) = server.export_state().await {
+                    self.saved_states.write().await.insert(spec.id.clone(), state);
+                }
+                server.shutdown().await;
+            }
+        }
+
+        let mut first_error = None;
+        for spec in specs.iter().filter(|s| s.enabled) {
+            if self.plugins.read().await.contains_key(&spec.id) {
+                continue;
+            }
+            let loaded = async {
+                validate_server_id(&spec.id)?;
+                if self.servers.read().await.contains_key(&spec.id) {
+                    anyhow::bail!("A server with ID {} is already running", spec.id);
+                }
+                load_plugin(spec).await
+            }
+            .await;
+            match loaded {
+                Ok(server) => {
+                    self.insert_server(spec.id.clone(), server).await;
+                    self.plugins.write().await.insert(spec.id.clone(), spec.clone());
+                }
+                Err(e) => {
+                    warn!("Could not load plugin {}: {e:?}", spec.id);
+                    first_error.get_or_insert(e.context(format!("loading plugin {}", spec.id)));
+                }
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the server of scripted tools with one for the tools in settings.
+    ///
+    /// # Arguments
+    /// * `tools` - Scripted tools in settings
+    pub async fn sync_scripted_tools(&self, tools: &[ScriptedTool]) {
+        self.set_builtin_server(SCRIPTS_SERVER, scripted_tools_server(tools))
+            .await;
+    }
+
+    /// Replaces the shell server with one for the shell settings.
+    ///
+    /// # Arguments
+    /// * `settings` - Settings of the shell server
+    pub async fn sync_shell(&self, settings: &ShellSettings) {
+        self.set_builtin_server(SHELL_SERVER, shell_server(settings))
+            .await;
+    }
+
+    /// Replaces the OpenAPI server with one for its settings. Secrets are
+    /// loaded here, so secrets changed later apply once the server is
+    /// replaced again.
+    ///
+    /// # Arguments
+    /// * `settings` - Settings of the OpenAPI server
+    pub async fn sync_openapi(&self, settings: &OpenApiSettings) {
+        let secrets = load_secrets().await.unwrap_or_else(|e| {
+            warn!("Could not load secrets for the OpenAPI server: {e:#}");
+            Default::default()
+        });
+        self.set_builtin_server(OPENAPI_SERVER, openapi_server(settings, &secrets))
+            .await;
+    }
+
+    /// Stops all servers of this host concurrently.
+    ///
+    /// External servers get their stdin closed and a few seconds to exit before
+    /// they are killed. The host has no servers afterwards.
+    pub async fn shutdown(&self) {
+        let servers: Vec<_> = self.servers.write().await.drain().collect();
+        futures::future::join_all(servers.into_iter().map(|(_, s)| async move {
+            s.write().await.shutdown().await;
+        }))
+        .await;
+    }
+
+    /// Lists the tools of a single server.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server
+    ///
+    /// # Returns
+    /// The server's tools, or `None` if no server with that ID is running
+    pub async fn server_tools(&self, server_id: &str) -> Option<Vec<McpTool>> {
+        let server = self.server(server_id).await?;
+        Some(self.tools_of(server_id, &server).await)
+    }
+
+    /// Looks a server up, without keeping the map locked.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server
+    ///
+    /// # Returns
+    /// The server, or `None` if no server with that ID is running
+    async fn server(&self, server_id: &str) -> Option<SharedServer> {
+        self.servers.read().await.get(server_id).cloned()
+    }
+
+    /// The servers of this host, taken out of the map so that it is not locked
+    /// while they are awaited.
+    async fn server_list(&self) -> Vec<(String, SharedServer)> {
+        self.servers
+            .read()
+            .await
+            .iter()
+            .map(|(id, s)| (id.clone(), s.clone()))
+            .collect()
+    }
+
+    /// Lists the tools of a server. A server busy with a call is not waited
+    /// for if it listed its tools before; those are given instead.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server
+    /// * `server` - The server
+    ///
+    /// # Returns
+    /// The server's tools
+    async fn tools_of(&self, server_id: &str, server: &SharedServer) -> Vec<McpTool> {
+        let listed = match server.try_read() {
+            Ok(s) => Some(s.list_tools().await),
+            Err(_) => None,
+        };
+        let tools = match listed {
+            Some(tools) => tools,
+            None => {
+                let listed = self.listed_tools.read().await.get(server_id).cloned();
+                match listed {
+                    Some(tools) => return tools,
+                    None => server.read().await.list_tools().await,
+                }
+            }
+        };
+        self.listed_tools
+            .write()
+            .await
+            .insert(server_id.to_string(), tools.clone());
+        tools
+    }
+
+    /// Finds the input schema of a tool, looking in the parent host for
+    /// servers this host does not have.
+    ///
+    /// # Returns
+    /// The schema, or `None` if there is no such server or tool
+    async fn tool_schema(&self, server_id: &str, tool_name: &str) -> Option<Value> {
+        let tools = match self.server_tools(server_id).await {
+            Some(tools) => tools,
+            None => {
+                let parent = self.parent.as_ref()?;
+                return Box::pin(parent.tool_schema(server_id, tool_name)).await;
+            }
+        };
+        tools
+            .into_iter()
+            .find(|t| t.name == tool_name)
+            .map(|t| t.input_schema)
+    }
+
+    /// Returns the host at the bottom of the layering, which holds the globally
+    /// configured servers.
+    pub fn root(self: &Arc<Self>) -> Arc<MCPHost> {
+        match &self.parent {
+            Some(parent) => parent.root(),
+            None => self.clone(),
+        }
+    }
+
+    /// Lists the IDs of the servers available through this host, including
+    /// those of a parent host.
+    ///
+    /// # Returns
+    /// The server IDs, sorted, hidden servers included
+    pub async fn server_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.servers.read().await.keys().cloned().collect();
+        if let Some(parent) = &self.parent {
+            ids.extend(Box::pin(parent.server_ids()).await);
+        }
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Hides servers from this host: their tools are not listed, and calls to
+    /// them fail. Replaces any servers hidden before.
+    ///
+    /// Layered hosts hide servers of their parent without affecting the parent,
+    /// which lets each chat turn off global servers for itself.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the servers to hide
+    pub async fn set_hidden_servers(&self, ids: impl IntoIterator<Item = String>) {
+        *self.hidden_servers.write().await = ids.into_iter().collect();
     }
 
     /// Adds an external MCP server to the host.
@@ -175,9 +564,301 @@ impl MCPHost {
     /// # Returns
     /// Ok(()) if the server was successfully added, or an error if spawning failed
     pub async fn add_server(&self, spec: ServerSpec) -> anyhow::Result<()> {
+        validate_server_id(&spec.id)?;
         let server =
             _McpServer::spawn(spec.clone(), self.request_timeout, self.startup_timeout).await?;
-        self.servers.write().await.insert(spec.id, Box::new(server));
+        self.insert_server(spec.id, Box::new(server)).await;
+        Ok(())
+    }
+
+    /// Restarts a single server and refreshes its tool list.
+    ///
+    /// The running process is stopped after its state is saved, then a new one is
+    /// spawned from the spec and the state restored into it. Other servers are not
+    /// touched. If the new process fails to start, the server stays stopped until it
+    /// is reloaded again or the app restarts.
+    ///
+    /// # Arguments
+    /// * `spec` - Current specification of the server
+    ///
+    /// # Returns
+    /// Ok(()) if the server was restarted, or an error if spawning failed
+    pub async fn reload_server(&self, spec: ServerSpec) -> anyhow::Result<()> {
+        validate_server_id(&spec.id)?;
+        let removed = self.servers.write().await.remove(&spec.id);
+        if let Some(server) = removed {
+            let mut server = server.write().await;
+            if let Some(state) = server.export_state().await {
+                self.saved_states
+                    .write()
+                    .await
+                    .insert(spec.id.clone(), state);
+            }
+            server.shutdown().await;
+        }
+        self.add_server(spec).await
+    }
+
+    /// Adds, replaces or removes a builtin server of this host, such as one
+    /// serving a chat's documents. Its state is not saved or restored.
+    ///
+    /// # Arguments
+    /// * `id` - Server ID
+    /// * `server` - The server, or `None` to remove it
+    pub async fn set_builtin_server(&self, id: &str, server: Option<Box<dyn MCPServer>>) {
+        let mut servers = self.servers.write().await;
+        match server {
+            Some(server) => {
+                servers.insert(id.to_string(), Arc::new(RwLock::new(server)));
+            }
+            None => {
+                servers.remove(id);
+            }
+        }
+        drop(servers);
+        self.listed_tools.write().await.remove(id);
+    }
+
+    /// Registers a newly spawned server, restoring its saved state if there is one.
+    ///
+    /// # Arguments
+    /// * `id` - Server ID
+    /// * `server` - The server to register
+    async fn insert_server(&self, id: String, mut server: Box<dyn MCPServer>) {
+        let state = { self.saved_states.read().await.get(&id).cloned() };
+        if let Some(state) = state
+            && let Err(e) = server.import_state(state).await
+        {
+            warn!("Could not restore state of server {id}: {e:?}");
+        }
+        self.listed_tools.write().await.remove(&id);
+        self.servers
+            .write()
+            .await
+            .insert(id, Arc::new(RwLock::new(server)));
+    }
+
+    /// Snapshots the state of every server that supports state persistence.
+    ///
+    /// States of servers that are not currently running (e.g. disabled ones) are
+    /// kept from the last snapshot, so they survive until the server is re-enabled.
+    ///
+    /// # Returns
+    /// Map of server ID to exported state
+    pub async fn export_states(&self) -> HashMap<String, Value> {
+        for (id, s) in self.server_list().await {
+            if let Some(state) = s.write().await.export_state().await {
+                self.saved_states.write().await.insert(id, state);
+            }
+        }
+        self.saved_states.read().await.clone()
+    }
+
+    /// Restores previously exported server states.
+    ///
+    /// Running servers import their state immediately; servers spawned later
+    /// import theirs when they start.
+    ///
+    /// # Arguments
+    /// * `states` - Map of server ID to state, as returned by `export_states`
+    pub async fn restore_states(&self, states: HashMap<String, Value>) {
+        for (id, state) in &states {
+            let Some(s) = self.server(id).await else {
+                continue;
+            };
+            if let Err(e) = s.write().await.import_state(state.clone()).await {
+                warn!("Could not restore state of server {id}: {e:?}");
+            }
+        }
+        *self.saved_states.write().await = states;
+    }
+
+    /// Replaces the remembered permission decisions, e.g. after loading settings.
+    ///
+    /// # Arguments
+    /// * `permissions` - Decisions by server ID and capability
+    pub async fn set_permissions(&self, permissions: Permissions) {
+        *self.permissions.write().await = permissions;
+    }
+
+    /// Returns the remembered permission decisions.
+    pub async fn permissions(&self) -> Permissions {
+        self.permissions.read().await.clone()
+    }
+
+    /// Sets how the user is asked for permissions. Until a prompter is set, only
+    /// remembered decisions are enforced and other tool calls are allowed.
+    ///
+    /// # Arguments
+    /// * `prompter` - Prompter used for capabilities without a decision
+    pub async fn set_prompter(&self, prompter: Arc<dyn PermissionPrompter>) {
+        *self.prompter.write().await = Some(prompter);
+    }
+
+    /// Lists the capabilities each untrusted server's tools need.
+    ///
+    /// # Returns
+    /// Server IDs with the capabilities their tools need and any remembered
+    /// decisions, sorted by server ID
+    pub async fn permission_summary(
+        &self,
+    ) -> Vec<(String, Vec<(Capability, Option<Decision>)>)> {
+        let mut summary = vec![];
+        for (id, s) in self.server_list().await {
+            if s.read().await.is_trusted() {
+                continue;
+            }
+            let mut caps: Vec<Capability> = self
+                .tools_of(&id, &s)
+                .await
+                .iter()
+                .flat_map(infer_capabilities)
+                .collect();
+            caps.sort();
+            caps.dedup();
+            let permissions = self.permissions.read().await;
+            let decided = permissions.get(&id);
+            summary.push((
+                id,
+                caps.into_iter()
+                    .map(|c| (c, decided.and_then(|d| d.get(&c)).copied()))
+                    .collect(),
+            ));
+        }
+        summary.sort_by(|a, b| a.0.cmp(&b.0));
+        summary
+    }
+
+    /// Checks that a tool call is allowed, asking the user if needed.
+    ///
+    /// Calls to servers of a parent host are checked by the parent. Untrusted
+    /// servers of a layered host are checked against the decisions of its root
+    /// host, which is the one with a prompter.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server that provides the tool
+    /// * `tool_name` - Name of the tool to execute
+    /// * `arguments` - Arguments of the call
+    ///
+    /// # Returns
+    /// Ok(()) if the call may go ahead, or an error explaining the refusal
+    async fn check_permissions(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> anyhow::Result<()> {
+        let (tool, details) = {
+            match (self.server(server_id).await, &self.parent) {
+                (Some(s), _) => {
+                    let (trusted, details) = {
+                        let s = s.read().await;
+                        (s.is_trusted(), s.approval_details(tool_name, arguments))
+                    };
+                    if trusted {
+                        return Ok(());
+                    }
+                    let tools = self.tools_of(server_id, &s).await;
+                    (tools.into_iter().find(|t| t.name == tool_name), details)
+                }
+                (None, Some(parent)) => {
+                    return Box::pin(parent.check_permissions(server_id, tool_name, arguments))
+                        .await;
+                }
+                // Unknown servers are reported by `invoke`
+                (None, None) => return Ok(()),
+            }
+        };
+        let Some(tool) = tool else {
+            return Ok(());
+        };
+        self.check_tool_permissions(server_id, &tool, details).await
+    }
+
+    /// Checks the capabilities a tool needs against this host's decisions, or
+    /// those of its root host, asking the user about undecided ones.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server that provides the tool
+    /// * `tool` - The tool to execute
+    /// * `details` - What the call does, if it needs approval every time; then
+    ///   the user is always asked, and the answer is not remembered
+    ///
+    /// # Returns
+    /// Ok(()) if the call may go ahead, or an error explaining the refusal
+    async fn check_tool_permissions(
+        &self,
+        server_id: &str,
+        tool: &McpTool,
+        details: Option<String>,
+    ) -> anyhow::Result<()> {
+        if let Some(parent) = &self.parent {
+            return Box::pin(parent.check_tool_permissions(server_id, tool, details)).await;
+        }
+        let tool_name = &tool.name;
+        let always_ask = details.is_some();
+        let prompter = self.prompter.read().await.clone();
+
+        for capability in infer_capabilities(tool) {
+            let decision = self
+                .permissions
+                .read()
+                .await
+                .get(server_id)
+                .and_then(|p| p.get(&capability))
+                .copied();
+            let refusal = || {
+                anyhow::anyhow!(
+                    "Permission denied: the user has not allowed the server {server_id} to {}.",
+                    capability.description()
+                )
+            };
+            match decision {
+                Some(Decision::Allow) if !always_ask => {}
+                Some(Decision::Deny) => return Err(refusal()),
+                _ => {
+                    // Without a prompter only remembered decisions apply, and
+                    // calls needing approval every time cannot be approved
+                    let Some(prompter) = &prompter else {
+                        if always_ask {
+                            anyhow::bail!(
+                                "The tool {tool_name} needs the user's approval for each call, \
+                                 which cannot be asked for here."
+                            );
+                        }
+                        continue;
+                    };
+                    let request = PermissionRequest {
+                        server_id: server_id.to_string(),
+                        tool_name: tool_name.to_string(),
+                        capability,
+                        details: details.clone(),
+                        remember: !always_ask,
+                    };
+                    let choice = prompter.ask(request).await;
+                    if choice == PermissionChoice::Deny && always_ask {
+                        return Err(refusal());
+                    }
+                    let remembered = match choice {
+                        _ if always_ask => None,
+                        PermissionChoice::Once => None,
+                        PermissionChoice::Always => Some(Decision::Allow),
+                        PermissionChoice::Deny => Some(Decision::Deny),
+                    };
+                    if let Some(d) = remembered {
+                        self.permissions
+                            .write()
+                            .await
+                            .entry(server_id.to_string())
+                            .or_default()
+                            .insert(capability, d);
+                    }
+                    if remembered == Some(Decision::Deny) {
+                        return Err(refusal());
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
@@ -185,16 +866,18 @@ impl MCPHost {
     ///
     /// Queries each server for its available tools and returns a combined list
     /// with server ID information. This allows the LLM to see all available
-    /// tools across all connected MCP servers.
+    /// tools across all connected MCP servers. Tools of a parent host are
+    /// included unless a local server has the same ID. Hidden servers are left out.
     ///
     /// # Returns
     /// Vector of tool descriptors with server ID and tool information
     pub async fn list_tools(&self) -> Vec<ToolDescriptor> {
         let mut res = vec![];
-        let servers = self.servers.read().await;
+        let servers = self.server_list().await;
+        let hidden = self.hidden_servers.read().await.clone();
         // Query each server for its tools
-        for (id, s) in servers.iter() {
-            let tools = s.list_tools().await;
+        for (id, s) in servers.iter().filter(|(id, _)| !hidden.contains(id)) {
+            let tools = self.tools_of(id, s).await;
             let ts: Vec<ToolDescriptor> = tools
                 .into_iter()
                 .map(move |t| ToolDescriptor {
@@ -204,6 +887,15 @@ impl MCPHost {
                 .collect();
             res.extend(ts);
         }
+        if let Some(parent) = &self.parent {
+            let inherited = Box::pin(parent.list_tools()).await;
+            res.extend(
+                inherited
+                    .into_iter()
+                    .filter(|t| !servers.iter().any(|(id, _)| *id == t.server_id))
+                    .filter(|t| !hidden.contains(&t.server_id)),
+            );
+        }
         res
     }
 
@@ -225,17 +917,22 @@ impl MCPHost {
         method: &str,
         params: Value,
     ) -> anyhow::Result<Value> {
-        let mut servers = self.servers.write().await;
-        let s = servers
-            .get_mut(server_id)
-            .ok_or_else(|| anyhow::anyhow!("unknown server {server_id}"))?;
-        s.rpc(method, params).await
+        // The map is released before the call, which may run for long
+        match (self.server(server_id).await, &self.parent) {
+            (Some(s), _) => s.write().await.rpc(method, params).await,
+            (None, Some(parent)) => Box::pin(parent.invoke(server_id, method, params)).await,
+            (None, None) => Err(anyhow::anyhow!("unknown server {server_id}")),
+        }
     }
 
     /// Executes a tool call on the specified server.
     ///
     /// High-level method for calling tools on MCP servers. Formats the parameters
-    /// appropriately and parses the result into a ToolResult structure.
+    /// appropriately and parses the result into a ToolResult structure. Calls to
+    /// untrusted servers are checked against the permission model first, and
+    /// calls to hidden servers are refused. Results are given in the format
+    /// set with [`MCPHost::with_result_formats`], asked of the tool if it
+    /// takes a format argument and converted otherwise.
     ///
     /// # Arguments
     /// * `server_id` - ID of the server that provides the tool
@@ -248,8 +945,21 @@ impl MCPHost {
         &self,
         server_id: &str,
         tool_name: &str,
-        arguments: Value,
+        mut arguments: Value,
     ) -> anyhow::Result<ToolResult> {
+        if self.hidden_servers.read().await.contains(server_id) {
+            anyhow::bail!("The server {server_id} is turned off for this chat.");
+        }
+        self.check_permissions(server_id, tool_name, &arguments).await?;
+
+        // Ask the tool for the preferred format if it can be asked
+        let format = self.result_formats.for_tool(server_id, tool_name);
+        let requested = format != ResultFormat::AsIs
+            && match self.tool_schema(server_id, tool_name).await {
+                Some(schema) => request_format(&schema, &mut arguments, format),
+                None => false,
+            };
+
         // Format parameters for the tools/call RPC method
         let params = json!({
             "name": tool_name,
@@ -258,6 +968,80 @@ impl MCPHost {
 
         // Execute the RPC call and parse the result
         let result = self.invoke(server_id, "tools/call", params).await?;
-        serde_json::from_value(result).map_err(|e| e.into())
+        let mut result: ToolResult = serde_json::from_value(result)?;
+        if !requested {
+            convert_result(&mut result, format);
+        }
+        Ok(result)
+    }
+
+    /// Reads a resource from the specified server, such as one linked in a tool result.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server that provides the resource
+    /// * `uri` - URI of the resource
+    ///
+    /// # Returns
+    /// The resource's contents, or an error if the server cannot read it
+    pub async fn read_resource(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> anyhow::Result<Vec<ResourceContents>> {
+        let result = self
+            .invoke(server_id, "resources/read", json!({ "uri": uri }))
+            .await?;
+        parse_resource_contents(result)
+    }
+
+    /// Lists the prompt templates offered by the servers, hidden servers left out.
+    ///
+    /// Servers that offer no prompts, or fail to list them, are skipped.
+    ///
+    /// # Returns
+    /// The prompts, ordered by server ID
+    pub async fn list_prompts(&self) -> Vec<PromptDescriptor> {
+        let hidden = self.hidden_servers.read().await.clone();
+        let mut res = vec![];
+        for id in self.server_ids().await {
+            if hidden.contains(&id) {
+                continue;
+            }
+            let prompts = self
+                .invoke(&id, "prompts/list", json!({}))
+                .await
+                .and_then(parse_prompt_list);
+            res.extend(prompts.into_iter().flatten().map(|prompt| PromptDescriptor {
+                server_id: id.clone(),
+                prompt,
+            }));
+        }
+        res
+    }
+
+    /// Expands a prompt template of the specified server.
+    ///
+    /// # Arguments
+    /// * `server_id` - ID of the server that provides the prompt
+    /// * `name` - Name of the prompt
+    /// * `arguments` - Values of the prompt's arguments, by name
+    ///
+    /// # Returns
+    /// The messages the prompt expanded to, or an error if the server cannot expand it
+    pub async fn get_prompt(
+        &self,
+        server_id: &str,
+        name: &str,
+        arguments: BTreeMap<String, String>,
+    ) -> anyhow::Result<Vec<PromptMessage>> {
+        if self.hidden_servers.read().await.contains(server_id) {
+            anyhow::bail!("The server {server_id} is turned off for this chat.");
+        }
+        let params = json!({
+            "name": name,
+            "arguments": arguments,
+        });
+        let result = self.invoke(server_id, "prompts/get", params).await?;
+        parse_prompt_messages(result)
     }
 }
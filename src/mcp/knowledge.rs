@@ -0,0 +1,304 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Documents attached to a chat, and the builtin server that searches them.
+//!
+//! Files dropped onto a chat are parsed into text and stored with the chat
+//! (see [`ChatDocument`]). Rather than pasting them into the conversation,
+//! each request lists them with a summary, and the model reads the parts it
+//! needs through the `search_documents` and `read_document` tools.
+
+use anyhow::{anyhow, bail};
+use serde_json::{Value, json};
+
+use crate::{
+    app_settings::ChatDocument,
+    mcp::{
+        McpTool, ToolResult, ToolResultContent,
+        documents::{self, DocumentKind},
+        fetch::html_to_markdown,
+        host::{MCPHost, MCPServer},
+    },
+};
+
+/// ID of the server serving a chat's documents.
+pub const DOCUMENTS_SERVER: &str = "documents";
+
+/// Largest file, in bytes, that can be attached to a chat.
+pub const MAX_DOCUMENT_BYTES: usize = 20_000_000;
+
+/// Characters per chunk searched by `search_documents`.
+const CHUNK_CHARS: usize = 1_500;
+
+/// Chunks returned by `search_documents` when no limit is given.
+const DEFAULT_RESULTS: usize = 5;
+
+/// Characters returned by `read_document` when no length is given.
+const DEFAULT_READ_LENGTH: usize = 8_000;
+
+/// Extracts the text of a file dropped onto a chat.
+///
+/// # Arguments
+/// * `name` - The file's name, whose extension helps detect its kind
+/// * `bytes` - The file
+///
+/// # Returns
+/// The document, without a summary yet, or an error if the file is too
+/// large, binary or has no text
+pub fn document_from_file(name: &str, bytes: Vec<u8>) -> anyhow::Result<ChatDocument> {
+    if bytes.len() > MAX_DOCUMENT_BYTES {
+        bail!(
+            "{name} is too large to attach ({} MB at most)",
+            MAX_DOCUMENT_BYTES / 1_000_000
+        );
+    }
+    let text = match DocumentKind::detect(None, name, &bytes) {
+        DocumentKind::Pdf => documents::pdf_to_text(&bytes)?,
+        DocumentKind::Docx => documents::docx_to_text(&bytes)?,
+        DocumentKind::Html => html_to_markdown(&String::from_utf8_lossy(&bytes)),
+        DocumentKind::Text => String::from_utf8(bytes)
+            .map_err(|_| anyhow!("{name} is not a text, Markdown, PDF or DOCX file"))?,
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("{name} has no text");
+    }
+    Ok(ChatDocument {
+        name: name.to_string(),
+        text: text.to_string(),
+        summary: None,
+    })
+}
+
+/// Serves a chat's documents from its toolset's host, or removes the server
+/// if the chat has none.
+///
+/// # Arguments
+/// * `host` - Host of the chat's toolset
+/// * `documents` - The chat's documents
+pub async fn serve_documents(host: &MCPHost, documents: &[ChatDocument]) {
+    let server: Option<Box<dyn MCPServer>> = if documents.is_empty() {
+        None
+    } else {
+        Some(Box::new(KnowledgeMcpServer {
+            documents: documents.to_vec(),
+        }))
+    };
+    host.set_builtin_server(DOCUMENTS_SERVER, server).await;
+}
+
+/// Built-in MCP server that searches and reads the documents of a chat.
+pub struct KnowledgeMcpServer {
+    /// The chat's documents
+    pub documents: Vec<ChatDocument>,
+}
+
+#[async_trait::async_trait]
+impl MCPServer for KnowledgeMcpServer {
+    /// Returns the tools for searching and reading the documents.
+    async fn list_tools(&self) -> Vec<McpTool> {
+        let names = self
+            .documents
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        vec![
+            McpTool {
+                name: "search_documents".into(),
+                description: Some(format!(
+                    "Search the documents the user attached to this chat ({names}) and return \
+                     the passages that best match the query."
+                )),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "Words to look for"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Passages to return (default 5)"
+                        }
+                    },
+                    "required": ["query"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "read_document".into(),
+                description: Some(
+                    "Read part of a document the user attached to this chat, from a character \
+                     offset."
+                        .into(),
+                ),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Name of the document"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "description": "Character to start at (default 0)"
+                        },
+                        "length": {
+                            "type": "integer",
+                            "description": "Characters to read (default 8000)"
+                        }
+                    },
+                    "required": ["name"]
+                }),
+                annotations: None,
+            },
+        ]
+    }
+
+    /// Handles tool calls for the documents server.
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let result = match name {
+            "search_documents" => {
+                let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+                let limit = args.get("limit").and_then(|v| v.as_u64());
+                self.search(query, limit.map_or(DEFAULT_RESULTS, |l| l as usize))
+            }
+            "read_document" => {
+                let document = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                let length = args.get("length").and_then(|v| v.as_u64());
+                self.read(
+                    document,
+                    offset,
+                    length.map_or(DEFAULT_READ_LENGTH, |l| l as usize),
+                )
+            }
+            _ => bail!("Unknown tool: {name}"),
+        };
+        let (text, is_error) = match result {
+            Ok(text) => (text, None),
+            Err(e) => (e.to_string(), Some(true)),
+        };
+        Ok(serde_json::to_value(ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".into(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error,
+        })?)
+    }
+}
+
+impl KnowledgeMcpServer {
+    /// Finds the passages sharing the most words with a query.
+    ///
+    /// # Arguments
+    /// * `query` - Words to look for
+    /// * `limit` - Passages to return at most
+    ///
+    /// # Returns
+    /// The passages, best first, each headed by its document and offset
+    fn search(&self, query: &str, limit: usize) -> anyhow::Result<String> {
+        let terms = words(query);
+        if terms.is_empty() {
+            bail!("No query given");
+        }
+        let mut hits: Vec<(usize, &str, usize, &str)> = vec![];
+        for document in &self.documents {
+            for (offset, chunk) in chunks(&document.text) {
+                let chunk_words = words(chunk);
+                let score = terms
+                    .iter()
+                    .map(|t| chunk_words.iter().filter(|w| *w == t).count())
+                    .filter(|&n| n > 0)
+                    // Matching more of the terms counts more than repeating one
+                    .map(|n| 10 + n)
+                    .sum::<usize>();
+                if score > 0 {
+                    hits.push((score, &document.name, offset, chunk));
+                }
+            }
+        }
+        if hits.is_empty() {
+            return Ok(format!("No passages match \"{query}\"."));
+        }
+        hits.sort_by(|a, b| b.0.cmp(&a.0).then(a.2.cmp(&b.2)));
+        Ok(hits
+            .into_iter()
+            .take(limit.max(1))
+            .map(|(_, name, offset, chunk)| format!("## {name} (offset {offset})\n\n{chunk}"))
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }
+
+    /// Reads part of a document.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the document
+    /// * `offset` - Character to start at
+    /// * `length` - Characters to read
+    fn read(&self, name: &str, offset: usize, length: usize) -> anyhow::Result<String> {
+        let document = self
+            .documents
+            .iter()
+            .find(|d| d.name == name)
+            .ok_or_else(|| anyhow!("No document named {name}"))?;
+        let total = document.text.chars().count();
+        if offset >= total {
+            bail!("{name} has only {total} characters");
+        }
+        let text: String = document.text.chars().skip(offset).take(length).collect();
+        let end = offset + text.chars().count();
+        if end < total {
+            Ok(format!(
+                "{text}\n\n[Characters {offset}-{end} of {total}; read from offset {end} for more]"
+            ))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+/// Splits a text into passages of about [`CHUNK_CHARS`] characters, at
+/// paragraph breaks where possible.
+///
+/// # Returns
+/// Each passage with the character offset it starts at
+fn chunks(text: &str) -> Vec<(usize, &str)> {
+    let mut chunks = vec![];
+    let (mut start, mut start_chars, mut chars) = (0, 0, 0);
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        chars += 1;
+        let len = chars - start_chars;
+        let paragraph = c == '\n' && text[i + 1..].starts_with('\n');
+        if (paragraph && len >= CHUNK_CHARS / 2) || len >= CHUNK_CHARS {
+            end = i + c.len_utf8();
+            chunks.push((start_chars, text[start..end].trim()));
+            start = end;
+            start_chars = chars;
+        }
+    }
+    if end < text.len() {
+        chunks.push((start_chars, text[start..].trim()));
+    }
+    chunks.retain(|(_, c)| !c.is_empty());
+    chunks
+}
+
+/// Lowercase words of a text, for matching.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,109 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Output of tool calls that are still running.
+//!
+//! Tools whose calls take a while, such as shell commands, [`start`] an entry
+//! when they begin and [`append`] their output as it arrives, so the chat can
+//! show it before the tool result is ready. The entry is dropped when the call
+//! [`finish`]es; the model only sees the final result.
+//!
+//! Like the job list in [`crate::jobs`], the entries are kept in memory and
+//! the UI [`subscribe`]s to be woken whenever they change.
+
+use std::sync::{
+    LazyLock, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use tokio::sync::watch;
+
+/// Characters of output kept per call; older output is dropped first.
+const MAX_CHARS: usize = 20_000;
+
+/// Outputs of running calls, oldest first
+static OUTPUTS: Mutex<Vec<LiveOutput>> = Mutex::new(Vec::new());
+/// Counter used to generate output IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+/// Bumped whenever an output changes
+static CHANGED: LazyLock<watch::Sender<u64>> = LazyLock::new(|| watch::Sender::new(0));
+
+/// Output of a running tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveOutput {
+    pub id: u64,
+    /// What is running, such as the command line
+    pub label: String,
+    /// The latest output, at most [`MAX_CHARS`] characters
+    pub text: String,
+}
+
+/// Adds an entry for a call that has started.
+///
+/// # Arguments
+/// * `label` - What is running, shown above its output
+///
+/// # Returns
+/// ID of the entry, for [`append`] and [`finish`]
+pub fn start(label: impl Into<String>) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut outputs) = OUTPUTS.lock() {
+        outputs.push(LiveOutput {
+            id,
+            label: label.into(),
+            text: String::new(),
+        });
+    }
+    changed();
+    id
+}
+
+/// Adds output to a running call's entry.
+///
+/// # Arguments
+/// * `id` - ID returned by [`start`]
+/// * `text` - The new output
+pub fn append(id: u64, text: &str) {
+    if let Ok(mut outputs) = OUTPUTS.lock()
+        && let Some(output) = outputs.iter_mut().find(|o| o.id == id)
+    {
+        output.text.push_str(text);
+        let excess = output.text.chars().count().saturating_sub(MAX_CHARS);
+        if excess > 0 {
+            let cut = output
+                .text
+                .char_indices()
+                .nth(excess)
+                .map_or(output.text.len(), |(i, _)| i);
+            output.text.drain(..cut);
+        }
+    }
+    changed();
+}
+
+/// Drops the entry of a call that has ended.
+///
+/// # Arguments
+/// * `id` - ID returned by [`start`]
+pub fn finish(id: u64) {
+    if let Ok(mut outputs) = OUTPUTS.lock() {
+        outputs.retain(|o| o.id != id);
+    }
+    changed();
+}
+
+/// Returns the outputs of running calls, oldest first.
+pub fn outputs() -> Vec<LiveOutput> {
+    OUTPUTS
+        .lock()
+        .map(|outputs| outputs.clone())
+        .unwrap_or_default()
+}
+
+/// Returns a receiver that is notified whenever an output changes.
+pub fn subscribe() -> watch::Receiver<u64> {
+    CHANGED.subscribe()
+}
+
+fn changed() {
+    CHANGED.send_modify(|n| *n += 1);
+}
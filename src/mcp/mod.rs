@@ -13,15 +13,60 @@
 //! - `transport`: Communication layer for server processes (native only)
 //! - `jsonrpc`: JSON-RPC protocol implementation for MCP communication
 //! - `config`: Configuration structures for MCP servers
+//! - `content`: Resources and binary data returned by tools
+//! - `files`: Reading and reviewed editing of a coding chat's files (native only)
+//! - `git`: Git repository access for coding chats (native only)
+//! - `knowledge`: Documents attached to a chat, searchable by the model
+//! - `live_output`: Output of tool calls shown while they run
+//! - `result_format`: Compact JSON or Markdown tool results, as a toolset prefers
+//! - `results`: Size limits for tool results, with paging through the full text
+//! - `secrets`: Secrets referenced from server arguments, resolved at spawn time
+//! - `shell`: Allowlisted commands run with the user's approval (run on native only)
+//! - `openapi`: REST API operations from OpenAPI documents as tools (run on native only)
+//! - `patch`: File edits reviewed hunk by hunk before they are applied
+//! - `permissions`: Permission prompts for tools of external servers
+//! - `plugin`: Builtin servers provided by WASM plugins (loaded on native only)
+//! - `project_map`: Directory tree and symbols of a coding chat's project (native only)
+//! - `prompts`: Prompt templates offered by servers
+//! - `scratchpad`: Notes the model keeps for itself, hidden from the transcript
+//! - `scripted`: Tools defined in settings as Rhai scripts (run on native only)
+//! - `tool_ref`: Tool names prefixed with their server's id
+//! - `validation`: Checks of server settings, with an error per field
+//! - `watch`: Restarting servers when their files change (native only)
 
 // Module declarations
 mod config; // Configuration structures and parsing
+pub mod content; // Resources and binary data in tool results
+mod documents; // Text extraction from PDF, DOCX and HTML documents
 pub mod fetch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod files; // Files of a coding chat's project, edited after review (native only)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod git; // Git repository of a coding chat's project (native only)
 pub mod host; // Main MCP host implementation (public for external access)
 mod jsonrpc; // JSON-RPC protocol implementation
+pub mod knowledge; // Documents attached to chats and the server searching them
+pub mod live_output; // Output of running tool calls, shown before their result
+pub mod openapi; // Operations of REST APIs described by OpenAPI documents
+pub mod patch; // Proposed file edits awaiting the user's review
+pub mod permissions; // Capability inference and permission prompts for external servers
+pub mod plugin; // Sandboxed WASM plugins serving builtin tools
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project_map; // Map of a coding chat's project for orienting the model (native only)
+pub mod prompts; // Prompt templates offered by servers
+pub mod result_format; // Format of tool results given to the model
+pub mod results; // Truncation of oversized tool results and the read_more store
+pub mod scratchpad; // Notes the model keeps for itself outside the transcript
+pub mod scripted; // Tools defined as Rhai scripts in settings
+pub mod secrets; // Secrets referenced from server arguments and environment
+pub mod shell; // Allowlisted shell commands, each approved by the user
 mod server; // Individual MCP server management
+pub mod tool_ref; // Encoding of server ids into tool names
+pub mod validation; // Checks of server settings before they are saved
 #[cfg(not(target_arch = "wasm32"))]
 mod transport; // Process-based transport (native platforms only) // built-in fetch MCP server
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch; // Reloading of servers whose files changed (native only)
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -44,6 +89,13 @@ pub struct ServerSpec {
     /// Whether this server is enabled (defaults to true for backward compatibility)
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// Restrictions applied to the server process (native only)
+    #[serde(default)]
+    pub sandbox: SandboxOptions,
+    /// File or directory whose changes restart the server, for servers under
+    /// development (native only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<String>,
 }
 
 /// Default value for the enabled field (true for backward compatibility)
@@ -51,6 +103,191 @@ fn default_enabled() -> bool {
     true
 }
 
+impl ServerSpec {
+    /// Copies the server under a new ID, `<id>-copy`, or `<id>-copy-2` and so
+    /// on if that is taken. The copy is disabled, so it does not start as a
+    /// second instance of the same server before it is edited.
+    ///
+    /// # Arguments
+    /// * `taken_ids` - IDs of the configured servers
+    ///
+    /// # Returns
+    /// The copy
+    pub fn duplicate(&self, taken_ids: &[String]) -> ServerSpec {
+        let base = format!("{}-copy", self.id);
+        let id = std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{base}-{n}")))
+            .find(|id| !taken_ids.contains(id))
+            .unwrap_or(base);
+        ServerSpec {
+            id,
+            enabled: false,
+            ..self.clone()
+        }
+    }
+}
+
+/// Command used to isolate a spawned MCP server.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxWrapper {
+    /// Run the command directly
+    #[default]
+    None,
+    /// Run the command under `bwrap` (Linux)
+    Bubblewrap,
+    /// Run the command under `firejail` (Linux)
+    Firejail,
+}
+
+impl SandboxWrapper {
+    /// Returns the wrapper's executable name, if any.
+    pub fn program(&self) -> Option<&'static str> {
+        match self {
+            SandboxWrapper::None => None,
+            SandboxWrapper::Bubblewrap => Some("bwrap"),
+            SandboxWrapper::Firejail => Some("firejail"),
+        }
+    }
+}
+
+/// Security options for a spawned MCP server.
+///
+/// The environment allowlist works on every platform. Network isolation and a
+/// read-only working directory need a sandbox wrapper; see `transport.rs` for
+/// how each wrapper is invoked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SandboxOptions {
+    /// Wrapper command used to isolate the server
+    #[serde(default)]
+    pub wrapper: SandboxWrapper,
+    /// Start the server with only the allowlisted variables and its own `env`,
+    /// instead of inheriting the app's whole environment
+    #[serde(default)]
+    pub restrict_env: bool,
+    /// Variables inherited from the app's environment when `restrict_env` is set
+    #[serde(default = "default_env_allowlist")]
+    pub env_allowlist: Vec<String>,
+    /// Cut the server off from the network
+    #[serde(default)]
+    pub no_network: bool,
+    /// Directory to start the server in; defaults to the app's working directory
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// Prevent the server from writing to its working directory
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl Default for SandboxOptions {
+    fn default() -> Self {
+        Self {
+            wrapper: SandboxWrapper::None,
+            restrict_env: false,
+            env_allowlist: default_env_allowlist(),
+            no_network: false,
+            working_dir: None,
+            read_only: false,
+        }
+    }
+}
+
+/// Variables most servers need to start: where to find programs and the user's home
+fn default_env_allowlist() -> Vec<String> {
+    vec!["PATH".into(), "HOME".into()]
+}
+
+impl SandboxOptions {
+    /// Describes the active restrictions for display, e.g. "bwrap, no network".
+    ///
+    /// # Returns
+    /// `None` if the server runs unrestricted
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = vec![];
+        if let Some(program) = self.wrapper.program() {
+            parts.push(program.to_string());
+        }
+        if self.restrict_env {
+            parts.push(format!("env: {}", self.env_allowlist.join(", ")));
+        }
+        if self.no_network {
+            parts.push("no network".into());
+        }
+        if self.read_only {
+            parts.push("read-only working directory".into());
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("; "))
+        }
+    }
+}
+
+/// A server entry in the `mcpServers` format used by Claude Desktop, VS Code and others.
+#[derive(Debug, Deserialize)]
+struct ExternalServerEntry {
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+/// Parses MCP server definitions from another client's JSON config.
+///
+/// Accepts a Claude Desktop style `{"mcpServers": {...}}` document, VS Code's
+/// `{"servers": {...}}` or `{"mcp": {"servers": {...}}}`, or the bare map of
+/// server name to `{command, args, env}`. Entries without a `command` (e.g.
+/// HTTP/SSE servers) are skipped, since only stdio servers are supported.
+/// Names that are not valid server ids are sanitized.
+///
+/// # Arguments
+/// * `json` - The JSON text to parse
+///
+/// # Returns
+/// Server specifications keyed by their name in the config, in name order
+pub fn parse_external_servers(json: &str) -> anyhow::Result<Vec<ServerSpec>> {
+    let doc: Value = serde_json::from_str(json)?;
+    let map = doc
+        .get("mcpServers")
+        .or_else(|| doc.get("servers"))
+        .or_else(|| doc.get("mcp").and_then(|m| m.get("servers")))
+        .unwrap_or(&doc)
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("Expected an object mapping server names to definitions"))?;
+
+    let mut specs: Vec<ServerSpec> = vec![];
+    for (name, entry) in map {
+        let entry: ExternalServerEntry = serde_json::from_value(entry.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid definition for {name}: {e}"))?;
+        let Some(cmd) = entry.command else {
+            continue;
+        };
+        // Names become part of tool names, so they may need changes to be valid ids
+        let base = tool_ref::sanitize_server_id(name);
+        let mut id = base.clone();
+        let mut n = 2;
+        while specs.iter().any(|s| s.id == id) {
+            id = format!("{base}_{n}");
+            n += 1;
+        }
+        specs.push(ServerSpec {
+            id,
+            cmd,
+            args: entry.args,
+            env: entry.env,
+            enabled: !entry.disabled,
+            sandbox: Default::default(),
+            watch: None,
+        });
+    }
+    specs.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(specs)
+}
+
 /// Represents a tool provided by an MCP server.
 ///
 /// Tools are functions that can be called by the LLM to perform actions
@@ -64,6 +301,26 @@ pub struct McpTool {
     pub description: Option<String>,
     /// JSON Schema defining the expected input parameters
     pub input_schema: Value,
+    /// Optional hints about the tool's behaviour
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Hints a server can give about a tool's behaviour.
+///
+/// These are advisory; they refine the capabilities inferred for permission
+/// prompts (see [`permissions::infer_capabilities`]).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolAnnotations {
+    /// Human-readable title of the tool
+    pub title: Option<String>,
+    /// The tool does not modify its environment
+    pub read_only_hint: Option<bool>,
+    /// The tool may perform destructive updates
+    pub destructive_hint: Option<bool>,
+    /// The tool interacts with external systems, e.g. the internet
+    pub open_world_hint: Option<bool>,
 }
 
 /// Associates a tool with its originating server.
@@ -108,4 +365,7 @@ pub struct ToolResultContent {
     pub data: Option<String>,
     /// Reference to a resource (for resource-type content)
     pub resource: Option<Value>,
+    /// URI of a linked resource (for resource_link content)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
 }
@@ -0,0 +1,1026 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin server calling REST APIs described by OpenAPI documents (run on
+//! native only).
+//!
+//! An API is added by the URL of its OpenAPI 3 or Swagger 2 document, in
+//! JSON. Its operations are read from the document once, when the user picks
+//! the ones to offer, and kept in the settings with their parameters, so the
+//! document is not fetched again at startup. Each picked operation becomes a
+//! tool taking the operation's path, query and header parameters, and `body`
+//! for a JSON request body; calls are sent as HTTP requests with the API's
+//! authentication, whose values may reference secrets (see
+//! [`crate::mcp::secrets`]).
+//!
+//! The server is not trusted. Calls that only read, with GET or HEAD, follow
+//! the remembered network permission; other calls ask every time, with the
+//! request shown.
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::mcp::secrets::Secrets;
+
+/// ID of the OpenAPI server.
+pub const OPENAPI_SERVER: &str = "openapi";
+
+/// Longest name of an operation's tool, leaving room for the API's name and
+/// the server ID within providers' 64-character limit.
+const MAX_OPERATION_NAME: usize = 40;
+
+/// How deeply `$ref`s are followed; deeper schemas are left as any object.
+const MAX_REF_DEPTH: usize = 8;
+
+/// Characters of an operation's description kept for its tool.
+const MAX_DESCRIPTION_CHARS: usize = 1_000;
+
+/// Settings of the OpenAPI server.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OpenApiSettings {
+    /// Whether the server is offered to the model
+    #[serde(default)]
+    pub enabled: bool,
+    /// The APIs whose operations are offered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub apis: Vec<OpenApiSource>,
+}
+
+/// An API described by an OpenAPI document.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OpenApiSource {
+    /// Short name, prefixed to the names of the API's tools
+    pub name: String,
+    /// URL of the OpenAPI or Swagger document
+    pub spec_url: String,
+    /// URL the paths of operations are appended to
+    #[serde(default)]
+    pub base_url: String,
+    /// How requests are authenticated
+    #[serde(default)]
+    pub auth: ApiAuth,
+    /// Operations offered as tools
+    #[serde(default)]
+    pub operations: Vec<ApiOperation>,
+}
+
+/// Authentication sent with every request to an API. Values may reference
+/// secrets as `$NAME` or `${NAME}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ApiAuth {
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// HTTP basic authentication
+    Basic { username: String, password: String },
+    /// A header, such as `X-API-Key`
+    Header { name: String, value: String },
+    /// A query parameter, such as `api_key`
+    Query { name: String, value: String },
+}
+
+/// An operation of an API, offered as a tool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiOperation {
+    /// Name of the tool, from the operation's ID or its method and path
+    pub name: String,
+    /// HTTP method, in capitals
+    pub method: String,
+    /// Path, with parameters in braces, e.g. `/pets/{petId}`
+    pub path: String,
+    /// Summary and description from the document
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Vec<ApiParameter>,
+    /// Schema of the JSON request body, if the operation takes one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// Whether the request body is required
+    #[serde(default)]
+    pub body_required: bool,
+}
+
+impl ApiOperation {
+    /// Whether the operation only reads, so calls need no approval each time.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.method.as_str(), "GET" | "HEAD")
+    }
+
+    /// Schema of the tool's arguments: the parameters by name, and `body`.
+    pub fn input_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        for param in &self.parameters {
+            let mut schema = param.schema.clone();
+            if let (Some(description), Some(object)) = (&param.description, schema.as_object_mut())
+            {
+                object
+                    .entry("description")
+                    .or_insert_with(|| Value::String(description.clone()));
+            }
+            properties.insert(param.name.clone(), schema);
+            if param.required {
+                required.push(param.name.clone());
+            }
+        }
+        if let Some(body) = &self.body {
+            properties.insert("body".into(), body.clone());
+            if self.body_required {
+                required.push("body".into());
+            }
+        }
+        json!({"type": "object", "properties": properties, "required": required})
+    }
+}
+
+/// Where a parameter is sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamLocation {
+    Path,
+    Query,
+    Header,
+}
+
+/// A parameter of an operation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiParameter {
+    pub name: String,
+    pub location: ParamLocation,
+    #[serde(default)]
+    pub required: bool,
+    /// JSON schema of the value
+    #[serde(default)]
+    pub schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// What an OpenAPI document describes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiDocument {
+    /// Title of the API
+    pub title: String,
+    /// URL of the document's first server, if it names one
+    pub base_url: Option<String>,
+    /// All operations of the API, in the order of the document
+    pub operations: Vec<ApiOperation>,
+}
+
+/// Fetches an OpenAPI document.
+///
+/// # Arguments
+/// * `url` - URL of the document, in JSON
+///
+/// # Returns
+/// The document, or an error if it could not be fetched or is not JSON
+pub async fn fetch_document(url: &str) -> anyhow::Result<Value> {
+    let response = reqwest::get(url.trim())
+        .await
+        .with_context(|| format!("Could not fetch {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Fetching {url} failed with HTTP {status}");
+    }
+    let text = response.text().await?;
+    serde_json::from_str(&text).map_err(|e| {
+        if text.trim_start().starts_with('{') {
+            anyhow::anyhow!("The document is not valid JSON: {e}")
+        } else {
+            anyhow::anyhow!("The document is not JSON; YAML documents are not supported")
+        }
+    })
+}
+
+/// Reads the operations of an OpenAPI 3 or Swagger 2 document. Local `$ref`s
+/// are inlined; form and file parameters, which cannot be sent as JSON, are
+/// left out.
+///
+/// # Arguments
+/// * `doc` - The document
+/// * `doc_url` - Where the document was fetched from, to resolve a relative
+///   server URL against
+///
+/// # Returns
+/// The API's title, base URL and operations, or an error if the document is
+/// not an OpenAPI document
+pub fn parse_document(doc: &Value, doc_url: &str) -> anyhow::Result<ApiDocument> {
+    let swagger = doc.get("swagger").is_some();
+    if !swagger && doc.get("openapi").is_none() {
+        bail!("Not an OpenAPI or Swagger document");
+    }
+    let title = doc
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .unwrap_or("API")
+        .to_string();
+    let base_url = if swagger {
+        swagger_base_url(doc, doc_url)
+    } else {
+        openapi_base_url(doc, doc_url)
+    };
+
+    let mut operations: Vec<ApiOperation> = vec![];
+    let paths = doc
+        .get("paths")
+        .and_then(Value::as_object)
+        .context("The document has no paths")?;
+    for (path, item) in paths {
+        let item = resolve(doc, item, 0);
+        let shared = item.get("parameters").and_then(Value::as_array);
+        for method in ["get", "put", "post", "delete", "patch", "head", "options"] {
+            let Some(op) = item.get(method) else {
+                continue;
+            };
+            let mut parameters: Vec<ApiParameter> = vec![];
+            let mut body = None;
+            let mut body_required = false;
+            let listed = shared.into_iter().flatten().chain(
+                op.get("parameters")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten(),
+            );
+            for param in listed {
+                let param = resolve(doc, param, 0);
+                let location = param.get("in").and_then(Value::as_str).unwrap_or_default();
+                if location == "body" {
+                    body = param.get("schema").map(|s| inline_refs(doc, s, 0));
+                    body_required = param.get("required") == Some(&Value::Bool(true));
+                    continue;
+                }
+                let Some(parsed) = parse_parameter(doc, &param, swagger) else {
+                    continue;
+                };
+                // Parameters of the operation replace those of its path
+                parameters.retain(|p| !(p.name == parsed.name && p.location == parsed.location));
+                parameters.push(parsed);
+            }
+            if let Some(request) = op.get("requestBody") {
+                let request = resolve(doc, request, 0);
+                let schema = request
+                    .get("content")
+                    .and_then(Value::as_object)
+                    .and_then(|content| content.iter().find(|(mime, _)| mime.contains("json")))
+                    .and_then(|(_, media)| media.get("schema"));
+                if let Some(schema) = schema {
+                    body = Some(inline_refs(doc, schema, 0));
+                    body_required = request.get("required") == Some(&Value::Bool(true));
+                }
+            }
+
+            let mut name = operation_name(op, method, path);
+            let base = name.clone();
+            let mut n = 2;
+            while operations.iter().any(|o| o.name == name) {
+                name = format!("{base}_{n}");
+                n += 1;
+            }
+            operations.push(ApiOperation {
+                name,
+                method: method.to_uppercase(),
+                path: path.clone(),
+                description: operation_description(op),
+                parameters,
+                body,
+                body_required,
+            });
+        }
+    }
+    Ok(ApiDocument {
+        title,
+        base_url,
+        operations,
+    })
+}
+
+/// Reads a path, query or header parameter; others are skipped.
+fn parse_parameter(doc: &Value, param: &Value, swagger: bool) -> Option<ApiParameter> {
+    let location = match param.get("in").and_then(Value::as_str)? {
+        "path" => ParamLocation::Path,
+        "query" => ParamLocation::Query,
+        "header" => ParamLocation::Header,
+        _ => return None,
+    };
+    let schema = if swagger {
+        // Swagger 2 puts the schema's keywords on the parameter itself
+        let mut schema = Map::new();
+        for key in [
+            "type", "format", "items", "enum", "default", "minimum", "maximum",
+        ] {
+            if let Some(value) = param.get(key) {
+                schema.insert(key.into(), inline_refs(doc, value, 0));
+            }
+        }
+        Value::Object(schema)
+    } else {
+        param
+            .get("schema")
+            .map(|s| inline_refs(doc, s, 0))
+            .unwrap_or_else(|| json!({"type": "string"}))
+    };
+    Some(ApiParameter {
+        name: param.get("name").and_then(Value::as_str)?.to_string(),
+        location,
+        required: location == ParamLocation::Path
+            || param.get("required") == Some(&Value::Bool(true)),
+        schema,
+        description: param
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// Name of an operation's tool: its ID, or its method and path, with other
+/// characters than letters, digits, `_` and `-` replaced.
+fn operation_name(op: &Value, method: &str, path: &str) -> String {
+    let raw = match op.get("operationId").and_then(Value::as_str) {
+        Some(id) if !id.trim().is_empty() => id.to_string(),
+        _ => format!("{method}_{path}"),
+    };
+    let mut name = String::new();
+    for c in raw.chars() {
+        let c = if c.is_ascii_alphanumeric() || c == '-' {
+            c
+        } else {
+            '_'
+        };
+        // Runs of `-` would contain the separator of server IDs and tool names
+        if !(matches!(c, '_' | '-') && name.ends_with(c)) {
+            name.push(c);
+        }
+    }
+    let name: String = name
+        .trim_matches(['_', '-'])
+        .chars()
+        .take(MAX_OPERATION_NAME)
+        .collect();
+    if name.is_empty() {
+        method.to_string()
+    } else {
+        name
+    }
+}
+
+/// Summary and description of an operation, shortened.
+fn operation_description(op: &Value) -> String {
+    let parts: Vec<&str> = ["summary", "description"]
+        .iter()
+        .filter_map(|key| op.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let mut description = parts.join("\n\n");
+    if description.chars().count() > MAX_DESCRIPTION_CHARS {
+        description = description.chars().take(MAX_DESCRIPTION_CHARS).collect();
+        description.push('…');
+    }
+    description
+}
+
+/// URL of the first server of an OpenAPI 3 document, with its variables set
+/// to their defaults.
+fn openapi_base_url(doc: &Value, doc_url: &str) -> Option<String> {
+    let server = doc.pointer("/servers/0")?;
+    let mut url = server.get("url")?.as_str()?.to_string();
+    if let Some(variables) = server.get("variables").and_then(Value::as_object) {
+        for (name, variable) in variables {
+            if let Some(default) = variable.get("default").and_then(Value::as_str) {
+                url = url.replace(&format!("{{{name}}}"), default);
+            }
+        }
+    }
+    absolute_url(&url, doc_url)
+}
+
+/// Base URL of a Swagger 2 document, from its scheme, host and base path,
+/// taking those it leaves out from the document's own URL.
+fn swagger_base_url(doc: &Value, doc_url: &str) -> Option<String> {
+    let fetched = reqwest::Url::parse(doc_url).ok();
+    let scheme = doc
+        .pointer("/schemes/0")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| fetched.as_ref().map(|u| u.scheme().to_string()))?;
+    let host = doc
+        .get("host")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            let u = fetched.as_ref()?;
+            Some(match u.port() {
+                Some(port) => format!("{}:{port}", u.host_str()?),
+                None => u.host_str()?.to_string(),
+            })
+        })?;
+    let base_path = doc.get("basePath").and_then(Value::as_str).unwrap_or("");
+    Some(format!("{scheme}://{host}{base_path}"))
+}
+
+/// Resolves a possibly relative URL against the document's URL.
+fn absolute_url(url: &str, doc_url: &str) -> Option<String> {
+    if let Ok(parsed) = reqwest::Url::parse(url) {
+        return Some(parsed.to_string());
+    }
+    let joined = reqwest::Url::parse(doc_url).ok()?.join(url).ok()?;
+    Some(joined.to_string())
+}
+
+/// Follows a local `$ref` to what it points at, or returns the value itself.
+fn resolve(doc: &Value, value: &Value, depth: usize) -> Value {
+    match value.get("$ref").and_then(Value::as_str) {
+        Some(reference) if depth < MAX_REF_DEPTH => match reference.strip_prefix('#') {
+            Some(pointer) => match doc.pointer(pointer) {
+                Some(target) => resolve(doc, target, depth + 1),
+                None => json!({}),
+            },
+            // References to other documents are not fetched
+            None => json!({}),
+        },
+        Some(_) => json!({"type": "object"}),
+        None => value.clone(),
+    }
+}
+
+/// Replaces the local `$ref`s in a schema with what they point at. Only
+/// [`MAX_REF_DEPTH`] references are followed in a row, which also ends
+/// recursive schemas.
+///
+/// # Arguments
+/// * `depth` - How many references were followed to get here
+fn inline_refs(doc: &Value, schema: &Value, depth: usize) -> Value {
+    if schema.get("$ref").is_some() {
+        if depth >= MAX_REF_DEPTH {
+            return json!({"type": "object"});
+        }
+        return inline_refs(doc, &resolve(doc, schema, 0), depth + 1);
+    }
+    match schema {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), inline_refs(doc, value, depth)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| inline_refs(doc, item, depth))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Name of an operation's tool, prefixed with its API's name.
+pub fn tool_name(api: &OpenApiSource, operation: &ApiOperation) -> String {
+    format!("{}_{}", api.name, operation.name)
+}
+
+/// Creates the OpenAPI server for the settings.
+///
+/// # Arguments
+/// * `settings` - Settings of the OpenAPI server
+/// * `secrets` - Secrets the authentication settings may refer to
+///
+/// # Returns
+/// The server, or `None` if it is turned off or offers no operations
+#[cfg(not(target_arch = "wasm32"))]
+pub fn openapi_server(
+    settings: &OpenApiSettings,
+    secrets: &Secrets,
+) -> Option<Box<dyn super::host::MCPServer>> {
+    if !settings.enabled || settings.apis.iter().all(|a| a.operations.is_empty()) {
+        return None;
+    }
+    Some(Box::new(native::OpenApiMcpServer::new(
+        settings.clone(),
+        secrets.clone(),
+    )))
+}
+
+/// Creates the OpenAPI server for the settings.
+///
+/// # Returns
+/// `None`, as calling APIs from the browser is blocked by CORS
+#[cfg(target_arch = "wasm32")]
+pub fn openapi_server(
+    settings: &OpenApiSettings,
+    _secrets: &Secrets,
+) -> Option<Box<dyn super::host::MCPServer>> {
+    if settings.enabled {
+        dioxus::logger::tracing::warn!("The OpenAPI server needs the desktop app");
+    }
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::time::Duration;
+
+    use anyhow::{Context, bail};
+    use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+    use serde_json::Value;
+
+    use super::{ApiAuth, ApiOperation, OpenApiSettings, OpenApiSource, ParamLocation, tool_name};
+    use crate::mcp::{
+        McpTool, ToolAnnotations, ToolResult, ToolResultContent,
+        host::MCPServer,
+        secrets::{Secrets, resolve_secrets},
+    };
+
+    /// Seconds a request may take before it is abandoned.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Characters of a response kept for the tool result.
+    const MAX_RESPONSE_CHARS: usize = 100_000;
+
+    /// Bytes of a response read; the rest is not downloaded. Enough for
+    /// [`MAX_RESPONSE_CHARS`] characters of any UTF-8 text.
+    const MAX_RESPONSE_BYTES: usize = 4 * MAX_RESPONSE_CHARS;
+
+    /// Characters encoded in path parameters: all but the unreserved ones. Values
+    /// that are `.` or `..` are refused, as URLs treat them as moves through
+    /// the path even when encoded.
+    const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+        .remove(b'-')
+        .remove(b'.')
+        .remove(b'_')
+        .remove(b'~');
+
+    /// Builtin MCP server calling the picked operations of APIs.
+    pub struct OpenApiMcpServer {
+        settings: OpenApiSettings,
+        client: reqwest::Client,
+        /// Secrets of the active profile, as loaded when the server was created
+        secrets: Secrets,
+    }
+
+    /// A request built from a tool call.
+    #[derive(Debug)]
+    struct ApiRequest {
+        method: reqwest::Method,
+        url: reqwest::Url,
+        headers: Vec<(String, String)>,
+        body: Option<Value>,
+    }
+
+    impl ApiRequest {
+        /// The request as shown for approval, without authentication.
+        fn describe(&self) -> String {
+            let mut text = format!("{} {}", self.method, self.url);
+            if let Some(body) = &self.body {
+                text.push('\n');
+                text.push_str(&serde_json::to_string_pretty(body).unwrap_or_default());
+            }
+            text
+        }
+    }
+
+    /// A parameter's value as text: strings as they are, others as JSON.
+    fn value_text(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    impl OpenApiMcpServer {
+        /// Creates a server with the given settings, resolving authentication
+        /// with the given secrets.
+        pub fn new(settings: OpenApiSettings, secrets: Secrets) -> Self {
+            let client = reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default();
+            Self {
+                settings,
+                client,
+                secrets,
+            }
+        }
+
+        /// Finds the API and operation of a tool.
+        fn find(&self, name: &str) -> Option<(&OpenApiSource, &ApiOperation)> {
+            self.settings.apis.iter().find_map(|api| {
+                api.operations
+                    .iter()
+                    .find(|op| tool_name(api, op) == name)
+                    .map(|op| (api, op))
+            })
+        }
+
+        /// Builds the request for a call, without authentication.
+        fn request(
+            api: &OpenApiSource,
+            op: &ApiOperation,
+            arguments: &Value,
+        ) -> anyhow::Result<ApiRequest> {
+            if api.base_url.trim().is_empty() {
+                bail!("{} has no base URL; set one in the settings", api.name);
+            }
+            let mut path = op.path.clone();
+            let mut query = vec![];
+            let mut headers = vec![];
+            for param in &op.parameters {
+                let Some(value) = arguments.get(&param.name).filter(|v| !v.is_null()) else {
+                    if param.required {
+                        bail!("`{}` is required", param.name);
+                    }
+                    continue;
+                };
+                match param.location {
+                    ParamLocation::Path => {
+                        let text = value_text(value);
+                        if matches!(text.as_str(), "" | "." | "..") {
+                            bail!("`{}` may not be {text:?}", param.name);
+                        }
+                        let encoded = utf8_percent_encode(&text, PATH_SEGMENT);
+                        path = path.replace(&format!("{{{}}}", param.name), &encoded.to_string());
+                    }
+                    ParamLocation::Query => match value {
+                        // Arrays are sent as repeated parameters
+                        Value::Array(items) => {
+                            query.extend(items.iter().map(|i| (param.name.clone(), value_text(i))))
+                        }
+                        _ => query.push((param.name.clone(), value_text(value))),
+                    },
+                    ParamLocation::Header => headers.push((param.name.clone(), value_text(value))),
+                }
+            }
+            let body = arguments.get("body").filter(|v| !v.is_null()).cloned();
+            if op.body_required && body.is_none() {
+                bail!("`body` is required");
+            }
+            let mut url = reqwest::Url::parse(&format!(
+                "{}{path}",
+                api.base_url.trim().trim_end_matches('/')
+            ))
+            .with_context(|| format!("Invalid URL for {}", op.path))?;
+            if !query.is_empty() {
+                url.query_pairs_mut().extend_pairs(query);
+            }
+            Ok(ApiRequest {
+                method: reqwest::Method::from_bytes(op.method.as_bytes())?,
+                url,
+                headers,
+                body: if op.body.is_some() { body } else { None },
+            })
+        }
+
+        /// Sends a request with the API's authentication.
+        ///
+        /// # Returns
+        /// The status line and response body, and whether the request failed
+        async fn send(
+            &self,
+            auth: &ApiAuth,
+            mut request: ApiRequest,
+            secrets: &Secrets,
+        ) -> anyhow::Result<(String, bool)> {
+            let resolve = |text: &str| resolve_secrets(text, secrets);
+            if let ApiAuth::Query { name, value } = auth {
+                request
+                    .url
+                    .query_pairs_mut()
+                    .append_pair(name, &resolve(value)?);
+            }
+            let mut builder = self.client.request(request.method, request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            builder = match auth {
+                ApiAuth::None | ApiAuth::Query { .. } => builder,
+                ApiAuth::Bearer { token } => builder.bearer_auth(resolve(token)?),
+                ApiAuth::Basic { username, password } => {
+                    builder.basic_auth(resolve(username)?, Some(resolve(password)?))
+                }
+                ApiAuth::Header { name, value } => builder.header(name, resolve(value)?),
+            };
+            if let Some(body) = &request.body {
+                builder = builder.json(body);
+            }
+            let mut response = builder.send().await?;
+            let status = response.status();
+            // Read up to the limit, so a large response is not downloaded whole
+            let mut body = vec![];
+            let mut complete = true;
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > MAX_RESPONSE_BYTES {
+                    body.extend_from_slice(&chunk[..MAX_RESPONSE_BYTES - body.len()]);
+                    complete = false;
+                    break;
+                }
+                body.extend_from_slice(&chunk);
+            }
+            let mut text = String::from_utf8_lossy(&body).into_owned();
+            if !complete || text.chars().count() > MAX_RESPONSE_CHARS {
+                text = text.chars().take(MAX_RESPONSE_CHARS).collect();
+                text.push_str("\n[response truncated]");
+            }
+            Ok((format!("HTTP {status}\n\n{text}"), !status.is_success()))
+        }
+
+        /// Calls an operation.
+        ///
+        /// # Returns
+        /// The response, and whether the call failed
+        async fn call(&self, name: &str, arguments: &Value) -> anyhow::Result<(String, bool)> {
+            let (api, op) = self
+                .find(name)
+                .with_context(|| format!("Unknown tool: {name}"))?;
+            let request = Self::request(api, op, arguments)?;
+            self.send(&api.auth, request, &self.secrets).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MCPServer for OpenApiMcpServer {
+        async fn list_tools(&self) -> Vec<McpTool> {
+            self.settings
+                .apis
+                .iter()
+                .flat_map(|api| {
+                    api.operations.iter().map(move |op| {
+                        let mut description = format!("{} {} ({})", op.method, op.path, api.name);
+                        if !op.description.is_empty() {
+                            description = format!("{}\n\n{description}", op.description);
+                        }
+                        McpTool {
+                            name: tool_name(api, op),
+                            description: Some(description),
+                            input_schema: op.input_schema(),
+                            annotations: Some(ToolAnnotations {
+                                title: None,
+                                read_only_hint: Some(op.is_read_only()),
+                                destructive_hint: Some(op.method == "DELETE"),
+                                open_world_hint: Some(true),
+                            }),
+                        }
+                    })
+                })
+                .collect()
+        }
+
+        /// Handles tool calls by sending the operation's request.
+        async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+            if method != "tools/call" {
+                bail!("Error: unknown RPC method {method}");
+            }
+            let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let (text, failed) = match self.call(name, &args).await {
+                Ok(result) => result,
+                Err(e) => (format!("Error: {e:#}"), true),
+            };
+            Ok(serde_json::to_value(ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".into(),
+                    text: Some(text),
+                    ..Default::default()
+                }],
+                is_error: failed.then_some(true),
+            })?)
+        }
+
+        /// Calls reach the network, and only with the user's permission.
+        fn is_trusted(&self) -> bool {
+            false
+        }
+
+        /// Shows the request of each call that may change something; reads
+        /// follow the remembered network permission.
+        fn approval_details(&self, tool_name: &str, arguments: &Value) -> Option<String> {
+            let (api, op) = self.find(tool_name)?;
+            if op.is_read_only() {
+                return None;
+            }
+            Some(match Self::request(api, op, arguments) {
+                Ok(request) => request.describe(),
+                Err(e) => format!("{} {}\nInvalid arguments: {e:#}", op.method, op.path),
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde_json::json;
+
+        use super::*;
+        use crate::mcp::openapi::ApiParameter;
+
+        fn api(operation: ApiOperation) -> OpenApiSource {
+            OpenApiSource {
+                name: "pets".into(),
+                base_url: "https://api.example.com/v1/".into(),
+                operations: vec![operation],
+                ..Default::default()
+            }
+        }
+
+        fn param(name: &str, location: ParamLocation, required: bool) -> ApiParameter {
+            ApiParameter {
+                name: name.into(),
+                location,
+                required,
+                schema: json!({"type": "string"}),
+                description: None,
+            }
+        }
+
+        fn get_pet() -> ApiOperation {
+            ApiOperation {
+                name: "getPet".into(),
+                method: "GET".into(),
+                path: "/pets/{petId}".into(),
+                description: String::new(),
+                parameters: vec![
+                    param("petId", ParamLocation::Path, true),
+                    param("tags", ParamLocation::Query, false),
+                    param("X-Trace", ParamLocation::Header, false),
+                ],
+                body: None,
+                body_required: false,
+            }
+        }
+
+        #[test]
+        fn maps_parameters_to_their_locations() {
+            let op = get_pet();
+            let args = json!({"petId": "a b/c", "tags": ["x", "y"], "X-Trace": 7});
+            let request = OpenApiMcpServer::request(&api(op.clone()), &op, &args).unwrap();
+            assert_eq!(
+                request.url.as_str(),
+                "https://api.example.com/v1/pets/a%20b%2Fc?tags=x&tags=y"
+            );
+            assert_eq!(
+                request.headers,
+                vec![("X-Trace".to_string(), "7".to_string())]
+            );
+            assert!(request.body.is_none());
+        }
+
+        #[test]
+        fn refuses_dot_segments_in_path_parameters() {
+            let op = get_pet();
+            for value in [".", "..", ""] {
+                let args = json!({ "petId": value });
+                assert!(OpenApiMcpServer::request(&api(op.clone()), &op, &args).is_err());
+            }
+            let args = json!({"petId": "rex.json"});
+            let request = OpenApiMcpServer::request(&api(op.clone()), &op, &args).unwrap();
+            assert_eq!(request.url.path(), "/v1/pets/rex.json");
+        }
+
+        #[test]
+        fn requires_required_parameters_and_body() {
+            let op = get_pet();
+            let missing = OpenApiMcpServer::request(&api(op.clone()), &op, &json!({}));
+            assert!(missing.unwrap_err().to_string().contains("petId"));
+
+            let add = ApiOperation {
+                name: "addPet".into(),
+                method: "POST".into(),
+                path: "/pets".into(),
+                description: String::new(),
+                parameters: vec![],
+                body: Some(json!({"type": "object"})),
+                body_required: true,
+            };
+            assert!(OpenApiMcpServer::request(&api(add.clone()), &add, &json!({})).is_err());
+            let body = json!({"name": "Rex"});
+            let request =
+                OpenApiMcpServer::request(&api(add.clone()), &add, &json!({ "body": body }))
+                    .unwrap();
+            assert_eq!(request.method, reqwest::Method::POST);
+            assert_eq!(request.body, Some(body));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_openapi_3_documents() {
+        let doc = json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Petstore"},
+            "servers": [{"url": "/api/{version}", "variables": {"version": {"default": "v3"}}}],
+            "paths": {
+                "/pets/{petId}": {
+                    "parameters": [{"name": "petId", "in": "path", "schema": {"type": "integer"}}],
+                    "get": {
+                        "operationId": "getPetById",
+                        "summary": "Find a pet",
+                        "parameters": [
+                            {"name": "verbose", "in": "query", "required": true,
+                             "schema": {"type": "boolean"}},
+                            {"name": "session", "in": "cookie"}
+                        ]
+                    },
+                    "put": {
+                        "requestBody": {
+                            "required": true,
+                            "content": {"application/json": {
+                                "schema": {"$ref": "#/components/schemas/Pet"}
+                            }}
+                        }
+                    }
+                }
+            },
+            "components": {"schemas": {"Pet": {
+                "type": "object",
+                "properties": {"name": {"type": "string"}}
+            }}}
+        });
+        let api = parse_document(&doc, "https://pets.example.com/openapi.json").unwrap();
+        assert_eq!(api.title, "Petstore");
+        assert_eq!(
+            api.base_url.as_deref(),
+            Some("https://pets.example.com/api/v3")
+        );
+        assert_eq!(api.operations.len(), 2);
+
+        let get = &api.operations[0];
+        assert_eq!(
+            (get.name.as_str(), get.method.as_str()),
+            ("getPetById", "GET")
+        );
+        assert_eq!(get.description, "Find a pet");
+        // The path's parameter is shared, and the cookie is left out
+        let names: Vec<&str> = get.parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["petId", "verbose"]);
+        assert!(get.parameters.iter().all(|p| p.required));
+        assert_eq!(get.input_schema()["required"], json!(["petId", "verbose"]));
+
+        let put = &api.operations[1];
+        assert_eq!(put.name, "put_pets_petId");
+        assert!(put.body_required);
+        assert_eq!(
+            put.body.as_ref().unwrap()["properties"]["name"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn parses_swagger_2_documents() {
+        let doc = json!({
+            "swagger": "2.0",
+            "info": {"title": "Store"},
+            "basePath": "/v2",
+            "paths": {
+                "/orders": {
+                    "post": {
+                        "operationId": "place order",
+                        "parameters": [
+                            {"name": "order", "in": "body", "schema": {"type": "object"}},
+                            {"name": "ids", "in": "query", "type": "array",
+                             "items": {"type": "string"}}
+                        ]
+                    },
+                    "get": {"operationId": "place order"}
+                }
+            }
+        });
+        let api = parse_document(&doc, "http://localhost:8080/swagger.json").unwrap();
+        assert_eq!(api.base_url.as_deref(), Some("http://localhost:8080/v2"));
+        let names: Vec<&str> = api.operations.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["place_order", "place_order_2"]);
+
+        let post = api.operations.iter().find(|o| o.method == "POST").unwrap();
+        assert_eq!(post.body, Some(json!({"type": "object"})));
+        assert!(!post.body_required);
+        assert_eq!(
+            post.parameters[0].schema,
+            json!({"type": "array", "items": {"type": "string"}})
+        );
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(parse_document(&json!({"info": {}}), "https://example.com").is_err());
+    }
+
+    #[test]
+    fn ends_recursive_references() {
+        let doc = json!({"definitions": {"Node": {
+            "type": "object",
+            "properties": {"next": {"$ref": "#/definitions/Node"}}
+        }}});
+        let schema = inline_refs(&doc, &json!({"$ref": "#/definitions/Node"}), 0);
+        let mut depth = 0;
+        let mut node = &schema;
+        while let Some(next) = node.pointer("/properties/next") {
+            node = next;
+            depth += 1;
+        }
+        assert_eq!(depth, MAX_REF_DEPTH);
+        assert_eq!(node, &json!({"type": "object"}));
+    }
+}
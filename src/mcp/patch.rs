@@ -0,0 +1,369 @@
+// Copyright © 2025 Nipun Kumar
+
+//! File edits proposed by the model, reviewed hunk by hunk before they are
+//! applied.
+//!
+//! A [`Patch`] holds a file's text before and after an edit and splits the
+//! difference into hunks. Tools that edit files call [`review`] and wait while
+//! the chat shows the patch; the user accepts or rejects each hunk, and only
+//! the accepted ones are applied with [`Patch::apply`]. Like the job list in
+//! [`crate::jobs`], patches awaiting review are kept in memory and the UI
+//! [`subscribe`]s to be woken when they change. Each patch belongs to the
+//! review session of the chat that proposed it, from [`new_session`], so a
+//! chat only shows its own patches.
+
+use std::sync::{
+    LazyLock, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use similar::{DiffOp, TextDiff};
+use tokio::sync::{oneshot, watch};
+
+/// Unchanged lines shown around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Where to send the answer to a review: whether each hunk was accepted, or
+/// `None` if the patch was rejected
+type Answer = oneshot::Sender<Option<Vec<bool>>>;
+
+/// Patches awaiting review, oldest first, with their review session and where
+/// to send the answer
+static PENDING: Mutex<Vec<(u64, Patch, Answer)>> = Mutex::new(Vec::new());
+/// Counter used to generate patch IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+/// Counter used to generate review session IDs
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(1);
+/// Bumped whenever the pending patches change
+static CHANGED: LazyLock<watch::Sender<u64>> = LazyLock::new(|| watch::Sender::new(0));
+
+/// A change to one file, split into hunks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    pub id: u64,
+    /// Path of the file, relative to the project
+    pub path: String,
+    /// Why the model made the edit
+    pub summary: String,
+    /// Text of the file before the edit; `None` if it does not exist yet
+    pub before: Option<String>,
+    /// Text of the file after the edit
+    pub after: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A group of nearby changed lines, with some unchanged lines around them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    /// Unified diff of the hunk, starting with its `@@` header
+    pub diff: String,
+    /// Lines removed and added, for summaries
+    pub removed: usize,
+    pub added: usize,
+}
+
+impl Patch {
+    /// Computes the patch turning one text into another.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file, relative to the project
+    /// * `summary` - Why the model made the edit
+    /// * `before` - Text of the file, or `None` for a new file
+    /// * `after` - Text of the file after the edit
+    pub fn new(
+        path: impl Into<String>,
+        summary: impl Into<String>,
+        before: Option<String>,
+        after: String,
+    ) -> Self {
+        let old = before.as_deref().unwrap_or("");
+        let diff = TextDiff::from_lines(old, &after);
+        let hunks = diff
+            .grouped_ops(CONTEXT_LINES)
+            .iter()
+            .map(|ops| hunk(&diff, ops))
+            .collect();
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            path: path.into(),
+            summary: summary.into(),
+            before,
+            after,
+            hunks,
+        }
+    }
+
+    /// The file's text with only some hunks applied.
+    ///
+    /// # Arguments
+    /// * `accepted` - Whether each hunk is applied, in order
+    pub fn apply(&self, accepted: &[bool]) -> String {
+        let old = self.before.as_deref().unwrap_or("");
+        let diff = TextDiff::from_lines(old, &self.after);
+        let old_lines = diff.old_slices();
+        let new_lines = diff.new_slices();
+        let groups = diff.grouped_ops(CONTEXT_LINES);
+
+        let mut text = String::new();
+        let mut group = 0;
+        for op in diff.ops() {
+            let (_, old_range, new_range) = op.as_tag_tuple();
+            if matches!(op, DiffOp::Equal { .. }) {
+                text.extend(old_lines[old_range].iter().copied());
+                continue;
+            }
+            // Hunks hold the changes in order, so a change after the last
+            // one of its hunk belongs to the next
+            while group + 1 < groups.len() && !groups[group].contains(op) {
+                group += 1;
+            }
+            if accepted.get(group).copied().unwrap_or(false) {
+                text.extend(new_lines[new_range].iter().copied());
+            } else {
+                text.extend(old_lines[old_range].iter().copied());
+            }
+        }
+        text
+    }
+
+    /// Unified diff of the whole patch, with file headers.
+    pub fn unified_diff(&self) -> String {
+        let old = if self.before.is_some() {
+            format!("a/{}", self.path)
+        } else {
+            "/dev/null".to_string()
+        };
+        let mut text = format!("--- {old}\n+++ b/{}\n", self.path);
+        for hunk in &self.hunks {
+            text.push_str(&hunk.diff);
+        }
+        text
+    }
+}
+
+/// The hunk for a group of diff operations.
+fn hunk<'a>(diff: &TextDiff<'a, 'a, 'a, str>, ops: &[DiffOp]) -> Hunk {
+    let (Some(first), Some(last)) = (ops.first(), ops.last()) else {
+        return Hunk {
+            diff: String::new(),
+            removed: 0,
+            added: 0,
+        };
+    };
+    let old_start = first.old_range().start;
+    let new_start = first.new_range().start;
+    let old_len = last.old_range().end - old_start;
+    let new_len = last.new_range().end - new_start;
+    let mut text = format!(
+        "@@ -{},{old_len} +{},{new_len} @@\n",
+        old_start + 1,
+        new_start + 1
+    );
+    let (mut removed, mut added) = (0, 0);
+    for op in ops {
+        for change in diff.iter_changes(op) {
+            let sign = match change.tag() {
+                similar::ChangeTag::Equal => ' ',
+                similar::ChangeTag::Delete => {
+                    removed += 1;
+                    '-'
+                }
+                similar::ChangeTag::Insert => {
+                    added += 1;
+                    '+'
+                }
+            };
+            text.push(sign);
+            text.push_str(change.value());
+            if change.missing_newline() {
+                text.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    Hunk {
+        diff: text,
+        removed,
+        added,
+    }
+}
+
+/// Starts a review session, for the patches proposed in one chat.
+///
+/// # Returns
+/// The session's ID
+pub fn new_session() -> u64 {
+    NEXT_SESSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Shows a patch for review and waits for the user's answer.
+///
+/// # Arguments
+/// * `session` - Review session of the chat proposing the patch
+/// * `patch` - The patch
+///
+/// # Returns
+/// Whether each hunk was accepted, or `None` if the whole patch was rejected
+/// or the review was abandoned
+pub async fn review(session: u64, patch: Patch) -> Option<Vec<bool>> {
+    let id = patch.id;
+    let (tx, rx) = oneshot::channel();
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.push((session, patch, tx));
+    }
+    changed();
+    let _withdraw = Withdraw(id);
+    rx.await.ok().flatten()
+}
+
+/// Removes a patch from review when its tool call is abandoned before the
+/// answer, e.g. because the turn was stopped.
+struct Withdraw(u64);
+
+impl Drop for Withdraw {
+    fn drop(&mut self) {
+        let removed = PENDING.lock().is_ok_and(|mut pending| {
+            let len = pending.len();
+            pending.retain(|(_, p, _)| p.id != self.0);
+            pending.len() < len
+        });
+        if removed {
+            changed();
+        }
+    }
+}
+
+/// Answers the review of a pending patch.
+///
+/// # Arguments
+/// * `id` - ID of the patch
+/// * `accepted` - Whether each hunk is accepted, or `None` to reject the patch
+pub fn answer(id: u64, accepted: Option<Vec<bool>>) {
+    let answered = PENDING.lock().ok().and_then(|mut pending| {
+        let i = pending.iter().position(|(_, p, _)| p.id == id)?;
+        Some(pending.remove(i))
+    });
+    if let Some((_, _, tx)) = answered {
+        let _ = tx.send(accepted);
+    }
+    changed();
+}
+
+/// Returns the patches of a review session awaiting review, oldest first.
+///
+/// # Arguments
+/// * `session` - The review session
+pub fn pending(session: u64) -> Vec<Patch> {
+    PENDING
+        .lock()
+        .map(|pending| {
+            pending
+                .iter()
+                .filter(|(s, _, _)| *s == session)
+                .map(|(_, p, _)| p.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns a receiver that is notified whenever the pending patches change.
+pub fn subscribe() -> watch::Receiver<u64> {
+    CHANGED.subscribe()
+}
+
+fn changed() {
+    CHANGED.send_modify(|n| *n += 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lines `1` to `n`, each ending with a line break.
+    fn lines(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("{i}\n")).collect()
+    }
+
+    /// A patch changing the given lines (counted from 1) of `n` lines.
+    fn patch_changing(n: usize, changed: &[usize]) -> Patch {
+        let before = lines(n);
+        let mut after = before.clone();
+        for &line in changed {
+            after[line - 1] = format!("changed {line}\n");
+        }
+        Patch::new("f.txt", "", Some(before.concat()), after.concat())
+    }
+
+    #[test]
+    fn applies_only_the_accepted_hunks() {
+        let patch = patch_changing(20, &[2, 18]);
+        assert_eq!(patch.hunks.len(), 2);
+
+        let mut expected = lines(20);
+        expected[1] = "changed 2\n".into();
+        assert_eq!(patch.apply(&[true, false]), expected.concat());
+
+        let mut expected = lines(20);
+        expected[17] = "changed 18\n".into();
+        assert_eq!(patch.apply(&[false, true]), expected.concat());
+
+        assert_eq!(patch.apply(&[true, true]), patch.after);
+    }
+
+    #[test]
+    fn rejecting_all_hunks_keeps_the_file() {
+        let patch = patch_changing(20, &[2, 18]);
+        assert_eq!(patch.apply(&[false, false]), patch.before.clone().unwrap());
+        // Missing answers count as rejections
+        assert_eq!(patch.apply(&[]), patch.before.clone().unwrap());
+    }
+
+    #[test]
+    fn applies_hunks_next_to_each_other_separately() {
+        // Just enough unchanged lines between the changes for two hunks
+        let gap = 2 * CONTEXT_LINES + 1;
+        let patch = patch_changing(gap + 4, &[2, gap + 3]);
+        assert_eq!(patch.hunks.len(), 2);
+
+        let mut expected = lines(gap + 4);
+        expected[gap + 2] = format!("changed {}\n", gap + 3);
+        assert_eq!(patch.apply(&[false, true]), expected.concat());
+
+        // Changes closer together make a single hunk
+        let patch = patch_changing(gap + 3, &[2, gap + 2]);
+        assert_eq!(patch.hunks.len(), 1);
+        assert_eq!(patch.apply(&[true]), patch.after);
+    }
+
+    #[test]
+    fn keeps_a_missing_final_line_break() {
+        let before = "one\ntwo\nthree";
+        let patch = Patch::new("f.txt", "", Some(before.into()), "one\ntwo\n3".into());
+        assert_eq!(patch.hunks.len(), 1);
+        assert!(patch.hunks[0].diff.contains("\\ No newline at end of file"));
+        assert_eq!(patch.apply(&[true]), "one\ntwo\n3");
+        assert_eq!(patch.apply(&[false]), before);
+    }
+
+    #[test]
+    fn creates_a_new_file() {
+        let patch = Patch::new("f.txt", "", None, "new\n".into());
+        assert_eq!(patch.apply(&[true]), "new\n");
+        assert_eq!(patch.apply(&[false]), "");
+        assert!(patch.unified_diff().starts_with("--- /dev/null\n"));
+    }
+
+    #[tokio::test]
+    async fn shows_patches_only_in_their_session() {
+        let (ours, theirs) = (new_session(), new_session());
+        let patch = Patch::new("f.txt", "", None, "new\n".into());
+        let id = patch.id;
+        let review = tokio::spawn(review(ours, patch));
+        tokio::task::yield_now().await;
+        assert_eq!(pending(ours).len(), 1);
+        assert!(pending(theirs).is_empty());
+
+        answer(id, Some(vec![true]));
+        assert_eq!(review.await.unwrap(), Some(vec![true]));
+        assert!(pending(ours).is_empty());
+    }
+}
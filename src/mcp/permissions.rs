@@ -0,0 +1,173 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Permission model for tools of external MCP servers.
+//!
+//! Each tool is mapped to the [`Capability`] categories it appears to need,
+//! inferred from its name and annotations. The first time a server
+//! calls a tool needing a capability it has no decision for, the host asks the
+//! user through a [`PermissionPrompter`]. "Always" and "deny" answers are kept
+//! as [`Decision`]s in the settings; "once" answers are not remembered.
+//!
+//! Some tools, such as those running shell commands, need approval for every
+//! call: the user is shown what the call does and asked each time, and no
+//! answer is remembered.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mcp::McpTool;
+
+/// A category of action a tool can take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Accessing the network, e.g. fetching URLs or calling web APIs
+    Network,
+    /// Creating, modifying or deleting files
+    FileWrite,
+    /// Running shell commands or other programs
+    ShellExec,
+}
+
+impl Capability {
+    /// Describes the capability for prompts, e.g. "access the network".
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::Network => "access the network",
+            Capability::FileWrite => "write files",
+            Capability::ShellExec => "run shell commands",
+        }
+    }
+
+    /// Short name for summaries.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Capability::Network => "Network",
+            Capability::FileWrite => "File write",
+            Capability::ShellExec => "Shell exec",
+        }
+    }
+}
+
+/// A remembered answer to a permission prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Allow,
+    Deny,
+}
+
+/// The user's answer to a permission prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionChoice {
+    /// Allow this call only
+    Once,
+    /// Allow this and future calls needing the capability
+    Always,
+    /// Refuse this and future calls needing the capability
+    Deny,
+}
+
+/// Remembered decisions, by server ID and capability.
+pub type Permissions = BTreeMap<String, BTreeMap<Capability, Decision>>;
+
+/// A tool call waiting for the user's permission.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionRequest {
+    pub server_id: String,
+    pub tool_name: String,
+    pub capability: Capability,
+    /// What the call does, such as the command it runs, for calls approved one
+    /// at a time
+    pub details: Option<String>,
+    /// Whether the answer may be remembered for later calls
+    pub remember: bool,
+}
+
+impl PermissionRequest {
+    /// Describes the request as a question for the user.
+    pub fn question(&self) -> String {
+        format!(
+            "The MCP server \"{}\" wants to {} (tool `{}`). Allow it?",
+            self.server_id,
+            self.capability.description(),
+            self.tool_name
+        )
+    }
+}
+
+/// Asks the user whether a tool call may go ahead.
+#[async_trait::async_trait]
+pub trait PermissionPrompter: Send + Sync {
+    /// Asks about a single capability and waits for the answer.
+    async fn ask(&self, request: PermissionRequest) -> PermissionChoice;
+}
+
+/// Words in tool names suggesting each capability.
+const NETWORK_WORDS: &[&str] = &[
+    "fetch", "http", "url", "download", "upload", "browse", "browser", "navigate", "web",
+    "request", "curl", "api", "email", "send",
+];
+const FILE_WRITE_WORDS: &[&str] = &[
+    "write", "edit", "create", "delete", "remove", "move", "rename", "mkdir", "save", "patch",
+    "append", "update", "commit", "push",
+];
+const SHELL_WORDS: &[&str] = &[
+    "shell", "exec", "execute", "bash", "command", "terminal", "run", "spawn", "script",
+];
+
+/// Infers which capabilities a tool needs.
+///
+/// Tool names are split into words (`write_file`, `runCommand`) and matched
+/// against keyword lists. Explicit annotations refine the result: a tool marked
+/// read-only never needs file writes, and a tool marked as open-world (talking to
+/// external systems) needs network access.
+///
+/// # Arguments
+/// * `tool` - Tool definition from the server
+///
+/// # Returns
+/// The capabilities, in a stable order
+pub fn infer_capabilities(tool: &McpTool) -> Vec<Capability> {
+    let words = name_words(&tool.name);
+    let has = |list: &[&str]| words.iter().any(|w| list.contains(&w.as_str()));
+
+    let mut caps = vec![];
+    let annotations = tool.annotations.clone().unwrap_or_default();
+    if has(NETWORK_WORDS) || annotations.open_world_hint == Some(true) {
+        caps.push(Capability::Network);
+    }
+    if has(FILE_WRITE_WORDS) && annotations.read_only_hint != Some(true) {
+        caps.push(Capability::FileWrite);
+    }
+    if has(SHELL_WORDS) {
+        caps.push(Capability::ShellExec);
+    }
+    caps
+}
+
+/// Splits a tool name into lowercase words at `_`, `-`, `.` and camelCase boundaries.
+fn name_words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
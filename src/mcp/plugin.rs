@@ -0,0 +1,591 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin servers provided by user-installed WASM plugins.
+//!
+//! A plugin is a WebAssembly module run in a sandbox (native only). It can only
+//! reach the outside world through the host functions the user granted it in
+//! settings, so it can add tools to the app without the app being forked and
+//! without the trust given to a server process.
+//!
+//! # Plugin interface
+//!
+//! Strings cross the boundary as UTF-8 JSON in the plugin's memory. A string
+//! handed back is a pointer and a length packed into one `i64`, pointer in the
+//! high 32 bits; the host never frees memory the plugin hands back.
+//!
+//! The module exports:
+//! - `memory`
+//! - `mcp_alloc(len: i32) -> i32`: room for a string the host passes in
+//! - `mcp_list_tools() -> i64`: the plugin's tools, as an array of MCP tools
+//! - `mcp_call_tool(ptr: i32, len: i32) -> i64`: runs the call
+//!   `{"name", "arguments"}` and returns an MCP tool result
+//!
+//! It may import, from the `mcmcpcp` module:
+//! - `fetch(ptr: i32, len: i32) -> i64`: sends `{"url", "method", "headers",
+//!   "body"}` and returns `{"status", "body"}` (needs the fetch capability)
+//! - `storage_get(ptr: i32, len: i32) -> i64`: returns the value stored under
+//!   a key, or `null` (needs the storage capability)
+//! - `storage_set(ptr: i32, len: i32) -> i64`: stores `{"key", "value"}` and
+//!   returns `null` (needs the storage capability)
+//! - `log(ptr: i32, len: i32)`: writes a message to the app's log
+//!
+//! Host functions return `{"error": "..."}` when they fail or the capability
+//! was not granted. Requests may only go to the hosts listed with the fetch
+//! capability, or to any host if `*` is listed, and redirects are only
+//! followed to those hosts. Strings passed either way may
+//! be at most 16 MiB long. Storage is kept as the server's state, so it is saved and
+//! restored with the state of other servers.
+
+use serde::{Deserialize, Serialize};
+
+/// A WASM plugin in settings, and the capabilities granted to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginSpec {
+    /// Unique identifier, used as the server ID of the plugin's tools
+    pub id: String,
+    /// Path of the `.wasm` file
+    pub path: String,
+    /// Whether the plugin is loaded
+    #[serde(default = "super::default_enabled")]
+    pub enabled: bool,
+    /// Host functions the plugin may use
+    #[serde(default)]
+    pub capabilities: PluginCapabilities,
+}
+
+/// Host functions granted to a plugin. Without any, a plugin can only compute.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PluginCapabilities {
+    /// Make HTTP requests
+    #[serde(default)]
+    pub fetch: bool,
+    /// Hosts requests may go to, `*` for any; none if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fetch_hosts: Vec<String>,
+    /// Keep values across calls and restarts
+    #[serde(default)]
+    pub storage: bool,
+}
+
+impl PluginCapabilities {
+    /// Checks that a URL may be fetched.
+    ///
+    /// # Arguments
+    /// * `url` - The URL
+    ///
+    /// # Returns
+    /// Ok(()) if the plugin may fetch it, or why not
+    pub fn check_fetch(&self, url: &str) -> anyhow::Result<()> {
+        if !self.fetch {
+            anyhow::bail!("The plugin may not make HTTP requests");
+        }
+        let url = reqwest::Url::parse(url)?;
+        let host = url.host_str().unwrap_or_default();
+        if !self.fetch_hosts.iter().any(|h| h == "*" || h == host) {
+            anyhow::bail!("The plugin may not make requests to {host}");
+        }
+        Ok(())
+    }
+}
+
+/// Loads a plugin as a builtin server.
+///
+/// # Arguments
+/// * `spec` - The plugin
+///
+/// # Returns
+/// The plugin's server, or an error if it could not be read or does not
+/// implement the plugin interface
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn load_plugin(spec: &PluginSpec) -> anyhow::Result<Box<dyn super::host::MCPServer>> {
+    Ok(Box::new(native::WasmPlugin::load(spec).await?))
+}
+
+/// Loads a plugin as a builtin server.
+///
+/// # Returns
+/// An error, as plugins need the desktop app
+#[cfg(target_arch = "wasm32")]
+pub async fn load_plugin(spec: &PluginSpec) -> anyhow::Result<Box<dyn super::host::MCPServer>> {
+    anyhow::bail!(
+        "Plugin {} was not loaded: plugins need the desktop app",
+        spec.id
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::time::Duration;
+
+    use anyhow::{Context, anyhow, bail};
+    use dioxus::logger::tracing::info;
+    use serde_json::{Map, Value, json};
+    use wasmtime::{
+        AsContextMut, Caller, Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits,
+        StoreLimitsBuilder, TypedFunc,
+    };
+
+    use super::{PluginCapabilities, PluginSpec};
+    use crate::mcp::{McpTool, ToolResult, host::MCPServer};
+
+    /// Instructions a plugin may run per call before it is stopped.
+    const FUEL_PER_CALL: u64 = 2_000_000_000;
+
+    /// Largest memory a plugin may grow to, in bytes.
+    const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+    /// Largest response body a plugin may fetch, in bytes.
+    const MAX_FETCH_BYTES: usize = 5_000_000;
+
+    /// Redirects followed for one request of a plugin.
+    const MAX_REDIRECTS: usize = 10;
+
+    /// Longest string a plugin may pass to or get from the host, in bytes.
+    const MAX_STRING_BYTES: usize = 16 * 1024 * 1024;
+
+    /// Fuel a plugin may burn before it yields to other tasks, so that a long
+    /// call does not hold up the executor.
+    const FUEL_YIELD_INTERVAL: u64 = 10_000_000;
+
+    /// Host-side state of a plugin.
+    struct PluginState {
+        id: String,
+        capabilities: PluginCapabilities,
+        storage: Map<String, Value>,
+        client: reqwest::Client,
+        limits: StoreLimits,
+    }
+
+    /// The HTTP client of a plugin. Redirects are only followed to hosts the
+    /// plugin may fetch from, so an allowed host cannot send it elsewhere.
+    ///
+    /// # Arguments
+    /// * `capabilities` - Capabilities granted to the plugin
+    pub(super) fn fetch_client(
+        capabilities: &PluginCapabilities,
+    ) -> anyhow::Result<reqwest::Client> {
+        let capabilities = capabilities.clone();
+        let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(e) = capabilities.check_fetch(attempt.url().as_str()) {
+                attempt.error(e)
+            } else {
+                attempt.follow()
+            }
+        });
+        Ok(reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(redirects)
+            .build()?)
+    }
+
+    /// A loaded plugin, serving its tools like a builtin server.
+    pub struct WasmPlugin {
+        store: Store<PluginState>,
+        instance: Instance,
+        tools: Vec<McpTool>,
+    }
+
+    impl WasmPlugin {
+        /// Compiles and instantiates a plugin, and asks it for its tools.
+        ///
+        /// # Arguments
+        /// * `spec` - The plugin
+        ///
+        /// # Returns
+        /// The loaded plugin, or an error if it failed to load
+        pub async fn load(spec: &PluginSpec) -> anyhow::Result<Self> {
+            let bytes = tokio::fs::read(&spec.path)
+                .await
+                .with_context(|| format!("reading {}", spec.path))?;
+            let mut config = Config::new();
+            config.async_support(true).consume_fuel(true);
+            let engine = Engine::new(&config)?;
+            let module = {
+                let engine = engine.clone();
+                tokio::task::spawn_blocking(move || Module::new(&engine, bytes)).await??
+            };
+
+            let mut linker = Linker::new(&engine);
+            link_host_functions(&mut linker)?;
+            let state = PluginState {
+                id: spec.id.clone(),
+                capabilities: spec.capabilities.clone(),
+                storage: Map::new(),
+                client: fetch_client(&spec.capabilities)?,
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .build(),
+            };
+            let mut store = Store::new(&engine, state);
+            store.limiter(|s| &mut s.limits);
+            store.set_fuel(FUEL_PER_CALL)?;
+            store.fuel_async_yield_interval(Some(FUEL_YIELD_INTERVAL))?;
+            let instance = linker.instantiate_async(&mut store, &module).await?;
+
+            let mut plugin = Self {
+                store,
+                instance,
+                tools: vec![],
+            };
+            let list = plugin
+                .instance
+                .get_typed_func::<(), i64>(&mut plugin.store, "mcp_list_tools")?;
+            plugin.store.set_fuel(FUEL_PER_CALL)?;
+            let packed = list.call_async(&mut plugin.store, ()).await?;
+            let memory = plugin.memory()?;
+            let tools = read_guest(&plugin.store, memory, packed)?;
+            plugin.tools = serde_json::from_slice(&tools).context("parsing the plugin's tools")?;
+            Ok(plugin)
+        }
+
+        /// The plugin's exported memory.
+        fn memory(&mut self) -> anyhow::Result<Memory> {
+            self.instance
+                .get_memory(&mut self.store, "memory")
+                .ok_or_else(|| anyhow!("The plugin exports no memory"))
+        }
+
+        /// Runs a tool call in the plugin.
+        ///
+        /// # Arguments
+        /// * `params` - Parameters of the `tools/call` request
+        ///
+        /// # Returns
+        /// The tool result returned by the plugin
+        async fn call_tool(&mut self, params: &Value) -> anyhow::Result<Value> {
+            let memory = self.memory()?;
+            let alloc = self
+                .instance
+                .get_typed_func::<i32, i32>(&mut self.store, "mcp_alloc")?;
+            let call = self
+                .instance
+                .get_typed_func::<(i32, i32), i64>(&mut self.store, "mcp_call_tool")?;
+            self.store.set_fuel(FUEL_PER_CALL)?;
+            let input = serde_json::to_vec(params)?;
+            let (ptr, len) = write_guest(&mut self.store, memory, alloc, &input).await?;
+            let packed = call.call_async(&mut self.store, (ptr, len)).await?;
+            let output = read_guest(&self.store, memory, packed)?;
+            let result: ToolResult =
+                serde_json::from_slice(&output).context("parsing the plugin's tool result")?;
+            Ok(serde_json::to_value(result)?)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MCPServer for WasmPlugin {
+        /// Returns the tools the plugin listed when it was loaded.
+        async fn list_tools(&self) -> Vec<McpTool> {
+            self.tools.clone()
+        }
+
+        /// Handles tool calls by running them in the plugin.
+        async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+            if method != "tools/call" {
+                bail!("Error: unknown RPC method {method}");
+            }
+            self.call_tool(&params).await
+        }
+
+        /// Exports the plugin's storage, if it may use any.
+        async fn export_state(&mut self) -> Option<Value> {
+            let state = self.store.data();
+            state
+                .capabilities
+                .storage
+                .then(|| Value::Object(state.storage.clone()))
+        }
+
+        /// Restores the plugin's storage.
+        async fn import_state(&mut self, state: Value) -> anyhow::Result<()> {
+            match state {
+                Value::Object(storage) => self.store.data_mut().storage = storage,
+                _ => bail!("Plugin storage must be a JSON object"),
+            }
+            Ok(())
+        }
+    }
+
+    /// Adds the host functions plugins may import to a linker.
+    fn link_host_functions(linker: &mut Linker<PluginState>) -> anyhow::Result<()> {
+        linker.func_wrap_async(
+            "mcmcpcp",
+            "fetch",
+            |mut caller: Caller<'_, PluginState>, (ptr, len): (i32, i32)| {
+                Box::new(async move {
+                    let input = read_caller(&mut caller, ptr, len)?;
+                    let state = caller.data();
+                    let response = fetch(&state.client, &state.capabilities, &input).await;
+                    write_caller(&mut caller, response).await
+                })
+            },
+        )?;
+        linker.func_wrap_async(
+            "mcmcpcp",
+            "storage_get",
+            |mut caller: Caller<'_, PluginState>, (ptr, len): (i32, i32)| {
+                Box::new(async move {
+                    let key = read_caller(&mut caller, ptr, len)?;
+                    let state = caller.data();
+                    let value = if state.capabilities.storage {
+                        let key: String = serde_json::from_slice(&key).unwrap_or_default();
+                        state.storage.get(&key).cloned().unwrap_or(Value::Null)
+                    } else {
+                        json!({ "error": "The plugin may not use storage" })
+                    };
+                    write_caller(&mut caller, value).await
+                })
+            },
+        )?;
+        linker.func_wrap_async(
+            "mcmcpcp",
+            "storage_set",
+            |mut caller: Caller<'_, PluginState>, (ptr, len): (i32, i32)| {
+                Box::new(async move {
+                    let input = read_caller(&mut caller, ptr, len)?;
+                    let state = caller.data_mut();
+                    let result = if state.capabilities.storage {
+                        match serde_json::from_slice::<Value>(&input) {
+                            Ok(Value::Object(mut entry)) => {
+                                let key = entry.get("key").and_then(|k| k.as_str());
+                                let key = key.unwrap_or_default().to_string();
+                                let value = entry.remove("value").unwrap_or(Value::Null);
+                                state.storage.insert(key, value);
+                                Value::Null
+                            }
+                            _ => json!({ "error": "Expected {\"key\", \"value\"}" }),
+                        }
+                    } else {
+                        json!({ "error": "The plugin may not use storage" })
+                    };
+                    write_caller(&mut caller, result).await
+                })
+            },
+        )?;
+        linker.func_wrap(
+            "mcmcpcp",
+            "log",
+            |mut caller: Caller<'_, PluginState>, ptr: i32, len: i32| -> anyhow::Result<()> {
+                let message = read_caller(&mut caller, ptr, len)?;
+                let id = &caller.data().id;
+                info!("Plugin {id}: {}", String::from_utf8_lossy(&message));
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Makes an HTTP request for a plugin.
+    ///
+    /// # Arguments
+    /// * `client` - HTTP client of the plugin
+    /// * `capabilities` - Capabilities granted to the plugin
+    /// * `input` - The request, as JSON
+    ///
+    /// # Returns
+    /// The status and body of the response, or the error
+    async fn fetch(
+        client: &reqwest::Client,
+        capabilities: &PluginCapabilities,
+        input: &[u8],
+    ) -> Value {
+        let result = async {
+            let request: Value = serde_json::from_slice(input)?;
+            let url = request.get("url").and_then(|u| u.as_str()).unwrap_or("");
+            capabilities.check_fetch(url)?;
+            let method = request
+                .get("method")
+                .and_then(|m| m.as_str())
+                .unwrap_or("GET");
+            let mut builder = client.request(method.parse()?, url);
+            if let Some(headers) = request.get("headers").and_then(|h| h.as_object()) {
+                for (name, value) in headers {
+                    builder = builder.header(name, value.as_str().unwrap_or_default());
+                }
+            }
+            if let Some(body) = request.get("body").and_then(|b| b.as_str()) {
+                builder = builder.body(body.to_string());
+            }
+            let mut response = builder.send().await?;
+            let status = response.status().as_u16();
+            let too_large = || anyhow!("The response is larger than {MAX_FETCH_BYTES} bytes");
+            if response
+                .content_length()
+                .is_some_and(|l| l > MAX_FETCH_BYTES as u64)
+            {
+                return Err(too_large());
+            }
+            // Read in chunks, so a large body is refused before it is all in memory
+            let mut body = vec![];
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > MAX_FETCH_BYTES {
+                    return Err(too_large());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            anyhow::Ok(json!({ "status": status, "body": String::from_utf8_lossy(&body) }))
+        }
+        .await;
+        result.unwrap_or_else(|e| json!({ "error": e.to_string() }))
+    }
+
+    /// Reads a string a plugin passed to a host function.
+    fn read_caller(
+        caller: &mut Caller<'_, PluginState>,
+        ptr: i32,
+        len: i32,
+    ) -> anyhow::Result<Vec<u8>> {
+        let memory = caller_memory(caller)?;
+        copy_out(
+            memory.data(&caller),
+            ptr as u32 as usize,
+            len as u32 as usize,
+        )
+    }
+
+    /// Returns a host function's result to a plugin.
+    async fn write_caller(
+        caller: &mut Caller<'_, PluginState>,
+        value: Value,
+    ) -> anyhow::Result<i64> {
+        let memory = caller_memory(caller)?;
+        let alloc = caller
+            .get_export("mcp_alloc")
+            .and_then(|e| e.into_func())
+            .ok_or_else(|| anyhow!("The plugin exports no mcp_alloc"))?
+            .typed::<i32, i32>(&caller)?;
+        let (ptr, len) = write_guest(caller, memory, alloc, &serde_json::to_vec(&value)?).await?;
+        Ok(pack(ptr, len))
+    }
+
+    /// The memory of the plugin calling a host function.
+    fn caller_memory(caller: &mut Caller<'_, PluginState>) -> anyhow::Result<Memory> {
+        caller
+            .get_export("memory")
+            .and_then(|e| e.into_memory())
+            .ok_or_else(|| anyhow!("The plugin exports no memory"))
+    }
+
+    /// Copies bytes into memory allocated by the plugin.
+    ///
+    /// # Returns
+    /// Where the bytes were written and their length
+    async fn write_guest(
+        mut store: impl AsContextMut<Data = PluginState>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        bytes: &[u8],
+    ) -> anyhow::Result<(i32, i32)> {
+        let len = i32::try_from(bytes.len())?;
+        let ptr = alloc.call_async(&mut store, len).await?;
+        memory.write(&mut store, ptr as u32 as usize, bytes)?;
+        Ok((ptr, len))
+    }
+
+    /// Copies out a string the plugin returned as a packed pointer and length.
+    fn read_guest(
+        store: &Store<PluginState>,
+        memory: Memory,
+        packed: i64,
+    ) -> anyhow::Result<Vec<u8>> {
+        let (ptr, len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+        copy_out(memory.data(store), ptr, len)
+    }
+
+    /// Copies a string out of a plugin's memory, checking where it lies before
+    /// anything is allocated for it, as the plugin chooses its length.
+    ///
+    /// # Arguments
+    /// * `data` - The plugin's memory
+    /// * `ptr` - Where the string starts
+    /// * `len` - Its length in bytes
+    ///
+    /// # Returns
+    /// The string's bytes, or an error if it is too long or not in the memory
+    fn copy_out(data: &[u8], ptr: usize, len: usize) -> anyhow::Result<Vec<u8>> {
+        if len > MAX_STRING_BYTES {
+            bail!(
+                "The plugin passed a string of {len} bytes; at most {MAX_STRING_BYTES} are allowed"
+            );
+        }
+        let end = ptr
+            .checked_add(len)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| anyhow!("The plugin passed a string outside its memory"))?;
+        Ok(data[ptr..end].to_vec())
+    }
+
+    /// Packs a pointer and a length into the `i64` returned to plugins.
+    fn pack(ptr: i32, len: i32) -> i64 {
+        ((ptr as u32 as i64) << 32) | len as u32 as i64
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves one HTTP request per connection, redirecting `/` to `location`
+    /// and answering anything else with `ok`.
+    ///
+    /// # Returns
+    /// The port the server listens on
+    async fn redirecting_server(location: impl Fn(u16) -> String + Send + 'static) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let n = socket.read(&mut request).await.unwrap_or(0);
+                let response = if request[..n].starts_with(b"GET / ") {
+                    format!(
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+                        location(port)
+                    )
+                } else {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok".to_string()
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    fn fetching(hosts: &[&str]) -> PluginCapabilities {
+        PluginCapabilities {
+            fetch: true,
+            fetch_hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            storage: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_redirects_to_allowed_hosts() {
+        let port = redirecting_server(|port| format!("http://127.0.0.1:{port}/next")).await;
+        let client = native::fetch_client(&fetching(&["127.0.0.1"])).unwrap();
+        let response = client
+            .get(format!("http://127.0.0.1:{port}/"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn refuses_redirects_to_other_hosts() {
+        let port = redirecting_server(|port| format!("http://localhost:{port}/secret")).await;
+        let client = native::fetch_client(&fetching(&["127.0.0.1"])).unwrap();
+        let error = client
+            .get(format!("http://127.0.0.1:{port}/"))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(error.is_redirect());
+    }
+}
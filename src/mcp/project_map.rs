@@ -0,0 +1,541 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin server giving coding chats a compact map of their project (native
+//! only).
+//!
+//! `project_map` lists the project's directory tree with file sizes and the
+//! top-level symbols of source files, so the model can find its way around a
+//! repository without reading every file; `file_symbols` lists one file's
+//! symbols with line numbers. Symbols are found by parsing files with
+//! tree-sitter, for Rust, Python, JavaScript, TypeScript and Go.
+//!
+//! Parsed symbols are cached per file and parsed again when the file's
+//! modification time or size changes. Hidden entries and files ignored by git
+//! are left out.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use anyhow::{Context, bail};
+use git2::Repository;
+use serde_json::{Value, json};
+use tree_sitter::{Language, Node, Parser};
+
+use crate::mcp::{McpTool, ToolAnnotations, ToolResult, ToolResultContent, host::MCPServer};
+
+/// ID of the project map server in coding chats.
+pub const PROJECT_MAP_SERVER: &str = "project";
+
+/// Directory levels shown by `project_map` when the model does not say.
+const DEFAULT_DEPTH: usize = 3;
+
+/// Entries listed by `project_map` before the rest are left out.
+const MAX_ENTRIES: usize = 400;
+
+/// Top-level symbols listed per file in `project_map`.
+const MAX_SYMBOLS_PER_FILE: usize = 12;
+
+/// Files larger than this are not parsed for symbols, in bytes.
+const MAX_PARSED_BYTES: u64 = 1_000_000;
+
+/// A named definition in a source file.
+#[derive(Debug, Clone)]
+struct Symbol {
+    /// What was defined, e.g. "fn" or "class"
+    kind: &'static str,
+    name: String,
+    /// Line of the definition, starting at 1
+    line: usize,
+    /// Members, such as the methods of a class or impl block
+    children: Vec<Symbol>,
+}
+
+/// Symbols of a file, with what the file looked like when it was parsed.
+struct CachedSymbols {
+    modified: SystemTime,
+    len: u64,
+    symbols: Arc<Vec<Symbol>>,
+}
+
+/// Symbols parsed so far, by file path.
+type SymbolCache = Arc<Mutex<HashMap<PathBuf, CachedSymbols>>>;
+
+/// Builtin MCP server mapping a project directory.
+pub struct ProjectMapMcpServer {
+    /// The project directory
+    root: PathBuf,
+    cache: SymbolCache,
+}
+
+impl ProjectMapMcpServer {
+    /// Creates a server for a project directory.
+    ///
+    /// # Arguments
+    /// * `root` - The project directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            cache: Default::default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MCPServer for ProjectMapMcpServer {
+    async fn list_tools(&self) -> Vec<McpTool> {
+        let read_only = Some(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        });
+        vec![
+            McpTool {
+                name: "project_map".into(),
+                description: Some("Show the project's directory tree with file sizes and the top-level symbols (functions, types, classes...) of source files. Use it first to find where things are.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Directory to map, relative to the project root (default: the whole project)"},
+                        "depth": {"type": "number", "description": "Directory levels to show (default 3)"},
+                        "symbols": {"type": "boolean", "description": "List the symbols of source files (default true)"}
+                    }
+                }),
+                annotations: read_only.clone(),
+            },
+            McpTool {
+                name: "file_symbols".into(),
+                description: Some("List the symbols defined in a source file with their line numbers, including members such as methods.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path of the file, relative to the project root"}
+                    },
+                    "required": ["path"]
+                }),
+                annotations: read_only,
+            },
+        ]
+    }
+
+    /// Handles tool calls on a blocking thread, since they read many files.
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let root = self.root.clone();
+        let cache = self.cache.clone();
+        let result = tokio::task::spawn_blocking(move || match name.as_str() {
+            "project_map" => project_map(&root, &cache, &args),
+            "file_symbols" => file_symbols(&root, &cache, &args),
+            _ => bail!("Unknown tool: {name}"),
+        })
+        .await?;
+
+        let (text, is_error) = match result {
+            Ok(text) => (text, None),
+            Err(e) => (format!("{e:#}"), Some(true)),
+        };
+        Ok(serde_json::to_value(ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".into(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error,
+        })?)
+    }
+}
+
+/// Resolves a path given by the model, which must be inside the project.
+fn resolve(root: &Path, path: Option<&str>) -> anyhow::Result<PathBuf> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("The project directory {} is not available", root.display()))?;
+    let Some(path) = path.filter(|p| !p.is_empty() && *p != ".") else {
+        return Ok(root);
+    };
+    let resolved = root
+        .join(path)
+        .canonicalize()
+        .with_context(|| format!("{path} does not exist"))?;
+    if !resolved.starts_with(&root) {
+        bail!("{path} is outside the project");
+    }
+    Ok(resolved)
+}
+
+/// Lists a directory tree with file sizes and top-level symbols.
+fn project_map(root: &Path, cache: &SymbolCache, args: &Value) -> anyhow::Result<String> {
+    let dir = resolve(root, args.get("path").and_then(|v| v.as_str()))?;
+    if !dir.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    let depth = args
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_DEPTH, |d| d.max(1) as usize);
+    let with_symbols = args.get("symbols").and_then(|v| v.as_bool()) != Some(false);
+
+    let mut map = MapWriter {
+        repo: Repository::discover(&dir).ok(),
+        cache,
+        with_symbols,
+        max_depth: depth,
+        out: String::new(),
+        entries: 0,
+        truncated: false,
+    };
+    map.out.push_str(&format!("{}/\n", dir.display()));
+    map.write_dir(&dir, 1)?;
+    if map.truncated {
+        map.out.push_str(&format!(
+            "[stopped after {MAX_ENTRIES} entries; map a subdirectory with `path` to see more]\n"
+        ));
+    }
+    Ok(map.out)
+}
+
+/// Writes the lines of a project map.
+struct MapWriter<'a> {
+    /// Repository of the project, for leaving out ignored files
+    repo: Option<Repository>,
+    cache: &'a SymbolCache,
+    with_symbols: bool,
+    max_depth: usize,
+    out: String,
+    entries: usize,
+    truncated: bool,
+}
+
+impl MapWriter<'_> {
+    fn write_dir(&mut self, dir: &Path, depth: usize) -> anyhow::Result<()> {
+        let indent = "  ".repeat(depth);
+        for (path, is_dir) in self.list(dir)? {
+            if self.entries >= MAX_ENTRIES {
+                self.truncated = true;
+                return Ok(());
+            }
+            self.entries += 1;
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if is_dir {
+                if depth >= self.max_depth {
+                    let count = self.list(&path).map(|e| e.len()).unwrap_or(0);
+                    self.out
+                        .push_str(&format!("{indent}{name}/ ({count} entries)\n"));
+                } else {
+                    self.out.push_str(&format!("{indent}{name}/\n"));
+                    self.write_dir(&path, depth + 1)?;
+                }
+                continue;
+            }
+            let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            self.out
+                .push_str(&format!("{indent}{name} ({})", format_size(len)));
+            if self.with_symbols
+                && let Some(symbols) = symbols_of(self.cache, &path)
+                && !symbols.is_empty()
+            {
+                let mut names: Vec<String> = symbols
+                    .iter()
+                    .take(MAX_SYMBOLS_PER_FILE)
+                    .map(|s| format!("{} {}", s.kind, s.name))
+                    .collect();
+                if symbols.len() > MAX_SYMBOLS_PER_FILE {
+                    names.push(format!("+{} more", symbols.len() - MAX_SYMBOLS_PER_FILE));
+                }
+                self.out.push_str(&format!(": {}", names.join(", ")));
+            }
+            self.out.push('\n');
+        }
+        Ok(())
+    }
+
+    /// Entries of a directory worth showing, directories first, by name.
+    fn list(&self, dir: &Path) -> anyhow::Result<Vec<(PathBuf, bool)>> {
+        let mut entries = vec![];
+        for entry in fs::read_dir(dir).with_context(|| format!("Cannot read {}", dir.display()))? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') || self.is_ignored(&path) {
+                continue;
+            }
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            entries.push((path, is_dir));
+        }
+        entries.sort_by(|(a, a_dir), (b, b_dir)| b_dir.cmp(a_dir).then_with(|| a.cmp(b)));
+        Ok(entries)
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let Some(repo) = &self.repo else {
+            return false;
+        };
+        let Some(relative) = repo.workdir().and_then(|w| path.strip_prefix(w).ok()) else {
+            return false;
+        };
+        repo.is_path_ignored(relative).unwrap_or(false)
+    }
+}
+
+/// Lists the symbols of a file with their line numbers.
+fn file_symbols(root: &Path, cache: &SymbolCache, args: &Value) -> anyhow::Result<String> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .context("Missing required argument 'path'")?;
+    let file = resolve(root, Some(path))?;
+    if !file.is_file() {
+        bail!("{path} is not a file");
+    }
+    if SourceLanguage::of(&file).is_none() {
+        bail!(
+            "Symbols of {path} are not available; supported are Rust, Python, JavaScript, TypeScript and Go files"
+        );
+    }
+    let symbols = symbols_of(cache, &file).context("The file could not be parsed")?;
+    if symbols.is_empty() {
+        return Ok(format!("{path} defines no symbols"));
+    }
+    let mut out = String::new();
+    write_symbols(&mut out, &symbols, 0);
+    Ok(out)
+}
+
+fn write_symbols(out: &mut String, symbols: &[Symbol], depth: usize) {
+    for symbol in symbols {
+        out.push_str(&format!(
+            "{}{} {} (line {})\n",
+            "  ".repeat(depth),
+            symbol.kind,
+            symbol.name,
+            symbol.line
+        ));
+        write_symbols(out, &symbol.children, depth + 1);
+    }
+}
+
+/// Sizes like "12.3 KB".
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{b} B"),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Symbols of a source file, from the cache if the file has not changed.
+///
+/// # Returns
+/// The symbols, or `None` if the file is not in a supported language, is too
+/// large or cannot be read
+fn symbols_of(cache: &SymbolCache, path: &Path) -> Option<Arc<Vec<Symbol>>> {
+    let language = SourceLanguage::of(path)?;
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_PARSED_BYTES {
+        return None;
+    }
+    let modified = metadata.modified().ok()?;
+    if let Ok(cache) = cache.lock()
+        && let Some(cached) = cache.get(path)
+        && cached.modified == modified
+        && cached.len == metadata.len()
+    {
+        return Some(cached.symbols.clone());
+    }
+
+    let source = fs::read(path).ok()?;
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(&source, None)?;
+    let symbols = Arc::new(language.symbols(tree.root_node(), &source));
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(
+            path.to_path_buf(),
+            CachedSymbols {
+                modified,
+                len: metadata.len(),
+                symbols: symbols.clone(),
+            },
+        );
+    }
+    Some(symbols)
+}
+
+/// Languages whose symbols can be listed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SourceLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl SourceLanguage {
+    /// The language of a file, by its extension.
+    fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Some(match ext {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+            "ts" | "mts" | "cts" => Self::TypeScript,
+            "tsx" => Self::Tsx,
+            "go" => Self::Go,
+            _ => return None,
+        })
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    /// Symbols defined directly in a node, such as a file or a class body.
+    fn symbols(self, node: Node, source: &[u8]) -> Vec<Symbol> {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .flat_map(|child| self.definitions(child, source))
+            .collect()
+    }
+
+    /// Symbols a node defines; a declaration may define several.
+    fn definitions(self, node: Node, source: &[u8]) -> Vec<Symbol> {
+        let named = |kind| named_symbol(kind, node, source, "name");
+        let with_members = |kind, body| {
+            named_symbol(kind, node, source, "name").map(|mut symbol| {
+                if let Some(body) = node.child_by_field_name(body) {
+                    symbol.children = self.symbols(body, source);
+                }
+                symbol
+            })
+        };
+        let symbol = match (self, node.kind()) {
+            (Self::Rust, "function_item" | "function_signature_item") => named("fn"),
+            (Self::Rust, "struct_item") => named("struct"),
+            (Self::Rust, "enum_item") => named("enum"),
+            (Self::Rust, "union_item") => named("union"),
+            (Self::Rust, "trait_item") => with_members("trait", "body"),
+            (Self::Rust, "mod_item") => with_members("mod", "body"),
+            (Self::Rust, "const_item") => named("const"),
+            (Self::Rust, "static_item") => named("static"),
+            (Self::Rust, "type_item") => named("type"),
+            (Self::Rust, "macro_definition") => named("macro"),
+            (Self::Rust, "impl_item") => {
+                let ty = field_text(node, "type", source);
+                let name = match field_text(node, "trait", source) {
+                    Some(tr) => format!("{tr} for {}", ty.unwrap_or_default()),
+                    None => ty.unwrap_or_default(),
+                };
+                Some(Symbol {
+                    kind: "impl",
+                    name,
+                    line: node.start_position().row + 1,
+                    children: node
+                        .child_by_field_name("body")
+                        .map(|body| self.symbols(body, source))
+                        .unwrap_or_default(),
+                })
+            }
+            (Self::Python, "function_definition") => named("def"),
+            (Self::Python, "class_definition") => with_members("class", "body"),
+            (Self::Python, "decorated_definition") => {
+                return node
+                    .child_by_field_name("definition")
+                    .map(|d| self.definitions(d, source))
+                    .unwrap_or_default();
+            }
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "function_declaration" | "generator_function_declaration" | "function_signature",
+            ) => named("function"),
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "class_declaration" | "abstract_class_declaration",
+            ) => with_members("class", "body"),
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "method_definition" | "method_signature" | "abstract_method_signature",
+            ) => named("method"),
+            (Self::TypeScript | Self::Tsx, "interface_declaration") => named("interface"),
+            (Self::TypeScript | Self::Tsx, "type_alias_declaration") => named("type"),
+            (Self::TypeScript | Self::Tsx, "enum_declaration") => named("enum"),
+            (Self::TypeScript | Self::Tsx, "internal_module" | "module") => {
+                with_members("namespace", "body")
+            }
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "lexical_declaration" | "variable_declaration",
+            ) => {
+                let mut cursor = node.walk();
+                return node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() == "variable_declarator")
+                    .filter_map(|c| named_symbol("const", c, source, "name"))
+                    .collect();
+            }
+            (Self::JavaScript | Self::TypeScript | Self::Tsx, "export_statement") => {
+                return node
+                    .child_by_field_name("declaration")
+                    .map(|d| self.definitions(d, source))
+                    .unwrap_or_default();
+            }
+            (Self::Go, "function_declaration") => named("func"),
+            (Self::Go, "method_declaration") => {
+                named_symbol("method", node, source, "name").map(|mut symbol| {
+                    if let Some(receiver) = field_text(node, "receiver", source) {
+                        symbol.name = format!("{receiver} {}", symbol.name);
+                    }
+                    symbol
+                })
+            }
+            (Self::Go, "type_declaration") => {
+                let mut cursor = node.walk();
+                return node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() == "type_spec")
+                    .filter_map(|c| named_symbol("type", c, source, "name"))
+                    .collect();
+            }
+            _ => None,
+        };
+        symbol.into_iter().collect()
+    }
+}
+
+/// A symbol named by a field of its node.
+fn named_symbol(kind: &'static str, node: Node, source: &[u8], field: &str) -> Option<Symbol> {
+    Some(Symbol {
+        kind,
+        name: field_text(node, field, source)?,
+        line: node.start_position().row + 1,
+        children: vec![],
+    })
+}
+
+/// Source text of a field of a node.
+fn field_text(node: Node, field: &str, source: &[u8]) -> Option<String> {
+    let text = node.child_by_field_name(field)?.utf8_text(source).ok()?;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
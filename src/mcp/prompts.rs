@@ -0,0 +1,116 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Prompt templates offered by MCP servers.
+//!
+//! Servers can offer prompts, templates with named arguments that expand to
+//! messages, through `prompts/list` and `prompts/get`. Built-in servers and
+//! many external ones offer none; they are simply left out of the list.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::mcp::ToolResultContent;
+
+/// A prompt template offered by a server.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct McpPrompt {
+    /// Name of the prompt, unique on its server
+    pub name: String,
+    /// Human-readable description of the prompt
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Arguments the template takes
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// An argument of a prompt template.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PromptArgument {
+    /// Name of the argument
+    pub name: String,
+    /// Human-readable description of the argument
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Whether the argument must be given
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A prompt template of a specific server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromptDescriptor {
+    /// ID of the server that provides the prompt
+    pub server_id: String,
+    /// The prompt itself
+    pub prompt: McpPrompt,
+}
+
+/// A message a prompt template expanded to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PromptMessage {
+    /// "user" or "assistant"
+    pub role: String,
+    /// Content of the message, in the same form as tool result content
+    pub content: ToolResultContent,
+}
+
+/// Parses the result of a `prompts/list` call.
+pub fn parse_prompt_list(result: Value) -> anyhow::Result<Vec<McpPrompt>> {
+    let prompts = result
+        .get("prompts")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("The server returned no prompts"))?;
+    Ok(serde_json::from_value(prompts)?)
+}
+
+/// Parses the result of a `prompts/get` call.
+pub fn parse_prompt_messages(result: Value) -> anyhow::Result<Vec<PromptMessage>> {
+    let messages = result
+        .get("messages")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("The server returned no prompt messages"))?;
+    Ok(serde_json::from_value(messages)?)
+}
+
+/// Joins the text of a prompt's messages, for sending as one user message.
+/// Images and other non-text content are left out.
+pub fn prompt_text(messages: &[PromptMessage]) -> String {
+    messages
+        .iter()
+        .filter_map(|m| m.content.text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Parses prompt arguments written as `name=value`, separated by spaces.
+/// Values may be quoted to contain spaces.
+///
+/// # Arguments
+/// * `args` - The arguments as typed
+///
+/// # Returns
+/// The arguments by name, or an error if one is not of the form `name=value`
+pub fn parse_prompt_args(args: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut parsed = BTreeMap::new();
+    let mut rest = args.trim_start();
+    while !rest.is_empty() {
+        let Some((name, after)) = rest.split_once('=') else {
+            anyhow::bail!("Expected name=value, found {rest:?}");
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            anyhow::bail!("Expected name=value, found {name:?}");
+        }
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => quoted
+                .split_once('"')
+                .ok_or_else(|| anyhow::anyhow!("Missing closing quote for {name}"))?,
+            None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+        parsed.insert(name.to_string(), value.to_string());
+        rest = after.trim_start();
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,257 @@
+// Copyright © 2025 Nipun Kumar
+
+//! The format tool results are given to the model in.
+//!
+//! Many servers return pretty-printed JSON, which costs context tokens for
+//! indentation the model does not need. A toolset can prefer compact JSON or
+//! readable Markdown, for all tools or tool by tool (see [`ResultFormats`]).
+//! Tools whose input schema has a format argument, such as `format` with
+//! `"json"` and `"markdown"` among its values, are asked for the format;
+//! otherwise JSON results are converted after the call. Results that are not
+//! JSON are left as they are.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::mcp::{ToolResult, tool_ref::ToolRef};
+
+/// Arguments servers commonly take to choose the format of their results.
+const FORMAT_ARGUMENTS: [&str; 3] = ["format", "output_format", "response_format"];
+
+/// Rows of a JSON array shown as a Markdown table; more are summarized.
+const MAX_TABLE_ROWS: usize = 50;
+
+/// Columns of a Markdown table; objects with more keys are shown as lists.
+const MAX_TABLE_COLUMNS: usize = 8;
+
+/// Format of the results of a tool, as given to the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResultFormat {
+    /// As the server returned them
+    #[default]
+    AsIs,
+    /// Readable Markdown: lists for objects, tables for lists of records
+    Markdown,
+    /// JSON without whitespace
+    Json,
+}
+
+impl ResultFormat {
+    /// Values of a format argument that ask for this format.
+    fn argument_values(&self) -> &'static [&'static str] {
+        match self {
+            ResultFormat::AsIs => &[],
+            ResultFormat::Markdown => &["markdown", "md"],
+            ResultFormat::Json => &["json"],
+        }
+    }
+}
+
+/// Preferred result formats of a toolset's tools.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResultFormats {
+    /// Format of tools without a preference of their own
+    pub default: ResultFormat,
+    /// Formats by tool, keyed by the tool's name as the model calls it
+    /// (`server--tool`) or by its name alone, for that tool on any server
+    pub tools: HashMap<String, ResultFormat>,
+}
+
+impl ResultFormats {
+    /// The same format for every tool.
+    pub fn all(format: ResultFormat) -> Self {
+        Self {
+            default: format,
+            tools: HashMap::new(),
+        }
+    }
+
+    /// Sets the format of one tool.
+    ///
+    /// # Arguments
+    /// * `tool` - Name of the tool as the model calls it, or its name alone
+    /// * `format` - Its format
+    pub fn with_tool(mut self, tool: &str, format: ResultFormat) -> Self {
+        self.tools.insert(tool.to_string(), format);
+        self
+    }
+
+    /// The format for a tool's results.
+    pub fn for_tool(&self, server_id: &str, tool: &str) -> ResultFormat {
+        self.tools
+            .get(&ToolRef::new(server_id, tool).encode())
+            .or_else(|| self.tools.get(tool))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Asks a tool for results in a format, if its input schema has an argument
+/// choosing the format and the call does not set it already.
+///
+/// # Arguments
+/// * `schema` - The tool's input schema
+/// * `arguments` - Arguments of the call
+/// * `format` - The format wanted
+///
+/// # Returns
+/// Whether the tool was asked, in which case its results are left as they are
+pub fn request_format(schema: &Value, arguments: &mut Value, format: ResultFormat) -> bool {
+    let (Some(properties), Some(args)) = (
+        schema.get("properties").and_then(Value::as_object),
+        arguments.as_object_mut(),
+    ) else {
+        return false;
+    };
+    for name in FORMAT_ARGUMENTS {
+        let Some(values) = properties
+            .get(name)
+            .and_then(|p| p.get("enum"))
+            .and_then(Value::as_array)
+        else {
+            continue;
+        };
+        if args.contains_key(name) {
+            return false;
+        }
+        let Some(value) = values.iter().filter_map(Value::as_str).find(|v| {
+            format
+                .argument_values()
+                .contains(&v.to_ascii_lowercase().as_str())
+        }) else {
+            return false;
+        };
+        args.insert(name.to_string(), Value::String(value.to_string()));
+        return true;
+    }
+    false
+}
+
+/// Converts the JSON text of a result into a format.
+///
+/// # Arguments
+/// * `result` - The result, changed in place
+/// * `format` - The format wanted
+pub fn convert_result(result: &mut ToolResult, format: ResultFormat) {
+    if format == ResultFormat::AsIs {
+        return;
+    }
+    for content in result.content.iter_mut().filter(|c| c.r#type == "text") {
+        let Some(text) = &content.text else {
+            continue;
+        };
+        let trimmed = text.trim();
+        if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(trimmed) else {
+            continue;
+        };
+        content.text = Some(match format {
+            ResultFormat::Json => value.to_string(),
+            _ => to_markdown(&value),
+        });
+    }
+}
+
+/// Renders JSON as Markdown: lists of records as tables, other arrays and
+/// objects as nested lists.
+pub fn to_markdown(value: &Value) -> String {
+    if let Some(table) = table(value) {
+        return table;
+    }
+    match value {
+        Value::Array(_) | Value::Object(_) => {
+            let mut md = String::new();
+            list(value, 0, &mut md);
+            md.trim_end().to_string()
+        }
+        _ => scalar(value),
+    }
+}
+
+/// Renders an array of objects with few, scalar fields as a table.
+fn table(value: &Value) -> Option<String> {
+    let rows = value.as_array()?;
+    if rows.is_empty() {
+        return None;
+    }
+    let mut columns: Vec<&String> = vec![];
+    for row in rows {
+        for (key, v) in row.as_object()? {
+            if v.is_array() || v.is_object() {
+                return None;
+            }
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+    if columns.is_empty() || columns.len() > MAX_TABLE_COLUMNS {
+        return None;
+    }
+    let mut md = format!(
+        "| {} |\n|{}\n",
+        columns
+            .iter()
+            .map(|c| cell(c))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        " --- |".repeat(columns.len())
+    );
+    for row in rows.iter().take(MAX_TABLE_ROWS) {
+        let cells = columns
+            .iter()
+            .map(|c| row.get(c.as_str()).map(scalar).unwrap_or_default())
+            .map(|v| cell(&v))
+            .collect::<Vec<_>>();
+        md.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    if rows.len() > MAX_TABLE_ROWS {
+        md.push_str(&format!("\n({} more rows)\n", rows.len() - MAX_TABLE_ROWS));
+    }
+    Some(md.trim_end().to_string())
+}
+
+/// Appends an array or object as a Markdown list, nested by `depth`.
+fn list(value: &Value, depth: usize, md: &mut String) {
+    let indent = "  ".repeat(depth);
+    let items: Vec<(Option<&str>, &Value)> = match value {
+        Value::Array(items) => items.iter().map(|v| (None, v)).collect(),
+        Value::Object(fields) => fields.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+        _ => return,
+    };
+    if items.is_empty() {
+        md.push_str(&format!("{indent}- (empty)\n"));
+    }
+    for (key, v) in items {
+        let label = key.map(|k| format!("**{k}**")).unwrap_or_default();
+        match v {
+            Value::Array(a) if !a.is_empty() => {
+                md.push_str(&format!("{indent}- {label}\n"));
+                list(v, depth + 1, md);
+            }
+            Value::Object(o) if !o.is_empty() => {
+                md.push_str(&format!("{indent}- {label}\n"));
+                list(v, depth + 1, md);
+            }
+            _ if key.is_some() => md.push_str(&format!("{indent}- {label}: {}\n", scalar(v))),
+            _ => md.push_str(&format!("{indent}- {}\n", scalar(v))),
+        }
+    }
+}
+
+/// Text of a scalar, or of an empty array or object.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "—".to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Text of a table cell, with pipes escaped and line breaks removed.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
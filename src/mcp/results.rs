@@ -0,0 +1,206 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Size limits for tool results.
+//!
+//! Results longer than [`MAX_RESULT_CHARS`] are replaced in the conversation by
+//! a preview of their beginning and end. The full text is kept in memory and the
+//! model can page through it with the builtin `read_more` tool. Stored results do
+//! not survive a restart; only the most recent [`MAX_STORED_RESULTS`] are kept.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use anyhow::bail;
+use serde_json::json;
+
+use crate::mcp::McpTool;
+
+/// Results longer than this many characters are truncated.
+pub const MAX_RESULT_CHARS: usize = 12_000;
+/// Characters kept from the start of a truncated result.
+const HEAD_CHARS: usize = 8_000;
+/// Characters kept from the end of a truncated result.
+const TAIL_CHARS: usize = 2_000;
+/// Largest chunk `read_more` returns at once, and its default.
+const READ_MORE_CHARS: usize = 8_000;
+/// Number of full results kept for `read_more`.
+pub const MAX_STORED_RESULTS: usize = 32;
+
+/// Prefix of the note appended to truncated results.
+const TRUNCATION_PREFIX: &str = "[Truncated result";
+
+/// Full text of recently truncated results, oldest first
+static STORED_RESULTS: Mutex<VecDeque<(String, String)>> = Mutex::new(VecDeque::new());
+/// Counter used to generate result IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Size information about a tool result shown in the conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultSize {
+    /// Characters of the result in the conversation
+    pub shown: usize,
+    /// Characters of the full result
+    pub total: usize,
+}
+
+impl ResultSize {
+    /// Whether the result was truncated.
+    pub fn truncated(&self) -> bool {
+        self.shown < self.total
+    }
+
+    /// Describes the size for display, e.g. "1.2k chars" or "truncated, 10k of 245.1k chars".
+    pub fn describe(&self) -> String {
+        if self.truncated() {
+            format!(
+                "truncated, {} of {} chars",
+                format_count(self.shown),
+                format_count(self.total)
+            )
+        } else {
+            format!("{} chars", format_count(self.total))
+        }
+    }
+}
+
+/// Formats a character count compactly.
+fn format_count(n: usize) -> String {
+    if n < 1_000 {
+        n.to_string()
+    } else if n < 1_000_000 {
+        format!("{:.1}k", n as f64 / 1_000.0)
+    } else {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    }
+}
+
+/// Truncates a tool result that is too long for the conversation.
+///
+/// # Arguments
+/// * `text` - The full result
+/// * `read_more_tool` - Full name of the `read_more` tool, if the model can call it;
+///   otherwise the rest of the result is discarded
+///
+/// # Returns
+/// The result unchanged if it is short enough, otherwise its head and tail with
+/// a note explaining how to read the rest
+pub fn limit_result(text: String, read_more_tool: Option<&str>) -> String {
+    let total = text.chars().count();
+    if total <= MAX_RESULT_CHARS {
+        return text;
+    }
+
+    let head: String = text.chars().take(HEAD_CHARS).collect();
+    let tail: String = text.chars().skip(total - TAIL_CHARS).collect();
+    let omitted = total - HEAD_CHARS - TAIL_CHARS;
+    let shown = HEAD_CHARS + TAIL_CHARS;
+
+    let note = match read_more_tool {
+        Some(tool) => {
+            let id = store(text);
+            format!(
+                "{TRUNCATION_PREFIX} {id}: showing {shown} of {total} characters. \
+                 Call `{tool}` with id \"{id}\" and offset {HEAD_CHARS} to read the omitted part.]"
+            )
+        }
+        None => format!("{TRUNCATION_PREFIX}: showing {shown} of {total} characters.]"),
+    };
+    format!("{head}\n\n[... {omitted} characters omitted ...]\n\n{tail}\n\n{note}")
+}
+
+/// Works out the size of a tool result as shown in the conversation.
+///
+/// # Arguments
+/// * `content` - Content of a tool message
+///
+/// # Returns
+/// The number of characters shown and, for truncated results, the full size
+pub fn result_size(content: &str) -> ResultSize {
+    let shown = content.chars().count();
+    let total = content
+        .lines()
+        .last()
+        .and_then(|l| l.strip_prefix(TRUNCATION_PREFIX))
+        .and_then(|l| l.split_once("showing "))
+        .and_then(|(_, l)| l.split_once(" characters"))
+        .and_then(|(counts, _)| counts.split_once(" of "))
+        .and_then(|(shown, total)| Some((shown.parse::<usize>().ok()?, total.parse().ok()?)));
+    match total {
+        Some((shown, total)) => ResultSize { shown, total },
+        None => ResultSize {
+            shown,
+            total: shown,
+        },
+    }
+}
+
+/// Keeps the full text of a truncated result, evicting the oldest if needed.
+fn store(text: String) -> String {
+    let id = format!("r{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    if let Ok(mut results) = STORED_RESULTS.lock() {
+        if results.len() >= MAX_STORED_RESULTS {
+            results.pop_front();
+        }
+        results.push_back((id.clone(), text));
+    }
+    id
+}
+
+/// Reads part of a stored result.
+///
+/// # Arguments
+/// * `id` - Result ID from the truncation note
+/// * `offset` - Character offset to start at
+/// * `length` - Number of characters to read, capped at 8000
+///
+/// # Returns
+/// The requested characters followed by a note on where to continue
+pub fn read_more(id: &str, offset: usize, length: usize) -> anyhow::Result<String> {
+    let results = STORED_RESULTS
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Result store is unavailable"))?;
+    let Some((_, text)) = results.iter().find(|(i, _)| i == id) else {
+        bail!("No stored result with id {id}; it may have expired");
+    };
+    let total = text.chars().count();
+    if offset >= total {
+        bail!("Offset {offset} is past the end of the result ({total} characters)");
+    }
+    let chunk: String = text
+        .chars()
+        .skip(offset)
+        .take(length.clamp(1, READ_MORE_CHARS))
+        .collect();
+    let end = offset + chunk.chars().count();
+    let note = if end < total {
+        format!("[Characters {offset}-{end} of {total}. Continue with offset {end}.]")
+    } else {
+        format!("[Characters {offset}-{end} of {total}. End of result.]")
+    };
+    Ok(format!("{chunk}\n\n{note}"))
+}
+
+/// Definition of the `read_more` tool.
+pub fn read_more_tool() -> McpTool {
+    McpTool {
+        name: "read_more".into(),
+        description: Some(
+            "Read more of a tool result that was truncated because it was too long.".into(),
+        ),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "description": "ID of the truncated result"},
+                "offset": {"type": "number", "description": "Character offset to start reading at"},
+                "length": {"type": "number", "description": "Number of characters to read (at most 8000)"}
+            },
+            "required": ["id", "offset"]
+        }),
+        annotations: None,
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright © 2025 Nipun Kumar
+
+//! The model's scratchpad: notes it keeps for itself during long runs.
+//!
+//! The builtin server offers `write_note` and `read_notes`. Notes are not
+//! stored apart from the conversation; they are the arguments of the
+//! `write_note` calls in it. Requests leave those calls out and list the notes
+//! in the system prompt instead, and the chat shows them in a collapsible
+//! panel rather than as messages, so they do not clutter the transcript.
+
+use anyhow::bail;
+use serde_json::{Value, json};
+
+use crate::{
+    llm::Message,
+    mcp::{
+        McpTool, ToolResult, ToolResultContent,
+        host::{MCPHost, MCPServer},
+        tool_ref::ToolRef,
+    },
+};
+
+/// ID of the scratchpad server.
+pub const SCRATCHPAD_SERVER: &str = "scratchpad";
+
+/// Introduces the notes in the system prompt of a request.
+const NOTES_INTRO: &str = "Notes you wrote in your scratchpad with write_note. The user does \
+not see them in the conversation:";
+
+/// Whether a tool name, as the model calls it, is a scratchpad tool.
+pub fn is_scratchpad_tool(name: &str) -> bool {
+    ToolRef::decode(name).is_some_and(|t| t.server_id == SCRATCHPAD_SERVER)
+}
+
+/// Whether a message is an assistant message that only calls scratchpad
+/// tools, with no text of its own.
+pub fn is_scratchpad_only(message: &Message) -> bool {
+    match message {
+        Message::Assistant {
+            content,
+            tool_calls: Some(calls),
+        } => {
+            content.as_deref().is_none_or(|c| c.trim().is_empty())
+                && !calls.is_empty()
+                && calls.iter().all(|c| {
+                    c.function
+                        .as_ref()
+                        .and_then(|f| f.name.as_deref())
+                        .is_some_and(is_scratchpad_tool)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// The notes written in a conversation, oldest first.
+///
+/// # Arguments
+/// * `messages` - The conversation
+pub fn notes(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .filter_map(|m| match m {
+            Message::Assistant {
+                tool_calls: Some(calls),
+                ..
+            } => Some(calls),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|c| c.function.as_ref())
+        .filter(|f| {
+            f.name
+                .as_deref()
+                .and_then(ToolRef::decode)
+                .is_some_and(|t| t.server_id == SCRATCHPAD_SERVER && t.tool == "write_note")
+        })
+        .filter_map(|f| {
+            let args: Value = serde_json::from_str(f.arguments.as_deref()?).ok()?;
+            let note = args.get("note")?.as_str()?.trim();
+            (!note.is_empty()).then(|| note.to_string())
+        })
+        .collect()
+}
+
+/// Takes the scratchpad calls and their results out of a request and lists
+/// the notes in its system prompt instead.
+///
+/// # Arguments
+/// * `messages` - The messages to send
+/// * `notes` - The notes written in the whole conversation, which may be
+///   longer than the messages sent
+///
+/// # Returns
+/// The messages to send, without scratchpad calls, with the notes in the
+/// system prompt
+pub fn with_notes(messages: Vec<Message>, notes: &[String]) -> Vec<Message> {
+    let mut hidden = vec![];
+    let mut messages: Vec<Message> = messages
+        .into_iter()
+        .filter_map(|m| match m {
+            Message::Assistant {
+                content,
+                tool_calls: Some(calls),
+            } => {
+                let (scratchpad, calls): (Vec<_>, Vec<_>) = calls.into_iter().partition(|c| {
+                    c.function
+                        .as_ref()
+                        .and_then(|f| f.name.as_deref())
+                        .is_some_and(is_scratchpad_tool)
+                });
+                hidden.extend(scratchpad.into_iter().filter_map(|c| c.id));
+                if calls.is_empty() && content.as_deref().is_none_or(|c| c.trim().is_empty()) {
+                    return None;
+                }
+                Some(Message::Assistant {
+                    content,
+                    tool_calls: (!calls.is_empty()).then_some(calls),
+                })
+            }
+            Message::Tool { tool_call_id, .. } if hidden.contains(&tool_call_id) => None,
+            m => Some(m),
+        })
+        .collect();
+    if notes.is_empty() {
+        return messages;
+    }
+    let list = notes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| format!("{}. {n}", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let section = format!("{NOTES_INTRO}\n\n{list}");
+    match messages.first_mut() {
+        Some(Message::System { content }) => content.push_str(&format!("\n\n{section}")),
+        _ => messages.insert(0, Message::System { content: section }),
+    }
+    messages
+}
+
+/// Serves the scratchpad from a toolset's host, holding the notes written
+/// in the conversation so far.
+///
+/// # Arguments
+/// * `host` - Host of the chat's toolset
+/// * `messages` - The conversation
+pub async fn serve_scratchpad(host: &MCPHost, messages: &[Message]) {
+    let server = ScratchpadMcpServer {
+        notes: notes(messages),
+    };
+    host.set_builtin_server(SCRATCHPAD_SERVER, Some(Box::new(server)))
+        .await;
+}
+
+/// Built-in MCP server where the model keeps notes for itself.
+pub struct ScratchpadMcpServer {
+    /// Notes written so far, oldest first
+    pub notes: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl MCPServer for ScratchpadMcpServer {
+    /// Returns the tools for writing and reading notes.
+    async fn list_tools(&self) -> Vec<McpTool> {
+        vec![
+            McpTool {
+                name: "write_note".into(),
+                description: Some(
+                    "Write a note to your scratchpad: plans, findings or intermediate results \
+                     to remember during a long task. Notes are kept for the rest of the \
+                     conversation but not shown to the user as messages."
+                        .into(),
+                ),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "note": {
+                            "type": "string",
+                            "description": "Text of the note"
+                        }
+                    },
+                    "required": ["note"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "read_notes".into(),
+                description: Some("Read the notes in your scratchpad, oldest first.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                annotations: None,
+            },
+        ]
+    }
+
+    /// Handles tool calls for the scratchpad server.
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let (text, is_error) = match name {
+            "write_note" => {
+                let note = args.get("note").and_then(|v| v.as_str()).unwrap_or("");
+                if note.trim().is_empty() {
+                    ("No note given".to_string(), Some(true))
+                } else {
+                    self.notes.push(note.trim().to_string());
+                    (format!("Noted ({} notes).", self.notes.len()), None)
+                }
+            }
+            "read_notes" if self.notes.is_empty() => ("No notes yet.".to_string(), None),
+            "read_notes" => (
+                self.notes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| format!("{}. {n}", i + 1))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                None,
+            ),
+            _ => bail!("Unknown tool: {name}"),
+        };
+        Ok(serde_json::to_value(ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".into(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error,
+        })?)
+    }
+}
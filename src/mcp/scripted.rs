@@ -0,0 +1,314 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Tools defined in settings as small Rhai scripts.
+//!
+//! Each tool has a name, a description and a JSON schema for its arguments,
+//! which are what the model sees, and a script run when the model calls it
+//! (native only). The script gets the call's arguments as `args` and its last
+//! value becomes the tool result: strings are returned as they are, anything
+//! else as JSON.
+//!
+//! Besides Rhai's standard library, scripts can use:
+//! - `http_get(url)`, `http_get(url, headers)`: returns `#{status, body}`
+//! - `http_post(url, body)`, `http_post(url, body, headers)`: returns
+//!   `#{status, body}`; a map body is sent as JSON
+//! - `from_json(text)` and `to_json(value)`
+//! - `print(text)`: writes to the app's log
+//!
+//! Scripts are written by the user, so unlike plugins they are not sandboxed
+//! beyond limits on how long they run.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// ID of the server serving scripted tools.
+pub const SCRIPTS_SERVER: &str = "scripts";
+
+/// A tool defined in settings as a Rhai script.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScriptedTool {
+    /// Name the model calls the tool by
+    pub name: String,
+    /// What the tool does, for the model
+    #[serde(default)]
+    pub description: String,
+    /// JSON schema of the tool's arguments
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    /// Rhai source run for each call
+    pub script: String,
+    /// Whether the tool is offered to the model
+    #[serde(default = "super::default_enabled")]
+    pub enabled: bool,
+}
+
+/// Schema of a tool without arguments
+pub fn default_input_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// Checks that a scripted tool can be served.
+///
+/// # Arguments
+/// * `tool` - The tool
+///
+/// # Returns
+/// Ok(()) if the name is valid, the schema is a JSON object and the script
+/// compiles, or what is wrong with it
+pub fn validate_tool(tool: &ScriptedTool) -> anyhow::Result<()> {
+    let name = &tool.name;
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Tool names must be 1 to 64 characters long");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_' && *c != '-')
+    {
+        anyhow::bail!("Tool name {name:?} contains {c:?}; use letters, digits, '_' and '-'");
+    }
+    if !tool.input_schema.is_object() {
+        anyhow::bail!("The schema of {name} must be a JSON object");
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    native::engine()
+        .compile(&tool.script)
+        .map_err(|e| anyhow::anyhow!("The script of {name} does not compile: {e}"))?;
+    Ok(())
+}
+
+/// Creates the server for the enabled scripted tools.
+///
+/// # Arguments
+/// * `tools` - Tools in settings
+///
+/// # Returns
+/// The server, or `None` if no tool is enabled. Tools whose script does not
+/// compile are left out.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn scripted_tools_server(tools: &[ScriptedTool]) -> Option<Box<dyn super::host::MCPServer>> {
+    let tools: Vec<_> = tools.iter().filter(|t| t.enabled).cloned().collect();
+    if tools.is_empty() {
+        return None;
+    }
+    Some(Box::new(native::ScriptedToolsMcpServer::new(tools)))
+}
+
+/// Creates the server for the enabled scripted tools.
+///
+/// # Returns
+/// `None`, as scripted tools need the desktop app
+#[cfg(target_arch = "wasm32")]
+pub fn scripted_tools_server(tools: &[ScriptedTool]) -> Option<Box<dyn super::host::MCPServer>> {
+    if tools.iter().any(|t| t.enabled) {
+        dioxus::logger::tracing::warn!("Scripted tools need the desktop app");
+    }
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{sync::Arc, time::Duration};
+
+    use anyhow::bail;
+    use dioxus::logger::tracing::{info, warn};
+    use rhai::{AST, Dynamic, Engine, EvalAltResult, Map, Scope};
+    use serde_json::Value;
+
+    use super::ScriptedTool;
+    use crate::mcp::{McpTool, ToolResult, ToolResultContent, host::MCPServer};
+
+    /// Operations a script may run per call before it is stopped.
+    const MAX_OPERATIONS: u64 = 50_000_000;
+
+    /// Largest response body a script may fetch, in bytes.
+    const MAX_FETCH_BYTES: usize = 5_000_000;
+
+    /// Most items an array or object map of a script may hold.
+    const MAX_COLLECTION_SIZE: usize = 100_000;
+
+    type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+    /// Builtin server running the scripted tools in settings.
+    pub struct ScriptedToolsMcpServer {
+        engine: Arc<Engine>,
+        tools: Vec<(ScriptedTool, AST)>,
+    }
+
+    impl ScriptedToolsMcpServer {
+        /// Compiles the scripts of the given tools.
+        ///
+        /// # Arguments
+        /// * `tools` - The tools to serve
+        pub fn new(tools: Vec<ScriptedTool>) -> Self {
+            let engine = engine();
+            let tools = tools
+                .into_iter()
+                .filter_map(|tool| match engine.compile(&tool.script) {
+                    Ok(ast) => Some((tool, ast)),
+                    Err(e) => {
+                        warn!("Script of tool {} does not compile: {e}", tool.name);
+                        None
+                    }
+                })
+                .collect();
+            Self {
+                engine: Arc::new(engine),
+                tools,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MCPServer for ScriptedToolsMcpServer {
+        /// Returns the scripted tools whose script compiled.
+        async fn list_tools(&self) -> Vec<McpTool> {
+            self.tools
+                .iter()
+                .map(|(tool, _)| McpTool {
+                    name: tool.name.clone(),
+                    description: Some(tool.description.clone()),
+                    input_schema: tool.input_schema.clone(),
+                    annotations: None,
+                })
+                .collect()
+        }
+
+        /// Handles tool calls by running the tool's script on a blocking thread.
+        async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+            if method != "tools/call" {
+                bail!("Error: unknown RPC method {method}");
+            }
+            let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let Some((_, ast)) = self.tools.iter().find(|(t, _)| t.name == name) else {
+                bail!("Unknown tool: {name}");
+            };
+            let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let engine = self.engine.clone();
+            let ast = ast.clone();
+            let result = tokio::task::spawn_blocking(move || run(&engine, &ast, &args)).await?;
+
+            let (text, is_error) = match result {
+                Ok(text) => (text, None),
+                Err(e) => (format!("Script error: {e}"), Some(true)),
+            };
+            Ok(serde_json::to_value(ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".into(),
+                    text: Some(text),
+                    ..Default::default()
+                }],
+                is_error,
+            })?)
+        }
+    }
+
+    /// Runs a tool's script.
+    ///
+    /// # Arguments
+    /// * `engine` - Engine with the host functions
+    /// * `ast` - The compiled script
+    /// * `args` - Arguments of the call, bound to `args`
+    ///
+    /// # Returns
+    /// The script's value as text
+    fn run(engine: &Engine, ast: &AST, args: &Value) -> anyhow::Result<String> {
+        let mut scope = Scope::new();
+        scope.push_dynamic("args", rhai::serde::to_dynamic(args).map_err(to_anyhow)?);
+        let value: Dynamic = engine
+            .eval_ast_with_scope(&mut scope, ast)
+            .map_err(to_anyhow)?;
+        if value.is_string() {
+            return Ok(value.into_string().unwrap_or_default());
+        }
+        let value: Value = rhai::serde::from_dynamic(&value).map_err(to_anyhow)?;
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Creates an engine with the host functions and limits for scripted tools.
+    pub fn engine() -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(64)
+            .set_max_string_size(MAX_FETCH_BYTES)
+            .set_max_array_size(MAX_COLLECTION_SIZE)
+            .set_max_map_size(MAX_COLLECTION_SIZE);
+        engine.on_print(|text| info!("Script: {text}"));
+        engine.on_debug(|text, _, pos| info!("Script ({pos}): {text}"));
+
+        engine.register_fn("http_get", |url: &str| http("GET", url, None, Map::new()));
+        engine.register_fn("http_get", |url: &str, headers: Map| {
+            http("GET", url, None, headers)
+        });
+        engine.register_fn("http_post", |url: &str, body: Dynamic| {
+            http("POST", url, Some(body), Map::new())
+        });
+        engine.register_fn("http_post", |url: &str, body: Dynamic, headers: Map| {
+            http("POST", url, Some(body), headers)
+        });
+        engine.register_fn("from_json", |text: &str| -> ScriptResult<Dynamic> {
+            let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+            rhai::serde::to_dynamic(&value)
+        });
+        engine.register_fn("to_json", |value: Dynamic| -> ScriptResult<String> {
+            let value: Value = rhai::serde::from_dynamic(&value)?;
+            Ok(value.to_string())
+        });
+        engine
+    }
+
+    /// Makes an HTTP request for a script, blocking its thread until done.
+    ///
+    /// # Arguments
+    /// * `method` - HTTP method
+    /// * `url` - The URL
+    /// * `body` - Request body; maps and arrays are sent as JSON
+    /// * `headers` - Request headers
+    ///
+    /// # Returns
+    /// `#{status, body}`, or the error raised in the script
+    fn http(method: &str, url: &str, body: Option<Dynamic>, headers: Map) -> ScriptResult<Map> {
+        let request = async {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()?;
+            let mut builder = client.request(method.parse()?, url);
+            for (name, value) in headers {
+                builder = builder.header(name.as_str(), value.to_string());
+            }
+            builder = match body {
+                Some(body) if body.is_string() => {
+                    builder.body(body.into_string().unwrap_or_default())
+                }
+                Some(body) => {
+                    let json: Value = rhai::serde::from_dynamic(&body).map_err(to_anyhow)?;
+                    builder.json(&json)
+                }
+                None => builder,
+            };
+            let mut response = builder.send().await?;
+            let status = response.status().as_u16();
+            // Read in chunks, so a large body is refused before it is all in memory
+            let mut body = vec![];
+            while let Some(chunk) = response.chunk().await? {
+                if body.len() + chunk.len() > MAX_FETCH_BYTES {
+                    bail!("The response is larger than {MAX_FETCH_BYTES} bytes");
+                }
+                body.extend_from_slice(&chunk);
+            }
+            anyhow::Ok((status, String::from_utf8_lossy(&body).into_owned()))
+        };
+        let (status, body) = tokio::runtime::Handle::current()
+            .block_on(request)
+            .map_err(|e| e.to_string())?;
+        let mut response = Map::new();
+        response.insert("status".into(), Dynamic::from_int(status.into()));
+        response.insert("body".into(), body.into());
+        Ok(response)
+    }
+
+    /// Converts a script error, which is not `Send`, into an `anyhow` error.
+    fn to_anyhow(e: Box<EvalAltResult>) -> anyhow::Error {
+        anyhow::anyhow!("{e}")
+    }
+}
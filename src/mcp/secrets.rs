@@ -0,0 +1,179 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Secrets referenced from server specifications.
+//!
+//! Arguments and environment values of a server may refer to secrets as
+//! `$NAME` or `${NAME}`; `$$` stands for a single `$`. The references are
+//! resolved when the server is spawned, from the secrets store of the active
+//! profile and then from the app's own environment, so settings files hold
+//! only the names and can be shared without leaking keys.
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+
+use crate::{
+    mcp::ServerSpec,
+    storage::{Storage, get_storage},
+};
+
+/// Secret values by name.
+pub type Secrets = BTreeMap<String, String>;
+
+/// Loads the secrets of the active profile.
+pub async fn load_secrets() -> anyhow::Result<Secrets> {
+    get_storage().await?.load_secrets().await
+}
+
+/// Stores a secret in the active profile, replacing any with the same name.
+///
+/// # Arguments
+/// * `name` - Name the secret is referenced by
+/// * `value` - The secret
+pub async fn save_secret(name: &str, value: &str) -> anyhow::Result<()> {
+    if !is_secret_name(name) {
+        bail!("{name:?} is not a valid secret name; use letters, digits and _");
+    }
+    let storage = get_storage().await?;
+    let mut secrets = storage.load_secrets().await?;
+    secrets.insert(name.to_string(), value.to_string());
+    storage.save_secrets(&secrets).await
+}
+
+/// Deletes a secret from the active profile.
+///
+/// # Arguments
+/// * `name` - Name of the secret
+pub async fn delete_secret(name: &str) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let mut secrets = storage.load_secrets().await?;
+    secrets.remove(name);
+    storage.save_secrets(&secrets).await
+}
+
+/// Whether a name can be referenced as `$NAME`: letters, digits and `_`, not
+/// starting with a digit.
+pub fn is_secret_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Name for a secret holding a server's environment variable, such as
+/// `BRAVE_API_KEY` for the `API_KEY` variable of the `brave` server.
+pub fn secret_name_for(server_id: &str, key: &str) -> String {
+    let name = if server_id.is_empty() {
+        key.to_string()
+    } else {
+        format!("{server_id}_{key}")
+    };
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl ServerSpec {
+    /// Resolves the secrets referenced in the arguments and environment values.
+    ///
+    /// # Arguments
+    /// * `secrets` - Secret values by name
+    ///
+    /// # Returns
+    /// The specification with the references replaced, or an error naming a
+    /// secret that is not set
+    pub fn with_secrets(&self, secrets: &Secrets) -> anyhow::Result<ServerSpec> {
+        let resolve = |text: &str| resolve_secrets(text, secrets);
+        let mut spec = self.clone();
+        for arg in &mut spec.args {
+            *arg = resolve(arg).map_err(|e| anyhow::anyhow!("{}: {e}", self.id))?;
+        }
+        for value in spec.env.values_mut() {
+            *value = resolve(value).map_err(|e| anyhow::anyhow!("{}: {e}", self.id))?;
+        }
+        Ok(spec)
+    }
+
+    /// Names of the secrets referenced in the arguments and environment values.
+    pub fn secret_refs(&self) -> Vec<String> {
+        let mut names = vec![];
+        for text in self.args.iter().chain(self.env.values()) {
+            let _ = substitute(text, |name| {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                Some(String::new())
+            });
+        }
+        names
+    }
+}
+
+/// Replaces the secret references in a text, from the secrets and then from
+/// the app's environment.
+///
+/// # Returns
+/// The text with the references replaced, or an error naming a secret that
+/// is not set
+pub fn resolve_secrets(text: &str, secrets: &Secrets) -> anyhow::Result<String> {
+    substitute(text, |name| {
+        secrets
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+    })
+}
+
+/// Replaces `$NAME` and `${NAME}` in a text; `$$` becomes `$`, and a `$` not
+/// followed by a name is kept.
+///
+/// # Arguments
+/// * `text` - Text with references
+/// * `lookup` - Value of a name, or `None` if it is not set
+///
+/// # Returns
+/// The text with the references replaced, or an error naming the first
+/// reference that is not set
+fn substitute(
+    text: &str,
+    mut lookup: impl FnMut(&str) -> Option<String>,
+) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) if is_secret_name(&braced[..end]) => (&braced[..end], end + 2),
+                _ => bail!("Unterminated or invalid reference in {text:?}"),
+            }
+        } else if let Some(escaped) = after.strip_prefix('$') {
+            out.push('$');
+            rest = escaped;
+            continue;
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        if !is_secret_name(name) {
+            // A lone `$`, as in a price or a regular expression
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        match lookup(name) {
+            Some(value) => out.push_str(&value),
+            None => bail!("No secret named {name}; add it under Secrets in the settings"),
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
@@ -11,10 +11,20 @@ use tokio::sync::Mutex;
 
 use crate::mcp::host::MCPServer;
 use crate::mcp::jsonrpc::{RpcMessage, RpcRequest};
-use crate::mcp::{McpTool, ServerSpec};
+use crate::mcp::{McpTool, ServerSpec, ToolResult};
+
+/// Tool an external server can expose to have its state persisted across restarts.
+/// It takes no arguments and returns the state as text (JSON or plain).
+const EXPORT_STATE_TOOL: &str = "export_state";
+/// Tool an external server can expose to restore persisted state.
+/// It receives the exported state as its `state` argument.
+const IMPORT_STATE_TOOL: &str = "import_state";
+
+/// How long a server gets to exit after its stdin is closed before it is killed.
+#[cfg(not(target_arch = "wasm32"))]
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
 
 pub struct _McpServer {
-    #[allow(unused)]
     pub spec: ServerSpec,
     #[cfg(not(target_arch = "wasm32"))]
     transport: Mutex<crate::mcp::transport::StdioTransport>,
@@ -27,12 +37,51 @@ pub struct _McpServer {
 #[async_trait::async_trait]
 impl MCPServer for _McpServer {
     async fn list_tools(&self) -> Vec<McpTool> {
-        self.tool_cache.lock().await.clone()
+        // State persistence tools are for the host, not the LLM
+        self.tool_cache
+            .lock()
+            .await
+            .iter()
+            .filter(|t| t.name != EXPORT_STATE_TOOL && t.name != IMPORT_STATE_TOOL)
+            .cloned()
+            .collect()
     }
 
     async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
         self.rpc_call(method, params).await
     }
+
+    async fn export_state(&mut self) -> Option<Value> {
+        if !self.has_tool(EXPORT_STATE_TOOL).await {
+            return None;
+        }
+        let result = self
+            .call_state_tool(EXPORT_STATE_TOOL, json!({}))
+            .await
+            .map_err(|e| warn!("Could not export state of {}: {e:?}", self.spec.id))
+            .ok()?;
+        let text: Vec<String> = result.content.into_iter().filter_map(|c| c.text).collect();
+        let text = text.join("\n");
+        Some(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+    }
+
+    async fn import_state(&mut self, state: Value) -> anyhow::Result<()> {
+        if !self.has_tool(IMPORT_STATE_TOOL).await {
+            return Ok(());
+        }
+        self.call_state_tool(IMPORT_STATE_TOOL, json!({ "state": state }))
+            .await?;
+        Ok(())
+    }
+
+    fn is_trusted(&self) -> bool {
+        false
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn shutdown(&mut self) {
+        self.transport.lock().await.shutdown(SHUTDOWN_GRACE).await;
+    }
 }
 
 impl _McpServer {
@@ -57,29 +106,24 @@ impl _McpServer {
         req_timeout: Duration,
         startup_timeout: Duration,
     ) -> Result<Self> {
-        use crate::mcp::transport::StdioTransport;
+        use crate::mcp::transport::{StdioTransport, build_command};
         use tokio::time::timeout;
 
-        let mut cmd = tokio::process::Command::new(&spec.cmd);
-        cmd.args(&spec.args)
-            .stdin(std::process::Stdio::piped())
+        // Secrets are resolved for the process only; the spec keeps the references
+        let secrets = crate::mcp::secrets::load_secrets().await?;
+        let mut cmd = build_command(&spec.with_secrets(&secrets)?)?;
+        cmd.stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped());
-
-        // Set environment variables
-        for (key, value) in &spec.env {
-            cmd.env(key, value);
-        }
+            .stderr(std::process::Stdio::piped())
+            // Fallback for servers dropped without a shutdown
+            .kill_on_drop(true);
 
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("spawning {}", spec.id))?;
+        let child = cmd.spawn().with_context(|| match spec.sandbox.wrapper.program() {
+            Some(wrapper) => format!("spawning {} with {wrapper} (is it installed?)", spec.id),
+            None => format!("spawning {}", spec.id),
+        })?;
 
-        let stdout = child.stdout.take().ok_or_else(|| anyhow!("no stdout"))?;
-        let stderr = child.stderr.take().ok_or_else(|| anyhow!("no stderr"))?;
-        let stdin = child.stdin.take().ok_or_else(|| anyhow!("no stdin"))?;
-
-        let transport = StdioTransport::new(stdout, stderr, stdin);
+        let transport = StdioTransport::new(child)?;
         let server = Self {
             spec,
             transport: Mutex::new(transport),
@@ -171,6 +215,27 @@ impl _McpServer {
         Ok(Value::Null)
     }
 
+    async fn has_tool(&self, name: &str) -> bool {
+        self.tool_cache.lock().await.iter().any(|t| t.name == name)
+    }
+
+    async fn call_state_tool(&self, name: &str, arguments: Value) -> Result<ToolResult> {
+        let result = self
+            .rpc_call(
+                "tools/call",
+                json!({
+                    "name": name,
+                    "arguments": arguments,
+                }),
+            )
+            .await?;
+        let result: ToolResult = serde_json::from_value(result)?;
+        if result.is_error == Some(true) {
+            return Err(anyhow!("{name} failed: {:?}", result.content));
+        }
+        Ok(result)
+    }
+
     pub async fn refresh_tools(&self) -> Result<()> {
         let tools = self.rpc_call("tools/list", json!({})).await?;
         let tools: Vec<McpTool> =
@@ -0,0 +1,403 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Builtin server running shell commands (native only).
+//!
+//! Only commands on the user's allowlist can run, and only inside the working
+//! directory from the settings. An allowlist entry is a program, optionally
+//! followed by arguments every allowed command must start with: `cargo` allows
+//! any cargo command, `git status` only `git status` with any further
+//! arguments. Commands run directly, without a shell, so pipes, redirections
+//! and variables are passed to the program as plain arguments.
+//!
+//! The server is not trusted, and every call asks the user for approval with
+//! the command line shown, whatever they answered before. Output is shown in
+//! the chat while the command runs (see [`crate::mcp::live_output`]).
+
+use serde::{Deserialize, Serialize};
+
+/// ID of the shell server.
+pub const SHELL_SERVER: &str = "shell";
+
+/// Seconds a command may run before it is killed, by default.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Settings of the shell server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShellSettings {
+    /// Whether the server is offered to the model
+    #[serde(default)]
+    pub enabled: bool,
+    /// Directory commands run in; they may not leave it
+    #[serde(default)]
+    pub working_dir: String,
+    /// Commands that may run, e.g. "cargo" or "git status"
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    /// Seconds a command may run before it is killed
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for ShellSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            working_dir: String::new(),
+            allowlist: vec![],
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// Whether the allowlist allows a command.
+///
+/// # Arguments
+/// * `allowlist` - Allowlist entries, e.g. "cargo" or "git status"
+/// * `command` - The program followed by its arguments
+pub fn is_allowed(allowlist: &[String], command: &[String]) -> bool {
+    allowlist.iter().any(|entry| {
+        let words: Vec<&str> = entry.split_whitespace().collect();
+        !words.is_empty()
+            && words.len() <= command.len()
+            && words.iter().zip(command).all(|(w, c)| w == c)
+    })
+}
+
+/// Creates the shell server for the settings.
+///
+/// # Arguments
+/// * `settings` - Settings of the shell server
+///
+/// # Returns
+/// The server, or `None` if it is turned off or has no working directory
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shell_server(settings: &ShellSettings) -> Option<Box<dyn super::host::MCPServer>> {
+    if !settings.enabled || settings.working_dir.trim().is_empty() {
+        return None;
+    }
+    Some(Box::new(native::ShellMcpServer::new(settings.clone())))
+}
+
+/// Creates the shell server for the settings.
+///
+/// # Returns
+/// `None`, as running commands needs the desktop app
+#[cfg(target_arch = "wasm32")]
+pub fn shell_server(settings: &ShellSettings) -> Option<Box<dyn super::host::MCPServer>> {
+    if settings.enabled {
+        dioxus::logger::tracing::warn!("The shell server needs the desktop app");
+    }
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{
+        path::{Path, PathBuf},
+        process::Stdio,
+        time::Duration,
+    };
+
+    use anyhow::{Context, bail};
+    use serde_json::{Value, json};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncRead, BufReader},
+        process::Command,
+        sync::mpsc,
+    };
+
+    use super::{ShellSettings, is_allowed};
+    use crate::mcp::{McpTool, ToolResult, ToolResultContent, host::MCPServer, live_output};
+
+    /// Characters of output kept for the tool result; earlier output is dropped.
+    const MAX_OUTPUT_CHARS: usize = 100_000;
+
+    /// Builtin MCP server running allowlisted commands.
+    pub struct ShellMcpServer {
+        settings: ShellSettings,
+    }
+
+    /// A command the model asked to run.
+    struct Invocation {
+        /// The program followed by its arguments
+        command: Vec<String>,
+        /// Directory to run in, relative to the working directory
+        cwd: Option<String>,
+    }
+
+    impl Invocation {
+        fn parse(arguments: &Value) -> anyhow::Result<Self> {
+            let program = arguments
+                .get("program")
+                .and_then(|v| v.as_str())
+                .context("`program` is required")?;
+            let mut command = vec![program.to_string()];
+            if let Some(args) = arguments.get("args").and_then(|v| v.as_array()) {
+                for arg in args {
+                    match arg {
+                        Value::String(s) => command.push(s.clone()),
+                        other => command.push(other.to_string()),
+                    }
+                }
+            }
+            let cwd = arguments
+                .get("cwd")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string());
+            Ok(Self { command, cwd })
+        }
+
+        /// The command line as the user would type it.
+        fn command_line(&self) -> String {
+            self.command
+                .iter()
+                .map(|word| {
+                    if !word.is_empty()
+                        && word
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "-_./=:,+@%".contains(c))
+                    {
+                        word.clone()
+                    } else {
+                        format!("'{}'", word.replace('\'', r"'\''"))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+
+    impl ShellMcpServer {
+        /// Creates a server with the given settings.
+        pub fn new(settings: ShellSettings) -> Self {
+            Self { settings }
+        }
+
+        /// Resolves the directory a command runs in, which must be inside the
+        /// working directory.
+        fn resolve_cwd(&self, cwd: Option<&str>) -> anyhow::Result<PathBuf> {
+            let root = Path::new(&self.settings.working_dir)
+                .canonicalize()
+                .with_context(|| {
+                    format!(
+                        "The working directory {} is not available",
+                        self.settings.working_dir
+                    )
+                })?;
+            let Some(cwd) = cwd else {
+                return Ok(root);
+            };
+            let dir = root
+                .join(cwd)
+                .canonicalize()
+                .with_context(|| format!("{cwd} is not a directory"))?;
+            if !dir.starts_with(&root) || !dir.is_dir() {
+                bail!("{cwd} is not a directory inside the working directory");
+            }
+            Ok(dir)
+        }
+
+        /// Runs a command, streaming its output to [`live_output`].
+        ///
+        /// # Returns
+        /// The command line with the output and exit status, and whether the
+        /// command failed
+        async fn run(&self, invocation: &Invocation) -> anyhow::Result<(String, bool)> {
+            if !is_allowed(&self.settings.allowlist, &invocation.command) {
+                bail!(
+                    "`{}` is not on the allowlist. Allowed commands: {}",
+                    invocation.command_line(),
+                    self.settings.allowlist.join(", ")
+                );
+            }
+            let dir = self.resolve_cwd(invocation.cwd.as_deref())?;
+            let mut child = Command::new(&invocation.command[0])
+                .args(&invocation.command[1..])
+                .current_dir(&dir)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()
+                .with_context(|| format!("Could not run {}", invocation.command[0]))?;
+
+            let command_line = invocation.command_line();
+            let live = LiveEntry(live_output::start(format!("$ {command_line}")));
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            if let Some(stdout) = child.stdout.take() {
+                tokio::spawn(forward_lines(stdout, tx.clone()));
+            }
+            if let Some(stderr) = child.stderr.take() {
+                tokio::spawn(forward_lines(stderr, tx));
+            }
+
+            let timeout = Duration::from_secs(self.settings.timeout_secs.max(1));
+            let mut output = String::new();
+            let run = async {
+                while let Some(line) = rx.recv().await {
+                    live_output::append(live.0, &line);
+                    output.push_str(&line);
+                    if output.len() > MAX_OUTPUT_CHARS * 2 {
+                        keep_tail(&mut output);
+                    }
+                }
+                child.wait().await
+            };
+            let status = tokio::time::timeout(timeout, run).await;
+            drop(live);
+            keep_tail(&mut output);
+
+            let mut text = format!("$ {command_line}\n{output}");
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            let failed = match status {
+                Ok(Ok(status)) => match status.code() {
+                    Some(code) => {
+                        text.push_str(&format!("[exit code {code}]"));
+                        code != 0
+                    }
+                    None => {
+                        text.push_str("[killed by a signal]");
+                        true
+                    }
+                },
+                Ok(Err(e)) => {
+                    text.push_str(&format!("[could not wait for the command: {e}]"));
+                    true
+                }
+                Err(_) => {
+                    let _ = child.kill().await;
+                    text.push_str(&format!("[killed after {} seconds]", timeout.as_secs()));
+                    true
+                }
+            };
+            Ok((text, failed))
+        }
+    }
+
+    /// Entry of a running command in [`live_output`], removed when dropped,
+    /// including when the turn is stopped while the command runs.
+    struct LiveEntry(u64);
+
+    impl Drop for LiveEntry {
+        fn drop(&mut self) {
+            live_output::finish(self.0);
+        }
+    }
+
+    /// Sends the lines of a command's output stream until it closes.
+    async fn forward_lines(stream: impl AsyncRead + Unpin, tx: mpsc::UnboundedSender<String>) {
+        let mut reader = BufReader::new(stream);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send(String::from_utf8_lossy(&buf).into_owned()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the start of the output so that at most [`MAX_OUTPUT_CHARS`]
+    /// characters are left.
+    fn keep_tail(output: &mut String) {
+        let excess = output.chars().count().saturating_sub(MAX_OUTPUT_CHARS);
+        if excess == 0 {
+            return;
+        }
+        let cut = output
+            .char_indices()
+            .nth(excess)
+            .map_or(output.len(), |(i, _)| i);
+        output.replace_range(..cut, "[earlier output dropped]\n");
+    }
+
+    #[async_trait::async_trait]
+    impl MCPServer for ShellMcpServer {
+        async fn list_tools(&self) -> Vec<McpTool> {
+            let allowed = if self.settings.allowlist.is_empty() {
+                "No commands are allowed yet.".to_string()
+            } else {
+                format!("Allowed commands: {}.", self.settings.allowlist.join(", "))
+            };
+            vec![McpTool {
+                name: "run_command".into(),
+                description: Some(format!(
+                    "Run a command in the project's working directory and return its output and exit code. \
+                     The command runs without a shell, so pipes, redirections and globs do not work. \
+                     Each call needs the user's approval. {allowed}"
+                )),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "program": {"type": "string", "description": "The program to run, e.g. cargo"},
+                        "args": {"type": "array", "items": {"type": "string"}, "description": "Arguments of the program"},
+                        "cwd": {"type": "string", "description": "Directory to run in, relative to the working directory (optional)"}
+                    },
+                    "required": ["program"]
+                }),
+                annotations: None,
+            }]
+        }
+
+        /// Handles tool calls by running the command.
+        async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+            if method != "tools/call" {
+                bail!("Error: unknown RPC method {method}");
+            }
+            let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            if name != "run_command" {
+                bail!("Unknown tool: {name}");
+            }
+            let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let (text, failed) = match Invocation::parse(&args) {
+                Ok(invocation) => match self.run(&invocation).await {
+                    Ok(result) => result,
+                    Err(e) => (format!("Error: {e:#}"), true),
+                },
+                Err(e) => (format!("Error: {e:#}"), true),
+            };
+            Ok(serde_json::to_value(ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".into(),
+                    text: Some(text),
+                    ..Default::default()
+                }],
+                is_error: failed.then_some(true),
+            })?)
+        }
+
+        /// Commands are run only with the user's approval.
+        fn is_trusted(&self) -> bool {
+            false
+        }
+
+        /// Shows the command line and directory of each call.
+        fn approval_details(&self, tool_name: &str, arguments: &Value) -> Option<String> {
+            if tool_name != "run_command" {
+                return None;
+            }
+            let details = match Invocation::parse(arguments) {
+                Ok(invocation) => {
+                    let dir = match &invocation.cwd {
+                        Some(cwd) => Path::new(&self.settings.working_dir).join(cwd),
+                        None => PathBuf::from(&self.settings.working_dir),
+                    };
+                    format!("$ {}\n(in {})", invocation.command_line(), dir.display())
+                }
+                Err(e) => format!("Invalid arguments: {e}"),
+            };
+            Some(details)
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Names of MCP tools as the model sees them.
+//!
+//! Tools from all servers are offered to the model together, so each name is
+//! prefixed with its server's id: `{server_id}--{tool}`. Decoding splits at the
+//! first separator, which is only unambiguous if server ids never contain `--`
+//! and do not end with `-`; [`validate_server_id`] enforces that when servers
+//! are configured, and [`sanitize_server_id`] fixes up imported ids. Tool names
+//! may contain the separator.
+
+use anyhow::bail;
+
+/// Separator between the server id and the tool name.
+pub const SEPARATOR: &str = "--";
+
+/// A tool of a specific server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ToolRef {
+    /// Id of the server providing the tool
+    pub server_id: String,
+    /// Name of the tool on that server
+    pub tool: String,
+}
+
+impl ToolRef {
+    pub fn new(server_id: impl Into<String>, tool: impl Into<String>) -> Self {
+        Self {
+            server_id: server_id.into(),
+            tool: tool.into(),
+        }
+    }
+
+    /// Returns the name the model uses to call the tool.
+    pub fn encode(&self) -> String {
+        format!("{}{SEPARATOR}{}", self.server_id, self.tool)
+    }
+
+    /// Parses a tool name used by the model.
+    ///
+    /// # Arguments
+    /// * `name` - Name from a tool call
+    ///
+    /// # Returns
+    /// The tool, or `None` if the name has no server id or no tool name
+    pub fn decode(name: &str) -> Option<Self> {
+        let (server_id, tool) = name.split_once(SEPARATOR)?;
+        if server_id.is_empty() || tool.is_empty() {
+            return None;
+        }
+        Some(Self::new(server_id, tool))
+    }
+}
+
+/// Checks that a server id can be used in tool names.
+///
+/// Ids may contain ASCII letters, digits, `_` and `-`, but not the separator
+/// `--`, and must not end with `-`, which would run into the separator.
+///
+/// # Returns
+/// An error explaining what is wrong with the id
+pub fn validate_server_id(id: &str) -> anyhow::Result<()> {
+    if id.is_empty() {
+        bail!("Server id must not be empty");
+    }
+    if let Some(c) = id
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_' && *c != '-')
+    {
+        bail!("Server id {id:?} contains {c:?}; use letters, digits, '_' and '-'");
+    }
+    if id.contains(SEPARATOR) {
+        bail!(
+            "Server id {id:?} must not contain {SEPARATOR:?}, which separates it from tool names"
+        );
+    }
+    if id.ends_with('-') {
+        bail!("Server id {id:?} must not end with '-'");
+    }
+    Ok(())
+}
+
+/// Turns an arbitrary name into a valid server id.
+///
+/// Other characters become `_`, runs of `-` are shortened to one, and a
+/// trailing `-` is dropped.
+pub fn sanitize_server_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.trim().chars() {
+        let c = if c.is_ascii_alphanumeric() || c == '-' {
+            c
+        } else {
+            '_'
+        };
+        if !(c == '-' && id.ends_with('-')) {
+            id.push(c);
+        }
+    }
+    let id = id.trim_end_matches('-');
+    if id.is_empty() {
+        "server".to_string()
+    } else {
+        id.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_the_separator() {
+        let tool = ToolRef::new("files", "read_file");
+        assert_eq!(tool.encode(), "files--read_file");
+        assert_eq!(ToolRef::decode("files--read_file"), Some(tool));
+        assert_eq!(ToolRef::decode("read_file"), None);
+        assert_eq!(ToolRef::decode("--read_file"), None);
+        assert_eq!(ToolRef::decode("files--"), None);
+    }
+
+    #[test]
+    fn tells_tools_of_the_same_name_apart() {
+        let git = ToolRef::new("git", "status");
+        let shell = ToolRef::new("shell", "status");
+        assert_ne!(git.encode(), shell.encode());
+        assert_eq!(ToolRef::decode(&git.encode()), Some(git));
+        assert_eq!(ToolRef::decode(&shell.encode()), Some(shell));
+    }
+
+    #[test]
+    fn keeps_separators_in_tool_names() {
+        let tool = ToolRef::new("my-server", "get--all-");
+        assert_eq!(ToolRef::decode(&tool.encode()), Some(tool));
+        assert_eq!(ToolRef::decode("a--b--c"), Some(ToolRef::new("a", "b--c")));
+    }
+
+    #[test]
+    fn rejects_invalid_server_ids() {
+        for id in ["", "my server", "files/v2", "ünïcode", "a.b"] {
+            assert!(validate_server_id(id).is_err(), "{id:?} was accepted");
+        }
+        for id in ["files", "my-server", "my_server_2", "-leading"] {
+            assert!(validate_server_id(id).is_ok(), "{id:?} was refused");
+        }
+    }
+
+    #[test]
+    fn rejects_server_ids_colliding_with_the_separator() {
+        // "a-" with tool "b" and "a" with tool "-b" would both be "a---b"
+        assert_eq!(
+            ToolRef::new("a-", "b").encode(),
+            ToolRef::new("a", "-b").encode()
+        );
+        assert!(validate_server_id("a-").is_err());
+        // "a--b" with tool "c" would decode as server "a", tool "b--c"
+        assert!(validate_server_id("a--b").is_err());
+    }
+
+    #[test]
+    fn sanitizes_imported_ids() {
+        assert_eq!(sanitize_server_id(" My Server! "), "My_Server_");
+        assert_eq!(sanitize_server_id("a---b--"), "a-b");
+        assert_eq!(sanitize_server_id("---"), "server");
+        for name in ["x--y-", "hello world", "é-", "--"] {
+            assert!(validate_server_id(&sanitize_server_id(name)).is_ok());
+        }
+    }
+}
@@ -1,14 +1,99 @@
 // Copyright © 2025 Nipun Kumar
 
-use anyhow::{Context, Result};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use dioxus::logger::tracing::warn;
 use serde_json::Value;
-use tokio::process::ChildStdout;
+use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     process::ChildStdin,
 };
 
+use crate::mcp::{SandboxWrapper, ServerSpec};
+
+/// Builds the command that starts a server, applying its sandbox options.
+///
+/// With a wrapper, the server runs as `bwrap [options] -- cmd args` or
+/// `firejail [options] -- cmd args`:
+/// - bubblewrap binds the host filesystem read-only, with a fresh `/dev`; only
+///   `/tmp` and the working directory (unless `read_only`) stay writable. The
+///   network namespace is unshared for `no_network`
+/// - firejail uses `--net=none` and `--read-only=<dir>`
+///
+/// # Arguments
+/// * `spec` - Server specification
+///
+/// # Returns
+/// The command, ready for stdio to be configured, or an error if the options
+/// cannot be honoured on this platform
+pub fn build_command(spec: &ServerSpec) -> Result<Command> {
+    let sandbox = &spec.sandbox;
+    let dir = match &sandbox.working_dir {
+        Some(d) if !d.trim().is_empty() => std::path::PathBuf::from(d.trim()),
+        _ => std::env::current_dir().context("reading working directory")?,
+    };
+    let dir_str = dir.display().to_string();
+
+    let mut cmd = match sandbox.wrapper {
+        SandboxWrapper::None => {
+            if sandbox.no_network || sandbox.read_only {
+                bail!(
+                    "{}: network isolation and a read-only working directory need a sandbox wrapper",
+                    spec.id
+                );
+            }
+            Command::new(&spec.cmd)
+        }
+        wrapper if !cfg!(target_os = "linux") => {
+            bail!("{}: {wrapper:?} is only available on Linux", spec.id);
+        }
+        SandboxWrapper::Bubblewrap => {
+            let mut cmd = Command::new("bwrap");
+            cmd.args(["--ro-bind", "/", "/", "--dev", "/dev"]);
+            cmd.args(["--bind", "/tmp", "/tmp", "--die-with-parent"]);
+            // Bound last, so it applies even to a working directory under /tmp
+            let dir_bind = if sandbox.read_only {
+                "--ro-bind"
+            } else {
+                "--bind"
+            };
+            cmd.args([dir_bind, &dir_str, &dir_str]);
+            if sandbox.no_network {
+                cmd.arg("--unshare-net");
+            }
+            cmd.args(["--chdir", &dir_str, "--", &spec.cmd]);
+            cmd
+        }
+        SandboxWrapper::Firejail => {
+            let mut cmd = Command::new("firejail");
+            cmd.arg("--quiet");
+            if sandbox.read_only {
+                cmd.arg(format!("--read-only={dir_str}"));
+            }
+            if sandbox.no_network {
+                cmd.arg("--net=none");
+            }
+            cmd.args(["--", &spec.cmd]);
+            cmd
+        }
+    };
+    cmd.args(&spec.args).current_dir(&dir);
+
+    if sandbox.restrict_env {
+        cmd.env_clear();
+        for key in &sandbox.env_allowlist {
+            if let Ok(value) = std::env::var(key) {
+                cmd.env(key, value);
+            }
+        }
+    }
+    cmd.envs(&spec.env);
+    Ok(cmd)
+}
+
 #[derive(Debug)]
 pub enum InboundLine {
     Stdout(String),
@@ -16,16 +101,22 @@ pub enum InboundLine {
 }
 
 pub struct StdioTransport {
-    stdin: ChildStdin,
+    child: Child,
+    /// Closed (`None`) once shutdown starts
+    stdin: Option<ChildStdin>,
     pub rx_lines: Option<mpsc::UnboundedReceiver<InboundLine>>,
 }
 
 impl StdioTransport {
-    pub fn new(
-        stdout: ChildStdout,
-        stderr: tokio::process::ChildStderr,
-        stdin: ChildStdin,
-    ) -> Self {
+    /// Takes over a spawned server's stdio.
+    ///
+    /// The child must have piped stdin, stdout and stderr, and should be spawned
+    /// with `kill_on_drop` so it does not outlive a transport that is dropped
+    /// without [`StdioTransport::shutdown`].
+    pub fn new(mut child: Child) -> Result<Self> {
+        let stdout = child.stdout.take().context("no stdout")?;
+        let stderr = child.stderr.take().context("no stderr")?;
+        let stdin = child.stdin.take().context("no stdin")?;
         let (tx, rx) = mpsc::unbounded_channel();
 
         // stdout reader
@@ -45,20 +136,112 @@ impl StdioTransport {
             }
         });
 
-        Self {
-            stdin,
+        Ok(Self {
+            child,
+            stdin: Some(stdin),
             rx_lines: Some(rx),
-        }
+        })
     }
 
     pub async fn send_json(&mut self, v: &Value) -> Result<()> {
         let mut s = serde_json::to_string(v)?;
         s.push('\n');
-        self.stdin
+        let stdin = self.stdin.as_mut().context("server is shutting down")?;
+        stdin
             .write_all(s.as_bytes())
             .await
             .context("writing to child stdin")?;
-        self.stdin.flush().await?;
+        stdin.flush().await?;
         Ok(())
     }
+
+    /// Stops the server process the way the MCP stdio transport specifies.
+    ///
+    /// MCP has no shutdown request: the client closes the server's stdin and
+    /// waits for it to exit, killing it if it does not exit in time. Closing
+    /// stdin lets the server finish any write in progress first.
+    ///
+    /// # Arguments
+    /// * `grace` - How long to wait for the server to exit on its own
+    pub async fn shutdown(&mut self, grace: Duration) {
+        // Dropping stdin closes it
+        self.stdin.take();
+        match tokio::time::timeout(grace, self.child.wait()).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Could not wait for server to exit: {e:?}"),
+            Err(_) => {
+                warn!("Server did not exit within {grace:?}; killing it");
+                if let Err(e) = self.child.kill().await {
+                    warn!("Could not kill server: {e:?}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::SandboxOptions;
+
+    fn spec(sandbox: SandboxOptions) -> ServerSpec {
+        ServerSpec {
+            id: "test".into(),
+            cmd: "server".into(),
+            args: vec!["--stdio".into()],
+            env: Default::default(),
+            enabled: true,
+            sandbox,
+            watch: None,
+        }
+    }
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bubblewrap_binds_the_filesystem_read_only() {
+        let cmd = build_command(&spec(SandboxOptions {
+            wrapper: SandboxWrapper::Bubblewrap,
+            working_dir: Some("/tmp/project".into()),
+            ..Default::default()
+        }))
+        .unwrap();
+        let args = args(&cmd).join(" ");
+        assert!(args.starts_with("--ro-bind / / --dev /dev --bind /tmp /tmp --die-with-parent "));
+        assert!(!args.contains("--dev-bind"));
+        assert!(args.contains("--bind /tmp/project /tmp/project --chdir /tmp/project"));
+        assert!(args.ends_with("-- server --stdio"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bubblewrap_keeps_a_read_only_working_directory() {
+        let cmd = build_command(&spec(SandboxOptions {
+            wrapper: SandboxWrapper::Bubblewrap,
+            working_dir: Some("/tmp/project".into()),
+            read_only: true,
+            no_network: true,
+            ..Default::default()
+        }))
+        .unwrap();
+        let args = args(&cmd).join(" ");
+        assert!(args.contains("--ro-bind /tmp/project /tmp/project"));
+        assert!(!args.contains("--bind /tmp/project"));
+        assert!(args.contains("--unshare-net"));
+    }
+
+    #[test]
+    fn isolation_needs_a_wrapper() {
+        let err = build_command(&spec(SandboxOptions {
+            no_network: true,
+            ..Default::default()
+        }));
+        assert!(err.is_err());
+    }
 }
@@ -0,0 +1,211 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Checks of MCP server settings before they are saved.
+//!
+//! Problems are reported by field, so forms can show each next to its input.
+//! On native platforms the command must be found, as a path or on `PATH`,
+//! and the watched path and working directory must exist.
+
+use std::collections::BTreeMap;
+
+use crate::mcp::{ServerSpec, tool_ref::validate_server_id};
+
+/// Fields of a server's settings that can be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ServerField {
+    Id,
+    Command,
+    Args,
+    Env,
+    EnvAllowlist,
+    Sandbox,
+    Watch,
+    WorkingDir,
+}
+
+/// Problems found in settings, one message per field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldErrors(BTreeMap<ServerField, String>);
+
+impl FieldErrors {
+    /// Whether nothing is wrong.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The problem with a field, if any.
+    pub fn get(&self, field: ServerField) -> Option<&str> {
+        self.0.get(&field).map(String::as_str)
+    }
+
+    /// Records the first problem found with a field.
+    fn add(&mut self, field: ServerField, message: impl Into<String>) {
+        self.0.entry(field).or_insert_with(|| message.into());
+    }
+}
+
+/// Checks a server's settings.
+///
+/// # Arguments
+/// * `spec` - The settings
+/// * `taken_ids` - IDs of the other configured servers
+///
+/// # Returns
+/// The problems found, by field
+pub fn validate_server(spec: &ServerSpec, taken_ids: &[String]) -> FieldErrors {
+    let mut errors = FieldErrors::default();
+
+    if spec.id.trim().is_empty() {
+        errors.add(ServerField::Id, "Give the server an ID");
+    } else if let Err(e) = validate_server_id(&spec.id) {
+        errors.add(ServerField::Id, e.to_string());
+    } else if taken_ids.contains(&spec.id) {
+        errors.add(ServerField::Id, "Another server has this ID");
+    }
+
+    let cmd = spec.cmd.trim();
+    if cmd.is_empty() {
+        errors.add(
+            ServerField::Command,
+            "Enter the command that starts the server",
+        );
+    } else if !command_exists(cmd) {
+        let message = if cmd.contains(char::is_whitespace) {
+            "Enter only the program here, and its arguments below".to_string()
+        } else {
+            format!("{cmd} was not found, as a path or on PATH")
+        };
+        errors.add(ServerField::Command, message);
+    }
+    if let Some(wrapper) = spec.sandbox.wrapper.program()
+        && !command_exists(wrapper)
+    {
+        errors.add(ServerField::Sandbox, format!("{wrapper} is not installed"));
+    }
+
+    if spec.args.iter().any(|a| a.contains('\0')) {
+        errors.add(
+            ServerField::Args,
+            "Arguments must not contain NUL characters",
+        );
+    }
+
+    for key in spec.env.keys() {
+        if let Err(e) = validate_env_key(key) {
+            errors.add(ServerField::Env, e);
+        }
+    }
+    if spec.sandbox.restrict_env {
+        for key in &spec.sandbox.env_allowlist {
+            if let Err(e) = validate_env_key(key) {
+                errors.add(ServerField::EnvAllowlist, e);
+            }
+        }
+    }
+
+    if let Some(watch) = &spec.watch
+        && !path_exists(watch)
+    {
+        errors.add(ServerField::Watch, format!("{watch} does not exist"));
+    }
+    if let Some(dir) = &spec.sandbox.working_dir
+        && !path_exists(dir)
+    {
+        errors.add(ServerField::WorkingDir, format!("{dir} does not exist"));
+    }
+
+    errors
+}
+
+/// Checks the name of an environment variable: letters, digits and `_`,
+/// not starting with a digit.
+///
+/// # Returns
+/// Why the name is invalid, if it is
+pub fn validate_env_key(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("Environment variable names must not be empty".to_string());
+    }
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("{key} starts with a digit"));
+    }
+    if let Some(c) = key
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+        return Err(format!("{key} contains {c:?}; use letters, digits and '_'"));
+    }
+    Ok(())
+}
+
+/// Checks the URL of an HTTP API, such as a provider's.
+///
+/// # Returns
+/// Why the URL is invalid, if it is
+pub fn validate_http_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Not a valid URL: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Use an http:// or https:// URL".to_string());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("The URL has no host".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a command can be run: a path to a file, or a program on `PATH`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn command_exists(cmd: &str) -> bool {
+    find_command(cmd).is_some()
+}
+
+/// Commands cannot be checked in the browser, so they are taken as given.
+#[cfg(target_arch = "wasm32")]
+pub fn command_exists(_cmd: &str) -> bool {
+    true
+}
+
+/// Finds a command the way the shell would: as a path if it has a directory
+/// part, else in the directories on `PATH`, trying `PATHEXT` extensions on
+/// Windows.
+///
+/// # Returns
+/// Where the command is, or `None` if it was not found
+#[cfg(not(target_arch = "wasm32"))]
+pub fn find_command(cmd: &str) -> Option<std::path::PathBuf> {
+    use std::path::{Path, PathBuf};
+
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+            .split(';')
+            .map(str::to_string)
+            .collect()
+    } else {
+        vec![]
+    };
+    let candidates = |base: PathBuf| {
+        let with_ext = extensions
+            .iter()
+            .map(|ext| PathBuf::from(format!("{}{ext}", base.display())))
+            .collect::<Vec<_>>();
+        std::iter::once(base).chain(with_ext)
+    };
+    let path = Path::new(cmd);
+    if path.components().count() > 1 {
+        return candidates(path.to_path_buf()).find(|p| p.is_file());
+    }
+    let dirs = std::env::var_os("PATH")?;
+    std::env::split_paths(&dirs)
+        .flat_map(|dir| candidates(dir.join(cmd)))
+        .find(|p| p.is_file())
+}
+
+/// Whether a file or directory exists. Always true in the browser, where
+/// paths cannot be checked.
+fn path_exists(path: &str) -> bool {
+    if cfg!(target_arch = "wasm32") {
+        return true;
+    }
+    std::path::Path::new(path.trim()).exists()
+}
@@ -0,0 +1,118 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Restarting of MCP servers under development when their files change.
+//!
+//! Servers with a [`ServerSpec::watch`] path are polled for the latest
+//! modification time under that path. A change is acted on once the time has
+//! stayed the same for one more poll, so a build writing many files restarts the
+//! server once, after it is done.
+
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use dioxus::logger::tracing::{info, warn};
+
+use crate::mcp::{ServerSpec, host::MCPHost};
+
+/// How often watched paths are checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most files looked at under one watched directory.
+const MAX_FILES: usize = 10_000;
+
+/// Directories never looked into, since they are large and rarely hold the
+/// server's own code.
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", "__pycache__"];
+
+/// A watched server and what is known about its files.
+struct Watched {
+    spec: ServerSpec,
+    /// Modification time the running server was started with
+    seen: Option<SystemTime>,
+    /// A newer time waiting to settle
+    pending: Option<SystemTime>,
+}
+
+/// Reloads servers whenever their watched files change, until cancelled.
+///
+/// Disabled servers and servers without a watch path are ignored; call again
+/// with the new specs when the settings change.
+///
+/// # Arguments
+/// * `host` - Host running the servers
+/// * `specs` - Configured servers
+pub async fn watch_servers(host: Arc<MCPHost>, specs: Vec<ServerSpec>) {
+    let mut watched: Vec<Watched> = specs
+        .into_iter()
+        .filter(|s| s.enabled && s.watch.is_some())
+        .map(|spec| Watched {
+            seen: spec.watch.as_deref().and_then(latest_change),
+            spec,
+            pending: None,
+        })
+        .collect();
+    if watched.is_empty() {
+        return;
+    }
+    loop {
+        crate::core::sleep(POLL_INTERVAL).await;
+        for w in &mut watched {
+            let Some(path) = w.spec.watch.as_deref() else {
+                continue;
+            };
+            let current = latest_change(path);
+            if current == w.seen {
+                w.pending = None;
+                continue;
+            }
+            if w.pending != current {
+                w.pending = current;
+                continue;
+            }
+            info!("{path} changed, reloading server {}", w.spec.id);
+            w.seen = current;
+            w.pending = None;
+            if let Err(e) = host.reload_server(w.spec.clone()).await {
+                warn!("Could not reload server {}: {e:?}", w.spec.id);
+            }
+        }
+    }
+}
+
+/// Finds the latest modification time of a file, or of the files in a directory.
+///
+/// # Returns
+/// The time, or `None` if the path does not exist
+fn latest_change(path: &str) -> Option<SystemTime> {
+    let path = Path::new(path);
+    let mut latest = path.metadata().ok()?.modified().ok();
+    let mut dirs = vec![path.to_path_buf()];
+    let mut files = 0;
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                dirs.push(entry.path());
+            }
+            latest = latest.max(meta.modified().ok());
+            files += 1;
+            if files >= MAX_FILES {
+                return latest;
+            }
+        }
+    }
+    latest
+}
@@ -7,58 +7,169 @@
 //! It uses the pulldown-cmark parser to process Markdown and converts it to a tree of
 //! Dioxus elements.
 //!
-//! The converter supports most common Markdown elements including headings, paragraphs,
-//! lists, code blocks, emphasis, tables, and more.
+//! The converter covers CommonMark and the GitHub extensions: tables, task lists,
+//! strikethrough, footnotes, alerts and bare URLs as links.
+//!
+//! Model output is not trusted, so raw HTML in it goes through [`sanitize_html`],
+//! which keeps formatting, tables and links but drops scripts, frames, event
+//! handlers and `javascript:` URLs. Raw HTML can also be turned off in the
+//! display settings, in which case it is shown as text.
+
+use std::{cell::RefCell, rc::Rc};
 
 use dioxus::prelude::*;
-use pulldown_cmark::{Event, HeadingLevel, Parser, TagEnd};
+use pulldown_cmark::{
+    Alignment, BlockQuoteKind, CodeBlockKind, Event, HeadingLevel, LinkType, Options, Parser, Tag,
+};
+
+use crate::app_settings::AppSettings;
+
+/// Options controlling which Markdown extensions are rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MarkdownOptions {
+    /// Parse `$...$` and `$$...$$` as LaTeX math
+    pub math: bool,
+    /// Render raw HTML, after [`sanitize_html`]; otherwise it is shown as text
+    pub raw_html: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            math: true,
+            raw_html: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Builds the pulldown-cmark parser options for these rendering options.
+    fn parser_options(&self) -> Options {
+        let mut parser_options = Options::ENABLE_TABLES
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_GFM;
+        if self.math {
+            parser_options.insert(Options::ENABLE_MATH);
+        }
+        parser_options
+    }
+
+    /// Reads the rendering options from the app settings context, if one is available.
+    ///
+    /// Must be called while a component is rendering; falls back to defaults otherwise.
+    pub fn from_context() -> Self {
+        try_consume_context::<Signal<Option<AppSettings>>>()
+            .and_then(|s| s.read().as_ref().map(|s| s.display.clone()))
+            .map(|d| Self {
+                math: d.render_math,
+                raw_html: d.raw_html,
+            })
+            .unwrap_or_default()
+    }
+}
 
 /// Converts a Markdown string to a Dioxus RSX Element.
 ///
+/// Uses the rendering options from the current app settings.
+/// See [`markdown_to_rsx_with`] for details.
+pub fn markdown_to_rsx(md: &str) -> Element {
+    markdown_to_rsx_with(md, MarkdownOptions::from_context())
+}
+
+/// Converts a Markdown string to a Dioxus RSX Element using explicit options.
+///
 /// This function parses Markdown text using pulldown-cmark and converts it into
 /// a tree of Dioxus elements that can be rendered in the UI. It maintains a stack
 /// of element vectors to handle nested structures properly.
 ///
 /// # Arguments
 /// * `md` - The Markdown string to convert
+/// * `options` - Which Markdown extensions to enable
 ///
 /// # Returns
 /// A Dioxus `Element` containing the rendered Markdown content
 ///
 /// # Supported Markdown Features
-/// - Headings (H1-H6)
-/// - Paragraphs
-/// - Emphasis (italic) and strong (bold) text
-/// - Inline code and code blocks
-/// - Lists (unordered)
-/// - Tables
-/// - Blockquotes
-/// - Horizontal rules
+/// CommonMark with the GitHub extensions:
+/// - Headings (H1-H6), paragraphs, blockquotes and horizontal rules
+/// - Emphasis, strong and strikethrough text
+/// - Inline code and code blocks, with the block's language as a class
+/// - Ordered and unordered lists, nested to any depth, and task lists
+/// - Tables, with a header and column alignment
+/// - Links, images, and bare `http(s)://` URLs made into links
+/// - Footnotes, listed at the end
+/// - Alerts (`> [!NOTE]` and the like)
 /// - Line breaks
-pub fn markdown_to_rsx(md: &str) -> Element {
+/// - Inline and display math (rendered client-side by KaTeX)
+pub fn markdown_to_rsx_with(md: &str, options: MarkdownOptions) -> Element {
     // Create a Markdown parser for the input text
-    let parser = Parser::new(md);
+    let parser = Parser::new_ext(md, options.parser_options());
 
     // Stack to handle nested elements - each level contains a vector of child elements
     let mut stack: Vec<Vec<Element>> = vec![vec![]];
+    // Tags of the open elements, parallel to the stack above its first level
+    let mut tags: Vec<Tag> = vec![];
+    // Column alignments of the table being read, and the next cell's column
+    let mut alignments: Vec<Alignment> = vec![];
+    let mut column = 0;
+    let mut in_table_head = false;
+    // Alt text of the image being read; images hold only text
+    let mut alt: Option<String> = None;
+    // Footnote numbers by label, in order of first reference, and definitions
+    let mut footnote_numbers: Vec<String> = vec![];
+    let mut footnotes: Vec<(usize, String, Vec<Element>)> = vec![];
+    // Inline HTML elements left open, with their level in the stack
+    let mut html_open: Vec<(&'static str, usize)> = vec![];
+    // Inline HTML element whose content is dropped, such as a script
+    let mut html_skip: Option<String> = None;
+    // Source of the HTML block being read
+    let mut html_block = String::new();
 
     // Process each Markdown event from the parser
     for ev in parser {
+        if html_skip.is_some()
+            && matches!(
+                ev,
+                Event::Text(_)
+                    | Event::Code(_)
+                    | Event::InlineMath(_)
+                    | Event::SoftBreak
+                    | Event::HardBreak
+            )
+        {
+            continue;
+        }
         match ev {
             // Start of a container element - push a new level onto the stack
-            Event::Start(..) => {
+            Event::Start(tag) => {
+                match &tag {
+                    Tag::Table(a) => alignments = a.clone(),
+                    Tag::TableHead => {
+                        in_table_head = true;
+                        column = 0;
+                    }
+                    Tag::TableRow => column = 0,
+                    Tag::Image { .. } => alt = Some(String::new()),
+                    _ => {}
+                }
                 stack.push(vec![]);
-                {}
+                tags.push(tag);
             }
             // End of a container element - pop the stack and create the appropriate RSX element
-            Event::End(tag) => {
+            Event::End(_) => {
+                // Inline HTML elements end with the Markdown element around them
+                close_inline_html(&mut stack, &mut html_open, 1);
+                html_skip = None;
                 let children = stack.pop().unwrap().into_iter();
+                let Some(tag) = tags.pop() else { continue };
                 let node = match tag {
                     // Block-level elements
-                    TagEnd::Paragraph => rsx! {
+                    Tag::Paragraph => rsx! {
                         p { {children} }
                     },
-                    TagEnd::Heading(level) => match level {
+                    Tag::Heading { level, .. } => match level {
                         HeadingLevel::H1 => rsx! {
                             h1 { {children} }
                         },
@@ -78,50 +189,179 @@ pub fn markdown_to_rsx(md: &str) -> Element {
                             h6 { {children} }
                         }, // H6 and any other levels
                     },
-                    TagEnd::BlockQuote(_) => rsx! {
+                    Tag::BlockQuote(None) => rsx! {
                         blockquote { {children} }
                     },
-                    TagEnd::CodeBlock => rsx! {
-                        pre {
-                            code { {children} }
+                    Tag::BlockQuote(Some(kind)) => {
+                        let (class, title) = match kind {
+                            BlockQuoteKind::Note => ("note", "Note"),
+                            BlockQuoteKind::Tip => ("tip", "Tip"),
+                            BlockQuoteKind::Important => ("important", "Important"),
+                            BlockQuoteKind::Warning => ("warning", "Warning"),
+                            BlockQuoteKind::Caution => ("caution", "Caution"),
+                        };
+                        rsx! {
+                            blockquote { class: "markdown-alert markdown-alert-{class}",
+                                p { class: "markdown-alert-title", "{title}" }
+                                {children}
+                            }
                         }
-                    },
-                    TagEnd::HtmlBlock => rsx! {
-                        blockquote { {children} } // Treat HTML blocks as blockquotes for safety
-                    },
+                    }
+                    Tag::CodeBlock(kind) => {
+                        // The info string's first word names the language
+                        let language = match &kind {
+                            CodeBlockKind::Fenced(info) => info
+                                .split_whitespace()
+                                .next()
+                                .map(|l| format!("language-{l}")),
+                            CodeBlockKind::Indented => None,
+                        };
+                        rsx! {
+                            pre {
+                                code { class: language, {children} }
+                            }
+                        }
+                    }
+                    Tag::HtmlBlock => {
+                        let html = std::mem::take(&mut html_block);
+                        if options.raw_html {
+                            rsx! {
+                                div {
+                                    class: "raw-html",
+                                    dangerous_inner_html: sanitize_html(&html),
+                                }
+                            }
+                        } else {
+                            rsx! {
+                                pre { "{html}" }
+                            }
+                        }
+                    }
 
                     // List elements
-                    TagEnd::List(_) => rsx! {
+                    Tag::List(None) => rsx! {
                         ul { {children} }
                     },
-                    TagEnd::Item => rsx! {
+                    Tag::List(Some(first)) => rsx! {
+                        ol { start: (first != 1).then(|| first.to_string()), {children} }
+                    },
+                    Tag::Item => rsx! {
                         li { {children} }
                     },
 
                     // Table elements
-                    TagEnd::Table => rsx! {
-                        table { {children} }
-                    },
-                    TagEnd::TableHead => rsx! {
-                        thead { {children} }
-                    },
-                    TagEnd::TableRow => rsx! {
+                    Tag::Table(_) => {
+                        // The first child is the header; the others are rows
+                        let mut children = children;
+                        let head = children.next();
+                        rsx! {
+                            table {
+                                {head}
+                                tbody { {children} }
+                            }
+                        }
+                    }
+                    Tag::TableHead => {
+                        in_table_head = false;
+                        rsx! {
+                            thead {
+                                tr { {children} }
+                            }
+                        }
+                    }
+                    Tag::TableRow => rsx! {
                         tr { {children} }
                     },
-                    TagEnd::TableCell => rsx! {
-                        td { {children} }
-                    },
+                    Tag::TableCell => {
+                        let align = match alignments.get(column) {
+                            Some(Alignment::Left) => Some("text-align: left;"),
+                            Some(Alignment::Center) => Some("text-align: center;"),
+                            Some(Alignment::Right) => Some("text-align: right;"),
+                            _ => None,
+                        };
+                        column += 1;
+                        if in_table_head {
+                            rsx! {
+                                th { style: align, {children} }
+                            }
+                        } else {
+                            rsx! {
+                                td { style: align, {children} }
+                            }
+                        }
+                    }
 
                     // Inline formatting elements
-                    TagEnd::Emphasis => rsx! {
+                    Tag::Emphasis => rsx! {
                         em { {children} }
                     },
-                    TagEnd::Strong => rsx! {
+                    Tag::Strong => rsx! {
                         strong { {children} }
                     },
+                    Tag::Strikethrough => rsx! {
+                        del { {children} }
+                    },
+                    Tag::Superscript => rsx! {
+                        sup { {children} }
+                    },
+                    Tag::Subscript => rsx! {
+                        sub { {children} }
+                    },
+                    Tag::Link {
+                        link_type,
+                        dest_url,
+                        title,
+                        ..
+                    } => {
+                        let href = if link_type == LinkType::Email {
+                            format!("mailto:{dest_url}")
+                        } else {
+                            dest_url.to_string()
+                        };
+                        if is_safe_url(&href) {
+                            let title = (!title.is_empty()).then(|| title.to_string());
+                            rsx! {
+                                a {
+                                    href,
+                                    title,
+                                    target: "_blank",
+                                    rel: "noopener noreferrer",
+                                    {children}
+                                }
+                            }
+                        } else {
+                            rsx! {
+                                span { {children} }
+                            }
+                        }
+                    }
+                    Tag::Image {
+                        dest_url, title, ..
+                    } => {
+                        let alt = alt.take().unwrap_or_default();
+                        if is_safe_url(&dest_url) {
+                            let title = (!title.is_empty()).then(|| title.to_string());
+                            rsx! {
+                                img {
+                                    src: dest_url.to_string(),
+                                    alt,
+                                    title,
+                                    style: "max-width: 100%;",
+                                }
+                            }
+                        } else {
+                            rsx! { "{alt}" }
+                        }
+                    }
+
+                    // Footnote definitions are listed at the end
+                    Tag::FootnoteDefinition(label) => {
+                        let number = footnote_number(&mut footnote_numbers, &label);
+                        footnotes.push((number, label.to_string(), children.collect()));
+                        continue;
+                    }
 
                     // Fallback for unsupported elements
-                    // TODO: Add support for links, images, strikethrough, footnotes
                     _ => rsx! {
                         div { {children} }
                     },
@@ -131,15 +371,76 @@ pub fn markdown_to_rsx(md: &str) -> Element {
             }
             // Leaf elements that don't contain other elements
             Event::Text(text) => {
-                // Plain text content
-                stack.last_mut().unwrap().push(rsx! { "{text}" });
+                if let Some(alt) = &mut alt {
+                    alt.push_str(&text);
+                    continue;
+                }
+                // Bare URLs become links, except in code and in links
+                let literal = tags
+                    .iter()
+                    .any(|t| matches!(t, Tag::CodeBlock(_) | Tag::Link { .. }));
+                if literal {
+                    stack.last_mut().unwrap().push(rsx! { "{text}" });
+                    continue;
+                }
+                for (piece, is_url) in split_urls(&text) {
+                    let node = if is_url {
+                        rsx! {
+                            a {
+                                href: piece,
+                                target: "_blank",
+                                rel: "noopener noreferrer",
+                                "{piece}"
+                            }
+                        }
+                    } else {
+                        rsx! { "{piece}" }
+                    };
+                    stack.last_mut().unwrap().push(node);
+                }
             }
             Event::Code(code) => {
+                if let Some(alt) = &mut alt {
+                    alt.push_str(&code);
+                    continue;
+                }
                 // Inline code
                 stack.last_mut().unwrap().push(rsx! {
                     code { "{code}" }
                 });
             }
+            Event::InlineMath(tex) => {
+                // Inline math: the TeX source is kept as fallback text and in
+                // `data-tex`, which assets/math.js renders with KaTeX
+                stack.last_mut().unwrap().push(rsx! {
+                    span { class: "math math-inline", "data-tex": "{tex}", "{tex}" }
+                });
+            }
+            Event::DisplayMath(tex) => {
+                // Display math: rendered as a centered block
+                stack.last_mut().unwrap().push(rsx! {
+                    div { class: "math math-display", "data-tex": "{tex}", "{tex}" }
+                });
+            }
+            Event::TaskListMarker(checked) => {
+                // Checkbox of a task list item, shown but not editable
+                stack.last_mut().unwrap().push(rsx! {
+                    input {
+                        class: "task-list-item-checkbox",
+                        r#type: "checkbox",
+                        checked,
+                        disabled: true,
+                    }
+                });
+            }
+            Event::FootnoteReference(label) => {
+                let number = footnote_number(&mut footnote_numbers, &label);
+                stack.last_mut().unwrap().push(rsx! {
+                    sup { class: "footnote-ref",
+                        a { href: "#fn-{label}", id: "fnref-{label}", "{number}" }
+                    }
+                });
+            }
             Event::Rule => {
                 // Horizontal rule
                 stack.last_mut().unwrap().push(rsx! {
@@ -147,21 +448,773 @@ pub fn markdown_to_rsx(md: &str) -> Element {
                 });
             }
             Event::SoftBreak | Event::HardBreak => {
+                if let Some(alt) = &mut alt {
+                    alt.push(' ');
+                    continue;
+                }
                 // Line breaks
                 stack.last_mut().unwrap().push(rsx! {
                     br {}
                 });
             }
-            _ => {
-                // Ignore other events (like HTML, links, images for now)
-                // These could be implemented in future versions
+            Event::Html(html) => html_block.push_str(&html),
+            Event::InlineHtml(html) if !options.raw_html => {
+                stack.last_mut().unwrap().push(rsx! { "{html}" });
+            }
+            Event::InlineHtml(html) => {
+                // Inline HTML comes one tag at a time; only formatting is kept
+                let Some((tag, _)) = parse_tag(&html) else {
+                    continue;
+                };
+                if let Some(skipped) = &html_skip {
+                    if tag.closing && tag.name == *skipped {
+                        html_skip = None;
+                    }
+                } else if DROPPED_WITH_CONTENT.contains(&tag.name.as_str()) {
+                    if !tag.closing && !tag.self_closing {
+                        html_skip = Some(tag.name);
+                    }
+                } else if tag.name == "br" {
+                    stack.last_mut().unwrap().push(rsx! {
+                        br {}
+                    });
+                } else if let Some(name) = INLINE_TAGS.iter().find(|t| **t == tag.name) {
+                    if !tag.closing && !tag.self_closing {
+                        stack.push(vec![]);
+                        html_open.push((name, stack.len() - 1));
+                    } else if tag.closing
+                        && html_open
+                            .last()
+                            .is_some_and(|(open, level)| open == name && *level == stack.len() - 1)
+                    {
+                        let top = stack.len() - 1;
+                        close_inline_html(&mut stack, &mut html_open, top);
+                    }
+                }
             }
         }
     }
 
     // Flatten all remaining stack levels and wrap in a div
+    close_inline_html(&mut stack, &mut html_open, 1);
     let children = stack.into_iter().flatten();
+    footnotes.sort_by_key(|(number, _, _)| *number);
+    rsx! {
+        div {
+            {children}
+            if !footnotes.is_empty() {
+                section { class: "footnotes",
+                    hr {}
+                    ol {
+                        for (number , label , content) in footnotes {
+                            li { key: "{label}", id: "fn-{label}", value: "{number}",
+                                {content.into_iter()}
+                                a { href: "#fnref-{label}", title: "Back to the text", " ↩" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Closes the inline HTML elements open at the top of the stack, down to a
+/// level, adding each to its parent.
+fn close_inline_html(
+    stack: &mut Vec<Vec<Element>>,
+    html_open: &mut Vec<(&'static str, usize)>,
+    down_to: usize,
+) {
+    while let Some(&(name, level)) = html_open.last()
+        && level == stack.len() - 1
+        && level >= down_to
+    {
+        html_open.pop();
+        let children = stack.pop().unwrap().into_iter();
+        let node = match name {
+            "b" => rsx! { b { {children} } },
+            "i" => rsx! { i { {children} } },
+            "em" => rsx! { em { {children} } },
+            "strong" => rsx! { strong { {children} } },
+            "u" => rsx! { u { {children} } },
+            "s" => rsx! { s { {children} } },
+            "del" => rsx! { del { {children} } },
+            "ins" => rsx! { ins { {children} } },
+            "mark" => rsx! { mark { {children} } },
+            "small" => rsx! { small { {children} } },
+            "sub" => rsx! { sub { {children} } },
+            "sup" => rsx! { sup { {children} } },
+            "kbd" => rsx! { kbd { {children} } },
+            "code" => rsx! { code { {children} } },
+            "q" => rsx! { q { {children} } },
+            "cite" => rsx! { cite { {children} } },
+            "abbr" => rsx! { abbr { {children} } },
+            _ => rsx! { span { {children} } },
+        };
+        stack.last_mut().unwrap().push(node);
+    }
+}
+
+/// Inline HTML elements rendered inside Markdown, without their attributes.
+const INLINE_TAGS: &[&str] = &[
+    "b", "i", "em", "strong", "u", "s", "del", "ins", "mark", "small", "sub", "sup", "kbd", "code",
+    "q", "cite", "abbr", "span",
+];
+
+/// Elements dropped along with their content.
+const DROPPED_WITH_CONTENT: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "template",
+    "noscript", "noembed", "textarea", "select", "title", "svg", "math", "xmp",
+];
+
+/// Elements kept by [`sanitize_html`]; others are dropped, but their content is kept.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+/// Elements without content or closing tag.
+const VOID_TAGS: &[&str] = &["br", "hr", "img"];
+
+/// Whether an attribute is kept on an element by [`sanitize_html`].
+fn is_allowed_attribute(tag: &str, attribute: &str) -> bool {
+    match attribute {
+        "title" | "lang" | "dir" => true,
+        "href" => tag == "a",
+        "src" | "alt" | "width" | "height" => tag == "img",
+        "colspan" | "rowspan" => matches!(tag, "td" | "th"),
+        "align" => matches!(tag, "td" | "th" | "p" | "div" | "img" | "h1" | "h2" | "h3"),
+        "start" => tag == "ol",
+        "open" => tag == "details",
+        _ => false,
+    }
+}
+
+/// A tag read from raw HTML.
+#[derive(Debug, Clone, PartialEq)]
+struct HtmlTag {
+    /// Name, in lowercase
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    /// Attributes, with names in lowercase and values as written
+    attributes: Vec<(String, String)>,
+}
+
+/// Reads the tag at the start of some HTML.
+///
+/// # Returns
+/// The tag and its length in bytes, or `None` if the text does not start
+/// with a complete tag
+fn parse_tag(html: &str) -> Option<(HtmlTag, usize)> {
+    let bytes = html.as_bytes();
+    if bytes.first() != Some(&b'<') {
+        return None;
+    }
+    let closing = bytes.get(1) == Some(&b'/');
+    let mut i = if closing { 2 } else { 1 };
+    if !bytes.get(i)?.is_ascii_alphabetic() {
+        return None;
+    }
+    let name_start = i;
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+        i += 1;
+    }
+    let name = html[name_start..i].to_ascii_lowercase();
+    let mut attributes = vec![];
+    let mut self_closing = false;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i)? {
+            b'>' => {
+                i += 1;
+                break;
+            }
+            b'/' | b'=' => {
+                self_closing = bytes[i] == b'/';
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let start = i;
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && !matches!(bytes[i], b'=' | b'>' | b'/')
+        {
+            i += 1;
+        }
+        let attribute = html[start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match *bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let end = i + 1 + html[i + 1..].find(quote as char)?;
+                    value = html[i + 1..end].to_string();
+                    i = end + 1;
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = html[start..i].to_string();
+                }
+            }
+        }
+        self_closing = false;
+        attributes.push((attribute, value));
+    }
+    Some((
+        HtmlTag {
+            name,
+            closing,
+            self_closing,
+            attributes,
+        },
+        i,
+    ))
+}
+
+/// Removes what could run script or change the page from raw HTML written by
+/// a model: comments, elements outside a formatting allowlist (scripts,
+/// frames, forms and the like, some with their content), attributes outside
+/// an allowlist (event handlers, styles, classes), and links and images whose
+/// URL is not a web or mail address. Links open in a new window.
+///
+/// # Arguments
+/// * `html` - The raw HTML
+///
+/// # Returns
+/// HTML that is safe to insert into the page
+pub fn sanitize_html(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    // Element whose content is being dropped
+    let mut skipping: Option<String> = None;
+    while let Some(lt) = rest.find('<') {
+        if skipping.is_none() {
+            out.push_str(&rest[..lt]);
+        }
+        rest = &rest[lt..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some((tag, len)) = parse_tag(rest) else {
+            if skipping.is_none() {
+                out.push_str("&lt;");
+            }
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[len..];
+        if let Some(skipped) = &skipping {
+            if tag.closing && tag.name == *skipped {
+                skipping = None;
+            }
+            continue;
+        }
+        if DROPPED_WITH_CONTENT.contains(&tag.name.as_str()) {
+            if !tag.closing && !tag.self_closing {
+                skipping = Some(tag.name);
+            }
+            continue;
+        }
+        if !ALLOWED_TAGS.contains(&tag.name.as_str()) {
+            continue;
+        }
+        if tag.closing {
+            if !VOID_TAGS.contains(&tag.name.as_str()) {
+                out.push_str(&format!("</{}>", tag.name));
+            }
+            continue;
+        }
+        out.push('<');
+        out.push_str(&tag.name);
+        for (attribute, value) in &tag.attributes {
+            if !is_allowed_attribute(&tag.name, attribute) {
+                continue;
+            }
+            // Entities could hide a scheme from the check
+            if matches!(attribute.as_str(), "href" | "src")
+                && (!is_safe_url(value) || value.contains('&'))
+            {
+                continue;
+            }
+            let value = value.replace('"', "&quot;").replace('<', "&lt;");
+            out.push_str(&format!(" {attribute}=\"{value}\""));
+        }
+        if tag.name == "a" {
+            out.push_str(" target=\"_blank\" rel=\"noopener noreferrer\"");
+        }
+        out.push('>');
+    }
+    if skipping.is_none() {
+        out.push_str(rest);
+    }
+    out
+}
+
+/// Number of a footnote, given in order of first appearance.
+fn footnote_number(numbers: &mut Vec<String>, label: &str) -> usize {
+    match numbers.iter().position(|l| l == label) {
+        Some(i) => i + 1,
+        None => {
+            numbers.push(label.to_string());
+            numbers.len()
+        }
+    }
+}
+
+/// Whether a link or image URL can be followed without running script:
+/// web and mail addresses, and relative links.
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim();
+    match url.split_once(':') {
+        // A colon after a slash, `?` or `#` is not a scheme
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            let scheme = scheme.to_ascii_lowercase();
+            matches!(scheme.as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Splits text into plain pieces and bare `http(s)://` URLs, as GitHub links
+/// them. Punctuation ending a sentence is not part of the URL, nor is a
+/// closing parenthesis without an opening one.
+///
+/// # Returns
+/// The pieces, in order, each with whether it is a URL
+fn split_urls(text: &str) -> Vec<(&str, bool)> {
+    let mut pieces = vec![];
+    let mut rest = text;
+    while let Some(start) = ["https://", "http://"]
+        .iter()
+        .filter_map(|p| rest.find(p))
+        .min()
+    {
+        let after = &rest[start..];
+        let mut end = after
+            .find(|c: char| c.is_whitespace() || c == '<')
+            .unwrap_or(after.len());
+        loop {
+            let url = &after[..end];
+            let trailing = url.chars().last();
+            let unbalanced =
+                trailing == Some(')') && url.matches('(').count() < url.matches(')').count();
+            if matches!(
+                trailing,
+                Some('.' | ',' | ':' | ';' | '!' | '?' | '"' | '\'' | '*' | '_' | '~')
+            ) || unbalanced
+            {
+                end -= 1;
+            } else {
+                break;
+            }
+        }
+        // A scheme with nothing after it is not a link
+        let scheme_len = if after.starts_with("https://") { 8 } else { 7 };
+        if end <= scheme_len {
+            pieces.push((&rest[..start + scheme_len], false));
+            rest = &rest[start + scheme_len..];
+            continue;
+        }
+        if start > 0 {
+            pieces.push((&rest[..start], false));
+        }
+        pieces.push((&after[..end], true));
+        rest = &after[end..];
+    }
+    if !rest.is_empty() {
+        pieces.push((rest, false));
+    }
+    pieces
+}
+
+/// Incremental splitter for Markdown that is still being streamed.
+///
+/// Streamed text is divided into completed top-level blocks, which can no longer
+/// change, and a trailing open block which may still grow with later deltas.
+/// Only the trailing block needs to be re-parsed when new text arrives.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamingMarkdown {
+    /// All text received so far
+    text: String,
+    /// Byte offset where the trailing open block begins
+    committed: usize,
+    /// Completed top-level blocks, in order
+    blocks: Vec<String>,
+}
+
+impl StreamingMarkdown {
+    /// Updates the renderer with the full streamed text so far.
+    ///
+    /// If `text` extends the previously seen text only the new suffix is processed,
+    /// otherwise (e.g. a new response started) the state is reset.
+    pub fn sync(&mut self, text: &str, options: MarkdownOptions) {
+        if let Some(delta) = text.strip_prefix(self.text.as_str()) {
+            if !delta.is_empty() {
+                self.push(delta, options);
+            }
+        } else {
+            *self = Self::default();
+            self.push(text, options);
+        }
+    }
+
+    /// Appends a delta and commits any top-level blocks that are now complete.
+    pub fn push(&mut self, delta: &str, options: MarkdownOptions) {
+        self.text.push_str(delta);
+        let tail = &self.text[self.committed..];
+
+        // Find the start offsets of top-level blocks in the open tail
+        let mut starts = vec![];
+        let mut depth = 0usize;
+        for (ev, range) in Parser::new_ext(tail, options.parser_options()).into_offset_iter() {
+            match ev {
+                Event::Start(..) => {
+                    if depth == 0 {
+                        starts.push(range.start);
+                    }
+                    depth += 1;
+                }
+                Event::End(..) => depth = depth.saturating_sub(1),
+                _ => {
+                    if depth == 0 {
+                        starts.push(range.start);
+                    }
+                }
+            }
+        }
+
+        // Every block except the last is complete once a following block has started
+        if starts.len() < 2 {
+            return;
+        }
+        let last = starts[starts.len() - 1];
+        let mut prev = 0;
+        for &start in &starts[1..] {
+            self.blocks.push(tail[prev..start].to_string());
+            prev = start;
+        }
+        self.committed += last;
+    }
+
+    /// Completed blocks that will not change any more.
+    pub fn blocks(&self) -> &[String] {
+        &self.blocks
+    }
+
+    /// The trailing, possibly incomplete block.
+    pub fn tail(&self) -> &str {
+        &self.text[self.committed..]
+    }
+}
+
+/// Renders Markdown that is being streamed, re-rendering only the trailing block.
+///
+/// Completed blocks are rendered through memoized [`MarkdownBlock`] components,
+/// so their elements are not rebuilt or diffed when new deltas arrive.
+#[component]
+pub fn StreamingMarkdownEl(text: String) -> Element {
+    let state = use_hook(|| Rc::new(RefCell::new(StreamingMarkdown::default())));
+    let options = MarkdownOptions::from_context();
+    let (blocks, tail) = {
+        let mut state = state.borrow_mut();
+        state.sync(&text, options);
+        (state.blocks().to_vec(), state.tail().to_string())
+    };
+
     rsx! {
-        div { {children} }
+        div {
+            for (i, block) in blocks.into_iter().enumerate() {
+                MarkdownBlock { key: "{i}", md: block }
+            }
+            {markdown_to_rsx_with(&tail, options)}
+        }
+    }
+}
+
+/// A single completed Markdown block; only re-rendered when its text changes.
+#[component]
+fn MarkdownBlock(md: String) -> Element {
+    markdown_to_rsx(&md)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[component]
+    fn Fixture(md: String) -> Element {
+        markdown_to_rsx_with(&md, MarkdownOptions::default())
+    }
+
+    /// Renders Markdown to HTML, as an exported chat would show it.
+    fn render(md: &str) -> String {
+        let mut dom = VirtualDom::new_with_props(Fixture, FixtureProps { md: md.to_string() });
+        dom.rebuild_in_place();
+        dioxus_ssr::render(&dom)
+    }
+
+    /// Renders each Markdown file in `tests/fixtures/md` and compares the HTML
+    /// with the snapshot checked in next to it, one element per line. Run with
+    /// `UPDATE_SNAPSHOTS=1` to write the snapshots instead.
+    #[test]
+    fn renders_fixtures_as_in_their_snapshots() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/md");
+        let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+        let mut fixtures: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "md"))
+            .collect();
+        fixtures.sort();
+        assert!(!fixtures.is_empty(), "No fixtures in {}", dir.display());
+
+        let mut changed = vec![];
+        for fixture in fixtures {
+            let md = std::fs::read_to_string(&fixture).unwrap();
+            let html = format!("{}\n", render(&md).replace("><", ">\n<"));
+            let snapshot = fixture.with_extension("html");
+            if update {
+                std::fs::write(&snapshot, html).unwrap();
+            } else if std::fs::read_to_string(&snapshot).ok().as_ref() != Some(&html) {
+                changed.push(fixture.file_name().unwrap().to_string_lossy().into_owned());
+            }
+        }
+        assert!(
+            changed.is_empty(),
+            "{changed:?} render differently from their snapshots; if that is \
+             intended, run the tests with UPDATE_SNAPSHOTS=1 and check the diff"
+        );
+    }
+
+    #[test]
+    fn renders_tables_with_a_header_and_alignment() {
+        let html = render("| Name | Count |\n|:-----|------:|\n| a | 1 |\n| b | 2 |\n");
+        assert!(html.contains("<table"));
+        let (head, body) = html.split_once("<tbody").expect("table has a body");
+        assert!(head.contains("<thead"));
+        assert_eq!(
+            head.matches("<th").count() - head.matches("<thead").count(),
+            2
+        );
+        assert!(head.contains("text-align: left;"));
+        assert!(head.contains("text-align: right;"));
+        assert!(!body.contains("<th "));
+        assert_eq!(body.matches("<tr").count(), 2);
+        assert_eq!(body.matches("<td").count(), 4);
+        assert!(body.contains("text-align: right;"));
+    }
+
+    #[test]
+    fn renders_task_list_checkboxes() {
+        let html = render("- [x] done\n- [ ] open\n- plain\n");
+        assert_eq!(html.matches(r#"type="checkbox""#).count(), 2);
+        assert!(html.contains("task-list-item-checkbox"));
+        assert!(html.contains("disabled"));
+        let (done, _) = html.split_once("done").unwrap();
+        assert!(done.contains("checked"));
+    }
+
+    #[test]
+    fn numbers_footnotes_by_first_reference() {
+        let html = render("One[^b] and two[^a].\n\n[^a]: Note A.\n\n[^b]: Note B.\n");
+        assert!(html.contains(r##"href="#fn-b""##));
+        assert!(html.contains(r#"id="fnref-b""#));
+        let (text, notes) = html.split_once(r#"class="footnotes""#).unwrap();
+        assert!(!text.contains("Note"));
+        assert!(notes.find("Note B").unwrap() < notes.find("Note A").unwrap());
+        assert!(notes.contains(r#"id="fn-a""#));
+        assert!(notes.contains(r##"href="#fnref-a""##));
+    }
+
+    #[test]
+    fn links_bare_urls() {
+        let html = render("See https://example.com/a(b). Or <https://example.org>.\n");
+        assert!(html.contains(r#"href="https://example.com/a(b)""#));
+        assert!(html.contains(r#"href="https://example.org""#));
+        assert!(!html.contains(r#"(b).""#));
+    }
+
+    #[test]
+    fn leaves_urls_in_code_unlinked() {
+        let html = render("`https://example.com` and\n\n```\nhttps://example.com\n```\n");
+        assert!(!html.contains("<a"));
+    }
+
+    #[test]
+    fn splits_urls_from_surrounding_punctuation() {
+        assert_eq!(
+            split_urls("(see https://x.io/a), or http://y.io!"),
+            [
+                ("(see ", false),
+                ("https://x.io/a", true),
+                ("), or ", false),
+                ("http://y.io", true),
+                ("!", false),
+            ]
+        );
+        assert_eq!(
+            split_urls("https:// alone"),
+            [("https://", false), (" alone", false)]
+        );
+    }
+
+    #[test]
+    fn sanitizing_drops_scripts_frames_and_styles_with_their_content() {
+        let html = sanitize_html(
+            "<p>a<script>alert(1)</script>b<iframe src=\"https://x\"></iframe>c\
+             <style>p { color: red }</style>d<SCRIPT type=module>x()</SCRIPT>e</p>",
+        );
+        assert_eq!(html, "<p>abcde</p>");
+        // Unknown elements are dropped, but not their text
+        assert_eq!(sanitize_html("<form><b>ok</b></form>"), "<b>ok</b>");
+        assert_eq!(sanitize_html("a<!-- <script> -->b"), "ab");
+    }
+
+    #[test]
+    fn sanitizing_strips_event_handlers_and_styles() {
+        let html = sanitize_html(
+            "<img src=\"https://x/a.png\" onerror=\"alert(1)\" ONLOAD=x style=\"a\">\
+             <div class=c onclick='alert(1)' title=\"t\">d</div>",
+        );
+        assert!(!html.to_lowercase().contains("on"), "{html}");
+        assert!(!html.contains("style") && !html.contains("class"), "{html}");
+        assert!(html.contains("src=\"https://x/a.png\""));
+        assert!(html.contains("title=\"t\""));
+    }
+
+    #[test]
+    fn sanitizing_rejects_script_urls() {
+        for href in [
+            "javascript:alert(1)",
+            "JaVaScRiPt:alert(1)",
+            "  javascript:alert(1)",
+            "\tjavascript:alert(1)",
+            "java\tscript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox(1)",
+            "&#106;avascript:alert(1)",
+            "&#x6A;avascript:alert(1)",
+            "javascript&colon;alert(1)",
+        ] {
+            let html = sanitize_html(&format!("<a href=\"{href}\">x</a>"));
+            assert!(!html.contains("href"), "{href} was kept: {html}");
+            let html = sanitize_html(&format!("<img src='{href}'>"));
+            assert!(!html.contains("src"), "{href} was kept: {html}");
+        }
+        let html = sanitize_html("<a href=javascript:alert(1)>x</a>");
+        assert!(!html.contains("href"), "{html}");
+        for url in [
+            "https://example.com/a?b=c",
+            "mailto:a@b.c",
+            "/docs#x",
+            "a/b:c",
+        ] {
+            assert!(is_safe_url(url), "{url}");
+        }
+        let html = sanitize_html("<a href=\"https://example.com\">x</a>");
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("rel=\"noopener noreferrer\""));
+    }
+
+    #[test]
+    fn sanitizing_handles_unquoted_and_unterminated_attributes() {
+        assert_eq!(
+            sanitize_html("<a href=https://x.y/z title=t>l</a>"),
+            "<a href=\"https://x.y/z\" title=\"t\" target=\"_blank\" rel=\"noopener noreferrer\">l</a>"
+        );
+        // Incomplete tags are shown as text
+        assert_eq!(
+            sanitize_html("<a href=\"https://x"),
+            "&lt;a href=\"https://x"
+        );
+        assert_eq!(sanitize_html("<b title='x>y"), "&lt;b title='x>y");
+        for html in [
+            "<",
+            "<a",
+            "<a ",
+            "<a href",
+            "<a href=",
+            "<a href= ",
+            "</",
+            "<a/",
+            "<é é=é>",
+            "<b =>x",
+        ] {
+            let out = sanitize_html(html);
+            assert!(!out.contains("<a"), "{html} gave {out}");
+        }
+        // A quote inside a value cannot end the attribute
+        assert_eq!(
+            sanitize_html("<span title='a\" onclick=\"x'>s</span>"),
+            "<span title=\"a&quot; onclick=&quot;x\">s</span>"
+        );
     }
 }
@@ -0,0 +1,187 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Notifications for turns that finish while the app is in the background.
+//!
+//! The desktop app shows the platform's own notifications. Elsewhere the Web
+//! Notifications API is used, and the favicon is also badged until the tab
+//! regains focus.
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+/// Turns shorter than this never trigger a notification.
+pub const NOTIFY_AFTER_MILLIS: u64 = 10_000;
+
+/// Script run in the page: shows a notification (by default only when the document
+/// is not focused), badges the favicon while unfocused, and reports back whether
+/// the notification was clicked.
+///
+/// Clicking a notification focuses the window, and browsers differ in whether the
+/// focus event comes before or after the click event, so a focus is only reported
+/// once a click had time to arrive. Only the first outcome is sent.
+#[cfg(not(feature = "desktop"))]
+const NOTIFY_JS: &str = r##"
+const title = await dioxus.recv();
+const body = await dioxus.recv();
+const unfocusedOnly = await dioxus.recv();
+const focused = document.hasFocus();
+if (focused && unfocusedOnly) {
+    dioxus.send(false);
+    return;
+}
+let settled = false;
+const settle = (clicked) => {
+    if (!settled) {
+        settled = true;
+        dioxus.send(clicked);
+    }
+};
+
+// Badge the favicon until the window regains focus
+const icon = focused ? null : document.querySelector("link[rel~='icon']");
+const originalHref = icon ? icon.href : null;
+if (icon) {
+    const img = new Image();
+    img.onload = () => {
+        const canvas = document.createElement("canvas");
+        canvas.width = 32;
+        canvas.height = 32;
+        const ctx = canvas.getContext("2d");
+        ctx.drawImage(img, 0, 0, 32, 32);
+        ctx.fillStyle = "#dc3545";
+        ctx.beginPath();
+        ctx.arc(24, 8, 8, 0, 2 * Math.PI);
+        ctx.fill();
+        icon.href = canvas.toDataURL("image/png");
+    };
+    img.src = originalHref;
+}
+if (!focused) {
+    window.addEventListener("focus", () => {
+        if (icon && originalHref) {
+            icon.href = originalHref;
+        }
+        setTimeout(() => settle(false), 1000);
+    }, { once: true });
+}
+
+if (!("Notification" in window)) {
+    return;
+}
+let permission = Notification.permission;
+if (permission === "default") {
+    permission = await Notification.requestPermission();
+}
+if (permission !== "granted") {
+    return;
+}
+const n = new Notification(title, { body });
+n.onclick = () => {
+    window.focus();
+    settle(true);
+    n.close();
+};
+n.onclose = () => settle(false);
+"##;
+
+/// Notifies the user that a turn has finished, if the app window is not focused.
+///
+/// # Arguments
+/// * `title` - Notification title
+/// * `body` - Notification body text
+///
+/// # Returns
+/// `true` if the user clicked the notification, `false` if it was dismissed, the
+/// window was already focused, or notifications are unavailable.
+pub async fn notify_turn_finished(title: &str, body: &str) -> bool {
+    notify(title, body, true).await
+}
+
+/// Shows a notification.
+///
+/// # Arguments
+/// * `title` - Notification title
+/// * `body` - Notification body text
+/// * `unfocused_only` - Skip the notification if the app window is focused
+///
+/// # Returns
+/// `true` if the user clicked the notification
+#[cfg(not(feature = "desktop"))]
+pub async fn notify(title: &str, body: &str, unfocused_only: bool) -> bool {
+    let mut eval = document::eval(NOTIFY_JS);
+    if let Err(e) = eval
+        .send(title)
+        .and_then(|_| eval.send(body))
+        .and_then(|_| eval.send(unfocused_only))
+    {
+        warn!("Could not show notification: {e:?}");
+        return false;
+    }
+    eval.recv::<bool>().await.unwrap_or(false)
+}
+
+/// Shows a notification of the platform, and brings the window to the front
+/// when it is clicked.
+///
+/// # Arguments
+/// * `title` - Notification title
+/// * `body` - Notification body text
+/// * `unfocused_only` - Skip the notification if the app window is focused
+///
+/// # Returns
+/// `true` if the user clicked the notification
+#[cfg(feature = "desktop")]
+pub async fn notify(title: &str, body: &str, unfocused_only: bool) -> bool {
+    let window = dioxus::desktop::window();
+    if unfocused_only && window.is_focused() {
+        return false;
+    }
+    let (title, body) = (title.to_string(), body.to_string());
+    let clicked = tokio::task::spawn_blocking(move || show_native(&title, &body))
+        .await
+        .unwrap_or(false);
+    if clicked {
+        window.set_minimized(false);
+        window.set_focus();
+    }
+    clicked
+}
+
+/// Shows a notification through the desktop's notification service and waits
+/// until it is clicked or closed.
+///
+/// # Returns
+/// `true` if the user clicked the notification
+#[cfg(all(feature = "desktop", target_os = "linux"))]
+fn show_native(title: &str, body: &str) -> bool {
+    let handle = match notify_rust::Notification::new()
+        .summary(title)
+        .body(body)
+        .action("default", "Open")
+        .show()
+    {
+        Ok(handle) => handle,
+        Err(e) => {
+            warn!("Could not show notification: {e}");
+            return false;
+        }
+    };
+    let mut clicked = false;
+    handle.wait_for_action(|action| clicked = action == "default");
+    clicked
+}
+
+/// Shows a notification through the system's notification center.
+///
+/// # Returns
+/// `false`: clicks are not reported on this platform
+#[cfg(all(feature = "desktop", not(target_os = "linux")))]
+fn show_native(title: &str, body: &str) -> bool {
+    if let Err(e) = notify_rust::Notification::new()
+        .summary(title)
+        .body(body)
+        .show()
+    {
+        warn!("Could not show notification: {e}");
+    }
+    false
+}
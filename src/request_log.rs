@@ -0,0 +1,233 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Optional recording of raw LLM requests and responses, for diagnosing provider quirks.
+//!
+//! When enabled, every request made by [`crate::llm::LlmClient`] is recorded with
+//! its response: the raw SSE lines for streaming responses, or the body otherwise.
+//! The most recent [`MAX_ENTRIES`] are kept in memory for the debug panel. On
+//! native platforms entries are also appended as JSON lines to `requests.log` in
+//! the config directory, which is rotated once it grows past [`MAX_LOG_BYTES`].
+//! Each line is sealed when stored data is encrypted.
+//! API keys never reach the log: they are only sent in headers, which are not
+//! recorded, and any copy of the key in a body is replaced with `[REDACTED]`.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::now_millis;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::storage::encryption::{reseal_lines, seal};
+
+/// Number of entries kept in memory.
+pub const MAX_ENTRIES: usize = 50;
+/// Size at which the log file is rotated.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated log files kept (`requests.log.1` is the newest).
+#[cfg(not(target_arch = "wasm32"))]
+const ROTATED_LOGS: usize = 3;
+/// Placeholder for redacted secrets.
+const REDACTED: &str = "[REDACTED]";
+
+/// Whether requests are being recorded
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Recent entries, oldest first
+static ENTRIES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+/// Counter used to generate entry IDs
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    pub id: u64,
+    /// When the request was sent, in milliseconds since the Unix epoch
+    pub started_at: u64,
+    /// When the response finished, if it has
+    pub finished_at: Option<u64>,
+    pub method: String,
+    pub url: String,
+    /// Request body, if there was one
+    pub request: Option<Value>,
+    /// HTTP status of the response, once known
+    pub status: Option<u16>,
+    /// Raw response body, or the SSE data lines of a streaming response
+    pub response: String,
+    /// Why the request failed, if it did
+    pub error: Option<String>,
+}
+
+/// Turns recording on or off. Entries already recorded are kept.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether requests are being recorded.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records the start of a request.
+///
+/// # Arguments
+/// * `method` - HTTP method
+/// * `url` - Request URL
+/// * `request` - Request body, if any
+/// * `api_key` - Key sent with the request, redacted wherever it appears
+///
+/// # Returns
+/// ID to pass to the other functions, or `None` when recording is off
+pub fn start(method: &str, url: &str, request: Option<&Value>, api_key: &str) -> Option<u64> {
+    if !is_enabled() {
+        return None;
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let entry = LogEntry {
+        id,
+        started_at: now_millis(),
+        finished_at: None,
+        method: method.to_string(),
+        url: redact(url, api_key),
+        request: request.map(|r| redact_value(r, api_key)),
+        status: None,
+        response: String::new(),
+        error: None,
+    };
+    if let Ok(mut entries) = ENTRIES.lock() {
+        if entries.len() >= MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+    Some(id)
+}
+
+/// Records the status of a response.
+pub fn set_status(id: Option<u64>, status: u16) {
+    update(id, |e| e.status = Some(status));
+}
+
+/// Appends to the recorded response of a request.
+pub fn append_response(id: Option<u64>, text: &str) {
+    update(id, |e| {
+        e.response.push_str(text);
+        e.response.push('\n');
+    });
+}
+
+/// Records the end of a request and writes it to the log file.
+///
+/// # Arguments
+/// * `id` - ID from [`start`]
+/// * `error` - Why the request failed, if it did
+pub fn finish(id: Option<u64>, error: Option<String>) {
+    update(id, |e| {
+        e.finished_at = Some(now_millis());
+        e.error = error;
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(entry) = id.and_then(|id| entries().into_iter().find(|e| e.id == id))
+        && let Err(e) = write_to_file(&entry)
+    {
+        dioxus::logger::tracing::warn!("Could not write request log: {e:?}");
+    }
+}
+
+/// Returns the recorded entries, newest first.
+pub fn entries() -> Vec<LogEntry> {
+    ENTRIES
+        .lock()
+        .map(|e| e.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Forgets the entries kept in memory. The log file is left alone.
+pub fn clear() {
+    if let Ok(mut entries) = ENTRIES.lock() {
+        entries.clear();
+    }
+}
+
+/// Path of the log file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log_path() -> std::path::PathBuf {
+    crate::storage::config_dir().join("requests.log")
+}
+
+/// Applies a change to an entry, if it is still in memory.
+fn update(id: Option<u64>, f: impl FnOnce(&mut LogEntry)) {
+    let Some(id) = id else { return };
+    if let Ok(mut entries) = ENTRIES.lock()
+        && let Some(entry) = entries.iter_mut().find(|e| e.id == id)
+    {
+        f(entry);
+    }
+}
+
+/// Replaces every copy of the key in a string.
+fn redact(text: &str, api_key: &str) -> String {
+    if api_key.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(api_key, REDACTED)
+    }
+}
+
+/// Replaces every copy of the key in the strings of a JSON value.
+fn redact_value(value: &Value, api_key: &str) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact(s, api_key)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| redact_value(v, api_key)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact_value(v, api_key)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Appends an entry to the log file, rotating it first if it is too big.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_to_file(entry: &LogEntry) -> anyhow::Result<()> {
+    use std::io::Write as _;
+
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_LOG_BYTES) {
+        for i in (1..ROTATED_LOGS).rev() {
+            let _ = std::fs::rename(
+                path.with_extension(format!("log.{i}")),
+                path.with_extension(format!("log.{}", i + 1)),
+            );
+        }
+        std::fs::rename(&path, path.with_extension("log.1"))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", seal(&serde_json::to_string(entry)?)?)?;
+    Ok(())
+}
+
+/// Seals the lines of the log files again after encryption was turned on or
+/// off.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reseal_files() -> anyhow::Result<()> {
+    let path = log_path();
+    reseal_lines(&path)?;
+    for i in 1..=ROTATED_LOGS {
+        reseal_lines(&path.with_extension(format!("log.{i}")))?;
+    }
+    Ok(())
+}
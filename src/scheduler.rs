@@ -0,0 +1,431 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Scheduled, recurring prompts (native builds only).
+//!
+//! Each [`ScheduledTask`] pairs a prompt and a toolset with a cron expression. While
+//! the app is running, [`run_scheduler`] checks for due tasks every
+//! [`CHECK_INTERVAL`], runs each one as a new chat through the core tool loop,
+//! saves the chat, and shows a notification linking to it. Runs missed while the
+//! app was closed are caught up once at startup.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use dioxus::{logger::tracing::warn, prelude::*};
+use dioxus_router::Navigator;
+
+use crate::{
+    app_settings::{Chat, MessageAuthor, ScheduledTask},
+    core::{
+        MCPHost, ToolsetHooks, load_settings, new_conversation, now_millis, run_tools_loop,
+    },
+    notifications::notify,
+    storage::{Storage, get_storage},
+    toolset::registry,
+};
+
+/// How often the scheduler looks for due tasks.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A parsed five-field cron expression.
+///
+/// Supports `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`,
+/// `0-30/10`) in every field, plus the shortcuts `@hourly`, `@daily`, `@weekly`
+/// and `@monthly`. As in cron, when both day of month and day of week are
+/// restricted a time matches if either does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month field is `*`
+    any_day: bool,
+    /// Whether the day of week field is `*`
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression.
+    ///
+    /// # Arguments
+    /// * `expr` - Minute, hour, day of month, month and day of week (0 or 7 is Sunday)
+    ///
+    /// # Returns
+    /// The schedule, or an error describing the invalid field
+    pub fn parse(expr: &str) -> anyhow::Result<Self> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            e => e,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!("Expected 5 fields (minute hour day month weekday), got {}", fields.len());
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).context("Invalid day of week")?;
+        // 7 is an alias for Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59).context("Invalid minute")?,
+            hours: parse_field(hour, 0, 23).context("Invalid hour")?,
+            days: parse_field(day, 1, 31).context("Invalid day of month")?,
+            months: parse_field(month, 1, 12).context("Invalid month")?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// Finds the first matching time strictly after `after`.
+    ///
+    /// # Returns
+    /// The next run time, or `None` if the expression never matches (e.g. `0 0 30 2 *`)
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let mut t = start + chrono::Duration::minutes(1);
+        // Leap days can be up to eight years apart
+        let limit = start + chrono::Duration::days(366 * 8);
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = chrono::NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(&t) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += chrono::Duration::minutes(1);
+            } else if let Some(local) = Local.from_local_datetime(&t).earliest() {
+                return Some(local);
+            } else {
+                // Skipped by a daylight saving change
+                t += chrono::Duration::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+/// Parses one cron field into a bitmask of allowed values.
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (r, s.parse::<u32>().context("Invalid step")?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            bail!("Step must be at least 1");
+        }
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            r => match r.split_once('-') {
+                Some((a, b)) => (a.parse()?, b.parse()?),
+                // `5/10` means every 10 starting at 5
+                None if part.contains('/') => (r.parse()?, max),
+                None => {
+                    let v = r.parse()?;
+                    (v, v)
+                }
+            },
+        };
+        if lo < min || hi > max || lo > hi {
+            bail!("{part} is outside {min}-{max}");
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+/// Returns when a task will next run, in milliseconds since the Unix epoch.
+///
+/// The time is counted from the last run, or from the task's creation if it has
+/// never run, so a time in the past means the task is due.
+pub fn next_run(task: &ScheduledTask) -> Option<u64> {
+    let cron = CronSchedule::parse(&task.cron).ok()?;
+    let from = Local
+        .timestamp_millis_opt(task.last_run.unwrap_or(task.id) as i64)
+        .single()?;
+    cron.next_after(from).map(|t| t.timestamp_millis() as u64)
+}
+
+/// Lists the next `count` run times of a task after now.
+pub fn upcoming_runs(task: &ScheduledTask, count: usize) -> Vec<u64> {
+    let Ok(cron) = CronSchedule::parse(&task.cron) else {
+        return vec![];
+    };
+    let mut runs = vec![];
+    let mut t = Local::now();
+    while runs.len() < count {
+        let Some(next) = cron.next_after(t) else { break };
+        runs.push(next.timestamp_millis() as u64);
+        t = next;
+    }
+    runs
+}
+
+/// Formats a timestamp in local time for display.
+pub fn format_time(millis: u64) -> String {
+    Local
+        .timestamp_millis_opt(millis as i64)
+        .single()
+        .map(|t| t.format("%a %d %b %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Runs a task's prompt as a new chat and saves it.
+///
+/// # Arguments
+/// * `task` - The task to run
+/// * `host` - Global MCP host
+///
+/// # Returns
+/// The ID of the saved chat, and the error that stopped the run early, if any
+pub async fn run_task(
+    task: &ScheduledTask,
+    host: Arc<MCPHost>,
+) -> anyhow::Result<(u32, Option<String>)> {
+    let settings = load_settings()
+        .await?
+        .context("No settings found; configure a provider first")?;
+    let model = settings
+        .provider
+        .get_model()
+        .context("No model configured")?;
+    let client = settings.client();
+
+    let (toolset, _) = registry::create_new(&task.toolset, host);
+    let mut messages = new_conversation(toolset.as_ref(), task.prompt.clone());
+    let result = run_tools_loop(
+        &client,
+        &model,
+        toolset.get_mcp_host(),
+        &mut messages,
+        toolset.max_tool_rounds(),
+        settings.max_tool_failures,
+        &mut ToolsetHooks(toolset.as_ref()),
+    )
+    .await;
+
+    // Keep partial transcripts so failed runs can be inspected
+    let mut chat = Chat {
+        id: None,
+        chat_type: task.toolset.clone(),
+        messages,
+        value: toolset.get_state().await,
+        archive: vec![],
+        disabled_servers: Default::default(),
+        authors: Default::default(),
+        pinned: Default::default(),
+        documents: vec![],
+        message_ids: vec![],
+        next_message_id: 0,
+        message_times: Default::default(),
+        tags: Default::default(),
+        archived: false,
+        metadata: Default::default(),
+        variables: Default::default(),
+        model: None,
+    };
+    chat.set_author(0, &MessageAuthor::model(&model));
+    chat.assign_message_ids();
+    let id = get_storage().await?.save_chat(&chat).await?;
+    Ok((id, result.err().map(|e| e.to_string())))
+}
+
+/// Runs a task, records the outcome on the stored task and notifies the user.
+///
+/// The notification also tells the user if the outcome could not be recorded.
+///
+/// # Arguments
+/// * `task` - The task to run
+/// * `host` - Global MCP host
+/// * `nav` - Navigator used to open the chat when the notification is clicked
+///
+/// # Returns
+/// An error if the outcome could not be recorded
+pub async fn run_and_record(
+    task: ScheduledTask,
+    host: Arc<MCPHost>,
+    nav: Navigator,
+) -> anyhow::Result<()> {
+    let started = now_millis();
+    let outcome = run_task(&task, host).await;
+
+    // Reload so edits made while the task was running are kept
+    let recorded = async {
+        let stg = get_storage().await?;
+        let mut tasks = stg.load_schedules().await?;
+        if let Some(t) = tasks.iter_mut().find(|t| t.id == task.id) {
+            t.last_run = Some(started);
+            match &outcome {
+                Ok((id, error)) => {
+                    t.last_chat_id = Some(*id);
+                    t.last_error = error.clone();
+                }
+                Err(e) => t.last_error = Some(e.to_string()),
+            }
+        }
+        stg.save_schedules(&tasks).await
+    }
+    .await;
+
+    let (chat_id, mut body) = match &outcome {
+        Ok((id, None)) => (Some(*id), "Finished. Click to open the chat.".to_string()),
+        Ok((id, Some(e))) => (Some(*id), format!("Stopped with an error: {e}")),
+        Err(e) => (None, format!("Could not run: {e}")),
+    };
+    if let Err(e) = &recorded {
+        body.push_str(&format!("\nThe run could not be recorded: {e}"));
+    }
+    let title = format!("Scheduled: {}", task.name);
+    spawn(async move {
+        if notify(&title, &body, false).await
+            && let Some(id) = chat_id
+        {
+            nav.push(crate::Route::ChatEl {
+                id,
+                anchor: String::new(),
+            });
+        }
+    });
+    recorded.context("Could not record the run")
+}
+
+/// Checks for due tasks forever, running them one at a time.
+///
+/// When each task last ran is also kept in memory, so a task whose run could
+/// not be recorded in storage does not run again at every check.
+///
+/// # Arguments
+/// * `host` - Global MCP host
+/// * `nav` - Navigator used to open chats from notifications
+pub async fn run_scheduler(host: Arc<MCPHost>, nav: Navigator) {
+    // Start of the last run of each task, by task ID
+    let mut last_runs: HashMap<u64, u64> = HashMap::new();
+    loop {
+        let due = async {
+            let now = now_millis();
+            let tasks = get_storage().await?.load_schedules().await?;
+            anyhow::Ok(
+                tasks
+                    .into_iter()
+                    .map(|mut t| {
+                        if let Some(&run) = last_runs.get(&t.id) {
+                            t.last_run = Some(t.last_run.map_or(run, |r| r.max(run)));
+                        }
+                        t
+                    })
+                    .filter(|t| t.enabled && next_run(t).is_some_and(|n| n <= now))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        .await;
+        match due {
+            Ok(due) => {
+                for task in due {
+                    last_runs.insert(task.id, now_millis());
+                    let name = task.name.clone();
+                    if let Err(e) = run_and_record(task, host.clone(), nav).await {
+                        warn!("Scheduled task {name} failed: {e:?}");
+                    }
+                }
+            }
+            Err(e) => warn!("Could not load scheduled tasks: {e:?}"),
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Midnight on a day of June 2025, when June 1 was a Sunday.
+    fn june(day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 6, day, 0, 0, 0).unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        CronSchedule::parse(expr).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn matches_either_day_field_when_both_are_restricted() {
+        // The 10th, or any Friday
+        assert_eq!(next("0 0 10 * 5", june(7)), Some(june(10)));
+        assert_eq!(next("0 0 10 * 5", june(10)), Some(june(13)));
+        // With one field `*`, only the other restricts the day
+        assert_eq!(next("0 0 * * 5", june(7)), Some(june(13)));
+        assert_eq!(next("0 0 10 * *", june(7)), Some(june(10)));
+    }
+
+    #[test]
+    fn parses_steps_and_ranges_with_steps() {
+        let bits = |values: &[u32]| values.iter().fold(0u64, |m, v| m | 1 << v);
+        assert_eq!(parse_field("*/15", 0, 59).unwrap(), bits(&[0, 15, 30, 45]));
+        assert_eq!(parse_field("10-20/5", 0, 59).unwrap(), bits(&[10, 15, 20]));
+        assert_eq!(parse_field("5/20", 0, 59).unwrap(), bits(&[5, 25, 45]));
+        assert_eq!(parse_field("1,3-4", 0, 59).unwrap(), bits(&[1, 3, 4]));
+        assert_eq!(
+            next("*/15 * * * *", june(7) + chrono::Duration::minutes(1)),
+            Some(june(7) + chrono::Duration::minutes(15))
+        );
+    }
+
+    #[test]
+    fn treats_seven_as_sunday() {
+        assert_eq!(next("0 0 * * 7", june(7)), Some(june(8)));
+        assert_eq!(next("0 0 * * 0", june(7)), Some(june(8)));
+    }
+
+    #[test]
+    fn rejects_out_of_range_fields() {
+        for expr in [
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * 32 * *",
+            "* * * 0 *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "* * * *",
+        ] {
+            assert!(CronSchedule::parse(expr).is_err(), "{expr} was accepted");
+        }
+    }
+
+    #[test]
+    fn never_matching_expressions_have_no_next_run() {
+        assert_eq!(next("0 0 30 2 *", june(7)), None);
+        // Leap days are found however far away they are
+        assert_eq!(
+            next("0 0 29 2 *", june(7)),
+            Some(Local.with_ymd_and_hms(2028, 2, 29, 0, 0, 0).unwrap())
+        );
+    }
+}
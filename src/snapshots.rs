@@ -0,0 +1,202 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Named versions of the stories of story chats.
+//!
+//! Snapshots are kept in storage apart from the chat, so they do not slow down
+//! loading it. Users take and name them, and one is taken automatically on each
+//! day the story changes. Restoring a snapshot records the restored story as a
+//! new version, after keeping the story as it was if no snapshot has it, so no
+//! version is lost.
+
+use serde_json::{Value, json};
+
+use crate::{
+    app_settings::{Chat, StorySnapshot, Toolsets},
+    core::now_millis,
+    storage::{Storage, get_storage},
+    toolset::story::Story,
+};
+
+/// Automatic snapshots kept per story; older ones are dropped.
+pub const MAX_AUTO_SNAPSHOTS: usize = 30;
+
+/// Lists the snapshots of a story chat.
+///
+/// # Arguments
+/// * `chat_id` - ID of the story chat
+///
+/// # Returns
+/// The snapshots, newest first
+pub async fn list_snapshots(chat_id: u32) -> anyhow::Result<Vec<StorySnapshot>> {
+    let mut snapshots = get_storage().await?.load_snapshots(chat_id).await?;
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+/// Takes a snapshot of a story.
+///
+/// # Arguments
+/// * `chat_id` - ID of the story chat
+/// * `name` - Name of the snapshot
+/// * `story` - The story, as stored in the chat's value
+/// * `auto` - Whether the snapshot is taken automatically
+///
+/// # Returns
+/// The new snapshot
+pub async fn take_snapshot(
+    chat_id: u32,
+    name: &str,
+    story: &Value,
+    auto: bool,
+) -> anyhow::Result<StorySnapshot> {
+    let storage = get_storage().await?;
+    let mut snapshots = storage.load_snapshots(chat_id).await?;
+    let snapshot = push_snapshot(&mut snapshots, name, story, auto);
+    storage.save_snapshots(chat_id, &snapshots).await?;
+    Ok(snapshot)
+}
+
+/// Deletes a snapshot.
+///
+/// # Arguments
+/// * `chat_id` - ID of the story chat
+/// * `id` - ID of the snapshot
+pub async fn delete_snapshot(chat_id: u32, id: u64) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let mut snapshots = storage.load_snapshots(chat_id).await?;
+    snapshots.retain(|s| s.id != id);
+    storage.save_snapshots(chat_id, &snapshots).await
+}
+
+/// Takes the day's automatic snapshot of a saved story chat, unless one was
+/// taken today or the story is unchanged since the last snapshot.
+///
+/// # Arguments
+/// * `chat` - The chat, as saved
+pub async fn auto_snapshot(chat: &Chat) -> anyhow::Result<()> {
+    let Some(chat_id) = chat.id.filter(|_| chat.chat_type == Toolsets::Story) else {
+        return Ok(());
+    };
+    if chat.value == json!(Story::default()) {
+        return Ok(());
+    }
+    let storage = get_storage().await?;
+    let mut snapshots = storage.load_snapshots(chat_id).await?;
+    let today = local_day(now_millis());
+    let taken_today = snapshots.iter().any(|s| s.auto && local_day(s.id) == today);
+    let unchanged = snapshots.last().is_some_and(|s| s.story == chat.value);
+    if taken_today || unchanged {
+        return Ok(());
+    }
+    push_snapshot(&mut snapshots, &format!("Auto, {today}"), &chat.value, true);
+    storage.save_snapshots(chat_id, &snapshots).await
+}
+
+/// Records the restore of a snapshot as a new version. The caller swaps the
+/// chat's story for the snapshot's.
+///
+/// # Arguments
+/// * `chat` - The story chat, with the story it has before the restore
+/// * `snapshot` - The snapshot being restored
+pub async fn record_restore(chat: &Chat, snapshot: &StorySnapshot) -> anyhow::Result<()> {
+    let Some(chat_id) = chat.id else {
+        anyhow::bail!("Save the story before restoring a snapshot");
+    };
+    let storage = get_storage().await?;
+    let mut snapshots = storage.load_snapshots(chat_id).await?;
+    if !snapshots.iter().any(|s| s.story == chat.value) {
+        let name = format!("Before restoring \"{}\"", snapshot.name);
+        push_snapshot(&mut snapshots, &name, &chat.value, false);
+    }
+    let name = format!("Restored \"{}\"", snapshot.name);
+    push_snapshot(&mut snapshots, &name, &snapshot.story, false);
+    storage.save_snapshots(chat_id, &snapshots).await
+}
+
+/// Appends a snapshot, dropping the oldest automatic ones over the limit.
+fn push_snapshot(
+    snapshots: &mut Vec<StorySnapshot>,
+    name: &str,
+    story: &Value,
+    auto: bool,
+) -> StorySnapshot {
+    // IDs are creation times, kept unique when taken in the same millisecond
+    let id = snapshots.last().map_or(0, |s| s.id + 1).max(now_millis());
+    let snapshot = StorySnapshot {
+        id,
+        name: name.to_string(),
+        auto,
+        story: story.clone(),
+    };
+    snapshots.push(snapshot.clone());
+    let autos = snapshots.iter().filter(|s| s.auto).count();
+    if autos > MAX_AUTO_SNAPSHOTS {
+        let mut excess = autos - MAX_AUTO_SNAPSHOTS;
+        snapshots.retain(|s| {
+            let drop = s.auto && excess > 0;
+            if drop {
+                excess -= 1;
+            }
+            !drop
+        });
+    }
+    snapshot
+}
+
+/// Counts the words in a snapshot's chapters.
+pub fn snapshot_words(snapshot: &StorySnapshot) -> usize {
+    let story: Story = serde_json::from_value(snapshot.story.clone()).unwrap_or_default();
+    story
+        .chapters
+        .iter()
+        .map(|c| c.content.split_whitespace().count())
+        .sum()
+}
+
+/// Formats a timestamp as a local date, such as "2025-03-14".
+#[cfg(not(target_arch = "wasm32"))]
+pub fn local_day(millis: u64) -> String {
+    use chrono::{Local, TimeZone};
+
+    Local
+        .timestamp_millis_opt(millis as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Formats a timestamp as a local date, such as "2025-03-14".
+#[cfg(target_arch = "wasm32")]
+pub fn local_day(millis: u64) -> String {
+    let d = js_sys::Date::new(&(millis as f64).into());
+    format!(
+        "{:04}-{:02}-{:02}",
+        d.get_full_year(),
+        d.get_month() + 1,
+        d.get_date()
+    )
+}
+
+/// Formats a timestamp as a local date and time, such as "2025-03-14 09:26".
+#[cfg(not(target_arch = "wasm32"))]
+pub fn local_time(millis: u64) -> String {
+    use chrono::{Local, TimeZone};
+
+    Local
+        .timestamp_millis_opt(millis as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Formats a timestamp as a local date and time, such as "2025-03-14 09:26".
+#[cfg(target_arch = "wasm32")]
+pub fn local_time(millis: u64) -> String {
+    let d = js_sys::Date::new(&(millis as f64).into());
+    format!(
+        "{} {:02}:{:02}",
+        local_day(millis),
+        d.get_hours(),
+        d.get_minutes()
+    )
+}
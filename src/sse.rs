@@ -0,0 +1,181 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Incremental decoder for server-sent events (SSE), as used by streaming chat
+//! completions.
+//!
+//! Network chunks do not respect line or event boundaries: a single event can
+//! arrive in several chunks, a chunk can hold several events, and a chunk can
+//! even end in the middle of a multi-byte character. [`SseDecoder`] buffers raw
+//! bytes until a line is complete and an event until the blank line ending it,
+//! following the framing rules of the SSE specification: `\n`, `\r\n` and `\r`
+//! line endings, comment lines starting with `:`, and `data` fields spanning
+//! several lines.
+
+/// A complete server-sent event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    /// Event type from the `event` field, if one was given
+    pub event: Option<String>,
+    /// Contents of the event's `data` fields, joined with line breaks
+    pub data: String,
+}
+
+/// Splits a stream of bytes into server-sent events.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// Bytes of the line being received
+    buffer: Vec<u8>,
+    /// `data` fields of the event being received
+    data: Vec<String>,
+    /// `event` field of the event being received
+    event: Option<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk of the stream to the decoder.
+    ///
+    /// # Arguments
+    /// * `chunk` - Bytes as received, possibly ending mid-line
+    ///
+    /// # Returns
+    /// The events completed by this chunk, in order
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = vec![];
+        let mut start = 0;
+        let mut i = 0;
+        while i < self.buffer.len() {
+            let end = match self.buffer[i] {
+                b'\n' => i + 1,
+                // A trailing `\r` may be the first half of `\r\n`; wait for the next chunk
+                b'\r' if i + 1 == self.buffer.len() => break,
+                b'\r' if self.buffer[i + 1] == b'\n' => i + 2,
+                b'\r' => i + 1,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let line = String::from_utf8_lossy(&self.buffer[start..i]).into_owned();
+            events.extend(self.process_line(&line));
+            start = end;
+            i = end;
+        }
+        self.buffer.drain(..start);
+        events
+    }
+
+    /// Ends the stream, returning the last event if it was not terminated by a blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let buffer = std::mem::take(&mut self.buffer);
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end_matches('\r');
+        if !line.is_empty() {
+            self.process_line(line);
+        }
+        self.dispatch()
+    }
+
+    /// Applies one line to the event being received.
+    ///
+    /// # Returns
+    /// The event, if the line was the blank line ending it
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None; // Comment, e.g. a keep-alive
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            _ => {} // `id` and `retry` are not used for chat completions
+        }
+        None
+    }
+
+    /// Completes the event being received, if it has any data.
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(text: &str) -> SseEvent {
+        SseEvent {
+            event: None,
+            data: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn buffers_a_line_split_mid_chunk() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"da").is_empty());
+        assert!(decoder.push(b"ta: {\"a\":").is_empty());
+        assert!(decoder.push(b" 1}\n").is_empty());
+        assert_eq!(
+            decoder.push(b"\ndata: x\n\n"),
+            [data("{\"a\": 1}"), data("x")]
+        );
+    }
+
+    #[test]
+    fn buffers_crlf_split_across_chunks() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"data: a\r").is_empty());
+        assert!(decoder.push(b"\n\r").is_empty());
+        assert_eq!(decoder.push(b"\n"), [data("a")]);
+        // A lone `\r` ends a line too, once the next byte shows it is not `\r\n`
+        assert!(decoder.push(b"data: b\r").is_empty());
+        assert_eq!(decoder.push(b"\rdata: c\n\n"), [data("b"), data("c")]);
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let mut decoder = SseDecoder::new();
+        let events = decoder.push(b"event: delta\ndata: one\n: keep-alive\ndata:two\n\n");
+        assert_eq!(
+            events,
+            [SseEvent {
+                event: Some("delta".into()),
+                data: "one\ntwo".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn keeps_a_character_split_across_chunks() {
+        let mut decoder = SseDecoder::new();
+        let text = "data: é\n\n".as_bytes();
+        assert!(decoder.push(&text[..7]).is_empty());
+        assert_eq!(decoder.push(&text[7..]), [data("é")]);
+    }
+
+    #[test]
+    fn finishes_a_trailing_event_without_a_blank_line() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"data: last\r\ndata: line").is_empty());
+        assert_eq!(decoder.finish(), Some(data("last\nline")));
+        assert_eq!(decoder.finish(), None);
+    }
+}
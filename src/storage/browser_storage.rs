@@ -4,35 +4,173 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use idb::{Database, DatabaseEvent, Factory, KeyPath, ObjectStoreParams, TransactionMode};
 use js_sys::wasm_bindgen::JsValue;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::{Value, json};
 use serde_wasm_bindgen::Serializer;
+use std::collections::HashMap;
 
 use dioxus::logger::tracing::warn;
 
-use super::Storage;
+use super::encryption::{self, EncryptionInfo};
+use super::{ChatUsage, Storage, StorageUsage};
 use crate::AppSettings;
+use crate::app_settings::{AbTrial, Checkpoint, Profiles, ScheduledTask, StorySnapshot};
+use crate::core::now_millis;
+use crate::mcp::secrets::Secrets;
 use crate::storage::Chat;
 
+/// Key of the server states record in the settings store (settings use key 1)
+const SERVER_STATES_KEY: f64 = 2.;
+
+/// Key of the crash recovery checkpoint record in the settings store
+const CHECKPOINT_KEY: f64 = 3.;
+
+/// Key of the profile index record in the default profile's settings store
+const PROFILES_KEY: f64 = 4.;
+
+/// Key of the scheduled tasks record in the settings store
+const SCHEDULES_KEY: f64 = 5.;
+
+/// Key of the secrets record in the settings store
+const SECRETS_KEY: f64 = 6.;
+
+/// Key of the encryption record in the default profile's settings store
+const ENCRYPTION_KEY: f64 = 7.;
+
+/// Key of the prompt A/B trials record in the settings store
+const AB_TRIALS_KEY: f64 = 8.;
+
+/// Field of a sealed record holding the sealed value
+const SEALED_FIELD: &str = "sealed";
+
+/// Field of a chat record holding when it was saved, in milliseconds since
+/// the Unix epoch; it is not part of the chat
+const SAVED_AT_FIELD: &str = "saved_at";
+
+/// Key of the first story snapshots record in the settings store; each story
+/// chat's snapshots are at this key plus the chat's ID
+const SNAPSHOTS_KEY_OFFSET: f64 = 1_000_000.;
+
+/// Record holding the snapshots of a story chat in the settings store
+#[derive(Serialize, Deserialize)]
+struct SnapshotsDoc {
+    id: u32,
+    snapshots: Vec<StorySnapshot>,
+}
+
+/// Record holding the secrets in the settings store
+#[derive(Serialize, Deserialize)]
+struct SecretsDoc {
+    id: u32,
+    secrets: Secrets,
+}
+
+/// Record holding the scheduled tasks in the settings store
+#[derive(Serialize, Deserialize)]
+struct SchedulesDoc {
+    id: u32,
+    tasks: Vec<ScheduledTask>,
+}
+
+/// Record holding the prompt A/B trials in the settings store
+#[derive(Serialize, Deserialize)]
+struct AbTrialsDoc {
+    id: u32,
+    trials: Vec<AbTrial>,
+}
+
+/// Record holding the profile index in the settings store
+#[derive(Serialize, Deserialize)]
+struct ProfilesDoc {
+    id: u32,
+    profiles: Profiles,
+}
+
+/// Record holding the crash recovery checkpoint in the settings store
+#[derive(Serialize, Deserialize)]
+struct CheckpointDoc {
+    id: u32,
+    chat: Chat,
+    #[serde(default)]
+    server_states: HashMap<String, Value>,
+}
+
+/// Record holding the salt and passphrase check of encrypted storage in the
+/// settings store
+#[derive(Serialize, Deserialize)]
+struct EncryptionDoc {
+    id: u32,
+    info: EncryptionInfo,
+}
+
+/// Record holding persisted MCP server states in the settings store
+#[derive(Serialize, Deserialize)]
+struct ServerStatesDoc {
+    id: u32,
+    states: HashMap<String, Value>,
+}
+
+/// Turns a value into a record, sealed if storage is encrypted. A sealed
+/// record keeps the value's `id` outside the sealed text, as the record key.
+fn record_value<T: Serialize>(value: &T) -> anyhow::Result<Value> {
+    let plain = serde_json::to_value(value)?;
+    if !encryption::is_enabled() {
+        return Ok(plain);
+    }
+    let mut record = serde_json::Map::new();
+    if let Some(id) = plain.get("id").filter(|id| !id.is_null()) {
+        record.insert("id".to_string(), id.clone());
+    }
+    let sealed = encryption::seal(&plain.to_string())?;
+    record.insert(SEALED_FIELD.to_string(), Value::String(sealed));
+    Ok(Value::Object(record))
+}
+
+/// Turns a value into a record to put in a store.
+fn to_record<T: Serialize>(value: &T) -> anyhow::Result<JsValue> {
+    record_value(value)?
+        .serialize(&Serializer::json_compatible())
+        .map_err(|e| anyhow!("{e:?}"))
+}
+
+/// Reads a value from a record, sealed or not.
+fn from_record<T: DeserializeOwned>(record: JsValue) -> anyhow::Result<T> {
+    let record: Value = serde_wasm_bindgen::from_value(record).map_err(|e| anyhow!("{e:?}"))?;
+    from_record_value(record)
+}
+
+/// Reads a value from a record, sealed or not. The record key given to a
+/// value sealed before it had an ID is put back in it.
+fn from_record_value<T: DeserializeOwned>(record: Value) -> anyhow::Result<T> {
+    let Some(sealed) = record.get(SEALED_FIELD).and_then(Value::as_str) else {
+        return Ok(serde_json::from_value(record)?);
+    };
+    let mut value: Value = serde_json::from_str(&encryption::unseal(sealed)?)?;
+    if let (Some(id), Some(fields)) = (record.get("id"), value.as_object_mut())
+        && fields.get("id").is_none_or(Value::is_null)
+    {
+        fields.insert("id".to_string(), id.clone());
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
 #[derive(Debug)]
 pub struct IdbStorage {
     db: Database,
 }
 
 impl IdbStorage {
-    pub async fn new() -> anyhow::Result<Self> {
-        let db = Self::create_db().await?;
+    pub async fn new(name: &str) -> anyhow::Result<Self> {
+        let db = Self::create_db(name).await?;
         Ok(Self { db })
     }
 
-    pub async fn create_db() -> anyhow::Result<Database> {
+    pub async fn create_db(name: &str) -> anyhow::Result<Database> {
         // Get a factory instance from global scope
         let factory = Factory::new().map_err(|e| anyhow!("{e:?}"))?;
 
         // Create an open request for the database
-        let mut open_request = factory
-            .open("app_storage", Some(1))
-            .map_err(|e| anyhow!("{e:?}"))?;
+        let mut open_request = factory.open(name, Some(1)).map_err(|e| anyhow!("{e:?}"))?;
 
         // Add an upgrade handler for database
         open_request.on_upgrade_needed(|event| {
@@ -74,7 +212,7 @@ impl Storage for IdbStorage {
             .map_err(|e| anyhow!("{e:?}"))?;
 
         // warn!("Got store, will put");
-        let doc = settings.serialize(&Serializer::json_compatible()).unwrap();
+        let doc = to_record(settings)?;
         // warn!("serialized: {doc:?}");
         let put_res = store
             .put(&doc, None)
@@ -101,10 +239,7 @@ impl Storage for IdbStorage {
             .map_err(|e| anyhow!("{e:?}"))?;
 
         // Deserialize the stored data
-        let stored_settings: Option<anyhow::Result<AppSettings>> =
-            stored_settings.map(|stored_settings| {
-                serde_wasm_bindgen::from_value(stored_settings).map_err(|e| anyhow!("{e:?}"))
-            });
+        let stored_settings: Option<anyhow::Result<AppSettings>> = stored_settings.map(from_record);
         let stored_settings = stored_settings.transpose()?;
 
         // Wait for the transaction to complete (alternatively, you can also commit the transaction)
@@ -121,7 +256,16 @@ impl Storage for IdbStorage {
             .object_store("sessions")
             .map_err(|e| anyhow!("{e:?}"))?;
 
-        let doc = chat.serialize(&Serializer::json_compatible()).unwrap();
+        let mut chat = chat.clone();
+        chat.touch(now_millis());
+        // The record also notes when it was saved, for the storage insights
+        let mut record = record_value(&chat)?;
+        if let Some(fields) = record.as_object_mut() {
+            fields.insert(SAVED_AT_FIELD.to_string(), json!(now_millis()));
+        }
+        let doc = record
+            .serialize(&Serializer::json_compatible())
+            .map_err(|e| anyhow!("{e:?}"))?;
         // warn!("serialized: {doc:?}");
         let put_res = if chat.id.is_some() {
             store
@@ -157,8 +301,7 @@ impl Storage for IdbStorage {
         let all = store.get_all(None, None).unwrap().await.unwrap();
         let mut all_chats: Vec<Chat> = vec![];
         for v in all {
-            let c = serde_wasm_bindgen::from_value(v).map_err(|e| anyhow!("{e:?}"))?;
-            all_chats.push(c);
+            all_chats.push(from_record(v)?);
         }
 
         transaction.await.map_err(|e| anyhow!("{e:?}"))?;
@@ -181,9 +324,7 @@ impl Storage for IdbStorage {
             .map_err(|e| anyhow!("{e:?}"))?;
 
         // Deserialize the stored data
-        let stored_chat: Option<anyhow::Result<Chat>> = stored_chat.map(|stored_chat| {
-            serde_wasm_bindgen::from_value(stored_chat).map_err(|e| anyhow!("{e:?}"))
-        });
+        let stored_chat: Option<anyhow::Result<Chat>> = stored_chat.map(from_record);
         let stored_chat = stored_chat.transpose()?;
 
         transaction.await.map_err(|e| anyhow!("{e:?}"))?;
@@ -205,6 +346,502 @@ impl Storage for IdbStorage {
             .await
             .map_err(|e| anyhow!("{e:?}"))?;
 
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+
+        // Snapshots of a story go with it
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        store
+            .delete(JsValue::from_f64(SNAPSHOTS_KEY_OFFSET + f64::from(id)))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn save_server_states(&self, states: &HashMap<String, Value>) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = ServerStatesDoc {
+            id: SERVER_STATES_KEY as u32,
+            states: states.clone(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_server_states(&self) -> anyhow::Result<HashMap<String, Value>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(SERVER_STATES_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let states = match stored {
+            Some(v) => from_record::<ServerStatesDoc>(v)?.states,
+            None => HashMap::new(),
+        };
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(states)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = CheckpointDoc {
+            id: CHECKPOINT_KEY as u32,
+            chat: checkpoint.chat.clone(),
+            server_states: checkpoint.server_states.clone(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self) -> anyhow::Result<Option<Checkpoint>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(CHECKPOINT_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let checkpoint = match stored {
+            Some(v) => match from_record::<CheckpointDoc>(v) {
+                Ok(doc) => Some(Checkpoint {
+                    chat: doc.chat,
+                    server_states: doc.server_states,
+                }),
+                Err(e) => {
+                    warn!("Failed to parse checkpoint: {e:?}");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(checkpoint)
+    }
+
+    async fn save_profiles(&self, profiles: &Profiles) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = ProfilesDoc {
+            id: PROFILES_KEY as u32,
+            profiles: profiles.clone(),
+        };
+        let doc = doc
+            .serialize(&Serializer::json_compatible())
+            .map_err(|e| anyhow!("{e:?}"))?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_profiles(&self) -> anyhow::Result<Option<Profiles>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(PROFILES_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let profiles = stored
+            .map(|v| {
+                serde_wasm_bindgen::from_value::<ProfilesDoc>(v)
+                    .map(|doc| doc.profiles)
+                    .map_err(|e| anyhow!("{e:?}"))
+            })
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(profiles)
+    }
+
+    async fn save_schedules(&self, tasks: &[ScheduledTask]) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = SchedulesDoc {
+            id: SCHEDULES_KEY as u32,
+            tasks: tasks.to_vec(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_schedules(&self) -> anyhow::Result<Vec<ScheduledTask>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(SCHEDULES_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let tasks = stored
+            .map(|v| from_record::<SchedulesDoc>(v).map(|doc| doc.tasks))
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(tasks.unwrap_or_default())
+    }
+
+    async fn save_ab_trials(&self, trials: &[AbTrial]) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = AbTrialsDoc {
+            id: AB_TRIALS_KEY as u32,
+            trials: trials.to_vec(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_ab_trials(&self) -> anyhow::Result<Vec<AbTrial>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(AB_TRIALS_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let trials = stored
+            .map(|v| from_record::<AbTrialsDoc>(v).map(|doc| doc.trials))
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(trials.unwrap_or_default())
+    }
+
+    async fn save_snapshots(
+        &self,
+        chat_id: u32,
+        snapshots: &[StorySnapshot],
+    ) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = SnapshotsDoc {
+            id: (SNAPSHOTS_KEY_OFFSET + f64::from(chat_id)) as u32,
+            snapshots: snapshots.to_vec(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_snapshots(&self, chat_id: u32) -> anyhow::Result<Vec<StorySnapshot>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(SNAPSHOTS_KEY_OFFSET + f64::from(chat_id)))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let snapshots = stored
+            .map(|v| from_record::<SnapshotsDoc>(v).map(|doc| doc.snapshots))
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(snapshots.unwrap_or_default())
+    }
+
+    async fn save_secrets(&self, secrets: &Secrets) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        let doc = SecretsDoc {
+            id: SECRETS_KEY as u32,
+            secrets: secrets.clone(),
+        };
+        let doc = to_record(&doc)?;
+        store
+            .put(&doc, None)
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_secrets(&self) -> anyhow::Result<Secrets> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(SECRETS_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let secrets = stored
+            .map(|v| from_record::<SecretsDoc>(v).map(|doc| doc.secrets))
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(secrets.unwrap_or_default())
+    }
+
+    async fn storage_usage(&self) -> anyhow::Result<StorageUsage> {
+        let mut usage = StorageUsage::default();
+        for store_name in ["settings", "sessions"] {
+            let transaction = self
+                .db
+                .transaction(&[store_name], TransactionMode::ReadOnly)
+                .map_err(|e| anyhow!("{e:?}"))?;
+            let store = transaction
+                .object_store(store_name)
+                .map_err(|e| anyhow!("{e:?}"))?;
+            let all = store
+                .get_all(None, None)
+                .map_err(|e| anyhow!("{e:?}"))?
+                .await
+                .map_err(|e| anyhow!("{e:?}"))?;
+            transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+            for v in all {
+                let record: Value =
+                    serde_wasm_bindgen::from_value(v).map_err(|e| anyhow!("{e:?}"))?;
+                // IndexedDB does not report record sizes; their JSON is close enough
+                let bytes = serde_json::to_string(&record)?.len() as u64;
+                usage.total_bytes += bytes;
+                if store_name != "sessions" {
+                    continue;
+                }
+                let saved_at = record
+                    .get(SAVED_AT_FIELD)
+                    .and_then(Value::as_f64)
+                    .map(|t| t as u64);
+                match from_record_value::<Chat>(record) {
+                    Ok(chat) => usage.chats.extend(ChatUsage::of(&chat, bytes, saved_at)),
+                    Err(e) => warn!("Failed to parse chat: {e:?}"),
+                }
+            }
+        }
+        usage.chats.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+        Ok(usage)
+    }
+
+    async fn save_encryption(&self, info: Option<&EncryptionInfo>) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        match info {
+            Some(info) => {
+                let doc = EncryptionDoc {
+                    id: ENCRYPTION_KEY as u32,
+                    info: info.clone(),
+                };
+                let doc = doc
+                    .serialize(&Serializer::json_compatible())
+                    .map_err(|e| anyhow!("{e:?}"))?;
+                store
+                    .put(&doc, None)
+                    .map_err(|e| anyhow!("{e:?}"))?
+                    .await
+                    .map_err(|e| anyhow!("{e:?}"))?;
+            }
+            None => {
+                store
+                    .delete(JsValue::from_f64(ENCRYPTION_KEY))
+                    .map_err(|e| anyhow!("{e:?}"))?
+                    .await
+                    .map_err(|e| anyhow!("{e:?}"))?;
+            }
+        }
+        transaction
+            .commit()
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        Ok(())
+    }
+
+    async fn load_encryption(&self) -> anyhow::Result<Option<EncryptionInfo>> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadOnly)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let stored: Option<JsValue> = store
+            .get(JsValue::from_f64(ENCRYPTION_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let info = stored
+            .map(|v| {
+                serde_wasm_bindgen::from_value::<EncryptionDoc>(v)
+                    .map(|doc| doc.info)
+                    .map_err(|e| anyhow!("{e:?}"))
+            })
+            .transpose()?;
+
+        transaction.await.map_err(|e| anyhow!("{e:?}"))?;
+        Ok(info)
+    }
+
+    async fn clear_checkpoint(&self) -> anyhow::Result<()> {
+        let transaction = self
+            .db
+            .transaction(&["settings"], TransactionMode::ReadWrite)
+            .map_err(|e| anyhow!("{e:?}"))?;
+        let store = transaction
+            .object_store("settings")
+            .map_err(|e| anyhow!("{e:?}"))?;
+
+        store
+            .delete(JsValue::from_f64(CHECKPOINT_KEY))
+            .map_err(|e| anyhow!("{e:?}"))?
+            .await
+            .map_err(|e| anyhow!("{e:?}"))?;
+
         transaction.await.map_err(|e| anyhow!("{e:?}"))?;
         Ok(())
     }
@@ -0,0 +1,340 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Encryption of stored chats and settings with a passphrase.
+//!
+//! Data is sealed with XChaCha20-Poly1305 under a key derived from the
+//! passphrase with Argon2id. A sealed value is text, [`SEALED_PREFIX`]
+//! followed by the base64 of the nonce and ciphertext, so each backend keeps
+//! it where it kept the JSON before. Reading also accepts plain JSON, so data
+//! saved before encryption was turned on still loads.
+//!
+//! The salt and a value sealed with the key, to check passphrases against,
+//! are kept unencrypted in the default profile's storage. Once unlocked, the
+//! key stays in memory until the app exits or encryption is turned off.
+
+use std::sync::RwLock;
+
+use anyhow::{anyhow, bail};
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chacha20poly1305::{
+    Key, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, AeadCore, OsRng, rand_core::RngCore},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Start of every sealed value.
+pub const SEALED_PREFIX: &str = "mcmcpcp-sealed:v1:";
+/// Text sealed in [`EncryptionInfo::check`].
+const CHECK_TEXT: &str = "mcmcpcp";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Whether data is sealed, and the key to seal it with
+static STATE: RwLock<KeyState> = RwLock::new(KeyState {
+    locked: false,
+    key: None,
+    retired: None,
+});
+
+#[derive(Clone)]
+struct KeyState {
+    /// Encryption is on but the passphrase was not entered yet
+    locked: bool,
+    /// Key new data is sealed with
+    key: Option<Key>,
+    /// Key of data sealed before encryption was turned off, still readable
+    retired: Option<Key>,
+}
+
+/// What is needed to check a passphrase and derive the key from it; stored
+/// unencrypted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptionInfo {
+    /// Salt for deriving the key, in base64
+    pub salt: String,
+    /// [`CHECK_TEXT`] sealed with the key
+    pub check: String,
+}
+
+/// Whether stored data is sealed.
+pub fn is_enabled() -> bool {
+    state().is_some_and(|s| s.locked || s.key.is_some())
+}
+
+/// Whether the passphrase is still needed to read stored data.
+pub fn is_locked() -> bool {
+    state().is_some_and(|s| s.locked)
+}
+
+/// Records that stored data is sealed, so nothing is saved unsealed before
+/// the passphrase is entered.
+pub fn set_locked() {
+    if let Ok(mut s) = STATE.write()
+        && s.key.is_none()
+    {
+        s.locked = true;
+    }
+}
+
+/// Checks a passphrase and, if it is right, keeps its key to read and write
+/// stored data.
+///
+/// # Arguments
+/// * `info` - The stored salt and check value
+/// * `passphrase` - The passphrase entered
+pub fn unlock(info: &EncryptionInfo, passphrase: &str) -> anyhow::Result<()> {
+    let salt = STANDARD.decode(&info.salt)?;
+    let key = derive_key(passphrase, &salt)?;
+    match open_with(&key, &info.check) {
+        Ok(text) if text == CHECK_TEXT => {}
+        _ => bail!("Wrong passphrase"),
+    }
+    if let Ok(mut s) = STATE.write() {
+        *s = KeyState {
+            locked: false,
+            key: Some(key),
+            retired: None,
+        };
+    }
+    Ok(())
+}
+
+/// Starts sealing data with a key derived from a new passphrase.
+///
+/// # Returns
+/// The salt and check value to store
+pub fn start_encryption(passphrase: &str) -> anyhow::Result<EncryptionInfo> {
+    if passphrase.is_empty() {
+        bail!("Enter a passphrase");
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let info = EncryptionInfo {
+        salt: STANDARD.encode(salt),
+        check: seal_with(&key, CHECK_TEXT)?,
+    };
+    if let Ok(mut s) = STATE.write() {
+        *s = KeyState {
+            locked: false,
+            key: Some(key),
+            retired: None,
+        };
+    }
+    Ok(info)
+}
+
+/// Stops sealing data. Data sealed so far can still be read until
+/// [`forget_retired_key`].
+pub fn stop_encryption() -> anyhow::Result<()> {
+    let mut s = STATE
+        .write()
+        .map_err(|_| anyhow!("Encryption state unavailable"))?;
+    if s.locked {
+        bail!("Unlock the storage first");
+    }
+    s.retired = s.key.take().or(s.retired.take());
+    Ok(())
+}
+
+/// Forgets the key of data sealed before encryption was turned off, once all
+/// of it was saved unsealed.
+pub fn forget_retired_key() {
+    if let Ok(mut s) = STATE.write() {
+        s.retired = None;
+    }
+}
+
+/// Seals text if encryption is on, or returns it as it is.
+pub fn seal(plain: &str) -> anyhow::Result<String> {
+    let s = state().ok_or_else(|| anyhow!("Encryption state unavailable"))?;
+    if s.locked {
+        bail!("The storage is locked");
+    }
+    match &s.key {
+        Some(key) => seal_with(key, plain),
+        None => Ok(plain.to_string()),
+    }
+}
+
+/// Opens sealed text, or returns unsealed text as it is.
+pub fn unseal(data: &str) -> anyhow::Result<String> {
+    if !is_sealed(data) {
+        return Ok(data.to_string());
+    }
+    let s = state().ok_or_else(|| anyhow!("Encryption state unavailable"))?;
+    if s.locked {
+        bail!("The storage is locked");
+    }
+    for key in s.key.iter().chain(s.retired.iter()) {
+        if let Ok(text) = open_with(key, data) {
+            return Ok(text);
+        }
+    }
+    bail!("Could not decrypt stored data")
+}
+
+/// Whether text was sealed.
+pub fn is_sealed(data: &str) -> bool {
+    data.starts_with(SEALED_PREFIX)
+}
+
+/// Seals each line of a file again as encryption now says: with the current
+/// key, or not at all once encryption is off. For files written a line at a
+/// time, such as the request log; a missing file is left alone.
+///
+/// # Arguments
+/// * `path` - The file
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reseal_lines(path: &std::path::Path) -> anyhow::Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut resealed = String::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        resealed.push_str(&seal(&unseal(line)?)?);
+        resealed.push('\n');
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, resealed)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Serializes a value to JSON, sealed if encryption is on.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<String> {
+    seal(&serde_json::to_string_pretty(value)?)
+}
+
+/// Deserializes a value from JSON, sealed or not.
+pub fn from_json<T: DeserializeOwned>(data: &str) -> anyhow::Result<T> {
+    Ok(serde_json::from_str(&unseal(data)?)?)
+}
+
+fn state() -> Option<KeyState> {
+    STATE.read().ok().map(|s| s.clone())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> anyhow::Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Could not derive the key: {e}"))?;
+    Ok(key)
+}
+
+fn seal_with(key: &Key, plain: &str) -> anyhow::Result<String> {
+    let cipher = XChaCha20Poly1305::new(key);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plain.as_bytes())
+        .map_err(|_| anyhow!("Could not encrypt"))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(format!("{SEALED_PREFIX}{}", STANDARD.encode(sealed)))
+}
+
+fn open_with(key: &Key, data: &str) -> anyhow::Result<String> {
+    let Some(encoded) = data.strip_prefix(SEALED_PREFIX) else {
+        bail!("Not sealed");
+    };
+    let sealed = STANDARD.decode(encoded.trim())?;
+    if sealed.len() < NONCE_LEN {
+        bail!("Sealed data is truncated");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plain = XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Could not decrypt"))?;
+    Ok(String::from_utf8(plain)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Held by each test, as the key is global.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    fn reset() {
+        *STATE.write().unwrap() = KeyState {
+            locked: false,
+            key: None,
+            retired: None,
+        };
+    }
+
+    #[test]
+    fn round_trips_sealed_text() {
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset();
+        assert_eq!(seal("plain").unwrap(), "plain");
+
+        start_encryption("passphrase").unwrap();
+        let sealed = seal("secret text").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("secret"));
+        assert_eq!(unseal(&sealed).unwrap(), "secret text");
+        // Plain data saved before encryption still loads
+        assert_eq!(unseal("{}").unwrap(), "{}");
+    }
+
+    #[test]
+    fn refuses_a_wrong_passphrase() {
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset();
+        let info = start_encryption("right").unwrap();
+        let sealed = seal("secret").unwrap();
+        reset();
+        set_locked();
+        assert!(unseal(&sealed).is_err());
+
+        assert!(unlock(&info, "wrong").is_err());
+        assert!(is_locked());
+        unlock(&info, "right").unwrap();
+        assert_eq!(unseal(&sealed).unwrap(), "secret");
+    }
+
+    #[test]
+    fn rejects_tampered_data() {
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset();
+        start_encryption("passphrase").unwrap();
+        let sealed = seal("secret").unwrap();
+        let mut bytes = STANDARD
+            .decode(sealed.strip_prefix(SEALED_PREFIX).unwrap())
+            .unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let tampered = format!("{SEALED_PREFIX}{}", STANDARD.encode(&bytes));
+        assert!(unseal(&tampered).is_err());
+        let truncated = format!("{SEALED_PREFIX}{}", STANDARD.encode(&bytes[..10]));
+        assert!(unseal(&truncated).is_err());
+        assert!(unseal(&format!("{SEALED_PREFIX}not base64!")).is_err());
+    }
+
+    #[test]
+    fn stops_sealing_and_forgets_the_key() {
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        reset();
+        start_encryption("passphrase").unwrap();
+        let old = seal("old").unwrap();
+
+        stop_encryption().unwrap();
+        assert!(!is_enabled());
+        let new = seal("new").unwrap();
+        assert_eq!(new, "new");
+        // Still readable, to be saved unsealed
+        assert_eq!(unseal(&old).unwrap(), "old");
+
+        forget_retired_key();
+        assert!(unseal(&old).is_err());
+        assert_eq!(unseal(&new).unwrap(), "new");
+    }
+}
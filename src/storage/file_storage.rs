@@ -2,11 +2,16 @@
 
 use anyhow::{Result, bail};
 use dioxus::logger::tracing::warn;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tokio::fs;
 
 use crate::AppSettings;
-use crate::app_settings::Chat;
+use crate::app_settings::{AbTrial, Chat, Checkpoint, Profiles, ScheduledTask, StorySnapshot};
+use crate::mcp::secrets::Secrets;
+use crate::storage::encryption::{EncryptionInfo, from_json, seal, to_json};
+use crate::storage::{ChatUsage, StorageUsage};
 
 #[derive(Debug)]
 pub struct FileStorage {
@@ -22,10 +27,42 @@ impl FileStorage {
         self.base.join("settings.json")
     }
 
+    fn server_states_path(&self) -> PathBuf {
+        self.base.join("server_states.json")
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        self.base.join("checkpoint.json")
+    }
+
+    fn profiles_path(&self) -> PathBuf {
+        self.base.join("profiles.json")
+    }
+
+    fn schedules_path(&self) -> PathBuf {
+        self.base.join("schedules.json")
+    }
+
+    fn ab_trials_path(&self) -> PathBuf {
+        self.base.join("ab_trials.json")
+    }
+
+    fn secrets_path(&self) -> PathBuf {
+        self.base.join("secrets.json")
+    }
+
+    fn encryption_path(&self) -> PathBuf {
+        self.base.join("encryption.json")
+    }
+
     fn chats_path(&self) -> PathBuf {
         self.base.join("chats")
     }
 
+    fn snapshots_path(&self, chat_id: u32) -> PathBuf {
+        self.base.join("snapshots").join(format!("{chat_id}.json"))
+    }
+
     async fn ensure_dir(&self) -> Result<()> {
         let path = self.settings_path();
         let Some(parent) = path.parent() else {
@@ -37,6 +74,28 @@ impl FileStorage {
         Ok(())
     }
 
+    /// Total size of the files under the base directory, leaving out other
+    /// profiles stored inside it.
+    async fn dir_size(&self) -> Result<u64> {
+        let profiles = self.base.join("profiles");
+        let mut total = 0;
+        let mut dirs = vec![self.base.clone()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(mut entries) = fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let kind = entry.file_type().await?;
+                if kind.is_dir() && entry.path() != profiles {
+                    dirs.push(entry.path());
+                } else if kind.is_file() {
+                    total += entry.metadata().await?.len();
+                }
+            }
+        }
+        Ok(total)
+    }
+
     async fn get_next_chat_id(&self) -> Result<u32> {
         self.ensure_dir().await?;
         let chats_dir = self.chats_path();
@@ -73,7 +132,7 @@ impl FileStorage {
 impl super::Storage for FileStorage {
     async fn save_settings(&self, settings: &AppSettings) -> Result<()> {
         self.ensure_dir().await?;
-        let json = serde_json::to_string_pretty(settings)?;
+        let json = to_json(settings)?;
         let path = self.settings_path();
         fs::write(&path, json).await?;
         Ok(())
@@ -86,7 +145,7 @@ impl super::Storage for FileStorage {
             return Ok(None);
         }
         match fs::read_to_string(&path).await {
-            Ok(data) => Ok(Some(serde_json::from_str(&data)?)),
+            Ok(data) => Ok(Some(from_json(&data)?)),
             Err(_) => Ok(None),
         }
     }
@@ -101,7 +160,8 @@ impl super::Storage for FileStorage {
         let path = self.chats_path().join(file_name);
         let mut c = chat.clone();
         c.id = Some(file_idx);
-        let json = serde_json::to_string_pretty(&c)?;
+        c.touch(crate::core::now_millis());
+        let json = to_json(&c)?;
         fs::write(&path, json).await?;
         Ok(file_idx)
     }
@@ -120,7 +180,7 @@ impl super::Storage for FileStorage {
                 && ext == "json"
             {
                 match tokio::fs::read_to_string(&path).await {
-                    Ok(content) => match serde_json::from_str::<Chat>(&content) {
+                    Ok(content) => match from_json::<Chat>(&content) {
                         Ok(chat) => chats.push(chat),
                         Err(e) => warn!("Failed to parse chat from {path:?}: {e}"),
                     },
@@ -138,7 +198,7 @@ impl super::Storage for FileStorage {
             return Ok(None);
         }
         match tokio::fs::read_to_string(&path).await {
-            Ok(content) => match serde_json::from_str::<Chat>(&content) {
+            Ok(content) => match from_json::<Chat>(&content) {
                 Ok(chat) => Ok(Some(chat)),
                 Err(e) => {
                     warn!("Failed to parse chat from {path:?}: {e}");
@@ -158,6 +218,217 @@ impl super::Storage for FileStorage {
         if path.exists() {
             tokio::fs::remove_file(&path).await?;
         }
+        let snapshots = self.snapshots_path(id);
+        if snapshots.exists() {
+            tokio::fs::remove_file(&snapshots).await?;
+        }
         Ok(())
     }
+
+    async fn save_server_states(&self, states: &HashMap<String, Value>) -> Result<()> {
+        self.ensure_dir().await?;
+        let json = to_json(states)?;
+        fs::write(self.server_states_path(), json).await?;
+        Ok(())
+    }
+
+    async fn load_server_states(&self) -> Result<HashMap<String, Value>> {
+        let path = self.server_states_path();
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let data = fs::read_to_string(&path).await?;
+        from_json(&data)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.ensure_dir().await?;
+        // Write to a temporary file first so a crash mid-write keeps the old checkpoint
+        let path = self.checkpoint_path();
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, seal(&serde_json::to_string(checkpoint)?)?).await?;
+        fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let path = self.checkpoint_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path).await?;
+        // Checkpoints used to hold only the chat
+        let checkpoint = from_json(&data).or_else(|e| {
+            from_json(&data)
+                .map(|chat| Checkpoint {
+                    chat,
+                    server_states: HashMap::new(),
+                })
+                .map_err(|_| e)
+        });
+        match checkpoint {
+            Ok(checkpoint) => Ok(Some(checkpoint)),
+            Err(e) => {
+                warn!("Failed to parse checkpoint from {path:?}: {e}");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn clear_checkpoint(&self) -> Result<()> {
+        let path = self.checkpoint_path();
+        if path.exists() {
+            fs::remove_file(&path).await?;
+        }
+        Ok(())
+    }
+
+    async fn save_profiles(&self, profiles: &Profiles) -> Result<()> {
+        self.ensure_dir().await?;
+        let json = serde_json::to_string_pretty(profiles)?;
+        fs::write(self.profiles_path(), json).await?;
+        Ok(())
+    }
+
+    async fn load_profiles(&self) -> Result<Option<Profiles>> {
+        let path = self.profiles_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path).await?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    async fn save_schedules(&self, tasks: &[ScheduledTask]) -> Result<()> {
+        self.ensure_dir().await?;
+        let json = to_json(tasks)?;
+        fs::write(self.schedules_path(), json).await?;
+        Ok(())
+    }
+
+    async fn load_schedules(&self) -> Result<Vec<ScheduledTask>> {
+        let path = self.schedules_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let data = fs::read_to_string(&path).await?;
+        from_json(&data)
+    }
+
+    async fn save_ab_trials(&self, trials: &[AbTrial]) -> Result<()> {
+        self.ensure_dir().await?;
+        let json = to_json(trials)?;
+        fs::write(self.ab_trials_path(), json).await?;
+        Ok(())
+    }
+
+    async fn load_ab_trials(&self) -> Result<Vec<AbTrial>> {
+        let path = self.ab_trials_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let data = fs::read_to_string(&path).await?;
+        from_json(&data)
+    }
+
+    async fn save_snapshots(&self, chat_id: u32, snapshots: &[StorySnapshot]) -> Result<()> {
+        let path = self.snapshots_path(chat_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(&path, seal(&serde_json::to_string(snapshots)?)?).await?;
+        Ok(())
+    }
+
+    async fn load_snapshots(&self, chat_id: u32) -> Result<Vec<StorySnapshot>> {
+        let path = self.snapshots_path(chat_id);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let data = fs::read_to_string(&path).await?;
+        from_json(&data)
+    }
+
+    async fn save_secrets(&self, secrets: &Secrets) -> Result<()> {
+        self.ensure_dir().await?;
+        let path = self.secrets_path();
+        fs::write(&path, to_json(secrets)?).await?;
+        // Readable by the user only
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).await?;
+        }
+        Ok(())
+    }
+
+    async fn load_secrets(&self) -> Result<Secrets> {
+        let path = self.secrets_path();
+        if !path.exists() {
+            return Ok(Secrets::new());
+        }
+        let data = fs::read_to_string(&path).await?;
+        from_json(&data)
+    }
+
+    async fn save_encryption(&self, info: Option<&EncryptionInfo>) -> Result<()> {
+        let path = self.encryption_path();
+        match info {
+            Some(info) => {
+                self.ensure_dir().await?;
+                fs::write(&path, serde_json::to_string_pretty(info)?).await?;
+            }
+            None if path.exists() => fs::remove_file(&path).await?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    async fn load_encryption(&self) -> Result<Option<EncryptionInfo>> {
+        let path = self.encryption_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path).await?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    async fn storage_usage(&self) -> Result<StorageUsage> {
+        let mut usage = StorageUsage {
+            total_bytes: self.dir_size().await?,
+            chats: vec![],
+        };
+        let chats_dir = self.chats_path();
+        if !chats_dir.exists() {
+            return Ok(usage);
+        }
+        let mut entries = fs::read_dir(&chats_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let meta = entry.metadata().await?;
+            let saved_at = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+            let chat = match fs::read_to_string(&path).await {
+                Ok(content) => from_json::<Chat>(&content),
+                Err(e) => {
+                    warn!("Failed to read chat file {path:?}: {e}");
+                    continue;
+                }
+            };
+            match chat {
+                Ok(chat) => usage
+                    .chats
+                    .extend(ChatUsage::of(&chat, meta.len(), saved_at)),
+                Err(e) => warn!("Failed to parse chat from {path:?}: {e}"),
+            }
+        }
+        usage.chats.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+        Ok(usage)
+    }
 }
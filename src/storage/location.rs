@@ -0,0 +1,255 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Where settings and chats are stored on native platforms.
+//!
+//! Data lives in the config directory unless the user picked another
+//! directory, which is recorded in `data_location.json` in the config
+//! directory. An executable with a file named `portable` next to it is in
+//! portable mode instead: its data lives in a `data` directory beside it, and
+//! nothing is written elsewhere.
+//!
+//! The config file and the location itself always stay in the config
+//! directory, so moving the data never loses track of it.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use super::config_dir;
+
+/// File next to the executable that turns on portable mode.
+pub const PORTABLE_MARKER: &str = "portable";
+/// File in the config directory naming the data directory.
+const LOCATION_FILE: &str = "data_location.json";
+/// Files that belong to the config directory and are never moved.
+const CONFIG_FILES: [&str; 4] = [LOCATION_FILE, "config.toml", "config.json", "requests.log"];
+
+/// The data directory in use, once looked up
+static DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// How the data directory was chosen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocationKind {
+    /// The config directory
+    Default,
+    /// A directory the user picked
+    Custom,
+    /// Next to the executable
+    Portable,
+}
+
+/// What to do with the data when changing its location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relocation {
+    /// Copy the data, leaving the old copy in place
+    Copy,
+    /// Copy the data, then delete the old copy
+    Move,
+    /// Use the data already in the new location
+    UseExisting,
+}
+
+/// Contents of `data_location.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct LocationFile {
+    data_dir: PathBuf,
+}
+
+/// Returns the directory of the portable data, if the app runs in portable mode.
+pub fn portable_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_MARKER)
+        .is_file()
+        .then(|| dir.join("data"))
+}
+
+/// Returns the directory holding settings and chats, and how it was chosen.
+pub fn data_location() -> (PathBuf, LocationKind) {
+    if let Some(dir) = portable_dir() {
+        return (dir, LocationKind::Portable);
+    }
+    let path = config_dir().join(LOCATION_FILE);
+    let custom = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<LocationFile>(&data).ok());
+    match custom {
+        Some(l) => (l.data_dir, LocationKind::Custom),
+        None => (config_dir(), LocationKind::Default),
+    }
+}
+
+/// Returns the directory holding settings and chats.
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = DATA_DIR.read()
+        && let Some(dir) = dir.as_ref()
+    {
+        return dir.clone();
+    }
+    let (dir, _) = data_location();
+    if let Ok(mut d) = DATA_DIR.write() {
+        *d = Some(dir.clone());
+    }
+    dir
+}
+
+/// Moves the data to another directory and stores it there from now on.
+///
+/// Files are copied and checked before the new location is recorded, so a
+/// failed copy leaves the data where it was. The old copy is only deleted
+/// after that, when moving.
+///
+/// # Arguments
+/// * `to` - The new data directory; the config directory resets to the default
+/// * `how` - Whether to copy, move, or use data already in `to`
+///
+/// # Returns
+/// The number of files copied
+pub async fn relocate(to: &Path, how: Relocation) -> anyhow::Result<usize> {
+    if portable_dir().is_some() {
+        bail!(
+            "The app runs in portable mode; remove the '{PORTABLE_MARKER}' file next to it to choose a location"
+        );
+    }
+    if !to.is_absolute() {
+        bail!("Enter the full path of the new location");
+    }
+    let from = data_dir();
+    if same_dir(&from, to) {
+        bail!("The data is already in {}", to.display());
+    }
+    if to.starts_with(&from) || from.starts_with(to) {
+        bail!("The new location cannot be inside the current one, or contain it");
+    }
+    let copied = match how {
+        Relocation::UseExisting => {
+            if !has_data(to) {
+                bail!("There is no data in {} to use", to.display());
+            }
+            0
+        }
+        Relocation::Copy | Relocation::Move => {
+            if has_data(to) {
+                bail!(
+                    "{} already holds data; use it instead, or pick an empty directory",
+                    to.display()
+                );
+            }
+            let files = data_files(&from).await?;
+            for file in &files {
+                copy_checked(&from.join(file), &to.join(file)).await?;
+            }
+            files.len()
+        }
+    };
+    record_location(to).await?;
+    if how == Relocation::Move {
+        for file in data_files(&from).await? {
+            fs::remove_file(from.join(&file)).await?;
+        }
+        remove_empty_dirs(&from).await;
+    }
+    Ok(copied)
+}
+
+/// Records the data directory, or forgets it if it is the config directory.
+async fn record_location(dir: &Path) -> anyhow::Result<()> {
+    let config = config_dir();
+    let path = config.join(LOCATION_FILE);
+    if same_dir(dir, &config) {
+        if path.exists() {
+            fs::remove_file(&path).await?;
+        }
+    } else {
+        fs::create_dir_all(&config).await?;
+        let location = LocationFile {
+            data_dir: dir.to_path_buf(),
+        };
+        fs::write(&path, serde_json::to_string_pretty(&location)?).await?;
+    }
+    if let Ok(mut d) = DATA_DIR.write() {
+        *d = Some(dir.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Whether a directory holds settings or chats.
+fn has_data(dir: &Path) -> bool {
+    dir.join("settings.json").exists() || dir.join("chats").is_dir()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Lists the data files under a directory, relative to it, leaving out
+/// those that stay in the config directory.
+async fn data_files(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    if !root.exists() {
+        return Ok(files);
+    }
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(root.join(&dir))
+            .await
+            .with_context(|| format!("reading {}", root.join(&dir).display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let relative = dir.join(entry.file_name());
+            let kind = entry.file_type().await?;
+            if kind.is_dir() {
+                dirs.push(relative);
+            } else if kind.is_file()
+                && !(dir.as_os_str().is_empty()
+                    && CONFIG_FILES.iter().any(|f| entry.file_name() == *f))
+            {
+                files.push(relative);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Copies a file and checks that the copy is complete.
+async fn copy_checked(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let written = fs::copy(from, to)
+        .await
+        .with_context(|| format!("copying {} to {}", from.display(), to.display()))?;
+    let expected = fs::metadata(from).await?.len();
+    if written != expected || fs::metadata(to).await?.len() != expected {
+        bail!("The copy of {} is incomplete", from.display());
+    }
+    Ok(())
+}
+
+/// Removes the directories left empty under a directory after moving its files.
+async fn remove_empty_dirs(root: &Path) {
+    let mut dirs = vec![];
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(mut entries) = fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+                pending.push(entry.path());
+                dirs.push(entry.path());
+            }
+        }
+    }
+    // Deepest first; directories that still hold something are kept
+    for dir in dirs.iter().rev() {
+        let _ = fs::remove_dir(dir).await;
+    }
+}
@@ -1,13 +1,27 @@
 // Copyright © 2025 Nipun Kumar
 
+use std::{collections::HashMap, sync::RwLock};
+
 use async_trait::async_trait;
+use serde_json::Value;
+
+use encryption::EncryptionInfo;
 
-use crate::{AppSettings, app_settings::Chat};
+use crate::{
+    AppSettings,
+    app_settings::{
+        AbTrial, Chat, Checkpoint, DEFAULT_PROFILE, Profiles, ScheduledTask, StorySnapshot,
+    },
+    mcp::secrets::Secrets,
+};
 
 #[cfg(target_arch = "wasm32")]
 mod browser_storage;
+pub mod encryption;
 #[cfg(not(target_arch = "wasm32"))]
 mod file_storage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod location;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub type AppStorage = file_storage::FileStorage;
@@ -22,27 +36,394 @@ pub trait Storage {
     async fn list_chats(&self) -> anyhow::Result<Vec<Chat>>;
     async fn get_chat(&self, id: u32) -> anyhow::Result<Option<Chat>>;
     async fn delete_chat(&self, id: u32) -> anyhow::Result<()>;
+    async fn save_server_states(&self, states: &HashMap<String, Value>) -> anyhow::Result<()>;
+    async fn load_server_states(&self) -> anyhow::Result<HashMap<String, Value>>;
+    async fn save_checkpoint(&self, checkpoint: &Checkpoint) -> anyhow::Result<()>;
+    async fn load_checkpoint(&self) -> anyhow::Result<Option<Checkpoint>>;
+    async fn clear_checkpoint(&self) -> anyhow::Result<()>;
+    /// Profile index; only stored in the default profile's storage
+    async fn save_profiles(&self, profiles: &Profiles) -> anyhow::Result<()>;
+    async fn load_profiles(&self) -> anyhow::Result<Option<Profiles>>;
+    async fn save_schedules(&self, tasks: &[ScheduledTask]) -> anyhow::Result<()>;
+    async fn load_schedules(&self) -> anyhow::Result<Vec<ScheduledTask>>;
+    /// Recorded prompt A/B trials, oldest first
+    async fn save_ab_trials(&self, trials: &[AbTrial]) -> anyhow::Result<()>;
+    async fn load_ab_trials(&self) -> anyhow::Result<Vec<AbTrial>>;
+    /// Snapshots of a story chat's story; deleted along with the chat
+    async fn save_snapshots(&self, chat_id: u32, snapshots: &[StorySnapshot])
+    -> anyhow::Result<()>;
+    async fn load_snapshots(&self, chat_id: u32) -> anyhow::Result<Vec<StorySnapshot>>;
+    /// Secrets referenced from MCP server specifications, kept out of the settings
+    async fn save_secrets(&self, secrets: &Secrets) -> anyhow::Result<()>;
+    async fn load_secrets(&self) -> anyhow::Result<Secrets>;
+    /// Space used by everything stored, and by each chat
+    async fn storage_usage(&self) -> anyhow::Result<StorageUsage>;
+    /// Salt and passphrase check of encrypted storage, or `None` to turn it
+    /// off; only stored in the default profile's storage
+    async fn save_encryption(&self, info: Option<&EncryptionInfo>) -> anyhow::Result<()>;
+    async fn load_encryption(&self) -> anyhow::Result<Option<EncryptionInfo>>;
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+/// Space taken by a stored chat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatUsage {
+    pub id: u32,
+    pub messages: usize,
+    /// Size of the stored chat, in bytes
+    pub bytes: u64,
+    /// Bytes of the images embedded in the chat, counted in `bytes`
+    pub image_bytes: u64,
+    /// When the chat was last saved, in milliseconds since the Unix epoch,
+    /// if the storage knows
+    pub saved_at: Option<u64>,
+}
+
+/// Space taken by the storage of a profile.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageUsage {
+    /// Size of everything stored, in bytes
+    pub total_bytes: u64,
+    /// The chats, largest first
+    pub chats: Vec<ChatUsage>,
+}
+
+impl ChatUsage {
+    /// Measures a stored chat.
+    ///
+    /// # Arguments
+    /// * `chat` - The chat as loaded
+    /// * `bytes` - Size of the stored chat
+    /// * `saved_at` - When the chat was last saved, if known
+    pub fn of(chat: &Chat, bytes: u64, saved_at: Option<u64>) -> Option<Self> {
+        Some(Self {
+            id: chat.id?,
+            messages: chat.messages.len(),
+            bytes,
+            image_bytes: chat.image_bytes(),
+            saved_at,
+        })
+    }
+}
+
+/// Deletes the chats last saved before a time. Chats whose age is unknown are kept.
+///
+/// # Arguments
+/// * `before` - The time, in milliseconds since the Unix epoch
+///
+/// # Returns
+/// The number of chats deleted
+pub async fn delete_chats_before(before: u64) -> anyhow::Result<usize> {
+    let storage = get_storage().await?;
+    let mut deleted = 0;
+    for chat in storage.storage_usage().await?.chats {
+        if chat.saved_at.is_some_and(|t| t < before) {
+            storage.delete_chat(chat.id).await?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Removes the images embedded in chats last saved before a time. Chats
+/// whose age is unknown are left alone.
+///
+/// # Arguments
+/// * `before` - The time, in milliseconds since the Unix epoch
+///
+/// # Returns
+/// The number of images removed
+pub async fn strip_images_before(before: u64) -> anyhow::Result<usize> {
+    let storage = get_storage().await?;
+    let mut removed = 0;
+    for usage in storage.storage_usage().await?.chats {
+        if usage.image_bytes > 0 && usage.saved_at.is_some_and(|t| t < before) {
+            removed += strip_chat_images(&storage, usage.id).await?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Removes the images embedded in a chat.
+///
+/// # Returns
+/// The number of images removed
+pub async fn strip_chat_images(storage: &AppStorage, id: u32) -> anyhow::Result<usize> {
+    let Some(mut chat) = storage.get_chat(id).await? else {
+        return Ok(0);
+    };
+    let removed = chat.strip_images();
+    if removed > 0 {
+        storage.save_chat(&chat).await?;
+    }
+    Ok(removed)
+}
+
+/// Profile used by `get_storage`, set at startup and when switching profiles
+static ACTIVE_PROFILE: RwLock<String> = RwLock::new(String::new());
+
+/// Returns the name of the active profile.
+pub fn active_profile() -> String {
+    let p = ACTIVE_PROFILE.read().map(|p| p.clone()).unwrap_or_default();
+    if p.is_empty() {
+        DEFAULT_PROFILE.to_string()
+    } else {
+        p
+    }
+}
+
+/// Makes `profile` the profile that `get_storage` opens.
+pub fn set_active_profile(profile: &str) {
+    if let Ok(mut p) = ACTIVE_PROFILE.write() {
+        *p = profile.to_string();
+    }
+}
+
+/// Turns a profile name into a string safe for file and database names.
+fn profile_key(profile: &str) -> String {
+    profile
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Finds the profile whose storage a new profile would share.
+///
+/// Names that only differ in characters not allowed in file names, such as
+/// "Work!" and "Work?", or only in case, lead to the same storage.
+///
+/// # Arguments
+/// * `name` - Name of the new profile
+/// * `names` - Names of the existing profiles
+///
+/// # Returns
+/// The name of the existing profile, if any
+pub fn conflicting_profile<'a>(name: &str, names: &'a [String]) -> Option<&'a String> {
+    let key = profile_key(name).to_lowercase();
+    names.iter().find(|n| profile_key(n).to_lowercase() == key)
+}
+
+/// Opens the storage of the active profile.
 pub async fn get_storage() -> anyhow::Result<AppStorage> {
+    get_profile_storage(&active_profile()).await
+}
+
+/// Returns the project config directory, where the optional config file
+/// lives, and settings and chats unless they were moved elsewhere.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn config_dir() -> std::path::PathBuf {
     use directories_next::ProjectDirs;
     use std::path::PathBuf;
 
-    let base = if let Some(proj_dirs) = ProjectDirs::from("com", "N K", "mcmcpcp") {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "N K", "mcmcpcp") {
         proj_dirs.config_dir().to_path_buf()
         // Lin: /home/alice/.config/barapp
         // Win: C:\Users\Alice\AppData\Roaming\Foo Corp\Bar App\config
         // Mac: /Users/Alice/Library/Application Support/com.Foo-Corp.Bar-App
     } else {
         PathBuf::from(".")
+    }
+}
+
+/// Opens the storage of a profile.
+///
+/// The default profile lives directly in the data directory, so data from
+/// before profiles existed stays in place; other profiles live in `profiles/<name>`.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn get_profile_storage(profile: &str) -> anyhow::Result<AppStorage> {
+    let data_dir = location::data_dir();
+    let base = if profile == DEFAULT_PROFILE {
+        data_dir
+    } else {
+        data_dir.join("profiles").join(profile_key(profile))
     };
     let storage = AppStorage::new(base);
     Ok(storage)
 }
 
+/// Opens the storage of a profile.
+///
+/// Each profile has its own IndexedDB database; the default profile keeps the
+/// original database name.
 #[cfg(target_arch = "wasm32")]
-pub async fn get_storage() -> anyhow::Result<AppStorage> {
-    let storage = AppStorage::new().await?;
+pub async fn get_profile_storage(profile: &str) -> anyhow::Result<AppStorage> {
+    let name = if profile == DEFAULT_PROFILE {
+        "app_storage".to_string()
+    } else {
+        format!("app_storage_{}", profile_key(profile))
+    };
+    let storage = AppStorage::new(&name).await?;
     Ok(storage)
 }
+
+/// Loads the profile index, which lives in the default profile's storage.
+///
+/// # Returns
+/// The saved profiles, or just the default profile if none were created
+pub async fn load_profiles() -> anyhow::Result<Profiles> {
+    let stg = get_profile_storage(DEFAULT_PROFILE).await?;
+    Ok(stg.load_profiles().await?.unwrap_or_default())
+}
+
+/// Saves the profile index to the default profile's storage.
+pub async fn save_profiles(profiles: &Profiles) -> anyhow::Result<()> {
+    get_profile_storage(DEFAULT_PROFILE)
+        .await?
+        .save_profiles(profiles)
+        .await
+}
+
+/// Loads what is needed to unlock encrypted storage, and keeps it locked
+/// until the passphrase is entered.
+///
+/// # Returns
+/// The salt and passphrase check, or `None` if storage is not encrypted
+pub async fn load_encryption() -> anyhow::Result<Option<EncryptionInfo>> {
+    let info = get_profile_storage(DEFAULT_PROFILE)
+        .await?
+        .load_encryption()
+        .await?;
+    if info.is_some() {
+        encryption::set_locked();
+    }
+    Ok(info)
+}
+
+/// Encrypts the settings and chats of every profile with a passphrase.
+pub async fn enable_encryption(passphrase: &str) -> anyhow::Result<()> {
+    if encryption::is_enabled() {
+        anyhow::bail!("Storage is already encrypted");
+    }
+    let data = load_all().await?;
+    let info = encryption::start_encryption(passphrase)?;
+    // Recorded first, so data sealed from here on is never mistaken for plain
+    get_profile_storage(DEFAULT_PROFILE)
+        .await?
+        .save_encryption(Some(&info))
+        .await?;
+    save_all(data).await?;
+    reseal_logs()
+}
+
+/// Decrypts the settings and chats of every profile and stops encrypting them.
+pub async fn disable_encryption() -> anyhow::Result<()> {
+    if !encryption::is_enabled() {
+        return Ok(());
+    }
+    let data = load_all().await?;
+    encryption::stop_encryption()?;
+    save_all(data).await?;
+    reseal_logs()?;
+    // Forgotten last, so the passphrase is still asked for if saving stopped halfway
+    get_profile_storage(DEFAULT_PROFILE)
+        .await?
+        .save_encryption(None)
+        .await?;
+    encryption::forget_retired_key();
+    Ok(())
+}
+
+/// Seals the request log and stream captures again after encryption was
+/// turned on or off. There are none on wasm.
+fn reseal_logs() -> anyhow::Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        crate::request_log::reseal_files()?;
+        crate::capture::reseal_captures()?;
+    }
+    Ok(())
+}
+
+/// Everything a profile stores that encryption covers.
+struct ProfileData {
+    storage: AppStorage,
+    settings: Option<AppSettings>,
+    chats: Vec<Chat>,
+    checkpoint: Option<Checkpoint>,
+    snapshots: Vec<(u32, Vec<StorySnapshot>)>,
+    secrets: Secrets,
+    schedules: Vec<ScheduledTask>,
+    ab_trials: Vec<AbTrial>,
+    server_states: HashMap<String, Value>,
+}
+
+/// Loads what encryption covers in every profile, so it can be saved again
+/// once encryption was turned on or off.
+async fn load_all() -> anyhow::Result<Vec<ProfileData>> {
+    let mut all = vec![];
+    for profile in load_profiles().await?.names {
+        let storage = get_profile_storage(&profile).await?;
+        let chats = storage.list_chats().await?;
+        let mut snapshots = vec![];
+        for id in chats.iter().filter_map(|c| c.id) {
+            let s = storage.load_snapshots(id).await?;
+            if !s.is_empty() {
+                snapshots.push((id, s));
+            }
+        }
+        all.push(ProfileData {
+            settings: storage.load_settings().await?,
+            checkpoint: storage.load_checkpoint().await?,
+            secrets: storage.load_secrets().await?,
+            schedules: storage.load_schedules().await?,
+            ab_trials: storage.load_ab_trials().await?,
+            server_states: storage.load_server_states().await?,
+            chats,
+            snapshots,
+            storage,
+        });
+    }
+    Ok(all)
+}
+
+/// Saves what `load_all` loaded, sealed or not as encryption now says.
+async fn save_all(all: Vec<ProfileData>) -> anyhow::Result<()> {
+    for data in all {
+        let storage = data.storage;
+        if let Some(settings) = &data.settings {
+            storage.save_settings(settings).await?;
+        }
+        for chat in &data.chats {
+            storage.save_chat(chat).await?;
+        }
+        if let Some(checkpoint) = &data.checkpoint {
+            storage.save_checkpoint(checkpoint).await?;
+        }
+        for (id, snapshots) in &data.snapshots {
+            storage.save_snapshots(*id, snapshots).await?;
+        }
+        if !data.secrets.is_empty() {
+            storage.save_secrets(&data.secrets).await?;
+        }
+        if !data.schedules.is_empty() {
+            storage.save_schedules(&data.schedules).await?;
+        }
+        if !data.ab_trials.is_empty() {
+            storage.save_ab_trials(&data.ab_trials).await?;
+        }
+        if !data.server_states.is_empty() {
+            storage.save_server_states(&data.server_states).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_profiles_sharing_storage() {
+        let names = vec![DEFAULT_PROFILE.to_string(), "Work!".to_string()];
+        assert_eq!(conflicting_profile("Work?", &names), Some(&names[1]));
+        assert_eq!(conflicting_profile("work!", &names), Some(&names[1]));
+        assert_eq!(conflicting_profile("Work!", &names), Some(&names[1]));
+        assert_eq!(conflicting_profile("Work", &names), None);
+        assert_eq!(conflicting_profile("Personal", &names), None);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Tools for models that do not accept them in requests.
+//!
+//! Such models get the tools described in the system prompt instead, and are
+//! asked to reply with a JSON object naming the tool and its arguments, which
+//! [`crate::core::extract_wierd_tool_calls`] recognises as a tool call. Earlier
+//! tool calls and results in the conversation are rewritten as plain assistant
+//! and user messages, since these models reject the tool roles too.
+
+use serde_json::{Value, json};
+
+use crate::llm::{ContentPart, Message, Tool, ToolCallDelta};
+
+/// Instructions on calling tools, put before the tool descriptions.
+const INSTRUCTIONS: &str = "You can use the tools listed below. To call a tool, reply with \
+only a JSON object of the form {\"name\": \"<tool name>\", \"arguments\": {<arguments>}} and \
+nothing else; the result will be sent back to you. Call one tool at a time. When you need no \
+tool, answer normally.";
+
+/// Rewrites a conversation for a model without tool support.
+///
+/// # Arguments
+/// * `messages` - The conversation
+/// * `tools` - Tools the model may call
+///
+/// # Returns
+/// The conversation with the tools described in the system prompt, and tool
+/// calls and results as plain text
+pub fn describe_tools(messages: &[Message], tools: &[Tool]) -> Vec<Message> {
+    let description = tools_description(tools);
+    let mut out: Vec<Message> = messages.iter().map(without_tool_roles).collect();
+    match out.first_mut() {
+        Some(Message::System { content }) => {
+            content.push_str("\n\n");
+            content.push_str(&description);
+        }
+        _ => out.insert(
+            0,
+            Message::System {
+                content: description,
+            },
+        ),
+    }
+    out
+}
+
+/// Describes the tools and how to call them.
+fn tools_description(tools: &[Tool]) -> String {
+    let mut text = format!("{INSTRUCTIONS}\n\nTools:");
+    for tool in tools {
+        let f = &tool.function;
+        text.push_str(&format!("\n\n- {}", f.name));
+        if let Some(description) = &f.description {
+            text.push_str(&format!(": {description}"));
+        }
+        if let Some(parameters) = &f.parameters {
+            text.push_str(&format!("\n  Arguments schema: {parameters}"));
+        }
+    }
+    text
+}
+
+/// Turns tool calls into assistant text and tool results into user messages.
+fn without_tool_roles(message: &Message) -> Message {
+    match message {
+        Message::Assistant {
+            content,
+            tool_calls: Some(calls),
+        } => {
+            let mut text: Vec<String> = content.iter().cloned().collect();
+            text.extend(calls.iter().map(call_json));
+            Message::Assistant {
+                content: Some(text.join("\n")),
+                tool_calls: None,
+            }
+        }
+        Message::Tool { content, .. } => Message::User {
+            content: vec![ContentPart::Text {
+                text: format!("Tool result:\n{content}"),
+            }],
+        },
+        other => other.clone(),
+    }
+}
+
+/// Writes a tool call the way the model is asked to.
+fn call_json(call: &ToolCallDelta) -> String {
+    let f = call.function.as_ref();
+    let arguments: Value = f
+        .and_then(|f| f.arguments.as_deref())
+        .and_then(|a| serde_json::from_str(a).ok())
+        .unwrap_or_else(|| json!({}));
+    json!({
+        "name": f.and_then(|f| f.name.as_deref()).unwrap_or_default(),
+        "arguments": arguments,
+    })
+    .to_string()
+}
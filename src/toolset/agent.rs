@@ -0,0 +1,395 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Agent mode: longer autonomous runs driven by an explicit task plan.
+//!
+//! The agent toolset layers a builtin `planner` MCP server over the globally
+//! configured servers. The model is asked to write a plan before acting and to
+//! keep it up to date with the planner tools; the plan is saved with the chat and
+//! shown in the side panel while the agent works.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::bail;
+use serde_json::{Value, json};
+
+use crate::llm::Message;
+use crate::mcp::{
+    McpTool, ToolResult, ToolResultContent,
+    host::{MCPHost, MCPServer},
+    result_format::{ResultFormat, ResultFormats},
+};
+
+use super::Toolset;
+
+/// Tool rounds an agent may run before asking the user whether to continue.
+pub const AGENT_MAX_TOOL_ROUNDS: u8 = 40;
+
+/// Part of the system prompt that makes the model plan, added to a chat's own
+/// prompt when agent mode is turned on.
+pub const AGENT_INSTRUCTIONS: &str = "PLANNING
+
+Before doing anything else, write a plan with the `revise_plan` tool: a goal and
+a short list of concrete steps.
+Then work through the steps one at a time using the other tools.
+When a step is finished, call `mark_done` with a brief result.
+If you learn something that changes the approach, call `revise_plan` or `add_step`
+rather than silently deviating from the plan.
+Keep going without asking the user until every step is done, unless you are blocked
+or need a decision only the user can make.
+When all steps are done, reply with a summary of what was accomplished.
+";
+
+/// Adds the agent mode instructions to a system prompt that lacks them.
+///
+/// # Arguments
+/// * `prompt` - The chat's system prompt
+///
+/// # Returns
+/// The prompt followed by [`AGENT_INSTRUCTIONS`]
+pub fn with_agent_instructions(prompt: &str) -> String {
+    if prompt.contains(AGENT_INSTRUCTIONS) {
+        return prompt.to_string();
+    }
+    let prompt = prompt.trim_end();
+    if prompt.is_empty() {
+        AGENT_INSTRUCTIONS.to_string()
+    } else {
+        format!("{prompt}\n\n{AGENT_INSTRUCTIONS}")
+    }
+}
+
+/// Removes the agent mode instructions from a system prompt, leaving the rest
+/// as it was written.
+///
+/// # Arguments
+/// * `prompt` - The chat's system prompt
+///
+/// # Returns
+/// The prompt without [`AGENT_INSTRUCTIONS`]
+pub fn without_agent_instructions(prompt: &str) -> String {
+    match prompt.split_once(AGENT_INSTRUCTIONS) {
+        Some((before, after)) => match (before.trim_end(), after.trim_start()) {
+            (before, "") => before.to_string(),
+            ("", after) => after.to_string(),
+            (before, after) => format!("{before}\n\n{after}"),
+        },
+        None => prompt.to_string(),
+    }
+}
+
+/// A single step of a plan.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct PlanStep {
+    /// What the step does
+    pub description: String,
+    /// Whether the step has been completed
+    pub done: bool,
+    /// Outcome recorded when the step was completed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+}
+
+/// The agent's plan for the current task.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Plan {
+    /// The overall goal
+    pub goal: String,
+    /// Steps, in order
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    /// Renders the plan as Markdown for the side panel.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Plan\n\n");
+        if self.steps.is_empty() && self.goal.is_empty() {
+            md.push_str("*No plan yet.*\n");
+            return md;
+        }
+        if !self.goal.is_empty() {
+            md.push_str(&format!("**Goal:** {}\n\n", self.goal));
+        }
+        let done = self.steps.iter().filter(|s| s.done).count();
+        md.push_str(&format!("**Progress:** {done}/{} steps\n\n", self.steps.len()));
+        for (i, step) in self.steps.iter().enumerate() {
+            let mark = if step.done { "✅" } else { "⬜" };
+            md.push_str(&format!("{mark} **{}.** {}\n\n", i + 1, step.description));
+            if let Some(result) = &step.result {
+                md.push_str(&format!("> {result}\n\n"));
+            }
+        }
+        md
+    }
+
+    /// Lists the steps as plain text for tool results.
+    fn summary(&self) -> String {
+        let mut s = format!("Goal: {}\n", self.goal);
+        for (i, step) in self.steps.iter().enumerate() {
+            let mark = if step.done { "x" } else { " " };
+            s.push_str(&format!("{}. [{mark}] {}\n", i + 1, step.description));
+        }
+        s
+    }
+}
+
+/// Toolset for agent mode: the global MCP servers plus the planner.
+#[derive(Clone)]
+pub struct AgentTools {
+    pub host: Arc<MCPHost>,
+}
+
+impl AgentTools {
+    /// Creates an agent toolset.
+    ///
+    /// # Arguments
+    /// * `global` - Host with the globally configured MCP servers
+    /// * `plan` - Plan saved with the chat, if any
+    pub fn new(global: Arc<MCPHost>, plan: Plan) -> Self {
+        let mut servers: HashMap<String, Box<dyn MCPServer>> = HashMap::new();
+        servers.insert("planner".into(), Box::new(PlannerMcpServer::new(plan)));
+        // Results pile up over long runs, so they are kept compact; files
+        // read are kept verbatim so edits can quote them
+        let formats =
+            ResultFormats::all(ResultFormat::Json).with_tool("read_file", ResultFormat::AsIs);
+        Self {
+            host: Arc::new(MCPHost::new_layered(servers, global).with_result_formats(formats)),
+        }
+    }
+
+    /// Reads the current plan from the planner server.
+    async fn plan(&self) -> Plan {
+        self.host
+            .invoke("planner", "get_state", json!({}))
+            .await
+            .ok()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait::async_trait]
+impl Toolset for AgentTools {
+    fn get_system_prompt(&self) -> String {
+        with_agent_instructions(
+            "You are an autonomous agent working on the user's task.
+You have access to tools which you can call to complete it.",
+        )
+    }
+
+    fn get_mcp_host(&self) -> Arc<MCPHost> {
+        self.host.clone()
+    }
+
+    async fn prune_messages(&self, _messages: &[Message]) -> Option<Vec<Message>> {
+        None
+    }
+
+    async fn run_command(&self, name: &str, _args: &str) -> anyhow::Result<String> {
+        anyhow::bail!("Unknown command /{name}")
+    }
+
+    async fn get_state(&self) -> Value {
+        json!(self.plan().await)
+    }
+
+    async fn get_markdown_repr(&self) -> Option<String> {
+        Some(self.plan().await.to_markdown())
+    }
+
+    fn max_tool_rounds(&self) -> u8 {
+        AGENT_MAX_TOOL_ROUNDS
+    }
+}
+
+/// Builtin MCP server holding the agent's plan.
+pub struct PlannerMcpServer {
+    pub plan: Plan,
+}
+
+impl PlannerMcpServer {
+    pub fn new(plan: Plan) -> Self {
+        Self { plan }
+    }
+
+    fn revise_plan(&mut self, args: Value) -> ToolResult {
+        if let Some(goal) = args.get("goal").and_then(|v| v.as_str()) {
+            self.plan.goal = goal.to_string();
+        }
+        let Some(steps) = args.get("steps").and_then(|v| v.as_array()) else {
+            return text_result("Missing required argument 'steps'.".into(), true);
+        };
+        // Completed steps are history; only the remaining steps are replaced
+        self.plan.steps.retain(|s| s.done);
+        self.plan.steps.extend(
+            steps
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|d| PlanStep {
+                    description: d.to_string(),
+                    ..Default::default()
+                }),
+        );
+        text_result(format!("Plan updated.\n{}", self.plan.summary()), false)
+    }
+
+    fn add_step(&mut self, args: Value) -> ToolResult {
+        let Some(description) = args.get("description").and_then(|v| v.as_str()) else {
+            return text_result("Missing required argument 'description'.".into(), true);
+        };
+        let step = PlanStep {
+            description: description.to_string(),
+            ..Default::default()
+        };
+        let position = args
+            .get("position")
+            .and_then(|v| v.as_u64())
+            .map(|p| (p as usize).saturating_sub(1));
+        match position {
+            Some(p) if p <= self.plan.steps.len() => self.plan.steps.insert(p, step),
+            _ => self.plan.steps.push(step),
+        }
+        text_result(format!("Step added.\n{}", self.plan.summary()), false)
+    }
+
+    fn mark_done(&mut self, args: Value) -> ToolResult {
+        let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let count = self.plan.steps.len();
+        let Some(s) = step.checked_sub(1).and_then(|i| self.plan.steps.get_mut(i)) else {
+            return text_result(
+                format!("Step {step} does not exist. The plan has {count} steps."),
+                true,
+            );
+        };
+        s.done = true;
+        s.result = args
+            .get("result")
+            .and_then(|v| v.as_str())
+            .map(|r| r.to_string());
+        let remaining = self.plan.steps.iter().filter(|s| !s.done).count();
+        text_result(
+            format!("Step {step} marked done. {remaining} step(s) remaining."),
+            false,
+        )
+    }
+}
+
+/// Builds a text-only tool result.
+fn text_result(text: String, is_error: bool) -> ToolResult {
+    ToolResult {
+        content: vec![ToolResultContent {
+            r#type: "text".to_string(),
+            text: Some(text),
+            ..Default::default()
+        }],
+        is_error: Some(is_error),
+    }
+}
+
+#[async_trait::async_trait]
+impl MCPServer for PlannerMcpServer {
+    async fn list_tools(&self) -> Vec<McpTool> {
+        vec![
+            McpTool {
+                name: "revise_plan".into(),
+                description: Some("Set the goal and replace all steps that are not done yet. Use this to write the initial plan and whenever the approach changes.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "goal": {"type": "string", "description": "The overall goal of the task"},
+                        "steps": {"type": "array", "items": {"type": "string"}, "description": "Remaining steps, in order"}
+                    },
+                    "required": ["steps"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "add_step".into(),
+                description: Some("Add a step to the plan.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "description": {"type": "string", "description": "What the step does"},
+                        "position": {"type": "number", "description": "Step number to insert at (1-based, optional - defaults to the end)"}
+                    },
+                    "required": ["description"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "mark_done".into(),
+                description: Some("Mark a step of the plan as done.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "step": {"type": "number", "description": "Step number (1-based)"},
+                        "result": {"type": "string", "description": "Brief outcome of the step"}
+                    },
+                    "required": ["step"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "get_plan".into(),
+                description: Some("Get the current plan and which steps are done.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+                annotations: None,
+            },
+        ]
+    }
+
+    async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
+        if method == "get_state" {
+            return Ok(json!(self.plan));
+        }
+
+        if method != "tools/call" {
+            bail!("Error: unknown RPC method {method}");
+        }
+
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+
+        let result = match name {
+            "revise_plan" => self.revise_plan(args),
+            "add_step" => self.add_step(args),
+            "mark_done" => self.mark_done(args),
+            "get_plan" => text_result(self.plan.summary(), false),
+            _ => text_result(format!("Unknown tool: {name}"), true),
+        };
+
+        Ok(serde_json::to_value(result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_agent_instructions_to_the_chat_prompt() {
+        let prompt = with_agent_instructions("Answer in French.\n");
+        assert_eq!(prompt, format!("Answer in French.\n\n{AGENT_INSTRUCTIONS}"));
+        assert_eq!(with_agent_instructions(&prompt), prompt);
+        assert_eq!(with_agent_instructions(""), AGENT_INSTRUCTIONS);
+    }
+
+    #[test]
+    fn removes_agent_instructions_keeping_the_chat_prompt() {
+        let prompt = with_agent_instructions("Answer in French.");
+        assert_eq!(without_agent_instructions(&prompt), "Answer in French.");
+        assert_eq!(without_agent_instructions("Be brief."), "Be brief.");
+        // Edits made after the instructions are kept
+        let edited = format!("{prompt}Use metric units.");
+        assert_eq!(
+            without_agent_instructions(&edited),
+            "Answer in French.\n\nUse metric units."
+        );
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Side conversations with the characters of a story, to develop their voice.
+//!
+//! The model plays the character from its record and the chapters it appears
+//! in, and knows nothing of the tools or the rest of the chat. An interview
+//! is not part of the chat; its transcript can be kept as a character note.
+
+use crate::{
+    core::stream_response,
+    llm::{ContentPart, LlmClient, Message},
+};
+
+use super::story::{Chapter, Character, Story};
+
+/// Words of chapter text the character is given, from the latest chapters
+/// they appear in. Summaries of the other chapters are given in full.
+const CHAPTER_CONTEXT_WORDS: usize = 3_000;
+
+/// Name the user goes by in transcripts.
+const INTERVIEWER: &str = "Author";
+
+/// A conversation with a character of the story.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interview {
+    /// Name of the character
+    pub character: String,
+    /// The conversation, starting with the system prompt
+    pub messages: Vec<Message>,
+}
+
+impl Interview {
+    /// Starts an interview with a character.
+    ///
+    /// # Arguments
+    /// * `story` - The story
+    /// * `name` - Name of the character
+    ///
+    /// # Returns
+    /// The interview, or `None` if the story has no such character
+    pub fn start(story: &Story, name: &str) -> Option<Self> {
+        let character = story.characters.get(name)?;
+        Some(Self {
+            character: character.name.clone(),
+            messages: vec![Message::System {
+                content: system_prompt(story, character),
+            }],
+        })
+    }
+
+    /// Whether anything has been said yet.
+    pub fn is_empty(&self) -> bool {
+        self.messages.len() <= 1
+    }
+
+    /// Asks the character something and adds the question and reply to the
+    /// conversation. Stop sequences, prefill and output constraints set on
+    /// the client are not applied.
+    ///
+    /// # Arguments
+    /// * `client` - LLM client to use
+    /// * `model` - Model playing the character
+    /// * `question` - What the user says
+    ///
+    /// # Returns
+    /// An error if the request fails, leaving the conversation as it was
+    pub async fn ask(
+        &mut self,
+        client: &LlmClient,
+        model: &str,
+        question: String,
+    ) -> anyhow::Result<()> {
+        let client = client
+            .clone()
+            .with_stop(vec![])
+            .with_prefill(String::new())
+            .with_constraint(None);
+        let mut messages = self.messages.clone();
+        messages.push(Message::User {
+            content: vec![ContentPart::Text { text: question }],
+        });
+        let (reply, _) = stream_response(&client, model, &messages, &[], &mut ()).await?;
+        if reply.trim().is_empty() {
+            anyhow::bail!("{} did not answer", self.character);
+        }
+        messages.push(Message::Assistant {
+            content: Some(reply.trim().to_string()),
+            tool_calls: None,
+        });
+        self.messages = messages;
+        Ok(())
+    }
+
+    /// The conversation as a character note, one paragraph per line spoken.
+    pub fn transcript(&self) -> String {
+        let mut transcript = format!("Interview with {}:\n", self.character);
+        for message in &self.messages {
+            match message {
+                Message::User { content } => {
+                    for part in content {
+                        if let ContentPart::Text { text } = part {
+                            transcript.push_str(&format!("\n{INTERVIEWER}: {}\n", text.trim()));
+                        }
+                    }
+                }
+                Message::Assistant {
+                    content: Some(text),
+                    ..
+                } => {
+                    transcript.push_str(&format!("\n{}: {}\n", self.character, text.trim()));
+                }
+                _ => {}
+            }
+        }
+        transcript
+    }
+}
+
+/// Instructions for playing a character, with what they know of the story.
+///
+/// # Arguments
+/// * `story` - The story
+/// * `character` - The character to play
+pub fn system_prompt(story: &Story, character: &Character) -> String {
+    let title = if story.metadata.title.is_empty() {
+        "an untitled story"
+    } else {
+        &story.metadata.title
+    };
+    let mut prompt = format!(
+        "You are {name}, a character in {title}. The author of the story is interviewing \
+         you to find your voice. Answer in the first person, as {name} would: in their \
+         words, with their opinions, moods and secrets, and knowing only what they know. \
+         Never step out of the role or mention that you are an AI.\n\n\
+         Who you are:\n\n{record}",
+        name = character.name,
+        record = character.to_markdown(),
+    );
+    if !story.metadata.synopsis.is_empty() {
+        prompt.push_str(&format!(
+            "\nThe story, as the author sees it (you may know less): {}\n",
+            story.metadata.synopsis
+        ));
+    }
+
+    let chapters = relevant_chapters(story, &character.name);
+    if chapters.is_empty() {
+        return prompt;
+    }
+    // Full text of the latest chapters within the budget, summaries of the rest
+    let mut budget = CHAPTER_CONTEXT_WORDS;
+    let mut full = vec![false; chapters.len()];
+    for (i, (_, chapter)) in chapters.iter().enumerate().rev() {
+        if chapter.word_count > budget {
+            break;
+        }
+        budget -= chapter.word_count;
+        full[i] = true;
+    }
+    prompt.push_str("\nWhat you have lived through, in the chapters you appear in:\n");
+    for ((index, chapter), full) in chapters.iter().zip(full) {
+        prompt.push_str(&format!(
+            "\n## Chapter {}: {}\n\n",
+            index + 1,
+            chapter.title
+        ));
+        if full {
+            prompt.push_str(&format!("{}\n", chapter.content));
+        } else if !chapter.summary.is_empty() {
+            prompt.push_str(&format!("{}\n", chapter.summary));
+        } else {
+            prompt.push_str("(Not summarized)\n");
+        }
+    }
+    prompt
+}
+
+/// Chapters that mention a character by full or first name, in story order.
+fn relevant_chapters<'a>(story: &'a Story, name: &str) -> Vec<(usize, &'a Chapter)> {
+    let name = name.to_lowercase();
+    let first = name
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    story
+        .chapters
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            let text = format!("{} {}", c.content, c.summary).to_lowercase();
+            text.contains(&name)
+                || text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|w| !w.is_empty() && w == first)
+        })
+        .collect()
+}
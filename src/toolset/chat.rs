@@ -1,19 +1,38 @@
 // Copyright © 2025 Nipun Kumar
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use serde_json::Value;
 
-use crate::{mcp::host::MCPHost, toolset::Toolset};
+use crate::{llm::Message, mcp::host::MCPHost, toolset::Toolset};
 
 #[derive(Clone)]
 pub struct ChatTools {
     pub host: Arc<MCPHost>,
+    /// State the chat had in another mode, such as an agent's plan, kept
+    /// until the chat returns to that mode
+    state: Value,
 }
 
 impl ChatTools {
-    pub fn new(host: Arc<MCPHost>) -> Self {
-        Self { host }
+    /// Creates a chat toolset.
+    ///
+    /// The toolset gets its own host layered over the global one, so servers
+    /// can be hidden from one chat without affecting others.
+    ///
+    /// # Arguments
+    /// * `global` - Host with the globally configured MCP servers
+    pub fn new(global: Arc<MCPHost>) -> Self {
+        Self {
+            host: Arc::new(MCPHost::new_layered(HashMap::new(), global)),
+            state: Value::Null,
+        }
+    }
+
+    /// Keeps the state the chat had in another mode.
+    pub fn with_state(mut self, state: Value) -> Self {
+        self.state = state;
+        self
     }
 }
 
@@ -35,8 +54,16 @@ You use tools step-by-step to accomplish a given task, with each tool use inform
         self.host.clone()
     }
 
+    async fn prune_messages(&self, _messages: &[Message]) -> Option<Vec<Message>> {
+        None
+    }
+
+    async fn run_command(&self, name: &str, _args: &str) -> anyhow::Result<String> {
+        anyhow::bail!("Unknown command /{name}")
+    }
+
     async fn get_state(&self) -> Value {
-        Value::Null
+        self.state.clone()
     }
 
     async fn get_markdown_repr(&self) -> Option<String> {
@@ -0,0 +1,241 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Coding mode: chats about a project directory on this machine (native only).
+//!
+//! The coding toolset layers builtin servers for the project over the globally
+//! configured servers: the `files` server, the `git` server and the `project`
+//! map. The project directory is chosen with the `/project` command and saved
+//! with the chat. Edits the user accepted can be undone, last first, with
+//! `/undo` while the chat is open.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::bail;
+use serde_json::{Value, json};
+
+use crate::{
+    llm::Message,
+    mcp::{
+        files::{EditHistory, FILES_SERVER, FilesMcpServer, undo_last},
+        git::{GIT_SERVER, GitMcpServer, current_branch},
+        host::{MCPHost, MCPServer},
+        patch,
+        project_map::{PROJECT_MAP_SERVER, ProjectMapMcpServer},
+        result_format::{ResultFormat, ResultFormats},
+    },
+    toolset::{SlashCommand, Toolset},
+};
+
+/// The project a coding chat is about.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Project {
+    /// Directory of the project; empty until one is chosen
+    #[serde(default)]
+    pub root: String,
+}
+
+/// Toolset for coding chats: the global MCP servers plus the project's servers.
+#[derive(Clone)]
+pub struct CodingTools {
+    pub host: Arc<MCPHost>,
+    project: Arc<RwLock<Project>>,
+    /// Edits applied in this chat, for `/undo`
+    history: EditHistory,
+    /// Review session the chat's file edits are shown in
+    review_session: u64,
+}
+
+impl CodingTools {
+    /// Creates a coding toolset.
+    ///
+    /// # Arguments
+    /// * `global` - Host with the globally configured MCP servers
+    /// * `project` - Project saved with the chat, if any
+    pub fn new(global: Arc<MCPHost>, project: Project) -> Self {
+        let history = EditHistory::default();
+        let review_session = patch::new_session();
+        let mut servers: HashMap<String, Box<dyn MCPServer>> = HashMap::new();
+        if let Some(files) = files_server(&project, &history, review_session) {
+            servers.insert(FILES_SERVER.into(), files);
+        }
+        if let Some(git) = git_server(&project) {
+            servers.insert(GIT_SERVER.into(), git);
+        }
+        if let Some(map) = project_map_server(&project) {
+            servers.insert(PROJECT_MAP_SERVER.into(), map);
+        }
+        // Only the model reads most results, but file contents stay verbatim
+        // so edits can quote them
+        let formats = ResultFormats::all(ResultFormat::Json)
+            .with_tool("read_file", ResultFormat::AsIs)
+            .with_tool("show_file_at_rev", ResultFormat::AsIs);
+        Self {
+            host: Arc::new(MCPHost::new_layered(servers, global).with_result_formats(formats)),
+            project: Arc::new(RwLock::new(project)),
+            history,
+            review_session,
+        }
+    }
+
+    /// Switches the chat to a project, replacing the project's servers.
+    ///
+    /// # Arguments
+    /// * `project` - The project
+    async fn set_project(&self, project: Project) {
+        self.host
+            .set_builtin_server(
+                FILES_SERVER,
+                files_server(&project, &self.history, self.review_session),
+            )
+            .await;
+        self.host
+            .set_builtin_server(GIT_SERVER, git_server(&project))
+            .await;
+        self.host
+            .set_builtin_server(PROJECT_MAP_SERVER, project_map_server(&project))
+            .await;
+        *self.project.write().unwrap() = project;
+    }
+
+    fn project(&self) -> Project {
+        self.project.read().unwrap().clone()
+    }
+}
+
+/// The files server for a project, if it has a directory.
+fn files_server(
+    project: &Project,
+    history: &EditHistory,
+    session: u64,
+) -> Option<Box<dyn MCPServer>> {
+    if project.root.is_empty() {
+        return None;
+    }
+    Some(Box::new(FilesMcpServer::new(
+        &project.root,
+        history.clone(),
+        session,
+    )))
+}
+
+/// The git server for a project, if it has a directory.
+fn git_server(project: &Project) -> Option<Box<dyn MCPServer>> {
+    if project.root.is_empty() {
+        return None;
+    }
+    Some(Box::new(GitMcpServer::new(&project.root)))
+}
+
+/// The project map server for a project, if it has a directory.
+fn project_map_server(project: &Project) -> Option<Box<dyn MCPServer>> {
+    if project.root.is_empty() {
+        return None;
+    }
+    Some(Box::new(ProjectMapMcpServer::new(&project.root)))
+}
+
+#[async_trait::async_trait]
+impl Toolset for CodingTools {
+    fn get_system_prompt(&self) -> String {
+        "You are an expert software engineer helping the user with their code.
+
+TOOLS
+
+Start with `project_map` to see how the project is laid out and where things
+are defined, and `file_symbols` for the definitions in a file and their lines.
+Read files with `read_file` before changing them. Change files with `edit_file`
+(replacing an exact passage) or `write_file` (new files); the user reviews each
+change and may accept only some of its hunks, so read the result again if they
+did not accept all of them. Never try to change files any other way.
+Use the `git` tools to see what changed in the project (`status`, `diff`), its
+history (`log`) and earlier versions of files (`show_file_at_rev`).
+Creating branches and committing need the user's approval; only commit when the
+user asks you to, with a concise message describing the change.
+If there are no `files`, `project` or `git` tools, the user has not chosen a
+project directory yet; ask them to choose one with the /project command.
+"
+        .into()
+    }
+
+    fn get_mcp_host(&self) -> Arc<MCPHost> {
+        self.host.clone()
+    }
+
+    fn review_session(&self) -> Option<u64> {
+        Some(self.review_session)
+    }
+
+    async fn prune_messages(&self, _messages: &[Message]) -> Option<Vec<Message>> {
+        None
+    }
+
+    fn commands(&self) -> Vec<SlashCommand> {
+        vec![
+            SlashCommand::new(
+                "project",
+                "[directory]",
+                "Show or set the project directory of this chat",
+            ),
+            SlashCommand::new("undo", "", "Undo the last file edit you accepted"),
+        ]
+    }
+
+    async fn run_command(&self, name: &str, args: &str) -> anyhow::Result<String> {
+        match (name, args.trim()) {
+            ("project", "") => {
+                let project = self.project();
+                if project.root.is_empty() {
+                    return Ok("No project directory is set; use `/project <directory>`".into());
+                }
+                Ok(format!("The project is in `{}`", project.root))
+            }
+            ("project", dir) => {
+                let path = Path::new(dir);
+                if !path.is_dir() {
+                    bail!("{dir} is not a directory");
+                }
+                let root = path.canonicalize()?.to_string_lossy().into_owned();
+                self.set_project(Project { root: root.clone() }).await;
+                Ok(format!("The project is now in `{root}`"))
+            }
+            ("undo", _) => undo_last(&self.history),
+            _ => bail!("Unknown command /{name}"),
+        }
+    }
+
+    async fn get_state(&self) -> Value {
+        json!(self.project())
+    }
+
+    async fn get_markdown_repr(&self) -> Option<String> {
+        let project = self.project();
+        let mut md = String::from("# Project\n\n");
+        if project.root.is_empty() {
+            md.push_str("*No project directory; set one with `/project <directory>`.*\n");
+            return Some(md);
+        }
+        md.push_str(&format!("**Directory:** `{}`\n\n", project.root));
+        match current_branch(Path::new(&project.root)) {
+            Some(branch) => md.push_str(&format!("**Branch:** `{branch}`\n")),
+            None => md.push_str("*Not on a git branch.*\n"),
+        }
+        let history = self.history.lock().map(|h| h.clone()).unwrap_or_default();
+        if !history.is_empty() {
+            md.push_str("\n## Applied edits\n\n");
+            for edit in history.iter().rev() {
+                let verb = if edit.before.is_some() {
+                    "Edited"
+                } else {
+                    "Created"
+                };
+                md.push_str(&format!("- {verb} `{}`\n", edit.path));
+            }
+            md.push_str("\n*Undo the last one with `/undo`.*\n");
+        }
+        Some(md)
+    }
+}
@@ -0,0 +1,457 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Continuity checks of story chapters against the rest of the story.
+//!
+//! A chapter is cross-referenced with the other chapters and with the records
+//! of the characters and world elements it mentions. Two kinds of
+//! contradiction are found by matching sentences: colors given to the same
+//! feature of someone or something ("green eyes", then "brown eyes"), and
+//! characters who act again after an earlier chapter told of their death.
+//! For everything else, passages of other chapters that share the most words
+//! with the chapter are retrieved for each name it mentions, for the model to
+//! compare.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    story::{Chapter, Story},
+    words,
+};
+
+const COLORS: &[&str] = &[
+    "amber", "auburn", "black", "blond", "blonde", "blue", "brown", "copper", "crimson", "gold",
+    "golden", "gray", "green", "grey", "hazel", "red", "silver", "violet", "white", "yellow",
+];
+
+/// Features whose color is compared across the story.
+const FEATURES: &[&str] = &[
+    "banner", "beard", "cloak", "coat", "door", "doors", "eye", "eyes", "flag", "fur", "gate",
+    "hair", "roof", "skin", "stone", "walls",
+];
+
+/// Words in a sentence that make a dead character's mention a memory rather
+/// than a reappearance.
+const MEMORY_MARKERS: &[&str] = &[
+    "remember",
+    "remembered",
+    "remembering",
+    "memory",
+    "memories",
+    "grave",
+    "tomb",
+    "funeral",
+    "ghost",
+    "dream",
+    "dreamed",
+    "dreamt",
+    "late",
+    "mourn",
+    "mourned",
+    "death",
+    "died",
+    "dead",
+    "killed",
+    "body",
+    "portrait",
+    "photo",
+    "photograph",
+    "buried",
+    "missed",
+    "miss",
+];
+
+/// Related passages retrieved per name.
+const RELATED_PASSAGES: usize = 2;
+
+/// Characters of a passage or quote shown in reports.
+const QUOTE_CHARS: usize = 300;
+
+/// What kind of contradiction an issue is.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A feature described in two colors
+    Appearance,
+    /// A character acting after their death
+    DeadCharacter,
+}
+
+impl IssueKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueKind::Appearance => "Appearance",
+            IssueKind::DeadCharacter => "Dead character",
+        }
+    }
+}
+
+/// A contradiction between a chapter and the rest of the story.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContinuityIssue {
+    /// Index of the chapter checked
+    pub chapter: usize,
+    pub kind: IssueKind,
+    /// Character or world element concerned
+    pub subject: String,
+    pub description: String,
+    /// What the checked chapter says
+    pub evidence: String,
+    /// What it contradicts
+    pub source: String,
+    /// Chapter the contradicted text is from; `None` for the subject's record
+    #[serde(default)]
+    pub source_chapter: Option<usize>,
+}
+
+/// A passage of another chapter retrieved for a name the chapter mentions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedPassage {
+    /// Character or world element the passage mentions
+    pub subject: String,
+    /// Index of the chapter it is from
+    pub chapter: usize,
+    pub text: String,
+}
+
+/// A character or world element, with the names it goes by and its record.
+struct Subject {
+    name: String,
+    /// Lowercase full name, and first name for characters
+    names: Vec<String>,
+    record: String,
+    is_character: bool,
+}
+
+/// A color given to a feature in a sentence.
+struct ColorFact {
+    feature: String,
+    color: String,
+    sentence: String,
+}
+
+/// Checks a chapter for contradictions with the rest of the story.
+///
+/// # Arguments
+/// * `story` - The story
+/// * `index` - Index of the chapter to check
+///
+/// # Returns
+/// The issues found, and passages of other chapters to compare by hand
+pub fn check(story: &Story, index: usize) -> (Vec<ContinuityIssue>, Vec<RelatedPassage>) {
+    let Some(chapter) = story.chapters.get(index) else {
+        return (vec![], vec![]);
+    };
+    let subjects = subjects(story);
+    let sentences = sentences(&chapter.content);
+    let mut issues = vec![];
+    let mut related = vec![];
+
+    for subject in &subjects {
+        let own: Vec<&str> = sentences_about(&sentences, subject, &subjects);
+        if own.is_empty() {
+            continue;
+        }
+        issues.extend(appearance_issues(story, index, subject, &subjects, &own));
+        if subject.is_character
+            && let Some(issue) = death_issue(story, index, subject, &subjects, &own)
+        {
+            issues.push(issue);
+        }
+        related.extend(related_passages(story, index, subject, &own));
+    }
+    (issues, related)
+}
+
+/// The characters and world elements of a story, sorted by name.
+fn subjects(story: &Story) -> Vec<Subject> {
+    let mut subjects: Vec<Subject> = story
+        .characters
+        .values()
+        .map(|c| {
+            let full = c.name.to_lowercase();
+            let mut names = vec![full.clone()];
+            if let Some(first) = full.split_whitespace().next()
+                && first != full
+            {
+                names.push(first.to_string());
+            }
+            Subject {
+                name: c.name.clone(),
+                names,
+                record: format!("{} {}", c.description, c.traits.join(". ")),
+                is_character: true,
+            }
+        })
+        .chain(story.world_elements.values().map(|e| {
+            let properties: Vec<String> = e
+                .properties
+                .iter()
+                .map(|(k, v)| format!("{v} {k}"))
+                .collect();
+            Subject {
+                name: e.name.clone(),
+                names: vec![e.name.to_lowercase()],
+                record: format!("{}. {}", e.description, properties.join(". ")),
+                is_character: false,
+            }
+        }))
+        .filter(|s| !s.name.trim().is_empty())
+        .collect();
+    subjects.sort_by(|a, b| a.name.cmp(&b.name));
+    subjects
+}
+
+/// Splits a text into sentences at `.`, `!`, `?` and line breaks.
+fn sentences(text: &str) -> Vec<&str> {
+    text.split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Whether a text mentions a subject by one of its names, as whole words.
+fn mentions(text: &str, subject: &Subject) -> bool {
+    let text = words(text);
+    subject.names.iter().any(|name| {
+        let name = words(name);
+        !name.is_empty()
+            && text.windows(name.len()).any(|w| {
+                w.iter().zip(&name).all(|(word, n)| {
+                    word == n || *word == format!("{n}'s") || *word == format!("{n}’s")
+                })
+            })
+    })
+}
+
+/// Sentences that mention a subject and no other, so what they say is about it.
+fn sentences_about<'a>(sentences: &[&'a str], subject: &Subject, all: &[Subject]) -> Vec<&'a str> {
+    sentences
+        .iter()
+        .filter(|s| mentions(s, subject))
+        .filter(|s| !all.iter().any(|o| o.name != subject.name && mentions(s, o)))
+        .copied()
+        .collect()
+}
+
+/// Colors given to features in sentences, such as "blue" and "eyes".
+fn color_facts(sentences: &[&str]) -> Vec<ColorFact> {
+    let mut facts = vec![];
+    for sentence in sentences {
+        let words = words(sentence);
+        for pair in words.windows(2) {
+            if COLORS.contains(&pair[0].as_str()) && FEATURES.contains(&pair[1].as_str()) {
+                facts.push(ColorFact {
+                    feature: pair[1].trim_end_matches('s').to_string(),
+                    color: normalize_color(&pair[0]),
+                    sentence: sentence.to_string(),
+                });
+            }
+        }
+    }
+    facts
+}
+
+/// Treats spellings of the same color alike.
+fn normalize_color(color: &str) -> String {
+    match color {
+        "grey" => "gray",
+        "blonde" => "blond",
+        "golden" => "gold",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Features of a subject the chapter colors differently from its record or
+/// from other chapters.
+fn appearance_issues(
+    story: &Story,
+    index: usize,
+    subject: &Subject,
+    all: &[Subject],
+    own: &[&str],
+) -> Vec<ContinuityIssue> {
+    let mut issues: Vec<ContinuityIssue> = vec![];
+    let facts = color_facts(own);
+    if facts.is_empty() {
+        return issues;
+    }
+    // What the record says, then each other chapter
+    let record_sentences = sentences(&subject.record);
+    let mut sources: Vec<(Option<usize>, Vec<ColorFact>)> =
+        vec![(None, color_facts(&record_sentences))];
+    for (i, other) in story.chapters.iter().enumerate() {
+        if i != index {
+            let sentences = sentences(&other.content);
+            sources.push((
+                Some(i),
+                color_facts(&sentences_about(&sentences, subject, all)),
+            ));
+        }
+    }
+    for fact in facts {
+        if issues.iter().any(|i| i.evidence == fact.sentence) {
+            continue;
+        }
+        let conflict = sources.iter().find_map(|(chapter, facts)| {
+            facts
+                .iter()
+                .find(|f| f.feature == fact.feature && f.color != fact.color)
+                .map(|f| (*chapter, f))
+        });
+        if let Some((source_chapter, source)) = conflict {
+            let place = match source_chapter {
+                Some(i) => format!("chapter {}", i + 1),
+                None => "the record".to_string(),
+            };
+            issues.push(ContinuityIssue {
+                chapter: index,
+                kind: IssueKind::Appearance,
+                subject: subject.name.clone(),
+                description: format!(
+                    "{}: {} {feature} here, but {} {feature} in {place}",
+                    subject.name,
+                    fact.color,
+                    source.color,
+                    feature = plural(&fact.feature),
+                ),
+                evidence: quote(&fact.sentence),
+                source: quote(&source.sentence),
+                source_chapter,
+            });
+        }
+    }
+    issues
+}
+
+/// The plural of a feature, as features are compared in the singular.
+fn plural(feature: &str) -> String {
+    match feature {
+        "hair" | "fur" | "skin" | "stone" => feature.to_string(),
+        _ => format!("{feature}s"),
+    }
+}
+
+/// A character acting in the chapter after an earlier chapter told of their
+/// death.
+fn death_issue(
+    story: &Story,
+    index: usize,
+    subject: &Subject,
+    all: &[Subject],
+    own: &[&str],
+) -> Option<ContinuityIssue> {
+    let (death_chapter, death) =
+        story.chapters[..index]
+            .iter()
+            .enumerate()
+            .find_map(|(i, c)| {
+                let sentences = sentences(&c.content);
+                sentences_about(&sentences, subject, all)
+                    .into_iter()
+                    .find(|s| tells_death(s, subject))
+                    .map(|s| (i, s.to_string()))
+            })?;
+    let appearance = own.iter().find(|s| {
+        let words = words(s);
+        !words.iter().any(|w| MEMORY_MARKERS.contains(&w.as_str()))
+    })?;
+    Some(ContinuityIssue {
+        chapter: index,
+        kind: IssueKind::DeadCharacter,
+        subject: subject.name.clone(),
+        description: format!(
+            "{} appears here, but died in chapter {}",
+            subject.name,
+            death_chapter + 1
+        ),
+        evidence: quote(appearance),
+        source: quote(&death),
+        source_chapter: Some(death_chapter),
+    })
+}
+
+/// Whether a sentence tells of a character's death.
+fn tells_death(sentence: &str, subject: &Subject) -> bool {
+    let text = words(sentence).join(" ");
+    subject.names.iter().any(|name| {
+        let name = words(name).join(" ");
+        [
+            format!("{name} died"),
+            format!("{name} was killed"),
+            format!("{name} was murdered"),
+            format!("{name} was dead"),
+            format!("{name} is dead"),
+            format!("{name} lay dead"),
+            format!("death of {name}"),
+            format!("{name}'s funeral"),
+            format!("{name}'s grave"),
+            format!("{name}'s lifeless"),
+        ]
+        .iter()
+        .any(|phrase| text.contains(phrase.as_str()))
+    })
+}
+
+/// Passages of other chapters about a subject that share the most words with
+/// what the chapter says about it.
+fn related_passages(
+    story: &Story,
+    index: usize,
+    subject: &Subject,
+    own: &[&str],
+) -> Vec<RelatedPassage> {
+    let terms: Vec<String> = {
+        let mut terms: Vec<String> = own
+            .iter()
+            .flat_map(|s| words(s))
+            .filter(|w| w.chars().count() > 3)
+            .collect();
+        terms.sort();
+        terms.dedup();
+        terms
+    };
+    let mut hits: Vec<(usize, usize, &str)> = vec![];
+    for (i, chapter) in story.chapters.iter().enumerate() {
+        if i == index {
+            continue;
+        }
+        for passage in paragraphs(chapter) {
+            if !mentions(passage, subject) {
+                continue;
+            }
+            let passage_words = words(passage);
+            let score = terms.iter().filter(|t| passage_words.contains(t)).count();
+            hits.push((score, i, passage));
+        }
+    }
+    hits.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    hits.into_iter()
+        .take(RELATED_PASSAGES)
+        .map(|(_, chapter, text)| RelatedPassage {
+            subject: subject.name.clone(),
+            chapter,
+            text: quote(text),
+        })
+        .collect()
+}
+
+/// The paragraphs of a chapter.
+fn paragraphs(chapter: &Chapter) -> impl Iterator<Item = &str> {
+    chapter
+        .content
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+}
+
+/// A sentence or passage shortened for reports.
+fn quote(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > QUOTE_CHARS {
+        let short: String = text.chars().take(QUOTE_CHARS).collect();
+        format!("{short}…")
+    } else {
+        text
+    }
+}
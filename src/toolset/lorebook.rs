@@ -0,0 +1,110 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Lorebook: characters and world elements brought into the model's context
+//! only when the conversation mentions them.
+//!
+//! Entries with keywords are left out of the story sent with each request.
+//! Before a request, the last few messages are scanned for the keywords, and
+//! the entries they mention are added, most recently mentioned first, until
+//! a token budget is spent. Entries without keywords are always sent.
+
+use crate::{
+    core::estimate_tokens,
+    llm::{ContentPart, Message},
+};
+
+/// Messages, counted from the latest, scanned for keywords.
+pub const SCAN_DEPTH: usize = 4;
+
+/// Tokens that lore entries added to a request may take.
+pub const LORE_TOKEN_BUDGET: usize = 2000;
+
+/// A character or world element with keywords.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoreEntry {
+    pub name: String,
+    pub keywords: Vec<String>,
+    /// The entry as Markdown, as sent to the model
+    pub text: String,
+}
+
+/// Finds the entries mentioned in recent messages.
+///
+/// # Arguments
+/// * `entries` - The lorebook
+/// * `messages` - The conversation
+/// * `budget` - Tokens the entries may take
+///
+/// # Returns
+/// The entries to add to the request, most recently mentioned first
+pub fn triggered<'a>(
+    entries: &'a [LoreEntry],
+    messages: &[Message],
+    budget: usize,
+) -> Vec<&'a LoreEntry> {
+    let recent: Vec<String> = messages
+        .iter()
+        .rev()
+        .filter_map(message_text)
+        .take(SCAN_DEPTH)
+        .map(|t| t.to_lowercase())
+        .collect();
+
+    // Age of the latest message mentioning each entry
+    let mut mentioned: Vec<(usize, &LoreEntry)> = entries
+        .iter()
+        .filter_map(|entry| {
+            recent
+                .iter()
+                .position(|text| entry.keywords.iter().any(|k| mentions(text, k)))
+                .map(|age| (age, entry))
+        })
+        .collect();
+    mentioned.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.name.cmp(&y.name)));
+
+    let mut left = budget;
+    let mut included = vec![];
+    for (_, entry) in mentioned {
+        let tokens = estimate_tokens(&entry.text);
+        // A long entry is skipped, leaving room for shorter ones
+        if tokens <= left {
+            left -= tokens;
+            included.push(entry);
+        }
+    }
+    included
+}
+
+/// Text of a message from the user or a reply of the model.
+fn message_text(message: &Message) -> Option<String> {
+    match message {
+        Message::User { content } => Some(
+            content
+                .iter()
+                .filter_map(|c| match c {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Message::Assistant {
+            content: Some(content),
+            ..
+        } if !content.trim().is_empty() => Some(content.clone()),
+        _ => None,
+    }
+}
+
+/// Whether a lowercase text contains a keyword as whole words, ignoring case.
+fn mentions(text: &str, keyword: &str) -> bool {
+    let keyword = keyword.trim().to_lowercase();
+    if keyword.is_empty() {
+        return false;
+    }
+    text.match_indices(&keyword).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + keyword.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
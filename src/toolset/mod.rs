@@ -4,10 +4,47 @@ use std::sync::Arc;
 
 use serde_json::Value;
 
-use crate::mcp::host::MCPHost;
+use crate::{
+    app_settings::MessageAuthor,
+    llm::{Constraint, Message},
+    mcp::host::MCPHost,
+};
 
+pub mod agent;
+pub mod character_chat;
 pub mod chat;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod coding;
+pub mod continuity;
+pub mod lorebook;
+pub mod registry;
+pub mod roleplay;
+pub mod screenplay;
 pub mod story;
+pub mod style_guide;
+pub mod summaries;
+
+/// A command typed in the chat input as `/name args`, handled by the app
+/// instead of being sent to the model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlashCommand {
+    /// Name typed after the slash
+    pub name: String,
+    /// Arguments the command takes, as shown in the completion menu
+    pub usage: String,
+    /// What the command does
+    pub description: String,
+}
+
+impl SlashCommand {
+    pub fn new(name: &str, usage: &str, description: &str) -> Self {
+        Self {
+            name: name.into(),
+            usage: usage.into(),
+            description: description.into(),
+        }
+    }
+}
 
 #[async_trait::async_trait]
 pub trait Toolset {
@@ -18,4 +55,57 @@ pub trait Toolset {
     async fn get_state(&self) -> Value;
 
     async fn get_markdown_repr(&self) -> Option<String>;
+
+    /// Tool rounds to run before pausing for the user to confirm.
+    fn max_tool_rounds(&self) -> u8 {
+        crate::core::MAX_TOOL_ROUNDS
+    }
+
+    /// Grammar or JSON schema the model's output must follow, on backends that
+    /// support constrained generation. A constraint set by the user takes precedence.
+    fn output_constraint(&self) -> Option<Constraint> {
+        None
+    }
+
+    /// Shortens the conversation sent with each request, for toolsets whose
+    /// state makes old messages unnecessary. The stored conversation is not changed.
+    ///
+    /// # Returns
+    /// The messages to send instead, or `None` to send the whole conversation
+    async fn prune_messages(&self, messages: &[Message]) -> Option<Vec<Message>>;
+
+    /// Review session the toolset's proposed file edits are shown in, if it
+    /// proposes any; see [`crate::mcp::patch`].
+    fn review_session(&self) -> Option<u64> {
+        None
+    }
+
+    /// Who the model speaks as in the next turn, recorded as the author of
+    /// its replies instead of the model, with the model kept alongside.
+    fn speaker(&self) -> Option<MessageAuthor> {
+        None
+    }
+
+    /// Slash commands this toolset adds to the chat input.
+    fn commands(&self) -> Vec<SlashCommand> {
+        vec![]
+    }
+
+    /// Runs one of the toolset's slash commands.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the command, one of [`Toolset::commands`]
+    /// * `args` - Text typed after the command name
+    ///
+    /// # Returns
+    /// Markdown to show the user, or an error if the command failed
+    async fn run_command(&self, name: &str, args: &str) -> anyhow::Result<String>;
+}
+
+/// Splits a text into lowercase words, keeping apostrophes within words.
+pub(crate) fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .map(|w| w.trim_matches(|c| c == '\'' || c == '’').to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
 }
@@ -0,0 +1,199 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Registry of the toolsets chats can run in.
+//!
+//! Each toolset is registered with its ID, how it is shown, and a factory that
+//! builds it from the state stored with a chat. The UI, the scheduler and
+//! exports look toolsets up here instead of matching on [`Toolsets`], so a new
+//! toolset, including one provided at runtime, only needs to be registered.
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+use dioxus::logger::tracing::warn;
+use serde_json::{Value, json};
+
+use crate::{
+    app_settings::Toolsets,
+    mcp::host::MCPHost,
+    toolset::{
+        Toolset,
+        agent::{AgentTools, Plan},
+        chat::ChatTools,
+        roleplay::{RoleplayTools, Scene},
+        story::{Story, StoryWriter},
+    },
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::toolset::coding::{CodingTools, Project};
+
+/// Builds a toolset from the global MCP host and the state stored with a chat.
+pub type ToolsetFactory = Arc<dyn Fn(Arc<MCPHost>, &Value) -> Box<dyn Toolset> + Send + Sync>;
+
+/// A toolset chats can run in.
+#[derive(Clone)]
+pub struct ToolsetEntry {
+    /// ID stored as the chat's type
+    pub id: Toolsets,
+    /// Name shown in pickers and the chat list
+    pub name: String,
+    /// Emoji shown next to the name
+    pub icon: String,
+    /// Name of the side panel showing the toolset's state, such as "Story"
+    pub panel_label: String,
+    /// Builds the toolset from a chat's stored state
+    pub factory: ToolsetFactory,
+    /// State of a new chat
+    pub initial_state: Value,
+    /// JSON Schema of the state, if the toolset publishes one
+    pub state_schema: Option<Value>,
+}
+
+/// Registered toolsets, in the order they are offered.
+static REGISTRY: LazyLock<RwLock<Vec<ToolsetEntry>>> =
+    LazyLock::new(|| RwLock::new(builtin_toolsets()));
+
+/// The toolsets that come with the app.
+fn builtin_toolsets() -> Vec<ToolsetEntry> {
+    vec![
+        ToolsetEntry {
+            id: Toolsets::Chat,
+            name: "Chat".into(),
+            icon: "💬".into(),
+            panel_label: "Panel".into(),
+            factory: Arc::new(|host, state| {
+                Box::new(ChatTools::new(host).with_state(state.clone()))
+            }),
+            initial_state: json!({}),
+            state_schema: None,
+        },
+        ToolsetEntry {
+            id: Toolsets::Agent,
+            name: "Agent".into(),
+            icon: "🧭".into(),
+            panel_label: "Plan".into(),
+            factory: Arc::new(|host, state| {
+                let plan: Plan = serde_json::from_value(state.clone()).unwrap_or_else(|e| {
+                    warn!("Invalid agent plan: {e:?}");
+                    Default::default()
+                });
+                Box::new(AgentTools::new(host, plan))
+            }),
+            initial_state: json!(Plan::default()),
+            state_schema: None,
+        },
+        ToolsetEntry {
+            id: Toolsets::Story,
+            name: "Story".into(),
+            icon: "📝".into(),
+            panel_label: "Story".into(),
+            factory: Arc::new(|_, state| {
+                let story: Story = serde_json::from_value(state.clone()).unwrap_or_else(|e| {
+                    warn!("Invalid story metadata: {e:?}");
+                    Default::default()
+                });
+                Box::new(StoryWriter::new(story))
+            }),
+            initial_state: json!(Story::default()),
+            state_schema: None,
+        },
+        ToolsetEntry {
+            id: Toolsets::Roleplay,
+            name: "Role-play".into(),
+            icon: "🎭".into(),
+            panel_label: "Scene".into(),
+            factory: Arc::new(|host, state| {
+                let scene: Scene = serde_json::from_value(state.clone()).unwrap_or_else(|e| {
+                    warn!("Invalid scene: {e:?}");
+                    Default::default()
+                });
+                Box::new(RoleplayTools::new(host, scene))
+            }),
+            initial_state: json!(Scene::default()),
+            state_schema: None,
+        },
+        #[cfg(not(target_arch = "wasm32"))]
+        ToolsetEntry {
+            id: Toolsets::Coding,
+            name: "Coding".into(),
+            icon: "🛠️".into(),
+            panel_label: "Project".into(),
+            factory: Arc::new(|host, state| {
+                let project: Project = serde_json::from_value(state.clone()).unwrap_or_else(|e| {
+                    warn!("Invalid project: {e:?}");
+                    Default::default()
+                });
+                Box::new(CodingTools::new(host, project))
+            }),
+            initial_state: json!(Project::default()),
+            state_schema: None,
+        },
+    ]
+}
+
+/// Registers a toolset, replacing any registered with the same ID.
+///
+/// # Arguments
+/// * `entry` - The toolset
+pub fn register(entry: ToolsetEntry) {
+    let mut registry = REGISTRY.write().unwrap();
+    match registry.iter_mut().find(|e| e.id == entry.id) {
+        Some(existing) => *existing = entry,
+        None => registry.push(entry),
+    }
+}
+
+/// Lists the registered toolsets, in the order they are offered.
+pub fn entries() -> Vec<ToolsetEntry> {
+    REGISTRY.read().unwrap().clone()
+}
+
+/// Looks up a registered toolset.
+///
+/// # Arguments
+/// * `id` - The toolset's ID
+pub fn entry(id: &Toolsets) -> Option<ToolsetEntry> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|e| &e.id == id)
+        .cloned()
+}
+
+/// Looks up a toolset, falling back to the plain chat toolset for IDs that are
+/// not registered, such as those of a toolset that was removed.
+///
+/// # Arguments
+/// * `id` - The toolset's ID
+pub fn entry_or_chat(id: &Toolsets) -> ToolsetEntry {
+    entry(id).unwrap_or_else(|| {
+        warn!("Unknown toolset {id:?}; using the chat toolset");
+        entry(&Toolsets::Chat).expect("the chat toolset is built in")
+    })
+}
+
+/// Builds a toolset from the state stored with a chat.
+///
+/// # Arguments
+/// * `id` - The toolset's ID
+/// * `host` - Global MCP host
+/// * `state` - The chat's stored state
+pub fn create(id: &Toolsets, host: Arc<MCPHost>, state: &Value) -> Box<dyn Toolset> {
+    (entry_or_chat(id).factory)(host, state)
+}
+
+/// Builds a toolset for a new chat.
+///
+/// # Arguments
+/// * `id` - The toolset's ID
+/// * `host` - Global MCP host
+///
+/// # Returns
+/// The toolset and the state to store with the chat
+pub fn create_new(id: &Toolsets, host: Arc<MCPHost>) -> (Box<dyn Toolset>, Value) {
+    let entry = entry_or_chat(id);
+    (
+        (entry.factory)(host, &entry.initial_state),
+        entry.initial_state,
+    )
+}
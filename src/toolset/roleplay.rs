@@ -0,0 +1,368 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Role-play with a cast of personas the model speaks as, one at a time.
+//!
+//! The user picks which persona speaks next, or leaves it to the model as
+//! narrator. Replies are recorded with the persona that spoke as their
+//! author, so the chat shows each speaker's name and avatar. The model can
+//! also write the user's next message, as the persona the user plays, as a
+//! draft for them to edit before sending.
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{
+    app_settings::MessageAuthor,
+    core::stream_response,
+    llm::{ContentPart, LlmClient, Message},
+    mcp::host::MCPHost,
+};
+
+use super::Toolset;
+
+/// Name the user's persona goes by when they have not named it.
+const DEFAULT_PLAYER: &str = "User";
+
+/// A character in the role-play.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Persona {
+    /// Name shown above the persona's messages
+    pub name: String,
+    /// Who the persona is: looks, personality, manner of speaking
+    #[serde(default)]
+    pub description: String,
+    /// Emoji or image URL shown next to the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+/// The cast and turn order of a role-play, stored as the chat's state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Scene {
+    /// Where and when the role-play takes place, and what is going on
+    #[serde(default)]
+    pub setting: String,
+    /// Personas the model speaks as
+    #[serde(default)]
+    pub cast: Vec<Persona>,
+    /// The persona the user plays
+    #[serde(default)]
+    pub player: Persona,
+    /// Name of the persona that speaks next, or `None` for the model to
+    /// narrate and choose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+impl Scene {
+    /// Looks up a persona of the cast by name.
+    pub fn persona(&self, name: &str) -> Option<&Persona> {
+        self.cast.iter().find(|p| p.name == name)
+    }
+
+    /// The persona chosen to speak next, if they are still in the cast.
+    pub fn next_speaker(&self) -> Option<&Persona> {
+        self.persona(self.next.as_deref()?)
+    }
+
+    /// Name of the user's persona.
+    pub fn player_name(&self) -> &str {
+        match self.player.name.trim() {
+            "" => DEFAULT_PLAYER,
+            name => name,
+        }
+    }
+
+    /// Adds a persona to the cast, replacing any with the same name.
+    ///
+    /// # Arguments
+    /// * `persona` - The persona
+    ///
+    /// # Returns
+    /// An error if the persona has no name
+    pub fn add_persona(&mut self, persona: Persona) -> anyhow::Result<()> {
+        let name = persona.name.trim().to_string();
+        if name.is_empty() {
+            anyhow::bail!("A persona needs a name");
+        }
+        let persona = Persona { name, ..persona };
+        match self.cast.iter_mut().find(|p| p.name == persona.name) {
+            Some(existing) => *existing = persona,
+            None => self.cast.push(persona),
+        }
+        Ok(())
+    }
+
+    /// Takes a persona out of the cast, and out of the turn order.
+    pub fn remove_persona(&mut self, name: &str) {
+        self.cast.retain(|p| p.name != name);
+        if self.next.as_deref() == Some(name) {
+            self.next = None;
+        }
+    }
+
+    /// The cast and setting, as given to the model before each reply.
+    fn cast_sheet(&self) -> String {
+        let mut sheet = String::new();
+        if !self.setting.trim().is_empty() {
+            sheet.push_str(&format!("Setting: {}\n\n", self.setting.trim()));
+        }
+        sheet.push_str("Cast:\n");
+        for persona in &self.cast {
+            sheet.push_str(&format!("- {}", persona.name));
+            if !persona.description.trim().is_empty() {
+                sheet.push_str(&format!(": {}", persona.description.trim()));
+            }
+            sheet.push('\n');
+        }
+        sheet.push_str(&format!("\nThe user plays {}", self.player_name()));
+        if !self.player.description.trim().is_empty() {
+            sheet.push_str(&format!(": {}", self.player.description.trim()));
+        }
+        sheet.push('\n');
+        sheet
+    }
+
+    /// Instructions for the coming reply: who speaks, with the cast sheet.
+    fn direction(&self) -> String {
+        let turn = match self.next_speaker() {
+            Some(persona) => format!(
+                "Write the next reply as {name} only, in their voice, knowing only what they \
+                 know. Do not write lines or actions for anyone else, and do not start with \
+                 their name.",
+                name = persona.name
+            ),
+            None => format!(
+                "Narrate the next reply, and let whichever of the cast fits speak. Do not \
+                 write lines or actions for {}.",
+                self.player_name()
+            ),
+        };
+        format!("{}\n{turn}", self.cast_sheet())
+    }
+
+    /// The scene as Markdown, for the side panel.
+    pub fn to_markdown(&self) -> String {
+        let mut md = "# Scene\n\n".to_string();
+        if !self.setting.trim().is_empty() {
+            md.push_str(&format!("{}\n\n", self.setting.trim()));
+        }
+        md.push_str("## Cast\n\n");
+        if self.cast.is_empty() {
+            md.push_str("No personas yet. Add them below.\n\n");
+        }
+        for persona in &self.cast {
+            let avatar = persona.avatar.as_deref().unwrap_or_default();
+            let next = if self.next.as_deref() == Some(persona.name.as_str()) {
+                " *(speaks next)*"
+            } else {
+                ""
+            };
+            md.push_str(&format!("- {avatar} **{}**{next}", persona.name));
+            if !persona.description.trim().is_empty() {
+                md.push_str(&format!(": {}", persona.description.trim()));
+            }
+            md.push('\n');
+        }
+        md.push_str(&format!("\nYou play **{}**.\n", self.player_name()));
+        md
+    }
+}
+
+#[derive(Clone)]
+pub struct RoleplayTools {
+    pub host: Arc<MCPHost>,
+    scene: Scene,
+}
+
+impl RoleplayTools {
+    /// Creates a role-play toolset.
+    ///
+    /// Like the chat toolset, it gets its own host layered over the global
+    /// one, so the role-play can use the configured servers.
+    ///
+    /// # Arguments
+    /// * `global` - Host with the globally configured MCP servers
+    /// * `scene` - The cast and turn order
+    pub fn new(global: Arc<MCPHost>, scene: Scene) -> Self {
+        Self {
+            host: Arc::new(MCPHost::new_layered(HashMap::new(), global)),
+            scene,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Toolset for RoleplayTools {
+    fn get_system_prompt(&self) -> String {
+        "You are the narrator and the cast of an interactive role-play with the user. \
+Stay in character and in the scene, and never mention that you are an AI.
+
+Before each reply you are told the cast and who speaks next. Write only that \
+persona's lines and actions, in their voice, and leave the user's persona to the user. \
+Keep replies to a few paragraphs, and end them where the user can respond."
+            .into()
+    }
+
+    fn get_mcp_host(&self) -> Arc<MCPHost> {
+        self.host.clone()
+    }
+
+    /// Adds who speaks next, with the cast, after the conversation, so
+    /// changes to the scene apply from the next reply on.
+    async fn prune_messages(&self, messages: &[Message]) -> Option<Vec<Message>> {
+        let mut messages = messages.to_vec();
+        messages.push(Message::System {
+            content: self.scene.direction(),
+        });
+        Some(messages)
+    }
+
+    fn speaker(&self) -> Option<MessageAuthor> {
+        let persona = self.scene.next_speaker()?;
+        Some(MessageAuthor {
+            name: persona.name.clone(),
+            model: None,
+            avatar: persona.avatar.clone(),
+        })
+    }
+
+    async fn run_command(&self, name: &str, _args: &str) -> anyhow::Result<String> {
+        anyhow::bail!("Unknown command /{name}")
+    }
+
+    async fn get_state(&self) -> Value {
+        json!(self.scene)
+    }
+
+    async fn get_markdown_repr(&self) -> Option<String> {
+        Some(self.scene.to_markdown())
+    }
+}
+
+/// Has the model write the user's next message, as the persona they play,
+/// for them to edit before sending. Stop sequences, prefill and output
+/// constraints set on the client are not applied.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to write the message
+/// * `scene` - The cast and the user's persona
+/// * `messages` - The conversation so far
+///
+/// # Returns
+/// The draft, or an error if the request fails or the model wrote nothing
+pub async fn impersonate(
+    client: &LlmClient,
+    model: &str,
+    scene: &Scene,
+    messages: &[Message],
+) -> anyhow::Result<String> {
+    let client = client
+        .clone()
+        .with_stop(vec![])
+        .with_prefill(String::new())
+        .with_constraint(None);
+    let player = scene.player_name();
+    let mut messages = messages.to_vec();
+    messages.push(Message::User {
+        content: vec![ContentPart::Text {
+            text: format!(
+                "{}\nStep out of the cast for one message: write what {player} says or does \
+                 next, in the first person, as the user would write it. Reply with the message \
+                 only, without their name or any comment.",
+                scene.cast_sheet()
+            ),
+        }],
+    });
+    let (draft, _) = stream_response(&client, model, &messages, &[], &mut ()).await?;
+    let draft = draft.trim();
+    let draft = draft
+        .strip_prefix(&format!("{player}:"))
+        .unwrap_or(draft)
+        .trim();
+    if draft.is_empty() {
+        anyhow::bail!("The model did not write a message for {player}");
+    }
+    Ok(draft.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn persona(name: &str) -> Persona {
+        Persona {
+            name: name.into(),
+            description: format!("{name} is a knight"),
+            avatar: Some("🛡️".into()),
+        }
+    }
+
+    fn scene() -> Scene {
+        Scene {
+            setting: "A tavern at night".into(),
+            cast: vec![persona("Anna"), persona("Bert")],
+            player: Persona {
+                name: "Cleo".into(),
+                ..Default::default()
+            },
+            next: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn directs_the_chosen_persona_and_records_them_as_author() {
+        let mut scene = scene();
+        scene.next = Some("Bert".into());
+        let tools = RoleplayTools::new(Arc::new(MCPHost::new()), scene);
+
+        let author = tools.speaker().unwrap();
+        assert_eq!(author.name, "Bert");
+        assert_eq!(author.avatar.as_deref(), Some("🛡️"));
+
+        let messages = tools.prune_messages(&[]).await.unwrap();
+        let Some(Message::System { content }) = messages.last() else {
+            panic!("no direction in {messages:?}");
+        };
+        assert!(content.contains("as Bert only"));
+        assert!(content.contains("- Anna: Anna is a knight"));
+        assert!(content.contains("The user plays Cleo"));
+    }
+
+    #[tokio::test]
+    async fn leaves_the_turn_to_the_narrator_without_a_speaker() {
+        let mut scene = scene();
+        scene.next = Some("Dora".into());
+        let tools = RoleplayTools::new(Arc::new(MCPHost::new()), scene);
+
+        assert_eq!(tools.speaker(), None);
+        let messages = tools.prune_messages(&[]).await.unwrap();
+        assert!(
+            matches!(messages.last(), Some(Message::System { content }) if content.contains("Narrate"))
+        );
+    }
+
+    #[test]
+    fn edits_the_cast() {
+        let mut scene = scene();
+        scene.next = Some("Anna".into());
+
+        assert!(scene.add_persona(persona(" ")).is_err());
+        scene
+            .add_persona(Persona {
+                name: " Anna ".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(scene.cast.len(), 2);
+        assert_eq!(scene.persona("Anna").unwrap().description, "");
+
+        scene.remove_persona("Anna");
+        assert_eq!(scene.cast, vec![persona("Bert")]);
+        assert_eq!(scene.next, None);
+        assert_eq!(Scene::default().player_name(), DEFAULT_PLAYER);
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Screenplay mode of stories: chapters written as scenes of sluglines,
+//! action, dialogue and transitions instead of prose.
+//!
+//! Scenes are kept as structured elements so the layout is never lost to the
+//! model's formatting: they are rendered to Markdown for the chapter text and
+//! to the [Fountain](https://fountain.io) plain text format for export, and
+//! checked against the usual screenplay conventions.
+
+use serde::{Deserialize, Serialize};
+
+/// Prefixes of a slugline, saying whether the scene is inside or outside.
+const SCENE_PREFIXES: &[&str] = &[
+    "INT./EXT.",
+    "EXT./INT.",
+    "INT/EXT.",
+    "I/E.",
+    "INT.",
+    "EXT.",
+    "EST.",
+];
+
+/// Transitions that do not end in "TO:".
+const OTHER_TRANSITIONS: &[&str] = &["FADE IN:", "FADE OUT.", "FADE TO BLACK.", "THE END"];
+
+/// Most words in a parenthetical before it is flagged as action in disguise.
+const MAX_PARENTHETICAL_WORDS: usize = 8;
+
+/// How the chapters of a story are written.
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StoryFormat {
+    /// Chapters of prose text
+    #[default]
+    Prose,
+    /// Chapters of screenplay scenes
+    Screenplay,
+}
+
+/// A scene of a screenplay.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Scene {
+    /// Scene heading, such as "INT. KITCHEN - NIGHT"
+    pub slugline: String,
+    /// What happens in the scene, in order
+    #[serde(default)]
+    pub elements: Vec<SceneElement>,
+}
+
+/// A block of a scene.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SceneElement {
+    /// What is seen and heard, in the present tense
+    Action { text: String },
+    /// A line spoken by a character
+    Dialogue {
+        character: String,
+        /// How the line is said, such as "(whispering)"
+        #[serde(default)]
+        parenthetical: String,
+        line: String,
+    },
+    /// A cut to the next scene, such as "CUT TO:"
+    Transition { text: String },
+}
+
+/// Checks a slugline's form.
+///
+/// # Arguments
+/// * `slugline` - Scene heading
+///
+/// # Returns
+/// Why the slugline is not valid, if it is not
+pub fn validate_slugline(slugline: &str) -> Result<(), String> {
+    let slugline = slugline.trim().to_uppercase();
+    if slugline.is_empty() {
+        return Err("The slugline is empty; use e.g. \"INT. KITCHEN - NIGHT\"".into());
+    }
+    if scene_prefix(&slugline).is_none() {
+        return Err(format!(
+            "Slugline \"{slugline}\" must start with INT., EXT., INT./EXT. or EST., e.g. \"INT. KITCHEN - NIGHT\""
+        ));
+    }
+    Ok(())
+}
+
+/// The INT./EXT. prefix a slugline starts with.
+fn scene_prefix(slugline: &str) -> Option<&'static str> {
+    let slugline = slugline.to_uppercase();
+    SCENE_PREFIXES
+        .iter()
+        .find(|p| slugline.starts_with(*p))
+        .copied()
+}
+
+impl Scene {
+    /// Brings the scene into screenplay form: sluglines, character names and
+    /// transitions in capitals, and parentheticals in parentheses.
+    pub fn normalize(&mut self) {
+        self.slugline = self.slugline.trim().to_uppercase();
+        for element in &mut self.elements {
+            match element {
+                SceneElement::Action { text } => *text = text.trim().to_string(),
+                SceneElement::Dialogue {
+                    character,
+                    parenthetical,
+                    line,
+                } => {
+                    *character = character.trim().to_uppercase();
+                    let inner = parenthetical
+                        .trim()
+                        .trim_start_matches('(')
+                        .trim_end_matches(')')
+                        .trim();
+                    *parenthetical = if inner.is_empty() {
+                        String::new()
+                    } else {
+                        format!("({inner})")
+                    };
+                    *line = line.trim().to_string();
+                }
+                SceneElement::Transition { text } => *text = text.trim().to_uppercase(),
+            }
+        }
+    }
+
+    /// Number of words in the scene, slugline included.
+    pub fn word_count(&self) -> usize {
+        let elements: usize = self
+            .elements
+            .iter()
+            .map(|e| match e {
+                SceneElement::Action { text } | SceneElement::Transition { text } => {
+                    text.split_whitespace().count()
+                }
+                SceneElement::Dialogue {
+                    character,
+                    parenthetical,
+                    line,
+                } => {
+                    character.split_whitespace().count()
+                        + parenthetical.split_whitespace().count()
+                        + line.split_whitespace().count()
+                }
+            })
+            .sum();
+        self.slugline.split_whitespace().count() + elements
+    }
+
+    /// Checks the scene against screenplay conventions.
+    ///
+    /// # Returns
+    /// A description of each problem found
+    pub fn check(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if let Err(e) = validate_slugline(&self.slugline) {
+            issues.push(e);
+        } else if !self.slugline.contains(" - ") {
+            issues.push(format!(
+                "Slugline \"{}\" has no time of day, such as \" - NIGHT\"",
+                self.slugline
+            ));
+        }
+        if self.elements.is_empty() {
+            issues.push("The scene has no action or dialogue".into());
+        }
+        if let Some(SceneElement::Dialogue { .. }) = self.elements.first() {
+            issues.push("The scene opens with dialogue; open with action that sets it".into());
+        }
+        for (i, element) in self.elements.iter().enumerate() {
+            let n = i + 1;
+            match element {
+                SceneElement::Action { text } => {
+                    if text.is_empty() {
+                        issues.push(format!("Element {n}: the action is empty"));
+                    }
+                    if let Some(line) = text.lines().find(|l| looks_like_dialogue(l)) {
+                        issues.push(format!(
+                            "Element {n}: the action contains what looks like dialogue (\"{}\"); use a dialogue element",
+                            line.trim()
+                        ));
+                    }
+                }
+                SceneElement::Dialogue {
+                    character,
+                    parenthetical,
+                    line,
+                } => {
+                    if character.is_empty() {
+                        issues.push(format!("Element {n}: the dialogue has no character"));
+                    }
+                    if line.is_empty() {
+                        issues.push(format!("Element {n}: {character} has no line"));
+                    }
+                    if parenthetical.split_whitespace().count() > MAX_PARENTHETICAL_WORDS {
+                        issues.push(format!(
+                            "Element {n}: the parenthetical {parenthetical} is long; put what the character does in an action"
+                        ));
+                    }
+                }
+                SceneElement::Transition { text } => {
+                    if !text.ends_with("TO:") && !OTHER_TRANSITIONS.contains(&text.as_str()) {
+                        issues.push(format!(
+                            "Element {n}: transition \"{text}\" should end in \"TO:\", e.g. \"CUT TO:\""
+                        ));
+                    }
+                    if i + 1 < self.elements.len() {
+                        issues.push(format!(
+                            "Element {n}: the transition \"{text}\" is not at the end of the scene"
+                        ));
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// The scene as Markdown, for the chapter text.
+    ///
+    /// # Arguments
+    /// * `number` - Number of the scene in its chapter, from 1
+    pub fn to_markdown(&self, number: usize) -> String {
+        let mut md = format!("**{number}. {}**\n\n", self.slugline);
+        for element in &self.elements {
+            match element {
+                SceneElement::Action { text } => md.push_str(&format!("{text}\n\n")),
+                SceneElement::Dialogue {
+                    character,
+                    parenthetical,
+                    line,
+                } => {
+                    md.push_str(&format!("> **{character}**  \n"));
+                    if !parenthetical.is_empty() {
+                        md.push_str(&format!("> *{parenthetical}*  \n"));
+                    }
+                    md.push_str(&format!("> {}\n\n", line.replace('\n', "  \n> ")));
+                }
+                SceneElement::Transition { text } => md.push_str(&format!("*{text}*\n\n")),
+            }
+        }
+        md
+    }
+
+    /// The scene in the Fountain format.
+    ///
+    /// # Arguments
+    /// * `number` - Scene number, from 1
+    pub fn to_fountain(&self, number: usize) -> String {
+        // A heading Fountain would not recognize is forced with a period
+        let force = if scene_prefix(&self.slugline).is_some() {
+            ""
+        } else {
+            "."
+        };
+        let mut fountain = format!("{force}{} #{number}#\n\n", self.slugline);
+        for element in &self.elements {
+            match element {
+                SceneElement::Action { text } => {
+                    fountain.push_str(&fountain_action(text));
+                    fountain.push_str("\n\n");
+                }
+                SceneElement::Dialogue {
+                    character,
+                    parenthetical,
+                    line,
+                } => {
+                    let force = if character.chars().any(|c| c.is_lowercase()) {
+                        "@"
+                    } else {
+                        ""
+                    };
+                    fountain.push_str(&format!("{force}{character}\n"));
+                    if !parenthetical.is_empty() {
+                        fountain.push_str(&format!("{parenthetical}\n"));
+                    }
+                    // A blank line would end the dialogue; two spaces keep it going
+                    let line = line
+                        .lines()
+                        .map(|l| if l.trim().is_empty() { "  " } else { l })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    fountain.push_str(&format!("{line}\n\n"));
+                }
+                SceneElement::Transition { text } => {
+                    fountain.push_str(&format!("> {text}\n\n"));
+                }
+            }
+        }
+        fountain
+    }
+}
+
+/// Renders scenes as the Markdown text of a chapter.
+pub fn scenes_markdown(scenes: &[Scene]) -> String {
+    scenes
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s.to_markdown(i + 1))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// Action text in the Fountain format. Lines in capitals would be read as
+/// character names or headings, so they are forced to be action with `!`.
+pub fn fountain_action(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let has_letters = line.chars().any(|c| c.is_alphabetic());
+            if has_letters && !line.chars().any(|c| c.is_lowercase()) {
+                format!("!{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether a line of action reads like a script line, `NAME: words`.
+fn looks_like_dialogue(line: &str) -> bool {
+    let Some((name, rest)) = line.trim().split_once(':') else {
+        return false;
+    };
+    !rest.trim().is_empty()
+        && !name.is_empty()
+        && name.split_whitespace().count() <= 3
+        && name.chars().any(|c| c.is_alphabetic())
+        && !name.chars().any(|c| c.is_lowercase())
+}
@@ -6,13 +6,30 @@ use anyhow::bail;
 use dioxus::{logger::tracing::warn, prelude::*};
 use serde_json::{Value, json};
 
+use crate::core::drop_old_tool_results;
+use crate::llm::Message;
 use crate::mcp::{
     McpTool, ToolResult, ToolResultContent,
     fetch::FetchMcpServer,
     host::{MCPHost, MCPServer},
 };
 
-use super::Toolset;
+use super::{
+    SlashCommand, Toolset,
+    continuity::{self, ContinuityIssue},
+    lorebook::{self, LoreEntry},
+    screenplay::{self, Scene, SceneElement, StoryFormat},
+    style_guide::StyleGuide,
+    summaries,
+};
+
+/// Added to the system prompt of stories in screenplay format.
+const SCREENPLAY_PROMPT: &str = "\n\nThis story is a screenplay. Write each chapter as scenes \
+    with add_scene and update_scene, never as prose content: a slugline such as \
+    \"INT. KITCHEN - NIGHT\", action in the present tense describing only what can be \
+    seen and heard, dialogue with the speaking character and an optional short \
+    parenthetical, and a transition such as \"CUT TO:\" only at the end of a scene. \
+    Run check_screenplay after writing scenes and fix the issues it reports.";
 
 #[derive(Clone)]
 pub struct StoryWriter {
@@ -55,6 +72,76 @@ impl Toolset for StoryWriter {
         self.host.clone()
     }
 
+    /// Sends only the dialogue of earlier turns: their tool calls wrote the story,
+    /// which is included in the system prompt instead. Lorebook entries are
+    /// included only when the recent messages mention them.
+    async fn prune_messages(&self, messages: &[Message]) -> Option<Vec<Message>> {
+        let mut pruned = drop_old_tool_results(messages);
+        let story: Story = serde_json::from_value(self.get_state().await).unwrap_or_else(|e| {
+            warn!("Invalid story: {e:?}");
+            Default::default()
+        });
+        let screenplay = story.metadata.format == StoryFormat::Screenplay;
+        let (story, lore) = story.split_lore();
+        if let Some(Message::System { content }) = pruned.first_mut() {
+            if screenplay {
+                content.push_str(SCREENPLAY_PROMPT);
+            }
+            let story = CreativeWriterMcpServer::new(story).markdown();
+            content.push_str(&format!(
+                "\n\nThe story so far, for reference (change it only with the tools):\n\n{story}"
+            ));
+            content.push_str(&lore_context(&lore, messages));
+        }
+        Some(pruned)
+    }
+
+    fn commands(&self) -> Vec<SlashCommand> {
+        vec![SlashCommand::new(
+            "story",
+            "stats | fountain",
+            "Show word, chapter and character counts, or the story as a Fountain screenplay",
+        )]
+    }
+
+    async fn run_command(&self, name: &str, args: &str) -> anyhow::Result<String> {
+        match (name, args.trim()) {
+            ("story", "stats") => {
+                let tr = self
+                    .host
+                    .tool_call("creative_writer", "get_story_statistics", json!({}))
+                    .await?;
+                let text = tr
+                    .content
+                    .into_iter()
+                    .filter_map(|c| c.text)
+                    .collect::<String>();
+                // The statistics are one per line; keep them apart in Markdown
+                Ok(text.replace('\n', "  \n"))
+            }
+            ("story", "fountain") => {
+                let tr = self
+                    .host
+                    .tool_call(
+                        "creative_writer",
+                        "export_story",
+                        json!({"format": "fountain"}),
+                    )
+                    .await?;
+                let text = tr
+                    .content
+                    .into_iter()
+                    .filter_map(|c| c.text)
+                    .collect::<String>();
+                Ok(format!("```fountain\n{}\n```", text.trim_end()))
+            }
+            ("story", other) => {
+                bail!("Unknown subcommand {other:?}; try /story stats or /story fountain")
+            }
+            _ => bail!("Unknown command /{name}"),
+        }
+    }
+
     async fn get_state(&self) -> Value {
         let tr = self
             .host
@@ -132,6 +219,13 @@ pub struct Character {
     pub backstory: String,
     pub goals: String,
     pub relationships: HashMap<String, String>,
+    /// Words that bring the character into the model's context when mentioned;
+    /// without any, the character is always included
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Notes on the character, such as transcripts of interviews with them
+    #[serde(default)]
+    pub notes: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -141,6 +235,13 @@ pub struct Chapter {
     pub summary: String,
     pub word_count: usize,
     pub plot_points: Vec<String>,
+    /// Scenes of a screenplay chapter; its content is rendered from them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scenes: Vec<Scene>,
+    /// Sketch of the content when the summary was written, to tell when the
+    /// summary goes out of date
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub summary_sketch: Vec<u64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -149,6 +250,10 @@ pub struct WorldElement {
     pub element_type: String, // "location", "culture", "history", "magic_system", etc.
     pub description: String,
     pub properties: HashMap<String, String>,
+    /// Words that bring the element into the model's context when mentioned;
+    /// without any, the element is always included
+    #[serde(default)]
+    pub keywords: Vec<String>,
 }
 
 #[derive(Props, Default, Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -158,6 +263,12 @@ pub struct StoryMetadata {
     pub themes: Vec<String>,
     pub target_audience: String,
     pub synopsis: String,
+    /// Rules for the voice of the story, checked with the check_style tool
+    #[serde(default)]
+    pub style_guide: StyleGuide,
+    /// Whether chapters are prose or screenplay scenes
+    #[serde(default)]
+    pub format: StoryFormat,
 }
 
 #[derive(Props, Default, Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -168,6 +279,172 @@ pub struct Story {
     pub world_elements: HashMap<String, WorldElement>,
     pub story_notes: Vec<String>,
     pub plot_points: Vec<String>,
+    /// Contradictions found by check_continuity, shown in the story panel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub continuity_issues: Vec<ContinuityIssue>,
+}
+
+impl Character {
+    /// The character as a Markdown section.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("### {}\n\n", self.name);
+        md.push_str(&format!("**Description:** {}\n\n", self.description));
+
+        if !self.traits.is_empty() {
+            md.push_str(&format!("**Traits:** {}\n\n", self.traits.join(", ")));
+        }
+
+        if !self.backstory.is_empty() {
+            md.push_str(&format!("**Backstory:** {}\n\n", self.backstory));
+        }
+
+        if !self.goals.is_empty() {
+            md.push_str(&format!("**Goals:** {}\n\n", self.goals));
+        }
+
+        if !self.relationships.is_empty() {
+            md.push_str("**Relationships:**\n");
+            for (other_char, relationship) in &self.relationships {
+                md.push_str(&format!("- {}: {}\n", other_char, relationship));
+            }
+            md.push('\n');
+        }
+
+        if !self.keywords.is_empty() {
+            md.push_str(&format!("**Keywords:** {}\n\n", self.keywords.join(", ")));
+        }
+
+        if !self.notes.is_empty() {
+            md.push_str("**Notes:**\n\n");
+            for note in &self.notes {
+                md.push_str(&format!("{}\n\n", note));
+            }
+        }
+        md
+    }
+}
+
+impl WorldElement {
+    /// The element as a Markdown section.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("### {} ({})\n\n", self.name, self.element_type);
+        md.push_str(&format!("**Description:** {}\n\n", self.description));
+
+        if !self.properties.is_empty() {
+            md.push_str("**Properties:**\n");
+            for (key, value) in &self.properties {
+                md.push_str(&format!("- {}: {}\n", key, value));
+            }
+            md.push('\n');
+        }
+
+        if !self.keywords.is_empty() {
+            md.push_str(&format!("**Keywords:** {}\n\n", self.keywords.join(", ")));
+        }
+        md
+    }
+}
+
+impl Story {
+    /// Takes the characters and world elements with keywords out of the story,
+    /// as its lorebook.
+    ///
+    /// # Returns
+    /// The rest of the story, and the lorebook entries
+    pub fn split_lore(mut self) -> (Story, Vec<LoreEntry>) {
+        let mut lore = vec![];
+        self.characters.retain(|_, c| {
+            if c.keywords.is_empty() {
+                return true;
+            }
+            lore.push(LoreEntry {
+                name: c.name.clone(),
+                keywords: c.keywords.clone(),
+                text: c.to_markdown(),
+            });
+            false
+        });
+        self.world_elements.retain(|_, e| {
+            if e.keywords.is_empty() {
+                return true;
+            }
+            lore.push(LoreEntry {
+                name: e.name.clone(),
+                keywords: e.keywords.clone(),
+                text: e.to_markdown(),
+            });
+            false
+        });
+        lore.sort_by(|a, b| a.name.cmp(&b.name));
+        (self, lore)
+    }
+}
+
+/// Lorebook entries for the system prompt: those the recent messages mention,
+/// and the names of the rest, so the model can look them up.
+///
+/// # Arguments
+/// * `lore` - The lorebook
+/// * `messages` - The conversation
+fn lore_context(lore: &[LoreEntry], messages: &[Message]) -> String {
+    if lore.is_empty() {
+        return String::new();
+    }
+    let included = lorebook::triggered(lore, messages, lorebook::LORE_TOKEN_BUDGET);
+    let mut context = String::new();
+    if !included.is_empty() {
+        context.push_str("\n\nLorebook entries mentioned in the recent messages:\n\n");
+        for entry in &included {
+            context.push_str(&entry.text);
+        }
+    }
+    let others: Vec<&str> = lore
+        .iter()
+        .filter(|e| !included.contains(e))
+        .map(|e| e.name.as_str())
+        .collect();
+    if !others.is_empty() {
+        context.push_str(&format!(
+            "\n\nOther lorebook entries, sent when mentioned (look them up with \
+             get_character_details or get_world_element): {}",
+            others.join(", ")
+        ));
+    }
+    context
+}
+
+/// JSON schema of the elements of a screenplay scene, for the scene tools.
+fn scene_elements_schema() -> Value {
+    json!({
+        "type": "array",
+        "description": "Blocks of the scene in order: action ('text', present tense), dialogue ('character', optional 'parenthetical' such as 'whispering', 'line'), or transition ('text', e.g. 'CUT TO:', only at the end)",
+        "items": {
+            "type": "object",
+            "properties": {
+                "type": {"type": "string", "enum": ["action", "dialogue", "transition"]},
+                "text": {"type": "string"},
+                "character": {"type": "string"},
+                "parenthetical": {"type": "string"},
+                "line": {"type": "string"}
+            },
+            "required": ["type"]
+        }
+    })
+}
+
+/// Error for changing the text of a chapter written as scenes.
+fn scenes_only(chapter_index: usize) -> ToolResult {
+    ToolResult {
+        content: vec![ToolResultContent {
+            r#type: "text".to_string(),
+            text: Some(format!(
+                "Chapter {chapter_index} is written as screenplay scenes and its content is rendered from them. \
+                 Change it with add_scene, update_scene or delete_scene."
+            )),
+            ..Default::default()
+        }],
+        is_error: Some(true),
+    }
 }
 
 pub struct CreativeWriterMcpServer {
@@ -187,7 +464,7 @@ impl MCPServer for CreativeWriterMcpServer {
             // Story Structure & Management
             McpTool {
                 name: "update_story_metadata".into(),
-                description: Some("Update story metadata including title, genre, themes, target audience, and synopsis.".into()),
+                description: Some("Update story metadata including title, genre, themes, target audience, synopsis, and format (prose or screenplay).".into()),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -195,9 +472,11 @@ impl MCPServer for CreativeWriterMcpServer {
                         "genre": {"type": "string", "description": "Story genre"},
                         "themes": {"type": "array", "items": {"type": "string"}, "description": "Story themes"},
                         "target_audience": {"type": "string", "description": "Target audience"},
-                        "synopsis": {"type": "string", "description": "Story synopsis"}
+                        "synopsis": {"type": "string", "description": "Story synopsis"},
+                        "format": {"type": "string", "enum": ["prose", "screenplay"], "description": "Write chapters as prose, or as screenplay scenes with add_scene"}
                     }
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "create_chapter".into(),
@@ -213,6 +492,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["title", "content"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "update_chapter".into(),
@@ -228,6 +508,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["chapter_index"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "append_to_chapter".into(),
@@ -241,6 +522,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["chapter_index", "content"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "delete_chapter".into(),
@@ -252,6 +534,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["chapter_index"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "move_chapter".into(),
@@ -264,6 +547,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["from_index", "to_index"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "get_chapter".into(),
@@ -275,6 +559,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["chapter_index"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "list_chapters".into(),
@@ -284,6 +569,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "get_story_outline".into(),
@@ -293,6 +579,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "get_story_statistics".into(),
@@ -302,6 +589,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
 
             // Character Development
@@ -315,10 +603,12 @@ impl MCPServer for CreativeWriterMcpServer {
                         "description": {"type": "string", "description": "Physical and personality description"},
                         "traits": {"type": "array", "items": {"type": "string"}, "description": "Character traits"},
                         "backstory": {"type": "string", "description": "Character backstory"},
-                        "goals": {"type": "string", "description": "Character goals and motivations"}
+                        "goals": {"type": "string", "description": "Character goals and motivations"},
+                        "keywords": {"type": "array", "items": {"type": "string"}, "description": "Lorebook keywords, e.g. the name and nicknames: the character is sent to the model only when recent messages mention one. Leave out to always send it"}
                     },
                     "required": ["name", "description"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "update_character".into(),
@@ -330,10 +620,12 @@ impl MCPServer for CreativeWriterMcpServer {
                         "description": {"type": "string", "description": "Updated description"},
                         "traits": {"type": "array", "items": {"type": "string"}, "description": "Updated traits"},
                         "backstory": {"type": "string", "description": "Updated backstory"},
-                        "goals": {"type": "string", "description": "Updated goals"}
+                        "goals": {"type": "string", "description": "Updated goals"},
+                        "keywords": {"type": "array", "items": {"type": "string"}, "description": "Updated lorebook keywords; an empty list sends the character always"}
                     },
                     "required": ["name"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "add_character_relationship".into(),
@@ -347,6 +639,20 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["character1", "character2", "relationship"]
                 }),
+                annotations: None,
+            },
+            McpTool {
+                name: "add_character_note".into(),
+                description: Some("Add a note to a character, such as something learned about their voice or past.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "character_name": {"type": "string", "description": "Name of the character"},
+                        "note": {"type": "string", "description": "Note content"}
+                    },
+                    "required": ["character_name", "note"]
+                }),
+                annotations: None,
             },
             McpTool {
                 name: "get_character_details".into(),
@@ -358,6 +664,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["name"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "list_characters".into(),
@@ -367,6 +674,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
 
             // World-building
@@ -379,10 +687,12 @@ impl MCPServer for CreativeWriterMcpServer {
                         "name": {"type": "string", "description": "Element name"},
                         "element_type": {"type": "string", "description": "Type: location, culture, history, magic_system, technology, etc."},
                         "description": {"type": "string", "description": "Detailed description"},
-                        "properties": {"type": "object", "description": "Additional properties as key-value pairs"}
+                        "properties": {"type": "object", "description": "Additional properties as key-value pairs"},
+                        "keywords": {"type": "array", "items": {"type": "string"}, "description": "Lorebook keywords: the element is sent to the model only when recent messages mention one. Leave out to always send it"}
                     },
                     "required": ["name", "element_type", "description"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "get_world_element".into(),
@@ -394,6 +704,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["name"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "list_world_elements".into(),
@@ -404,6 +715,20 @@ impl MCPServer for CreativeWriterMcpServer {
                         "element_type": {"type": "string", "description": "Filter by element type (optional)"}
                     }
                 }),
+                annotations: None,
+            },
+            McpTool {
+                name: "set_lore_keywords".into(),
+                description: Some("Set the lorebook keywords of a character or world element. An entry with keywords is sent to the model only when recent messages mention one of them, saving context in long stories.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Name of the character or world element"},
+                        "keywords": {"type": "array", "items": {"type": "string"}, "description": "Words and names that bring the entry into context, matched as whole words ignoring case; an empty list sends it always"}
+                    },
+                    "required": ["name", "keywords"]
+                }),
+                annotations: None,
             },
 
             // Plot & Narrative
@@ -417,6 +742,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["plot_point"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "analyze_story_structure".into(),
@@ -426,6 +752,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
 
             // Writing Enhancement
@@ -439,6 +766,97 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["chapter_index"]
                 }),
+                annotations: None,
+            },
+            McpTool {
+                name: "set_style_guide".into(),
+                description: Some("Set the story's style guide, which keeps the voice consistent across sessions. Only the fields given are changed.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "tense": {"type": "string", "description": "Tense of the narration, e.g. 'past' or 'present'"},
+                        "pov": {"type": "string", "description": "Point of view, e.g. 'first person' or 'third person limited'"},
+                        "banned_words": {"type": "array", "items": {"type": "string"}, "description": "Words and phrases that must not be used"},
+                        "dialect_notes": {"type": "string", "description": "Notes on dialect, spelling and diction, e.g. 'British spelling'"}
+                    }
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "check_style".into(),
+                description: Some("Check chapter text against the style guide: banned words, and the tense and point of view of the narration. Use it after writing or revising chapters.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based); all chapters if omitted"}
+                    }
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "check_continuity".into(),
+                description: Some("Check a chapter for contradictions with the other chapters and with the character and world element records, such as eye colors changing or dead characters reappearing. Also returns related passages of other chapters to compare. Use it after writing or revising chapters.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based); the last chapter if omitted"}
+                    }
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "add_scene".into(),
+                description: Some("Add a screenplay scene to a chapter. The chapter's text is rendered from its scenes; use this instead of writing screenplay text into chapter content.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based)"},
+                        "slugline": {"type": "string", "description": "Scene heading, e.g. 'INT. KITCHEN - NIGHT'"},
+                        "elements": scene_elements_schema(),
+                        "position": {"type": "number", "description": "Position to insert the scene (0-based index, optional - defaults to end)"}
+                    },
+                    "required": ["chapter_index", "slugline", "elements"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "update_scene".into(),
+                description: Some("Replace the slugline or the elements of a screenplay scene.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based)"},
+                        "scene_index": {"type": "number", "description": "Scene index in the chapter (0-based)"},
+                        "slugline": {"type": "string", "description": "New scene heading"},
+                        "elements": scene_elements_schema()
+                    },
+                    "required": ["chapter_index", "scene_index"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "delete_scene".into(),
+                description: Some("Delete a screenplay scene from a chapter.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based)"},
+                        "scene_index": {"type": "number", "description": "Scene index in the chapter (0-based)"}
+                    },
+                    "required": ["chapter_index", "scene_index"]
+                }),
+                annotations: None,
+            },
+            McpTool {
+                name: "check_screenplay".into(),
+                description: Some("Check screenplay scenes for proper structure: sluglines, dialogue, parentheticals and transitions. Use it after writing or revising scenes.".into()),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "chapter_index": {"type": "number", "description": "Chapter index (0-based); all chapters if omitted"}
+                    }
+                }),
+                annotations: None,
             },
             McpTool {
                 name: "suggest_character_development".into(),
@@ -449,6 +867,7 @@ impl MCPServer for CreativeWriterMcpServer {
                         "character_name": {"type": "string", "description": "Character to analyze (optional)"}
                     }
                 }),
+                annotations: None,
             },
 
             // Notes & Organization
@@ -462,6 +881,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     },
                     "required": ["note"]
                 }),
+                annotations: None,
             },
             McpTool {
                 name: "get_story_notes".into(),
@@ -471,6 +891,7 @@ impl MCPServer for CreativeWriterMcpServer {
                     "properties": {},
                     "required": []
                 }),
+                annotations: None,
             },
 
             // Export & Formatting
@@ -480,13 +901,23 @@ impl MCPServer for CreativeWriterMcpServer {
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "format": {"type": "string", "description": "Export format: 'markdown', 'plain_text', or 'structured'", "default": "markdown"}
+                        "format": {"type": "string", "description": "Export format: 'markdown', 'plain_text', 'fountain' (screenplay), or 'structured'", "default": "markdown"}
                     }
                 }),
+                annotations: None,
             },
         ]
     }
 
+    async fn export_state(&mut self) -> Option<Value> {
+        Some(json!(self.story))
+    }
+
+    async fn import_state(&mut self, state: Value) -> anyhow::Result<()> {
+        self.story = serde_json::from_value(state)?;
+        Ok(())
+    }
+
     async fn rpc(&mut self, method: &str, params: Value) -> anyhow::Result<serde_json::Value> {
         if method == "get_state" {
             return Ok(json!(self.story));
@@ -520,11 +951,13 @@ impl MCPServer for CreativeWriterMcpServer {
             "create_character" => self.create_character(args),
             "update_character" => self.update_character(args),
             "add_character_relationship" => self.add_character_relationship(args),
+            "add_character_note" => self.add_character_note(args),
             "get_character_details" => self.get_character_details(args),
             "list_characters" => self.list_characters(),
 
             // World-building
             "create_world_element" => self.create_world_element(args),
+            "set_lore_keywords" => self.set_lore_keywords(args),
             "get_world_element" => self.get_world_element(args),
             "list_world_elements" => self.list_world_elements(args),
 
@@ -534,6 +967,13 @@ impl MCPServer for CreativeWriterMcpServer {
 
             // Writing Enhancement
             "analyze_chapter_content" => self.analyze_chapter_content(args),
+            "set_style_guide" => self.set_style_guide(args),
+            "check_style" => self.check_style(args),
+            "check_continuity" => self.check_continuity(args),
+            "add_scene" => self.add_scene(args),
+            "update_scene" => self.update_scene(args),
+            "delete_scene" => self.delete_scene(args),
+            "check_screenplay" => self.check_screenplay(args),
             "suggest_character_development" => self.suggest_character_development(args),
 
             // Notes & Organization
@@ -579,6 +1019,21 @@ impl CreativeWriterMcpServer {
         if let Some(synopsis) = args.get("synopsis").and_then(|v| v.as_str()) {
             self.story.metadata.synopsis = synopsis.to_string();
         }
+        if let Some(format) = args.get("format") {
+            match serde_json::from_value(format.clone()) {
+                Ok(format) => self.story.metadata.format = format,
+                Err(_) => {
+                    return ToolResult {
+                        content: vec![ToolResultContent {
+                            r#type: "text".to_string(),
+                            text: Some("Invalid format. Use 'prose' or 'screenplay'.".to_string()),
+                            ..Default::default()
+                        }],
+                        is_error: Some(true),
+                    };
+                }
+            }
+        }
 
         ToolResult {
             content: vec![ToolResultContent {
@@ -622,6 +1077,11 @@ impl CreativeWriterMcpServer {
             .map(|v| v as usize);
 
         let word_count = content.split_whitespace().count();
+        let summary_sketch = if summary.is_empty() {
+            vec![]
+        } else {
+            summaries::sketch(&content)
+        };
 
         let chapter = Chapter {
             title: title.clone(),
@@ -629,6 +1089,8 @@ impl CreativeWriterMcpServer {
             summary,
             word_count,
             plot_points,
+            scenes: vec![],
+            summary_sketch,
         };
 
         if let Some(pos) = position {
@@ -676,6 +1138,10 @@ impl CreativeWriterMcpServer {
             };
         }
 
+        if args.get("content").is_some() && !self.story.chapters[chapter_index].scenes.is_empty() {
+            return scenes_only(chapter_index);
+        }
+
         let chapter = &mut self.story.chapters[chapter_index];
         let mut updated_fields = Vec::new();
 
@@ -692,6 +1158,7 @@ impl CreativeWriterMcpServer {
 
         if let Some(summary) = args.get("summary").and_then(|v| v.as_str()) {
             chapter.summary = summary.to_string();
+            chapter.summary_sketch = summaries::sketch(&chapter.content);
             updated_fields.push("summary");
         }
 
@@ -751,6 +1218,10 @@ impl CreativeWriterMcpServer {
             };
         }
 
+        if !self.story.chapters[chapter_index].scenes.is_empty() {
+            return scenes_only(chapter_index);
+        }
+
         let content_to_append = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
 
         if content_to_append.is_empty() {
@@ -817,6 +1288,8 @@ impl CreativeWriterMcpServer {
         }
 
         let removed_chapter = self.story.chapters.remove(chapter_index);
+        // Issues refer to chapters by index; check again to find them
+        self.story.continuity_issues.clear();
 
         ToolResult {
             content: vec![ToolResultContent {
@@ -881,6 +1354,8 @@ impl CreativeWriterMcpServer {
         let chapter = self.story.chapters.remove(from_index);
         let chapter_title = chapter.title.clone();
         self.story.chapters.insert(to_index, chapter);
+        // Issues refer to chapters by index; check again to find them
+        self.story.continuity_issues.clear();
 
         ToolResult {
             content: vec![ToolResultContent {
@@ -974,6 +1449,9 @@ impl CreativeWriterMcpServer {
             if !chapter.summary.is_empty() {
                 list.push_str(&format!("**Summary:** {}\n\n", chapter.summary));
             }
+            if !chapter.summary.is_empty() && summaries::is_stale(chapter) {
+                list.push_str("*The summary is out of date; the chapter changed since.*\n\n");
+            }
 
             if !chapter.plot_points.is_empty() {
                 list.push_str("**Plot Points:** ");
@@ -1022,6 +1500,9 @@ impl CreativeWriterMcpServer {
             if !chapter.summary.is_empty() {
                 outline.push_str(&format!("   Summary: {}\n", chapter.summary));
             }
+            if !chapter.summary.is_empty() && summaries::is_stale(chapter) {
+                outline.push_str("   (Summary out of date)\n");
+            }
             if !chapter.plot_points.is_empty() {
                 outline.push_str(&format!(
                     "   Plot Points: {}\n",
@@ -1119,6 +1600,17 @@ impl CreativeWriterMcpServer {
                 .unwrap_or("")
                 .to_string(),
             relationships: HashMap::new(),
+            keywords: args
+                .get("keywords")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            notes: vec![],
         };
 
         self.story.characters.insert(name.clone(), character);
@@ -1153,6 +1645,13 @@ impl CreativeWriterMcpServer {
             if let Some(goals) = args.get("goals").and_then(|v| v.as_str()) {
                 character.goals = goals.to_string();
             }
+            if let Some(keywords) = args.get("keywords").and_then(|v| v.as_array()) {
+                character.keywords = keywords
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .collect();
+            }
 
             ToolResult {
                 content: vec![ToolResultContent {
@@ -1212,6 +1711,38 @@ impl CreativeWriterMcpServer {
         }
     }
 
+    fn add_character_note(&mut self, args: Value) -> ToolResult {
+        let name = args
+            .get("character_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let note = args.get("note").and_then(|v| v.as_str()).unwrap_or("");
+        let (text, is_error) = match self.story.characters.get_mut(name) {
+            _ if note.trim().is_empty() => ("Note content is required.".to_string(), true),
+            Some(character) => {
+                character.notes.push(note.trim().to_string());
+                (
+                    format!(
+                        "Note added to '{}'. The character has {} notes.",
+                        name,
+                        character.notes.len()
+                    ),
+                    false,
+                )
+            }
+            None => (format!("Character '{}' not found.", name), true),
+        };
+
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error: Some(is_error),
+        }
+    }
+
     fn get_character_details(&self, args: Value) -> ToolResult {
         let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -1334,6 +1865,16 @@ impl CreativeWriterMcpServer {
             element_type: element_type.clone(),
             description,
             properties,
+            keywords: args
+                .get("keywords")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
         };
 
         self.story.world_elements.insert(name.clone(), element);
@@ -1351,6 +1892,57 @@ impl CreativeWriterMcpServer {
         }
     }
 
+    fn set_lore_keywords(&mut self, args: Value) -> ToolResult {
+        let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let keywords: Vec<String> = args
+            .get("keywords")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let entry = if let Some(character) = self.story.characters.get_mut(name) {
+            character.keywords = keywords.clone();
+            Some("Character")
+        } else if let Some(element) = self.story.world_elements.get_mut(name) {
+            element.keywords = keywords.clone();
+            Some("World element")
+        } else {
+            None
+        };
+
+        let (text, is_error) = match entry {
+            Some(kind) if keywords.is_empty() => (
+                format!("{kind} '{name}' has no keywords, and is always sent to the model."),
+                false,
+            ),
+            Some(kind) => (
+                format!(
+                    "{kind} '{name}' is sent to the model when recent messages mention: {}",
+                    keywords.join(", ")
+                ),
+                false,
+            ),
+            None => (
+                format!("No character or world element named '{name}' found."),
+                true,
+            ),
+        };
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error: Some(is_error),
+        }
+    }
+
     fn get_world_element(&self, args: Value) -> ToolResult {
         let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -1559,6 +2151,358 @@ impl CreativeWriterMcpServer {
     }
 
     // Writing Enhancement Methods
+    fn set_style_guide(&mut self, args: Value) -> ToolResult {
+        let guide = &mut self.story.metadata.style_guide;
+        if let Some(tense) = args.get("tense").and_then(|v| v.as_str()) {
+            guide.tense = tense.to_string();
+        }
+        if let Some(pov) = args.get("pov").and_then(|v| v.as_str()) {
+            guide.pov = pov.to_string();
+        }
+        if let Some(banned) = args.get("banned_words").and_then(|v| v.as_array()) {
+            guide.banned_words = banned
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect();
+        }
+        if let Some(notes) = args.get("dialect_notes").and_then(|v| v.as_str()) {
+            guide.dialect_notes = notes.to_string();
+        }
+
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(format!("Style guide updated:\n{}", guide.to_markdown())),
+                ..Default::default()
+            }],
+            is_error: Some(false),
+        }
+    }
+
+    fn check_style(&self, args: Value) -> ToolResult {
+        let guide = &self.story.metadata.style_guide;
+        let error = |text: String| ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(text),
+                ..Default::default()
+            }],
+            is_error: Some(true),
+        };
+        if guide.is_empty() {
+            return error("No style guide is set. Set one with set_style_guide first.".into());
+        }
+        let index = args
+            .get("chapter_index")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let chapters: Vec<(usize, &Chapter)> = match index {
+            Some(i) => match self.story.chapters.get(i) {
+                Some(chapter) => vec![(i, chapter)],
+                None => {
+                    return error(format!(
+                        "Chapter index {} is out of range. Story has {} chapters.",
+                        i,
+                        self.story.chapters.len()
+                    ));
+                }
+            },
+            None => self.story.chapters.iter().enumerate().collect(),
+        };
+
+        let mut report = String::from("# Style Check\n\n");
+        let mut total = 0;
+        for (i, chapter) in chapters {
+            let issues = guide.check(&chapter.content);
+            total += issues.len();
+            report.push_str(&format!("## {}. {}\n\n", i + 1, chapter.title));
+            if issues.is_empty() {
+                report.push_str("No issues found.\n\n");
+            }
+            for issue in issues {
+                report.push_str(&format!("- {}\n", issue));
+            }
+            report.push('\n');
+        }
+        report.push_str(&format!("**Issues found:** {}\n", total));
+        if !guide.dialect_notes.is_empty() {
+            report.push_str(&format!(
+                "\nDialect notes are not checked automatically; review the text for them: {}\n",
+                guide.dialect_notes
+            ));
+        }
+
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(report),
+                ..Default::default()
+            }],
+            is_error: Some(false),
+        }
+    }
+
+    fn check_continuity(&mut self, args: Value) -> ToolResult {
+        let index = args
+            .get("chapter_index")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(self.story.chapters.len().saturating_sub(1));
+        let Some(chapter) = self.story.chapters.get(index) else {
+            return ToolResult {
+                content: vec![ToolResultContent {
+                    r#type: "text".to_string(),
+                    text: Some(format!(
+                        "Chapter index {} is out of range. Story has {} chapters.",
+                        index,
+                        self.story.chapters.len()
+                    )),
+                    ..Default::default()
+                }],
+                is_error: Some(true),
+            };
+        };
+
+        let (issues, related) = continuity::check(&self.story, index);
+        let mut report = format!(
+            "# Continuity Check\n\n## {}. {}\n\n",
+            index + 1,
+            chapter.title
+        );
+        if issues.is_empty() {
+            report.push_str("No contradictions found.\n\n");
+        }
+        for issue in &issues {
+            report.push_str(&format!(
+                "- **{}:** {}\n  - Here: \"{}\"\n  - Before: \"{}\"\n",
+                issue.kind.label(),
+                issue.description,
+                issue.evidence,
+                issue.source
+            ));
+        }
+        if !related.is_empty() {
+            report.push_str(
+                "\n## Related Passages\n\nCompare these with the chapter for contradictions the check cannot find:\n\n",
+            );
+            for passage in &related {
+                report.push_str(&format!(
+                    "- **{}** (chapter {}): \"{}\"\n",
+                    passage.subject,
+                    passage.chapter + 1,
+                    passage.text
+                ));
+            }
+        }
+        report.push_str(&format!("\n**Issues found:** {}\n", issues.len()));
+
+        self.story.continuity_issues.retain(|i| i.chapter != index);
+        self.story.continuity_issues.extend(issues);
+        self.story.continuity_issues.sort_by_key(|i| i.chapter);
+
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(report),
+                ..Default::default()
+            }],
+            is_error: Some(false),
+        }
+    }
+
+    fn add_scene(&mut self, args: Value) -> ToolResult {
+        let mut scene = match scene_from_args(&args) {
+            Ok(scene) => scene,
+            Err(e) => return scene_error(e),
+        };
+        let chapter_index = args
+            .get("chapter_index")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let Some(chapter) = self.story.chapters.get_mut(chapter_index) else {
+            return scene_error(format!(
+                "Chapter index {} is out of range. Story has {} chapters.",
+                chapter_index,
+                self.story.chapters.len()
+            ));
+        };
+        if chapter.scenes.is_empty() && !chapter.content.trim().is_empty() {
+            return scene_error(format!(
+                "Chapter {chapter_index} already has prose content. Add scenes to a new chapter, or clear its content with update_chapter first."
+            ));
+        }
+        if let Err(e) = screenplay::validate_slugline(&scene.slugline) {
+            return scene_error(e);
+        }
+        scene.normalize();
+        let issues = scene.check();
+        let position = args
+            .get("position")
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as usize).min(chapter.scenes.len()))
+            .unwrap_or(chapter.scenes.len());
+        let slugline = scene.slugline.clone();
+        chapter.scenes.insert(position, scene);
+        render_scenes(chapter);
+
+        scene_result(
+            format!(
+                "Scene {} '{}' added to chapter {} '{}'.",
+                position, slugline, chapter_index, chapter.title
+            ),
+            &issues,
+        )
+    }
+
+    fn update_scene(&mut self, args: Value) -> ToolResult {
+        let (chapter_index, scene_index) = match self.scene_position(&args) {
+            Ok(position) => position,
+            Err(e) => return scene_error(e),
+        };
+        let mut scene = self.story.chapters[chapter_index].scenes[scene_index].clone();
+        if let Some(slugline) = args.get("slugline").and_then(|v| v.as_str()) {
+            if let Err(e) = screenplay::validate_slugline(slugline) {
+                return scene_error(e);
+            }
+            scene.slugline = slugline.to_string();
+        }
+        if let Some(elements) = args.get("elements") {
+            match serde_json::from_value::<Vec<SceneElement>>(elements.clone()) {
+                Ok(elements) => scene.elements = elements,
+                Err(e) => return scene_error(format!("Invalid scene elements: {e}")),
+            }
+        }
+        scene.normalize();
+        let issues = scene.check();
+        let chapter = &mut self.story.chapters[chapter_index];
+        chapter.scenes[scene_index] = scene;
+        render_scenes(chapter);
+
+        scene_result(
+            format!(
+                "Scene {} of chapter {} '{}' updated.",
+                scene_index, chapter_index, chapter.title
+            ),
+            &issues,
+        )
+    }
+
+    fn delete_scene(&mut self, args: Value) -> ToolResult {
+        let (chapter_index, scene_index) = match self.scene_position(&args) {
+            Ok(position) => position,
+            Err(e) => return scene_error(e),
+        };
+        let chapter = &mut self.story.chapters[chapter_index];
+        let scene = chapter.scenes.remove(scene_index);
+        render_scenes(chapter);
+
+        scene_result(
+            format!(
+                "Scene {} '{}' deleted from chapter {} '{}'.",
+                scene_index, scene.slugline, chapter_index, chapter.title
+            ),
+            &[],
+        )
+    }
+
+    /// Chapter and scene indices of a scene tool call, checked against the story.
+    fn scene_position(&self, args: &Value) -> Result<(usize, usize), String> {
+        let chapter_index = args
+            .get("chapter_index")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let scene_index = args
+            .get("scene_index")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let Some(chapter) = self.story.chapters.get(chapter_index) else {
+            return Err(format!(
+                "Chapter index {} is out of range. Story has {} chapters.",
+                chapter_index,
+                self.story.chapters.len()
+            ));
+        };
+        if scene_index >= chapter.scenes.len() {
+            return Err(format!(
+                "Scene index {} is out of range. Chapter {} has {} scenes.",
+                scene_index,
+                chapter_index,
+                chapter.scenes.len()
+            ));
+        }
+        Ok((chapter_index, scene_index))
+    }
+
+    fn check_screenplay(&self, args: Value) -> ToolResult {
+        let index = args
+            .get("chapter_index")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let chapters: Vec<(usize, &Chapter)> = match index {
+            Some(i) => match self.story.chapters.get(i) {
+                Some(chapter) => vec![(i, chapter)],
+                None => {
+                    return scene_error(format!(
+                        "Chapter index {} is out of range. Story has {} chapters.",
+                        i,
+                        self.story.chapters.len()
+                    ));
+                }
+            },
+            None => self.story.chapters.iter().enumerate().collect(),
+        };
+
+        let mut report = String::from("# Screenplay Check\n\n");
+        if self.story.metadata.format != StoryFormat::Screenplay {
+            report.push_str(
+                "The story is not in screenplay format; set it with update_story_metadata.\n\n",
+            );
+        }
+        let mut total = 0;
+        for (i, chapter) in chapters {
+            report.push_str(&format!("## {}. {}\n\n", i + 1, chapter.title));
+            if chapter.scenes.is_empty() {
+                if !chapter.content.trim().is_empty() {
+                    total += 1;
+                    report.push_str(
+                        "- The chapter is prose, not scenes; rewrite it with add_scene\n\n",
+                    );
+                } else {
+                    report.push_str("No scenes yet.\n\n");
+                }
+                continue;
+            }
+            let mut clean = true;
+            for (j, scene) in chapter.scenes.iter().enumerate() {
+                let issues = scene.check();
+                if issues.is_empty() {
+                    continue;
+                }
+                clean = false;
+                total += issues.len();
+                report.push_str(&format!("### Scene {}: {}\n\n", j, scene.slugline));
+                for issue in issues {
+                    report.push_str(&format!("- {}\n", issue));
+                }
+                report.push('\n');
+            }
+            if clean {
+                report.push_str("No issues found.\n\n");
+            }
+        }
+        report.push_str(&format!("**Issues found:** {}\n", total));
+
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(report),
+                ..Default::default()
+            }],
+            is_error: Some(false),
+        }
+    }
+
     fn analyze_chapter_content(&self, args: Value) -> ToolResult {
         let chapter_index = args
             .get("chapter_index")
@@ -1795,12 +2739,13 @@ impl CreativeWriterMcpServer {
         match format {
             "markdown" => self.export_markdown(),
             "plain_text" => self.export_plain_text(),
+            "fountain" => self.export_fountain(),
             "structured" => self.export_structured(),
             _ => ToolResult {
                 content: vec![ToolResultContent {
                     r#type: "text".to_string(),
                     text: Some(
-                        "Invalid format. Use 'markdown', 'plain_text', or 'structured'."
+                        "Invalid format. Use 'markdown', 'plain_text', 'fountain', or 'structured'."
                             .to_string(),
                     ),
                     ..Default::default()
@@ -1811,6 +2756,18 @@ impl CreativeWriterMcpServer {
     }
 
     fn export_markdown(&self) -> ToolResult {
+        ToolResult {
+            content: vec![ToolResultContent {
+                r#type: "text".to_string(),
+                text: Some(self.markdown()),
+                ..Default::default()
+            }],
+            is_error: Some(false),
+        }
+    }
+
+    /// The story as Markdown.
+    pub fn markdown(&self) -> String {
         let title = if self.story.metadata.title.is_empty() {
             "Untitled"
         } else {
@@ -1829,8 +2786,18 @@ impl CreativeWriterMcpServer {
                 self.story.metadata.themes.join(", ")
             ));
         }
+        if self.story.metadata.format == StoryFormat::Screenplay {
+            export.push_str("**Format:** Screenplay\n");
+        }
         export.push('\n');
 
+        if !self.story.metadata.style_guide.is_empty() {
+            export.push_str(&format!(
+                "## Style Guide\n\n{}\n",
+                self.story.metadata.style_guide.to_markdown()
+            ));
+        }
+
         if !self.story.metadata.synopsis.is_empty() {
             export.push_str(&format!(
                 "## Synopsis\n\n{}\n\n",
@@ -1850,46 +2817,16 @@ impl CreativeWriterMcpServer {
         // Export characters
         if !self.story.characters.is_empty() {
             export.push_str("## Characters\n\n");
-            for (name, character) in &self.story.characters {
-                export.push_str(&format!("### {}\n\n", name));
-                export.push_str(&format!("**Description:** {}\n\n", character.description));
-
-                if !character.traits.is_empty() {
-                    export.push_str(&format!("**Traits:** {}\n\n", character.traits.join(", ")));
-                }
-
-                if !character.backstory.is_empty() {
-                    export.push_str(&format!("**Backstory:** {}\n\n", character.backstory));
-                }
-
-                if !character.goals.is_empty() {
-                    export.push_str(&format!("**Goals:** {}\n\n", character.goals));
-                }
-
-                if !character.relationships.is_empty() {
-                    export.push_str("**Relationships:**\n");
-                    for (other_char, relationship) in &character.relationships {
-                        export.push_str(&format!("- {}: {}\n", other_char, relationship));
-                    }
-                    export.push('\n');
-                }
+            for character in self.story.characters.values() {
+                export.push_str(&character.to_markdown());
             }
         }
 
         // Export world elements
         if !self.story.world_elements.is_empty() {
             export.push_str("## World Elements\n\n");
-            for (name, element) in &self.story.world_elements {
-                export.push_str(&format!("### {} ({})\n\n", name, element.element_type));
-                export.push_str(&format!("**Description:** {}\n\n", element.description));
-
-                if !element.properties.is_empty() {
-                    export.push_str("**Properties:**\n");
-                    for (key, value) in &element.properties {
-                        export.push_str(&format!("- {}: {}\n", key, value));
-                    }
-                    export.push('\n');
-                }
+            for element in self.story.world_elements.values() {
+                export.push_str(&element.to_markdown());
             }
         }
 
@@ -1925,10 +2862,47 @@ impl CreativeWriterMcpServer {
             export.push('\n');
         }
 
+        export
+    }
+
+    /// The story as a Fountain screenplay: a title page, then each chapter as
+    /// a section with its summary as synopsis. Prose chapters become action.
+    fn fountain(&self) -> String {
+        let metadata = &self.story.metadata;
+        let title = if metadata.title.is_empty() {
+            "Untitled"
+        } else {
+            &metadata.title
+        };
+        let mut export = format!("Title: {}\n", title);
+        if !metadata.genre.is_empty() {
+            export.push_str(&format!("Notes: {}\n", metadata.genre));
+        }
+        export.push('\n');
+
+        let mut number = 0;
+        for chapter in &self.story.chapters {
+            export.push_str(&format!("# {}\n\n", chapter.title));
+            if !chapter.summary.is_empty() {
+                export.push_str(&format!("= {}\n\n", chapter.summary));
+            }
+            if chapter.scenes.is_empty() && !chapter.content.trim().is_empty() {
+                export.push_str(&screenplay::fountain_action(chapter.content.trim()));
+                export.push_str("\n\n");
+            }
+            for scene in &chapter.scenes {
+                number += 1;
+                export.push_str(&scene.to_fountain(number));
+            }
+        }
+        export
+    }
+
+    fn export_fountain(&self) -> ToolResult {
         ToolResult {
             content: vec![ToolResultContent {
                 r#type: "text".to_string(),
-                text: Some(export),
+                text: Some(self.fountain()),
                 ..Default::default()
             }],
             is_error: Some(false),
@@ -1967,3 +2941,54 @@ impl CreativeWriterMcpServer {
         }
     }
 }
+
+/// A scene from the slugline and elements of a scene tool call.
+fn scene_from_args(args: &Value) -> Result<Scene, String> {
+    let slugline = args
+        .get("slugline")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let elements = match args.get("elements") {
+        Some(elements) => serde_json::from_value(elements.clone())
+            .map_err(|e| format!("Invalid scene elements: {e}"))?,
+        None => vec![],
+    };
+    Ok(Scene { slugline, elements })
+}
+
+/// Renders a screenplay chapter's scenes into its content and word count.
+fn render_scenes(chapter: &mut Chapter) {
+    chapter.content = screenplay::scenes_markdown(&chapter.scenes);
+    chapter.word_count = chapter.scenes.iter().map(|s| s.word_count()).sum();
+}
+
+fn scene_error(text: String) -> ToolResult {
+    ToolResult {
+        content: vec![ToolResultContent {
+            r#type: "text".to_string(),
+            text: Some(text),
+            ..Default::default()
+        }],
+        is_error: Some(true),
+    }
+}
+
+/// Result of a scene change, with the screenplay problems left in the scene.
+fn scene_result(message: String, issues: &[String]) -> ToolResult {
+    let mut text = message;
+    if !issues.is_empty() {
+        text.push_str("\n\nScreenplay issues to fix:\n");
+        for issue in issues {
+            text.push_str(&format!("- {}\n", issue));
+        }
+    }
+    ToolResult {
+        content: vec![ToolResultContent {
+            r#type: "text".to_string(),
+            text: Some(text),
+            ..Default::default()
+        }],
+        is_error: Some(false),
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Style guides of stories, and checks of chapter text against them.
+//!
+//! The checks are heuristics over narration, with dialogue in quotes left out:
+//! tense is judged from common auxiliary and speech verbs, and point of view
+//! from personal pronouns. Banned words and phrases are matched anywhere,
+//! ignoring case. Dialect notes are for the model and are not checked.
+
+use serde::{Deserialize, Serialize};
+
+use super::words;
+
+/// Share of tense markers in the wrong tense above which narration is flagged.
+const MAX_WRONG_TENSE_SHARE: f64 = 0.25;
+
+/// Pronouns of the wrong person tolerated in narration before it is flagged.
+const MAX_WRONG_PRONOUNS: usize = 2;
+
+/// Words of narration without a first or second person pronoun after which
+/// first or second person narration is flagged.
+const MIN_WORDS_FOR_PRONOUNS: usize = 200;
+
+const PAST_MARKERS: &[&str] = &[
+    "was", "were", "had", "did", "said", "went", "thought", "looked", "felt", "saw", "knew",
+];
+const PRESENT_MARKERS: &[&str] = &[
+    "is", "are", "am", "has", "does", "says", "goes", "thinks", "looks", "feels", "sees", "knows",
+];
+const FIRST_PERSON: &[&str] = &["i", "me", "my", "mine", "myself", "we", "us", "our", "ours"];
+const SECOND_PERSON: &[&str] = &["you", "your", "yours", "yourself", "yourselves"];
+
+/// Rules for the voice of a story, kept across sessions.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StyleGuide {
+    /// Tense of the narration, such as "past" or "present"
+    #[serde(default)]
+    pub tense: String,
+    /// Point of view, such as "first person" or "third person limited"
+    #[serde(default)]
+    pub pov: String,
+    /// Words and phrases that must not appear in the text
+    #[serde(default)]
+    pub banned_words: Vec<String>,
+    /// Notes on dialect, spelling and diction
+    #[serde(default)]
+    pub dialect_notes: String,
+}
+
+impl StyleGuide {
+    /// Whether no rules are set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Lists the rules as Markdown bullet points.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        if !self.tense.is_empty() {
+            md.push_str(&format!("- **Tense:** {}\n", self.tense));
+        }
+        if !self.pov.is_empty() {
+            md.push_str(&format!("- **Point of view:** {}\n", self.pov));
+        }
+        if !self.banned_words.is_empty() {
+            md.push_str(&format!(
+                "- **Banned words:** {}\n",
+                self.banned_words.join(", ")
+            ));
+        }
+        if !self.dialect_notes.is_empty() {
+            md.push_str(&format!("- **Dialect notes:** {}\n", self.dialect_notes));
+        }
+        md
+    }
+
+    /// Checks a text against the guide.
+    ///
+    /// # Arguments
+    /// * `text` - Text of a chapter
+    ///
+    /// # Returns
+    /// A description of each violation found
+    pub fn check(&self, text: &str) -> Vec<String> {
+        let mut issues = vec![];
+        let all_words = words(text);
+        let narration = words(&strip_dialogue(text));
+
+        for banned in self.banned_words.iter().filter(|b| !b.trim().is_empty()) {
+            let count = count_phrase(&all_words, &words(banned));
+            if count > 0 {
+                issues.push(format!("Banned word \"{banned}\" is used {}", times(count)));
+            }
+        }
+
+        let tense = self.tense.to_lowercase();
+        let (expected, wrong, wrong_markers) = if tense.contains("past") {
+            ("past", "present", PRESENT_MARKERS)
+        } else if tense.contains("present") {
+            ("present", "past", PAST_MARKERS)
+        } else {
+            ("", "", &[][..])
+        };
+        if !expected.is_empty() {
+            let right_markers = if expected == "past" {
+                PAST_MARKERS
+            } else {
+                PRESENT_MARKERS
+            };
+            let right = count_any(&narration, right_markers);
+            let off = count_any(&narration, wrong_markers);
+            if off > 0 && off as f64 / (right + off) as f64 > MAX_WRONG_TENSE_SHARE {
+                issues.push(format!(
+                    "Narration should be in the {expected} tense, but {off} of {} tense markers are {wrong} tense, such as \"{}\"",
+                    right + off,
+                    first_of(&narration, wrong_markers),
+                ));
+            }
+        }
+
+        let pov = self.pov.to_lowercase();
+        let first = count_any(&narration, FIRST_PERSON);
+        let second = count_any(&narration, SECOND_PERSON);
+        let long_enough = narration.len() >= MIN_WORDS_FOR_PRONOUNS;
+        if pov.contains("third") {
+            if first > MAX_WRONG_PRONOUNS {
+                issues.push(format!(
+                    "Narration should be in the third person, but uses first person pronouns {}, such as \"{}\"",
+                    times(first),
+                    first_of(&narration, FIRST_PERSON)
+                ));
+            }
+            if second > MAX_WRONG_PRONOUNS {
+                issues.push(format!(
+                    "Narration should be in the third person, but addresses \"you\" {}",
+                    times(second)
+                ));
+            }
+        } else if pov.contains("first") && first == 0 && long_enough {
+            issues.push(
+                "Narration should be in the first person, but has no first person pronouns"
+                    .to_string(),
+            );
+        } else if pov.contains("second") && second == 0 && long_enough {
+            issues.push(
+                "Narration should be in the second person, but never addresses \"you\"".to_string(),
+            );
+        }
+        issues
+    }
+}
+
+/// Removes text in double quotes, straight or curly, leaving the narration.
+fn strip_dialogue(text: &str) -> String {
+    let mut narration = String::with_capacity(text.len());
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '“' => quoted = true,
+            '”' => quoted = false,
+            _ if !quoted => narration.push(c),
+            _ => {}
+        }
+        if matches!(c, '"' | '“' | '”') {
+            // Keep the words on either side apart
+            narration.push(' ');
+        }
+    }
+    narration
+}
+
+/// Counts the occurrences of a phrase, given as words, in a list of words.
+fn count_phrase(words: &[String], phrase: &[String]) -> usize {
+    if phrase.is_empty() {
+        return 0;
+    }
+    words.windows(phrase.len()).filter(|w| w == &phrase).count()
+}
+
+/// Counts the words that are one of `markers`.
+fn count_any(words: &[String], markers: &[&str]) -> usize {
+    words
+        .iter()
+        .filter(|w| markers.contains(&w.as_str()))
+        .count()
+}
+
+/// The first word that is one of `markers`, for examples in reports.
+fn first_of<'a>(words: &'a [String], markers: &[&str]) -> &'a str {
+    words
+        .iter()
+        .find(|w| markers.contains(&w.as_str()))
+        .map_or("", |w| w.as_str())
+}
+
+/// Formats a count of occurrences, such as "once" or "3 times".
+fn times(count: usize) -> String {
+    if count == 1 {
+        "once".to_string()
+    } else {
+        format!("{count} times")
+    }
+}
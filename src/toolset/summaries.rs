@@ -0,0 +1,285 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Automatic summaries of story chapters.
+//!
+//! When a summary is written, a sketch of the chapter's text is kept with it:
+//! the smallest hashes of its word pairs. Comparing it with a sketch of the
+//! current text estimates how much of the chapter changed since, without
+//! keeping a copy of the old text. Chapters that changed enough, or have no
+//! summary, are summarized again by a background request to the model, and
+//! the summary and plot points are saved through the story's tools.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    core::stream_response,
+    llm::{ContentPart, LlmClient, Message},
+    mcp::host::MCPHost,
+    storage::{Storage, get_storage},
+};
+
+use super::story::{Chapter, Story};
+
+/// Hashes kept in a sketch of a chapter's text.
+const SKETCH_SIZE: usize = 64;
+
+/// Estimated share of word pairs a chapter must keep for its summary to
+/// stay current.
+const MIN_SIMILARITY: f64 = 0.8;
+
+/// Words of a chapter sent to be summarized; longer chapters are cut.
+const MAX_CHAPTER_WORDS: usize = 12_000;
+
+/// Instructions for summarizing a chapter.
+const SUMMARY_PROMPT: &str = "You summarize chapters of a story for its author's outline. \
+Reply with a JSON object only, with two fields: \"summary\", a few sentences on what happens \
+in the chapter, and \"plot_points\", a list of its key events, each a short sentence.";
+
+/// Whether summaries are being refreshed, so refreshes do not overlap.
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+/// Bumped whenever refreshed summaries are saved
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Sketches a text for [`similarity`]: the smallest hashes of its distinct
+/// pairs of consecutive words, ignoring case and punctuation.
+///
+/// # Arguments
+/// * `text` - Text of a chapter
+///
+/// # Returns
+/// Up to [`SKETCH_SIZE`] hashes, in ascending order
+pub fn sketch(text: &str) -> Vec<u64> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    let mut hashes: Vec<u64> = match words.len() {
+        0 => vec![],
+        1 => vec![fnv1a(&words[0])],
+        _ => words
+            .windows(2)
+            .map(|pair| fnv1a(&format!("{} {}", pair[0], pair[1])))
+            .collect(),
+    };
+    hashes.sort_unstable();
+    hashes.dedup();
+    hashes.truncate(SKETCH_SIZE);
+    hashes
+}
+
+/// Estimates the share of word pairs two texts have in common from their
+/// sketches, between 0 and 1.
+pub fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    // The smallest hashes of the union, and how many of them both texts have
+    let mut union: Vec<u64> = a.iter().chain(b).copied().collect();
+    union.sort_unstable();
+    union.dedup();
+    union.truncate(SKETCH_SIZE);
+    let shared = union
+        .iter()
+        .filter(|h| a.binary_search(h).is_ok() && b.binary_search(h).is_ok())
+        .count();
+    shared as f64 / union.len() as f64
+}
+
+/// 64-bit FNV-1a hash, stable across builds so sketches can be stored.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Whether a chapter's summary is missing or no longer matches its text.
+/// Summaries written before sketches were kept count as current.
+pub fn is_stale(chapter: &Chapter) -> bool {
+    if chapter.content.trim().is_empty() {
+        return false;
+    }
+    if chapter.summary.trim().is_empty() {
+        return true;
+    }
+    !chapter.summary_sketch.is_empty()
+        && similarity(&chapter.summary_sketch, &sketch(&chapter.content)) < MIN_SIMILARITY
+}
+
+/// Asks the model for a chapter's summary and plot points. Stop sequences,
+/// prefill and output constraints set on the client are not applied.
+///
+/// # Arguments
+/// * `client` - LLM client to use
+/// * `model` - Model to write the summary
+/// * `chapter` - The chapter
+///
+/// # Returns
+/// The summary and the plot points, or an error if the request fails or the
+/// reply cannot be read
+pub async fn summarize_chapter(
+    client: &LlmClient,
+    model: &str,
+    chapter: &Chapter,
+) -> anyhow::Result<(String, Vec<String>)> {
+    let client = client
+        .clone()
+        .with_stop(vec![])
+        .with_prefill(String::new())
+        .with_constraint(None);
+    let words: Vec<&str> = chapter.content.split_whitespace().collect();
+    let text = words[..words.len().min(MAX_CHAPTER_WORDS)].join(" ");
+    let prompt = [
+        Message::System {
+            content: SUMMARY_PROMPT.to_string(),
+        },
+        Message::User {
+            content: vec![ContentPart::Text {
+                text: format!("# {}\n\n{text}", chapter.title),
+            }],
+        },
+    ];
+    let (reply, _) = stream_response(&client, model, &prompt, &[], &mut ()).await?;
+    parse_summary(&reply)
+}
+
+/// Reads the summary and plot points from the model's reply, allowing for a
+/// code fence or text around the JSON object.
+fn parse_summary(reply: &str) -> anyhow::Result<(String, Vec<String>)> {
+    let start = reply.find('{');
+    let end = reply.rfind('}');
+    let (Some(start), Some(end)) = (start, end) else {
+        anyhow::bail!("The model did not reply with JSON: {reply}");
+    };
+    let value: Value = serde_json::from_str(&reply[start..=end])?;
+    let summary = value
+        .get("summary")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if summary.is_empty() {
+        anyhow::bail!("The model returned an empty summary");
+    }
+    let plot_points = value
+        .get("plot_points")
+        .and_then(|v| v.as_array())
+        .map(|points| {
+            points
+                .iter()
+                .filter_map(|p| p.as_str())
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok((summary, plot_points))
+}
+
+/// Summarizes the chapters of a story whose summaries are stale, saving each
+/// through the story's `update_chapter` tool, then saves the story in its chat.
+/// Chapters edited while their summary was written are left for next time.
+/// Does nothing if a refresh is already running.
+///
+/// # Arguments
+/// * `host` - MCP host of the story chat
+/// * `client` - LLM client to use
+/// * `model` - Model to write the summaries
+/// * `chat_id` - ID of the story chat, if it is saved
+///
+/// # Returns
+/// The number of chapters summarized
+pub async fn refresh_summaries(
+    host: Arc<MCPHost>,
+    client: LlmClient,
+    model: String,
+    chat_id: Option<u32>,
+) -> anyhow::Result<usize> {
+    if REFRESHING.swap(true, Ordering::SeqCst) {
+        return Ok(0);
+    }
+    let result = refresh(&host, &client, &model, chat_id).await;
+    REFRESHING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn refresh(
+    host: &MCPHost,
+    client: &LlmClient,
+    model: &str,
+    chat_id: Option<u32>,
+) -> anyhow::Result<usize> {
+    let story = load_story(host).await?;
+    let stale: Vec<(usize, Chapter)> = story
+        .chapters
+        .into_iter()
+        .enumerate()
+        .filter(|(_, c)| is_stale(c))
+        .collect();
+    let mut summarized = 0;
+    for (index, chapter) in stale {
+        let (summary, plot_points) = summarize_chapter(client, model, &chapter).await?;
+        // Skip the chapter if it was edited, moved or deleted in the meantime
+        let current = load_story(host).await?;
+        if current.chapters.get(index).map(|c| &c.content) != Some(&chapter.content) {
+            continue;
+        }
+        let tr = host
+            .tool_call(
+                "creative_writer",
+                "update_chapter",
+                json!({
+                    "chapter_index": index,
+                    "summary": summary,
+                    "plot_points": plot_points,
+                }),
+            )
+            .await?;
+        if tr.is_error == Some(true) {
+            let text: String = tr.content.into_iter().filter_map(|c| c.text).collect();
+            anyhow::bail!(
+                "Could not save the summary of chapter {}: {text}",
+                index + 1
+            );
+        }
+        summarized += 1;
+    }
+    if summarized > 0 {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+
+    if summarized > 0
+        && let Some(id) = chat_id
+    {
+        let storage = get_storage().await?;
+        if let Some(mut chat) = storage.get_chat(id).await? {
+            chat.value = serde_json::to_value(load_story(host).await?)?;
+            storage.save_chat(&chat).await?;
+        }
+    }
+    Ok(summarized)
+}
+
+/// Counts the refreshes that saved summaries, so views of a story can tell
+/// when to reload it.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// The story as its MCP server holds it.
+pub async fn load_story(host: &MCPHost) -> anyhow::Result<Story> {
+    let tr = host
+        .tool_call(
+            "creative_writer",
+            "export_story",
+            json!({"format": "structured"}),
+        )
+        .await?;
+    let text: String = tr.content.into_iter().filter_map(|c| c.text).collect();
+    Ok(serde_json::from_str(&text)?)
+}
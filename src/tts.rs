@@ -0,0 +1,179 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Text to speech, for listening to stories.
+//!
+//! Text is spoken one sentence at a time, so views can highlight the sentence
+//! being read and stop or resume between sentences. Speech goes through a
+//! [`SpeechBackend`]; the one available everywhere is the Web Speech API of
+//! the browser or desktop webview, which uses the system's voices.
+
+use dioxus::prelude::*;
+use pulldown_cmark::{Event, Parser, TagEnd};
+
+/// Speech rates offered, as multiples of the voice's normal rate.
+pub const RATES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// Words ending in a period that do not end a sentence.
+const ABBREVIATIONS: &[&str] = &["mr", "mrs", "ms", "dr", "st", "prof", "mt", "vs", "etc"];
+
+/// Speaks the text sent at the rate sent, and reports "end" when done,
+/// "stopped" if cancelled, or an error.
+const SPEAK_JS: &str = r#"
+const text = await dioxus.recv();
+const rate = await dioxus.recv();
+if (!("speechSynthesis" in window)) {
+    dioxus.send("Speech is not available in this browser");
+    return;
+}
+const utterance = new SpeechSynthesisUtterance(text);
+utterance.rate = rate;
+utterance.onend = () => dioxus.send("end");
+utterance.onerror = (e) => dioxus.send(
+    e.error === "interrupted" || e.error === "canceled" ? "stopped" : "Speech failed: " + e.error
+);
+window.speechSynthesis.speak(utterance);
+"#;
+
+/// Cancels what is being spoken.
+const STOP_JS: &str = r#"
+if ("speechSynthesis" in window) {
+    window.speechSynthesis.cancel();
+}
+"#;
+
+/// A way of speaking text aloud.
+#[async_trait::async_trait(?Send)]
+pub trait SpeechBackend {
+    /// Speaks text and waits until it is spoken.
+    ///
+    /// # Arguments
+    /// * `text` - Text to speak, usually a sentence
+    /// * `rate` - Speech rate, 1.0 being the voice's normal rate
+    ///
+    /// # Returns
+    /// `true` if the text was spoken to the end, `false` if it was stopped,
+    /// or an error if speech is unavailable or failed
+    async fn speak(&self, text: &str, rate: f32) -> anyhow::Result<bool>;
+
+    /// Stops speaking at once.
+    fn stop(&self);
+}
+
+/// Speech with the system's voices through the Web Speech API.
+pub struct WebSpeech;
+
+#[async_trait::async_trait(?Send)]
+impl SpeechBackend for WebSpeech {
+    async fn speak(&self, text: &str, rate: f32) -> anyhow::Result<bool> {
+        let mut eval = document::eval(SPEAK_JS);
+        eval.send(text)
+            .and_then(|_| eval.send(rate))
+            .map_err(|e| anyhow::anyhow!("Could not start speech: {e:?}"))?;
+        let status: String = eval
+            .recv()
+            .await
+            .map_err(|e| anyhow::anyhow!("Speech was interrupted: {e:?}"))?;
+        match status.as_str() {
+            "end" => Ok(true),
+            "stopped" => Ok(false),
+            _ => anyhow::bail!(status),
+        }
+    }
+
+    fn stop(&self) {
+        let _ = document::eval(STOP_JS);
+    }
+}
+
+/// The speech backend to use.
+pub fn backend() -> Box<dyn SpeechBackend> {
+    Box::new(WebSpeech)
+}
+
+/// The text of Markdown to be spoken: its paragraphs, headings and list
+/// items, each split into sentences, without the formatting.
+///
+/// # Arguments
+/// * `markdown` - Markdown text, such as a chapter
+///
+/// # Returns
+/// The sentences of each block, leaving out empty blocks
+pub fn speakable_paragraphs(markdown: &str) -> Vec<Vec<String>> {
+    let mut paragraphs = vec![];
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::CodeBlock,
+            ) => {
+                let sentences = sentences(&text);
+                if !sentences.is_empty() {
+                    paragraphs.push(sentences);
+                }
+                text.clear();
+            }
+            _ => {}
+        }
+    }
+    let sentences = sentences(&text);
+    if !sentences.is_empty() {
+        paragraphs.push(sentences);
+    }
+    paragraphs
+}
+
+/// Splits text into sentences at `.`, `!`, `?` and `…` followed by a space,
+/// keeping closing quotes and brackets with their sentence.
+pub fn sentences(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut sentences = vec![];
+    let mut current = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        current.push(c);
+        i += 1;
+        if !matches!(c, '.' | '!' | '?' | '…') {
+            continue;
+        }
+        while i < chars.len()
+            && matches!(
+                chars[i],
+                '.' | '!' | '?' | '…' | '"' | '\'' | '”' | '’' | ')' | ']'
+            )
+        {
+            current.push(chars[i]);
+            i += 1;
+        }
+        if i < chars.len() && !chars[i].is_whitespace() {
+            continue;
+        }
+        if c == '.' && ends_with_abbreviation(&current) {
+            continue;
+        }
+        push_sentence(&mut sentences, &current);
+        current.clear();
+    }
+    push_sentence(&mut sentences, &current);
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<String>, text: &str) {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.is_empty() {
+        sentences.push(text);
+    }
+}
+
+/// Whether text ends with an abbreviation such as "Mr." or an initial.
+fn ends_with_abbreviation(text: &str) -> bool {
+    let word = text
+        .trim_end_matches('.')
+        .rsplit(|c: char| !c.is_alphabetic())
+        .next()
+        .unwrap_or_default();
+    let initial = word.chars().count() == 1 && word.chars().all(|c| c.is_uppercase());
+    initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
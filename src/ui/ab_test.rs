@@ -0,0 +1,293 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+
+use crate::{
+    AppSettings,
+    ab_test::{self, AbTally},
+    app_settings::{AbTrial, AbVariant, AbVote, Toolsets},
+    core::now_millis,
+    mcp::host::MCPHost,
+    toolset::registry,
+};
+
+const INPUT_STYLE: &str = "
+    width: 100%;
+    padding: 0.5rem;
+    border: 1px solid #ddd;
+    border-radius: 3px;
+    box-sizing: border-box;
+    margin-bottom: 0.5rem;
+";
+
+/// Page for A/B testing system prompts: one user prompt is run under two
+/// variants, the outputs are shown side by side, and the user's vote is
+/// recorded. Votes are tallied by the variants' labels.
+///
+/// The variants start as those of the last recorded trial, so a prompt can be
+/// tried again after editing one side.
+#[component]
+pub fn AbTest() -> Element {
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let mut variants: Signal<Option<[AbVariant; 2]>> = use_signal(|| None);
+    let mut prompt = use_signal(String::new);
+    let mut outputs: Signal<Option<[Result<String, String>; 2]>> = use_signal(|| None);
+    let mut running = use_signal(|| false);
+    let mut voted = use_signal(|| false);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut trials = use_resource(move || async move {
+        ab_test::list_trials().await.unwrap_or_else(|e| {
+            error.set(Some(format!("Could not load results: {e}")));
+            vec![]
+        })
+    });
+
+    // Start from the last trial's variants, or each toolset's own prompt
+    use_effect(move || {
+        if variants.peek().is_some() {
+            return;
+        }
+        let Some(loaded) = trials.read().clone() else {
+            return;
+        };
+        let start = match loaded.first() {
+            Some(last) => last.variants.clone(),
+            None => [
+                default_variant("A", Toolsets::Chat, host()),
+                default_variant("B", Toolsets::Chat, host()),
+            ],
+        };
+        variants.set(Some(start));
+    });
+
+    let settings = consume_context::<Signal<Option<AppSettings>>>();
+    let configured = settings
+        .read()
+        .as_ref()
+        .is_some_and(|s| s.provider.get_model().is_some());
+
+    let run = move |_| async move {
+        let (Some(settings), Some(current)) = (settings(), variants()) else {
+            return;
+        };
+        let Some(model) = settings.provider.get_model() else {
+            return;
+        };
+        running.set(true);
+        voted.set(false);
+        error.set(None);
+        outputs.set(None);
+        let client = settings.client();
+        let results = ab_test::run_trial(
+            &client,
+            &model,
+            host(),
+            &current,
+            &prompt(),
+            settings.max_tool_failures,
+        )
+        .await;
+        outputs.set(Some(results));
+        running.set(false);
+    };
+
+    let vote = move |vote: AbVote| async move {
+        let (Some(settings), Some(current), Some([Ok(a), Ok(b)])) =
+            (settings(), variants(), outputs())
+        else {
+            return;
+        };
+        let trial = AbTrial {
+            id: now_millis(),
+            model: settings.provider.get_model().unwrap_or_default(),
+            prompt: prompt(),
+            variants: current,
+            outputs: [a, b],
+            vote,
+        };
+        match ab_test::record_trial(trial).await {
+            Ok(()) => {
+                voted.set(true);
+                trials.restart();
+            }
+            Err(e) => error.set(Some(format!("Could not record the vote: {e}"))),
+        }
+    };
+
+    let clear = move |_| async move {
+        match ab_test::clear_trials().await {
+            Ok(()) => trials.restart(),
+            Err(e) => error.set(Some(format!("Could not clear results: {e}"))),
+        }
+    };
+
+    let recorded = trials().unwrap_or_default();
+    let tallies = ab_test::tally(&recorded);
+    let can_vote = !voted() && matches!(outputs(), Some([Ok(_), Ok(_)]));
+
+    rsx! {
+        div { style: "padding: 3em 1rem 1rem 1rem; height: 100%; overflow-y: auto; box-sizing: border-box;",
+            h3 { "Prompt A/B test" }
+            p { style: "color: #666; margin-top: 0;",
+                "Run one prompt with two system prompts or toolsets, then vote for the better answer."
+            }
+            if !configured {
+                p { style: "color: #dc3545;", "Choose a provider and model in Settings first." }
+            }
+            if let Some([a, b]) = variants() {
+                div { style: "display: flex; gap: 1em; flex-wrap: wrap;",
+                    VariantEditor {
+                        variant: a,
+                        on_change: move |v: AbVariant| {
+                            variants.with_mut(|vs| if let Some(vs) = vs { vs[0] = v });
+                        },
+                    }
+                    VariantEditor {
+                        variant: b,
+                        on_change: move |v: AbVariant| {
+                            variants.with_mut(|vs| if let Some(vs) = vs { vs[1] = v });
+                        },
+                    }
+                }
+            }
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;",
+                "User prompt"
+            }
+            textarea {
+                style: "{INPUT_STYLE} min-height: 4rem;",
+                value: prompt(),
+                oninput: move |e| prompt.set(e.value()),
+            }
+            button {
+                disabled: running() || !configured || prompt().trim().is_empty(),
+                onclick: run,
+                if running() { "Running both…" } else { "Run A and B" }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545; margin-top: 0.5em;", "{e}" }
+            }
+            if let (Some(results), Some(current)) = (outputs(), variants()) {
+                div { style: "display: flex; gap: 0.5em; align-items: stretch; margin-top: 1em;",
+                    for (i , result) in results.into_iter().enumerate() {
+                        div {
+                            key: "{i}",
+                            class: "message ai-message",
+                            style: "flex: 1; min-width: 0;",
+                            div { style: "font-weight: bold; margin-bottom: 0.5em;",
+                                "{current[i].label}"
+                            }
+                            match result {
+                                Ok(text) => crate::md2rsx::markdown_to_rsx(&text),
+                                Err(e) => rsx! {
+                                    div { style: "color: #dc3545; font-size: 0.9em;", "✗ {e}" }
+                                },
+                            }
+                        }
+                    }
+                }
+                if can_vote {
+                    div { style: "display: flex; gap: 0.5em; justify-content: center; margin-top: 0.5em;",
+                        button { onclick: move |_| vote(AbVote::A), "{current[0].label} is better" }
+                        button { onclick: move |_| vote(AbVote::Tie), "Tie" }
+                        button { onclick: move |_| vote(AbVote::B), "{current[1].label} is better" }
+                    }
+                } else if voted() {
+                    div { style: "text-align: center; color: #666; margin-top: 0.5em;",
+                        "Vote recorded."
+                    }
+                }
+            }
+            h4 { style: "margin-top: 2em;", "Results" }
+            if tallies.is_empty() {
+                p { style: "color: #666; font-style: italic;", "No votes yet" }
+            }
+            for t in tallies {
+                TallyRow { key: "{t.labels[0]}-{t.labels[1]}", tally: t }
+            }
+            if !recorded.is_empty() {
+                button { style: "margin-top: 0.5em;", onclick: clear, "Clear results" }
+            }
+        }
+    }
+}
+
+/// Editor for one side of the test: its label, toolset and system prompt.
+///
+/// # Arguments
+/// * `variant` - The variant
+/// * `on_change` - Called with the edited variant
+#[component]
+fn VariantEditor(variant: AbVariant, on_change: EventHandler<AbVariant>) -> Element {
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let label = variant.label.clone();
+    rsx! {
+        div { style: "flex: 1; min-width: 18em;",
+            input {
+                style: "{INPUT_STYLE} font-weight: bold;",
+                placeholder: "Label",
+                value: "{variant.label}",
+                oninput: {
+                    let variant = variant.clone();
+                    move |e: Event<FormData>| on_change(AbVariant { label: e.value(), ..variant.clone() })
+                },
+            }
+            select {
+                style: INPUT_STYLE,
+                title: "Changing the toolset resets the system prompt to the toolset's own",
+                onchange: move |e: Event<FormData>| {
+                    let toolset = e.value().parse().unwrap_or(Toolsets::Chat);
+                    on_change(default_variant(&label, toolset, host()));
+                },
+                for entry in registry::entries() {
+                    option {
+                        value: "{entry.id}",
+                        selected: variant.toolset == entry.id,
+                        "{entry.icon} {entry.name}"
+                    }
+                }
+            }
+            textarea {
+                style: "{INPUT_STYLE} min-height: 10rem; font-family: monospace; font-size: 0.85em;",
+                value: "{variant.system_prompt}",
+                oninput: {
+                    let variant = variant.clone();
+                    move |e: Event<FormData>| on_change(AbVariant { system_prompt: e.value(), ..variant.clone() })
+                },
+            }
+        }
+    }
+}
+
+/// Votes for one pair of variants, with a bar showing the share of each.
+#[component]
+fn TallyRow(tally: AbTally) -> Element {
+    let total = (tally.wins[0] + tally.wins[1] + tally.ties).max(1);
+    let share = |n: usize| n * 100 / total;
+    let (a, b) = (share(tally.wins[0]), share(tally.wins[1]));
+    rsx! {
+        div { style: "margin-bottom: 0.75em;",
+            div { style: "display: flex; justify-content: space-between; font-size: 0.9em;",
+                span { "{tally.labels[0]}: {tally.wins[0]}" }
+                span { style: "color: #666;", "ties: {tally.ties}" }
+                span { "{tally.labels[1]}: {tally.wins[1]}" }
+            }
+            div { style: "display: flex; height: 0.5em; border-radius: 3px; overflow: hidden; background: #dee2e6;",
+                div { style: "width: {a}%; background: #0d6efd;" }
+                div { style: "flex: 1;" }
+                div { style: "width: {b}%; background: #fd7e14;" }
+            }
+        }
+    }
+}
+
+/// A variant using a toolset's own system prompt.
+fn default_variant(label: &str, toolset: Toolsets, host: Arc<MCPHost>) -> AbVariant {
+    let (ts, _) = registry::create_new(&toolset, host);
+    AbVariant {
+        label: label.to_string(),
+        toolset,
+        system_prompt: ts.get_system_prompt(),
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::jobs::{self, Job, JobStatus};
+
+/// Toolbar indicator of background jobs: a count while jobs run, and a
+/// warning after one fails. Hovering lists the jobs; clicking a warning
+/// dismisses it.
+#[component]
+pub fn ActivityIndicator() -> Element {
+    let mut list: Signal<Vec<Job>> = use_signal(jobs::jobs);
+    use_future(move || async move {
+        let mut changes = jobs::subscribe();
+        while changes.changed().await.is_ok() {
+            list.set(jobs::jobs());
+        }
+    });
+
+    let list = list();
+    if list.is_empty() {
+        return rsx! {};
+    }
+    let running = list
+        .iter()
+        .filter(|j| j.status == JobStatus::Running)
+        .count();
+    let failed = list.len() - running;
+    let details = list
+        .iter()
+        .map(|j| match &j.status {
+            JobStatus::Failed(e) => format!("⚠ {}: {e}", j.label),
+            _ => format!("… {}", j.label),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    rsx! {
+        span {
+            style: "display: inline-flex; align-items: center; gap: 0.5em; margin-right: 1em; font-size: 0.85em; color: #666; cursor: default;",
+            title: "{details}",
+            if running > 0 {
+                span { "⟳ {running} background job" if running > 1 { "s" } }
+            }
+            if failed > 0 {
+                span {
+                    style: "color: #dc3545; cursor: pointer;",
+                    title: "{details}\n\nClick to dismiss",
+                    onclick: move |_| jobs::clear_finished(),
+                    "⚠ {failed} failed"
+                }
+            }
+        }
+    }
+}
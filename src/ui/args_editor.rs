@@ -0,0 +1,255 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Editor of the arguments an MCP server is started with.
+//!
+//! Each argument is a chip, kept exactly as entered, so arguments with spaces
+//! or quotes reach the server unchanged. Typed text becomes one argument;
+//! a pasted command line is split the way a shell would split it.
+
+use dioxus::prelude::*;
+
+/// Splits a command line into arguments the way a POSIX shell would, without
+/// expanding anything. Single quotes keep their content as it is; double
+/// quotes keep it too, except for `\"`, `\\`, `\$` and `` \` ``. Outside
+/// quotes, a backslash escapes the character after it if it is a space, a
+/// quote or a backslash, and is kept otherwise, so Windows paths survive.
+/// An unterminated quote runs to the end of the text.
+///
+/// # Returns
+/// The arguments, in order
+pub fn split_args(text: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    // Whether an argument was started, so that `''` gives an empty one
+    let mut started = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                started = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                started = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.peek() {
+                            Some(&next @ ('"' | '\\' | '$' | '`')) => {
+                                current.push(next);
+                                chars.next();
+                            }
+                            _ => current.push('\\'),
+                        },
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                started = true;
+                match chars.peek() {
+                    Some(&next) if next.is_whitespace() || matches!(next, '\'' | '"' | '\\') => {
+                        current.push(next);
+                        chars.next();
+                    }
+                    _ => current.push('\\'),
+                }
+            }
+            c if c.is_whitespace() => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            _ => {
+                started = true;
+                current.push(c);
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// Quotes an argument for display, so that [`split_args`] gives it back.
+/// Arguments without spaces, quotes or backslashes are shown as they are.
+pub fn quote_arg(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '\\'));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Arguments joined into one line for display, quoted where needed.
+pub fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| quote_arg(a))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// List editor of a server's arguments: chips that can be removed or moved,
+/// and an input adding one argument per Enter. Pasting a command line adds
+/// each of its arguments.
+///
+/// # Arguments
+/// * `args` - The arguments, in order
+/// * `on_change` - Called with the new arguments
+#[component]
+pub fn ArgsEditor(args: Vec<String>, on_change: Callback<Vec<String>, ()>) -> Element {
+    let mut new_arg = use_signal(String::new);
+    // Set by a paste, for the input that follows it to be split
+    let mut pasted = use_signal(|| false);
+    let count = args.len();
+
+    let current = args.clone();
+    let mut add = move |added: Vec<String>| {
+        let mut args = current.clone();
+        args.extend(added);
+        on_change(args);
+        new_arg.set(String::new());
+    };
+    let current = args.clone();
+    let remove = move |index: usize| {
+        let mut args = current.clone();
+        args.remove(index);
+        on_change(args);
+    };
+    let current = args.clone();
+    let swap = move |a: usize, b: usize| {
+        let mut args = current.clone();
+        args.swap(a, b);
+        on_change(args);
+    };
+
+    let chip_button = "border: none; background: none; cursor: pointer; padding: 0 0.15rem; \
+                       color: #666;";
+
+    rsx! {
+        div {
+            if !args.is_empty() {
+                div { style: "display: flex; flex-wrap: wrap; gap: 0.25rem; margin-bottom: 0.25rem;",
+                    for (i , arg) in args.into_iter().enumerate() {
+                        span {
+                            key: "{i}-{arg}",
+                            style: "
+                                display: inline-flex;
+                                align-items: center;
+                                padding: 0.125rem 0.25rem;
+                                background: #e9ecef;
+                                border-radius: 3px;
+                                font-family: monospace;
+                                font-size: 0.9em;
+                            ",
+                            button {
+                                style: chip_button,
+                                title: "Move earlier",
+                                disabled: i == 0,
+                                onclick: {
+                                    let swap = swap.clone();
+                                    move |_| swap(i - 1, i)
+                                },
+                                "‹"
+                            }
+                            span {
+                                style: "white-space: pre;",
+                                title: "One argument, passed exactly as shown",
+                                if arg.is_empty() {
+                                    span { style: "color: #888;", "(empty)" }
+                                } else {
+                                    "{arg}"
+                                }
+                            }
+                            button {
+                                style: chip_button,
+                                title: "Move later",
+                                disabled: i + 1 == count,
+                                onclick: {
+                                    let swap = swap.clone();
+                                    move |_| swap(i, i + 1)
+                                },
+                                "›"
+                            }
+                            button {
+                                style: chip_button,
+                                title: "Remove",
+                                onclick: {
+                                    let remove = remove.clone();
+                                    move |_| remove(i)
+                                },
+                                "×"
+                            }
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 0.5rem;",
+                input {
+                    style: "
+                        flex: 1;
+                        padding: 0.5rem;
+                        border: 1px solid #ddd;
+                        border-radius: 3px;
+                        box-sizing: border-box;
+                        font-family: monospace;
+                    ",
+                    value: new_arg(),
+                    placeholder: "One argument, then Enter; or paste a whole command line",
+                    onpaste: move |_| pasted.set(true),
+                    oninput: {
+                        let mut add = add.clone();
+                        move |e: Event<FormData>| {
+                            let text = e.value();
+                            // A pasted line of several arguments is split into them
+                            if pasted() {
+                                pasted.set(false);
+                                let split = split_args(&text);
+                                if split.len() > 1 {
+                                    add(split);
+                                    return;
+                                }
+                            }
+                            new_arg.set(text);
+                        }
+                    },
+                    onkeydown: {
+                        let mut add = add.clone();
+                        move |e: Event<KeyboardData>| {
+                            if e.key() == Key::Enter && !new_arg.read().is_empty() {
+                                e.prevent_default();
+                                add(vec![new_arg()]);
+                            }
+                        }
+                    },
+                }
+                button {
+                    style: "
+                        background: #28a745;
+                        color: white;
+                        border: none;
+                        padding: 0.375rem 0.75rem;
+                        border-radius: 3px;
+                        cursor: pointer;
+                        font-size: 0.9em;
+                    ",
+                    title: "Add the text as one argument, spaces included",
+                    disabled: new_arg.read().is_empty(),
+                    onclick: move |_| add(vec![new_arg()]),
+                    "Add"
+                }
+            }
+        }
+    }
+}
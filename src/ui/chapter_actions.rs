@@ -0,0 +1,175 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::toolset::{
+    story::{Chapter, Story},
+    summaries,
+};
+
+/// Words of a chapter's ending quoted when asking to continue it.
+const CONTINUE_CONTEXT_WORDS: usize = 150;
+
+/// Things the model can be asked to do with a chapter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChapterAction {
+    Rewrite,
+    Expand,
+    Summarize,
+    Continue,
+}
+
+impl ChapterAction {
+    const ALL: [ChapterAction; 4] = [
+        ChapterAction::Rewrite,
+        ChapterAction::Expand,
+        ChapterAction::Summarize,
+        ChapterAction::Continue,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ChapterAction::Rewrite => "Rewrite",
+            ChapterAction::Expand => "Expand",
+            ChapterAction::Summarize => "Summarize",
+            ChapterAction::Continue => "Continue",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ChapterAction::Rewrite => "Ask the model to rewrite this chapter",
+            ChapterAction::Expand => "Ask the model to add detail and scenes to this chapter",
+            ChapterAction::Summarize => "Ask the model to write this chapter's summary",
+            ChapterAction::Continue => "Ask the model to write on from where this chapter ends",
+        }
+    }
+
+    /// Composes the user message asking for the action.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the chapter in the story
+    /// * `chapter` - The chapter
+    ///
+    /// # Returns
+    /// The message, naming the chapter and the tool to change it with
+    pub fn prompt(&self, index: usize, chapter: &Chapter) -> String {
+        let name = format!(
+            "chapter {} (\"{}\", chapter_index {index})",
+            index + 1,
+            chapter.title
+        );
+        let mut prompt = match self {
+            ChapterAction::Rewrite => format!(
+                "Rewrite {name}. Keep its events, characters and plot points, but improve \
+                 the prose, pacing and dialogue. Replace its content with update_chapter."
+            ),
+            ChapterAction::Expand => format!(
+                "Expand {name}. Add description, dialogue and scenes that deepen what \
+                 happens, without changing its events. Replace its content with update_chapter."
+            ),
+            ChapterAction::Summarize => format!(
+                "Summarize {name} in a few sentences, and list its key plot points. \
+                 Save them with update_chapter, leaving the content as it is."
+            ),
+            ChapterAction::Continue => format!(
+                "Continue {name} from where it ends, in the same voice, and carry the \
+                 story forward. Add the new text with append_to_chapter."
+            ),
+        };
+        if !chapter.summary.is_empty() && *self != ChapterAction::Summarize {
+            prompt.push_str(&format!("\n\nThe chapter's summary: {}", chapter.summary));
+        }
+        if *self == ChapterAction::Continue {
+            let ending = last_words(&chapter.content, CONTINUE_CONTEXT_WORDS);
+            if !ending.is_empty() {
+                prompt.push_str(&format!("\n\nThe chapter ends:\n\n> …{ending}"));
+            }
+        }
+        prompt
+    }
+}
+
+/// Chapters of a story with buttons asking the model to rewrite, expand,
+/// summarize or continue each one. Chapters whose summary is missing or out
+/// of date are marked, and can be summarized in the background.
+///
+/// # Arguments
+/// * `story` - The story
+/// * `disabled` - Whether the model is busy, so no action can be started
+/// * `on_action` - Called with the message asking for the action
+/// * `on_update_summaries` - Called to summarize the marked chapters
+#[component]
+pub fn ChapterActions(
+    story: Story,
+    disabled: bool,
+    on_action: Callback<String, ()>,
+    on_update_summaries: Callback<(), ()>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    if story.chapters.is_empty() {
+        return rsx! {};
+    }
+    let stale = story
+        .chapters
+        .iter()
+        .filter(|c| summaries::is_stale(c))
+        .count();
+
+    rsx! {
+        div { style: "margin: 0.5em 0; font-size: 0.9em; border-bottom: 1px solid #dee2e6; padding-bottom: 0.5em;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Chapters ({story.chapters.len()})"
+            }
+            if open() {
+                if stale > 0 {
+                    div { style: "margin-top: 0.25em;",
+                        button {
+                            disabled,
+                            title: "Summarize the marked chapters in the background",
+                            onclick: move |_| on_update_summaries(()),
+                            "Update summaries ({stale})"
+                        }
+                    }
+                }
+                for (i , chapter) in story.chapters.iter().enumerate() {
+                    div { key: "{i}", style: "padding: 0.25em 0;",
+                        div {
+                            "{i + 1}. {chapter.title}"
+                            if summaries::is_stale(chapter) {
+                                span {
+                                    style: "margin-left: 0.5em; color: #b8860b; font-size: 0.85em;",
+                                    title: if chapter.summary.is_empty() { "The chapter has no summary yet" } else { "The chapter changed since it was summarized" },
+                                    if chapter.summary.is_empty() { "no summary" } else { "summary out of date" }
+                                }
+                            }
+                        }
+                        div { style: "display: flex; flex-wrap: wrap; gap: 0.25em; margin-top: 0.25em;",
+                            for action in ChapterAction::ALL {
+                                button {
+                                    key: "{action.label()}",
+                                    disabled,
+                                    title: action.description(),
+                                    onclick: {
+                                        let prompt = action.prompt(i, chapter);
+                                        move |_| on_action(prompt.clone())
+                                    },
+                                    "{action.label()}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The last `n` words of a text, joined by single spaces.
+fn last_words(text: &str, n: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words[words.len().saturating_sub(n)..].join(" ")
+}
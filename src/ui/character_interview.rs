@@ -0,0 +1,167 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{
+    llm::{ContentPart, Message},
+    toolset::{character_chat::Interview, story::Story},
+};
+
+/// Side conversation with a character of the story, for the story side panel:
+/// pick a character to talk to them in their voice, then keep the transcript
+/// as a note on the character or end the interview.
+///
+/// # Arguments
+/// * `story` - The story
+/// * `interview` - The interview going on, if any
+/// * `waiting` - Whether the character is answering
+/// * `disabled` - Whether no model is available to play the character
+/// * `on_start` - Called with the name of the character to interview
+/// * `on_ask` - Called with what the user says to the character
+/// * `on_save` - Called to keep the transcript as a character note and end
+/// * `on_close` - Called to end the interview without keeping it
+#[component]
+pub fn CharacterInterview(
+    story: Story,
+    interview: Option<Interview>,
+    waiting: bool,
+    disabled: bool,
+    on_start: Callback<String, ()>,
+    on_ask: Callback<String, ()>,
+    on_save: Callback<(), ()>,
+    on_close: Callback<(), ()>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let mut picked = use_signal(String::new);
+    let mut question = use_signal(String::new);
+    if story.characters.is_empty() {
+        return rsx! {};
+    }
+    let mut names: Vec<&String> = story.characters.keys().collect();
+    names.sort();
+    let selected = if story.characters.contains_key(&*picked.read()) {
+        picked()
+    } else {
+        names[0].clone()
+    };
+
+    let mut ask = move || {
+        let text = question.read().trim().to_string();
+        if text.is_empty() || waiting {
+            return;
+        }
+        question.set(String::new());
+        on_ask(text);
+    };
+
+    rsx! {
+        div { style: "margin: 0.5em 0; font-size: 0.9em; border-bottom: 1px solid #dee2e6; padding-bottom: 0.5em;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Interview a character"
+            }
+            if open() {
+                match interview {
+                    None => rsx! {
+                        div { style: "display: flex; gap: 0.25em; margin-top: 0.25em;",
+                            select {
+                                value: "{selected}",
+                                onchange: move |e| picked.set(e.value()),
+                                for name in names {
+                                    option { key: "{name}", value: "{name}", "{name}" }
+                                }
+                            }
+                            button {
+                                disabled,
+                                title: "Talk to the character, played by the model from their record and chapters",
+                                onclick: {
+                                    let name = selected.clone();
+                                    move |_| on_start(name.clone())
+                                },
+                                "Interview"
+                            }
+                        }
+                    },
+                    Some(interview) => rsx! {
+                        div { style: "margin-top: 0.25em; max-height: 20em; overflow-y: auto;",
+                            if interview.is_empty() {
+                                div { style: "color: #888;",
+                                    "Ask {interview.character} anything: about their past, what they want, what they think of the others."
+                                }
+                            }
+                            for (i , (speaker , text)) in lines(&interview).into_iter().enumerate() {
+                                div { key: "{i}", style: "margin: 0.25em 0; white-space: pre-wrap;",
+                                    strong { "{speaker}: " }
+                                    "{text}"
+                                }
+                            }
+                            if waiting {
+                                div { style: "color: #888;", "{interview.character} is answering…" }
+                            }
+                        }
+                        div { style: "display: flex; gap: 0.25em; margin-top: 0.25em;",
+                            input {
+                                style: "flex: 1; min-width: 0;",
+                                placeholder: "Ask {interview.character}…",
+                                value: question(),
+                                oninput: move |e| question.set(e.value()),
+                                onkeydown: move |e: Event<KeyboardData>| {
+                                    if e.data().key() == Key::Enter {
+                                        e.prevent_default();
+                                        ask();
+                                    }
+                                },
+                            }
+                            button {
+                                disabled: waiting || disabled,
+                                onclick: move |_| ask(),
+                                "Ask"
+                            }
+                        }
+                        div { style: "display: flex; gap: 0.25em; margin-top: 0.25em;",
+                            button {
+                                disabled: waiting || interview.is_empty(),
+                                title: "Keep the transcript as a note on the character, and end the interview",
+                                onclick: move |_| on_save(()),
+                                "Save as note"
+                            }
+                            button {
+                                title: "End the interview without keeping it",
+                                onclick: move |_| on_close(()),
+                                "End"
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// What was said in an interview, as speaker and text.
+fn lines(interview: &Interview) -> Vec<(String, String)> {
+    interview
+        .messages
+        .iter()
+        .filter_map(|m| match m {
+            Message::User { content } => Some((
+                "You".to_string(),
+                content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentPart::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            Message::Assistant {
+                content: Some(text),
+                ..
+            } => Some((interview.character.clone(), text.clone())),
+            _ => None,
+        })
+        .collect()
+}
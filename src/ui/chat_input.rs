@@ -2,63 +2,583 @@
 
 use std::rc::Rc;
 
-use dioxus::prelude::*;
+use dioxus::{html::FileEngine, logger::tracing::warn, prelude::*};
+
+use crate::{
+    app_settings::AppSettings,
+    core::{MEDIA_TOKENS, estimate_tokens},
+    llm::{ContentPart, InputAudio},
+    toolset::SlashCommand,
+    ui::{history_search::HistorySearch, nav_guard::use_guarded_draft},
+};
 
 const SEND_ICON: Asset = asset!("/assets/send.png");
 
+/// Largest audio file that can be attached, in bytes.
+const MAX_AUDIO_BYTES: u64 = 20 * 1024 * 1024;
+
+/// ID of the message text area, for the paste handler.
+const INPUT_ID: &str = "chat-input";
+
+/// Intercepts pastes of formatted text into the message text area, has them
+/// converted to Markdown, and inserts the result at the cursor. Pastes without
+/// formatting are left to the browser.
+const PASTE_JS: &str = r##"
+const el = document.getElementById(await dioxus.recv());
+if (el) {
+    el.addEventListener("paste", async (e) => {
+        const html = e.clipboardData && e.clipboardData.getData("text/html");
+        if (!html || !/<(b|strong|i|em|a|h[1-6]|ul|ol|li|table|pre|code|blockquote)[\s>]/i.test(html)) {
+            return;
+        }
+        e.preventDefault();
+        dioxus.send(html);
+        const markdown = await dioxus.recv();
+        el.setRangeText(markdown, el.selectionStart, el.selectionEnd, "end");
+        el.dispatchEvent(new Event("input", { bubbles: true }));
+    });
+}
+await new Promise(() => {});
+"##;
+
+/// Expands snippets in the message text area: Tab after an abbreviation
+/// replaces it with its expansion, read from the text area's `data-snippets`
+/// attribute, and then selects each `{{placeholder}}` of the expansion in
+/// turn. Other uses of Tab, such as completing commands, are left alone.
+const SNIPPETS_JS: &str = r##"
+const el = document.getElementById(await dioxus.recv());
+if (el) {
+    // The last expansion, while Tab moves between its placeholders; its end is
+    // kept as the length of the text after it, which typing does not change
+    let expanded = null;
+    const placeholder = (from, end) => {
+        const re = /\{\{[^{}\n]*\}\}/g;
+        re.lastIndex = from;
+        const m = re.exec(el.value);
+        return m && m.index + m[0].length <= end ? m : null;
+    };
+    const select = (m) => el.setSelectionRange(m.index, m.index + m[0].length);
+    el.addEventListener("keydown", (e) => {
+        if (e.key === "Escape") {
+            expanded = null;
+        }
+        if (e.key !== "Tab" || e.shiftKey || e.ctrlKey || e.altKey || e.metaKey || e.isComposing) {
+            return;
+        }
+        const text = el.value;
+        if (expanded) {
+            const m = placeholder(el.selectionEnd, text.length - expanded.tail);
+            if (m) {
+                e.preventDefault();
+                select(m);
+                return;
+            }
+            expanded = null;
+        }
+        const start = el.selectionStart;
+        if (start !== el.selectionEnd || (text.startsWith("/") && !/\s/.test(text))) {
+            return;
+        }
+        const word = /(\S+)$/.exec(text.slice(0, start));
+        const snippets = JSON.parse(el.dataset.snippets || "{}");
+        if (!word || !Object.hasOwn(snippets, word[1])) {
+            return;
+        }
+        e.preventDefault();
+        const from = start - word[1].length;
+        const expansion = snippets[word[1]];
+        el.setRangeText(expansion, from, start, "end");
+        expanded = { tail: el.value.length - from - expansion.length };
+        el.dispatchEvent(new Event("input", { bubbles: true }));
+        const m = placeholder(from, from + expansion.length);
+        if (m) {
+            select(m);
+        } else {
+            expanded = null;
+        }
+    });
+}
+await new Promise(() => {});
+"##;
+
+/// Commands every chat offers; toolsets can add their own.
+pub fn builtin_commands() -> Vec<SlashCommand> {
+    vec![
+        SlashCommand::new("model", "[model]", "Show the model, or switch to another"),
+        SlashCommand::new("clear", "", "Start a new conversation"),
+        SlashCommand::new("export", "", "Save the conversation as HTML"),
+        SlashCommand::new("tools", "", "List the tools the model can use"),
+        SlashCommand::new(
+            "prompt",
+            "[server/prompt name=value …]",
+            "List the servers' prompt templates, or send one",
+        ),
+    ]
+}
+
+/// Splits a message into a command and its arguments, if it starts with
+/// one of `commands`. Other messages starting with a slash, like paths, are
+/// left alone.
+///
+/// # Arguments
+/// * `text` - The message as typed
+/// * `commands` - The commands available
+///
+/// # Returns
+/// The command's name and the text after it, trimmed
+pub fn parse_command(text: &str, commands: &[SlashCommand]) -> Option<(String, String)> {
+    let rest = text.trim().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    commands
+        .iter()
+        .any(|c| c.name == name)
+        .then(|| (name.to_string(), args.trim().to_string()))
+}
+
+/// Commands whose names start with the one being typed, while the message is
+/// still just a slash and a partial name.
+fn completions<'a>(text: &str, commands: &'a [SlashCommand]) -> Vec<&'a SlashCommand> {
+    let Some(typed) = text.strip_prefix('/') else {
+        return vec![];
+    };
+    if typed.contains(char::is_whitespace) {
+        return vec![];
+    }
+    commands
+        .iter()
+        .filter(|c| c.name.starts_with(typed))
+        .collect()
+}
+
+/// Input for the next message, with an estimate of the request's size.
+///
+/// A resizable multi-line editor. Enter sends and Shift+Enter starts a new
+/// line, unless Enter is set to insert line breaks in the display settings;
+/// Ctrl+Enter always sends. The message can be previewed as rendered Markdown,
+/// and formatted text pasted from web pages or documents is converted to
+/// Markdown.
+///
+/// Typing `/` opens a menu of `commands`; Tab or a click completes the one
+/// being typed. A message starting with one of them goes to `on_command`
+/// instead of `on_send`. Tab after a snippet's abbreviation expands it, and
+/// then moves between the expansion's placeholders.
+///
+/// Up in an empty input brings back the chat's previous messages, one after
+/// another, and Down goes forward again, as in a shell. Ctrl+R searches the
+/// messages sent in all chats.
+///
+/// # Arguments
+/// * `disabled` - Whether sending is disabled
+/// * `on_send` - Called with the message content
+/// * `request_tokens` - Estimated tokens of the request without the new message
+/// * `context_length` - Size of the model's context window, if known
+/// * `commands` - Slash commands offered
+/// * `on_command` - Called with a command's name and arguments
+/// * `draft` - Text to put in the input, such as a message that failed to send
+/// * `history` - Messages sent in the chat, oldest first
 #[component]
-pub fn ChatInput(disabled: bool, on_send: Callback<String, ()>) -> Element {
+pub fn ChatInput(
+    disabled: bool,
+    on_send: Callback<Vec<ContentPart>, ()>,
+    #[props(default)] request_tokens: Option<usize>,
+    #[props(default)] context_length: Option<u64>,
+    #[props(default)] commands: Vec<SlashCommand>,
+    #[props(default)] on_command: Option<Callback<(String, String), ()>>,
+    #[props(default)] draft: Option<String>,
+    #[props(default)] history: Vec<String>,
+) -> Element {
     let mut text = use_signal(|| "".to_string());
+    // Leaving the page asks first while a message is typed but not sent
+    use_guarded_draft(text);
+    use_effect(use_reactive!(|draft| {
+        if let Some(draft) = draft {
+            text.set(draft);
+        }
+    }));
+    // Audio files to send with the next message, with their names
+    let mut attachments: Signal<Vec<(String, InputAudio)>> = use_signal(Vec::new);
+    let mut attach_error: Signal<Option<String>> = use_signal(|| None);
+    let mut preview = use_signal(|| false);
+    // Position in `history` of the message brought back with Up
+    let mut recalled = use_signal(|| None::<usize>);
+    let mut searching = use_signal(|| false);
+    let mut input_el: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
+    let focus_input = move || {
+        if let Some(el) = input_el() {
+            spawn(async move {
+                let _ = el.set_focus(true).await;
+            });
+        }
+    };
+    let app_settings = try_consume_context::<Signal<Option<AppSettings>>>();
+    let enter_sends = app_settings
+        .and_then(|s| s.read().as_ref().map(|s| s.display.enter_sends))
+        .unwrap_or(true);
+    // Expansions by abbreviation, read by the snippet handler
+    let snippets: serde_json::Map<String, serde_json::Value> = app_settings
+        .and_then(|s| {
+            s.read().as_ref().map(|s| {
+                s.snippets
+                    .iter()
+                    .map(|sn| (sn.abbreviation.clone(), sn.expansion.clone().into()))
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+    let snippets = serde_json::Value::Object(snippets).to_string();
     let set_text = move |e: Event<FormData>| {
         if disabled {
             return;
         }
         text.set(e.value());
     };
+    // Kept in a signal so the send handler stays Copy
+    let mut command_list = use_signal(Vec::new);
+    if *command_list.peek() != commands {
+        command_list.set(commands.clone());
+    }
     let mut _send = move || {
         if disabled {
             return;
         }
-        on_send(text.cloned());
+        let command = parse_command(&text.read(), &command_list.read());
+        if let (Some(on_command), Some(command)) = (on_command, command) {
+            on_command(command);
+            text.set("".to_string());
+            return;
+        }
+        let audio = attachments.take();
+        let mut content = vec![];
+        if !text.read().is_empty() || audio.is_empty() {
+            content.push(ContentPart::Text {
+                text: text.cloned(),
+            });
+        }
+        content.extend(
+            audio
+                .into_iter()
+                .map(|(_, input_audio)| ContentPart::InputAudio { input_audio }),
+        );
+        on_send(content);
         text.set("".to_string());
+        recalled.set(None);
+        attach_error.set(None);
     };
     let send = move |_e: Event<MouseData>| {
         _send();
     };
+    let matching: Vec<SlashCommand> = completions(&text.read(), &commands)
+        .into_iter()
+        .cloned()
+        .collect();
+    let first_match = matching.first().map(|c| c.name.clone());
+    let keydown = move |e: Event<KeyboardData>| {
+        let k: Rc<KeyboardData> = e.data();
+        if k.code() == Code::Tab
+            && let Some(name) = &first_match
+        {
+            e.prevent_default();
+            text.set(format!("/{name} "));
+            return;
+        }
+        let modifiers = k.modifiers();
+        if k.code() == Code::KeyR && modifiers.ctrl() {
+            e.prevent_default();
+            searching.set(true);
+            return;
+        }
+        // Still showing a recalled message, unchanged
+        let showing = recalled().filter(|&i| history.get(i) == Some(&*text.read()));
+        if k.code() == Code::ArrowUp && (showing.is_some() || text.read().is_empty()) {
+            let previous = match showing {
+                Some(i) => i.checked_sub(1),
+                None => history.len().checked_sub(1),
+            };
+            if let Some(i) = previous {
+                e.prevent_default();
+                recalled.set(Some(i));
+                text.set(history[i].clone());
+            }
+            return;
+        }
+        if k.code() == Code::ArrowDown
+            && let Some(i) = showing
+        {
+            e.prevent_default();
+            if i + 1 < history.len() {
+                recalled.set(Some(i + 1));
+                text.set(history[i + 1].clone());
+            } else {
+                recalled.set(None);
+                text.set(String::new());
+            }
+            return;
+        }
+        if k.code() != Code::Enter || k.is_composing() {
+            return;
+        }
+        if modifiers.ctrl() || modifiers.meta() || (enter_sends && !modifiers.shift()) {
+            e.prevent_default();
+            _send();
+        }
+    };
+    let attach = move |e: Event<FormData>| async move {
+        attach_error.set(None);
+        let Some(engine) = e.files() else { return };
+        for name in engine.files() {
+            match read_audio(engine.as_ref(), &name).await {
+                Ok(audio) => {
+                    // Desktop platforms give full paths; show just the file name
+                    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(&name);
+                    attachments.write().push((file_name.to_string(), audio));
+                }
+                Err(err) => attach_error.set(Some(format!("{name}: {err}"))),
+            }
+        }
+    };
     let disabled = if disabled { Some(true) } else { None };
+    let tokens = request_tokens
+        .map(|t| t + estimate_tokens(&text.read()) + attachments.read().len() * MEDIA_TOKENS);
+    let too_long = tokens
+        .zip(context_length)
+        .is_some_and(|(t, c)| t as u64 > c);
     // let nav = navigator();
     rsx! {
+        if !attachments.read().is_empty() || attach_error.read().is_some() {
+            div { style: "display: flex; flex-wrap: wrap; gap: 0.5em; margin-bottom: 0.5em; font-size: 0.9em;",
+                for (i , (name , _)) in attachments.read().iter().enumerate() {
+                    span {
+                        key: "{i}",
+                        style: "background: #e9ecef; border-radius: 1em; padding: 0.1em 0.6em;",
+                        "🎵 {name} "
+                        button {
+                            style: "border: none; background: none; cursor: pointer; padding: 0;",
+                            title: "Remove",
+                            onclick: move |_| {
+                                attachments.write().remove(i);
+                            },
+                            "✕"
+                        }
+                    }
+                }
+                if let Some(e) = attach_error() {
+                    span { style: "color: #dc3545;", "{e}" }
+                }
+            }
+        }
         div { style: "
             display: flex;
             flex-direction: row;
+            position: relative;
             ",
+            if searching() {
+                HistorySearch {
+                    on_pick: move |picked: String| {
+                        text.set(picked);
+                        recalled.set(None);
+                        searching.set(false);
+                        focus_input();
+                    },
+                    on_close: move |_| {
+                        searching.set(false);
+                        focus_input();
+                    },
+                }
+            } else if !matching.is_empty() && !preview() {
+                div { style: "
+                    position: absolute;
+                    bottom: 100%;
+                    left: 0;
+                    z-index: 10;
+                    min-width: 20em;
+                    margin-bottom: 0.25em;
+                    padding: 0.25em 0;
+                    background: #fff;
+                    border: 1px solid #ccc;
+                    border-radius: 4px;
+                    box-shadow: 0 2px 8px rgba(0, 0, 0, 0.15);
+                    font-size: 0.9em;
+                    ",
+                    for c in matching {
+                        div {
+                            key: "{c.name}",
+                            style: "padding: 0.2em 0.75em; cursor: pointer;",
+                            title: "Tab or click to complete",
+                            // Keep the focus in the text area
+                            onmousedown: move |e: Event<MouseData>| e.prevent_default(),
+                            onclick: {
+                                let name = c.name.clone();
+                                move |_| text.set(format!("/{name} "))
+                            },
+                            code { "/{c.name}" }
+                            if !c.usage.is_empty() {
+                                span { style: "color: #666;", " {c.usage}" }
+                            }
+                            span { style: "color: #888;", " — {c.description}" }
+                        }
+                    }
+                }
+            }
+            // Kept mounted while previewing so the paste handler stays attached
             textarea {
-                style: "flex-grow: 1; max-height: 10em; height: 4em;",
+                id: INPUT_ID,
+                style: format!(
+                    "flex-grow: 1; min-height: 4em; height: 6em; max-height: 60vh; resize: vertical; display: {};",
+                    if preview() { "none" } else { "block" },
+                ),
+                placeholder: if enter_sends { "Message (Enter to send, Shift+Enter for a new line)" } else { "Message (Ctrl+Enter to send)" },
                 disabled,
                 oninput: set_text,
-                onkeypress: move |e: Event<KeyboardData>| {
-                    let k: Rc<KeyboardData> = e.data;
-                    let code = k.code();
-                    let modifiers = k.modifiers();
-                    if code == Code::Enter && modifiers.ctrl() {
-                        _send();
-                    }
+                onkeydown: keydown,
+                "data-snippets": snippets,
+                onmounted: move |e: Event<MountedData>| {
+                    input_el.set(Some(e.data()));
+                    spawn(convert_pastes());
+                    spawn(expand_snippets());
                 },
                 value: text,
             }
+            if preview() {
+                div {
+                    style: "flex-grow: 1; min-height: 4em; max-height: 60vh; overflow: auto; padding: 0 0.5em; border: 1px solid #ccc; border-radius: 2px;",
+                    onclick: move |_| preview.set(false),
+                    if text.read().trim().is_empty() {
+                        p { style: "color: #888;", "Nothing to preview" }
+                    } else {
+                        {crate::md2rsx::markdown_to_rsx(&text.read())}
+                    }
+                }
+            }
+            button {
+                style: "
+                border: none;
+                background: none;
+                padding: 0 0.5em;
+                cursor: pointer;
+                font-size: 1.1em;
+                ",
+                title: if preview() { "Edit" } else { "Preview as Markdown" },
+                onclick: move |_| preview.toggle(),
+                if preview() { "✎" } else { "👁" }
+            }
+            label {
+                style: "
+                display: inline-flex;
+                align-items: center;
+                justify-content: center;
+                padding: 0 0.5em;
+                cursor: pointer;
+                font-size: 1.3em;
+                ",
+                title: "Attach audio, for models that accept it",
+                "🎤"
+                input {
+                    r#type: "file",
+                    accept: "audio/*",
+                    multiple: true,
+                    style: "display: none;",
+                    disabled,
+                    onchange: attach,
+                }
+            }
             button {
                 style: "
                 display: inline-flex;
                 align-items: center;
                 justify-content: center;
                 border-radius: 0.5em;
-                ", 
+                ",
                 onclick: send, disabled,
                 img {
-                    style: "",  
-                    src: SEND_ICON 
+                    style: "",
+                    src: SEND_ICON
+                }
+            }
+        }
+        if let Some(tokens) = tokens {
+            div {
+                style: format!(
+                    "font-size: 0.8em; text-align: right; margin-top: 0.25em; color: {};",
+                    if too_long { "#dc3545" } else { "#888" },
+                ),
+                title: "Estimated size of the request, including the conversation and tool descriptions",
+                match context_length {
+                    Some(c) => rsx! { "≈ {tokens} / {c} tokens" },
+                    None => rsx! { "≈ {tokens} tokens" },
+                }
+                if too_long {
+                    " — longer than the model's context window; delete or summarize earlier turns first"
                 }
             }
         }
     }
 }
+
+/// Converts formatted text pasted into the message text area to Markdown,
+/// until the text area goes away.
+///
+/// Does nothing on renderers without JavaScript, where pastes stay plain text.
+async fn convert_pastes() {
+    let mut eval = document::eval(PASTE_JS);
+    if let Err(e) = eval.send(INPUT_ID) {
+        warn!("Could not handle pastes: {e:?}");
+        return;
+    }
+    while let Ok(html) = eval.recv::<String>().await {
+        let markdown = html2md::parse_html(&html);
+        if eval.send(markdown.trim()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Expands snippets typed in the message text area, until the text area goes
+/// away.
+///
+/// Does nothing on renderers without JavaScript.
+async fn expand_snippets() {
+    let mut eval = document::eval(SNIPPETS_JS);
+    if let Err(e) = eval.send(INPUT_ID) {
+        warn!("Could not expand snippets: {e:?}");
+        return;
+    }
+    // Keep the handler alive as long as the text area
+    let _ = eval.recv::<()>().await;
+}
+
+/// Reads an attached audio file and encodes it for sending.
+///
+/// # Arguments
+/// * `engine` - Files picked by the user
+/// * `name` - Name of the file to read
+///
+/// # Returns
+/// The encoded audio, or an error if the file is too big, unreadable, or not
+/// in a known audio format
+async fn read_audio(engine: &dyn FileEngine, name: &str) -> anyhow::Result<InputAudio> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    let format =
+        audio_format(name).ok_or_else(|| anyhow::anyhow!("not a supported audio format"))?;
+    if engine.file_size(name).await.unwrap_or(0) > MAX_AUDIO_BYTES {
+        anyhow::bail!("larger than {} MB", MAX_AUDIO_BYTES / 1024 / 1024);
+    }
+    let bytes = engine
+        .read_file(name)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("could not be read"))?;
+    Ok(InputAudio {
+        data: STANDARD.encode(bytes),
+        format,
+    })
+}
+
+/// Determines the audio format from a file name's extension.
+fn audio_format(name: &str) -> Option<String> {
+    let ext = name.rsplit_once('.')?.1.to_lowercase();
+    match ext.as_str() {
+        "wav" | "mp3" | "ogg" | "flac" | "m4a" | "aac" | "webm" => Some(ext),
+        "mpeg" | "mpga" => Some("mp3".to_string()),
+        _ => None,
+    }
+}
@@ -1,13 +1,63 @@
 // Copyright © 2025 Nipun Kumar
 
+use std::collections::BTreeSet;
+
 use dioxus::{logger::tracing::warn, prelude::*};
 
 use crate::{
-    Route,
+    AppSettings, Route,
     app_settings::Chat,
+    core::{now_millis, time_ago},
+    export::{chat_to_html, export_chats, export_file_name, save_html},
+    llm::{ContentPart, Message},
     storage::{AppStorage, Storage, get_storage},
+    ui::nav_guard::NavGuard,
 };
 
+/// Orders the chat history can be sorted in, all newest or largest first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    Updated,
+    Created,
+    Messages,
+    Tokens,
+}
+
+impl SortBy {
+    const ALL: [SortBy; 4] = [
+        SortBy::Updated,
+        SortBy::Created,
+        SortBy::Messages,
+        SortBy::Tokens,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SortBy::Updated => "Last updated",
+            SortBy::Created => "Created",
+            SortBy::Messages => "Message count",
+            SortBy::Tokens => "Tokens",
+        }
+    }
+
+    /// Sorts chats, newest or largest first. Chats saved before their
+    /// times were kept come last, newest by ID first.
+    fn sort(&self, chats: &mut [Chat]) {
+        chats.sort_by(|a, b| {
+            let order = match self {
+                SortBy::Updated => b.metadata.updated_at.cmp(&a.metadata.updated_at),
+                SortBy::Created => b.metadata.created_at.cmp(&a.metadata.created_at),
+                SortBy::Messages => b.metadata.message_count.cmp(&a.metadata.message_count),
+                SortBy::Tokens => b.metadata.tokens.cmp(&a.metadata.tokens),
+            };
+            order.then(b.id.cmp(&a.id))
+        });
+    }
+}
+
+/// Style of the buttons acting on the selected chats.
+const BULK_BUTTON: &str = "padding: 0.25rem 0.5rem; font-size: 0.8rem; cursor: pointer;";
+
 #[derive(Props, Clone, PartialEq)]
 pub struct ChatLogProps {
     pub on_close: Option<EventHandler<()>>,
@@ -15,7 +65,13 @@ pub struct ChatLogProps {
 
 #[component]
 pub fn ChatLog(props: ChatLogProps) -> Element {
+    let app_settings = use_context::<Signal<Option<AppSettings>>>();
+    // Opening a chat asks first while the open one has work in progress
+    let guard = use_context::<NavGuard>();
+    let guarded = guard.has_work();
     let stg: Resource<Option<AppStorage>> = use_resource(move || async move {
+        // Settings are reloaded when the profile changes; reopen that profile's storage
+        let _ = app_settings.read();
         match get_storage().await {
             Ok(s) => Some(s),
             Err(e) => {
@@ -32,13 +88,19 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
         async move {
             let Some(stg) = &*stg.read() else { return None };
             let Some(stg) = stg else { return None };
-            let chats = match stg.list_chats().await {
+            let mut chats = match stg.list_chats().await {
                 Ok(c) => c,
                 Err(e) => {
                     warn!("Could not get chats: {e:?}");
                     return None;
                 }
             };
+            // Chats not saved since metadata was kept still sort and filter
+            for chat in &mut chats {
+                if chat.metadata.updated_at.is_none() {
+                    chat.refresh_metadata();
+                }
+            }
             Some(chats)
         }
     });
@@ -56,6 +118,92 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
         });
     };
 
+    let mut export_status = use_signal(|| None::<String>);
+    let export_chat = move |chat: Chat| {
+        spawn(async move {
+            let html = chat_to_html(&chat).await;
+            match save_html(&export_file_name(&chat), &html).await {
+                Ok(path) => export_status.set(Some(format!("Exported to {path}"))),
+                Err(e) => {
+                    warn!("Failed to export chat: {e:?}");
+                    export_status.set(Some(format!("Export failed: {e}")));
+                }
+            }
+        });
+    };
+
+    // Filter of the list, selection and the actions on the selected chats
+    let mut filter = use_signal(String::new);
+    let mut sort_by = use_signal(|| SortBy::Updated);
+    // Toolset and model to show the chats of; all if empty
+    let mut toolset_filter = use_signal(String::new);
+    let mut model_filter = use_signal(String::new);
+    let mut tool_calls_only = use_signal(|| false);
+    let mut show_archived = use_signal(|| false);
+    let mut selected = use_signal(BTreeSet::<u32>::new);
+    let mut tag = use_signal(String::new);
+    let mut confirm_delete = use_signal(|| false);
+
+    let delete_selected = move |ids: BTreeSet<u32>| {
+        spawn(async move {
+            let result = async {
+                let storage = get_storage().await?;
+                for id in &ids {
+                    storage.delete_chat(*id).await?;
+                }
+                anyhow::Ok(ids.len())
+            }
+            .await;
+            match result {
+                Ok(n) => export_status.set(Some(format!("Deleted {}", chats_count(n)))),
+                Err(e) => {
+                    warn!("Failed to delete chats: {e:?}");
+                    export_status.set(Some(format!("Delete failed: {e}")));
+                }
+            }
+            selected.write().clear();
+            confirm_delete.set(false);
+            refresh_trigger += 1;
+        });
+    };
+    let export_selected = move |chats: Vec<Chat>| {
+        spawn(async move {
+            match export_chats(&chats).await {
+                Ok(path) => export_status.set(Some(format!(
+                    "Exported {} to {path}",
+                    chats_count(chats.len())
+                ))),
+                Err(e) => {
+                    warn!("Failed to export chats: {e:?}");
+                    export_status.set(Some(format!("Export failed: {e}")));
+                }
+            }
+        });
+    };
+    // Applies a change to the selected chats and saves them
+    let update_selected =
+        move |chats: Vec<Chat>, change: Box<dyn Fn(&mut Chat)>, done: &'static str| {
+            spawn(async move {
+                let result = async {
+                    let storage = get_storage().await?;
+                    for mut chat in chats.clone() {
+                        change(&mut chat);
+                        storage.save_chat(&chat).await?;
+                    }
+                    anyhow::Ok(chats.len())
+                }
+                .await;
+                match result {
+                    Ok(n) => export_status.set(Some(format!("{done} {}", chats_count(n)))),
+                    Err(e) => {
+                        warn!("Failed to update chats: {e:?}");
+                        export_status.set(Some(format!("Update failed: {e}")));
+                    }
+                }
+                refresh_trigger += 1;
+            });
+        };
+
     let Some(chats) = chats() else {
         return rsx! {
             div { style: "padding: 1rem;", "Loading..." }
@@ -66,6 +214,30 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
             div { style: "padding: 1rem;", "Loading..." }
         };
     };
+    let query = filter.read().trim().to_lowercase();
+    let all_chats = chats;
+    let toolsets: BTreeSet<String> = all_chats.iter().map(|c| c.chat_type.to_string()).collect();
+    let models: BTreeSet<String> = all_chats
+        .iter()
+        .flat_map(|c| c.metadata.models.iter().cloned())
+        .collect();
+    let mut chats: Vec<Chat> = all_chats
+        .iter()
+        .filter(|c| (show_archived() || !c.archived) && matches_filter(c, &query))
+        .filter(|c| toolset_filter.read().is_empty() || c.chat_type.to_string() == toolset_filter())
+        .filter(|c| model_filter.read().is_empty() || c.metadata.models.contains(&model_filter()))
+        .filter(|c| !tool_calls_only() || c.metadata.has_tool_calls)
+        .cloned()
+        .collect();
+    sort_by().sort(&mut chats);
+    let now = now_millis();
+    let matching: BTreeSet<u32> = chats.iter().filter_map(|c| c.id).collect();
+    let chosen: Vec<Chat> = all_chats
+        .iter()
+        .filter(|c| c.id.is_some_and(|id| selected.read().contains(&id)))
+        .cloned()
+        .collect();
+    let all_archived = !chosen.is_empty() && chosen.iter().all(|c| c.archived);
 
     rsx! {
         div {
@@ -96,14 +268,219 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
 
             hr { style: "margin-bottom: 1rem;" }
 
+            if let Some(status) = export_status() {
+                div { style: "font-size: 0.8rem; color: #666; margin-bottom: 0.5rem; word-break: break-all;",
+                    "{status}"
+                }
+            }
+
+            div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem; font-size: 0.9rem;",
+                input {
+                    style: "flex: 1; min-width: 10rem;",
+                    placeholder: "Filter by text or #tag",
+                    value: filter(),
+                    oninput: move |e| filter.set(e.value()),
+                }
+                label { style: "display: inline-flex; align-items: center; gap: 0.25rem;",
+                    input {
+                        r#type: "checkbox",
+                        checked: show_archived(),
+                        oninput: move |e: Event<FormData>| show_archived.set(e.checked()),
+                    }
+                    "Show archived"
+                }
+            }
+
+            div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem; font-size: 0.9rem;",
+                label { style: "display: inline-flex; align-items: center; gap: 0.25rem;",
+                    "Sort by"
+                    select {
+                        onchange: move |e| {
+                            if let Some(by) = SortBy::ALL.into_iter().find(|s| s.label() == e.value()) {
+                                sort_by.set(by);
+                            }
+                        },
+                        for by in SortBy::ALL {
+                            option {
+                                key: "{by.label()}",
+                                value: by.label(),
+                                selected: by == sort_by(),
+                                "{by.label()}"
+                            }
+                        }
+                    }
+                }
+                select {
+                    title: "Show the chats of a toolset",
+                    onchange: move |e| toolset_filter.set(e.value()),
+                    option { value: "", selected: toolset_filter.read().is_empty(), "All toolsets" }
+                    for t in toolsets {
+                        option {
+                            key: "{t}",
+                            value: "{t}",
+                            selected: t == toolset_filter(),
+                            "{t}"
+                        }
+                    }
+                }
+                select {
+                    title: "Show the chats a model answered in",
+                    onchange: move |e| model_filter.set(e.value()),
+                    option { value: "", selected: model_filter.read().is_empty(), "All models" }
+                    for m in models {
+                        option {
+                            key: "{m}",
+                            value: "{m}",
+                            selected: m == model_filter(),
+                            "{m}"
+                        }
+                    }
+                }
+                label { style: "display: inline-flex; align-items: center; gap: 0.25rem;",
+                    input {
+                        r#type: "checkbox",
+                        checked: tool_calls_only(),
+                        oninput: move |e: Event<FormData>| tool_calls_only.set(e.checked()),
+                    }
+                    "With tool calls"
+                }
+            }
+
+            div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem; font-size: 0.8rem;",
+                button {
+                    style: BULK_BUTTON,
+                    disabled: matching.is_empty(),
+                    title: "Select every chat the filter shows",
+                    onclick: {
+                        let matching = matching.clone();
+                        move |_| selected.write().extend(matching.iter().copied())
+                    },
+                    "Select all matching ({matching.len()})"
+                }
+                if !chosen.is_empty() {
+                    span { style: "color: #666;", "{chats_count(chosen.len())} selected" }
+                    button {
+                        style: BULK_BUTTON,
+                        onclick: move |_| {
+                            selected.write().clear();
+                            confirm_delete.set(false);
+                        },
+                        "Clear"
+                    }
+                    button {
+                        style: BULK_BUTTON,
+                        title: "Export the selected chats as HTML pages in a zip file",
+                        onclick: {
+                            let chosen = chosen.clone();
+                            move |_| export_selected(chosen.clone())
+                        },
+                        "Export"
+                    }
+                    button {
+                        style: BULK_BUTTON,
+                        title: if all_archived { "Show the selected chats in the history again" } else { "Hide the selected chats from the history, without deleting them" },
+                        onclick: {
+                            let chosen = chosen.clone();
+                            move |_| {
+                                let archived = !all_archived;
+                                update_selected(
+                                    chosen.clone(),
+                                    Box::new(move |c: &mut Chat| c.archived = archived),
+                                    if archived { "Archived" } else { "Unarchived" },
+                                );
+                                selected.write().clear();
+                            }
+                        },
+                        if all_archived { "Unarchive" } else { "Archive" }
+                    }
+                    input {
+                        style: "width: 7rem;",
+                        placeholder: "Tag",
+                        value: tag(),
+                        oninput: move |e| tag.set(e.value()),
+                    }
+                    button {
+                        style: BULK_BUTTON,
+                        disabled: normalize_tag(&tag()).is_empty(),
+                        title: "Add the tag to the selected chats",
+                        onclick: {
+                            let chosen = chosen.clone();
+                            move |_| {
+                                let name = normalize_tag(&tag());
+                                update_selected(
+                                    chosen.clone(),
+                                    Box::new(move |c: &mut Chat| {
+                                        c.tags.insert(name.clone());
+                                    }),
+                                    "Tagged",
+                                );
+                                tag.set(String::new());
+                            }
+                        },
+                        "Tag"
+                    }
+                    button {
+                        style: BULK_BUTTON,
+                        disabled: normalize_tag(&tag()).is_empty(),
+                        title: "Remove the tag from the selected chats",
+                        onclick: {
+                            let chosen = chosen.clone();
+                            move |_| {
+                                let name = normalize_tag(&tag());
+                                update_selected(
+                                    chosen.clone(),
+                                    Box::new(move |c: &mut Chat| {
+                                        c.tags.remove(&name);
+                                    }),
+                                    "Untagged",
+                                );
+                                tag.set(String::new());
+                            }
+                        },
+                        "Untag"
+                    }
+                    if confirm_delete() {
+                        button {
+                            style: "{BULK_BUTTON} background: #ff4444; color: white; border: none; border-radius: 3px;",
+                            onclick: move |_| delete_selected(selected()),
+                            "Delete {chats_count(chosen.len())}"
+                        }
+                        button {
+                            style: BULK_BUTTON,
+                            onclick: move |_| confirm_delete.set(false),
+                            "Keep"
+                        }
+                    } else {
+                        button {
+                            style: "{BULK_BUTTON} color: #ff4444;",
+                            onclick: move |_| confirm_delete.set(true),
+                            "Delete"
+                        }
+                    }
+                }
+            }
+
             if chats.is_empty() {
-                div { style: "text-align: center; color: #666; padding: 2rem;", "No chats yet" }
+                div { style: "text-align: center; color: #666; padding: 2rem;",
+                    if all_chats.is_empty() { "No chats yet" } else { "No chats match the filter" }
+                }
             } else {
                 for c in chats {
                     {
                         let chat_id = c.id;
                         let message_count = c.messages.len();
+                        let tokens = c.metadata.tokens;
+                        let updated = c
+                            .metadata
+                            .updated_at
+                            .map(|t| format!(" · {}", time_ago(t, now)))
+                            .unwrap_or_default();
+                        let chat_type = c.chat_type.to_string();
+                        let exported = c.clone();
                         let on_close_handler = props.on_close;
+                        let tags = c.tags.clone();
+                        let archived = c.archived;
+                        let checked = chat_id.is_some_and(|id| selected.read().contains(&id));
 
                         rsx! {
 
@@ -117,35 +494,104 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
                                                                 border-radius: 4px;
                                                                 background: #f9f9f9;
                                                             ",
-        
+
+                                if let Some(id) = chat_id {
+                                    input {
+                                        r#type: "checkbox",
+                                        style: "margin-right: 0.5rem;",
+                                        checked,
+                                        onclick: move |e: Event<MouseData>| e.stop_propagation(),
+                                        oninput: move |e: Event<FormData>| {
+                                            if e.checked() {
+                                                selected.write().insert(id);
+                                            } else {
+                                                selected.write().remove(&id);
+                                            }
+                                            confirm_delete.set(false);
+                                        },
+                                    }
+                                }
+
                                 div { style: "flex: 1;",
                                     if let Some(id) = chat_id {
                                         Link {
                                             style: "text-decoration: none; color: #333;",
-                                            to: Route::ChatEl { id },
+                                            to: Route::ChatEl { id, anchor: String::new() },
+                                            onclick_only: guarded,
                                             onclick: move |_| {
+                                                if guarded {
+                                                    guard.ask(Route::ChatEl { id, anchor: String::new() });
+                                                }
                                                 if let Some(on_close) = &on_close_handler {
                                                     on_close.call(());
                                                 }
                                             },
                                             div { style: "font-weight: bold; margin-bottom: 0.25rem;", "Chat #{id}" }
-                                            div { style: "font-size: 0.8rem; color: #666;", "{message_count} messages" }
+                                            div { style: "font-size: 0.8rem; color: #666;",
+                                                "{chat_type} · {message_count} messages · ~{tokens} tokens{updated}"
+                                            }
                                         }
                                     } else {
                                         Link {
                                             style: "text-decoration: none; color: #333;",
                                             to: Route::NewChat {},
+                                            onclick_only: guarded,
                                             onclick: move |_| {
+                                                if guarded {
+                                                    guard.ask(Route::NewChat {});
+                                                }
                                                 if let Some(on_close) = &on_close_handler {
                                                     on_close.call(());
                                                 }
                                             },
                                             div { style: "font-weight: bold; margin-bottom: 0.25rem;", "Unnamed chat" }
-                                            div { style: "font-size: 0.8rem; color: #666;", "{message_count} messages" }
+                                            div { style: "font-size: 0.8rem; color: #666;",
+                                                "{chat_type} · {message_count} messages · ~{tokens} tokens{updated}"
+                                            }
+                                        }
+                                    }
+                                    if archived || !tags.is_empty() {
+                                        div { style: "display: flex; flex-wrap: wrap; gap: 0.25rem; margin-top: 0.25rem; font-size: 0.75rem;",
+                                            if archived {
+                                                span { style: "padding: 0 0.4rem; border-radius: 8px; background: #e9ecef; color: #666;",
+                                                    "archived"
+                                                }
+                                            }
+                                            for t in tags {
+                                                span {
+                                                    key: "{t}",
+                                                    style: "padding: 0 0.4rem; border-radius: 8px; background: #e7f1ff; color: #0d6efd; cursor: pointer;",
+                                                    title: "Show the chats with this tag",
+                                                    onclick: {
+                                                        let t = t.clone();
+                                                        move |_| filter.set(format!("#{t}"))
+                                                    },
+                                                    "#{t}"
+                                                }
+                                            }
                                         }
                                     }
                                 }
-        
+
+                                button {
+                                    style: "
+                                                                    background: #6c757d;
+                                                                    color: white;
+                                                                    border: none;
+                                                                    border-radius: 3px;
+                                                                    padding: 0.25rem 0.5rem;
+                                                                    cursor: pointer;
+                                                                    font-size: 0.8rem;
+                                                                    margin-left: 0.5rem;
+                                                                ",
+                                    title: "Export as a standalone HTML page",
+                                    onclick: move |e: Event<MouseData>| {
+                                        e.stop_propagation();
+                                        export_chat(exported.clone());
+                                    },
+                                    "Export"
+                                }
+
                                 if let Some(id) = chat_id {
                                     button {
                                         style: "
@@ -173,3 +619,60 @@ pub fn ChatLog(props: ChatLogProps) -> Element {
         }
     }
 }
+
+/// "1 chat" or "N chats".
+fn chats_count(n: usize) -> String {
+    if n == 1 {
+        "1 chat".to_string()
+    } else {
+        format!("{n} chats")
+    }
+}
+
+/// A tag as stored: trimmed, lowercase, without a leading `#`, and with
+/// spaces replaced by dashes.
+fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Whether a chat matches the filter of the chat history: every word of it,
+/// `#tag` words matching tags and other words the text of the messages.
+///
+/// # Arguments
+/// * `chat` - The chat
+/// * `query` - The filter, in lowercase
+fn matches_filter(chat: &Chat, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let text = chat
+        .messages
+        .iter()
+        .flat_map(|m| match m {
+            Message::User { content } => content
+                .iter()
+                .filter_map(|c| match c {
+                    ContentPart::Text { text } => Some(text.to_lowercase()),
+                    _ => None,
+                })
+                .collect(),
+            Message::Assistant {
+                content: Some(text),
+                ..
+            } => vec![text.to_lowercase()],
+            _ => vec![],
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    query
+        .split_whitespace()
+        .all(|word| match word.strip_prefix('#') {
+            Some(tag) => chat.tags.iter().any(|t| t.starts_with(tag)),
+            None => text.contains(word),
+        })
+}
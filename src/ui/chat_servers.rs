@@ -0,0 +1,78 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::collections::BTreeSet;
+
+use dioxus::prelude::*;
+
+/// Popover for choosing which MCP servers a chat may use.
+///
+/// Servers are on unless turned off, so servers added later are available to
+/// existing chats.
+///
+/// # Arguments
+/// * `servers` - IDs of the connected servers
+/// * `disabled` - Servers turned off for this chat
+/// * `on_change` - Called with the new set of servers turned off
+#[component]
+pub fn ChatServers(
+    servers: Vec<String>,
+    disabled: BTreeSet<String>,
+    on_change: Callback<BTreeSet<String>, ()>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let active = servers.iter().filter(|s| !disabled.contains(*s)).count();
+
+    rsx! {
+        span { style: "position: relative; display: inline-block; margin: 0 0 0.5em 1em; font-size: 0.9em;",
+            button {
+                title: "Choose which MCP servers this chat can use",
+                onclick: move |_| open.toggle(),
+                "Servers ({active}/{servers.len()})"
+            }
+            if open() {
+                div { style: "
+                    position: absolute;
+                    bottom: 100%;
+                    left: 0;
+                    z-index: 10;
+                    min-width: 14em;
+                    margin-bottom: 0.25em;
+                    padding: 0.5em 0.75em;
+                    background: #fff;
+                    border: 1px solid #ccc;
+                    border-radius: 4px;
+                    box-shadow: 0 2px 8px rgba(0, 0, 0, 0.15);
+                    ",
+                    div { style: "font-weight: 600; margin-bottom: 0.25em;", "Servers for this chat" }
+                    if servers.is_empty() {
+                        div { style: "color: #666;", "No servers are connected" }
+                    }
+                    for id in servers {
+                        label {
+                            key: "{id}",
+                            style: "display: flex; align-items: center; gap: 0.5em; padding: 0.1em 0;",
+                            input {
+                                r#type: "checkbox",
+                                checked: !disabled.contains(&id),
+                                oninput: {
+                                    let id = id.clone();
+                                    let disabled = disabled.clone();
+                                    move |e: Event<FormData>| {
+                                        let mut disabled = disabled.clone();
+                                        if e.checked() {
+                                            disabled.remove(&id);
+                                        } else {
+                                            disabled.insert(id.clone());
+                                        }
+                                        on_change(disabled);
+                                    }
+                                },
+                            }
+                            "{id}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
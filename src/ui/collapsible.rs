@@ -5,9 +5,14 @@ use dioxus::prelude::*;
 // const EXPAND_ICON: Asset = asset!("/assets/expand.png");
 // const COLLAPSE_ICON: Asset = asset!("/assets/collapse.png");
 
+/// Content with a button to show or hide it.
+///
+/// `c` is the initial state; when it changes, the content is collapsed or
+/// expanded to match, overriding the user's choice.
 #[component]
 pub fn Collapsible(c: bool, children: Element) -> Element {
     let mut collapsed = use_signal(|| c);
+    use_effect(use_reactive!(|c| collapsed.set(c)));
     rsx! {
         button {
             class: "delete-group-btn",
@@ -0,0 +1,122 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::core::Candidate;
+
+/// Most models that can be compared at once, including the chat's own model.
+pub const MAX_COMPARED_MODELS: usize = 3;
+
+/// Inputs for the extra models to ask in compare mode.
+///
+/// # Arguments
+/// * `models` - The extra models chosen so far
+/// * `options` - Known model names, offered as suggestions
+/// * `on_change` - Called with the new list of non-empty model names
+#[component]
+pub fn CompareModels(
+    models: Vec<String>,
+    options: Vec<String>,
+    on_change: Callback<Vec<String>, ()>,
+) -> Element {
+    let slots = MAX_COMPARED_MODELS - 1;
+    rsx! {
+        div { style: "display: flex; gap: 0.5em; align-items: center; margin-bottom: 0.5em; font-size: 0.9em;",
+            "Also ask:"
+            for i in 0..slots {
+                input {
+                    key: "{i}",
+                    list: "compare-model-options",
+                    placeholder: "model",
+                    style: "flex: 1; min-width: 0;",
+                    value: models.get(i).cloned().unwrap_or_default(),
+                    onchange: {
+                        let models = models.clone();
+                        move |e: Event<FormData>| {
+                            let mut updated = models.clone();
+                            updated.resize(slots, String::new());
+                            updated[i] = e.value().trim().to_string();
+                            updated.retain(|m| !m.is_empty());
+                            on_change(updated);
+                        }
+                    },
+                }
+            }
+            datalist { id: "compare-model-options",
+                for o in options {
+                    option { key: "{o}", value: "{o}" }
+                }
+            }
+        }
+    }
+}
+
+/// Side-by-side responses of the models asked in compare mode.
+///
+/// # Arguments
+/// * `candidates` - One response per model, updated while they stream
+/// * `streaming` - Whether responses are still arriving
+/// * `on_pick` - Called with the index of the response to keep
+/// * `on_discard` - Called to keep none of them
+#[component]
+pub fn CompareResponses(
+    candidates: Vec<Candidate>,
+    streaming: bool,
+    on_pick: Callback<usize, ()>,
+    on_discard: Callback<(), ()>,
+) -> Element {
+    rsx! {
+        div { style: "margin: 1em 0;",
+            div { style: "display: flex; gap: 0.5em; align-items: stretch;",
+                for (i , c) in candidates.into_iter().enumerate() {
+                    div {
+                        key: "{i}",
+                        class: "message ai-message",
+                        style: "flex: 1; min-width: 0; display: flex; flex-direction: column;",
+                        div { style: "font-weight: bold; font-family: monospace; margin-bottom: 0.5em;",
+                            "{c.model}"
+                        }
+                        div { style: "flex-grow: 1; overflow-x: auto;",
+                            if streaming {
+                                crate::md2rsx::StreamingMarkdownEl { text: c.text.clone() }
+                            } else {
+                                {crate::md2rsx::markdown_to_rsx(&c.text)}
+                            }
+                            for call in tool_call_labels(&c) {
+                                div { style: "font-family: monospace; font-size: 0.85em;", "🔧 {call}" }
+                            }
+                            if let Some(e) = &c.error {
+                                div { style: "color: #dc3545; font-size: 0.9em;", "✗ {e}" }
+                            }
+                        }
+                        button {
+                            style: "margin-top: 0.5em;",
+                            disabled: streaming || c.error.is_some(),
+                            onclick: move |_| on_pick(i),
+                            "Keep this response"
+                        }
+                    }
+                }
+            }
+            if !streaming {
+                button { style: "margin-top: 0.5em;", onclick: move |_| on_discard(()), "Keep none" }
+            }
+        }
+    }
+}
+
+/// Describes a candidate's tool calls as `name(arguments)`.
+fn tool_call_labels(candidate: &Candidate) -> Vec<String> {
+    candidate
+        .tool_calls
+        .iter()
+        .filter_map(|t| t.function.as_ref())
+        .map(|f| {
+            format!(
+                "{}({})",
+                f.name.as_deref().unwrap_or_default(),
+                f.arguments.as_deref().unwrap_or_default()
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::toolset::story::Story;
+
+/// Contradictions found by the check_continuity tool, for the story side
+/// panel, grouped by the chapter they were found in.
+///
+/// # Arguments
+/// * `story` - The story
+#[component]
+pub fn ContinuityIssues(story: Story) -> Element {
+    let mut open = use_signal(|| true);
+    let issues = &story.continuity_issues;
+    if issues.is_empty() {
+        return rsx! {};
+    }
+    let mut chapters: Vec<usize> = issues.iter().map(|i| i.chapter).collect();
+    chapters.dedup();
+
+    rsx! {
+        div { style: "margin: 0.5em 0; font-size: 0.9em; border-bottom: 1px solid #dee2e6; padding-bottom: 0.5em;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Continuity issues ({issues.len()})"
+            }
+            if open() {
+                for index in chapters {
+                    div { key: "{index}", style: "margin-top: 0.25em;",
+                        div { style: "font-weight: 600;",
+                            "{index + 1}. "
+                            {story.chapters.get(index).map(|c| c.title.clone()).unwrap_or_default()}
+                        }
+                        for (i , issue) in issues.iter().filter(|i| i.chapter == index).enumerate() {
+                            div {
+                                key: "{i}",
+                                style: "margin: 0.25em 0 0.25em 0.75em;",
+                                span { style: "color: #dc3545;", "{issue.kind.label()}: " }
+                                "{issue.description}"
+                                div { style: "color: #666; font-style: italic;", "“{issue.evidence}”" }
+                                div { style: "color: #888; font-style: italic;",
+                                    match issue.source_chapter {
+                                        Some(source) => format!("Chapter {}: “{}”", source + 1, issue.source),
+                                        None => format!("{}'s record: “{}”", issue.subject, issue.source),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
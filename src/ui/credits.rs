@@ -0,0 +1,198 @@
+// Copyright © 2025 Nipun Kumar
+
+//! OpenRouter key status: the key's label and limits, the account's remaining
+//! credits, and a warning in the chat when they are nearly used up.
+//!
+//! The status is fetched from OpenRouter's `/key` and `/credits` endpoints and
+//! kept for a few minutes, so checking after every turn costs little.
+
+use std::sync::Mutex;
+
+use dioxus::prelude::*;
+
+use crate::{
+    app_settings::OPENROUTER_API_URL,
+    core::now_millis,
+    llm::{Credits, KeyInfo, LlmClient},
+};
+
+/// Remaining credit below which the chat warns, in USD.
+pub const LOW_CREDITS_USD: f64 = 0.5;
+
+/// How long a fetched status is reused by the chat's warning.
+const STATUS_MAX_AGE_MILLIS: u64 = 5 * 60 * 1000;
+
+/// Last status fetched, with the key it is for and when it was fetched
+static LAST_STATUS: Mutex<Option<(String, u64, AccountStatus)>> = Mutex::new(None);
+
+/// What OpenRouter reports about an API key and its account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountStatus {
+    pub key: KeyInfo,
+    /// Credits of the account; `None` if OpenRouter did not report them
+    pub credits: Option<Credits>,
+}
+
+impl AccountStatus {
+    /// What can still be spent with the key in USD: the lower of the key's
+    /// remaining limit and the account's remaining credits.
+    ///
+    /// # Returns
+    /// The amount, or `None` if neither is limited or known
+    pub fn remaining(&self) -> Option<f64> {
+        let credits = self
+            .credits
+            .as_ref()
+            .map(|c| (c.total_credits - c.total_usage).max(0.0));
+        match (self.key.limit_remaining, credits) {
+            (Some(key), Some(credits)) => Some(key.min(credits)),
+            (key, credits) => key.or(credits),
+        }
+    }
+
+    /// Whether credits are nearly used up. Free-tier keys have no credits to
+    /// run out of, only rate limits.
+    pub fn is_low(&self) -> bool {
+        !self.key.is_free_tier && self.remaining().is_some_and(|r| r < LOW_CREDITS_USD)
+    }
+}
+
+/// Fetches the status of an OpenRouter API key.
+///
+/// # Arguments
+/// * `api_key` - The key
+///
+/// # Returns
+/// The status, or an error if the key's details cannot be fetched. Missing
+/// credits, e.g. for keys not allowed to see them, are not an error.
+pub async fn fetch_status(api_key: &str) -> anyhow::Result<AccountStatus> {
+    let client = LlmClient::new(OPENROUTER_API_URL.to_string(), api_key.to_string());
+    let key = client.key_info().await?;
+    let credits = client.credits().await.ok();
+    let status = AccountStatus { key, credits };
+    if let Ok(mut last) = LAST_STATUS.lock() {
+        *last = Some((api_key.to_string(), now_millis(), status.clone()));
+    }
+    Ok(status)
+}
+
+/// The status of an OpenRouter API key, fetched again only if the last one
+/// is older than a few minutes.
+async fn recent_status(api_key: &str) -> anyhow::Result<AccountStatus> {
+    let cached = LAST_STATUS.lock().ok().and_then(|last| {
+        last.as_ref()
+            .filter(|(key, at, _)| {
+                key == api_key && now_millis().saturating_sub(*at) < STATUS_MAX_AGE_MILLIS
+            })
+            .map(|(_, _, status)| status.clone())
+    });
+    match cached {
+        Some(status) => Ok(status),
+        None => fetch_status(api_key).await,
+    }
+}
+
+/// Formats an amount in USD.
+fn usd(amount: f64) -> String {
+    format!("${amount:.2}")
+}
+
+/// The key's label and limits and the account's credits, for the settings.
+///
+/// # Arguments
+/// * `api_key` - The OpenRouter API key
+#[component]
+pub fn OpenRouterKeyStatus(api_key: String) -> Element {
+    let mut status = use_resource(use_reactive!(|api_key| async move {
+        if api_key.trim().is_empty() {
+            return None;
+        }
+        Some(fetch_status(&api_key).await.map_err(|e| e.to_string()))
+    }));
+
+    let body = match &*status.read() {
+        None => rsx! { span { style: "color: #666;", "Checking the key…" } },
+        Some(None) => return rsx! {},
+        Some(Some(Err(e))) => rsx! {
+            span { style: "color: #dc3545;", "Could not check the key: {e}" }
+        },
+        Some(Some(Ok(s))) => {
+            let spent = match s.key.limit {
+                Some(limit) => format!("{} of {} spent", usd(s.key.usage), usd(limit)),
+                None => format!("{} spent, no limit", usd(s.key.usage)),
+            };
+            let credits = s.credits.as_ref().map(|c| {
+                format!(
+                    "{} left of {}",
+                    usd((c.total_credits - c.total_usage).max(0.0)),
+                    usd(c.total_credits)
+                )
+            });
+            let rate = s
+                .key
+                .rate_limit
+                .as_ref()
+                .map(|r| format!("{} requests per {}", r.requests, r.interval));
+            let color = if s.is_low() { "#dc3545" } else { "inherit" };
+            rsx! {
+                div { "Key: " strong { "{s.key.label}" }
+                    if s.key.is_free_tier {
+                        " (free tier)"
+                    }
+                }
+                div { "Key usage: {spent}" }
+                if let Some(credits) = credits {
+                    div { style: "color: {color};", "Account credits: {credits}" }
+                }
+                if let Some(rate) = rate {
+                    div { "Rate limit: {rate}" }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div { style: "margin-top: 0.5em; padding: 0.5em; border: 1px solid #ddd; border-radius: 4px; font-size: 0.9em; display: flex; flex-direction: column; gap: 0.2em;",
+            {body}
+            div {
+                button { onclick: move |_| status.restart(), "⟳ Refresh" }
+            }
+        }
+    }
+}
+
+/// Warning shown in the chat when the OpenRouter key is nearly out of credits,
+/// so a long story does not fail halfway through a reply.
+///
+/// # Arguments
+/// * `api_key` - The OpenRouter API key
+/// * `turns` - Changes after each turn, to check again (at most every few
+///   minutes)
+#[component]
+pub fn CreditsWarning(api_key: String, turns: usize) -> Element {
+    let mut dismissed = use_signal(|| false);
+    let status = use_resource(use_reactive!(|(api_key, turns)| async move {
+        let _ = turns;
+        if api_key.trim().is_empty() {
+            return None;
+        }
+        recent_status(&api_key).await.ok()
+    }));
+
+    let Some(Some(status)) = status() else {
+        return rsx! {};
+    };
+    if !status.is_low() || dismissed() {
+        return rsx! {};
+    }
+    let remaining = status.remaining().map(usd).unwrap_or_default();
+
+    rsx! {
+        div { style: "background: #fff3cd; border: 1px solid #ffeeba; color: #856404; border-radius: 4px; padding: 0.5em 1em; margin: 0.5em 1em; display: flex; gap: 1em; align-items: center;",
+            span { style: "flex: 1;",
+                "OpenRouter credits are nearly used up ({remaining} left). Top up at openrouter.ai before the next reply fails."
+            }
+            button { onclick: move |_| dismissed.set(true), "Dismiss" }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::request_log::{self, LogEntry};
+
+/// Debug page listing the recorded LLM requests with their raw JSON.
+///
+/// Shows the newest entries first. Recording is turned on in Settings; the
+/// panel does not update by itself, so it has a refresh button.
+#[component]
+pub fn DebugPanel() -> Element {
+    let mut entries = use_signal(request_log::entries);
+
+    rsx! {
+        div { style: "padding: 3em 1rem 1rem 1rem; height: 100%; overflow-y: auto; box-sizing: border-box;",
+            h3 { "LLM request log" }
+            if !request_log::is_enabled() {
+                p { style: "color: #666;",
+                    "Recording is off. Turn on \"Record LLM requests and responses\" in Settings."
+                }
+            }
+            div { style: "display: flex; gap: 0.5em; align-items: center; margin-bottom: 1em;",
+                button { onclick: move |_| entries.set(request_log::entries()), "⟳ Refresh" }
+                button {
+                    onclick: move |_| {
+                        request_log::clear();
+                        entries.set(vec![]);
+                    },
+                    "Clear"
+                }
+                if cfg!(not(target_arch = "wasm32")) {
+                    span { style: "font-size: 0.85em; color: #666;", {log_file_note()} }
+                }
+            }
+            if entries.read().is_empty() {
+                p { style: "color: #666; font-style: italic;", "No requests recorded" }
+            }
+            for entry in entries() {
+                LogEntryView { key: "{entry.id}", entry }
+            }
+        }
+    }
+}
+
+/// Where the log file is, for the panel's header.
+fn log_file_note() -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        format!("Also written to {}", request_log::log_path().display())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        String::new()
+    }
+}
+
+/// One recorded request, expandable to show the raw request and response.
+#[component]
+fn LogEntryView(entry: LogEntry) -> Element {
+    let status = match (&entry.error, entry.status, entry.finished_at) {
+        (Some(e), _, _) => format!("✗ {e}"),
+        (None, Some(s), Some(_)) => format!("{s}"),
+        (None, _, None) => "in progress".to_string(),
+        (None, None, Some(_)) => "no response".to_string(),
+    };
+    let duration = entry
+        .finished_at
+        .map(|f| format!(" · {} ms", f.saturating_sub(entry.started_at)))
+        .unwrap_or_default();
+    let request = entry
+        .request
+        .as_ref()
+        .and_then(|r| serde_json::to_string_pretty(r).ok())
+        .unwrap_or_default();
+    let color = if entry.error.is_some() { "#dc3545" } else { "#333" };
+
+    rsx! {
+        details { style: "border: 1px solid #ddd; border-radius: 4px; padding: 0.5em; margin-bottom: 0.5em;",
+            summary { style: "cursor: pointer; font-family: monospace; color: {color};",
+                "#{entry.id} {entry.method} {entry.url} — {status}{duration}"
+            }
+            if !request.is_empty() {
+                h5 { style: "margin: 0.75em 0 0.25em 0;", "Request" }
+                pre { style: "
+                        margin: 0;
+                        max-height: 30em;
+                        overflow: auto;
+                        background: #f8f9fa;
+                        padding: 0.5em;
+                        font-size: 0.8em;
+                        white-space: pre-wrap;
+                        word-break: break-word;
+                    ",
+                    "{request}"
+                }
+            }
+            h5 { style: "margin: 0.75em 0 0.25em 0;", "Response" }
+            pre { style: "
+                    margin: 0;
+                    max-height: 30em;
+                    overflow: auto;
+                    background: #f8f9fa;
+                    padding: 0.5em;
+                    font-size: 0.8em;
+                    white-space: pre-wrap;
+                    word-break: break-word;
+                ",
+                "{entry.response}"
+            }
+        }
+    }
+}
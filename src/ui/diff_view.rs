@@ -0,0 +1,58 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Whether a tool result is a unified diff, such as the output of the git
+/// server's `diff` tool.
+pub fn is_unified_diff(text: &str) -> bool {
+    let text = text.trim_start();
+    text.starts_with("diff --git ") || (text.starts_with("--- ") && text.contains("\n+++ "))
+}
+
+/// A unified diff with added and removed lines highlighted, one block per file.
+///
+/// # Arguments
+/// * `diff` - The diff text
+#[component]
+pub fn DiffView(diff: String) -> Element {
+    let mut files: Vec<Vec<(String, &'static str)>> = vec![];
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") || files.is_empty() {
+            files.push(vec![]);
+        }
+        files
+            .last_mut()
+            .unwrap()
+            .push((line.to_string(), line_style(line)));
+    }
+
+    rsx! {
+        for (i , lines) in files.into_iter().enumerate() {
+            div {
+                key: "{i}",
+                class: "diff-file",
+                style: "font-family: 'Fira Code', 'JetBrains Mono', 'Courier New', monospace; font-size: 0.85em; border: 1px solid rgba(128, 128, 128, 0.4); border-radius: 4px; margin: 0.5em 0; overflow-x: auto;",
+                for (j , (line , style)) in lines.into_iter().enumerate() {
+                    div { key: "{j}", style: "white-space: pre; padding: 0 0.5em; {style}",
+                        "{line}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Style of a diff line, by what it is.
+fn line_style(line: &str) -> &'static str {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff --git") {
+        "font-weight: bold; opacity: 0.8;"
+    } else if line.starts_with("@@") {
+        "color: #6f42c1; background: rgba(111, 66, 193, 0.1);"
+    } else if line.starts_with('+') {
+        "background: rgba(40, 167, 69, 0.2);"
+    } else if line.starts_with('-') {
+        "background: rgba(220, 53, 69, 0.2);"
+    } else {
+        ""
+    }
+}
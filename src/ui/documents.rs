@@ -0,0 +1,64 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::app_settings::ChatDocument;
+
+/// Collapsible list of the documents attached to a chat, kept at the top of
+/// the chat with the pinned messages. Each document shows its summary, or its
+/// size until the summary is written.
+///
+/// # Arguments
+/// * `documents` - The chat's documents
+/// * `status` - Progress or error of the last drop, if any
+/// * `on_remove` - Called with the position of the document to remove
+#[component]
+pub fn DocumentsPanel(
+    documents: Vec<ChatDocument>,
+    status: Option<String>,
+    on_remove: EventHandler<usize>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    if documents.is_empty() && status.is_none() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { style: "margin: 0 1em; padding: 0.25em 0.5em; font-size: 0.9em; border-bottom: 1px solid #dee2e6;",
+            if !documents.is_empty() {
+                button {
+                    style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                    title: "The model searches these documents when it needs them",
+                    onclick: move |_| open.toggle(),
+                    if open() { "▾ " } else { "▸ " }
+                    "📄 Documents ({documents.len()})"
+                }
+            }
+            if let Some(status) = status {
+                div { style: "color: #666;", "{status}" }
+            }
+            if open() {
+                for (i , document) in documents.into_iter().enumerate() {
+                    div {
+                        key: "{document.name}",
+                        style: "display: flex; align-items: baseline; gap: 0.5em; padding: 0.25em 0;",
+                        div { style: "flex: 1;",
+                            strong { "{document.name}" }
+                            div { style: "color: #666;",
+                                match &document.summary {
+                                    Some(summary) => summary.clone(),
+                                    None => format!("{} characters", document.text.chars().count()),
+                                }
+                            }
+                        }
+                        button {
+                            title: "Remove the document from the chat",
+                            onclick: move |_| on_remove(i),
+                            "×"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::storage::{disable_encryption, enable_encryption, encryption::is_enabled};
+
+/// Turns encryption of stored settings and chats with a passphrase on or off.
+/// All profiles are encrypted or decrypted at once.
+#[component]
+pub fn EncryptionSettings() -> Element {
+    let mut enabled = use_signal(is_enabled);
+    let mut passphrase = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut confirm_disable = use_signal(|| false);
+    let mut working = use_signal(|| false);
+    let mut status: Signal<Option<Result<String, String>>> = use_signal(|| None);
+
+    let mismatch = !confirm().is_empty() && confirm() != passphrase();
+
+    let encrypt = move |_| async move {
+        working.set(true);
+        let res = enable_encryption(&passphrase()).await;
+        working.set(false);
+        enabled.set(is_enabled());
+        match res {
+            Ok(()) => {
+                passphrase.set(String::new());
+                confirm.set(String::new());
+                status.set(Some(Ok(
+                    "Storage is encrypted; the passphrase is asked for at startup".to_string(),
+                )));
+            }
+            Err(e) => status.set(Some(Err(format!("Could not encrypt storage: {e:#}")))),
+        }
+    };
+    let decrypt = move |_| async move {
+        confirm_disable.set(false);
+        working.set(true);
+        let res = disable_encryption().await;
+        working.set(false);
+        enabled.set(is_enabled());
+        status.set(Some(match res {
+            Ok(()) => Ok("Storage is no longer encrypted".to_string()),
+            Err(e) => Err(format!("Could not decrypt storage: {e:#}")),
+        }));
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 0.5rem 0;", "Encryption" }
+        if enabled() {
+            div { style: "font-size: 0.9em; margin-bottom: 0.5rem;",
+                "Settings and chats are stored encrypted with your passphrase."
+            }
+            if confirm_disable() {
+                div { style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem; background: #fff3cd; border-radius: 3px; font-size: 0.9em;",
+                    span { style: "flex: 1;", "Store settings and chats unencrypted from now on?" }
+                    button { disabled: working(), onclick: decrypt, "Confirm" }
+                    button { onclick: move |_| confirm_disable.set(false), "Cancel" }
+                }
+            } else {
+                button {
+                    disabled: working(),
+                    onclick: move |_| confirm_disable.set(true),
+                    if working() { "Working…" } else { "Turn off encryption" }
+                }
+            }
+        } else {
+            p { style: "margin: 0 0 0.5rem 0; font-size: 0.9em; color: #666;",
+                "Encrypt settings, chats, secrets, server state and request logs of all profiles with a passphrase, asked for at startup. "
+                "There is no way to recover the data if the passphrase is forgotten."
+            }
+            div { style: "display: flex; flex-wrap: wrap; gap: 0.5rem;",
+                input {
+                    r#type: "password",
+                    style: "flex: 1; padding: 0.5rem; border: 1px solid #ddd; border-radius: 3px;",
+                    placeholder: "Passphrase",
+                    value: passphrase(),
+                    disabled: working(),
+                    oninput: move |e| passphrase.set(e.value()),
+                }
+                input {
+                    r#type: "password",
+                    style: "flex: 1; padding: 0.5rem; border: 1px solid #ddd; border-radius: 3px;",
+                    placeholder: "Passphrase again",
+                    value: confirm(),
+                    disabled: working(),
+                    oninput: move |e| confirm.set(e.value()),
+                }
+                button {
+                    disabled: working() || passphrase().is_empty() || confirm() != passphrase(),
+                    onclick: encrypt,
+                    if working() { "Encrypting…" } else { "Encrypt storage" }
+                }
+            }
+            if mismatch {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;",
+                    "The passphrases differ"
+                }
+            }
+        }
+        match status() {
+            Some(Ok(msg)) => rsx! {
+                div { style: "color: #28a745; font-size: 0.9em; margin-top: 0.25rem;", "{msg}" }
+            },
+            Some(Err(msg)) => rsx! {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;", "{msg}" }
+            },
+            None => rsx! {},
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::input_history;
+
+/// Most matches listed at once.
+const MAX_RESULTS: usize = 12;
+
+/// Characters of a past message shown in the list.
+const PREVIEW_CHARS: usize = 160;
+
+/// Fuzzy search over the messages sent in all chats, opened with Ctrl+R in
+/// the chat input. Enter or a click picks the highlighted message, the arrow
+/// keys move the highlight, and Escape closes the search.
+///
+/// # Arguments
+/// * `on_pick` - Called with the picked message
+/// * `on_close` - Called when the search is closed without picking
+#[component]
+pub fn HistorySearch(on_pick: Callback<String, ()>, on_close: Callback<(), ()>) -> Element {
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| 0usize);
+    let prompts = use_resource(|| async { input_history::all_prompts().await });
+
+    let matches: Vec<String> = match &*prompts.read() {
+        Some(Ok(prompts)) => input_history::search(prompts, &query.read(), MAX_RESULTS)
+            .into_iter()
+            .cloned()
+            .collect(),
+        _ => vec![],
+    };
+    let status = match &*prompts.read() {
+        None => Some("Loading past messages…".to_string()),
+        Some(Err(e)) => Some(format!("Could not load past messages: {e}")),
+        Some(Ok(p)) if p.is_empty() => Some("No messages sent yet".to_string()),
+        Some(Ok(_)) if matches.is_empty() => Some("No matching messages".to_string()),
+        Some(Ok(_)) => None,
+    };
+    let count = matches.len();
+    let highlighted = (*selected.read()).min(count.saturating_sub(1));
+    let picked = matches.get(highlighted).cloned();
+
+    let keydown = move |e: Event<KeyboardData>| match e.data().key() {
+        Key::Escape => {
+            e.prevent_default();
+            on_close(());
+        }
+        Key::Enter => {
+            e.prevent_default();
+            if let Some(text) = &picked {
+                on_pick(text.clone());
+            }
+        }
+        Key::ArrowDown => {
+            e.prevent_default();
+            selected.set((highlighted + 1).min(count.saturating_sub(1)));
+        }
+        Key::ArrowUp => {
+            e.prevent_default();
+            selected.set(highlighted.saturating_sub(1));
+        }
+        _ => {}
+    };
+
+    rsx! {
+        div { style: "
+            position: absolute;
+            bottom: 100%;
+            left: 0;
+            right: 0;
+            z-index: 10;
+            margin-bottom: 0.25em;
+            padding: 0.5em;
+            background: #fff;
+            border: 1px solid #ccc;
+            border-radius: 4px;
+            box-shadow: 0 2px 8px rgba(0, 0, 0, 0.15);
+            font-size: 0.9em;
+            ",
+            input {
+                style: "width: 100%; box-sizing: border-box; margin-bottom: 0.25em;",
+                placeholder: "Search past messages (Enter to use, Esc to close)",
+                autofocus: true,
+                value: query(),
+                oninput: move |e| {
+                    query.set(e.value());
+                    selected.set(0);
+                },
+                onkeydown: keydown,
+                onmounted: move |e| async move {
+                    let _ = e.set_focus(true).await;
+                },
+            }
+            if let Some(status) = status {
+                div { style: "color: #888; padding: 0.2em 0.5em;", "{status}" }
+            }
+            for (i , text) in matches.into_iter().enumerate() {
+                div {
+                    key: "{i}",
+                    style: format!(
+                        "padding: 0.2em 0.5em; cursor: pointer; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; border-radius: 3px; background: {};",
+                        if i == highlighted { "#e7f1ff" } else { "transparent" },
+                    ),
+                    title: "{text}",
+                    onmouseenter: move |_| selected.set(i),
+                    onclick: {
+                        let text = text.clone();
+                        move |_| on_pick(text.clone())
+                    },
+                    "{preview(&text)}"
+                }
+            }
+        }
+    }
+}
+
+/// A past message on one line, shortened.
+fn preview(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > PREVIEW_CHARS {
+        let short: String = line.chars().take(PREVIEW_CHARS).collect();
+        format!("{short}…")
+    } else {
+        line
+    }
+}
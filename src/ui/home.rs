@@ -6,35 +6,100 @@
 //! It handles message display, streaming responses, tool execution, and manages the
 //! conversation flow between the user, LLM, and MCP tools.
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    sync::Arc,
+};
 
-use dioxus::{logger::tracing::warn, prelude::*};
-use serde_json::json;
+use dioxus::{
+    html::{FileEngine, HasFileData},
+    logger::tracing::warn,
+    prelude::*,
+};
 
 use crate::{
-    app_settings::{AppSettings, Chat, Toolsets},
-    mcp::host::MCPHost,
+    app_settings::{
+        AppSettings, Chat, MessageAuthor, ModelChoice, ProviderSettings, StorySnapshot, Toolsets,
+    },
+    core::{
+        CancelHandle, Candidate, MAX_TOOL_FAILURES, RemovedTurn, TurnTiming,
+        estimate_request_tokens, now_millis, set_system_prompt, summarize_document, time_ago,
+        tools_to_message_objects, turn_start, with_documents, with_pinned, with_variables,
+    },
+    export::{chat_to_html, export_file_name, save_html},
+    jobs::{self, spawn_job},
+    mcp::{
+        host::MCPHost,
+        knowledge::document_from_file,
+        prompts::{parse_prompt_args, prompt_text},
+        scratchpad::{is_scratchpad_only, notes, with_notes},
+    },
+    notifications::{NOTIFY_AFTER_MILLIS, notify_turn_finished},
     storage::{Storage, get_storage},
     toolset::{
         Toolset,
-        chat::ChatTools,
-        story::{Story, StoryWriter},
+        agent::{Plan, with_agent_instructions, without_agent_instructions},
+        character_chat::Interview,
+        registry,
+        roleplay::{self, RoleplayTools, Scene},
+        story::{CreativeWriterMcpServer, Story, StoryWriter},
+        summaries,
+    },
+    utils::{
+        SessionSignals, propose_tool_calls, remember_model, run_tools_loop, save_chat_to_storage,
+        switch_model,
     },
-    utils::{run_tools_loop, save_chat_to_storage},
 };
 use crate::{
-    llm::{ContentPart, LlmClient, Message}, // LLM types and client
+    llm::{Constraint, ContentPart, Message, ToolCallDelta}, // LLM types and client
     ui::{
-        chat_input::ChatInput, // Component for message input
+        chapter_actions::ChapterActions, // Requests to rework chapters of the story
+        character_interview::CharacterInterview, // Side conversations with characters
+        continuity_issues::ContinuityIssues, // Contradictions found between chapters
+        chat_input::{ChatInput, builtin_commands}, // Message input and its slash commands
+        chat_servers::ChatServers, // MCP servers turned on for the chat
+        compare::{CompareModels, CompareResponses}, // Side-by-side responses of several models
+        credits::CreditsWarning, // Warning when OpenRouter credits run low
+        model_loaded::ModelLoadedIndicator, // Whether the Ollama model is loaded
+        model_switcher::ModelSwitcher, // Ctrl+M switcher between recently used models
+        provider_check::ProviderDownWarning, // Warning while the provider seems down
+        nav_guard::use_guarded_turn, // Warning before leaving a running turn
+        documents::DocumentsPanel, // Files attached to the chat
+        reply_options::ReplyOptions, // Stop sequences and prefill for replies
+        roleplay::{CastEditor, TurnOrder}, // Turn order and cast of role-play chats
+        scratchpad::ScratchpadPanel, // Notes the model kept for itself
+        snackbar::Snackbar, // Undo notice after deleting a turn
+        variables::{VariablesPanel, conversation_placeholders}, // Per-chat prompt variables
+        sources::turn_sources, // Sources consulted by fetch and search tools
+        story_snapshots::StorySnapshots, // Named versions of the story
+        system_prompt::SystemPromptEditor, // Editable system prompt of the chat
+        proposed_calls::ProposedCalls, // Review of tool calls in plan-only mode
+        jump_to_latest::JumpToLatest, // Scroll-to-bottom pill
+        live_output::LiveToolOutput, // Output of tool calls still running
+        patch_review::PatchReview, // File edits waiting for review
         message::MessageEl,    // Component for displaying individual messages
         message_group::{MessageGroupEl, group_messages}, // Component for grouped messages
+        offline::Online, // Whether sending can reach the provider
+        pane_tabs::{Pane, PaneTabs, use_pane_swipe}, // Chat or panel on narrow screens
+        pins::{
+            MessageLinkButton, PinButton, PinEntry, PinsPanel, message_anchor,
+            parse_message_anchor, pin_entries, scroll_to_message,
+        }, // Pinned messages and links to messages
+        send_status::{SendStatus, SendStatusLine}, // Pending, sent or failed status of a message
+        turn_timing::{ThroughputLine, TurnTimingLine}, // Generation speed and turn timing
     },
 };
 
+/// A stored chat, scrolled to the message its anchor links to, if any.
 #[component]
-pub fn ChatEl(id: u32) -> Element {
+pub fn ChatEl(id: u32, anchor: String) -> Element {
     rsx! {
-        Home { id: Signal::new(Some(id)), chat_type: Toolsets::Chat }
+        Home {
+            id: Signal::new(Some(id)),
+            chat_type: Toolsets::Chat,
+            anchor: parse_message_anchor(&anchor),
+        }
     }
 }
 
@@ -52,39 +117,67 @@ pub fn NewStory() -> Element {
     }
 }
 
+/// New chat in any registered toolset, by the toolset's ID.
+#[component]
+pub fn NewToolsetChat(toolset: Toolsets) -> Element {
+    rsx! {
+        Home { id: Signal::new(None), chat_type: toolset }
+    }
+}
+
 /// Main chat interface component.
 ///
 /// This component provides the primary user interface for chatting with LLMs.
 /// It manages the conversation state, handles streaming responses, executes tools,
 /// and provides safety mechanisms to prevent runaway tool execution.
+///
+/// # Arguments
+/// * `id` - ID of the stored chat, or `None` for a new one
+/// * `chat_type` - Toolset of a new chat
+/// * `anchor` - Stable ID of a message to scroll to once the chat is loaded
 #[component]
-pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
+pub fn Home(
+    id: Signal<Option<u32>>,
+    chat_type: Toolsets,
+    #[props(default)] anchor: Option<u32>,
+) -> Element {
     let nav = navigator();
     let mut toolset: Signal<Box<dyn Toolset>> = use_signal(|| {
-        let host = consume_context::<Arc<MCPHost>>();
-        let ts: Box<dyn Toolset> = match chat_type {
-            Toolsets::Chat => Box::new(ChatTools::new(host)),
-            Toolsets::Story => Box::new(StoryWriter::new(Default::default())),
-        };
-        ts
+        registry::create_new(&chat_type, consume_context::<Arc<MCPHost>>()).0
     });
     let mut chat: Signal<Chat> = use_signal(|| {
         let ts = &*toolset.read();
         Chat {
             id: None,
-            chat_type,
+            chat_type: chat_type.clone(),
             messages: vec![Message::System {
                 content: ts.get_system_prompt(),
             }],
-            value: match chat_type {
-                Toolsets::Chat => {
-                    json!({})
-                }
-                Toolsets::Story => serde_json::to_value(Story::default()).unwrap(),
-            },
+            value: registry::entry_or_chat(&chat_type).initial_state,
+            archive: vec![],
+            disabled_servers: Default::default(),
+            authors: Default::default(),
+            pinned: Default::default(),
+            documents: vec![],
+            message_ids: vec![],
+            next_message_id: 0,
+            message_times: Default::default(),
+            tags: Default::default(),
+            archived: false,
+            metadata: Default::default(),
+            variables: Default::default(),
+            model: None,
         }
     });
     let mut display: Signal<Option<String>> = use_signal(|| None);
+    // Pane shown on narrow screens, switched with tabs or by swiping
+    let pane = use_signal(|| Pane::Chat);
+    let (on_swipe_start, on_swipe_end) = use_pane_swipe(pane);
+    // Position and delivery of the message the user sent last
+    let mut send_status: Signal<Option<(usize, SendStatus)>> = use_signal(|| None);
+    // Speed of the response streaming in, and timing of the last turn
+    let throughput: Signal<Option<f64>> = use_signal(|| None);
+    let mut turn_timing: Signal<Option<TurnTiming>> = use_signal(|| None);
     let _ = use_resource(move || async move {
         let Some(id) = id() else {
             let ts = &*toolset.read();
@@ -104,25 +197,45 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
         };
         if let Ok(Some(ch)) = storage.get_chat(id).await {
             let host = consume_context::<Arc<MCPHost>>();
-            let ts: Box<dyn Toolset> = if ch.chat_type == Toolsets::Story {
-                let story: Story = serde_json::from_value(ch.value.clone()).unwrap_or_else(|e| {
-                    warn!("Invalid story metadta: {e:?}");
-                    Default::default()
-                });
-                Box::new(StoryWriter::new(story))
-            } else {
-                Box::new(ChatTools::new(host))
-            };
+            let ts = registry::create(&ch.chat_type, host, &ch.value);
             display.set(ts.get_markdown_repr().await);
             toolset.set(ts);
+            if chat.peek().id != ch.id {
+                send_status.set(None);
+                turn_timing.set(None);
+            }
             chat.set(ch);
         }
     });
+    // Scroll to the linked message once the chat showing it is loaded
+    let mut scrolled_to = use_signal(|| None::<u32>);
+    use_effect(use_reactive!(|anchor| {
+        let Some(target) = anchor else { return };
+        let shown = {
+            let chat = chat.read();
+            chat.id.is_some() && chat.id == *id.peek() && chat.message_index(target).is_some()
+        };
+        if shown && *scrolled_to.peek() != Some(target) {
+            scrolled_to.set(Some(target));
+            scroll_to_message(target);
+        }
+    }));
+    // A model chosen for the chat replaces the one in the settings
+    let chat_model = use_memo(move || chat.read().model.clone());
     let settings = use_resource(move || async move {
         let settings_ctx = consume_context::<Signal<Option<AppSettings>>>();
+        let chat_model = chat_model();
 
-        settings_ctx.read().clone()
+        settings_ctx
+            .read()
+            .clone()
+            .map(|s| s.for_chat(chat_model.as_ref()))
     });
+    // Reply options set above the chat input, applied to every request
+    let mut stop_sequences: Signal<Vec<String>> = use_signal(Vec::new);
+    let mut prefill = use_signal(String::new);
+    let mut constraint: Signal<Option<Constraint>> = use_signal(|| None);
+
     // Initialize LLM client from settings
     let client = use_resource(move || async move {
         let Some(settings) = settings() else {
@@ -131,14 +244,17 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
         let Some(settings) = settings else {
             return None;
         };
-        let api_base = settings.provider.get_api_url();
-        let api_key = settings
-            .provider
-            .get_api_key()
-            .unwrap_or_else(|| "".to_string());
-
         // Create LLM client with configured API settings
-        let lmc = LlmClient::new(api_base, api_key);
+        let lmc = settings
+            .client()
+            .with_stop(stop_sequences())
+            .with_prefill(prefill());
+        // Only local backends can constrain output
+        let lmc = if settings.provider.is_local() {
+            lmc.with_constraint(constraint().or_else(|| toolset.read().output_constraint()))
+        } else {
+            lmc
+        };
         Some(lmc)
     });
 
@@ -172,16 +288,65 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
     // Track if the system is currently processing a request
     let mut busy = use_signal(|| false);
 
+    // Key of the OpenRouter provider, whose credits are checked after turns
+    let openrouter_key = move || match settings().flatten()?.provider {
+        ProviderSettings::OpenRouter { api_key, .. } => Some(api_key),
+        _ => None,
+    };
+
+    // Server, model and keep-alive of the Ollama provider, whose model is
+    // loaded when a chat opens
+    let ollama_model = move || match settings().flatten()?.provider {
+        ProviderSettings::Ollama {
+            api_url,
+            model,
+            keep_alive,
+            ..
+        } => Some((api_url, model?, keep_alive)),
+        _ => None,
+    };
+
+    // The provider and its last connection test, if that failed
+    let failed_check = move || {
+        let s = settings().flatten()?;
+        let check = s.provider_checks.get(s.provider.kind())?.clone();
+        (!check.is_ok()).then_some((s.provider, check))
+    };
+
+    // Offline, only local backends can still be reached
+    let Online(online) = use_context::<Online>();
+    let offline = move || !online() && !settings().flatten().is_some_and(|s| s.provider.is_local());
+
     // Determine if the chat input should be disabled
-    let disabled = use_resource(move || async move { !is_configured().unwrap_or(false) || busy() });
+    let disabled = use_resource(move || async move {
+        let blocked = busy() || offline();
+        !is_configured().unwrap_or(false) || blocked
+    });
 
     // Current streaming message content (for real-time display)
-    let mut streaming_msg: Signal<Option<String>> = use_signal(|| None);
+    let streaming_msg: Signal<Option<String>> = use_signal(|| None);
+
+    // Global MCP host, whose server states are persisted along with chats
+    let global_host = use_signal(consume_context::<Arc<MCPHost>>);
+
+    // Servers turned off for this chat are hidden from its toolset's host
+    let disabled_servers = use_memo(move || chat.read().disabled_servers.clone());
+    let _ = use_resource(move || async move {
+        let disabled = disabled_servers();
+        let host = toolset.read().get_mcp_host();
+        host.set_hidden_servers(disabled).await;
+    });
+    // Global servers the chat can choose from, refreshed when settings change
+    let server_ids = use_resource(move || async move {
+        let _ = settings();
+        global_host().server_ids().await
+    });
 
     // Use the extracted save_chat_to_storage utility function
     let save_chat = move || async move {
         let ts = &*toolset.read();
-        save_chat_to_storage(&mut chat, ts, &mut display, id, &nav).await
+        let host = global_host();
+        save_chat_to_storage(&mut chat, ts, &host, &mut display, id, &nav).await
     };
 
     // Flag to show warning when too many tool calls are made
@@ -190,6 +355,61 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
     // Error state for handling run_tools_loop errors
     let mut error_state: Signal<Option<String>> = use_signal(|| None);
 
+    // Turns run in a ChatSession whose events update these signals; the handle stops them
+    let running_turn = use_signal(|| false);
+    let cancel = use_signal(CancelHandle::default);
+    // Leaving the chat asks first while a turn runs, offering to stop it
+    use_guarded_turn(busy, cancel);
+    let session_signals = SessionSignals {
+        chat,
+        streaming_msg,
+        display,
+        running: running_turn,
+        send_status,
+        throughput,
+        timing: turn_timing,
+    };
+
+    // Summarizes changed chapters of a story in the background
+    let summarize_chapters = move || {
+        let (Some(Some(client)), Some(Some(model))) = (client(), model()) else {
+            return;
+        };
+        if chat.peek().chat_type != Toolsets::Story {
+            return;
+        }
+        let host = toolset.peek().get_mcp_host();
+        let chat_id = chat.peek().id;
+        spawn_job("Summarizing changed chapters", async move {
+            summaries::refresh_summaries(host, client, model, chat_id)
+                .await
+                .map(|_| ())
+        });
+    };
+    // Shows the summaries once they are saved
+    use_future(move || async move {
+        let mut generation = summaries::generation();
+        let mut changes = jobs::subscribe();
+        while changes.changed().await.is_ok() {
+            if summaries::generation() == generation
+                || chat.peek().chat_type != Toolsets::Story
+            {
+                continue;
+            }
+            generation = summaries::generation();
+            let host = toolset.peek().get_mcp_host();
+            match summaries::load_story(&host).await {
+                Ok(story) => {
+                    chat.with_mut(|c| {
+                        c.value = serde_json::to_value(&story).unwrap_or_default()
+                    });
+                    display.set(Some(CreativeWriterMcpServer::new(story).markdown()));
+                }
+                Err(e) => warn!("Could not reload the story: {e:?}"),
+            }
+        }
+    });
+
     // Main loop for handling LLM responses and tool execution using extracted utility
     let run_tools_loop_impl = move || async move {
         // Ensure we have all required components
@@ -205,132 +425,966 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
         };
 
         let ts = &*toolset.read();
+        let max_rounds = ts.max_tool_rounds();
+        let max_failures = settings()
+            .flatten()
+            .map_or(MAX_TOOL_FAILURES, |s| s.max_tool_failures);
 
         error_state.set(None);
-        let count = run_tools_loop(
+        let started = now_millis();
+        let result = run_tools_loop(
             &client,
             &model,
-            &mut chat,
-            ts,
-            &mut streaming_msg,
+            session_signals,
+            ts.as_ref(),
+            max_failures,
+            cancel(),
             save_chat,
         )
-        .await?;
+        .await;
+
+        // Let the user know when a long turn finishes while the window is in the background
+        if now_millis().saturating_sub(started) >= NOTIFY_AFTER_MILLIS {
+            let chat_id = chat.peek().id;
+            let body = if result.is_ok() {
+                "The assistant has finished responding."
+            } else {
+                "The assistant stopped with an error."
+            };
+            spawn(async move {
+                if notify_turn_finished("MCMCPCP", body).await
+                    && let Some(id) = chat_id
+                {
+                    nav.push(crate::Route::ChatEl {
+                        id,
+                        anchor: String::new(),
+                    });
+                }
+            });
+        }
+        let count = result?;
+
+        if settings()
+            .flatten()
+            .is_some_and(|s| s.auto_summarize_chapters)
+        {
+            summarize_chapters();
+        }
 
         // Handle tool count warning if too many tools were executed
-        if count >= 10 {
+        if count >= max_rounds {
             tool_count_warning.set(true);
         }
 
         Ok(count)
     };
 
+    // Plan-only mode: tool calls are shown for review instead of being run
+    let mut plan_only = use_signal(|| false);
+    let mut proposed: Signal<Vec<ToolCallDelta>> = use_signal(Vec::new);
+    // Incremented for each proposal so the review panel starts afresh
+    let mut proposal_round = use_signal(|| 0u32);
+
+    // Streams one response and keeps its tool calls for review
+    let propose_impl = move || async move {
+        let Some(Some(model)) = model() else {
+            return anyhow::Ok(());
+        };
+        let Some(Some(client)) = client() else {
+            return Ok(());
+        };
+        error_state.set(None);
+        let ts = &*toolset.read();
+        let calls = propose_tool_calls(
+            &client,
+            &model,
+            session_signals,
+            ts.as_ref(),
+            cancel(),
+            save_chat,
+        )
+        .await?;
+        proposal_round.set(proposal_round() + 1);
+        proposed.set(calls);
+        Ok(())
+    };
+
+    // Whether the chat's model can see images returned by tools
+    let accepts_images = move || async move {
+        let (Some(Some(model)), Some(Some(client))) = (model(), client()) else {
+            return false;
+        };
+        client.supports_images(&model).await
+    };
+
+    // Estimated size of the next request, before the message being typed
+    let request_tokens = use_resource(move || async move {
+        let messages = chat.read().messages.clone();
+        let ts = &*toolset.read();
+        let pruned = ts.prune_messages(&messages).await.unwrap_or(messages);
+        let messages = with_pinned(pruned, &chat.read().pinned_messages());
+        let messages = with_documents(messages, &chat.read().documents);
+        let messages = with_notes(messages, &notes(&chat.read().messages));
+        let messages = with_variables(messages, &chat.read().variables);
+        let tools = tools_to_message_objects(ts.get_mcp_host().list_tools().await);
+        Some(estimate_request_tokens(&messages, &tools))
+    });
+    let context_length = use_resource(move || async move {
+        let (Some(Some(model)), Some(Some(client))) = (model(), client()) else {
+            return None;
+        };
+        client.context_length(&model).await
+    });
+
+    // Continues the conversation in the current mode
+    let continue_turn = move || async move {
+        let res = if plan_only() {
+            propose_impl().await
+        } else {
+            run_tools_loop_impl().await.map(|_| ())
+        };
+        // The model just used comes first in the model switcher
+        if let Some(Some(s)) = settings() {
+            let mut settings_ctx = consume_context::<Signal<Option<AppSettings>>>();
+            if let Err(e) = remember_model(&mut settings_ctx, &s.provider).await {
+                warn!("Could not remember the model: {e:?}");
+            }
+        }
+        res
+    };
+
+    // Runs the selected proposed calls, then lets the model continue with the results
+    let execute_proposed = move |selected: Vec<bool>| async move {
+        let calls = proposed.take();
+        busy.set(true);
+        let host = toolset.read().get_mcp_host();
+        let images = accepts_images().await;
+        match crate::core::run_selected_tools(calls, &selected, host, images).await {
+            Ok(results) => {
+                chat.with_mut(|c| c.messages.extend(results));
+                if let Err(e) = continue_turn().await {
+                    error_state.set(Some(format!("Error during conversation: {}", e)));
+                }
+            }
+            Err(e) => error_state.set(Some(format!("Error during tool execution: {}", e))),
+        }
+        busy.set(false);
+    };
+
+    // Records every proposed call as declined, leaving the turn to the user
+    let mut decline_proposed = move || {
+        let calls = proposed.take();
+        let selected = vec![false; calls.len()];
+        async move {
+            let host = global_host();
+            let results = crate::core::run_selected_tools(calls, &selected, host, false).await;
+            if let Ok(results) = results {
+                chat.with_mut(|c| c.messages.extend(results));
+            }
+        }
+    };
+
+    // Compare mode: the chat's model and up to two more answer side by side
+    let mut compare = use_signal(|| false);
+    let mut compare_models: Signal<Vec<String>> = use_signal(Vec::new);
+    let mut candidates: Signal<Vec<Candidate>> = use_signal(Vec::new);
+    let mut comparing = use_signal(|| false);
+    // Known model names, suggested when choosing models to compare
+    let model_options = use_resource(move || async move {
+        if !compare() {
+            return vec![];
+        }
+        let Some(Some(client)) = client() else {
+            return vec![];
+        };
+        client.model_names().await.unwrap_or_default()
+    });
+
+    // Streams responses from all compared models at once for the user to choose from
+    let compare_impl = move || async move {
+        let Some(Some(model)) = model() else {
+            return anyhow::Ok(());
+        };
+        let Some(Some(client)) = client() else {
+            return Ok(());
+        };
+        let mut models = vec![model];
+        for m in compare_models() {
+            if !models.contains(&m) {
+                models.push(m);
+            }
+        }
+        candidates.set(
+            models
+                .iter()
+                .map(|m| Candidate {
+                    model: m.clone(),
+                    text: String::new(),
+                    tool_calls: vec![],
+                    error: None,
+                })
+                .collect(),
+        );
+        comparing.set(true);
+        let messages = chat.read().messages.clone();
+        let host = toolset.read().get_mcp_host();
+        let results =
+            crate::core::compare_responses(&client, &models, host, &messages, |i, text| {
+                let mut candidates = candidates;
+                if let Some(c) = candidates.write().get_mut(i) {
+                    c.text = text.to_string();
+                }
+            })
+            .await;
+        candidates.set(results);
+        comparing.set(false);
+        save_chat().await
+    };
+
+    // Keeps one compared response and continues the conversation with it
+    let pick_candidate = move |index: usize| async move {
+        let Some(candidate) = candidates.take().into_iter().nth(index) else {
+            return;
+        };
+        busy.set(true);
+        let author = MessageAuthor::model(&candidate.model);
+        let calls = chat.with_mut(|c| {
+            let from = c.messages.len();
+            let calls = crate::core::accept_candidate(&mut c.messages, candidate, &mut ());
+            c.set_author(from, &author);
+            calls
+        });
+        let res = async {
+            if calls.is_empty() {
+                return save_chat().await;
+            }
+            let selected = vec![true; calls.len()];
+            let host = toolset.read().get_mcp_host();
+            let images = accepts_images().await;
+            let results = crate::core::run_selected_tools(calls, &selected, host, images).await?;
+            chat.with_mut(|c| c.messages.extend(results));
+            continue_turn().await
+        }
+        .await;
+        if let Err(e) = res {
+            error_state.set(Some(format!("Error during conversation: {}", e)));
+        }
+        busy.set(false);
+    };
+
+    // Text handed back to the input, such as a message that failed to send
+    let mut draft: Signal<Option<String>> = use_signal(|| None);
+
+    // Answers the user message at an index, tracking whether the request got through
+    let deliver = move |index: usize| async move {
+        send_status.set(Some((index, SendStatus::Pending)));
+        // Start the LLM response and tool execution loop, or ask several models
+        let res = if compare() && !compare_models.read().is_empty() {
+            compare_impl().await
+        } else {
+            continue_turn().await
+        };
+        let pending = matches!(send_status(), Some((i, SendStatus::Pending)) if i == index);
+        match res {
+            // Nothing came back, so the error belongs to the message
+            Err(e) if pending => {
+                send_status.set(Some((index, SendStatus::Failed(format!("{e:#}")))))
+            }
+            Err(e) => error_state.set(Some(format!("Error during conversation: {}", e))),
+            Ok(()) if pending => send_status.set(Some((index, SendStatus::Sent))),
+            Ok(()) => {}
+        }
+    };
+
     // Handles sending a new user message and starting the conversation loop.
     //
     // Adds the user's message to the chat history and initiates the LLM
     // response and tool execution loop.
-    let send_msg = move |s: String| async move {
+    let send_msg = move |content: Vec<ContentPart>| async move {
         // Clear any previous errors
         error_state.set(None);
 
+        // A new message answers any calls still awaiting review
+        if !proposed.read().is_empty() {
+            decline_proposed().await;
+        }
+        // Responses not chosen by now are dropped
+        candidates.set(vec![]);
+
         // Add user message to chat history
-        chat.with_mut(|c| {
-            c.messages.push(Message::User {
-                content: vec![ContentPart::Text { text: s }],
-            });
+        let index = chat.with_mut(|c| {
+            c.messages.push(Message::User { content });
+            c.messages.len() - 1
         });
+        deliver(index).await;
+    };
 
-        // Start the LLM response and tool execution loop
-        if let Err(e) = run_tools_loop_impl().await {
-            error_state.set(Some(format!("Error during conversation: {}", e)));
+    // Sends a message that failed to send again
+    let retry_send = move |index: usize| async move {
+        if busy() {
+            return;
         }
+        busy.set(true);
+        error_state.set(None);
+        deliver(index).await;
+        busy.set(false);
     };
 
-    // Handle deletion of message groups
-    let delete_group = move |group_id: String| async move {
+    // Takes a message that failed to send out of the chat and back into the input
+    let edit_unsent = move |index: usize| async move {
+        if busy() || chat.read().messages.len() != index + 1 {
+            return;
+        }
+        let content = chat.with_mut(|c| {
+            c.set_pinned(index, false);
+            match c.messages.pop() {
+                Some(Message::User { content }) => content,
+                Some(other) => {
+                    c.messages.push(other);
+                    vec![]
+                }
+                None => vec![],
+            }
+        });
+        let text = content
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        send_status.set(None);
+        draft.set(Some(text));
+    };
+
+    // Switches the chat between the plain chat toolset and agent mode. The agent
+    // instructions are added to the chat's own system prompt and taken out again,
+    // and the plan is kept while agent mode is off, until the user clears it
+    let mut toggle_agent_mode = move |on: bool| {
+        let chat_type = if on { Toolsets::Agent } else { Toolsets::Chat };
+        let value = chat.read().value.clone();
+        let plan = serde_json::from_value::<Plan>(value.clone()).ok();
+        let (ts, value) = match (&plan, on) {
+            (None, true) => registry::create_new(&chat_type, global_host()),
+            _ => (registry::create(&chat_type, global_host(), &value), value),
+        };
         chat.with_mut(|c| {
-            // Find the group and remove all its messages
-            let groups = group_messages(&c.messages);
-            
-            // Find the group to delete
-            if let Some(target_group) = groups.iter().find(|g| g.group_id == group_id) {
-                let mut new_messages = Vec::new();
-                
-                // Keep all messages except those in the target group
-                for message in &c.messages {
-                    let mut should_keep = true;
-                    
-                    // Check if this is the assistant message from the target group
-                    if let Message::Assistant { content, tool_calls } = message {
-                        if let Message::Assistant { content: target_content, tool_calls: target_tool_calls } = &target_group.assistant_message {
-                            if content == target_content && tool_calls == target_tool_calls {
-                                should_keep = false;
-                            }
-                        }
+            let prompt = match c.messages.first() {
+                Some(Message::System { content }) => content.clone(),
+                _ => registry::create_new(&Toolsets::Chat, global_host()).0.get_system_prompt(),
+            };
+            let prompt = if on {
+                with_agent_instructions(&prompt)
+            } else {
+                without_agent_instructions(&prompt)
+            };
+            c.chat_type = chat_type;
+            c.value = value;
+            set_system_prompt(&mut c.messages, prompt);
+        });
+        display.set(on.then(|| plan.unwrap_or_default().to_markdown()));
+        toolset.set(ts);
+    };
+    // Starts agent mode over with an empty plan
+    let mut clear_plan = move || {
+        let (ts, value) = registry::create_new(&Toolsets::Agent, global_host());
+        chat.with_mut(|c| c.value = value);
+        display.set(Some(Plan::default().to_markdown()));
+        toolset.set(ts);
+    };
+
+    // Deletion of assistant turns, with their tool results; the last one can be undone
+    let mut undo: Signal<Option<RemovedTurn>> = use_signal(|| None);
+    let mut deletions = use_signal(|| 0u32);
+    let delete_turn = move |index: usize| async move {
+        let Some(removed) = chat.with_mut(|c| c.remove_turn(index)) else {
+            return;
+        };
+        undo.set(Some(removed));
+        deletions += 1;
+        let _ = save_chat().await;
+    };
+    let undo_delete = move |_| async move {
+        let Some(removed) = undo.take() else { return };
+        chat.with_mut(|c| c.restore_turn(removed));
+        let _ = save_chat().await;
+    };
+
+    // Replaces a turn's tool calls and results with a summary, archiving the originals
+    let summarize_turn = move |index: usize| async move {
+        let (Some(Some(client)), Some(Some(model))) = (client(), model()) else {
+            return;
+        };
+        busy.set(true);
+        let messages = chat.read().messages.clone();
+        match crate::core::summarize_turn(&client, &model, &messages, index).await {
+            // Skip the change if the conversation was edited in the meantime
+            Ok(summary) if chat.read().messages == messages => {
+                chat.with_mut(|c| {
+                    if let Some(removed) = c.replace_turn(index, summary) {
+                        c.archive.push(removed);
                     }
-                    
-                    // Check if this is a tool message from the target group
-                    if let Message::Tool { .. } = message {
-                        for tool_msg in &target_group.tool_messages {
-                            if tool_msg == message {
-                                should_keep = false;
-                                break;
-                            }
+                });
+                let _ = save_chat().await;
+            }
+            Ok(_) => {}
+            Err(e) => error_state.set(Some(format!("Could not summarize tool results: {e}"))),
+        }
+        busy.set(false);
+    };
+
+    // Replaces the chat's system prompt; it applies from the next request on
+    let save_system_prompt = move |prompt: String| async move {
+        chat.with_mut(|c| set_system_prompt(&mut c.messages, prompt));
+        let _ = save_chat().await;
+    };
+
+    // Pinned messages are sent with every request, however the conversation is shortened
+    let set_pinned = move |index: usize, pinned: bool| async move {
+        chat.with_mut(|c| c.set_pinned(index, pinned));
+        // A running turn saves the chat when it finishes
+        if !busy() {
+            let _ = save_chat().await;
+        }
+    };
+    let unpin = move |pin: PinEntry| async move {
+        chat.with_mut(|c| match pin.archived {
+            Some((turn, offset)) => c.unpin_archived(turn, offset),
+            None => c.set_pinned(pin.index, false),
+        });
+        if !busy() {
+            let _ = save_chat().await;
+        }
+    };
+
+    // Files dropped onto the chat become documents the model searches, each
+    // summarized by the model once stored
+    let mut document_status: Signal<Option<String>> = use_signal(|| None);
+    let attach_documents = move |engine: Arc<dyn FileEngine>| async move {
+        if busy() {
+            document_status.set(Some("Wait for the answer before attaching files.".into()));
+            return;
+        }
+        let mut added = vec![];
+        let mut errors = vec![];
+        for path in engine.files() {
+            // Desktop platforms give full paths; documents go by file name
+            let name = path.rsplit(['/', '\\']).next().unwrap_or(&path).to_string();
+            document_status.set(Some(format!("Reading {name}…")));
+            let result = match engine.read_file(&path).await {
+                Some(bytes) => document_from_file(&name, bytes),
+                None => Err(anyhow::anyhow!("{name} could not be read")),
+            };
+            match result {
+                Ok(document) => {
+                    chat.with_mut(|c| {
+                        c.documents.retain(|d| d.name != document.name);
+                        c.documents.push(document);
+                    });
+                    added.push(name);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        if added.is_empty() {
+            document_status.set((!errors.is_empty()).then(|| errors.join("; ")));
+            return;
+        }
+        let _ = save_chat().await;
+        if let (Some(Some(client)), Some(Some(model))) = (client(), model()) {
+            for name in &added {
+                document_status.set(Some(format!("Summarizing {name}…")));
+                let found = chat
+                    .read()
+                    .documents
+                    .iter()
+                    .find(|d| &d.name == name)
+                    .cloned();
+                let Some(document) = found else { continue };
+                match summarize_document(&client, &model, &document).await {
+                    Ok(summary) => chat.with_mut(|c| {
+                        if let Some(d) = c.documents.iter_mut().find(|d| &d.name == name) {
+                            d.summary = Some(summary);
                         }
-                    }
-                    
-                    if should_keep {
-                        new_messages.push(message.clone());
-                    }
+                    }),
+                    Err(e) => errors.push(format!("Could not summarize {name}: {e}")),
                 }
-                
-                c.messages = new_messages;
             }
+            if !busy() {
+                let _ = save_chat().await;
+            }
+        }
+        document_status.set((!errors.is_empty()).then(|| errors.join("; ")));
+    };
+    let remove_document = move |index: usize| async move {
+        chat.with_mut(|c| {
+            if index < c.documents.len() {
+                c.documents.remove(index);
+            }
+        });
+        if !busy() {
+            let _ = save_chat().await;
+        }
+    };
+
+    // Variables are read when a request is made, so they can change any time
+    let set_variables = move |variables: BTreeMap<String, String>| async move {
+        chat.with_mut(|c| c.variables = variables);
+        if !busy() {
+            let _ = save_chat().await;
+        }
+    };
+
+    // Swaps the story for a snapshot's, recording the restore as a new version
+    let restore_snapshot = move |snapshot: StorySnapshot| async move {
+        if busy() {
+            return;
+        }
+        let current = chat.read().clone();
+        if let Err(e) = crate::snapshots::record_restore(&current, &snapshot).await {
+            error_state.set(Some(format!("Could not restore the snapshot: {e}")));
+            return;
+        }
+        let story: Story = serde_json::from_value(snapshot.story).unwrap_or_else(|e| {
+            warn!("Invalid story in snapshot: {e:?}");
+            Default::default()
         });
-        
-        // Save the updated chat
+        toolset.set(Box::new(StoryWriter::new(story)));
+        let _ = save_chat().await;
+    };
+
+    // Side conversation with a character of the story, kept out of the chat
+    let mut interview: Signal<Option<Interview>> = use_signal(|| None);
+    let mut interviewing = use_signal(|| false);
+    let ask_character = move |question: String| async move {
+        let (Some(Some(client)), Some(Some(model))) = (client(), model()) else {
+            return;
+        };
+        let Some(mut current) = interview() else {
+            return;
+        };
+        interviewing.set(true);
+        match current.ask(&client, &model, question).await {
+            // Skip the reply if the interview was ended in the meantime
+            Ok(())
+                if interview.read().as_ref().map(|i| &i.character)
+                    == Some(&current.character) =>
+            {
+                interview.set(Some(current));
+            }
+            Ok(()) => {}
+            Err(e) => {
+                error_state.set(Some(format!("{} did not answer: {e}", current.character)))
+            }
+        }
+        interviewing.set(false);
+    };
+    let save_interview = move || async move {
+        let Some(current) = interview() else {
+            return;
+        };
+        let host = toolset.read().get_mcp_host();
+        let res = host
+            .tool_call(
+                "creative_writer",
+                "add_character_note",
+                serde_json::json!({
+                    "character_name": current.character,
+                    "note": current.transcript(),
+                }),
+            )
+            .await;
+        match res {
+            Ok(tr) if tr.is_error != Some(true) => {
+                interview.set(None);
+                let _ = save_chat().await;
+            }
+            Ok(tr) => {
+                let text: String = tr.content.into_iter().filter_map(|c| c.text).collect();
+                error_state.set(Some(format!("Could not save the interview: {text}")));
+            }
+            Err(e) => error_state.set(Some(format!("Could not save the interview: {e}"))),
+        }
+    };
+
+    // Cast and turn order of a role-play, changed from the controls
+    let set_scene = move |scene: Scene| async move {
+        chat.with_mut(|c| c.value = serde_json::to_value(&scene).unwrap_or_default());
+        display.set(Some(scene.to_markdown()));
+        toolset.set(Box::new(RoleplayTools::new(global_host(), scene)));
         let _ = save_chat().await;
     };
+    let scene =
+        move || serde_json::from_value::<Scene>(chat.read().value.clone()).unwrap_or_default();
+    // Has a persona reply now, without a message from the user
+    let continue_as = move |name: String| async move {
+        if busy() {
+            return;
+        }
+        set_scene(Scene {
+            next: Some(name),
+            ..scene()
+        })
+        .await;
+        busy.set(true);
+        error_state.set(None);
+        candidates.set(vec![]);
+        if let Err(e) = continue_turn().await {
+            error_state.set(Some(format!("Error during conversation: {}", e)));
+        }
+        busy.set(false);
+    };
+    // The model writes the user's next message into the input, for them to edit
+    let mut drafting = use_signal(|| false);
+    let impersonate = move || async move {
+        let (Some(Some(client)), Some(Some(model))) = (client(), model()) else {
+            return;
+        };
+        drafting.set(true);
+        let messages = chat.read().messages.clone();
+        match roleplay::impersonate(&client, &model, &scene(), &messages).await {
+            Ok(text) => draft.set(Some(text)),
+            Err(e) => error_state.set(Some(format!("Could not draft your message: {e}"))),
+        }
+        drafting.set(false);
+    };
+
+    // Sets the model of this chat, or with `None` goes back to the settings' model
+    let set_chat_model = move |model: Option<ModelChoice>| async move {
+        chat.with_mut(|c| c.model = model);
+        let _ = save_chat().await;
+    };
+
+    // Turns servers on or off for this chat; it applies from the next request on
+    let save_disabled_servers = move |disabled: BTreeSet<String>| async move {
+        chat.with_mut(|c| c.disabled_servers = disabled);
+        let _ = save_chat().await;
+    };
+
+    // Output of the last slash command, shown above the input until dismissed
+    let mut command_output: Signal<Option<String>> = use_signal(|| None);
+    let commands = use_memo(move || {
+        let mut commands = builtin_commands();
+        commands.extend(toolset.read().commands());
+        commands
+    });
+
+    // Runs a slash command typed in the chat input. Commands that are not built
+    // in belong to the toolset.
+    let run_command = move |name: String, args: String| async move {
+        command_output.set(None);
+        let res: anyhow::Result<Option<String>> = async {
+            match name.as_str() {
+                "model" if args.is_empty() => Ok(Some(match model().flatten() {
+                    Some(m) => format!("Using **{m}**"),
+                    None => "No model is selected".to_string(),
+                })),
+                "model" => {
+                    let mut settings_ctx = consume_context::<Signal<Option<AppSettings>>>();
+                    let effective = switch_model(&mut settings_ctx, &args).await?;
+                    Ok(Some(match effective {
+                        Some(m) if m == args => format!("Switched to **{m}**"),
+                        m => format!(
+                            "Saved, but an override keeps the model at **{}**",
+                            m.unwrap_or_default()
+                        ),
+                    }))
+                }
+                "clear" => {
+                    let chat_type = chat.read().chat_type.clone();
+                    if id().is_some() {
+                        nav.push(match chat_type {
+                            Toolsets::Story => crate::Route::NewStory {},
+                            Toolsets::Coding | Toolsets::Roleplay | Toolsets::Custom(_) => crate::Route::NewToolsetChat {
+                                toolset: chat_type,
+                            },
+                            _ => crate::Route::NewChat {},
+                        });
+                        return Ok(None);
+                    }
+                    // Already a new chat, which navigating would not reset
+                    let (ts, value) = registry::create_new(&chat_type, global_host());
+                    display.set(ts.get_markdown_repr().await);
+                    chat.with_mut(|c| {
+                        c.messages.truncate(1);
+                        c.value = value;
+                        c.archive.clear();
+                        c.authors.clear();
+                        c.pinned.clear();
+                    });
+                    toolset.set(ts);
+                    candidates.set(vec![]);
+                    undo.set(None);
+                    Ok(None)
+                }
+                "export" => {
+                    let c = chat.read().clone();
+                    let html = chat_to_html(&c).await;
+                    let path = save_html(&export_file_name(&c), &html).await?;
+                    Ok(Some(format!("Exported to `{path}`")))
+                }
+                "tools" => {
+                    let host = toolset.read().get_mcp_host();
+                    let tools = host.list_tools().await;
+                    if tools.is_empty() {
+                        return Ok(Some("No tools are available".to_string()));
+                    }
+                    let list: Vec<String> = tools
+                        .iter()
+                        .map(|t| {
+                            let description = t.tool.description.as_deref().unwrap_or_default();
+                            let summary = description.lines().next().unwrap_or_default();
+                            format!("- `{}` **{}** — {summary}", t.server_id, t.tool.name)
+                        })
+                        .collect();
+                    Ok(Some(list.join("\n")))
+                }
+                "prompt" if args.is_empty() => {
+                    let host = toolset.read().get_mcp_host();
+                    let prompts = host.list_prompts().await;
+                    if prompts.is_empty() {
+                        return Ok(Some(
+                            "The connected servers offer no prompt templates".to_string(),
+                        ));
+                    }
+                    let list: Vec<String> = prompts
+                        .iter()
+                        .map(|p| {
+                            let arguments: String = p
+                                .prompt
+                                .arguments
+                                .iter()
+                                .map(|a| {
+                                    let optional = if a.required { "" } else { "?" };
+                                    format!(" {}{optional}=…", a.name)
+                                })
+                                .collect();
+                            let description = p.prompt.description.as_deref().unwrap_or_default();
+                            format!(
+                                "- `/prompt {}/{}{arguments}` — {description}",
+                                p.server_id, p.prompt.name
+                            )
+                        })
+                        .collect();
+                    Ok(Some(list.join("\n")))
+                }
+                "prompt" => {
+                    let (prompt, rest) =
+                        args.split_once(char::is_whitespace).unwrap_or((&args, ""));
+                    let Some((server_id, prompt)) = prompt.split_once('/') else {
+                        anyhow::bail!("Name the prompt as server/prompt; /prompt lists them");
+                    };
+                    let arguments = parse_prompt_args(rest)?;
+                    let host = toolset.read().get_mcp_host();
+                    let text = prompt_text(&host.get_prompt(server_id, prompt, arguments).await?);
+                    if text.trim().is_empty() {
+                        anyhow::bail!("The prompt has no text to send");
+                    }
+                    busy.set(true);
+                    send_msg(vec![ContentPart::Text { text }]).await;
+                    busy.set(false);
+                    Ok(None)
+                }
+                _ => {
+                    let ts = &*toolset.read();
+                    Ok(Some(ts.run_command(&name, &args).await?))
+                }
+            }
+        }
+        .await;
+        match res {
+            Ok(output) => command_output.set(output),
+            Err(e) => command_output.set(Some(format!("**/{name} failed:** {e}"))),
+        }
+    };
+
+    // Auto-scroll state: follow new content while the view is pinned to the bottom,
+    // and count messages that arrive while the user has scrolled up.
+    let mut pinned_to_bottom = use_signal(|| true);
+    let mut bottom_anchor: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
+    let mut seen_messages = use_signal(|| 0usize);
+    let mut unread = use_signal(|| 0usize);
+    use_effect(move || {
+        let count = chat.read().messages.len();
+        let _ = streaming_msg.read(); // also follow the live stream
+        let anchor = bottom_anchor();
+        if *pinned_to_bottom.peek() {
+            seen_messages.set(count);
+            unread.set(0);
+            if let Some(anchor) = anchor {
+                spawn(async move {
+                    let _ = anchor.scroll_to(ScrollBehavior::Instant).await;
+                });
+            }
+        } else {
+            unread.set(count.saturating_sub(*seen_messages.peek()));
+        }
+    });
+    let handle_scroll = move |e: Event<ScrollData>| {
+        let distance = e.scroll_height() - e.scroll_top() - e.client_height();
+        let at_bottom = distance < 48;
+        if *pinned_to_bottom.peek() != at_bottom {
+            pinned_to_bottom.set(at_bottom);
+        }
+        if at_bottom && *unread.peek() > 0 {
+            seen_messages.set(chat.peek().messages.len());
+            unread.set(0);
+        }
+    };
+    let jump_to_latest = move |_| {
+        pinned_to_bottom.set(true);
+        seen_messages.set(chat.peek().messages.len());
+        unread.set(0);
+        if let Some(anchor) = bottom_anchor() {
+            spawn(async move {
+                let _ = anchor.scroll_to(ScrollBehavior::Smooth).await;
+            });
+        }
+    };
 
     // Renders the currently streaming message if one exists.
     //
     // Shows real-time LLM responses as they're being generated,
-    // with proper Markdown rendering.
+    // with incremental Markdown rendering of the trailing block.
     let stream_output: Option<Element> = streaming_msg().map(move |m| {
         rsx! {
-            div { class: "message ai-message", {crate::md2rsx::markdown_to_rsx(&m)} }
+            div { class: "message ai-message",
+                crate::md2rsx::StreamingMarkdownEl { text: m }
+                if let Some(rate) = throughput() {
+                    ThroughputLine { rate }
+                }
+            }
         }
     });
     let display = display.cloned();
     let chat_class = if display.is_some() { "small" } else { "large" };
+    // On narrow screens only one of the chat and its panel is shown
+    let shown = if display.is_some() {
+        pane()
+    } else {
+        Pane::Chat
+    };
+    let panel_label = registry::entry_or_chat(&chat.read().chat_type).panel_label;
 
     // Render the main chat interface
     rsx! {
-        div { class: "content {chat_class}",
+        div {
+            class: "content {chat_class}",
+            ontouchstart: on_swipe_start,
+            ontouchend: on_swipe_end,
+            if display.is_some() {
+                PaneTabs { pane, panel_label }
+            }
             div {
-                class: "chat {chat_class}",
+                class: "chat {chat_class} {Pane::Chat.class(shown)}",
                 style: "
                 display: flex;
                 flex-direction: column;
                 ",
-                div { style: "
+                // Dropping files attaches them as documents
+                ondragover: move |e: Event<DragData>| e.prevent_default(),
+                ondrop: move |e: Event<DragData>| async move {
+                    e.prevent_default();
+                    if let Some(engine) = e.files() {
+                        attach_documents(engine).await;
+                    }
+                },
+                div {
+                    style: "
                     flex-grow: 1;
                     overflow: auto;
                     ",
+                    onscroll: handle_scroll,
+                    DocumentsPanel {
+                        documents: chat.read().documents.clone(),
+                        status: document_status(),
+                        on_remove: move |index: usize| async move {
+                            remove_document(index).await;
+                        },
+                    }
+                    VariablesPanel {
+                        variables: chat.read().variables.clone(),
+                        placeholders: conversation_placeholders(&chat.read().messages),
+                        on_change: move |variables| async move {
+                            set_variables(variables).await;
+                        },
+                    }
+                    PinsPanel {
+                        pins: pin_entries(&chat.read()),
+                        on_unpin: move |pin: PinEntry| async move {
+                            unpin(pin).await;
+                        },
+                    }
                     // Group messages and render them
                     {
                         let messages = &chat.read().messages;
+                        let authors = chat.read().authors.clone();
+                        let pinned = chat.read().pinned.clone();
+                        let ids: Vec<u32> = (0..messages.len()).map(|i| chat.read().message_id(i)).collect();
+                        // When each message was added, shown on hover
+                        let now = now_millis();
+                        let times: Vec<Option<String>> = (0..messages.len())
+                            .map(|i| chat.read().message_time(i).map(|t| time_ago(t, now)))
+                            .collect();
+                        let chat_id = chat.read().id;
                         let groups = group_messages(messages);
+                        // Tool results of turns before the latest user message start collapsed
+                        let current_turn = turn_start(messages, messages.len());
                         let mut rendered_messages = Vec::new();
+                        let prompt = match messages.first() {
+                            Some(Message::System { content }) => content.clone(),
+                            _ => String::new(),
+                        };
+                        rendered_messages.push(rsx! {
+                            SystemPromptEditor {
+                                prompt,
+                                default_prompt: toolset.read().get_system_prompt(),
+                                disabled: busy(),
+                                on_save: move |prompt: String| async move {
+                                    save_system_prompt(prompt).await;
+                                },
+                            }
+                        });
                         
-                        for message in messages {
+                        for (index, message) in messages.iter().enumerate() {
                             match message {
+                                // Shown in the editor above
+                                Message::System { .. } if index == 0 => {}
                                 Message::System { .. } | Message::User { .. } => {
+                                    let status = match send_status.read().as_ref() {
+                                        Some((i, status)) if *i == index => Some(status.clone()),
+                                        _ => None,
+                                    };
                                     // Render system and user messages normally
                                     rendered_messages.push(rsx! {
-                                        MessageEl { msg: message.clone() }
+                                        div { id: message_anchor(ids[index]), style: "position: relative;", title: times[index].clone(),
+                                            if let Some(chat_id) = chat_id {
+                                                MessageLinkButton { chat_id, message_id: ids[index] }
+                                            }
+                                            PinButton {
+                                                pinned: pinned.contains(&ids[index]),
+                                                on_toggle: move |pin: bool| async move {
+                                                    set_pinned(index, pin).await;
+                                                },
+                                            }
+                                            MessageEl { msg: message.clone() }
+                                            if let Some(status) = status {
+                                                SendStatusLine {
+                                                    status,
+                                                    on_retry: move |_| async move {
+                                                        retry_send(index).await;
+                                                    },
+                                                    on_edit: move |_| async move {
+                                                        edit_unsent(index).await;
+                                                    },
+                                                }
+                                            }
+                                        }
                                     });
                                 }
+                                // Scratchpad notes are shown in their own panel
+                                Message::Assistant { .. } if is_scratchpad_only(message) => {}
                                 Message::Assistant { .. } => {
                                     // Find the group this assistant message belongs to
                                     if let Some(group) = groups.iter().find(|g| {
@@ -347,12 +1401,35 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                                         // Render as a grouped message
                                         let group_clone = group.clone();
                                         rendered_messages.push(rsx! {
-                                            MessageGroupEl {
-                                                group: group_clone,
-                                                show_delete: true,
-                                                on_delete: EventHandler::new(move |group_id: String| async move {
-                                                    delete_group(group_id).await;
-                                                })
+                                            div { id: message_anchor(ids[index]), style: "position: relative;", title: times[index].clone(),
+                                                if let Some(chat_id) = chat_id {
+                                                    MessageLinkButton { chat_id, message_id: ids[index] }
+                                                }
+                                                PinButton {
+                                                    pinned: pinned.contains(&ids[index]),
+                                                    on_toggle: move |pin: bool| async move {
+                                                        set_pinned(index, pin).await;
+                                                    },
+                                                }
+                                                MessageGroupEl {
+                                                    group: group_clone,
+                                                    show_delete: true,
+                                                    collapse_results: index < current_turn,
+                                                    author: authors.get(&ids[index]).cloned(),
+                                                    sources: match message {
+                                                        // Answers list the sources fetched since the user's message
+                                                        Message::Assistant { tool_calls, .. } if tool_calls.as_ref().is_none_or(|c| c.is_empty()) => {
+                                                            turn_sources(&messages[turn_start(messages, index)..index])
+                                                        }
+                                                        _ => vec![],
+                                                    },
+                                                    on_delete: EventHandler::new(move |_group_id: String| async move {
+                                                        delete_turn(index).await;
+                                                    }),
+                                                    on_summarize: (!busy()).then(|| EventHandler::new(move |_| async move {
+                                                        summarize_turn(index).await;
+                                                    })),
+                                                }
                                             }
                                         });
                                     } else {
@@ -385,9 +1462,57 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                         }
                     }
 
+                    ScratchpadPanel { notes: notes(&chat.read().messages) }
+
                     // Show streaming message if one is being generated
                     {stream_output}
 
+                    if let (false, Some(timing)) = (running_turn(), turn_timing()) {
+                        TurnTimingLine { timing }
+                    }
+
+                    if running_turn() {
+                        LiveToolOutput {}
+                        if let Some(session) = toolset.read().review_session() {
+                            PatchReview { session }
+                        }
+                        div { style: "margin: 0 1em;",
+                            button {
+                                title: "Stop the response; tool calls not yet run are skipped",
+                                onclick: move |_| cancel.read().cancel(),
+                                "■ Stop"
+                            }
+                        }
+                    }
+
+                    // Responses of compared models, waiting for the user's pick
+                    if !candidates.read().is_empty() {
+                        CompareResponses {
+                            candidates: candidates(),
+                            streaming: comparing(),
+                            on_pick: move |i: usize| async move {
+                                pick_candidate(i).await;
+                            },
+                            on_discard: move |_| candidates.set(vec![]),
+                        }
+                    }
+
+                    // Tool calls awaiting review in plan-only mode
+                    if !proposed.read().is_empty() {
+                        ProposedCalls {
+                            key: "{proposal_round}",
+                            calls: proposed(),
+                            disabled: busy(),
+                            on_execute: move |selected: Vec<bool>| async move {
+                                execute_proposed(selected).await;
+                            },
+                            on_discard: move |_| async move {
+                                decline_proposed().await;
+                                let _ = save_chat().await;
+                            },
+                        }
+                    }
+
                     // Show tool count warning if too many tools have been executed
                     if tool_count_warning() {
                         div { style: "
@@ -397,7 +1522,7 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                             padding: 1em;
                             margin: 1em 0;
                             ",
-                            "10 tool calls have been made without user intervention."
+                            "{toolset.read().max_tool_rounds()} tool calls have been made without user intervention."
                             div { style: "margin-top: 0.5em;",
                                 button {
                                     style: "margin-right: 0.5em;",
@@ -422,6 +1547,35 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                         }
                     }
 
+                    // Warn before OpenRouter credits run out mid-reply
+                    if let Some(api_key) = openrouter_key() {
+                        CreditsWarning { api_key, turns: chat.read().messages.len() }
+                    }
+
+                    // Warn while the last connection test of the provider failed
+                    if let Some((provider, check)) = failed_check() {
+                        ProviderDownWarning { provider, check }
+                    }
+
+                    // Ctrl+M switches between recently used models
+                    ModelSwitcher {
+                        chat_model: chat_model(),
+                        on_chat_model: move |model: Option<ModelChoice>| async move {
+                            set_chat_model(model).await;
+                        },
+                    }
+
+                    // Keep the local model loaded so replies do not wait for it
+                    if let Some((api_url, model, keep_alive)) = ollama_model() {
+                        ModelLoadedIndicator {
+                            api_url,
+                            model,
+                            keep_alive,
+                            chat_id: chat.read().id,
+                            turns: chat.read().messages.len(),
+                        }
+                    }
+
                     // Show error message if there's an error
                     if let Some(error_msg) = error_state() {
                         div { style: "
@@ -444,7 +1598,7 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                                     onclick: move |_| async move {
                                         error_state.set(None);
                                         // Clear any previous errors and retry
-                                        if let Err(e) = run_tools_loop_impl().await {
+                                        if let Err(e) = continue_turn().await {
                                             error_state.set(Some(format!("Error during retry: {}", e)));
                                         }
                                     },
@@ -460,15 +1614,178 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
                             }
                         }
                     }
+
+                    // Scroll target marking the end of the transcript
+                    div {
+                        onmounted: move |e: Event<MountedData>| {
+                            bottom_anchor.set(Some(e.data()));
+                        },
+                    }
+
+                    // Offer a way back down when scrolled away from the latest content
+                    if !pinned_to_bottom() {
+                        JumpToLatest {
+                            unread: unread(),
+                            streaming: streaming_msg.read().is_some(),
+                            onclick: jump_to_latest,
+                        }
+                    }
+
+                    // Undo for the last deleted turn
+                    if let Some(removed) = undo() {
+                        Snackbar {
+                            key: "{deletions}",
+                            message: format!("Deleted {} messages", removed.messages.len()),
+                            action: "Undo",
+                            on_action: undo_delete,
+                            on_dismiss: move |_| undo.set(None),
+                        }
+                    }
                 }
                 // Fixed chat input area at the bottom
                 div { style: "
                     flex-grow: 0;
                     padding: 1.5em;
                     ",
+                    if let Some(output) = command_output() {
+                        div { style: "
+                            position: relative;
+                            max-height: 40vh;
+                            overflow: auto;
+                            margin-bottom: 0.75em;
+                            padding: 0 2em 0 1em;
+                            background: #f8f9fa;
+                            border: 1px solid #dee2e6;
+                            border-radius: 4px;
+                            font-size: 0.9em;
+                            ",
+                            button {
+                                style: "position: absolute; top: 0.5em; right: 0.5em; border: none; background: none; cursor: pointer;",
+                                title: "Dismiss",
+                                onclick: move |_| command_output.set(None),
+                                "✕"
+                            }
+                            {crate::md2rsx::markdown_to_rsx(&output)}
+                        }
+                    }
+                    if !matches!(
+                        chat.read().chat_type,
+                        Toolsets::Story | Toolsets::Coding | Toolsets::Roleplay
+                    ) {
+                        label {
+                            style: "
+                            display: inline-flex;
+                            align-items: center;
+                            gap: 0.5em;
+                            margin-bottom: 0.5em;
+                            font-size: 0.9em;
+                            ",
+                            title: "Plan first, track progress in the side panel, and run longer without pausing",
+                            input {
+                                r#type: "checkbox",
+                                checked: chat.read().chat_type == Toolsets::Agent,
+                                disabled: busy(),
+                                oninput: move |e: Event<FormData>| toggle_agent_mode(e.checked()),
+                            }
+                            "Agent mode"
+                        }
+                        if chat.read().chat_type == Toolsets::Agent
+                            && serde_json::from_value::<Plan>(chat.read().value.clone())
+                                .is_ok_and(|p| p != Plan::default())
+                        {
+                            button {
+                                style: "margin: 0 0 0.5em 0.5em; font-size: 0.8em;",
+                                disabled: busy(),
+                                title: "Forget the plan, so the agent starts a new one",
+                                onclick: move |_| clear_plan(),
+                                "Clear plan"
+                            }
+                        }
+                    }
+                    label {
+                        style: "
+                        display: inline-flex;
+                        align-items: center;
+                        gap: 0.5em;
+                        margin: 0 0 0.5em 1em;
+                        font-size: 0.9em;
+                        ",
+                        title: "Show the tool calls the model wants to make and choose which to run",
+                        input {
+                            r#type: "checkbox",
+                            checked: plan_only(),
+                            oninput: move |e: Event<FormData>| plan_only.set(e.checked()),
+                        }
+                        "Plan only"
+                    }
+                    label {
+                        style: "
+                        display: inline-flex;
+                        align-items: center;
+                        gap: 0.5em;
+                        margin: 0 0 0.5em 1em;
+                        font-size: 0.9em;
+                        ",
+                        title: "Ask up to three models at once and keep the best response",
+                        input {
+                            r#type: "checkbox",
+                            checked: compare(),
+                            oninput: move |e: Event<FormData>| compare.set(e.checked()),
+                        }
+                        "Compare models"
+                    }
+                    if chat.read().chat_type != Toolsets::Story {
+                        ChatServers {
+                            servers: server_ids().unwrap_or_default(),
+                            disabled: disabled_servers(),
+                            on_change: move |d: BTreeSet<String>| async move {
+                                save_disabled_servers(d).await;
+                            },
+                        }
+                    }
+                    if compare() {
+                        CompareModels {
+                            models: compare_models(),
+                            options: model_options().unwrap_or_default(),
+                            on_change: move |m: Vec<String>| compare_models.set(m),
+                        }
+                    }
+                    ReplyOptions {
+                        stop: stop_sequences(),
+                        prefill: prefill(),
+                        on_stop_change: move |s: Vec<String>| stop_sequences.set(s),
+                        on_prefill_change: move |p: String| prefill.set(p),
+                        local: settings().flatten().is_some_and(|s| s.provider.is_local()),
+                        constraint: constraint(),
+                        on_constraint_change: move |c: Option<Constraint>| constraint.set(c),
+                    }
+                    if chat.read().chat_type == Toolsets::Roleplay {
+                        TurnOrder {
+                            scene: scene(),
+                            disabled: disabled().unwrap_or(true),
+                            drafting: drafting(),
+                            on_next: move |next: Option<String>| async move {
+                                set_scene(Scene { next, ..scene() }).await;
+                            },
+                            on_continue: move |name: String| async move {
+                                continue_as(name).await;
+                            },
+                            on_impersonate: move |_| async move {
+                                impersonate().await;
+                            },
+                        }
+                    }
                     ChatInput {
                         disabled: disabled().unwrap_or(true),
-                        on_send: Callback::new(move |s: String| async move {
+                        request_tokens: request_tokens().flatten(),
+                        context_length: context_length().flatten(),
+                        commands: commands(),
+                        draft: draft(),
+                        history: crate::input_history::chat_prompts(&chat.read().messages),
+                        on_command: move |(name, args): (String, String)| async move {
+                            run_command(name, args).await;
+                        },
+                        on_send: Callback::new(move |s: Vec<ContentPart>| async move {
                             // Prevent multiple concurrent requests
                             {
                                 if busy() {
@@ -478,6 +1795,7 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
 
                             // Process the message
                             {
+                                draft.set(None);
                                 busy.set(true);
                                 send_msg(s).await;
                                 busy.set(false);
@@ -488,10 +1806,71 @@ pub fn Home(id: Signal<Option<u32>>, chat_type: Toolsets) -> Element {
             }
             if let Some(d) = display {
                 div {
-                    class: "tool-display",
+                    class: "tool-display {Pane::Panel.class(shown)}",
                     style: "
                     overflow: auto;
                     ",
+                    if let (Some(id), Toolsets::Story) = (id(), chat.read().chat_type.clone()) {
+                        div { style: "text-align: right; font-size: 0.9em;",
+                            Link {
+                                to: crate::Route::ReadStory { id },
+                                title: "Read the story without the chat",
+                                "📖 Reading view"
+                            }
+                        }
+                        ChapterActions {
+                            story: serde_json::from_value::<Story>(chat.read().value.clone())
+                                .unwrap_or_default(),
+                            disabled: busy() || disabled().unwrap_or(true),
+                            on_action: move |prompt: String| async move {
+                                if busy() {
+                                    return;
+                                }
+                                busy.set(true);
+                                send_msg(vec![ContentPart::Text { text: prompt }]).await;
+                                busy.set(false);
+                            },
+                            on_update_summaries: move |_| summarize_chapters(),
+                        }
+                        CharacterInterview {
+                            story: serde_json::from_value::<Story>(chat.read().value.clone())
+                                .unwrap_or_default(),
+                            interview: interview(),
+                            waiting: interviewing(),
+                            disabled: disabled().unwrap_or(true),
+                            on_start: move |name: String| {
+                                let story = serde_json::from_value::<Story>(chat.read().value.clone())
+                                    .unwrap_or_default();
+                                interview.set(Interview::start(&story, &name));
+                            },
+                            on_ask: move |question: String| async move {
+                                ask_character(question).await;
+                            },
+                            on_save: move |_| async move {
+                                save_interview().await;
+                            },
+                            on_close: move |_| interview.set(None),
+                        }
+                        ContinuityIssues {
+                            story: serde_json::from_value::<Story>(chat.read().value.clone())
+                                .unwrap_or_default(),
+                        }
+                        StorySnapshots {
+                            chat_id: id,
+                            story: chat.read().value.clone(),
+                            on_restore: move |s: StorySnapshot| async move {
+                                restore_snapshot(s).await;
+                            },
+                        }
+                    }
+                    if chat.read().chat_type == Toolsets::Roleplay {
+                        CastEditor {
+                            scene: scene(),
+                            on_change: move |s: Scene| async move {
+                                set_scene(s).await;
+                            },
+                        }
+                    }
                     {crate::md2rsx::markdown_to_rsx(&d)}
                 }
             }
@@ -0,0 +1,189 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+
+use crate::{
+    mcp::{ServerSpec, parse_external_servers},
+    ui::args_editor::join_args,
+};
+
+/// Form for importing MCP servers from a Claude Desktop / VS Code style JSON config.
+///
+/// The pasted JSON is parsed into a list of servers to pick from. Servers whose ID
+/// is already configured are shown but cannot be selected, so imports never
+/// overwrite existing definitions.
+#[component]
+pub fn ImportServers(
+    existing: Vec<ServerSpec>,
+    on_import: Callback<Vec<ServerSpec>, ()>,
+    on_cancel: Callback<(), ()>,
+) -> Element {
+    let mut json_text = use_signal(String::new);
+    let mut parsed = use_signal(Vec::<ServerSpec>::new);
+    let mut selected = use_signal(HashSet::<String>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let existing_ids: HashSet<String> = existing.iter().map(|s| s.id.clone()).collect();
+
+    let ids = existing_ids.clone();
+    let parse = move |_| match parse_external_servers(&json_text()) {
+        Ok(specs) => {
+            if specs.is_empty() {
+                error.set(Some("No stdio servers found in this JSON".into()));
+            } else {
+                error.set(None);
+            }
+            selected.set(
+                specs
+                    .iter()
+                    .filter(|s| !ids.contains(&s.id))
+                    .map(|s| s.id.clone())
+                    .collect(),
+            );
+            parsed.set(specs);
+        }
+        Err(e) => {
+            error.set(Some(format!("Could not parse JSON: {e}")));
+            parsed.set(vec![]);
+        }
+    };
+
+    let import = move |_| {
+        let chosen: Vec<ServerSpec> = parsed()
+            .into_iter()
+            .filter(|s| selected.read().contains(&s.id))
+            .collect();
+        if !chosen.is_empty() {
+            on_import(chosen);
+        }
+    };
+
+    let selected_count = selected.read().len();
+
+    rsx! {
+        div { style: "
+                border: 1px solid #007bff;
+                border-radius: 4px;
+                padding: 1rem;
+                margin-bottom: 0.5rem;
+                background: #f8f9fa;
+            ",
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;",
+                "Paste an mcpServers JSON block"
+            }
+            textarea {
+                style: "
+                    width: 100%;
+                    min-height: 8rem;
+                    padding: 0.5rem;
+                    border: 1px solid #ddd;
+                    border-radius: 3px;
+                    box-sizing: border-box;
+                    font-family: monospace;
+                    font-size: 0.85em;
+                ",
+                value: json_text(),
+                placeholder: r#"{{ "mcpServers": {{ "git": {{ "command": "uvx", "args": ["mcp-server-git"] }} }} }}"#,
+                oninput: move |e| {
+                    json_text.set(e.value());
+                    parsed.set(vec![]);
+                },
+            }
+            if let Some(err) = error() {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;", "{err}" }
+            }
+
+            if !parsed().is_empty() {
+                div { style: "margin-top: 1rem;",
+                    for server in parsed() {
+                        {
+                            let exists = existing_ids.contains(&server.id);
+                            let checked = selected.read().contains(&server.id);
+                            let id = server.id.clone();
+                            let cmdline = format!("{} {}", server.cmd, join_args(&server.args));
+                            let opacity = if exists { "0.6" } else { "1" };
+                            rsx! {
+                                label {
+                                    key: "{server.id}",
+                                    style: "
+                                        display: flex;
+                                        align-items: center;
+                                        gap: 0.5rem;
+                                        padding: 0.25rem;
+                                        opacity: {opacity};
+                                    ",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked,
+                                        disabled: exists,
+                                        oninput: move |e: Event<FormData>| {
+                                            if e.checked() {
+                                                selected.write().insert(id.clone());
+                                            } else {
+                                                selected.write().remove(&id);
+                                            }
+                                        },
+                                    }
+                                    span { style: "font-weight: bold;", "{server.id}" }
+                                    span { style: "font-family: monospace; font-size: 0.8em; color: #666;",
+                                        "{cmdline}"
+                                    }
+                                    if exists {
+                                        span { style: "font-size: 0.8em; color: #856404;", "(already configured)" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { style: "display: flex; gap: 0.5rem; justify-content: flex-end; margin-top: 1rem;",
+                button {
+                    style: "
+                        background: #6c757d;
+                        color: white;
+                        border: none;
+                        padding: 0.5rem 1rem;
+                        border-radius: 3px;
+                        cursor: pointer;
+                    ",
+                    onclick: move |_| {
+                        on_cancel(());
+                    },
+                    "Cancel"
+                }
+                if parsed().is_empty() {
+                    button {
+                        style: "
+                            background: #007bff;
+                            color: white;
+                            border: none;
+                            padding: 0.5rem 1rem;
+                            border-radius: 3px;
+                            cursor: pointer;
+                        ",
+                        onclick: parse,
+                        "Parse"
+                    }
+                } else {
+                    button {
+                        style: "
+                            background: #007bff;
+                            color: white;
+                            border: none;
+                            padding: 0.5rem 1rem;
+                            border-radius: 3px;
+                            cursor: pointer;
+                        ",
+                        disabled: selected_count == 0,
+                        onclick: import,
+                        "Import {selected_count} server(s)"
+                    }
+                }
+            }
+        }
+    }
+}
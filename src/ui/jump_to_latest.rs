@@ -0,0 +1,28 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Floating pill shown when the chat is scrolled away from the latest message.
+///
+/// Displays the number of messages that arrived while scrolled up, and a dot
+/// while a response is still streaming in below the visible area.
+#[component]
+pub fn JumpToLatest(unread: usize, streaming: bool, onclick: EventHandler<()>) -> Element {
+    rsx! {
+        div { class: "jump-to-latest",
+            button {
+                onclick: move |_| onclick.call(()),
+                title: "Jump to latest",
+                "↓ "
+                if unread > 0 {
+                    "{unread} new"
+                } else {
+                    "Latest"
+                }
+                if streaming {
+                    span { class: "jump-to-latest-dot" }
+                }
+            }
+        }
+    }
+}
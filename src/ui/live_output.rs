@@ -0,0 +1,33 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::mcp::live_output::{self, LiveOutput};
+
+/// Output of the tool calls still running, such as shell commands, shown in
+/// the chat until their results arrive.
+#[component]
+pub fn LiveToolOutput() -> Element {
+    let mut list: Signal<Vec<LiveOutput>> = use_signal(live_output::outputs);
+    use_future(move || async move {
+        let mut changes = live_output::subscribe();
+        while changes.changed().await.is_ok() {
+            list.set(live_output::outputs());
+        }
+    });
+
+    rsx! {
+        for output in list() {
+            div {
+                key: "{output.id}",
+                class: "message tool-message",
+                div { style: "font-family: monospace; font-weight: bold; margin-bottom: 0.3em;",
+                    "⟳ {output.label}"
+                }
+                pre { style: "margin: 0; max-height: 20em; overflow-y: auto; white-space: pre-wrap; word-break: break-word;",
+                    "{output.text}"
+                }
+            }
+        }
+    }
+}
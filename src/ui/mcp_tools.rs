@@ -5,6 +5,29 @@ use std::sync::Arc;
 
 use crate::mcp::ToolDescriptor;
 use crate::mcp::host::MCPHost;
+use crate::mcp::permissions::{Capability, Decision};
+use crate::ui::permissions::save_decision;
+use crate::ui::server_groups::ServerGroups;
+use crate::ui::tool_schema::SchemaTable;
+
+/// Capabilities each external server needs, with remembered decisions
+type PermissionSummary = Vec<(String, Vec<(Capability, Option<Decision>)>)>;
+
+/// Whether a tool matches a search: every word of the query appears in its
+/// name, its description or its server's ID, ignoring case.
+fn matches_search(tool: &ToolDescriptor, query: &str) -> bool {
+    let text = format!(
+        "{} {} {}",
+        tool.tool.name,
+        tool.tool.description.as_deref().unwrap_or_default(),
+        tool.server_id
+    )
+    .to_lowercase();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
 
 #[derive(Props, Clone, PartialEq)]
 pub struct McpToolsProps {
@@ -14,18 +37,33 @@ pub struct McpToolsProps {
 #[component]
 pub fn McpTools(props: McpToolsProps) -> Element {
     let mut tools = use_signal(Vec::<ToolDescriptor>::new);
+    let mut permissions = use_signal(PermissionSummary::new);
+    let mut refresh_trigger = use_signal(|| 0);
+    let mut query = use_signal(String::new);
 
     // Load tools when component mounts
     use_effect(move || {
+        let _ = refresh_trigger();
         let host = consume_context::<Arc<MCPHost>>();
         let host_clone = host.clone();
         spawn(async move {
             let tool_list = host_clone.list_tools().await;
             tools.set(tool_list);
+            permissions.set(host_clone.permission_summary().await);
         });
     });
+    let reset = move |(server_id, capability): (String, Capability)| async move {
+        save_decision(server_id, capability, None).await;
+        refresh_trigger.set(refresh_trigger() + 1);
+    };
     let tools = tools();
+    let permissions = permissions();
     let is_empty = tools.is_empty();
+    let total = tools.len();
+    let tools: Vec<ToolDescriptor> = tools
+        .into_iter()
+        .filter(|t| matches_search(t, &query.read()))
+        .collect();
 
     rsx! {
         div { style: "
@@ -66,6 +104,61 @@ pub fn McpTools(props: McpToolsProps) -> Element {
                 }
             }
 
+            ServerGroups {
+                on_switched: move |_| refresh_trigger.set(refresh_trigger() + 1),
+            }
+
+            // Permissions of external servers
+            if !permissions.is_empty() {
+                div { style: "margin-bottom: 2rem;",
+                    h3 { style: "margin: 0 0 0.5rem 0; color: #333; font-size: 1.1rem;",
+                        "Permissions"
+                    }
+                    for (server_id , caps) in permissions {
+                        div {
+                            key: "{server_id}",
+                            style: "
+                                border: 1px solid #e0e0e0;
+                                border-radius: 8px;
+                                padding: 0.75rem 1rem;
+                                margin-bottom: 0.5rem;
+                            ",
+                            div { style: "font-weight: bold; margin-bottom: 0.25rem;", "{server_id}" }
+                            if caps.is_empty() {
+                                div { style: "font-size: 0.85rem; color: #666;",
+                                    "No sensitive capabilities detected"
+                                }
+                            }
+                            for (capability , decision) in caps {
+                                {
+                                    let (status, color) = match decision {
+                                        Some(Decision::Allow) => ("Always allowed", "#28a745"),
+                                        Some(Decision::Deny) => ("Denied", "#dc3545"),
+                                        None => ("Ask", "#6c757d"),
+                                    };
+                                    let server_id = server_id.clone();
+                                    rsx! {
+                                        div {
+                                            key: "{capability.label()}",
+                                            style: "display: flex; align-items: center; gap: 0.5rem; font-size: 0.9rem;",
+                                            span { style: "flex: 1;", "{capability.label()}" }
+                                            span { style: "color: {color};", "{status}" }
+                                            if decision.is_some() {
+                                                button {
+                                                    style: "font-size: 0.75rem; cursor: pointer;",
+                                                    onclick: move |_| reset((server_id.clone(), capability)),
+                                                    "Reset"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
             // Tools list
             div { style: "
                     display: flex;
@@ -83,6 +176,30 @@ pub fn McpTools(props: McpToolsProps) -> Element {
                         "No MCP tools available"
                     }
                 } else {
+                    div { style: "display: flex; align-items: center; gap: 0.5rem;",
+                        input {
+                            style: "
+                                flex: 1;
+                                padding: 0.5rem;
+                                border: 1px solid #ddd;
+                                border-radius: 4px;
+                            ",
+                            r#type: "search",
+                            placeholder: "Search tools by name, description or server",
+                            value: query(),
+                            oninput: move |e| query.set(e.value()),
+                        }
+                        span { style: "font-size: 0.85rem; color: #666; white-space: nowrap;",
+                            if tools.len() == total {
+                                "{total} tools"
+                            } else {
+                                "{tools.len()} of {total} tools"
+                            }
+                        }
+                    }
+                    if tools.is_empty() {
+                        div { style: "color: #666; font-style: italic;", "No tools match the search" }
+                    }
                     {
                         tools
                             .into_iter()
@@ -190,21 +307,10 @@ fn ToolCard(props: ToolCardProps) -> Element {
                             color: #34495e;
                             font-size: 0.9rem;
                         ",
-                        "Input Schema:"
+                        "Parameters"
                     }
 
-                    pre { style: "
-                            background: #2c3e50;
-                            color: #ecf0f1;
-                            padding: 1rem;
-                            border-radius: 4px;
-                            font-size: 0.8rem;
-                            overflow-x: auto;
-                            margin: 0;
-                            white-space: pre-wrap;
-                        ",
-                        "{serde_json::to_string_pretty(&props.tool.tool.input_schema).unwrap_or_else(|_| \"Invalid JSON\".to_string())}"
-                    }
+                    SchemaTable { schema: props.tool.tool.input_schema.clone() }
                 }
             }
         }
@@ -3,8 +3,10 @@
 use dioxus::prelude::*;
 
 use crate::{
+    app_settings::MessageAuthor,
+    core::TOOL_IMAGES_INTRO,
     llm::{ContentPart, FunctionDelta, Message},
-    ui::collapsible::Collapsible,
+    ui::{collapsible::Collapsible, tool_content::ToolResultBody},
 };
 
 #[component]
@@ -48,20 +50,45 @@ pub fn MessageEl(msg: Message) -> Element {
             }
         }
         Message::Tool { content, .. } => {
-            let el = crate::md2rsx::markdown_to_rsx(&content)?;
             rsx! {
                 div {
                     class: "message tool-message",
-                    Collapsible { c: true, {el} }
+                    Collapsible { c: true, ToolResultBody { content } }
+                }
+            }
+        }
+        // Images returned by tools, shown with the tool results rather than as user input
+        Message::User { content }
+            if matches!(content.first(), Some(ContentPart::Text { text }) if text == TOOL_IMAGES_INTRO) =>
+        {
+            rsx! {
+                div {
+                    class: "message tool-message",
+                    Collapsible {
+                        c: false,
+                        for part in content.into_iter().skip(1) {
+                            if let ContentPart::ImageUrl { image_url } = part {
+                                img {
+                                    style: "max-width: 100%; display: block; margin: 0.5em 0;",
+                                    src: "{image_url.url}",
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
         Message::User { content } => {
+            let mut audio = vec![];
             let strings: Vec<String> = content
                 .into_iter()
-                .map(|p| match p {
-                    ContentPart::Text { text } => text,
-                    ContentPart::ImageUrl { .. } => "[Image]".to_string(),
+                .filter_map(|p| match p {
+                    ContentPart::Text { text } => Some(text),
+                    ContentPart::ImageUrl { .. } => Some("[Image]".to_string()),
+                    ContentPart::InputAudio { input_audio } => {
+                        audio.push(input_audio);
+                        None
+                    }
                 })
                 .collect();
             let text = strings.join("\n");
@@ -69,9 +96,66 @@ pub fn MessageEl(msg: Message) -> Element {
             rsx! {
                 div {
                     class: "message human-message",
-                    Collapsible { c: false, {el} }
+                    Collapsible {
+                        c: false,
+                        {el}
+                        for a in audio {
+                            audio {
+                                controls: true,
+                                style: "display: block; margin-top: 0.5em;",
+                                src: "data:audio/{a.format};base64,{a.data}",
+                            }
+                        }
+                    }
                 }
             }
         }
     }
 }
+
+/// Name and avatar of a message's author, shown above the message.
+///
+/// # Arguments
+/// * `author` - Who wrote the message
+#[component]
+pub fn AuthorBadge(author: MessageAuthor) -> Element {
+    let avatar = author.avatar.clone().unwrap_or_default();
+    let is_image = ["http://", "https://", "data:image/"]
+        .iter()
+        .any(|p| avatar.starts_with(p));
+    // Authors without an avatar get their initial on a color of their own
+    let hue = author
+        .name
+        .bytes()
+        .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32))
+        % 360;
+    let initial = author
+        .name
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_default();
+    let circle = "width: 1.5em; height: 1.5em; border-radius: 50%; flex-shrink: 0; display: inline-flex; align-items: center; justify-content: center;";
+
+    rsx! {
+        div { style: "display: flex; align-items: center; gap: 0.5em; margin-bottom: 0.5em; font-size: 0.85em; opacity: 0.9;",
+            if is_image {
+                img {
+                    style: "{circle} object-fit: cover;",
+                    src: "{avatar}",
+                    alt: "",
+                }
+            } else if !avatar.is_empty() {
+                span { style: "{circle}", "{avatar}" }
+            } else {
+                span { style: "{circle} background: hsl({hue}, 55%, 45%); color: white; font-weight: 600;",
+                    "{initial}"
+                }
+            }
+            span { style: "font-weight: 600;", "{author.name}" }
+            if let Some(model) = &author.model {
+                span { style: "opacity: 0.75;", "{model}" }
+            }
+        }
+    }
+}
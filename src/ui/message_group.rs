@@ -8,8 +8,15 @@
 use dioxus::prelude::*;
 
 use crate::{
-    llm::{Message, FunctionDelta},
-    ui::collapsible::Collapsible,
+    app_settings::MessageAuthor,
+    llm::{Message, FunctionDelta, ToolCallDelta},
+    mcp::{results::result_size, scratchpad::is_scratchpad_tool},
+    ui::{
+        collapsible::Collapsible,
+        message::AuthorBadge,
+        sources::SourcesList,
+        tool_content::{SaveResultButton, ToolResultBody},
+    },
 };
 
 /// Represents a group of related messages that should be displayed as one entity
@@ -48,6 +55,30 @@ impl MessageGroup {
     // pub fn has_tool_messages(&self) -> bool {
     //     !self.tool_messages.is_empty()
     // }
+
+    /// Returns this group without scratchpad calls and their results, which
+    /// are shown in the scratchpad panel instead.
+    pub fn without_scratchpad(mut self) -> Self {
+        let Message::Assistant { tool_calls: Some(calls), .. } = &mut self.assistant_message else {
+            return self;
+        };
+        let is_scratchpad = |c: &ToolCallDelta| {
+            c.function
+                .as_ref()
+                .and_then(|f| f.name.as_deref())
+                .is_some_and(is_scratchpad_tool)
+        };
+        let hidden: Vec<String> = calls
+            .iter()
+            .filter(|c| is_scratchpad(c))
+            .filter_map(|c| c.id.clone())
+            .collect();
+        calls.retain(|c| !is_scratchpad(c));
+        self.tool_messages.retain(|m| {
+            !matches!(m, Message::Tool { tool_call_id, .. } if hidden.contains(tool_call_id))
+        });
+        self
+    }
 }
 
 #[derive(Props, Clone, PartialEq)]
@@ -58,12 +89,23 @@ pub struct MessageGroupProps {
     pub on_delete: Option<EventHandler<String>>,
     /// Whether to show delete button
     pub show_delete: bool,
+    /// Whether tool results start collapsed, as they do for turns before the current one
+    #[props(default = true)]
+    pub collapse_results: bool,
+    /// Callback for replacing the tool calls and results with a summary
+    pub on_summarize: Option<EventHandler<()>>,
+    /// URLs consulted by fetch and search tools while writing this answer
+    #[props(default)]
+    pub sources: Vec<String>,
+    /// Who wrote the assistant message, if recorded
+    #[props(default)]
+    pub author: Option<MessageAuthor>,
 }
 
 /// Component for rendering a message group as a unified entity
 #[component]
 pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
-    let group = props.group.clone();
+    let group = props.group.clone().without_scratchpad();
     
     // Render the assistant message content
     let assistant_content = match &group.assistant_message {
@@ -121,6 +163,15 @@ pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
         _ => rsx! { div { "Invalid assistant message" } }
     };
     
+    // Calls by ID, to name the files tool results are saved as
+    let calls: Vec<(Option<String>, FunctionDelta)> = match &group.assistant_message {
+        Message::Assistant { tool_calls: Some(calls), .. } => calls
+            .iter()
+            .filter_map(|tc| Some((tc.id.clone(), tc.function.clone()?)))
+            .collect(),
+        _ => vec![],
+    };
+
     // Render tool messages if any
     let tool_content = if !group.tool_messages.is_empty() {
         rsx! {
@@ -128,13 +179,19 @@ pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
                 class: "message-group-content",
                 style: "display: absolute;",
                 Collapsible {
-                    c: true,
+                    c: props.collapse_results,
                     div { class: "tool-results",
                         style: "margin-top: 1em; padding-top: 1em; border-top: 1px solid rgba(255, 255, 255, 0.2);",
                         for tool_msg in &group.tool_messages {
                             match tool_msg {
-                                Message::Tool { content, .. } => {
-                                    let el = crate::md2rsx::markdown_to_rsx(content)?;
+                                Message::Tool { content, tool_call_id } => {
+                                    let call = calls
+                                        .iter()
+                                        .find(|(id, _)| id.as_ref() == Some(tool_call_id))
+                                        .map(|(_, f)| f.clone());
+                                    let size = result_size(content);
+                                    let size_label = size.describe();
+                                    let size_color = if size.truncated() { "#ffc107" } else { "inherit" };
                                     rsx! {
                                         div { class: "tool-result",
                                             style: "
@@ -155,8 +212,17 @@ pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
                                                     gap: 0.5em;
                                                 ",
                                                 "🔧 Tool Result",
-                                                {el}
+                                                span {
+                                                    style: "font-size: 0.8em; font-weight: normal; opacity: 0.8; color: {size_color};",
+                                                    "({size_label})"
+                                                }
+                                                SaveResultButton {
+                                                    content: content.clone(),
+                                                    tool_name: call.as_ref().and_then(|f| f.name.clone()),
+                                                    arguments: call.and_then(|f| f.arguments),
+                                                }
                                             }
+                                            ToolResultBody { content: content.clone() }
                                             // Collapsible { c: true, {el} }
                                         }
                                     }
@@ -171,6 +237,9 @@ pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
     } else {
         rsx! { div {} }
     };
+    let has_tool_messages = !group.tool_messages.is_empty();
+    let sources = props.sources.clone();
+    let author = props.author.clone();
     
     rsx! {
         div { 
@@ -209,13 +278,53 @@ pub fn MessageGroupEl(props: MessageGroupProps) -> Element {
                 }
             }
             
+            if has_tool_messages {
+                if let Some(on_summarize) = props.on_summarize {
+                    button {
+                        class: "delete-group-btn",
+                        style: "
+                            position: absolute;
+                            top: 8px;
+                            right: 76px;
+                            background: rgba(255, 255, 255, 0.2);
+                            color: white;
+                            border: none;
+                            border-radius: 50%;
+                            width: 20px;
+                            height: 20px;
+                            cursor: pointer;
+                            font-size: 12px;
+                            display: flex;
+                            align-items: center;
+                            justify-content: center;
+                            opacity: 0.7;
+                            z-index: 10;
+                        ",
+                        onclick: move |e: Event<MouseData>| {
+                            e.stop_propagation();
+                            on_summarize.call(());
+                        },
+                        title: "Summarize & prune: replace the tool calls and results with a summary",
+                        "≡"
+                    }
+                }
+            }
+
             Collapsible {
                 c: false,
                 div { class: "message-group-content",
-                    
+
+                    if let Some(author) = author {
+                        AuthorBadge { author }
+                    }
+
                     // Assistant message content
                     {assistant_content}
 
+                    if !sources.is_empty() {
+                        SourcesList { urls: sources }
+                    }
+
                     {tool_content}
                 }
             }
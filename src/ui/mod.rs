@@ -5,13 +5,74 @@
 //! This module contains all the UI components that make up the application interface,
 //! including the main chat interface, settings page, and various reusable components.
 
+pub mod ab_test; // Side-by-side runs of a prompt under two system prompts
+pub mod activity; // Toolbar indicator of background jobs
+mod args_editor; // List editor of the arguments MCP servers are started with
 mod box_select; // Multi-select dropdown component
+mod chapter_actions; // Per-chapter requests to rewrite, expand, summarize or continue
+mod character_interview; // Side conversations with the characters of a story
 mod chat_input; // Chat message input component
+mod chat_servers; // Per-chat choice of MCP servers
 pub mod chat_log;
+mod compare; // Compare mode: several models answering the same message
+mod continuity_issues; // Contradictions found between the chapters of a story
+mod diff_view; // Unified diffs with added and removed lines highlighted
+mod documents; // Documents attached to a chat by dropping files
+mod encryption_settings; // Encryption of stored data with a passphrase
+mod history_search; // Search of past messages from the chat input
 mod collapsible; // Collapsible/expandable content component
+mod credits; // OpenRouter key limits and credits, and a warning when they run low
+pub mod debug_panel; // Inspector for recorded LLM requests
 pub mod home; // Main chat interface (public for routing)
+mod import_servers; // Import MCP servers from other clients' JSON configs
+mod jump_to_latest; // "Jump to latest" pill for the chat scroll area
+mod live_output; // Output of tool calls shown while they run
 pub mod mcp_tools;
 mod message; // Message display component
+mod model_loaded; // Whether the Ollama model is loaded, loading it when a chat opens
+mod model_switcher; // Ctrl+M switcher between recently used models and providers
+pub mod nav_guard; // Warning before leaving a chat with a turn running or a message unsent
 pub mod message_group; // Message group component for grouped assistant/tool messages
+pub mod permissions; // Permission prompts for MCP server capabilities
+pub mod offline; // Service worker and offline state of the web app
+#[cfg(not(target_arch = "wasm32"))]
+mod openapi; // Settings of the OpenAPI server: APIs and the operations offered (native only)
+mod pane_tabs; // Chat and panel as tabs on narrow screens
+mod patch_review; // Review of file edits proposed by the model
+mod pins; // Pinned messages, always sent to the model
+#[cfg(not(target_arch = "wasm32"))]
+mod plugins; // WASM plugins and the capabilities granted to them (native only)
+mod provider_check; // Connection tests of providers, and a warning while one seems down
+mod provider_options; // Extra headers and body fields for provider requests
+mod proposed_calls; // Review of tool calls proposed in plan-only mode
+mod reply_options; // Stop sequences and prefill for assistant replies
+mod roleplay; // Turn order, impersonation and cast of role-play chats
+pub mod reading_view; // Distraction-free reading of stories
+pub mod recovery; // Prompt to restore a turn interrupted by a crash
+#[cfg(not(target_arch = "wasm32"))]
+pub mod schedules; // Management of scheduled prompts (native only)
+#[cfg(not(target_arch = "wasm32"))]
+mod scripted_tools; // Tools defined as Rhai scripts (native only)
+pub mod send_status; // Pending, sent or failed status of the last user message
+mod scratchpad; // Notes the model keeps for itself, hidden from the transcript
+mod server_groups; // Named groups of MCP servers, switched between from the tools panel
+mod sources; // Sources list under answers written from fetched pages
+mod snackbar; // Transient notices with an action, such as undo
+mod snippets; // Abbreviations expanded in the message input
+#[cfg(not(target_arch = "wasm32"))]
+mod secrets; // Secrets referenced from MCP server settings (native only)
+#[cfg(not(target_arch = "wasm32"))]
+mod shell; // Settings of the shell server (native only)
 pub mod settings; // Settings configuration page (public for routing)
+pub mod startup; // Startup warm-up and loading screen
+mod storage_insights; // Space used by chats, and cleanup of old ones
+#[cfg(not(target_arch = "wasm32"))]
+mod storage_location; // Where settings and chats are stored (native only)
+mod tool_schema; // Input schemas of tools as tables of their parameters
+mod tool_content; // Rendering of JSON, resources and binary data in tool results
+mod turn_timing; // Generation speed and how long the parts of a turn took
+pub mod unlock; // Passphrase prompt for encrypted storage at startup
+mod variables; // Per-chat variables filled into prompts when sent
+mod story_snapshots; // Named versions of a story, for restoring
+mod system_prompt; // Viewer and editor for a chat's system prompt
 pub mod slideout; // MCP tools display component
@@ -0,0 +1,104 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Whether the Ollama model is loaded in memory.
+//!
+//! Ollama unloads models that have not been used for a while, and loading one
+//! again can take longer than the reply itself. The model is loaded as soon
+//! as a chat is opened, and its state is checked after each turn and every
+//! little while, so a slow first token is not a surprise.
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::{
+    core::sleep,
+    llm::{LlmClient, LoadedModel},
+};
+
+/// How often the server is asked whether the model is still loaded.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Indicator of whether the Ollama model is loaded, loading it when the chat
+/// is opened.
+///
+/// # Arguments
+/// * `api_url` - URL of the Ollama server's OpenAI-compatible API
+/// * `model` - The chat's model
+/// * `keep_alive` - How long the server should keep the model loaded
+/// * `chat_id` - The open chat, to load the model again when another is opened
+/// * `turns` - Changes after each turn, to check again
+#[component]
+pub fn ModelLoadedIndicator(
+    api_url: String,
+    model: String,
+    keep_alive: Option<String>,
+    chat_id: Option<u32>,
+    turns: usize,
+) -> Element {
+    let mut warm_up = use_resource({
+        let (api_url, model) = (api_url.clone(), model.clone());
+        use_reactive!(|(api_url, model, keep_alive, chat_id)| async move {
+            let _ = chat_id;
+            LlmClient::new(api_url, String::new())
+                .warm_up(&model, keep_alive.as_deref())
+                .await
+                .map_err(|e| e.to_string())
+        })
+    });
+    let mut tick = use_signal(|| 0u64);
+    use_future(move || async move {
+        loop {
+            sleep(POLL_INTERVAL).await;
+            tick += 1;
+        }
+    });
+    let loaded = use_resource({
+        let model = model.clone();
+        use_reactive!(|(api_url, model, turns)| async move {
+            let _ = (turns, tick(), warm_up.read().is_some());
+            let models = LlmClient::new(api_url, String::new())
+                .loaded_models()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok::<Option<LoadedModel>, String>(models.into_iter().find(|m| m.is(&model)))
+        })
+    });
+
+    let (color, text, title) = match (&*warm_up.read(), &*loaded.read()) {
+        (None, _) => ("#ffc107", format!("Loading {model}…"), String::new()),
+        (Some(Err(e)), _) => ("#dc3545", format!("{model} could not be loaded"), e.clone()),
+        (_, Some(Ok(Some(m)))) => (
+            "#28a745",
+            format!("{model} loaded"),
+            m.expires_at
+                .as_ref()
+                .map(|at| format!("Unloads at {at}"))
+                .unwrap_or_default(),
+        ),
+        (_, Some(Ok(None))) => (
+            "#888",
+            format!("{model} not loaded"),
+            "The next reply will wait for the model to load".to_string(),
+        ),
+        (_, Some(Err(e))) => ("#dc3545", "Ollama unreachable".to_string(), e.clone()),
+        (_, None) => ("#888", format!("Checking {model}…"), String::new()),
+    };
+    let unloaded = matches!(&*loaded.read(), Some(Ok(None)));
+
+    rsx! {
+        div {
+            style: "margin: 0.25em 1em; font-size: 0.8em; color: #666; display: flex; gap: 0.5em; align-items: center;",
+            title: "{title}",
+            span { style: "color: {color};", "●" }
+            span { "{text}" }
+            if unloaded {
+                button {
+                    style: "font-size: 0.9em;",
+                    onclick: move |_| warm_up.restart(),
+                    "Load now"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Quick switcher between recently used models, opened with Ctrl+M.
+//!
+//! Models are remembered with the provider serving them, so switching between
+//! a local and a hosted model is one pick. The pick replaces the model in the
+//! settings, or with Shift held, only the open chat's model.
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    app_settings::{AppSettings, ModelChoice},
+    input_history::fuzzy_score,
+    utils::switch_to_model,
+};
+
+/// Most models listed at once.
+const MAX_RESULTS: usize = 12;
+
+/// Opens the switcher on Ctrl+M (Cmd+M on macOS) anywhere on the page. The
+/// handler of a switcher shown before, in a chat since closed, is replaced.
+const SHORTCUT_JS: &str = r##"
+if (window.modelSwitcherShortcut) {
+    document.removeEventListener("keydown", window.modelSwitcherShortcut);
+}
+window.modelSwitcherShortcut = (e) => {
+    if ((e.ctrlKey || e.metaKey) && !e.altKey && !e.shiftKey && e.key.toLowerCase() === "m") {
+        e.preventDefault();
+        dioxus.send(true);
+    }
+};
+document.addEventListener("keydown", window.modelSwitcherShortcut);
+"##;
+
+/// A model listed in the switcher.
+#[derive(Clone, PartialEq)]
+struct Entry {
+    /// The model and the kind of provider serving it
+    choice: ModelChoice,
+    /// Name of the provider, for display
+    provider_name: &'static str,
+}
+
+/// Recently used models, most recent first, with the model in the settings
+/// first if it was not used yet.
+fn entries(settings: &AppSettings) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = vec![];
+    for provider in std::iter::once(&settings.provider).chain(&settings.recent_models) {
+        let Some(choice) = provider.model_choice() else {
+            continue;
+        };
+        if !entries.iter().any(|e| e.choice == choice) {
+            entries.push(Entry {
+                choice,
+                provider_name: provider.name(),
+            });
+        }
+    }
+    entries
+}
+
+/// Fuzzy search over recently used models, opened with Ctrl+M. Enter or a
+/// click uses the highlighted model for all chats; with Shift held, for the
+/// open chat only. The arrow keys move the highlight, and Escape closes it.
+/// While closed, it notes the open chat's own model, if it has one.
+///
+/// # Arguments
+/// * `chat_model` - The open chat's own model, if it has one
+/// * `on_chat_model` - Called with the open chat's new model, or `None` to
+///   use the one in the settings again
+#[component]
+pub fn ModelSwitcher(
+    chat_model: Option<ModelChoice>,
+    on_chat_model: Callback<Option<ModelChoice>, ()>,
+) -> Element {
+    let mut settings = use_context::<Signal<Option<AppSettings>>>();
+    let mut open = use_signal(|| false);
+    let mut query = use_signal(String::new);
+    let mut selected = use_signal(|| 0usize);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    use_future(move || async move {
+        let mut eval = document::eval(SHORTCUT_JS);
+        while eval.recv::<bool>().await.is_ok() {
+            open.toggle();
+            query.set(String::new());
+            selected.set(0);
+            error.set(None);
+        }
+    });
+
+    // A chat with a model of its own says so, as the settings no longer tell
+    if !open() {
+        let Some(choice) = chat_model else {
+            return rsx! {};
+        };
+        return rsx! {
+            div { style: "margin: 0.25em 1em; font-size: 0.8em; color: #666; display: flex; gap: 0.5em; align-items: center;",
+                span { title: "Ctrl+M to switch", "This chat uses {choice.model} ({choice.provider})" }
+                button {
+                    style: "font-size: 0.9em;",
+                    onclick: move |_| on_chat_model(None),
+                    "Use the settings' model"
+                }
+            }
+        };
+    }
+    let Some(current) = settings() else {
+        return rsx! {};
+    };
+    let global = current.provider.model_choice();
+    let mut matches: Vec<(i64, Entry)> = entries(&current)
+        .into_iter()
+        .filter_map(|e| {
+            let text = format!("{} {}", e.choice.model, e.provider_name);
+            fuzzy_score(&query.read(), &text).map(|s| (s, e))
+        })
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let matches: Vec<Entry> = matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, e)| e)
+        .collect();
+    let count = matches.len();
+    let highlighted = (*selected.read()).min(count.saturating_sub(1));
+    let picked = matches.get(highlighted).map(|e| e.choice.clone());
+
+    // Uses a model for all chats, or with `for_chat` for the open one only
+    let pick = move |choice: ModelChoice, for_chat: bool| async move {
+        if for_chat {
+            on_chat_model(Some(choice));
+            open.set(false);
+            return;
+        }
+        match switch_to_model(&mut settings, &choice).await {
+            Ok(_) => open.set(false),
+            Err(e) => {
+                warn!("Could not switch to {}: {e:?}", choice.model);
+                error.set(Some(format!("{e:#}")));
+            }
+        }
+    };
+
+    let keydown = move |e: Event<KeyboardData>| {
+        let picked = picked.clone();
+        async move {
+            match e.data().key() {
+                Key::Escape => {
+                    e.prevent_default();
+                    open.set(false);
+                }
+                Key::Enter => {
+                    e.prevent_default();
+                    if let Some(choice) = picked {
+                        pick(choice, e.modifiers().shift()).await;
+                    }
+                }
+                Key::ArrowDown => {
+                    e.prevent_default();
+                    selected.set((highlighted + 1).min(count.saturating_sub(1)));
+                }
+                Key::ArrowUp => {
+                    e.prevent_default();
+                    selected.set(highlighted.saturating_sub(1));
+                }
+                _ => {}
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            style: "position: fixed; inset: 0; z-index: 30; background: rgba(0, 0, 0, 0.2);",
+            onclick: move |_| open.set(false),
+            div {
+                style: "
+                    position: absolute;
+                    top: 15%;
+                    left: 50%;
+                    transform: translateX(-50%);
+                    width: min(32em, 90vw);
+                    padding: 0.5em;
+                    background: #fff;
+                    border: 1px solid #ccc;
+                    border-radius: 4px;
+                    box-shadow: 0 2px 8px rgba(0, 0, 0, 0.15);
+                    font-size: 0.9em;
+                    ",
+                onclick: move |e| e.stop_propagation(),
+                input {
+                    style: "width: 100%; box-sizing: border-box; margin-bottom: 0.25em;",
+                    placeholder: "Switch model (Enter for all chats, Shift+Enter for this chat)",
+                    autofocus: true,
+                    value: query(),
+                    oninput: move |e| {
+                        query.set(e.value());
+                        selected.set(0);
+                    },
+                    onkeydown: keydown,
+                    onmounted: move |e| async move {
+                        let _ = e.set_focus(true).await;
+                    },
+                }
+                if let Some(error) = error() {
+                    div { style: "color: #dc3545; padding: 0.2em 0.5em;", "{error}" }
+                }
+                if matches.is_empty() {
+                    div { style: "color: #888; padding: 0.2em 0.5em;",
+                        if query.read().is_empty() {
+                            "No models used yet"
+                        } else {
+                            "No matching models"
+                        }
+                    }
+                }
+                for (i , entry) in matches.into_iter().enumerate() {
+                    div {
+                        key: "{entry.choice.provider}/{entry.choice.model}",
+                        style: format!(
+                            "display: flex; gap: 0.5em; align-items: baseline; padding: 0.2em 0.5em; cursor: pointer; border-radius: 3px; background: {};",
+                            if i == highlighted { "#e7f1ff" } else { "transparent" },
+                        ),
+                        title: "Click to use for all chats, Shift+click for this chat only",
+                        onmouseenter: move |_| selected.set(i),
+                        onclick: {
+                            let choice = entry.choice.clone();
+                            move |e: Event<MouseData>| {
+                                let choice = choice.clone();
+                                async move { pick(choice, e.modifiers().shift()).await }
+                            }
+                        },
+                        span { style: "flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;",
+                            "{entry.choice.model}"
+                        }
+                        span { style: "color: #888; font-size: 0.85em;", "{entry.provider_name}" }
+                        if chat_model.as_ref() == Some(&entry.choice) {
+                            span { style: "color: #0d6efd; font-size: 0.85em;", "this chat" }
+                        } else if chat_model.is_none() && global.as_ref() == Some(&entry.choice) {
+                            span { style: "color: #28a745; font-size: 0.85em;", "in use" }
+                        }
+                    }
+                }
+                if chat_model.is_some() {
+                    div {
+                        style: "padding: 0.4em 0.5em 0.2em; color: #0d6efd; cursor: pointer; border-top: 1px solid #eee; margin-top: 0.25em;",
+                        onclick: move |_| {
+                            on_chat_model(None);
+                            open.set(false);
+                        },
+                        "Use the model in the settings for this chat"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Warning before leaving a chat with work in progress.
+//!
+//! Leaving the chat page drops the turn running in it, and with it whatever
+//! the model has written so far, as well as a message typed but not sent.
+//! Links in the toolbar and the chat list go through [`NavGuard`], which asks
+//! first and can stop the turn and wait for the chat to be saved.
+
+use dioxus::{core::use_drop, prelude::*, router::Navigator};
+
+use crate::{Route, core::CancelHandle};
+
+/// Work in progress on the page, and navigation waiting for the user's
+/// decision about it. Provided by the layout.
+#[derive(Clone, Copy)]
+pub struct NavGuard {
+    /// Whether the open chat is running a turn or saving its result
+    busy: Signal<bool>,
+    /// Stops the open chat's turn
+    cancel: Signal<Option<CancelHandle>>,
+    /// Whether the chat input holds text that was not sent
+    unsent: Signal<bool>,
+    /// Where the user asked to go, while they decide
+    pending: Signal<Option<Route>>,
+    /// Whether the turn was stopped, to leave once the chat is saved
+    stopping: Signal<bool>,
+    /// Router the guarded links navigate with
+    nav: Navigator,
+}
+
+impl NavGuard {
+    /// Provides the guard to the layout's children.
+    pub fn provide() -> Self {
+        let nav = navigator();
+        use_context_provider(|| NavGuard {
+            busy: Signal::new(false),
+            cancel: Signal::new(None),
+            unsent: Signal::new(false),
+            pending: Signal::new(None),
+            stopping: Signal::new(false),
+            nav,
+        })
+    }
+
+    /// Whether leaving now would lose work.
+    pub fn has_work(&self) -> bool {
+        (self.busy)() || (self.unsent)()
+    }
+
+    /// Holds a route until the user decides what to do with the work in
+    /// progress. Links pass it when [`NavGuard::has_work`], and navigate
+    /// themselves otherwise.
+    pub fn ask(mut self, route: Route) {
+        self.pending.set(Some(route));
+    }
+
+    /// Goes where the user asked to, even if work is lost.
+    fn leave(mut self) {
+        self.stopping.set(false);
+        if let Some(route) = self.pending.take() {
+            self.nav.push(route);
+        }
+    }
+}
+
+/// Registers the open chat's turns with the guard.
+///
+/// # Arguments
+/// * `busy` - Whether a turn is running or its result is being saved
+/// * `cancel` - Stops the running turn
+pub fn use_guarded_turn(busy: Signal<bool>, cancel: Signal<CancelHandle>) {
+    let Some(mut guard) = try_use_context::<NavGuard>() else {
+        return;
+    };
+    use_effect(move || guard.busy.set(busy()));
+    use_effect(move || guard.cancel.set(Some(cancel())));
+    use_drop(move || {
+        guard.busy.set(false);
+        guard.cancel.set(None);
+    });
+}
+
+/// Registers the text of the chat input with the guard.
+///
+/// # Arguments
+/// * `text` - Text typed in the input
+pub fn use_guarded_draft(text: Signal<String>) {
+    let Some(mut guard) = try_use_context::<NavGuard>() else {
+        return;
+    };
+    use_effect(move || guard.unsent.set(!text.read().trim().is_empty()));
+    use_drop(move || guard.unsent.set(false));
+}
+
+/// Asks what to do with the work in progress when the user navigates away
+/// from it: stop the turn and leave once the chat is saved, leave anyway, or
+/// stay.
+#[component]
+pub fn LeaveDialog() -> Element {
+    let mut guard = use_context::<NavGuard>();
+
+    // Leaves once the stopped turn has ended and the chat is saved
+    use_effect(move || {
+        if (guard.stopping)() && !(guard.busy)() {
+            guard.leave();
+        }
+    });
+
+    if guard.pending.read().is_none() {
+        return rsx! {};
+    }
+    let busy = (guard.busy)();
+    let stopping = (guard.stopping)();
+    let message = match (busy, (guard.unsent)()) {
+        (true, true) => {
+            "A reply is still being written, and your message has not been sent. Leaving now \
+             abandons both."
+        }
+        (true, false) => "A reply is still being written. Leaving now abandons it.",
+        _ => "Your message has not been sent. Leaving now discards it.",
+    };
+
+    rsx! {
+        div { style: "
+            position: fixed;
+            top: 3em;
+            left: 50%;
+            transform: translateX(-50%);
+            z-index: 20;
+            max-width: 40em;
+            background-color: #fff3cd;
+            border: 1px solid #ffeeba;
+            border-radius: 4px;
+            padding: 1em;
+            color: #856404;
+            ",
+            div { style: "margin-bottom: 0.5em;",
+                if stopping {
+                    "Stopping the reply and saving the chat…"
+                } else {
+                    "{message}"
+                }
+            }
+            if busy {
+                button {
+                    style: "margin-right: 0.5em;",
+                    disabled: stopping,
+                    title: "Stop the reply, keep what it has written so far, and leave once the chat is saved",
+                    onclick: move |_| {
+                        if let Some(cancel) = &*guard.cancel.read() {
+                            cancel.cancel();
+                        }
+                        guard.stopping.set(true);
+                    },
+                    "Stop and save"
+                }
+            }
+            button {
+                style: "margin-right: 0.5em;",
+                onclick: move |_| guard.leave(),
+                "Leave anyway"
+            }
+            button {
+                onclick: move |_| {
+                    guard.stopping.set(false);
+                    guard.pending.set(None);
+                },
+                "Stay"
+            }
+        }
+    }
+}
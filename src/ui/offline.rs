@@ -0,0 +1,70 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Installable web app: service worker registration and the online state.
+//!
+//! The service worker (`public/sw.js`) caches the app shell so the installed
+//! web app opens offline. Stored chats are in IndexedDB, so they can still be
+//! read and exported; only sending needs a connection.
+
+use dioxus::prelude::*;
+
+/// Reports whether the browser is online now and whenever that changes.
+const ONLINE_JS: &str = r#"
+dioxus.send(navigator.onLine);
+window.addEventListener("online", () => dioxus.send(true));
+window.addEventListener("offline", () => dioxus.send(false));
+await new Promise(() => {});
+"#;
+
+/// Registers the service worker caching the app shell.
+const REGISTER_SW_JS: &str = r#"
+if ("serviceWorker" in navigator) {
+    navigator.serviceWorker
+        .register("./sw.js")
+        .catch((e) => console.warn("Service worker not registered:", e));
+}
+"#;
+
+/// Whether the app has a network connection, provided as context by the
+/// root component. Always online outside the browser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Online(pub Signal<bool>);
+
+/// Tracks whether the browser is online and provides it as [`Online`] context.
+pub fn use_online_provider() {
+    let mut online = use_signal(|| true);
+    use_context_provider(|| Online(online));
+    use_future(move || async move {
+        if !cfg!(target_arch = "wasm32") {
+            return;
+        }
+        let mut eval = document::eval(ONLINE_JS);
+        while let Ok(on) = eval.recv::<bool>().await {
+            online.set(on);
+        }
+    });
+}
+
+/// Registers the service worker in release web builds. Debug builds skip it,
+/// so the dev server's unhashed files are never served from the cache.
+pub fn use_service_worker() {
+    use_hook(|| {
+        if cfg!(target_arch = "wasm32") && !cfg!(debug_assertions) {
+            let _ = document::eval(REGISTER_SW_JS);
+        }
+    });
+}
+
+/// Banner shown while offline, saying what still works.
+#[component]
+pub fn OfflineBanner() -> Element {
+    let Online(online) = use_context::<Online>();
+    if online() {
+        return rsx! {};
+    }
+    rsx! {
+        div { class: "offline-banner",
+            "You are offline. Chats can still be read and exported; sending needs a connection."
+        }
+    }
+}
@@ -0,0 +1,486 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{
+    AppSettings,
+    mcp::{
+        openapi::{
+            ApiAuth, ApiOperation, OpenApiSettings, OpenApiSource, fetch_document, parse_document,
+        },
+        validation::validate_http_url,
+    },
+};
+
+/// Longest name suggested for an API from its title.
+const MAX_SUGGESTED_NAME: usize = 16;
+
+/// Name for an API's tools from its title, e.g. `petstore` for "Swagger
+/// Petstore": lowercase letters and digits, words joined with `_`.
+fn suggested_name(title: &str) -> String {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty() && !w.eq_ignore_ascii_case("swagger"))
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let name: String = words.join("_").chars().take(MAX_SUGGESTED_NAME).collect();
+    let name = name.trim_end_matches('_').to_string();
+    if name.is_empty() { "api".into() } else { name }
+}
+
+/// Why an API's settings cannot be saved, if they cannot.
+fn source_error(source: &OpenApiSource, others: &[OpenApiSource]) -> Option<String> {
+    if source.name.is_empty()
+        || !source
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Some("The name may only contain letters, digits and _".into());
+    }
+    if others.iter().any(|o| o.name == source.name) {
+        return Some(format!("Another API is named {}", source.name));
+    }
+    if let Err(e) = validate_http_url(&source.base_url) {
+        return Some(format!("Base URL: {e}"));
+    }
+    if source.operations.is_empty() {
+        return Some("Pick at least one operation".into());
+    }
+    None
+}
+
+/// Settings of the OpenAPI server: whether it runs, and the APIs whose
+/// operations it offers.
+///
+/// # Arguments
+/// * `settings` - Current settings
+/// * `on_save` - Called with the settings after the OpenAPI settings are changed
+#[component]
+pub fn OpenApiServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>) -> Element {
+    let openapi = settings.openapi.clone();
+    let apis = openapi.apis.clone();
+    // Index of the API being edited, or the number of APIs when adding one
+    let mut editing = use_signal(|| None::<usize>);
+
+    let save = use_callback(move |openapi: OpenApiSettings| {
+        on_save(AppSettings {
+            openapi,
+            ..settings.clone()
+        });
+    });
+
+    let enabled = openapi.enabled;
+    let count = openapi.apis.len();
+    let current = openapi.clone();
+    let save_source = move |(index, source): (usize, OpenApiSource)| {
+        let mut openapi = current.clone();
+        if index < openapi.apis.len() {
+            openapi.apis[index] = source;
+        } else {
+            openapi.apis.push(source);
+        }
+        save(openapi);
+        editing.set(None);
+    };
+    let current = openapi.clone();
+    let remove_source = move |index: usize| {
+        let mut openapi = current.clone();
+        openapi.apis.remove(index);
+        save(openapi);
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "REST APIs (OpenAPI)" }
+        p { style: "color: #666; font-size: 0.9em; margin-top: 0;",
+            "Offers operations of REST APIs as tools, read from their OpenAPI or Swagger document (JSON). "
+            "Reads are sent with your network permission for the server; you approve every other request before it is sent."
+        }
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: enabled,
+                oninput: move |e: Event<FormData>| {
+                    save(OpenApiSettings {
+                        enabled: e.checked(),
+                        ..openapi.clone()
+                    });
+                },
+            }
+            "Offer the OpenAPI server to the model"
+        }
+        for (index , source) in apis.clone().into_iter().enumerate() {
+            if editing() == Some(index) {
+                ApiSourceForm {
+                    key: "{index}",
+                    source: Some(source),
+                    others: apis
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != index)
+                        .map(|(_, s)| s.clone())
+                        .collect::<Vec<_>>(),
+                    on_save: {
+                        let save_source = save_source.clone();
+                        move |s: OpenApiSource| save_source.clone()((index, s))
+                    },
+                    on_cancel: move |_| editing.set(None),
+                }
+            } else {
+                div {
+                    key: "{index}",
+                    style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem; margin-bottom: 0.5rem; border: 1px solid #ddd; border-radius: 4px; background: #f9f9f9;",
+                    div { style: "flex: 1;",
+                        div { style: "font-weight: bold;", "{source.name}" }
+                        div { style: "font-family: monospace; font-size: 0.8em; color: #666;",
+                            "{source.base_url}"
+                        }
+                        div { style: "font-size: 0.8em; color: #888;",
+                            "{source.operations.len()} operations"
+                        }
+                    }
+                    button { onclick: move |_| editing.set(Some(index)), "Edit" }
+                    button {
+                        onclick: {
+                            let remove_source = remove_source.clone();
+                            move |_| remove_source(index)
+                        },
+                        "Remove"
+                    }
+                }
+            }
+        }
+        if editing() == Some(count) {
+            ApiSourceForm {
+                source: None,
+                others: apis.clone(),
+                on_save: move |s: OpenApiSource| save_source.clone()((count, s)),
+                on_cancel: move |_| editing.set(None),
+            }
+        } else if editing().is_none() {
+            div {
+                button { onclick: move |_| editing.set(Some(count)), "+ Add API" }
+            }
+        }
+    }
+}
+
+/// Form adding or editing an API: its document is loaded to pick the
+/// operations offered, and its name, base URL and authentication are set.
+///
+/// # Arguments
+/// * `source` - The API, or `None` to add one
+/// * `others` - The other APIs, whose names are taken
+/// * `on_save` - Called with the API's new settings
+/// * `on_cancel` - Called when the form is closed without saving
+#[component]
+fn ApiSourceForm(
+    source: Option<OpenApiSource>,
+    others: Vec<OpenApiSource>,
+    on_save: Callback<OpenApiSource, ()>,
+    on_cancel: Callback<(), ()>,
+) -> Element {
+    let initial = source.clone().unwrap_or_default();
+    let mut spec_url = use_signal(|| initial.spec_url.clone());
+    let mut name = use_signal(|| initial.name.clone());
+    let mut base_url = use_signal(|| initial.base_url.clone());
+    let mut auth = use_signal(|| initial.auth.clone());
+    // Operations of the loaded document, or the saved ones until it is loaded
+    let mut available = use_signal(|| initial.operations.clone());
+    let mut picked = use_signal(|| {
+        initial
+            .operations
+            .iter()
+            .map(|o| o.name.clone())
+            .collect::<Vec<_>>()
+    });
+    let mut filter = use_signal(String::new);
+    let mut loading = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    let load = move |_| async move {
+        loading.set(true);
+        error.set(None);
+        let url = spec_url().trim().to_string();
+        let loaded = async {
+            let doc = fetch_document(&url).await?;
+            parse_document(&doc, &url)
+        }
+        .await;
+        match loaded {
+            Ok(api) => {
+                if name.read().is_empty() {
+                    name.set(suggested_name(&api.title));
+                }
+                let no_base_url = base_url.read().is_empty();
+                if no_base_url && let Some(url) = api.base_url {
+                    base_url.set(url);
+                }
+                // Operations picked before stay picked if the document still has them
+                let names: Vec<String> = api.operations.iter().map(|o| o.name.clone()).collect();
+                picked.write().retain(|p| names.contains(p));
+                available.set(api.operations);
+            }
+            Err(e) => error.set(Some(format!("{e:#}"))),
+        }
+        loading.set(false);
+    };
+
+    let draft = move || OpenApiSource {
+        name: name().trim().to_string(),
+        spec_url: spec_url().trim().to_string(),
+        base_url: base_url().trim().to_string(),
+        auth: auth(),
+        operations: available
+            .read()
+            .iter()
+            .filter(|o| picked.read().contains(&o.name))
+            .cloned()
+            .collect(),
+    };
+    let problem = source_error(&draft(), &others);
+
+    let query = filter.read().to_lowercase();
+    let shown: Vec<ApiOperation> = available
+        .read()
+        .iter()
+        .filter(|o| {
+            query.is_empty()
+                || format!("{} {} {} {}", o.name, o.method, o.path, o.description)
+                    .to_lowercase()
+                    .contains(&query)
+        })
+        .cloned()
+        .collect();
+    let shown_names: Vec<String> = shown.iter().map(|o| o.name.clone()).collect();
+    let auth_kind = match auth() {
+        ApiAuth::None => "none",
+        ApiAuth::Bearer { .. } => "bearer",
+        ApiAuth::Basic { .. } => "basic",
+        ApiAuth::Header { .. } => "header",
+        ApiAuth::Query { .. } => "query",
+    };
+    let field = "display: flex; flex-direction: column; gap: 0.25rem; margin-bottom: 0.5rem;";
+    let hint = "font-size: 0.9em; color: #666;";
+
+    rsx! {
+        div { style: "border: 1px solid #007bff; border-radius: 4px; padding: 1rem; margin-bottom: 0.5rem; background: #f8f9fa;",
+            div { style: field,
+                label { style: hint, "OpenAPI or Swagger document (JSON)" }
+                div { style: "display: flex; gap: 0.5rem;",
+                    input {
+                        style: "flex: 1;",
+                        placeholder: "https://petstore3.swagger.io/api/v3/openapi.json",
+                        value: spec_url(),
+                        oninput: move |e| spec_url.set(e.value()),
+                    }
+                    button {
+                        disabled: loading() || spec_url.read().trim().is_empty(),
+                        onclick: load,
+                        if loading() {
+                            "Loading…"
+                        } else if available.read().is_empty() {
+                            "Load operations"
+                        } else {
+                            "Reload operations"
+                        }
+                    }
+                }
+            }
+            div { style: field,
+                label { style: hint, "Name, prefixed to the tools' names" }
+                input {
+                    placeholder: "petstore",
+                    value: name(),
+                    oninput: move |e| name.set(e.value()),
+                }
+            }
+            div { style: field,
+                label { style: hint, "Base URL requests are sent to" }
+                input {
+                    placeholder: "https://api.example.com/v1",
+                    value: base_url(),
+                    oninput: move |e| base_url.set(e.value()),
+                }
+            }
+            div { style: field,
+                label { style: hint,
+                    "Authentication; values may reference secrets as "
+                    code { "${{NAME}}" }
+                }
+                select {
+                    onchange: move |e| {
+                        auth.set(
+                            match e.value().as_str() {
+                                "bearer" => ApiAuth::Bearer {
+                                    token: String::new(),
+                                },
+                                "basic" => ApiAuth::Basic {
+                                    username: String::new(),
+                                    password: String::new(),
+                                },
+                                "header" => ApiAuth::Header {
+                                    name: "X-API-Key".into(),
+                                    value: String::new(),
+                                },
+                                "query" => ApiAuth::Query {
+                                    name: "api_key".into(),
+                                    value: String::new(),
+                                },
+                                _ => ApiAuth::None,
+                            },
+                        );
+                    },
+                    for (value , label) in [
+                        ("none", "None"),
+                        ("bearer", "Bearer token"),
+                        ("basic", "Username and password"),
+                        ("header", "Header"),
+                        ("query", "Query parameter"),
+                    ]
+                    {
+                        option { value, selected: auth_kind == value, "{label}" }
+                    }
+                }
+                {match auth() {
+                    ApiAuth::None => rsx! {},
+                    ApiAuth::Bearer { token } => rsx! {
+                        input {
+                            placeholder: "Token, e.g. ${{EXAMPLE_TOKEN}}",
+                            value: token,
+                            oninput: move |e| auth.set(ApiAuth::Bearer { token: e.value() }),
+                        }
+                    },
+                    ApiAuth::Basic { username, password } => rsx! {
+                        input {
+                            placeholder: "Username",
+                            value: "{username}",
+                            oninput: {
+                                let password = password.clone();
+                                move |e: Event<FormData>| {
+                                    auth.set(ApiAuth::Basic {
+                                        username: e.value(),
+                                        password: password.clone(),
+                                    })
+                                }
+                            },
+                        }
+                        input {
+                            placeholder: "Password, e.g. ${{EXAMPLE_PASSWORD}}",
+                            value: "{password}",
+                            oninput: move |e| {
+                                auth.set(ApiAuth::Basic {
+                                    username: username.clone(),
+                                    password: e.value(),
+                                })
+                            },
+                        }
+                    },
+                    ApiAuth::Header { name: header, value } | ApiAuth::Query { name: header, value } => {
+                        let query = auth_kind == "query";
+                        let make = move |name: String, value: String| {
+                            if query {
+                                ApiAuth::Query { name, value }
+                            } else {
+                                ApiAuth::Header { name, value }
+                            }
+                        };
+                        let name_placeholder = if query { "Parameter name" } else { "Header name" };
+                        rsx! {
+                            input {
+                                placeholder: name_placeholder,
+                                value: header.clone(),
+                                oninput: {
+                                    let value = value.clone();
+                                    move |e: Event<FormData>| auth.set(make(e.value(), value.clone()))
+                                },
+                            }
+                            input {
+                                placeholder: "Value, e.g. ${{EXAMPLE_KEY}}",
+                                value: "{value}",
+                                oninput: move |e| auth.set(make(header.clone(), e.value())),
+                            }
+                        }
+                    }
+                }}
+            }
+            if !available.read().is_empty() {
+                div { style: field,
+                    div { style: "display: flex; gap: 0.5rem; align-items: center;",
+                        label { style: "{hint} flex: 1;",
+                            "Operations offered as tools ({picked.read().len()} of {available.read().len()})"
+                        }
+                        input {
+                            placeholder: "Filter",
+                            value: filter(),
+                            oninput: move |e| filter.set(e.value()),
+                        }
+                        button {
+                            onclick: {
+                                let shown_names = shown_names.clone();
+                                move |_| {
+                                    let mut picked = picked.write();
+                                    for name in &shown_names {
+                                        if !picked.contains(name) {
+                                            picked.push(name.clone());
+                                        }
+                                    }
+                                }
+                            },
+                            "Pick shown"
+                        }
+                        button {
+                            onclick: move |_| picked.write().retain(|p| !shown_names.contains(p)),
+                            "Drop shown"
+                        }
+                    }
+                    div { style: "max-height: 16em; overflow-y: auto; border: 1px solid #ddd; border-radius: 3px; background: #fff;",
+                        for op in shown {
+                            label {
+                                key: "{op.name}",
+                                style: "display: flex; gap: 0.5rem; align-items: baseline; padding: 0.2rem 0.5rem; font-size: 0.85em;",
+                                title: "{op.description}",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: picked.read().contains(&op.name),
+                                    oninput: {
+                                        let op_name = op.name.clone();
+                                        move |e: Event<FormData>| {
+                                            if e.checked() {
+                                                picked.write().push(op_name.clone());
+                                            } else {
+                                                picked.write().retain(|p| *p != op_name);
+                                            }
+                                        }
+                                    },
+                                }
+                                span { style: "font-family: monospace; min-width: 4em; color: #8e44ad;",
+                                    "{op.method}"
+                                }
+                                span { style: "font-family: monospace;", "{op.path}" }
+                                span { style: "color: #888;", "{op.name}" }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545; font-size: 0.85em; margin-bottom: 0.5rem;",
+                    "{e}"
+                }
+            } else if let Some(problem) = &problem {
+                div { style: "color: #856404; font-size: 0.85em; margin-bottom: 0.5rem;",
+                    "{problem}"
+                }
+            }
+            div { style: "display: flex; gap: 0.5rem; justify-content: flex-end;",
+                button { onclick: move |_| on_cancel(()), "Cancel" }
+                button {
+                    disabled: problem.is_some(),
+                    onclick: move |_| on_save(draft()),
+                    "Save API"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Horizontal distance, in pixels, a touch has to move to count as a swipe.
+const SWIPE_MIN_DISTANCE: f64 = 60.;
+
+/// Side of a chat shown on narrow screens, where the chat and its panel
+/// (the story, plan or tool output) do not fit side by side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pane {
+    Chat,
+    Panel,
+}
+
+impl Pane {
+    /// Class hiding a pane on narrow screens while the other one is shown.
+    ///
+    /// # Arguments
+    /// * `shown` - The pane shown
+    pub fn class(self, shown: Pane) -> &'static str {
+        if self == shown { "" } else { "pane-hidden" }
+    }
+}
+
+/// Tabs switching between the chat and its panel. They are only shown on
+/// narrow screens, where the two panes are not side by side.
+///
+/// # Arguments
+/// * `pane` - The pane shown
+/// * `panel_label` - Name of the panel, such as "Story"
+#[component]
+pub fn PaneTabs(pane: Signal<Pane>, panel_label: String) -> Element {
+    let tabs = [(Pane::Chat, "Chat".to_string()), (Pane::Panel, panel_label)];
+    rsx! {
+        div { class: "pane-tabs",
+            for (tab , label) in tabs {
+                button {
+                    key: "{label}",
+                    class: if pane() == tab { "active" },
+                    onclick: move |_| pane.set(tab),
+                    "{label}"
+                }
+            }
+        }
+    }
+}
+
+/// Swipe between the chat and its panel: left for the panel, right for the chat.
+///
+/// # Returns
+/// Handlers for the start and the end of a touch
+pub fn use_pane_swipe(
+    mut pane: Signal<Pane>,
+) -> (
+    impl FnMut(Event<TouchData>) + Copy,
+    impl FnMut(Event<TouchData>) + Copy,
+) {
+    let mut start: Signal<Option<(f64, f64)>> = use_signal(|| None);
+    let on_start = move |e: Event<TouchData>| {
+        start.set(e.touches().first().map(|t| {
+            let p = t.client_coordinates();
+            (p.x, p.y)
+        }));
+    };
+    let on_end = move |e: Event<TouchData>| {
+        let end = e.touches_changed().first().map(|t| t.client_coordinates());
+        let (Some((x0, y0)), Some(p)) = (start.take(), end) else {
+            return;
+        };
+        let (dx, dy) = (p.x - x0, p.y - y0);
+        // Mostly sideways, so scrolling up and down does not switch panes
+        if dx.abs() >= SWIPE_MIN_DISTANCE && dx.abs() > 2. * dy.abs() {
+            pane.set(if dx < 0. { Pane::Panel } else { Pane::Chat });
+        }
+    };
+    (on_start, on_end)
+}
@@ -0,0 +1,89 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{
+    mcp::patch::{self, Patch},
+    ui::diff_view::DiffView,
+};
+
+/// Edits proposed by the model and waiting for review, shown in the chat
+/// while the turn waits for them.
+///
+/// # Arguments
+/// * `session` - Review session of the chat, whose patches are shown
+#[component]
+pub fn PatchReview(session: u64) -> Element {
+    let mut list: Signal<Vec<Patch>> = use_signal(|| patch::pending(session));
+    use_future(move || async move {
+        let mut changes = patch::subscribe();
+        while changes.changed().await.is_ok() {
+            list.set(patch::pending(session));
+        }
+    });
+
+    rsx! {
+        for p in list() {
+            PatchCard { key: "{p.id}", patch: p.clone() }
+        }
+    }
+}
+
+/// One proposed edit, with a checkbox per hunk and buttons to apply the
+/// checked hunks or reject the edit.
+///
+/// # Arguments
+/// * `patch` - The proposed edit
+#[component]
+fn PatchCard(patch: Patch) -> Element {
+    let mut accepted = use_signal(|| vec![true; patch.hunks.len()]);
+    let id = patch.id;
+    let count = accepted.read().iter().filter(|a| **a).count();
+    let total = patch.hunks.len();
+    let verb = if patch.before.is_some() {
+        "Edit"
+    } else {
+        "Create"
+    };
+
+    rsx! {
+        div {
+            class: "message tool-message",
+            style: "border: 1px solid #007bff; border-radius: 6px;",
+            div { style: "display: flex; align-items: baseline; gap: 0.5em; margin-bottom: 0.3em;",
+                strong { "✎ {verb} " code { "{patch.path}" } }
+                span { style: "color: #666; font-size: 0.9em;", "{patch.summary}" }
+            }
+            for (i , hunk) in patch.hunks.iter().enumerate() {
+                div { key: "{i}", style: "display: flex; gap: 0.5em; align-items: flex-start;",
+                    input {
+                        r#type: "checkbox",
+                        style: "margin-top: 0.9em;",
+                        title: "Apply this hunk",
+                        checked: accepted.read()[i],
+                        oninput: move |e: Event<FormData>| accepted.write()[i] = e.checked(),
+                    }
+                    div { style: "flex: 1; min-width: 0;",
+                        DiffView { diff: hunk.diff.clone() }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 0.5em; justify-content: flex-end;",
+                button {
+                    onclick: move |_| patch::answer(id, None),
+                    "Reject"
+                }
+                button {
+                    style: "background: #007bff; color: white; border: none; padding: 0.4em 0.8em; border-radius: 3px;",
+                    disabled: count == 0,
+                    onclick: move |_| patch::answer(id, Some(accepted())),
+                    if count == total {
+                        "Apply"
+                    } else {
+                        "Apply {count} of {total} hunks"
+                    }
+                }
+            }
+        }
+    }
+}
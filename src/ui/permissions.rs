@@ -0,0 +1,168 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::sync::Arc;
+
+use dioxus::{logger::tracing::warn, prelude::*};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    app_settings::AppSettings,
+    mcp::{
+        host::MCPHost,
+        permissions::{
+            Capability, Decision, PermissionChoice, PermissionPrompter, PermissionRequest,
+        },
+    },
+    storage::{Storage, get_storage},
+};
+
+/// A permission request waiting for the user, with the channel to answer on.
+type PendingRequest = (PermissionRequest, oneshot::Sender<PermissionChoice>);
+
+/// Prompter that forwards requests to the [`PermissionDialog`].
+struct DialogPrompter {
+    tx: mpsc::UnboundedSender<PendingRequest>,
+}
+
+#[async_trait::async_trait]
+impl PermissionPrompter for DialogPrompter {
+    async fn ask(&self, request: PermissionRequest) -> PermissionChoice {
+        let (tx, rx) = oneshot::channel();
+        if self.tx.send((request, tx)).is_err() {
+            return PermissionChoice::Deny;
+        }
+        // A dropped dialog counts as a refusal
+        rx.await.unwrap_or(PermissionChoice::Deny)
+    }
+}
+
+/// Remembers or forgets a permission decision in the host, the app-wide
+/// settings and storage.
+///
+/// # Arguments
+/// * `server_id` - Server the decision applies to
+/// * `capability` - Capability the decision applies to
+/// * `decision` - The decision, or `None` to ask again next time
+pub async fn save_decision(server_id: String, capability: Capability, decision: Option<Decision>) {
+    let apply = |settings: &mut AppSettings| {
+        let entry = settings.permissions.entry(server_id.clone()).or_default();
+        match decision {
+            Some(d) => {
+                entry.insert(capability, d);
+            }
+            None => {
+                entry.remove(&capability);
+            }
+        }
+        settings.permissions.retain(|_, p| !p.is_empty());
+    };
+
+    // Stored settings are updated separately so config overrides are not saved
+    let res = async {
+        let stg = get_storage().await?;
+        if let Some(mut s) = stg.load_settings().await? {
+            apply(&mut s);
+            stg.save_settings(&s).await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = res {
+        warn!("Could not save permission decision: {e:?}");
+    }
+
+    let mut settings = consume_context::<Signal<Option<AppSettings>>>();
+    if let Some(s) = settings.write().as_mut() {
+        apply(s);
+    }
+    let host = consume_context::<Arc<MCPHost>>();
+    if let Some(s) = settings.read().as_ref() {
+        host.set_permissions(s.permissions.clone()).await;
+    }
+}
+
+/// Asks the user whether an MCP server may use a capability, one request at a time.
+///
+/// Mounting the dialog enables the permission model: it installs itself as the
+/// global host's prompter.
+#[component]
+pub fn PermissionDialog() -> Element {
+    let mut pending: Signal<Vec<PendingRequest>> = use_signal(Vec::new);
+    let _ = use_future(move || async move {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let host = consume_context::<Arc<MCPHost>>();
+        host.set_prompter(Arc::new(DialogPrompter { tx })).await;
+        while let Some(request) = rx.recv().await {
+            pending.write().push(request);
+        }
+    });
+
+    let mut answer = move |choice: PermissionChoice| {
+        if pending.read().is_empty() {
+            return;
+        }
+        let (request, tx) = pending.write().remove(0);
+        let _ = tx.send(choice);
+        if !request.remember {
+            return;
+        }
+        let decision = match choice {
+            PermissionChoice::Once => return,
+            PermissionChoice::Always => Decision::Allow,
+            PermissionChoice::Deny => Decision::Deny,
+        };
+        spawn(save_decision(
+            request.server_id,
+            request.capability,
+            Some(decision),
+        ));
+    };
+
+    let Some(request) = pending.read().first().map(|(r, _)| r.clone()) else {
+        return rsx! {};
+    };
+    let question = request.question();
+    let waiting = pending.read().len() - 1;
+
+    rsx! {
+        div { style: "
+            position: fixed;
+            inset: 0;
+            z-index: 20;
+            background: rgba(0, 0, 0, 0.4);
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            ",
+            div { style: "
+                max-width: 32em;
+                background: white;
+                border-radius: 8px;
+                padding: 1.5em;
+                box-shadow: 0 4px 16px rgba(0, 0, 0, 0.3);
+                ",
+                h3 { style: "margin-top: 0;", "🔒 Permission request" }
+                p { "{question}" }
+                if let Some(details) = &request.details {
+                    pre { style: "background: #f8f9fa; border: 1px solid #dee2e6; border-radius: 4px; padding: 0.5em; white-space: pre-wrap; word-break: break-word; max-height: 12em; overflow-y: auto;",
+                        "{details}"
+                    }
+                }
+                if waiting > 0 {
+                    p { style: "font-size: 0.85em; color: #666;", "{waiting} more request(s) waiting" }
+                }
+                div { style: "display: flex; gap: 0.5em; justify-content: flex-end;",
+                    button { onclick: move |_| answer(PermissionChoice::Deny), "Deny" }
+                    button { onclick: move |_| answer(PermissionChoice::Once), "Allow once" }
+                    if request.remember {
+                        button {
+                            style: "background: #007bff; color: white; border: none; padding: 0.4em 0.8em; border-radius: 3px;",
+                            onclick: move |_| answer(PermissionChoice::Always),
+                            "Always allow"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{Route, app_settings::Chat, core::transcript, llm::Message};
+
+/// Characters of a pinned message shown in the pins panel.
+const SNIPPET_CHARS: usize = 120;
+
+/// Scrolls the element with the ID sent into view and highlights it briefly.
+const SCROLL_JS: &str = r#"
+const el = document.getElementById(await dioxus.recv());
+if (el) {
+    el.scrollIntoView({ behavior: "smooth", block: "start" });
+    el.classList.remove("message-highlight");
+    // Restart the animation if the message is already highlighted
+    void el.offsetWidth;
+    el.classList.add("message-highlight");
+}
+"#;
+
+/// Copies the text sent, resolved as a URL against the page's, to the clipboard.
+const COPY_LINK_JS: &str = r#"
+const path = await dioxus.recv();
+await navigator.clipboard.writeText(new URL(path, location.href).href);
+"#;
+
+/// A pinned message, as listed in the pins panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinEntry {
+    /// Position of the message, or of the summary that replaced its turn
+    pub index: usize,
+    /// Stable ID of the message, or of the summary, to scroll to
+    pub id: u32,
+    /// Position of the turn in the archive and of the message in the turn,
+    /// if the turn was replaced by a summary
+    pub archived: Option<(usize, usize)>,
+    /// Start of the message's text
+    pub snippet: String,
+}
+
+/// ID of the element showing a message, for scrolling and linking to it.
+///
+/// # Arguments
+/// * `id` - Stable ID of the message (see [`Chat::message_id`])
+pub fn message_anchor(id: u32) -> String {
+    format!("msg-{id}")
+}
+
+/// The stable message ID in an anchor made by [`message_anchor`].
+pub fn parse_message_anchor(anchor: &str) -> Option<u32> {
+    anchor.strip_prefix("msg-")?.parse().ok()
+}
+
+/// Scrolls to a message and highlights it.
+///
+/// # Arguments
+/// * `id` - Stable ID of the message
+pub fn scroll_to_message(id: u32) {
+    let eval = document::eval(SCROLL_JS);
+    if let Err(e) = eval.send(message_anchor(id)) {
+        warn!("Could not scroll to message: {e:?}");
+    }
+}
+
+/// Lists the pinned messages of a chat in conversation order.
+pub fn pin_entries(chat: &Chat) -> Vec<PinEntry> {
+    let mut entries: Vec<PinEntry> = chat
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| chat.is_pinned(*index))
+        .map(|(index, message)| PinEntry {
+            index,
+            id: chat.message_id(index),
+            archived: None,
+            snippet: snippet(message),
+        })
+        .collect();
+    for (a, turn) in chat.archive.iter().enumerate() {
+        for (offset, message) in turn.messages.iter().enumerate() {
+            if turn.is_pinned(offset) {
+                entries.push(PinEntry {
+                    index: turn.index,
+                    id: chat.message_id(turn.index),
+                    archived: Some((a, offset)),
+                    snippet: snippet(message),
+                });
+            }
+        }
+    }
+    entries.sort_by_key(|e| e.index);
+    entries
+}
+
+/// Toggle pinning a message, shown at the corner of the message.
+///
+/// # Arguments
+/// * `pinned` - Whether the message is pinned
+/// * `on_toggle` - Called with whether the message should be pinned
+#[component]
+pub fn PinButton(pinned: bool, on_toggle: EventHandler<bool>) -> Element {
+    let (opacity, title) = if pinned {
+        ("1", "Unpin: stop always sending this message to the model")
+    } else {
+        (
+            "0.35",
+            "Pin: always send this message to the model, even after the conversation is shortened",
+        )
+    };
+    rsx! {
+        button {
+            style: "position: absolute; top: -4px; right: 3.5em; z-index: 1; border: none; background: none; cursor: pointer; font-size: 1em; opacity: {opacity};",
+            title,
+            onclick: move |_| on_toggle(!pinned),
+            "📌"
+        }
+    }
+}
+
+/// Button copying a link to a message, as `/chats/3#msg-42`, shown at the
+/// corner of the message next to the pin.
+///
+/// # Arguments
+/// * `chat_id` - ID of the chat
+/// * `message_id` - Stable ID of the message
+#[component]
+pub fn MessageLinkButton(chat_id: u32, message_id: u32) -> Element {
+    let mut copied = use_signal(|| false);
+    let nav = navigator();
+    rsx! {
+        button {
+            style: "position: absolute; top: -4px; right: 5em; z-index: 1; border: none; background: none; cursor: pointer; font-size: 1em; opacity: 0.35;",
+            title: if copied() { "Link copied" } else { "Copy a link to this message" },
+            onclick: move |_| async move {
+                let route = Route::ChatEl {
+                    id: chat_id,
+                    anchor: message_anchor(message_id),
+                };
+                let eval = document::eval(COPY_LINK_JS);
+                if let Err(e) = eval.send(route.to_string()) {
+                    warn!("Could not copy the link: {e:?}");
+                    return;
+                }
+                // The address bar shows the link too, where there is one
+                nav.replace(route);
+                copied.set(true);
+            },
+            if copied() { "✓" } else { "🔗" }
+        }
+    }
+}
+
+/// Collapsible list of a chat's pinned messages, kept at the top of the chat.
+/// Clicking a message scrolls to it.
+///
+/// # Arguments
+/// * `pins` - The pinned messages
+/// * `on_unpin` - Called with the message to unpin
+#[component]
+pub fn PinsPanel(pins: Vec<PinEntry>, on_unpin: EventHandler<PinEntry>) -> Element {
+    let mut open = use_signal(|| false);
+    if pins.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { style: "position: sticky; top: 0; z-index: 2; margin: 0 1em; padding: 0.25em 0.5em; font-size: 0.9em; background: var(--theme-surface, white); border-bottom: 1px solid #dee2e6;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "📌 Pinned ({pins.len()})"
+            }
+            if open() {
+                for pin in pins {
+                    div {
+                        key: "{pin.index}-{pin.archived:?}",
+                        style: "display: flex; align-items: baseline; gap: 0.5em; padding: 0.25em 0;",
+                        span {
+                            style: "flex: 1; cursor: pointer; color: #0d6efd; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;",
+                            title: "Scroll to the message",
+                            onclick: {
+                                let id = pin.id;
+                                move |_| scroll_to_message(id)
+                            },
+                            "{pin.snippet}"
+                        }
+                        if pin.archived.is_some() {
+                            span {
+                                style: "color: #666;",
+                                title: "The turn was replaced by a summary; the message is still sent to the model",
+                                "summarized"
+                            }
+                        }
+                        button {
+                            title: "Unpin",
+                            onclick: {
+                                let pin = pin.clone();
+                                move |_| on_unpin(pin.clone())
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The start of a message's text, on one line.
+fn snippet(message: &Message) -> String {
+    let text = transcript(std::slice::from_ref(message));
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(SNIPPET_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line,
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{
+    AppSettings,
+    mcp::{
+        plugin::{PluginCapabilities, PluginSpec},
+        tool_ref::validate_server_id,
+    },
+};
+
+/// List of WASM plugins with the capabilities granted to each, and a form to
+/// add one. Plugins are loaded as soon as the settings are saved.
+///
+/// # Arguments
+/// * `settings` - Current settings
+/// * `on_save` - Called with the settings after plugins are changed
+#[component]
+pub fn PluginSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>) -> Element {
+    let mut new_id = use_signal(String::new);
+    let mut new_path = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let plugins = settings.plugins.clone();
+    let save = use_callback(move |plugins: Vec<PluginSpec>| {
+        on_save(AppSettings {
+            plugins,
+            ..settings.clone()
+        });
+    });
+
+    let existing = plugins.clone();
+    let add = move |_| {
+        let id = new_id().trim().to_string();
+        let path = new_path().trim().to_string();
+        if let Err(e) = validate_server_id(&id) {
+            error.set(Some(e.to_string()));
+            return;
+        }
+        if existing.iter().any(|p| p.id == id) {
+            error.set(Some(format!("A plugin with ID {id} already exists")));
+            return;
+        }
+        if path.is_empty() {
+            error.set(Some("Enter the path of the .wasm file".into()));
+            return;
+        }
+        let mut plugins = existing.clone();
+        plugins.push(PluginSpec {
+            id,
+            path,
+            enabled: true,
+            capabilities: PluginCapabilities::default(),
+        });
+        save(plugins);
+        new_id.set(String::new());
+        new_path.set(String::new());
+        error.set(None);
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Plugins" }
+        p { style: "color: #666; font-size: 0.9em; margin-top: 0;",
+            "WASM plugins add tools that run in a sandbox. They can only fetch URLs or keep data if you allow it."
+        }
+        if plugins.is_empty() {
+            p { style: "color: #666; font-style: italic;", "No plugins installed" }
+        }
+        for (index , plugin) in plugins.iter().cloned().enumerate() {
+            div {
+                key: "{plugin.id}",
+                style: "border: 1px solid #ddd; border-radius: 4px; padding: 0.5rem; margin-bottom: 0.5rem;",
+                div { style: "display: flex; align-items: center; gap: 0.5rem;",
+                    input {
+                        r#type: "checkbox",
+                        title: "Load this plugin",
+                        checked: plugin.enabled,
+                        oninput: {
+                            let plugins = plugins.clone();
+                            move |e: Event<FormData>| {
+                                let mut plugins = plugins.clone();
+                                plugins[index].enabled = e.checked();
+                                save(plugins);
+                            }
+                        },
+                    }
+                    strong { "{plugin.id}" }
+                    span { style: "flex: 1; color: #666; font-size: 0.85em; overflow-wrap: anywhere;",
+                        "{plugin.path}"
+                    }
+                    button {
+                        title: "Remove the plugin",
+                        onclick: {
+                            let plugins = plugins.clone();
+                            move |_| {
+                                let mut plugins = plugins.clone();
+                                plugins.remove(index);
+                                save(plugins);
+                            }
+                        },
+                        "×"
+                    }
+                }
+                CapabilityEditor {
+                    capabilities: plugin.capabilities.clone(),
+                    onchange: {
+                        let plugins = plugins.clone();
+                        move |capabilities: PluginCapabilities| {
+                            let mut plugins = plugins.clone();
+                            plugins[index].capabilities = capabilities;
+                            save(plugins);
+                        }
+                    },
+                }
+            }
+        }
+        div { style: "display: flex; gap: 0.5rem; flex-wrap: wrap;",
+            input {
+                style: "width: 8em;",
+                placeholder: "ID",
+                value: new_id(),
+                oninput: move |e| new_id.set(e.value()),
+            }
+            input {
+                style: "flex: 1; min-width: 12em;",
+                placeholder: "Path of the .wasm file",
+                value: new_path(),
+                oninput: move |e| new_path.set(e.value()),
+            }
+            button { onclick: add, "+ Add Plugin" }
+        }
+        if let Some(e) = error() {
+            div { style: "color: #dc3545; margin-top: 0.5rem;", "{e}" }
+        }
+    }
+}
+
+/// Checkboxes for the host functions granted to a plugin.
+///
+/// # Arguments
+/// * `capabilities` - The granted capabilities
+/// * `onchange` - Called with the edited capabilities
+#[component]
+fn CapabilityEditor(
+    capabilities: PluginCapabilities,
+    onchange: Callback<PluginCapabilities, ()>,
+) -> Element {
+    let hosts = capabilities.fetch_hosts.join(", ");
+    rsx! {
+        div { style: "display: flex; align-items: center; gap: 1rem; flex-wrap: wrap; margin-top: 0.25rem; font-size: 0.9em;",
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: capabilities.fetch,
+                    oninput: {
+                        let capabilities = capabilities.clone();
+                        move |e: Event<FormData>| {
+                            onchange(PluginCapabilities {
+                                fetch: e.checked(),
+                                ..capabilities.clone()
+                            })
+                        }
+                    },
+                }
+                " Fetch URLs"
+            }
+            if capabilities.fetch {
+                input {
+                    style: "flex: 1; min-width: 10em;",
+                    placeholder: "Allowed hosts, comma-separated, or * for any",
+                    value: "{hosts}",
+                    onchange: {
+                        let capabilities = capabilities.clone();
+                        move |e: Event<FormData>| {
+                            let fetch_hosts = e
+                                .value()
+                                .split(',')
+                                .map(|h| h.trim().to_string())
+                                .filter(|h| !h.is_empty())
+                                .collect();
+                            onchange(PluginCapabilities {
+                                fetch_hosts,
+                                ..capabilities.clone()
+                            })
+                        }
+                    },
+                }
+            }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: capabilities.storage,
+                    oninput: {
+                        let capabilities = capabilities.clone();
+                        move |e: Event<FormData>| {
+                            onchange(PluginCapabilities {
+                                storage: e.checked(),
+                                ..capabilities.clone()
+                            })
+                        }
+                    },
+                }
+                " Keep data"
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::llm::ToolCallDelta;
+
+/// Review panel for tool calls proposed in "plan only" mode.
+///
+/// Lists each call with its arguments and a checkbox; all calls start selected.
+/// Executing runs the selected calls and records the others as declined. Give
+/// each proposal its own `key` so the selection starts afresh.
+#[component]
+pub fn ProposedCalls(
+    calls: Vec<ToolCallDelta>,
+    disabled: bool,
+    on_execute: Callback<Vec<bool>, ()>,
+    on_discard: Callback<(), ()>,
+) -> Element {
+    let count = calls.len();
+    let mut selected = use_signal(|| vec![true; count]);
+    let selected_count = selected.read().iter().filter(|s| **s).count();
+
+    rsx! {
+        div { style: "
+            background-color: #e7f1ff;
+            border: 1px solid #b8d4fe;
+            border-radius: 4px;
+            padding: 1em;
+            margin: 1em 0;
+            ",
+            div { style: "font-weight: bold; margin-bottom: 0.5em;",
+                "The model wants to call {count} tool(s). Select the calls to run:"
+            }
+            for (i , call) in calls.into_iter().enumerate() {
+                {
+                    let (name, raw_args) = call
+                        .function
+                        .map(|f| (f.name.unwrap_or_default(), f.arguments.unwrap_or_default()))
+                        .unwrap_or_default();
+                    // Pretty-print valid JSON, show anything else as sent
+                    let args = serde_json::from_str::<serde_json::Value>(&raw_args)
+                        .ok()
+                        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+                        .unwrap_or(raw_args);
+                    rsx! {
+                        label {
+                            key: "{i}",
+                            style: "
+                                display: flex;
+                                gap: 0.5em;
+                                align-items: flex-start;
+                                margin-bottom: 0.5em;
+                                background: white;
+                                border-radius: 4px;
+                                padding: 0.5em;
+                            ",
+                            input {
+                                r#type: "checkbox",
+                                checked: selected.read().get(i).copied().unwrap_or(false),
+                                disabled,
+                                oninput: move |e: Event<FormData>| {
+                                    if let Some(s) = selected.write().get_mut(i) {
+                                        *s = e.checked();
+                                    }
+                                },
+                            }
+                            div { style: "flex: 1; min-width: 0;",
+                                div { style: "font-family: monospace; font-weight: bold;", "🔧 {name}" }
+                                pre { style: "
+                                        margin: 0.25em 0 0 0;
+                                        font-size: 0.85em;
+                                        white-space: pre-wrap;
+                                        word-break: break-word;
+                                    ",
+                                    "{args}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 0.5em;",
+                button {
+                    disabled,
+                    onclick: move |_| on_execute(selected()),
+                    if selected_count == count {
+                        "Run all"
+                    } else {
+                        "Run {selected_count} selected"
+                    }
+                }
+                button { disabled, onclick: move |_| on_discard(()), "Don't run any" }
+            }
+        }
+    }
+}
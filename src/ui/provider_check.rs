@@ -0,0 +1,159 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Connection tests of providers, and a warning while one seems down.
+//!
+//! A test lists the provider's models and streams a tiny completion that
+//! offers a tool, reporting how long each took, whether the reply streamed
+//! and whether the model called the tool. The last result of each kind of
+//! provider is kept in the settings, and a failed one is shown in the chat
+//! until a test passes again.
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    app_settings::{AppSettings, ProviderSettings},
+    core::{now_millis, time_ago},
+    llm::ProviderCheck,
+    utils::record_provider_check,
+};
+
+/// Tests a provider and records the result.
+///
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `provider` - The provider to test, with its model set
+async fn test_provider(mut settings: Signal<Option<AppSettings>>, provider: ProviderSettings) {
+    let Some(model) = provider.get_model() else {
+        return;
+    };
+    let check = provider.client().check(&model).await;
+    if let Err(e) = record_provider_check(&mut settings, &provider, check).await {
+        warn!("Could not record the connection test: {e:?}");
+    }
+}
+
+/// Findings of a test, one per line.
+fn findings(check: &ProviderCheck) -> Vec<String> {
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    let mut lines = vec![];
+    if let Some(ms) = check.models_ms {
+        lines.push(format!("Model list: {ms} ms"));
+    }
+    if check.model_listed == Some(false) {
+        lines.push(format!("{} is not among the listed models", check.model));
+    }
+    if let Some(ms) = check.first_token_ms {
+        lines.push(format!("First token: {ms} ms"));
+    }
+    if let Some(ms) = check.completion_ms {
+        lines.push(format!("Whole reply: {ms} ms"));
+    }
+    if let Some(streaming) = check.streaming {
+        lines.push(format!("Streaming: {}", yes_no(streaming)));
+    }
+    if let Some(tools) = check.tool_calls {
+        lines.push(format!("Tool calls: {}", yes_no(tools)));
+    }
+    lines
+}
+
+/// "Test connection" button for the provider in the settings, with the
+/// result of its last test.
+///
+/// # Arguments
+/// * `provider` - The provider, as saved
+/// * `last` - Result of its last test, if it was tested
+/// * `on_tested` - Called once the result is recorded
+#[component]
+pub fn ProviderCheckPanel(
+    provider: ProviderSettings,
+    last: Option<ProviderCheck>,
+    on_tested: Callback<(), ()>,
+) -> Element {
+    let settings = use_context::<Signal<Option<AppSettings>>>();
+    let mut testing = use_signal(|| false);
+    let has_model = provider.get_model().is_some();
+    let name = provider.name();
+
+    let test = move |_| {
+        let provider = provider.clone();
+        async move {
+            testing.set(true);
+            test_provider(settings, provider).await;
+            testing.set(false);
+            on_tested(());
+        }
+    };
+
+    rsx! {
+        div { style: "margin-top: 0.5em; padding: 0.5em; border: 1px solid #ddd; border-radius: 4px; font-size: 0.9em; display: flex; flex-direction: column; gap: 0.2em;",
+            div {
+                button {
+                    disabled: testing() || !has_model,
+                    title: if has_model { "List the models and ask the model for a tiny reply that calls a tool" } else { "Choose a model first" },
+                    onclick: test,
+                    if testing() {
+                        "Testing…"
+                    } else {
+                        "Test connection"
+                    }
+                }
+            }
+            if let Some(check) = last {
+                div { style: "color: #666;", "Last tested {time_ago(check.checked_at, now_millis())} with {check.model}" }
+                if let Some(error) = &check.error {
+                    div { style: "color: #dc3545;", "{name} could not be reached: {error}" }
+                }
+                for line in findings(&check) {
+                    div { "{line}" }
+                }
+            }
+        }
+    }
+}
+
+/// Warning in the chat when the last connection test of the provider
+/// failed, with a button to test it again.
+///
+/// # Arguments
+/// * `provider` - The provider in use
+/// * `check` - Result of its last test
+#[component]
+pub fn ProviderDownWarning(provider: ProviderSettings, check: ProviderCheck) -> Element {
+    let settings = use_context::<Signal<Option<AppSettings>>>();
+    let mut dismissed = use_signal(|| false);
+    let mut testing = use_signal(|| false);
+    let Some(error) = check.error.clone() else {
+        return rsx! {};
+    };
+    if dismissed() {
+        return rsx! {};
+    }
+    let name = provider.name();
+    let when = time_ago(check.checked_at, now_millis());
+
+    rsx! {
+        div { style: "background: #fff3cd; border: 1px solid #ffeeba; color: #856404; border-radius: 4px; padding: 0.5em 1em; margin: 0.5em 1em; display: flex; gap: 1em; align-items: center;",
+            span { style: "flex: 1;", title: "{error}",
+                "{name} seemed down when tested {when}; replies may fail."
+            }
+            button {
+                disabled: testing(),
+                onclick: move |_| {
+                    let provider = provider.clone();
+                    async move {
+                        testing.set(true);
+                        test_provider(settings, provider).await;
+                        testing.set(false);
+                    }
+                },
+                if testing() {
+                    "Testing…"
+                } else {
+                    "Test again"
+                }
+            }
+            button { onclick: move |_| dismissed.set(true), "Dismiss" }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+use serde_json::Value;
+
+use crate::app_settings::ProviderOptions;
+
+/// Example body fields, shown as a placeholder.
+const BODY_PLACEHOLDER: &str = r#"{
+  "provider": { "order": ["anthropic", "openai"], "allow_fallbacks": true },
+  "transforms": ["middle-out"]
+}"#;
+
+/// Editor for extra headers and body fields sent to the provider.
+///
+/// Headers are edited one per line as `Name: value`, body fields as a JSON
+/// object. Nothing is saved until both parse.
+///
+/// # Arguments
+/// * `options` - The provider's current options
+/// * `on_save` - Called with the new options
+#[component]
+pub fn ProviderOptionsEditor(
+    options: ProviderOptions,
+    on_save: Callback<ProviderOptions, ()>,
+) -> Element {
+    let mut open = use_signal(|| !options.is_empty());
+    let mut headers_text = use_signal(|| {
+        options
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    let mut body_text = use_signal(|| {
+        if options.body.is_empty() {
+            String::new()
+        } else {
+            serde_json::to_string_pretty(&options.body).unwrap_or_default()
+        }
+    });
+    let mut status: Signal<Option<Result<(), String>>> = use_signal(|| None);
+
+    let save = move |_| {
+        let res = parse_options(&headers_text(), &body_text());
+        status.set(Some(res.as_ref().map(|_| ()).map_err(|e| format!("{e:#}"))));
+        if let Ok(options) = res {
+            on_save(options);
+        }
+    };
+
+    rsx! {
+        div { style: "margin-top: 1em;",
+            div {
+                style: "cursor: pointer; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Request options"
+            }
+            if open() {
+                div { style: "display: flex; flex-direction: column; gap: 0.25em; margin-top: 0.5em;",
+                    label { "Extra headers, one per line" }
+                    textarea {
+                        style: "height: 4em; font-family: monospace;",
+                        value: "{headers_text}",
+                        placeholder: "HTTP-Referer: https://example.com\nX-Title: My app",
+                        oninput: move |e: Event<FormData>| headers_text.set(e.value()),
+                    }
+                    label { style: "margin-top: 0.5em;", "Extra body fields (JSON object)" }
+                    textarea {
+                        style: "height: 8em; font-family: monospace;",
+                        value: "{body_text}",
+                        placeholder: BODY_PLACEHOLDER,
+                        oninput: move |e: Event<FormData>| body_text.set(e.value()),
+                    }
+                    div { style: "display: flex; gap: 0.5em; align-items: center;",
+                        button { onclick: save, "Save options" }
+                        match status() {
+                            Some(Ok(())) => rsx! {
+                                span { style: "color: #28a745;", "Saved" }
+                            },
+                            Some(Err(e)) => rsx! {
+                                span { style: "color: #dc3545;", "{e}" }
+                            },
+                            None => rsx! {},
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parses the text of the editor.
+///
+/// # Arguments
+/// * `headers` - `Name: value` lines; blank lines are ignored
+/// * `body` - A JSON object, or blank for none
+///
+/// # Returns
+/// The options, or an error describing the first problem
+fn parse_options(headers: &str, body: &str) -> anyhow::Result<ProviderOptions> {
+    let mut options = ProviderOptions::default();
+    for line in headers.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Some((name, value)) = line.split_once(':') else {
+            anyhow::bail!("Expected \"Name: value\", got {line:?}");
+        };
+        options
+            .headers
+            .insert(name.trim().to_string(), value.trim().to_string());
+    }
+    if !body.trim().is_empty() {
+        match serde_json::from_str(body)? {
+            Value::Object(fields) => options.body = fields,
+            _ => anyhow::bail!("Extra body fields must be a JSON object"),
+        }
+    }
+    options.validate()?;
+    Ok(options)
+}
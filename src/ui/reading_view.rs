@@ -0,0 +1,454 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::{core::use_drop, logger::tracing::warn, prelude::*};
+
+use crate::{
+    Route,
+    app_settings::{Chat, Toolsets},
+    llm::Message,
+    storage::{Storage, get_storage},
+    toolset::story::Story,
+    tts,
+};
+
+/// Words read per minute, for the reading time estimate.
+const WORDS_PER_MINUTE: usize = 250;
+
+/// Font sizes offered, in pixels.
+const MIN_FONT_SIZE: u32 = 14;
+const MAX_FONT_SIZE: u32 = 28;
+
+/// Scrolls the chapter with the ID sent to the top of the reading pane.
+const SCROLL_JS: &str = r#"
+const el = document.getElementById(await dioxus.recv());
+if (el) {
+    el.scrollIntoView({ behavior: "smooth", block: "start" });
+}
+"#;
+
+/// Scrolls the sentence being read aloud into view.
+const FOLLOW_JS: &str = r#"
+const el = document.getElementById("reading-aloud");
+if (el) {
+    el.scrollIntoView({ behavior: "smooth", block: "center" });
+}
+"#;
+
+/// Reading a story aloud, a sentence at a time.
+#[derive(Clone, Copy)]
+struct Playback {
+    /// Chapter and sentence being read, or where reading was paused
+    position: Signal<Option<(usize, usize)>>,
+    playing: Signal<bool>,
+    /// Bumped to end the reading in progress
+    run: Signal<u64>,
+    /// Speech rate, 1.0 being the voice's normal rate
+    rate: Signal<f32>,
+    /// Whether reading goes on into the next chapter at the end of one
+    play_on: Signal<bool>,
+    error: Signal<Option<String>>,
+}
+
+impl Playback {
+    /// Starts reading, from a sentence of a chapter to the end of the
+    /// chapter, or of the story if reading goes on into the next chapters.
+    ///
+    /// # Arguments
+    /// * `chapters` - Sentences of each chapter, as from [`spoken_chapters`]
+    /// * `chapter` - Index of the chapter to start in
+    /// * `sentence` - Index of the sentence to start at
+    fn start(mut self, chapters: Vec<Vec<String>>, chapter: usize, sentence: usize) {
+        tts::backend().stop();
+        self.run += 1;
+        let run = *self.run.peek();
+        self.playing.set(true);
+        self.error.set(None);
+        spawn(async move {
+            let backend = tts::backend();
+            let (mut c, mut s) = (chapter, sentence);
+            while let Some(sentences) = chapters.get(c) {
+                if s >= sentences.len() {
+                    if !*self.play_on.peek() {
+                        break;
+                    }
+                    c += 1;
+                    s = 0;
+                    continue;
+                }
+                if *self.run.peek() != run {
+                    return;
+                }
+                self.position.set(Some((c, s)));
+                let _ = document::eval(FOLLOW_JS);
+                let rate = *self.rate.peek();
+                match backend.speak(&sentences[s], rate).await {
+                    Ok(true) => s += 1,
+                    // Paused, stopped or started elsewhere, which set the state
+                    Ok(false) => return,
+                    Err(e) => {
+                        warn!("Could not read aloud: {e:?}");
+                        self.error.set(Some(e.to_string()));
+                        self.playing.set(false);
+                        return;
+                    }
+                }
+            }
+            if *self.run.peek() == run {
+                self.playing.set(false);
+                self.position.set(None);
+            }
+        });
+    }
+
+    /// Stops reading, keeping the position to resume from.
+    fn pause(mut self) {
+        self.run += 1;
+        self.playing.set(false);
+        tts::backend().stop();
+    }
+
+    /// Stops reading and forgets the position.
+    fn stop(mut self) {
+        self.pause();
+        self.position.set(None);
+    }
+}
+
+/// Distraction-free view of a story: just the text, with typography controls
+/// and a sidebar for jumping between chapters.
+///
+/// # Arguments
+/// * `id` - ID of the story's chat
+#[component]
+pub fn ReadStory(id: u32) -> Element {
+    let chat = use_resource(move || async move {
+        let storage = get_storage().await?;
+        storage.get_chat(id).await
+    });
+    let mut font_size = use_signal(|| 18u32);
+    // Line length in characters
+    let mut width = use_signal(|| 70u32);
+    let mut serif = use_signal(|| true);
+    let playback = Playback {
+        position: use_signal(|| None),
+        playing: use_signal(|| false),
+        run: use_signal(|| 0),
+        rate: use_signal(|| 1.0),
+        play_on: use_signal(|| true),
+        error: use_signal(|| None),
+    };
+    // Speech would go on after leaving the page
+    use_drop(|| tts::backend().stop());
+
+    let chat = match &*chat.read() {
+        None => {
+            return rsx! {
+                div { style: "padding: 3em 1rem;", "Loading..." }
+            };
+        }
+        Some(Err(e)) => {
+            warn!("Could not load story: {e:?}");
+            return rsx! {
+                div { style: "padding: 3em 1rem;", "Could not load the story: {e}" }
+            };
+        }
+        Some(Ok(chat)) => chat.clone(),
+    };
+    let Some(chat) = chat.filter(|c| c.chat_type == Toolsets::Story) else {
+        return rsx! {
+            div { style: "padding: 3em 1rem;", "There is no story with this ID." }
+        };
+    };
+    let story: Story = serde_json::from_value(chat.value.clone()).unwrap_or_else(|e| {
+        warn!("Invalid story: {e:?}");
+        Default::default()
+    });
+    let stats = story_stats(&story, &chat);
+    let spoken = spoken_chapters(&story);
+    let mut rate = playback.rate;
+    let mut play_on = playback.play_on;
+    let title = if story.metadata.title.is_empty() {
+        "Untitled story".to_string()
+    } else {
+        story.metadata.title.clone()
+    };
+    let font_family = if serif() {
+        "Georgia, 'Times New Roman', serif"
+    } else {
+        "system-ui, -apple-system, 'Segoe UI', sans-serif"
+    };
+
+    rsx! {
+        div { style: "display: flex; height: 100%; box-sizing: border-box;",
+            // Chapter navigation
+            nav { style: "
+                flex: 0 0 14em;
+                padding: 3em 1em 1em 1em;
+                overflow-y: auto;
+                border-right: 1px solid #dee2e6;
+                background: #f8f9fa;
+                font-size: 0.9em;
+                ",
+                div { style: "font-weight: 600; margin-bottom: 0.5em;", "Chapters" }
+                if story.chapters.is_empty() {
+                    div { style: "color: #666;", "No chapters yet" }
+                }
+                for (i , chapter) in story.chapters.iter().enumerate() {
+                    div {
+                        key: "{i}",
+                        style: "padding: 0.25em 0; cursor: pointer; color: #0d6efd;",
+                        onclick: move |_| async move {
+                            let eval = document::eval(SCROLL_JS);
+                            if let Err(e) = eval.send(chapter_anchor(i)) {
+                                warn!("Could not scroll to chapter: {e:?}");
+                            }
+                        },
+                        "{i + 1}. {chapter.title}"
+                    }
+                }
+            }
+            div { style: "flex: 1; display: flex; flex-direction: column; min-width: 0;",
+                // Typography controls
+                div { style: "
+                    display: flex;
+                    flex-wrap: wrap;
+                    align-items: center;
+                    gap: 1em;
+                    padding: 0.5em 1em;
+                    border-bottom: 1px solid #dee2e6;
+                    font-size: 0.9em;
+                    ",
+                    Link { to: Route::ChatEl { id, anchor: String::new() }, "← Back to the chat" }
+                    span { style: "display: inline-flex; align-items: center; gap: 0.25em;",
+                        "Text size"
+                        button {
+                            disabled: font_size() <= MIN_FONT_SIZE,
+                            onclick: move |_| font_size -= 2,
+                            "A−"
+                        }
+                        button {
+                            disabled: font_size() >= MAX_FONT_SIZE,
+                            onclick: move |_| font_size += 2,
+                            "A+"
+                        }
+                    }
+                    label { style: "display: inline-flex; align-items: center; gap: 0.5em;",
+                        "Width"
+                        input {
+                            r#type: "range",
+                            min: "40",
+                            max: "120",
+                            step: "5",
+                            value: "{width}",
+                            oninput: move |e: Event<FormData>| {
+                                if let Ok(w) = e.value().parse() {
+                                    width.set(w);
+                                }
+                            },
+                        }
+                    }
+                    label { style: "display: inline-flex; align-items: center; gap: 0.5em;",
+                        input {
+                            r#type: "checkbox",
+                            checked: serif(),
+                            oninput: move |e: Event<FormData>| serif.set(e.checked()),
+                        }
+                        "Serif"
+                    }
+                    span { style: "display: inline-flex; align-items: center; gap: 0.25em;",
+                        if (playback.playing)() {
+                            button {
+                                title: "Pause reading aloud",
+                                onclick: move |_| playback.pause(),
+                                "⏸ Pause"
+                            }
+                        } else {
+                            button {
+                                disabled: story.chapters.is_empty(),
+                                title: "Read the story aloud from where it was paused, or from the start",
+                                onclick: {
+                                    let spoken = spoken.clone();
+                                    move |_| {
+                                        let (c, s) = (playback.position)().unwrap_or((0, 0));
+                                        playback.start(spoken.clone(), c, s);
+                                    }
+                                },
+                                if (playback.position)().is_some() { "▶ Resume" } else { "▶ Listen" }
+                            }
+                        }
+                        if (playback.position)().is_some() {
+                            button {
+                                title: "Stop reading aloud",
+                                onclick: move |_| playback.stop(),
+                                "⏹"
+                            }
+                        }
+                        select {
+                            title: "Speech rate",
+                            onchange: move |e| {
+                                if let Ok(r) = e.value().parse() {
+                                    rate.set(r);
+                                }
+                            },
+                            for r in tts::RATES {
+                                option {
+                                    key: "{r}",
+                                    value: "{r}",
+                                    selected: r == rate(),
+                                    "{r}×"
+                                }
+                            }
+                        }
+                    }
+                    label {
+                        style: "display: inline-flex; align-items: center; gap: 0.5em;",
+                        title: "Go on reading into the next chapter at the end of one",
+                        input {
+                            r#type: "checkbox",
+                            checked: play_on(),
+                            oninput: move |e: Event<FormData>| play_on.set(e.checked()),
+                        }
+                        "Play on"
+                    }
+                    if let Some(error) = (playback.error)() {
+                        span { style: "color: #dc3545;", "{error}" }
+                    }
+                }
+                // The story
+                div { style: "flex: 1; overflow-y: auto;",
+                    article { style: format!(
+                            "max-width: {}ch; margin: 0 auto; padding: 2em 1.5em 4em 1.5em; font-size: {}px; line-height: 1.7; font-family: {font_family};",
+                            width(),
+                            font_size(),
+                        ),
+                        h1 { "{title}" }
+                        p { style: "color: #666; font-size: 0.8em;", "{stats}" }
+                        if !story.metadata.synopsis.is_empty() {
+                            p { style: "font-style: italic;", "{story.metadata.synopsis}" }
+                        }
+                        for (i , chapter) in story.chapters.iter().enumerate() {
+                            section { key: "{i}",
+                                h2 { id: chapter_anchor(i),
+                                    span {
+                                        id: if (playback.position)() == Some((i, 0)) { "reading-aloud" },
+                                        style: if (playback.position)() == Some((i, 0)) { "background: #fff3b0;" },
+                                        "{chapter.title}"
+                                    }
+                                    button {
+                                        style: "margin-left: 0.5em; border: none; background: none; cursor: pointer; font-size: 0.6em; color: #0d6efd;",
+                                        title: "Listen to this chapter",
+                                        onclick: {
+                                            let spoken = spoken.clone();
+                                            move |_| playback.start(spoken.clone(), i, 0)
+                                        },
+                                        "▶"
+                                    }
+                                }
+                                match (playback.position)() {
+                                    // Sentences of the chapter being read, to highlight the current one
+                                    Some((c, current)) if c == i => rsx! {
+                                        for (k , paragraph) in sentence_paragraphs(&chapter.content).into_iter().enumerate() {
+                                            p { key: "{k}",
+                                                for (n , sentence) in paragraph {
+                                                    span {
+                                                        key: "{n}",
+                                                        id: if n == current { "reading-aloud" },
+                                                        style: if n == current { "background: #fff3b0;" },
+                                                        title: "Read aloud from here",
+                                                        onclick: {
+                                                            let spoken = spoken.clone();
+                                                            move |_| playback.start(spoken.clone(), i, n)
+                                                        },
+                                                        "{sentence} "
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    },
+                                    _ => crate::md2rsx::markdown_to_rsx(&chapter.content),
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The sentences read aloud for each chapter: its title, then its text.
+fn spoken_chapters(story: &Story) -> Vec<Vec<String>> {
+    story
+        .chapters
+        .iter()
+        .map(|chapter| {
+            std::iter::once(chapter.title.clone())
+                .chain(
+                    tts::speakable_paragraphs(&chapter.content)
+                        .into_iter()
+                        .flatten(),
+                )
+                .collect()
+        })
+        .collect()
+}
+
+/// A chapter's text as paragraphs of sentences, numbered as in
+/// [`spoken_chapters`] so the one being read can be highlighted.
+fn sentence_paragraphs(content: &str) -> Vec<Vec<(usize, String)>> {
+    // Sentence 0 is the chapter title
+    let mut n = 0;
+    tts::speakable_paragraphs(content)
+        .into_iter()
+        .map(|paragraph| {
+            paragraph
+                .into_iter()
+                .map(|sentence| {
+                    n += 1;
+                    (n, sentence)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// ID of a chapter's heading, for scrolling to it.
+fn chapter_anchor(index: usize) -> String {
+    format!("chapter-{}", index + 1)
+}
+
+/// Summarizes the length of a story and the conversation that wrote it.
+///
+/// # Arguments
+/// * `story` - The story
+/// * `chat` - The chat the story was written in
+///
+/// # Returns
+/// A line such as "3 chapters · 4200 words · about 17 minutes to read · 12 messages"
+fn story_stats(story: &Story, chat: &Chat) -> String {
+    let words: usize = story
+        .chapters
+        .iter()
+        .map(|c| c.content.split_whitespace().count())
+        .sum();
+    let minutes = words.div_ceil(WORDS_PER_MINUTE);
+    let messages = chat
+        .messages
+        .iter()
+        .filter(|m| matches!(m, Message::User { .. } | Message::Assistant { .. }))
+        .count();
+    let plural = |n: usize, word: &str| {
+        if n == 1 {
+            format!("{n} {word}")
+        } else {
+            format!("{n} {word}s")
+        }
+    };
+    format!(
+        "{} · {} · about {} to read · written in {}",
+        plural(story.chapters.len(), "chapter"),
+        plural(words, "word"),
+        plural(minutes, "minute"),
+        plural(messages, "message"),
+    )
+}
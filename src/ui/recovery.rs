@@ -0,0 +1,88 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::sync::Arc;
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    app_settings::Checkpoint,
+    mcp::host::MCPHost,
+    storage::{Storage, get_storage},
+};
+
+/// Banner offering to restore a turn that was interrupted before it was saved.
+///
+/// Checkpoints are written while the tool loop runs and cleared once the chat is
+/// saved, so a checkpoint found at startup means the app exited mid-turn.
+/// Restoring one also restores the state the MCP servers had at the time.
+#[component]
+pub fn RecoveryPrompt() -> Element {
+    let nav = navigator();
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let mut checkpoint: Signal<Option<Checkpoint>> = use_signal(|| None);
+    let _ = use_resource(move || async move {
+        let stg = get_storage().await?;
+        checkpoint.set(stg.load_checkpoint().await?);
+        anyhow::Ok(())
+    });
+
+    let restore = move |_| async move {
+        let Some(cp) = checkpoint.take() else { return };
+        let res = async {
+            let stg = get_storage().await?;
+            if !cp.server_states.is_empty() {
+                let host = host();
+                host.restore_states(cp.server_states).await;
+                crate::utils::save_server_states(&host).await?;
+            }
+            let id = stg.save_chat(&cp.chat).await?;
+            stg.clear_checkpoint().await?;
+            anyhow::Ok(id)
+        }
+        .await;
+        match res {
+            Ok(id) => {
+                nav.push(crate::Route::ChatEl { id, anchor: String::new() });
+            }
+            Err(e) => warn!("Could not restore checkpoint: {e:?}"),
+        }
+    };
+    let discard = move |_| async move {
+        checkpoint.set(None);
+        let res = async { get_storage().await?.clear_checkpoint().await }.await;
+        if let Err(e) = res {
+            warn!("Could not discard checkpoint: {e:?}");
+        }
+    };
+
+    let Some(cp) = checkpoint() else {
+        return rsx! {};
+    };
+    let count = cp.chat.messages.len();
+
+    rsx! {
+        div { style: "
+            position: fixed;
+            top: 3em;
+            left: 50%;
+            transform: translateX(-50%);
+            z-index: 10;
+            max-width: 40em;
+            background-color: #fff3cd;
+            border: 1px solid #ffeeba;
+            border-radius: 4px;
+            padding: 1em;
+            color: #856404;
+            ",
+            div { style: "margin-bottom: 0.5em;",
+                "A conversation was interrupted before it could be saved ({count} messages). Restore it?"
+            }
+            button {
+                style: "margin-right: 0.5em;",
+                onclick: restore,
+                "Restore"
+            }
+            button { onclick: discard, "Discard" }
+        }
+    }
+}
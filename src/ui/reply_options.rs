@@ -0,0 +1,198 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::llm::Constraint;
+
+/// Advanced options for the assistant's replies: stop sequences, a prefill and,
+/// for local backends, an output constraint.
+///
+/// Shown collapsed above the chat input. Stop sequences are entered one per
+/// line, with `\n` standing for a line break so sequences like `\nUser:` can be
+/// typed.
+///
+/// # Arguments
+/// * `stop` - Current stop sequences
+/// * `prefill` - Current start of the assistant's reply
+/// * `local` - Whether the provider is a local backend that can constrain output
+/// * `constraint` - Current output constraint
+/// * `on_stop_change` - Called with the new stop sequences
+/// * `on_prefill_change` - Called with the new prefill
+/// * `on_constraint_change` - Called with the new output constraint
+#[component]
+pub fn ReplyOptions(
+    stop: Vec<String>,
+    prefill: String,
+    local: bool,
+    constraint: Option<Constraint>,
+    on_stop_change: Callback<Vec<String>, ()>,
+    on_prefill_change: Callback<String, ()>,
+    on_constraint_change: Callback<Option<Constraint>, ()>,
+) -> Element {
+    let active = [
+        (!stop.is_empty()).then(|| format!("{} stop sequence(s)", stop.len())),
+        (!prefill.is_empty()).then(|| "prefill".to_string()),
+        match (&constraint, local) {
+            (Some(Constraint::Grammar(_)), true) => Some("grammar".to_string()),
+            (Some(Constraint::JsonSchema(_)), true) => Some("JSON schema".to_string()),
+            _ => None,
+        },
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+    let stop_text = stop
+        .iter()
+        .map(|s| escape(s))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    rsx! {
+        details { style: "margin-bottom: 0.5em; font-size: 0.9em;",
+            summary { style: "cursor: pointer; color: #666;",
+                "Reply options"
+                if !active.is_empty() {
+                    " ({active})"
+                }
+            }
+            div { style: "display: flex; gap: 1em; margin-top: 0.5em;",
+                label { style: "flex: 1; display: flex; flex-direction: column; gap: 0.25em;",
+                    span { title: "The model stops writing when it produces one of these. Write \\n for a line break.",
+                        "Stop sequences (one per line)"
+                    }
+                    textarea {
+                        style: "height: 4em; font-family: monospace;",
+                        placeholder: "\\nUser:",
+                        value: "{stop_text}",
+                        onchange: move |e: Event<FormData>| {
+                            let stop = e
+                                .value()
+                                .lines()
+                                .filter(|l| !l.is_empty())
+                                .map(unescape)
+                                .collect();
+                            on_stop_change(stop);
+                        },
+                    }
+                }
+                label { style: "flex: 1; display: flex; flex-direction: column; gap: 0.25em;",
+                    span { title: "The assistant's reply to your next messages starts with this text",
+                        "Start the reply with"
+                    }
+                    textarea {
+                        style: "height: 4em;",
+                        placeholder: "e.g. {{\" or *",
+                        value: "{prefill}",
+                        onchange: move |e: Event<FormData>| on_prefill_change(e.value()),
+                    }
+                }
+            }
+            if local {
+                OutputConstraint { constraint, on_change: on_constraint_change }
+            }
+        }
+    }
+}
+
+/// Editor for a grammar or JSON schema the reply must follow.
+///
+/// The constraint is applied when the text area loses focus; a schema that is
+/// not valid JSON is reported and not applied.
+#[component]
+fn OutputConstraint(
+    constraint: Option<Constraint>,
+    on_change: Callback<Option<Constraint>, ()>,
+) -> Element {
+    let (initial_kind, initial_text) = match &constraint {
+        None => ("none", String::new()),
+        Some(Constraint::Grammar(g)) => ("gbnf", g.clone()),
+        Some(Constraint::JsonSchema(s)) => {
+            ("json", serde_json::to_string_pretty(s).unwrap_or_default())
+        }
+    };
+    let mut kind = use_signal(|| initial_kind.to_string());
+    let mut text = use_signal(|| initial_text);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let mut apply = move || {
+        error.set(None);
+        let text = text();
+        let constraint = match kind().as_str() {
+            _ if text.trim().is_empty() => None,
+            "gbnf" => Some(Constraint::Grammar(text)),
+            "json" => match serde_json::from_str(&text) {
+                Ok(schema) => Some(Constraint::JsonSchema(schema)),
+                Err(e) => {
+                    error.set(Some(format!("Not valid JSON: {e}")));
+                    return;
+                }
+            },
+            _ => None,
+        };
+        on_change(constraint);
+    };
+
+    let placeholder = if kind() == "json" {
+        r#"{"type": "object", "properties": {"answer": {"type": "string"}}}"#
+    } else {
+        r#"root ::= "yes" | "no""#
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 0.25em; margin-top: 0.5em;",
+            label { style: "display: flex; gap: 0.5em; align-items: center;",
+                title: "Make the model's output follow a grammar (llama.cpp) or JSON schema (llama.cpp, Ollama)",
+                "Constrain output:"
+                select {
+                    value: "{kind}",
+                    onchange: move |e: Event<FormData>| {
+                        kind.set(e.value());
+                        apply();
+                    },
+                    option { value: "none", "None" }
+                    option { value: "json", "JSON schema" }
+                    option { value: "gbnf", "GBNF grammar" }
+                }
+            }
+            if kind() != "none" {
+                textarea {
+                    style: "height: 6em; font-family: monospace;",
+                    placeholder,
+                    value: "{text}",
+                    oninput: move |e: Event<FormData>| text.set(e.value()),
+                    onchange: move |_| apply(),
+                }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545;", "{e}" }
+            }
+        }
+    }
+}
+
+/// Writes line breaks and tabs in a stop sequence as `\n` and `\t`.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+/// Reverses [`escape`].
+fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -0,0 +1,199 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::toolset::roleplay::{Persona, Scene};
+
+/// Turn order of a role-play, above the chat input: who speaks next, a
+/// button per persona to have them reply now, and a button to have the
+/// model draft the user's next message.
+///
+/// # Arguments
+/// * `scene` - The cast and turn order
+/// * `disabled` - Whether a turn is running or no model is available
+/// * `drafting` - Whether the model is writing the user's message
+/// * `on_next` - Called with who speaks next, `None` for the narrator
+/// * `on_continue` - Called with the persona to reply as, without a user message
+/// * `on_impersonate` - Called to draft the user's next message into the input
+#[component]
+pub fn TurnOrder(
+    scene: Scene,
+    disabled: bool,
+    drafting: bool,
+    on_next: Callback<Option<String>, ()>,
+    on_continue: Callback<String, ()>,
+    on_impersonate: Callback<(), ()>,
+) -> Element {
+    let next = scene
+        .next_speaker()
+        .map(|p| p.name.clone())
+        .unwrap_or_default();
+    let player = scene.player_name().to_string();
+
+    rsx! {
+        div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5em; margin-bottom: 0.5em; font-size: 0.9em;",
+            label { style: "display: inline-flex; align-items: center; gap: 0.25em;",
+                title: "The persona that answers your next message",
+                "Speaks next"
+                select {
+                    value: "{next}",
+                    onchange: move |e| {
+                        let name = e.value();
+                        on_next((!name.is_empty()).then_some(name));
+                    },
+                    option { value: "", "Narrator" }
+                    for persona in scene.cast.iter() {
+                        option { key: "{persona.name}", value: "{persona.name}", "{persona.name}" }
+                    }
+                }
+            }
+            for persona in scene.cast.iter() {
+                button {
+                    key: "{persona.name}",
+                    disabled,
+                    title: "Continue as {persona.name}: they reply now, without a message from you",
+                    onclick: {
+                        let name = persona.name.clone();
+                        move |_| on_continue(name.clone())
+                    },
+                    "▶ "
+                    if let Some(avatar) = &persona.avatar {
+                        "{avatar} "
+                    }
+                    "{persona.name}"
+                }
+            }
+            button {
+                disabled: disabled || drafting,
+                title: "Have the model write your next message, as {player}, for you to edit before sending",
+                onclick: move |_| on_impersonate(()),
+                if drafting { "Drafting…" } else { "🎭 Impersonate" }
+            }
+        }
+    }
+}
+
+/// Editor of a role-play's setting and cast, for the side panel.
+///
+/// # Arguments
+/// * `scene` - The cast and turn order
+/// * `on_change` - Called with the scene once edited
+#[component]
+pub fn CastEditor(scene: Scene, on_change: Callback<Scene, ()>) -> Element {
+    let mut open = use_signal(|| false);
+    let mut adding = use_signal(Persona::default);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let add = {
+        let scene = scene.clone();
+        move |_| {
+            let mut scene = scene.clone();
+            match scene.add_persona(adding()) {
+                Ok(()) => {
+                    adding.set(Persona::default());
+                    error.set(None);
+                    on_change(scene);
+                }
+                Err(e) => error.set(Some(e.to_string())),
+            }
+        }
+    };
+
+    rsx! {
+        div { style: "margin: 0.5em 0; font-size: 0.9em; border-bottom: 1px solid #dee2e6; padding-bottom: 0.5em;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Edit the scene"
+            }
+            if open() {
+                div { style: "display: flex; flex-direction: column; gap: 0.25em; margin-top: 0.25em;",
+                    textarea {
+                        rows: 3,
+                        placeholder: "Setting: where and when, and what is going on",
+                        value: "{scene.setting}",
+                        onchange: {
+                            let scene = scene.clone();
+                            move |e: Event<FormData>| on_change(Scene { setting: e.value(), ..scene.clone() })
+                        },
+                    }
+                    input {
+                        placeholder: "Your persona's name",
+                        value: "{scene.player.name}",
+                        onchange: {
+                            let scene = scene.clone();
+                            move |e: Event<FormData>| {
+                                let mut scene = scene.clone();
+                                scene.player.name = e.value().trim().to_string();
+                                on_change(scene);
+                            }
+                        },
+                    }
+                    input {
+                        placeholder: "Who you play",
+                        value: "{scene.player.description}",
+                        onchange: {
+                            let scene = scene.clone();
+                            move |e: Event<FormData>| {
+                                let mut scene = scene.clone();
+                                scene.player.description = e.value();
+                                on_change(scene);
+                            }
+                        },
+                    }
+                    for persona in scene.cast.iter() {
+                        div { key: "{persona.name}", style: "display: flex; align-items: center; gap: 0.25em;",
+                            span { style: "flex: 1;",
+                                if let Some(avatar) = &persona.avatar {
+                                    "{avatar} "
+                                }
+                                strong { "{persona.name}" }
+                            }
+                            button {
+                                title: "Take {persona.name} out of the cast",
+                                onclick: {
+                                    let (scene, name) = (scene.clone(), persona.name.clone());
+                                    move |_| {
+                                        let mut scene = scene.clone();
+                                        scene.remove_persona(&name);
+                                        on_change(scene);
+                                    }
+                                },
+                                "✕"
+                            }
+                        }
+                    }
+                    div { style: "display: flex; gap: 0.25em;",
+                        input {
+                            style: "width: 3em;",
+                            placeholder: "🙂",
+                            title: "Emoji or image URL shown next to the name",
+                            value: adding.read().avatar.clone().unwrap_or_default(),
+                            oninput: move |e| {
+                                let avatar = e.value().trim().to_string();
+                                adding.with_mut(|p| p.avatar = (!avatar.is_empty()).then_some(avatar));
+                            },
+                        }
+                        input {
+                            style: "flex: 1; min-width: 0;",
+                            placeholder: "Name",
+                            value: adding.read().name.clone(),
+                            oninput: move |e| adding.with_mut(|p| p.name = e.value()),
+                        }
+                    }
+                    textarea {
+                        rows: 2,
+                        placeholder: "Who they are: looks, personality, how they speak",
+                        value: adding.read().description.clone(),
+                        oninput: move |e| adding.with_mut(|p| p.description = e.value()),
+                    }
+                    button { onclick: add, "Add to the cast" }
+                    if let Some(e) = error() {
+                        div { style: "color: #dc3545;", "{e}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::sync::Arc;
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    Route,
+    app_settings::{ScheduledTask, Toolsets},
+    core::now_millis,
+    mcp::host::MCPHost,
+    scheduler::{CronSchedule, format_time, next_run, run_and_record, upcoming_runs},
+    storage::{Storage, get_storage},
+    toolset::registry,
+};
+
+/// An edit applied to the stored list of tasks.
+type TaskEdit = Box<dyn FnOnce(&mut Vec<ScheduledTask>)>;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct SchedulesProps {
+    pub on_close: Option<EventHandler<()>>,
+}
+
+/// Management panel for scheduled prompts: lists tasks with their upcoming runs,
+/// and adds, toggles, runs and deletes them.
+#[component]
+pub fn Schedules(props: SchedulesProps) -> Element {
+    let nav = navigator();
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let mut refresh_trigger = use_signal(|| 0);
+    let tasks = use_resource(move || {
+        let _ = refresh_trigger(); // Subscribe to refresh trigger
+        async move {
+            match get_storage().await {
+                Ok(stg) => stg.load_schedules().await.unwrap_or_else(|e| {
+                    warn!("Could not load scheduled tasks: {e:?}");
+                    vec![]
+                }),
+                Err(e) => {
+                    warn!("Could not get storage: {e:?}");
+                    vec![]
+                }
+            }
+        }
+    });
+    // Pick up runs finished by the background scheduler while the panel is open
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(crate::scheduler::CHECK_INTERVAL).await;
+            refresh_trigger.set(refresh_trigger() + 1);
+        }
+    });
+    let mut show_add_form = use_signal(|| false);
+    let mut running = use_signal(|| None::<u64>);
+    let mut error = use_signal(|| None::<String>);
+
+    let save_tasks = move |tasks: Vec<ScheduledTask>| async move {
+        let res = async { get_storage().await?.save_schedules(&tasks).await }.await;
+        if let Err(e) = &res {
+            warn!("Could not save scheduled tasks: {e:?}");
+        }
+        error.set(res.err().map(|e| format!("Could not save scheduled tasks: {e}")));
+        refresh_trigger.set(refresh_trigger() + 1);
+    };
+    let update_tasks = move |f: TaskEdit| async move {
+        let mut current = tasks().unwrap_or_default();
+        f(&mut current);
+        save_tasks(current).await;
+    };
+    let run_now = move |task: ScheduledTask| async move {
+        running.set(Some(task.id));
+        let res = run_and_record(task, host(), nav).await;
+        if let Err(e) = &res {
+            warn!("Could not run scheduled task: {e:?}");
+        }
+        error.set(res.err().map(|e| format!("{e:#}")));
+        running.set(None);
+        refresh_trigger.set(refresh_trigger() + 1);
+    };
+
+    let Some(tasks) = tasks() else {
+        return rsx! {
+            div { style: "padding: 1rem;", "Loading..." }
+        };
+    };
+
+    // Next runs across all enabled tasks, soonest first
+    let mut upcoming: Vec<(u64, String)> = tasks
+        .iter()
+        .filter(|t| t.enabled)
+        .flat_map(|t| {
+            upcoming_runs(t, 5)
+                .into_iter()
+                .map(|r| (r, t.name.clone()))
+        })
+        .collect();
+    upcoming.sort();
+    upcoming.truncate(8);
+
+    rsx! {
+        div {
+            style: "padding: 1rem; height: 100%; overflow-y: auto;",
+            onclick: move |e: Event<MouseData>| {
+                e.stop_propagation();
+            },
+
+            div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;",
+                h3 { style: "margin: 0;", "Scheduled Prompts" }
+                if let Some(on_close) = props.on_close {
+                    button {
+                        style: "
+                            background: none;
+                            border: none;
+                            font-size: 1.2rem;
+                            cursor: pointer;
+                            padding: 0.25rem;
+                            color: #666;
+                        ",
+                        onclick: move |_| {
+                            on_close.call(());
+                        },
+                        "×"
+                    }
+                }
+            }
+
+            hr { style: "margin-bottom: 1rem;" }
+
+            div { style: "font-size: 0.85rem; color: #666; margin-bottom: 1rem;",
+                "Tasks run while the app is open; runs missed while it was closed are caught up at startup."
+            }
+
+            if let Some(err) = error() {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-bottom: 1rem;", "{err}" }
+            }
+
+            if !upcoming.is_empty() {
+                h4 { style: "margin: 0 0 0.5rem 0;", "Upcoming runs" }
+                ul { style: "margin: 0 0 1rem 0; padding-left: 1.25rem; font-size: 0.9rem;",
+                    for (i , (at , name)) in upcoming.into_iter().enumerate() {
+                        li { key: "{i}",
+                            span { style: "font-family: monospace;", "{format_time(at)}" }
+                            " — {name}"
+                        }
+                    }
+                }
+            }
+
+            if tasks.is_empty() && !show_add_form() {
+                div { style: "text-align: center; color: #666; padding: 2rem;", "No scheduled prompts yet" }
+            }
+
+            for task in tasks.clone() {
+                {
+                    let id = task.id;
+                    let next = next_run(&task)
+                        .map(|n| format_time(n.max(now_millis())))
+                        .unwrap_or_else(|| "never".into());
+                    let last = task.last_run.map(format_time);
+                    let is_running = running() == Some(id);
+                    let t = task.clone();
+                    let opacity = if task.enabled { "1" } else { "0.6" };
+                    rsx! {
+                        div {
+                            key: "{id}",
+                            style: "
+                                padding: 0.5rem;
+                                margin-bottom: 0.5rem;
+                                border: 1px solid #ddd;
+                                border-radius: 4px;
+                                background: #f9f9f9;
+                                opacity: {opacity};
+                            ",
+                            div { style: "display: flex; justify-content: space-between; align-items: center;",
+                                div { style: "font-weight: bold;", "{task.name}" }
+                                label { style: "font-size: 0.8rem; display: flex; align-items: center; gap: 0.25rem;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: task.enabled,
+                                        oninput: move |e: Event<FormData>| {
+                                            let enabled = e.checked();
+                                            update_tasks(
+                                                Box::new(move |ts| {
+                                                    if let Some(t) = ts.iter_mut().find(|t| t.id == id) {
+                                                        t.enabled = enabled;
+                                                    }
+                                                }),
+                                            )
+                                        },
+                                    }
+                                    "Enabled"
+                                }
+                            }
+                            div { style: "font-size: 0.8rem; color: #666; margin: 0.25rem 0;",
+                                span { style: "font-family: monospace;", "{task.cron}" }
+                                " · {registry::entry_or_chat(&task.toolset).name}"
+                                if task.enabled {
+                                    " · next {next}"
+                                }
+                            }
+                            div { style: "font-size: 0.85rem; white-space: pre-wrap; margin-bottom: 0.25rem;",
+                                "{task.prompt}"
+                            }
+                            if let Some(last) = last {
+                                div { style: "font-size: 0.8rem; color: #666;",
+                                    "Last run {last}"
+                                    if let Some(chat_id) = task.last_chat_id {
+                                        " · "
+                                        Link {
+                                            to: Route::ChatEl { id: chat_id, anchor: String::new() },
+                                            onclick: move |_| {
+                                                if let Some(on_close) = props.on_close {
+                                                    on_close.call(());
+                                                }
+                                            },
+                                            "open chat"
+                                        }
+                                    }
+                                }
+                            }
+                            if let Some(err) = &task.last_error {
+                                div { style: "font-size: 0.8rem; color: #dc3545;", "{err}" }
+                            }
+                            div { style: "display: flex; gap: 0.5rem; justify-content: flex-end; margin-top: 0.5rem;",
+                                button {
+                                    style: "
+                                        background: #007bff;
+                                        color: white;
+                                        border: none;
+                                        border-radius: 3px;
+                                        padding: 0.25rem 0.5rem;
+                                        cursor: pointer;
+                                        font-size: 0.8rem;
+                                    ",
+                                    disabled: running().is_some(),
+                                    onclick: move |_| run_now(t.clone()),
+                                    if is_running {
+                                        "Running..."
+                                    } else {
+                                        "Run now"
+                                    }
+                                }
+                                button {
+                                    style: "
+                                        background: #ff4444;
+                                        color: white;
+                                        border: none;
+                                        border-radius: 3px;
+                                        padding: 0.25rem 0.5rem;
+                                        cursor: pointer;
+                                        font-size: 0.8rem;
+                                    ",
+                                    onclick: move |_| {
+                                        update_tasks(Box::new(move |ts| ts.retain(|t| t.id != id)))
+                                    },
+                                    "Delete"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if show_add_form() {
+                AddScheduleForm {
+                    on_save: move |task: ScheduledTask| {
+                        let mut current = tasks.clone();
+                        current.push(task);
+                        show_add_form.set(false);
+                        save_tasks(current)
+                    },
+                    on_cancel: move |_| show_add_form.set(false),
+                }
+            } else {
+                button {
+                    style: "
+                        background: #28a745;
+                        color: white;
+                        border: none;
+                        padding: 0.5rem 1rem;
+                        border-radius: 3px;
+                        cursor: pointer;
+                        margin-top: 0.5rem;
+                    ",
+                    onclick: move |_| show_add_form.set(true),
+                    "Add Scheduled Prompt"
+                }
+            }
+        }
+    }
+}
+
+/// Form for creating a scheduled prompt, validating the cron expression as it is typed.
+#[component]
+fn AddScheduleForm(on_save: Callback<ScheduledTask, ()>, on_cancel: Callback<(), ()>) -> Element {
+    let mut name = use_signal(String::new);
+    let mut prompt = use_signal(String::new);
+    let mut cron = use_signal(|| "0 8 * * *".to_string());
+    let mut toolset = use_signal(|| Toolsets::Chat);
+
+    let cron_check = CronSchedule::parse(&cron());
+    let preview = match &cron_check {
+        Ok(c) => c
+            .next_after(chrono::Local::now())
+            .map(|t| format!("Next run: {}", format_time(t.timestamp_millis() as u64)))
+            .unwrap_or_else(|| "This schedule never runs".into()),
+        Err(e) => format!("{e:#}"),
+    };
+    let valid = cron_check.is_ok() && !name().trim().is_empty() && !prompt().trim().is_empty();
+
+    let input_style = "
+        width: 100%;
+        padding: 0.5rem;
+        border: 1px solid #ddd;
+        border-radius: 3px;
+        box-sizing: border-box;
+        margin-bottom: 0.5rem;
+    ";
+
+    rsx! {
+        div { style: "
+                border: 1px solid #007bff;
+                border-radius: 4px;
+                padding: 1rem;
+                margin-top: 0.5rem;
+                background: #f8f9fa;
+            ",
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;", "Name" }
+            input {
+                style: input_style,
+                placeholder: "Morning news digest",
+                value: name(),
+                oninput: move |e| name.set(e.value()),
+            }
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;", "Prompt" }
+            textarea {
+                style: "{input_style} min-height: 5rem;",
+                placeholder: "Summarize the new items from my RSS server",
+                value: prompt(),
+                oninput: move |e| prompt.set(e.value()),
+            }
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;", "Run in" }
+            select {
+                style: input_style,
+                onchange: move |e| toolset.set(e.value().parse().unwrap_or(Toolsets::Chat)),
+                for entry in registry::entries() {
+                    option {
+                        value: "{entry.id}",
+                        selected: toolset() == entry.id,
+                        "{entry.icon} {entry.name}"
+                    }
+                }
+            }
+            label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;",
+                "Schedule (cron: minute hour day month weekday)"
+            }
+            input {
+                style: "{input_style} font-family: monospace;",
+                value: cron(),
+                oninput: move |e| cron.set(e.value()),
+            }
+            div {
+                style: if cron_check.is_ok() { "font-size: 0.8rem; color: #666;" } else { "font-size: 0.8rem; color: #dc3545;" },
+                "{preview}"
+            }
+            div { style: "font-size: 0.8rem; color: #666; margin-bottom: 0.5rem;",
+                "e.g. 0 8 * * * (daily at 8:00), 0 9 * * 1-5 (weekdays at 9:00), */30 * * * * (every 30 minutes)"
+            }
+            div { style: "display: flex; gap: 0.5rem; justify-content: flex-end;",
+                button {
+                    style: "
+                        background: #6c757d;
+                        color: white;
+                        border: none;
+                        padding: 0.5rem 1rem;
+                        border-radius: 3px;
+                        cursor: pointer;
+                    ",
+                    onclick: move |_| on_cancel(()),
+                    "Cancel"
+                }
+                button {
+                    style: "
+                        background: #007bff;
+                        color: white;
+                        border: none;
+                        padding: 0.5rem 1rem;
+                        border-radius: 3px;
+                        cursor: pointer;
+                    ",
+                    disabled: !valid,
+                    onclick: move |_| {
+                        on_save(ScheduledTask {
+                            id: now_millis(),
+                            name: name().trim().to_string(),
+                            prompt: prompt(),
+                            toolset: toolset(),
+                            cron: cron().trim().to_string(),
+                            enabled: true,
+                            last_run: None,
+                            last_chat_id: None,
+                            last_error: None,
+                        });
+                    },
+                    "Save"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Notes the model wrote in its scratchpad, shown collapsed under the chat
+/// instead of as messages.
+///
+/// # Arguments
+/// * `notes` - The notes, oldest first
+#[component]
+pub fn ScratchpadPanel(notes: Vec<String>) -> Element {
+    let mut open = use_signal(|| false);
+    if notes.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { style: "margin: 0.5em 1em; font-size: 0.9em; border: 1px dashed #ccc; border-radius: 4px; padding: 0.25em 0.5em; color: #555;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600; color: inherit;",
+                title: "Notes the model kept for itself; they are sent with every request",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Scratchpad ({notes.len()})"
+            }
+            if open() {
+                ol { style: "margin: 0.25em 0; padding-left: 1.5em;",
+                    for (i , note) in notes.iter().enumerate() {
+                        li { key: "{i}", style: "white-space: pre-wrap; margin-bottom: 0.25em;",
+                            "{note}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
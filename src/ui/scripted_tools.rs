@@ -0,0 +1,225 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{
+    AppSettings,
+    mcp::scripted::{ScriptedTool, default_input_schema, validate_tool},
+};
+
+/// Script shown in the editor for a new tool.
+const EXAMPLE_SCRIPT: &str = r#"// `args` holds the arguments of the call
+let response = http_get("https://example.com/api?q=" + args.query);
+response.body"#;
+
+/// List of scripted tools with an editor for adding and changing them. Tools
+/// are served as soon as the settings are saved.
+///
+/// # Arguments
+/// * `settings` - Current settings
+/// * `on_save` - Called with the settings after scripted tools are changed
+#[component]
+pub fn ScriptedToolSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>) -> Element {
+    // Index of the tool being edited; the length of the list for a new one
+    let mut editing = use_signal(|| None::<usize>);
+
+    let tools = settings.scripted_tools.clone();
+    let save = use_callback(move |scripted_tools: Vec<ScriptedTool>| {
+        on_save(AppSettings {
+            scripted_tools,
+            ..settings.clone()
+        });
+    });
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Scripted Tools" }
+        p { style: "color: #666; font-size: 0.9em; margin-top: 0;",
+            "Small Rhai scripts the model can call as tools, e.g. to query an internal API. "
+            "A script gets the call's arguments as "
+            code { "args" }
+            " and can use "
+            code { "http_get" }
+            ", "
+            code { "http_post" }
+            ", "
+            code { "from_json" }
+            " and "
+            code { "to_json" }
+            "."
+        }
+        if tools.is_empty() && editing().is_none() {
+            p { style: "color: #666; font-style: italic;", "No scripted tools" }
+        }
+        for (index , tool) in tools.iter().cloned().enumerate() {
+            if editing() == Some(index) {
+                ScriptedToolEditor {
+                    key: "{tool.name}",
+                    tool: tool.clone(),
+                    others: tools.iter().enumerate().filter(|(i, _)| *i != index).map(|(_, t)| t.name.clone()).collect::<Vec<_>>(),
+                    on_save: {
+                        let tools = tools.clone();
+                        move |tool: ScriptedTool| {
+                            let mut tools = tools.clone();
+                            tools[index] = tool;
+                            save(tools);
+                            editing.set(None);
+                        }
+                    },
+                    on_cancel: move |_| editing.set(None),
+                }
+            } else {
+                div {
+                    key: "{tool.name}",
+                    style: "display: flex; align-items: center; gap: 0.5rem; border: 1px solid #ddd; border-radius: 4px; padding: 0.5rem; margin-bottom: 0.5rem;",
+                    input {
+                        r#type: "checkbox",
+                        title: "Offer this tool to the model",
+                        checked: tool.enabled,
+                        oninput: {
+                            let tools = tools.clone();
+                            move |e: Event<FormData>| {
+                                let mut tools = tools.clone();
+                                tools[index].enabled = e.checked();
+                                save(tools);
+                            }
+                        },
+                    }
+                    strong { "{tool.name}" }
+                    span { style: "flex: 1; color: #666; font-size: 0.85em;", "{tool.description}" }
+                    button { onclick: move |_| editing.set(Some(index)), "Edit" }
+                    button {
+                        title: "Remove the tool",
+                        onclick: {
+                            let tools = tools.clone();
+                            move |_| {
+                                let mut tools = tools.clone();
+                                tools.remove(index);
+                                save(tools);
+                            }
+                        },
+                        "×"
+                    }
+                }
+            }
+        }
+        if editing() == Some(tools.len()) {
+            ScriptedToolEditor {
+                tool: ScriptedTool {
+                    name: String::new(),
+                    description: String::new(),
+                    input_schema: serde_json::json!({
+                        "type": "object",
+                        "properties": { "query": { "type": "string" } },
+                        "required": ["query"]
+                    }),
+                    script: EXAMPLE_SCRIPT.to_string(),
+                    enabled: true,
+                },
+                others: tools.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
+                on_save: {
+                    let tools = tools.clone();
+                    move |tool: ScriptedTool| {
+                        let mut tools = tools.clone();
+                        tools.push(tool);
+                        save(tools);
+                        editing.set(None);
+                    }
+                },
+                on_cancel: move |_| editing.set(None),
+            }
+        } else if editing().is_none() {
+            button {
+                onclick: {
+                    let count = tools.len();
+                    move |_| editing.set(Some(count))
+                },
+                "+ Add Scripted Tool"
+            }
+        }
+    }
+}
+
+/// Form for the name, description, schema and script of a scripted tool.
+///
+/// # Arguments
+/// * `tool` - The tool as it was before editing
+/// * `others` - Names of the other tools, which the name must not repeat
+/// * `on_save` - Called with the edited tool once it is valid
+/// * `on_cancel` - Called when editing is abandoned
+#[component]
+fn ScriptedToolEditor(
+    tool: ScriptedTool,
+    others: Vec<String>,
+    on_save: Callback<ScriptedTool, ()>,
+    on_cancel: Callback<(), ()>,
+) -> Element {
+    let mut name = use_signal(|| tool.name.clone());
+    let mut description = use_signal(|| tool.description.clone());
+    let mut schema =
+        use_signal(|| serde_json::to_string_pretty(&tool.input_schema).unwrap_or_default());
+    let mut script = use_signal(|| tool.script.clone());
+    let mut error = use_signal(|| None::<String>);
+
+    let submit = move |_| {
+        let input_schema = if schema().trim().is_empty() {
+            default_input_schema()
+        } else {
+            match serde_json::from_str(&schema()) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    error.set(Some(format!("The schema is not valid JSON: {e}")));
+                    return;
+                }
+            }
+        };
+        let edited = ScriptedTool {
+            name: name().trim().to_string(),
+            description: description().trim().to_string(),
+            input_schema,
+            script: script(),
+            enabled: tool.enabled,
+        };
+        if others.contains(&edited.name) {
+            error.set(Some(format!("A tool named {} already exists", edited.name)));
+            return;
+        }
+        match validate_tool(&edited) {
+            Ok(()) => on_save(edited),
+            Err(e) => error.set(Some(e.to_string())),
+        }
+    };
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; gap: 0.5rem; border: 1px solid #ddd; border-radius: 4px; padding: 0.5rem; margin-bottom: 0.5rem;",
+            input {
+                placeholder: "Name, e.g. search_wiki",
+                value: name(),
+                oninput: move |e| name.set(e.value()),
+            }
+            input {
+                placeholder: "What the tool does, for the model",
+                value: description(),
+                oninput: move |e| description.set(e.value()),
+            }
+            label { style: "font-size: 0.9em; color: #666;", "Arguments (JSON schema)" }
+            textarea {
+                style: "width: 100%; box-sizing: border-box; height: 8em; font-family: monospace;",
+                value: "{schema}",
+                oninput: move |e: Event<FormData>| schema.set(e.value()),
+            }
+            label { style: "font-size: 0.9em; color: #666;", "Script" }
+            textarea {
+                style: "width: 100%; box-sizing: border-box; height: 12em; font-family: monospace;",
+                value: "{script}",
+                oninput: move |e: Event<FormData>| script.set(e.value()),
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545;", "{e}" }
+            }
+            div { style: "display: flex; gap: 0.5rem;",
+                button { onclick: submit, "Save Tool" }
+                button { onclick: move |_| on_cancel(()), "Cancel" }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::mcp::{
+    ServerSpec,
+    secrets::{delete_secret, is_secret_name, load_secrets, save_secret},
+};
+
+/// Secrets of the active profile, referenced from MCP servers as `$NAME`.
+/// Values are never shown; they can only be replaced or deleted.
+///
+/// # Arguments
+/// * `servers` - The profile's MCP servers, to show where secrets are used
+#[component]
+pub fn SecretsSettings(servers: Vec<ServerSpec>) -> Element {
+    let mut name = use_signal(String::new);
+    let mut value = use_signal(String::new);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut secrets = use_resource(use_reactive!(|servers| async move {
+        // Reload after server edits, which may store a secret
+        let _ = servers;
+        load_secrets().await.unwrap_or_else(|e| {
+            warn!("Could not load secrets: {e:?}");
+            Default::default()
+        })
+    }));
+    let stored = secrets().unwrap_or_default();
+    let users = |secret: &str| {
+        servers
+            .iter()
+            .filter(|s| s.secret_refs().iter().any(|r| r == secret))
+            .map(|s| s.id.clone())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    // Referenced but set neither here nor in the environment
+    let mut missing: Vec<(String, String)> = vec![];
+    for server in &servers {
+        for secret in server.secret_refs() {
+            if !stored.contains_key(&secret)
+                && std::env::var(&secret).is_err()
+                && !missing.iter().any(|(m, _)| *m == secret)
+            {
+                missing.push((secret.clone(), users(&secret)));
+            }
+        }
+    }
+    let rows: Vec<(String, String)> = stored.keys().map(|k| (k.clone(), users(k))).collect();
+
+    let add = move |_| async move {
+        let n = name.read().trim().to_string();
+        if !is_secret_name(&n) {
+            error.set(Some(
+                "Names may contain letters, digits and _, and not start with a digit".into(),
+            ));
+            return;
+        }
+        match save_secret(&n, &value()).await {
+            Ok(()) => {
+                name.set(String::new());
+                value.set(String::new());
+                error.set(None);
+            }
+            Err(e) => error.set(Some(format!("Could not save the secret: {e}"))),
+        }
+        secrets.restart();
+    };
+
+    rsx! {
+        div { style: "margin-bottom: 1rem;",
+            h4 { style: "margin: 0 0 0.5rem 0;", "Secrets" }
+            p { style: "margin: 0 0 0.5rem 0; font-size: 0.9em; color: #666;",
+                "Server arguments and environment values can refer to secrets as $NAME or ${{NAME}}. "
+                "They are filled in when the server starts, so the settings hold only the names."
+            }
+            for (secret , used_by) in rows {
+                div {
+                    key: "{secret}",
+                    style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.25rem; font-size: 0.9em;",
+                    span { style: "font-family: monospace;", "{secret}" }
+                    span { style: "color: #888;", "••••••" }
+                    if !used_by.is_empty() {
+                        span { style: "color: #666;", "used by {used_by}" }
+                    }
+                    button {
+                        style: "margin-left: auto;",
+                        title: "Delete this secret",
+                        onclick: {
+                            let secret = secret.clone();
+                            move |_| {
+                                let secret = secret.clone();
+                                async move {
+                                    if let Err(e) = delete_secret(&secret).await {
+                                        error.set(Some(format!("Could not delete the secret: {e}")));
+                                    }
+                                    secrets.restart();
+                                }
+                            }
+                        },
+                        "Delete"
+                    }
+                }
+            }
+            for (secret , used_by) in missing {
+                div {
+                    key: "missing-{secret}",
+                    style: "padding: 0.25rem; font-size: 0.9em; color: #856404;",
+                    "⚠ {secret} is not set, and {used_by} will not start without it"
+                }
+            }
+            div { style: "display: flex; gap: 0.5rem; margin-top: 0.5rem;",
+                input {
+                    style: "flex: 1; font-family: monospace;",
+                    placeholder: "NAME",
+                    value: name,
+                    oninput: move |e: Event<FormData>| name.set(e.value()),
+                }
+                input {
+                    style: "flex: 2;",
+                    r#type: "password",
+                    placeholder: "Value",
+                    value: value,
+                    oninput: move |e: Event<FormData>| value.set(e.value()),
+                }
+                button { onclick: add, "Save secret" }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;", "{e}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Delivery of the message the user sent last.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendStatus {
+    /// Waiting for the response to start
+    Pending,
+    /// The response started
+    Sent,
+    /// The request failed before any of the response arrived
+    Failed(String),
+}
+
+/// Status shown under a user message: a spinner until the response starts,
+/// and the error with ways to retry or edit the message if it was not sent.
+/// Nothing is shown once the message is sent.
+///
+/// # Arguments
+/// * `status` - Delivery of the message
+/// * `on_retry` - Called to send the message again
+/// * `on_edit` - Called to take the message back into the input
+#[component]
+pub fn SendStatusLine(
+    status: SendStatus,
+    on_retry: EventHandler<()>,
+    on_edit: EventHandler<()>,
+) -> Element {
+    match status {
+        SendStatus::Sent => rsx! {},
+        SendStatus::Pending => rsx! {
+            div {
+                class: "send-status",
+                style: "margin: -0.5em 1em 0.5em 0; text-align: right; font-size: 0.85em; color: #666;",
+                title: "Waiting for the response to start",
+                span { class: "send-spinner" }
+                "Sending…"
+            }
+        },
+        SendStatus::Failed(error) => rsx! {
+            div {
+                class: "send-status",
+                style: "display: flex; justify-content: flex-end; align-items: baseline; gap: 0.5em; margin: -0.5em 1em 0.5em 0; font-size: 0.85em; color: #dc3545;",
+                span {
+                    style: "overflow: hidden; text-overflow: ellipsis; white-space: nowrap; max-width: 40em;",
+                    title: "{error}",
+                    "⚠ Not sent: {error}"
+                }
+                button {
+                    title: "Send the message again",
+                    onclick: move |_| on_retry(()),
+                    "Retry"
+                }
+                button {
+                    title: "Remove the message and put its text back in the input",
+                    onclick: move |_| on_edit(()),
+                    "Edit"
+                }
+            }
+        },
+    }
+}
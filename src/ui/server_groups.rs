@@ -0,0 +1,192 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Named groups of MCP servers, such as "Research" or "Coding", switched
+//! between from the tools panel.
+//!
+//! Switching to a group enables exactly its servers and disables the others
+//! in the settings, which syncs the host to them. A group is saved from the
+//! servers enabled at the time.
+
+use std::{sync::Arc, time::Duration};
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    app_settings::{AppSettings, ServerGroup},
+    core::sleep,
+    mcp::host::MCPHost,
+    utils::{save_server_groups, switch_server_group},
+};
+
+/// Longest wait for the host to start and stop servers after a switch.
+const SWITCH_WAIT: Duration = Duration::from_secs(30);
+
+/// Waits until the host runs the enabled servers and none of the others, or
+/// [`SWITCH_WAIT`] has passed, as servers that fail to start never appear.
+///
+/// # Arguments
+/// * `host` - Global MCP host
+/// * `settings` - Settings after the switch
+async fn wait_for_servers(host: &MCPHost, settings: &AppSettings) {
+    let step = Duration::from_millis(250);
+    let mut waited = Duration::ZERO;
+    while waited < SWITCH_WAIT {
+        let running = host.server_ids().await;
+        let synced = settings
+            .mcp_servers
+            .iter()
+            .flatten()
+            .all(|s| running.contains(&s.id) == s.enabled);
+        if synced {
+            return;
+        }
+        sleep(step).await;
+        waited += step;
+    }
+}
+
+/// Buttons switching between server groups, with the group matching the
+/// enabled servers highlighted, and a field saving the enabled servers as a
+/// group.
+///
+/// # Arguments
+/// * `on_switched` - Called once the host runs the servers of a new group
+#[component]
+pub fn ServerGroups(on_switched: Callback<(), ()>) -> Element {
+    let mut settings = use_context::<Signal<Option<AppSettings>>>();
+    let mut new_name = use_signal(String::new);
+    let mut switching: Signal<Option<String>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let Some(current) = settings() else {
+        return rsx! {};
+    };
+    if current.mcp_servers.as_ref().is_none_or(|s| s.is_empty()) {
+        return rsx! {};
+    }
+    let active = current.active_server_group().map(|g| g.name.clone());
+    let enabled = current.enabled_servers();
+    let groups = current.server_groups.clone();
+
+    let switch = move |name: String| async move {
+        switching.set(Some(name.clone()));
+        error.set(None);
+        match switch_server_group(&mut settings, &name).await {
+            Ok(()) => {
+                let host = consume_context::<Arc<MCPHost>>();
+                if let Some(s) = settings.peek().clone() {
+                    wait_for_servers(&host, &s).await;
+                }
+                on_switched(());
+            }
+            Err(e) => {
+                warn!("Could not switch to server group {name}: {e:?}");
+                error.set(Some(format!("{e:#}")));
+            }
+        }
+        switching.set(None);
+    };
+    let save_groups = move |groups: Vec<ServerGroup>| async move {
+        if let Err(e) = save_server_groups(&mut settings, groups).await {
+            warn!("Could not save the server groups: {e:?}");
+            error.set(Some(format!("{e:#}")));
+        }
+    };
+
+    let name = new_name.read().trim().to_string();
+    let can_save = !name.is_empty();
+    let replaces = groups.iter().any(|g| g.name == name);
+    let save_current = {
+        let groups = groups.clone();
+        let enabled = enabled.clone();
+        move |_| {
+            let name = new_name.read().trim().to_string();
+            let mut groups = groups.clone();
+            let group = ServerGroup {
+                name: name.clone(),
+                servers: enabled.clone(),
+            };
+            // Saving under an existing name updates that group
+            match groups.iter_mut().find(|g| g.name == name) {
+                Some(existing) => *existing = group,
+                None => groups.push(group),
+            }
+            new_name.set(String::new());
+            save_groups(groups)
+        }
+    };
+
+    rsx! {
+        div { style: "margin-bottom: 2rem;",
+            h3 { style: "margin: 0 0 0.5rem 0; color: #333; font-size: 1.1rem;", "Server groups" }
+            if groups.is_empty() {
+                div { style: "font-size: 0.85rem; color: #666; margin-bottom: 0.5rem;",
+                    "Save the enabled servers as a group to switch back to them in one click."
+                }
+            }
+            div { style: "display: flex; flex-wrap: wrap; gap: 0.5rem; margin-bottom: 0.5rem;",
+                for group in groups.clone() {
+                    {
+                        let is_active = active.as_deref() == Some(group.name.as_str());
+                        let is_switching = switching.read().as_deref() == Some(group.name.as_str());
+                        let remaining: Vec<ServerGroup> = groups
+                            .iter()
+                            .filter(|g| g.name != group.name)
+                            .cloned()
+                            .collect();
+                        let name = group.name.clone();
+                        let members = group.servers.join(", ");
+                        rsx! {
+                            span {
+                                key: "{group.name}",
+                                style: format!(
+                                    "display: inline-flex; align-items: center; border: 1px solid {}; border-radius: 4px; background: {};",
+                                    if is_active { "#007bff" } else { "#ccc" },
+                                    if is_active { "#e7f1ff" } else { "#fff" },
+                                ),
+                                button {
+                                    style: "border: none; background: none; cursor: pointer; padding: 0.25rem 0.5rem;",
+                                    title: "{members}",
+                                    disabled: is_active || switching.read().is_some(),
+                                    onclick: move |_| switch(name.clone()),
+                                    if is_switching {
+                                        "{group.name}…"
+                                    } else {
+                                        "{group.name} ({group.servers.len()})"
+                                    }
+                                }
+                                button {
+                                    style: "border: none; background: none; cursor: pointer; color: #666; padding: 0.25rem;",
+                                    title: "Delete the group; its servers are kept",
+                                    onclick: move |_| save_groups(remaining.clone()),
+                                    "×"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 0.5rem; align-items: center; font-size: 0.9rem;",
+                input {
+                    style: "flex: 1; padding: 0.25rem;",
+                    placeholder: "Group name, e.g. Research",
+                    value: new_name(),
+                    oninput: move |e| new_name.set(e.value()),
+                }
+                button {
+                    disabled: !can_save,
+                    title: "Save the {enabled.len()} enabled servers as a group",
+                    onclick: save_current,
+                    if replaces {
+                        "Update group"
+                    } else {
+                        "Save enabled servers"
+                    }
+                }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545; font-size: 0.85rem; margin-top: 0.25rem;", "{e}" }
+            }
+        }
+    }
+}
@@ -1,14 +1,51 @@
 // Copyright © 2025 Nipun Kumar
 
+use std::sync::Arc;
+
 use dioxus::{logger::tracing::warn, prelude::*};
 
+use crate::ui::credits::OpenRouterKeyStatus;
+use crate::ui::snippets::SnippetSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::openapi::OpenApiServerSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::plugins::PluginSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::scripted_tools::ScriptedToolSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::secrets::SecretsSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::shell::ShellServerSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::storage_location::StorageLocationSettings;
 use crate::{
-    AppSettings,
-    app_settings::ProviderSettings,
-    llm::LlmClient,
-    mcp::ServerSpec,
-    storage::{Storage, get_storage},
-    ui::box_select::BoxSelect,
+    AppSettings, Route,
+    app_settings::{
+        DEFAULT_PROFILE, DisplaySettings, OPENROUTER_API_URL, Profiles, ProviderSettings,
+        SettingsOverrides, with_overrides,
+    },
+    core::MAX_TOOL_FAILURES,
+    llm::{DEFAULT_STREAM_IDLE_TIMEOUT, LlmClient},
+    mcp::{
+        SandboxWrapper, ServerSpec,
+        host::MCPHost,
+        secrets::{save_secret, secret_name_for},
+        validation::{ServerField, validate_env_key, validate_http_url, validate_server},
+    },
+    storage::{
+        Storage, active_profile, conflicting_profile, get_storage, load_profiles, save_profiles,
+        set_active_profile,
+    },
+    ui::{
+        args_editor::{ArgsEditor, join_args},
+        box_select::BoxSelect,
+        encryption_settings::EncryptionSettings,
+        import_servers::ImportServers,
+        provider_check::ProviderCheckPanel,
+        provider_options::ProviderOptionsEditor,
+        storage_insights::StorageInsights,
+    },
+    utils::load_active_profile,
 };
 
 #[derive(Props, Clone, PartialEq)]
@@ -19,9 +56,11 @@ pub struct SettingsProps {
 #[allow(non_snake_case)]
 #[component]
 pub fn Settings(props: SettingsProps) -> Element {
+    let overrides = use_context::<SettingsOverrides>();
     let mut provider = use_signal(move || ProviderSettings::OpenRouter {
         api_key: "".to_string(),
         model: None,
+        options: Default::default(),
     });
     let mut settings = use_resource(move || async move {
         let storage = match get_storage().await {
@@ -41,6 +80,7 @@ pub fn Settings(props: SettingsProps) -> Element {
             provider: ProviderSettings::OpenRouter {
                 api_key: "".to_string(),
                 model: None,
+                options: Default::default(),
             },
             last_chat_id: None,
             mcp_servers: Some(vec![ServerSpec {
@@ -49,7 +89,24 @@ pub fn Settings(props: SettingsProps) -> Element {
                 args: vec!["@playwright/mcp@latest".into(), "--headless".into()],
                 env: Default::default(),
                 enabled: false,
+                sandbox: Default::default(),
+                watch: None,
             }]),
+            display: Default::default(),
+            permissions: Default::default(),
+            stream_idle_timeout_secs: DEFAULT_STREAM_IDLE_TIMEOUT.as_secs(),
+            debug_logging: false,
+            tool_support: Default::default(),
+            max_tool_failures: MAX_TOOL_FAILURES,
+            plugins: vec![],
+            scripted_tools: vec![],
+            shell: Default::default(),
+            openapi: Default::default(),
+            snippets: vec![],
+            auto_summarize_chapters: true,
+            recent_models: vec![],
+            provider_checks: Default::default(),
+            server_groups: vec![],
         });
         provider.set(s.provider.clone());
         s
@@ -67,8 +124,9 @@ pub fn Settings(props: SettingsProps) -> Element {
         {
             warn!("Could not save settings: {e:?}");
         }
+        // Keep config file and environment overrides in effect for the running app
         let mut settings_ctx = consume_context::<Signal<Option<AppSettings>>>();
-        settings_ctx.set(Some(s));
+        settings_ctx.set(with_overrides(Some(s)));
         settings.restart();
     };
     let handle_provider_change = move |ps: ProviderSettings| async move {
@@ -81,12 +139,87 @@ pub fn Settings(props: SettingsProps) -> Element {
         };
         save_settings(s).await;
     };
+    let handle_display_change = move |display: DisplaySettings| async move {
+        let Some(current_settings) = settings() else {
+            return;
+        };
+        let s = AppSettings {
+            display,
+            ..current_settings
+        };
+        save_settings(s).await;
+    };
+
+    let handle_story_change = move |auto_summarize_chapters: bool| async move {
+        let Some(current_settings) = settings() else {
+            return;
+        };
+        let s = AppSettings {
+            auto_summarize_chapters,
+            ..current_settings
+        };
+        save_settings(s).await;
+    };
+
+    let handle_debug_change = move |debug_logging: bool| async move {
+        let Some(current_settings) = settings() else {
+            return;
+        };
+        let s = AppSettings {
+            debug_logging,
+            ..current_settings
+        };
+        save_settings(s).await;
+    };
+    let handle_tool_support_change = move |(model, supported): (String, Option<bool>)| async move {
+        let Some(mut current_settings) = settings() else {
+            return;
+        };
+        match supported {
+            Some(s) => current_settings.tool_support.insert(model, s),
+            None => current_settings.tool_support.remove(&model),
+        };
+        save_settings(current_settings).await;
+    };
+    let handle_timeout_change = move |secs: u64| async move {
+        let Some(current_settings) = settings() else {
+            return;
+        };
+        let s = AppSettings {
+            stream_idle_timeout_secs: secs,
+            ..current_settings
+        };
+        save_settings(s).await;
+    };
+    let handle_max_failures_change = move |max_tool_failures: u8| async move {
+        let Some(current_settings) = settings() else {
+            return;
+        };
+        let s = AppSettings {
+            max_tool_failures,
+            ..current_settings
+        };
+        save_settings(s).await;
+    };
 
-    let settings = settings();
-    if settings.is_none() {
+    let Some(current) = settings() else {
         return rsx! { "Loading..." };
-    }
-    let settings = settings.unwrap();
+    };
+    let has_overrides = !overrides.is_empty();
+    let profile = active_profile();
+    // Browsers keep data in IndexedDB, which has no location to choose
+    #[cfg(not(target_arch = "wasm32"))]
+    let storage_location = rsx! {
+        StorageLocationSettings {
+            on_change: move |_| {
+                settings.restart();
+            },
+        }
+
+        hr { style: "margin: 2rem 0 1rem 0;" }
+    };
+    #[cfg(target_arch = "wasm32")]
+    let storage_location = rsx! {};
 
     rsx! {
         div {
@@ -117,11 +250,84 @@ pub fn Settings(props: SettingsProps) -> Element {
 
             hr { style: "margin-bottom: 1rem;" }
 
+            if has_overrides {
+                div { style: "
+                        background-color: #fff3cd;
+                        border: 1px solid #ffeeba;
+                        border-radius: 4px;
+                        padding: 0.5rem;
+                        margin-bottom: 1rem;
+                        color: #856404;
+                        font-size: 0.9rem;
+                    ",
+                    "Some settings are overridden by config.toml/config.json or MCMCPCP_* environment variables. "
+                    "Changes made here are saved, but the overrides take precedence."
+                }
+            }
+
+            ElProfileSettings {
+                on_switch: move |_| {
+                    settings.restart();
+                },
+            }
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
             ElProviderSettings { ps: provider, onchange: handle_provider_change }
+            ProviderCheckPanel {
+                provider: current.provider.clone(),
+                last: current.provider_checks.get(current.provider.kind()).cloned(),
+                on_tested: move |_| settings.restart(),
+            }
+            if let Some(model) = current.provider.get_model() {
+                ElToolSupportSettings {
+                    supported: current.tool_support.get(&model).copied(),
+                    model,
+                    onchange: handle_tool_support_change,
+                }
+            }
+            ElStreamSettings {
+                timeout_secs: current.stream_idle_timeout_secs,
+                onchange: handle_timeout_change,
+            }
+            ElToolFailureSettings {
+                max_failures: current.max_tool_failures,
+                onchange: handle_max_failures_change,
+            }
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            ElDisplaySettings { display: current.display.clone(), onchange: handle_display_change }
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            SnippetSettings { settings: current.clone(), on_save: save_settings }
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            ElStorySettings {
+                auto_summarize: current.auto_summarize_chapters,
+                onchange: handle_story_change,
+            }
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            ElDebugSettings { enabled: current.debug_logging, onchange: handle_debug_change }
 
             hr { style: "margin: 2rem 0 1rem 0;" }
 
-            McpServerSettings { settings, on_save: save_settings }
+            {storage_location}
+
+            StorageInsights {}
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            EncryptionSettings {}
+
+            hr { style: "margin: 2rem 0 1rem 0;" }
+
+            // Keyed by profile so the server list is rebuilt after switching
+            McpServerSettings { key: "{profile}", settings: current, on_save: save_settings }
         }
     }
 }
@@ -138,6 +344,7 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
     let servers = use_signal(|| settings.mcp_servers.clone().unwrap_or_default());
     let mut editing_server = use_signal(|| None::<usize>);
     let mut show_add_form = use_signal(|| false);
+    let mut show_import = use_signal(|| false);
 
     // let mut _s = servers.clone();
     // let _st = settings.clone();
@@ -167,6 +374,28 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
         show_add_form.set(false);
     };
 
+    let mut _s = servers;
+    let _st = settings.clone();
+    let import_servers = move |imported: Vec<ServerSpec>| {
+        let mut current_servers = _s();
+        // Never overwrite servers that are already configured
+        for server in imported {
+            if !current_servers.iter().any(|s| s.id == server.id) {
+                current_servers.push(server);
+            }
+        }
+
+        let updated_settings = AppSettings {
+            mcp_servers: Some(current_servers.clone()),
+            .._st.clone()
+        };
+
+        _s.set(current_servers);
+        on_save(updated_settings);
+
+        show_import.set(false);
+    };
+
     let mut _s = servers;
     let _st = settings.clone();
     let update_server = move |(index, server): (usize, ServerSpec)| {
@@ -185,6 +414,25 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
         editing_server.set(None);
     };
 
+    let mut _s = servers;
+    let _st = settings.clone();
+    let duplicate_server = move |index: usize| {
+        let mut current_servers = _s();
+        if let Some(server) = current_servers.get(index) {
+            let ids: Vec<String> = current_servers.iter().map(|s| s.id.clone()).collect();
+            // The copy goes right below, open for the edits that set it apart
+            current_servers.insert(index + 1, server.duplicate(&ids));
+            let updated_settings = AppSettings {
+                mcp_servers: Some(current_servers.clone()),
+                .._st.clone()
+            };
+
+            _s.set(current_servers);
+            on_save(updated_settings);
+            editing_server.set(Some(index + 1));
+        }
+    };
+
     let mut _s = servers;
     let _st = settings.clone();
     let delete_server = move |index: usize| {
@@ -215,6 +463,12 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
                             key: "{index}",
                             server: server.clone(),
                             index,
+                            taken_ids: servers()
+                                .iter()
+                                .enumerate()
+                                .filter(|(i, _)| *i != index)
+                                .map(|(_, s)| s.id.clone())
+                                .collect::<Vec<_>>(),
                             is_editing: editing_server() == Some(index),
                             on_edit: move |idx: usize| {
                                 editing_server.set(Some(idx));
@@ -223,6 +477,7 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
                             on_cancel: move |_| {
                                 editing_server.set(None);
                             },
+                            on_duplicate: duplicate_server.clone(),
                             on_delete: delete_server.clone(),
                         }
                     }
@@ -233,27 +488,72 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
             if show_add_form() {
                 ServerForm {
                     server: None,
+                    taken_ids: servers().iter().map(|s| s.id.clone()).collect::<Vec<_>>(),
                     on_save: add_server,
                     on_cancel: move |_| {
                         show_add_form.set(false);
                     },
                 }
-            } else {
-                button {
-                    style: "
-                        background: #007bff;
-                        color: white;
-                        border: none;
-                        padding: 0.5rem 1rem;
-                        border-radius: 4px;
-                        cursor: pointer;
-                    ",
-                    onclick: move |_| {
-                        show_add_form.set(true);
+            } else if show_import() {
+                ImportServers {
+                    existing: servers(),
+                    on_import: import_servers,
+                    on_cancel: move |_| {
+                        show_import.set(false);
                     },
-                    "+ Add Server"
+                }
+            } else {
+                div { style: "display: flex; gap: 0.5rem;",
+                    button {
+                        style: "
+                            background: #007bff;
+                            color: white;
+                            border: none;
+                            padding: 0.5rem 1rem;
+                            border-radius: 4px;
+                            cursor: pointer;
+                        ",
+                        onclick: move |_| {
+                            show_add_form.set(true);
+                        },
+                        "+ Add Server"
+                    }
+                    button {
+                        style: "
+                            background: #6c757d;
+                            color: white;
+                            border: none;
+                            padding: 0.5rem 1rem;
+                            border-radius: 4px;
+                            cursor: pointer;
+                        ",
+                        onclick: move |_| {
+                            show_import.set(true);
+                        },
+                        "Import from JSON"
+                    }
                 }
             }
+
+            hr { style: "margin: 1.5rem 0 1rem 0;" }
+
+            PluginSettings { settings: settings.clone(), on_save }
+
+            hr { style: "margin: 1.5rem 0 1rem 0;" }
+
+            ScriptedToolSettings { settings: settings.clone(), on_save }
+
+            hr { style: "margin: 1.5rem 0 1rem 0;" }
+
+            ShellServerSettings { settings: settings.clone(), on_save }
+
+            hr { style: "margin: 1.5rem 0 1rem 0;" }
+
+            OpenApiServerSettings { settings: settings.clone(), on_save }
+
+            hr { style: "margin: 1.5rem 0 1rem 0;" }
+
+            SecretsSettings { servers: servers() }
         }
     }
 }
@@ -262,10 +562,12 @@ fn McpServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>)
 fn ServerItem(
     server: ServerSpec,
     index: usize,
+    taken_ids: Vec<String>,
     is_editing: bool,
     on_edit: Callback<usize, ()>,
     on_save: Callback<(usize, ServerSpec), ()>,
     on_cancel: Callback<(), ()>,
+    on_duplicate: Callback<usize, ()>,
     on_delete: Callback<usize, ()>,
 ) -> Element {
     let on_toggle = {
@@ -276,10 +578,26 @@ fn ServerItem(
             on_save((index, updated_server));
         }
     };
+    let mut reloading = use_signal(|| false);
+    let mut reload_error = use_signal(|| None::<String>);
+    let reload = {
+        let server = server.clone();
+        move |_| {
+            let server = server.clone();
+            async move {
+                let host = consume_context::<Arc<MCPHost>>();
+                reloading.set(true);
+                let res = host.reload_server(server).await;
+                reload_error.set(res.err().map(|e| format!("{e:#}")));
+                reloading.set(false);
+            }
+        }
+    };
     if is_editing {
         rsx! {
             ServerForm {
                 server: Some(server),
+                taken_ids,
                 on_save: move |s: ServerSpec| {
                     on_save((index, s));
                 },
@@ -287,7 +605,7 @@ fn ServerItem(
             }
         }
     } else {
-        let args_display = server.args.join(" ");
+        let args_display = join_args(&server.args);
         let env_display = server
             .env
             .iter()
@@ -295,6 +613,8 @@ fn ServerItem(
             .collect::<Vec<_>>()
             .join(", ");
 
+        let sandbox_display = server.sandbox.summary();
+
         let status_color = if server.enabled { "#28a745" } else { "#6c757d" };
         let status_text = if server.enabled {
             "Enabled"
@@ -340,6 +660,21 @@ fn ServerItem(
                                 "Env: {env_display}"
                             }
                         }
+                        if let Some(sandbox) = sandbox_display {
+                            div { style: "font-size: 0.8em; color: #856404; margin-top: 0.25rem;",
+                                "🔒 Sandbox: {sandbox}"
+                            }
+                        }
+                        if let Some(watch) = &server.watch {
+                            div { style: "font-family: monospace; font-size: 0.8em; color: #888; margin-top: 0.25rem;",
+                                "Restarts when changed: {watch}"
+                            }
+                        }
+                        if let Some(e) = reload_error() {
+                            div { style: "font-size: 0.8em; color: #dc3545; margin-top: 0.25rem;",
+                                "Reload failed: {e}"
+                            }
+                        }
                     }
                     div { style: "
                         display: flex;
@@ -385,6 +720,43 @@ fn ServerItem(
                                 },
                                 "Edit"
                             }
+                            button {
+                                style: "
+                                    background: #6c757d;
+                                    color: white;
+                                    border: none;
+                                    padding: 0.25rem 0.5rem;
+                                    border-radius: 3px;
+                                    cursor: pointer;
+                                    font-size: 0.8rem;
+                                ",
+                                title: "Add a disabled copy of this server, to change what differs",
+                                onclick: move |_| {
+                                    on_duplicate(index);
+                                },
+                                "Duplicate"
+                            }
+                            if server.enabled {
+                                button {
+                                    style: "
+                                        background: #17a2b8;
+                                        color: white;
+                                        border: none;
+                                        padding: 0.25rem 0.5rem;
+                                        border-radius: 3px;
+                                        cursor: pointer;
+                                        font-size: 0.8rem;
+                                    ",
+                                    title: "Restart this server and refresh its tools",
+                                    disabled: reloading(),
+                                    onclick: reload,
+                                    if reloading() {
+                                        "Reloading…"
+                                    } else {
+                                        "Reload"
+                                    }
+                                }
+                            }
                             button {
                                 style: "
                                     background: #dc3545;
@@ -411,31 +783,57 @@ fn ServerItem(
 #[component]
 fn ServerForm(
     server: Option<ServerSpec>,
+    taken_ids: Vec<String>,
     on_save: Callback<ServerSpec, ()>,
     on_cancel: Callback<(), ()>,
 ) -> Element {
     let mut id = use_signal(|| server.as_ref().map(|s| s.id.clone()).unwrap_or_default());
     let mut cmd = use_signal(|| server.as_ref().map(|s| s.cmd.clone()).unwrap_or_default());
-    let mut args_text = use_signal(|| {
+    let mut args = use_signal(|| server.as_ref().map(|s| s.args.clone()).unwrap_or_default());
+    let mut watch_text = use_signal(|| {
         server
             .as_ref()
-            .map(|s| s.args.join(" "))
+            .and_then(|s| s.watch.clone())
             .unwrap_or_default()
     });
     let mut env_vars = use_signal(|| server.as_ref().map(|s| s.env.clone()).unwrap_or_default());
     let mut new_env_key = use_signal(String::new);
     let mut new_env_value = use_signal(String::new);
+    let mut new_env_secret = use_signal(|| false);
+    let mut env_error = use_signal(|| None::<String>);
+    let mut sandbox = use_signal(|| {
+        server
+            .as_ref()
+            .map(|s| s.sandbox.clone())
+            .unwrap_or_default()
+    });
+    let mut allowlist_text = use_signal(|| sandbox.read().env_allowlist.join(", "));
 
-    let add_env_var = move |_| {
+    let add_env_var = move |_| async move {
         let key = new_env_key().trim().to_string();
-        let value = new_env_value().trim().to_string();
+        let mut value = new_env_value().trim().to_string();
 
         if !key.is_empty() {
+            if let Err(e) = validate_env_key(&key) {
+                env_error.set(Some(e));
+                return;
+            }
+            // Keep the value out of the settings, which hold only a reference
+            if new_env_secret() {
+                let name = secret_name_for(id().trim(), &key);
+                if let Err(e) = save_secret(&name, &value).await {
+                    env_error.set(Some(format!("Could not save the secret: {e}")));
+                    return;
+                }
+                value = format!("${{{name}}}");
+            }
             let mut current_env = env_vars();
             current_env.insert(key, value);
             env_vars.set(current_env);
             new_env_key.set(String::new());
             new_env_value.set(String::new());
+            new_env_secret.set(false);
+            env_error.set(None);
         }
     };
 
@@ -446,31 +844,51 @@ fn ServerForm(
     };
 
     let server_enabled = server.as_ref().map(|s| s.enabled).unwrap_or(true);
-    let handle_save = move |_| async move {
+    // The server as the form describes it
+    let form_spec = move || {
         let id_val = id().trim().to_string();
         let cmd_val = cmd().trim().to_string();
-        let args_text = args_text.cloned();
-        let args_val = args_text.trim();
 
-        if id_val.is_empty() || cmd_val.is_empty() {
-            return; // Basic validation
+        let mut sandbox_val = sandbox();
+        sandbox_val.env_allowlist = allowlist_text()
+            .split(',')
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if sandbox_val.wrapper == SandboxWrapper::None {
+            // These need a wrapper and would stop the server from starting
+            sandbox_val.no_network = false;
+            sandbox_val.read_only = false;
         }
 
-        let args_vec = if args_val.is_empty() {
-            Vec::new()
-        } else {
-            args_val.split_whitespace().map(|s| s.to_string()).collect()
-        };
-
-        let server_spec = ServerSpec {
+        ServerSpec {
             id: id_val,
             cmd: cmd_val,
-            args: args_vec,
+            args: args(),
             env: env_vars(),
             enabled: server_enabled,
-        };
+            sandbox: sandbox_val,
+            watch: Some(watch_text().trim().to_string()).filter(|w| !w.is_empty()),
+        }
+    };
+    // Problems are shown as the form is edited, and for a new server only
+    // once saving was tried, so an empty form does not start out in red
+    let errors = use_memo(move || validate_server(&form_spec(), &taken_ids));
+    let mut tried_saving = use_signal(|| server.is_some());
+    let field_error = move |field: ServerField| -> Option<String> {
+        if !tried_saving() {
+            return None;
+        }
+        errors.read().get(field).map(str::to_string)
+    };
 
-        on_save(server_spec);
+    let handle_save = move |_| async move {
+        tried_saving.set(true);
+        // Invalid servers would fail to start, or make tool names ambiguous
+        if !errors.read().is_empty() {
+            return;
+        }
+        on_save(form_spec());
     };
 
     rsx! {
@@ -499,6 +917,18 @@ fn ServerForm(
                         id.set(e.value());
                     },
                 }
+                // A malformed ID is pointed out while it is typed
+                if let Some(e) = field_error(ServerField::Id)
+                    .or_else(|| {
+                        errors
+                            .read()
+                            .get(ServerField::Id)
+                            .filter(|_| !id().trim().is_empty())
+                            .map(str::to_string)
+                    })
+                {
+                    div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                }
             }
 
             div { style: "margin-bottom: 1rem;",
@@ -514,16 +944,32 @@ fn ServerForm(
                         box-sizing: border-box;
                     ",
                     value: cmd(),
-                    placeholder: "e.g., python -m weather_server",
+                    placeholder: "e.g., python",
                     oninput: move |e| {
                         cmd.set(e.value());
                     },
                 }
+                if let Some(e) = field_error(ServerField::Command) {
+                    div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                }
+            }
+
+            div { style: "margin-bottom: 1rem;",
+                label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;",
+                    "Arguments"
+                }
+                ArgsEditor {
+                    args: args(),
+                    on_change: move |a: Vec<String>| args.set(a),
+                }
+                if let Some(e) = field_error(ServerField::Args) {
+                    div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                }
             }
 
             div { style: "margin-bottom: 1rem;",
                 label { style: "display: block; margin-bottom: 0.25rem; font-weight: bold;",
-                    "Arguments (space-separated)"
+                    "Restart when changed (optional)"
                 }
                 input {
                     style: "
@@ -533,12 +979,18 @@ fn ServerForm(
                         border-radius: 3px;
                         box-sizing: border-box;
                     ",
-                    value: args_text(),
-                    placeholder: "e.g., --port 8080 --config config.json",
+                    value: watch_text(),
+                    placeholder: "e.g., /home/me/weather-server/dist",
                     oninput: move |e| {
-                        args_text.set(e.value());
+                        watch_text.set(e.value());
                     },
                 }
+                if let Some(e) = field_error(ServerField::Watch) {
+                    div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                }
+                div { style: "color: #666; font-size: 0.8em; margin-top: 0.25rem;",
+                    "File or folder of a server under development; the server restarts when anything in it changes."
+                }
             }
 
             // Environment Variables Section
@@ -623,6 +1075,7 @@ fn ServerForm(
                                 box-sizing: border-box;
                                 font-size: 0.9em;
                             ",
+                            r#type: if new_env_secret() { "password" } else { "text" },
                             value: new_env_value(),
                             placeholder: "e.g., your-api-key-here",
                             oninput: move |e| {
@@ -644,6 +1097,134 @@ fn ServerForm(
                         "Add"
                     }
                 }
+                label {
+                    style: "display: flex; align-items: center; gap: 0.25rem; margin-top: 0.25rem; font-size: 0.85em; color: #666;",
+                    title: "Store the value in the secrets store and keep only a reference to it in the settings",
+                    input {
+                        r#type: "checkbox",
+                        checked: new_env_secret(),
+                        onchange: move |e| new_env_secret.set(e.checked()),
+                    }
+                    "Secret"
+                }
+                if let Some(e) = env_error().or_else(|| field_error(ServerField::Env)) {
+                    div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                }
+            }
+
+            // Security options, applied when the server process is spawned
+            if cfg!(not(target_arch = "wasm32")) {
+                div { style: "margin-bottom: 1rem;",
+                    label { style: "display: block; margin-bottom: 0.5rem; font-weight: bold;",
+                        "Security"
+                    }
+                    div { style: "display: flex; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem;",
+                        span { style: "font-size: 0.9em;", "Sandbox wrapper" }
+                        select {
+                            style: "padding: 0.25rem; border: 1px solid #ddd; border-radius: 3px;",
+                            onchange: move |e| {
+                                sandbox.write().wrapper = match e.value().as_str() {
+                                    "bwrap" => SandboxWrapper::Bubblewrap,
+                                    "firejail" => SandboxWrapper::Firejail,
+                                    _ => SandboxWrapper::None,
+                                };
+                            },
+                            for w in [SandboxWrapper::None, SandboxWrapper::Bubblewrap, SandboxWrapper::Firejail] {
+                                option {
+                                    value: w.program().unwrap_or("none"),
+                                    selected: sandbox.read().wrapper == w,
+                                    {w.program().unwrap_or("None")}
+                                }
+                            }
+                        }
+                    }
+                    if let Some(e) = field_error(ServerField::Sandbox) {
+                        div { style: "color: #dc3545; font-size: 0.85em; margin-bottom: 0.5rem;", "{e}" }
+                    }
+                    if sandbox.read().wrapper == SandboxWrapper::Bubblewrap {
+                        div { style: "font-size: 0.8em; color: #666; margin-bottom: 0.5rem;",
+                            "bwrap makes the filesystem read-only, except /tmp and the working directory."
+                        }
+                    }
+                    label { style: "display: flex; align-items: center; gap: 0.5rem; font-size: 0.9em; margin-bottom: 0.25rem;",
+                        input {
+                            r#type: "checkbox",
+                            checked: sandbox.read().restrict_env,
+                            oninput: move |e: Event<FormData>| {
+                                sandbox.write().restrict_env = e.checked();
+                            },
+                        }
+                        "Only pass allowlisted environment variables"
+                    }
+                    if sandbox.read().restrict_env {
+                        input {
+                            style: "
+                                width: 100%;
+                                padding: 0.375rem;
+                                margin-bottom: 0.5rem;
+                                border: 1px solid #ddd;
+                                border-radius: 3px;
+                                box-sizing: border-box;
+                                font-family: monospace;
+                                font-size: 0.9em;
+                            ",
+                            value: allowlist_text(),
+                            placeholder: "e.g., PATH, HOME",
+                            oninput: move |e| {
+                                allowlist_text.set(e.value());
+                            },
+                        }
+                        if let Some(e) = field_error(ServerField::EnvAllowlist) {
+                            div { style: "color: #dc3545; font-size: 0.85em; margin-bottom: 0.5rem;", "{e}" }
+                        }
+                    }
+                    label { style: "display: flex; align-items: center; gap: 0.5rem; font-size: 0.9em; margin-bottom: 0.25rem;",
+                        input {
+                            r#type: "checkbox",
+                            checked: sandbox.read().no_network,
+                            disabled: sandbox.read().wrapper == SandboxWrapper::None,
+                            oninput: move |e: Event<FormData>| {
+                                sandbox.write().no_network = e.checked();
+                            },
+                        }
+                        "No network access"
+                    }
+                    label { style: "display: flex; align-items: center; gap: 0.5rem; font-size: 0.9em; margin-bottom: 0.25rem;",
+                        input {
+                            r#type: "checkbox",
+                            checked: sandbox.read().read_only,
+                            disabled: sandbox.read().wrapper == SandboxWrapper::None,
+                            oninput: move |e: Event<FormData>| {
+                                sandbox.write().read_only = e.checked();
+                            },
+                        }
+                        "Read-only working directory"
+                    }
+                    input {
+                        style: "
+                            width: 100%;
+                            padding: 0.375rem;
+                            border: 1px solid #ddd;
+                            border-radius: 3px;
+                            box-sizing: border-box;
+                            font-size: 0.9em;
+                        ",
+                        value: sandbox.read().working_dir.clone().unwrap_or_default(),
+                        placeholder: "Working directory (defaults to the app's)",
+                        oninput: move |e| {
+                            let dir = e.value();
+                            sandbox.write().working_dir = if dir.trim().is_empty() { None } else { Some(dir) };
+                        },
+                    }
+                    if let Some(e) = field_error(ServerField::WorkingDir) {
+                        div { style: "color: #dc3545; font-size: 0.85em; margin-top: 0.25rem;", "{e}" }
+                    }
+                    if sandbox.read().wrapper == SandboxWrapper::None {
+                        div { style: "font-size: 0.8em; color: #666; margin-top: 0.25rem;",
+                            "Network isolation and a read-only working directory need bwrap or firejail (Linux)."
+                        }
+                    }
+                }
             }
 
             div { style: "display: flex; gap: 0.5rem; justify-content: flex-end;",
@@ -670,6 +1251,7 @@ fn ServerForm(
                         border-radius: 3px;
                         cursor: pointer;
                     ",
+                    title: if tried_saving() && !errors.read().is_empty() { "Fix the problems above to save" } else { "" },
                     onclick: handle_save,
                     "Save"
                 }
@@ -678,15 +1260,355 @@ fn ServerForm(
     }
 }
 
+/// Profile picker: switches between named profiles, each with its own settings,
+/// MCP servers and chat history, and creates or removes profiles.
+#[component]
+fn ElProfileSettings(on_switch: Callback<(), ()>) -> Element {
+    let nav = navigator();
+    let mut settings_ctx = use_context::<Signal<Option<AppSettings>>>();
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let mut profiles = use_resource(move || async move {
+        match load_profiles().await {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Could not load profiles: {e:?}");
+                Profiles::default()
+            }
+        }
+    });
+    let mut new_name = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let switch_profile = move |name: String| async move {
+        let Some(mut p) = profiles() else { return };
+        if name == p.active {
+            return;
+        }
+        let host = host();
+        let res = async {
+            // Keep the outgoing profile's server state, then stop its servers so
+            // none of them carry over into the new profile
+            crate::utils::save_server_states(&host).await?;
+            host.sync_plugins(&[]).await?;
+            host.sync_scripted_tools(&[]).await;
+            host.sync_shell(&Default::default()).await;
+            host.sync_servers(vec![]).await?;
+
+            p.active = name.clone();
+            if !p.names.contains(&name) {
+                p.names.push(name.clone());
+            }
+            save_profiles(&p).await?;
+            set_active_profile(&name);
+            load_active_profile(&mut settings_ctx, &host).await
+        }
+        .await;
+        match res {
+            Ok(()) => {
+                error.set(None);
+                profiles.restart();
+                nav.push(Route::NewChat {});
+                on_switch(());
+            }
+            Err(e) => error.set(Some(format!("Could not switch profile: {e}"))),
+        }
+    };
+
+    let delete_profile = move |name: String| async move {
+        let Some(mut p) = profiles() else { return };
+        p.names.retain(|n| n != &name);
+        match save_profiles(&p).await {
+            Ok(()) => profiles.restart(),
+            Err(e) => error.set(Some(format!("Could not delete profile: {e}"))),
+        }
+    };
+
+    let Some(p) = profiles() else {
+        return rsx! {};
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Profile" }
+        div { style: "display: flex; flex-direction: column; gap: 0.25rem;",
+            for name in p.names.clone() {
+                {
+                    let active = name == p.active;
+                    let deletable = !active && name != DEFAULT_PROFILE;
+                    let n = name.clone();
+                    let d = name.clone();
+                    rsx! {
+                        div {
+                            key: "{name}",
+                            style: "display: flex; align-items: center; gap: 0.5rem;",
+                            label { style: "display: flex; align-items: center; gap: 0.5rem; flex: 1;",
+                                input {
+                                    r#type: "radio",
+                                    name: "profile",
+                                    checked: active,
+                                    oninput: move |_| switch_profile(n.clone()),
+                                }
+                                "{name}"
+                            }
+                            if deletable {
+                                button {
+                                    style: "
+                                        background: #dc3545;
+                                        color: white;
+                                        border: none;
+                                        padding: 0.125rem 0.5rem;
+                                        border-radius: 3px;
+                                        cursor: pointer;
+                                        font-size: 0.8rem;
+                                    ",
+                                    title: "Remove from the list; its data is kept on disk",
+                                    onclick: move |_| delete_profile(d.clone()),
+                                    "Remove"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        div { style: "display: flex; gap: 0.5rem; margin-top: 0.5rem;",
+            input {
+                style: "
+                    flex: 1;
+                    padding: 0.5rem;
+                    border: 1px solid #ddd;
+                    border-radius: 3px;
+                ",
+                placeholder: "New profile name",
+                value: new_name(),
+                oninput: move |e| new_name.set(e.value()),
+            }
+            button {
+                style: "
+                    background: #007bff;
+                    color: white;
+                    border: none;
+                    padding: 0.5rem 1rem;
+                    border-radius: 3px;
+                    cursor: pointer;
+                ",
+                disabled: new_name().trim().is_empty(),
+                onclick: move |_| async move {
+                    let name = new_name().trim().to_string();
+                    let names = profiles().map(|p| p.names).unwrap_or_default();
+                    match conflicting_profile(&name, &names) {
+                        Some(existing) if *existing == name => {
+                            error.set(Some(format!("Profile {name} already exists")));
+                            return;
+                        }
+                        Some(existing) => {
+                            error.set(Some(format!(
+                                "{name} is too close to the name of profile {existing}"
+                            )));
+                            return;
+                        }
+                        None => {}
+                    }
+                    new_name.set(String::new());
+                    switch_profile(name).await;
+                },
+                "Create and switch"
+            }
+        }
+        if let Some(err) = error() {
+            div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;", "{err}" }
+        }
+    }
+}
+
+/// Override of whether the selected model accepts tools.
+///
+/// Models that do not get the tools described in the system prompt and call
+/// them by replying with JSON.
+///
+/// # Arguments
+/// * `model` - The selected model
+/// * `supported` - The override, or `None` to go by the model metadata
+/// * `onchange` - Called with the model and its new override
+#[component]
+fn ElToolSupportSettings(
+    model: String,
+    supported: Option<bool>,
+    onchange: Callback<(String, Option<bool>), ()>,
+) -> Element {
+    let value = match supported {
+        None => "auto",
+        Some(true) => "native",
+        Some(false) => "prompt",
+    };
+    rsx! {
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-top: 1rem;",
+            "Tools for {model}:"
+            select {
+                value,
+                onchange: move |e: Event<FormData>| {
+                    let supported = match e.value().as_str() {
+                        "native" => Some(true),
+                        "prompt" => Some(false),
+                        _ => None,
+                    };
+                    onchange((model.clone(), supported));
+                },
+                option { value: "auto", selected: value == "auto", "Automatic (from model info)" }
+                option { value: "native", selected: value == "native", "Send as tools" }
+                option { value: "prompt", selected: value == "prompt", "Describe in the prompt" }
+            }
+        }
+    }
+}
+
+#[component]
+fn ElStreamSettings(timeout_secs: u64, onchange: Callback<u64, ()>) -> Element {
+    rsx! {
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-top: 1rem;",
+            "Abort a response after"
+            input {
+                r#type: "number",
+                min: "1",
+                style: "width: 5em;",
+                value: "{timeout_secs}",
+                onchange: move |e: Event<FormData>| {
+                    if let Ok(secs) = e.value().parse::<u64>()
+                        && secs > 0
+                    {
+                        onchange(secs);
+                    }
+                },
+            }
+            "seconds without new data"
+        }
+    }
+}
+
+#[component]
+fn ElToolFailureSettings(max_failures: u8, onchange: Callback<u8, ()>) -> Element {
+    rsx! {
+        label {
+            style: "display: flex; align-items: center; gap: 0.5rem; margin-top: 1rem;",
+            title: "Failed tool calls are reported to the model so it can try again; this many failures in a row stop the response with an error",
+            "Stop after"
+            input {
+                r#type: "number",
+                min: "1",
+                max: "20",
+                style: "width: 5em;",
+                value: "{max_failures}",
+                onchange: move |e: Event<FormData>| {
+                    if let Ok(n) = e.value().parse::<u8>()
+                        && n > 0
+                    {
+                        onchange(n);
+                    }
+                },
+            }
+            "failed tool calls in a row"
+        }
+    }
+}
+
+#[component]
+fn ElDebugSettings(enabled: bool, onchange: Callback<bool, ()>) -> Element {
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Debugging" }
+        label { style: "display: flex; align-items: center; gap: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: enabled,
+                oninput: move |e: Event<FormData>| onchange(e.checked()),
+            }
+            "Record LLM requests and responses (API keys are redacted)"
+        }
+        div { style: "margin-top: 0.5rem;",
+            Link { to: Route::DebugPanel {}, "Open request log" }
+        }
+        div { style: "margin-top: 0.5rem;",
+            Link { to: Route::AbTest {}, "A/B test system prompts" }
+        }
+    }
+}
+
+#[component]
+fn ElStorySettings(auto_summarize: bool, onchange: Callback<bool, ()>) -> Element {
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Stories" }
+        label { style: "display: flex; align-items: center; gap: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: auto_summarize,
+                oninput: move |e: Event<FormData>| onchange(e.checked()),
+            }
+            "Summarize changed chapters in the background after each turn"
+        }
+        div { style: "margin-top: 0.25rem; font-size: 0.85em; color: #666;",
+            "Keeps chapter summaries and plot points current with an extra request to the model per changed chapter."
+        }
+    }
+}
+
+#[component]
+fn ElDisplaySettings(display: DisplaySettings, onchange: Callback<DisplaySettings, ()>) -> Element {
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Display" }
+        label { style: "display: flex; align-items: center; gap: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: display.render_math,
+                oninput: {
+                    let display = display.clone();
+                    move |e: Event<FormData>| {
+                        let mut d = display.clone();
+                        d.render_math = e.checked();
+                        onchange(d);
+                    }
+                },
+            }
+            "Render LaTeX math ($...$ and $$...$$)"
+        }
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-top: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: display.raw_html,
+                oninput: {
+                    let display = display.clone();
+                    move |e: Event<FormData>| {
+                        let mut d = display.clone();
+                        d.raw_html = e.checked();
+                        onchange(d);
+                    }
+                },
+            }
+            "Render HTML in messages (scripts, frames and event handlers are always removed; otherwise it is shown as text)"
+        }
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-top: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: display.enter_sends,
+                oninput: move |e: Event<FormData>| {
+                    let mut d = display.clone();
+                    d.enter_sends = e.checked();
+                    onchange(d);
+                },
+            }
+            "Send messages with Enter (Shift+Enter for a new line; otherwise Ctrl+Enter sends)"
+        }
+    }
+}
+
+/// Name of a provider kind, as offered in the provider selector.
+fn provider_type(ps: &ProviderSettings) -> &'static str {
+    ps.kind()
+}
+
 #[component]
 fn ElProviderSettings(
     ps: Signal<ProviderSettings>,
     onchange: Callback<ProviderSettings, ()>,
 ) -> Element {
-    let mut p_type = use_signal(|| match ps() {
-        ProviderSettings::OpenRouter { .. } => "openrouter".to_string(),
-        ProviderSettings::Ollama { .. } => "ollama".to_string(),
-    });
+    let mut p_type = use_signal(|| provider_type(&ps()).to_string());
     rsx! {
         h4 { style: "margin: 0 0 1rem 0;", "API provider" }
         BoxSelect {
@@ -704,6 +1626,14 @@ fn ElProviderSettings(
         if p_type() == "ollama" {
             OllamaSettings { ps, onchange }
         }
+        // Options belong to the saved provider, so wait until it is the one shown
+        if p_type() == provider_type(&ps()) {
+            ProviderOptionsEditor {
+                key: "{p_type}",
+                options: ps().get_options().clone(),
+                on_save: move |options| onchange(ps().with_options(options)),
+            }
+        }
     }
 }
 
@@ -712,26 +1642,83 @@ fn OllamaSettings(
     ps: Signal<ProviderSettings>,
     onchange: Callback<ProviderSettings, ()>,
 ) -> Element {
-    let mut available_models = use_signal(Vec::<String>::new);
+    // Start from the list prefetched at startup, if any
+    let mut available_models = use_signal(|| {
+        LlmClient::cached_model_names(&ps.peek().get_api_url()).unwrap_or_default()
+    });
 
+    // The endpoint as typed while it is not a valid URL, which is not saved
+    let mut url_draft: Signal<Option<String>> = use_signal(|| None);
     let handle_url_change = move |e: Event<FormData>| async move {
-        let model = if let ProviderSettings::Ollama { model, .. } = ps() {
-            model
+        if validate_http_url(&e.value()).is_err() {
+            url_draft.set(Some(e.value()));
+            return;
+        }
+        url_draft.set(None);
+        let (model, options, keep_alive) = if let ProviderSettings::Ollama {
+            model,
+            options,
+            keep_alive,
+            ..
+        } = ps()
+        {
+            (model, options, keep_alive)
         } else {
-            None
+            (None, Default::default(), None)
         };
         onchange(ProviderSettings::Ollama {
             api_url: e.value(),
             model,
+            options,
+            keep_alive,
         });
     };
     let set_model = move |model: Option<String>| async move {
-        let api_url = if let ProviderSettings::Ollama { api_url, .. } = ps() {
-            api_url
+        let (api_url, options, keep_alive) = if let ProviderSettings::Ollama {
+            api_url,
+            options,
+            keep_alive,
+            ..
+        } = ps()
+        {
+            (api_url, options, keep_alive)
         } else {
-            "http://192.168.29.3:11434/v1".to_string()
+            (
+                "http://192.168.29.3:11434/v1".to_string(),
+                Default::default(),
+                None,
+            )
         };
-        onchange(ProviderSettings::Ollama { api_url, model });
+        onchange(ProviderSettings::Ollama {
+            api_url,
+            model,
+            options,
+            keep_alive,
+        });
+    };
+    let handle_keep_alive_change = move |e: Event<FormData>| async move {
+        let (api_url, model, options) = if let ProviderSettings::Ollama {
+            api_url,
+            model,
+            options,
+            ..
+        } = ps()
+        {
+            (api_url, model, options)
+        } else {
+            (
+                "http://192.168.29.3:11434/v1".to_string(),
+                None,
+                Default::default(),
+            )
+        };
+        let value = e.value().trim().to_string();
+        onchange(ProviderSettings::Ollama {
+            api_url,
+            model,
+            options,
+            keep_alive: (!value.is_empty()).then_some(value),
+        });
     };
     let get_available_models = move || async move {
         let api_url = if let ProviderSettings::Ollama { api_url, .. } = ps() {
@@ -740,9 +1727,7 @@ fn OllamaSettings(
             "http://192.168.29.3:11434/v1".to_string()
         };
         let lmc = LlmClient::new(api_url, "".to_string());
-        let models = lmc.models().await?;
-        let names = models.data.into_iter().map(|m| m.id).collect::<Vec<_>>();
-        anyhow::Ok(names)
+        lmc.model_names().await
     };
     let refresh_model_list = move |_e: Event<MouseData>| async move {
         match get_available_models().await {
@@ -755,10 +1740,16 @@ fn OllamaSettings(
         }
     };
 
-    let (api_url, model) = if let ProviderSettings::Ollama { api_url, model } = ps() {
-        (api_url, model)
+    let (api_url, model, keep_alive) = if let ProviderSettings::Ollama {
+        api_url,
+        model,
+        keep_alive,
+        ..
+    } = ps()
+    {
+        (api_url, model, keep_alive)
     } else {
-        ("http://192.168.29.3:11434/v1".to_string(), None)
+        ("http://192.168.29.3:11434/v1".to_string(), None, None)
     };
 
     rsx! {
@@ -769,7 +1760,13 @@ fn OllamaSettings(
             flex-direction: column;
             ",
             label { style: "margin-top: 1em;", "API endpoint" }
-            input { value: api_url, oninput: handle_url_change }
+            input {
+                value: url_draft().unwrap_or(api_url),
+                oninput: handle_url_change,
+            }
+            if let Some(e) = url_draft().and_then(|u| validate_http_url(&u).err()) {
+                small { style: "color: #dc3545;", "{e}" }
+            }
             // label { style: "margin-top: 1em;", "API Key" }
             // input { value: settings.api_key, oninput: handle_key_change }
             label { style: "margin-top: 1em;",
@@ -790,6 +1787,15 @@ fn OllamaSettings(
                     on_select: set_model,
                 }
             }
+            label { style: "margin-top: 1em;", "Keep model loaded for" }
+            input {
+                value: keep_alive.unwrap_or_default(),
+                placeholder: "Server default (5m)",
+                oninput: handle_keep_alive_change,
+            }
+            small { style: "color: #666;",
+                "How long the model stays in memory after a request, e.g. 30m or 2h, or -1 to keep it loaded. The model is also loaded when a chat is opened."
+            }
         }
     }
 }
@@ -800,30 +1806,40 @@ fn OpenRouterSettings(
     onchange: Callback<ProviderSettings, ()>,
 ) -> Element {
     let mut filter = use_signal(|| "".to_string());
-    let mut available_models = use_signal(Vec::<String>::new);
+    // Start from the list prefetched at startup, if any
+    let mut available_models =
+        use_signal(|| LlmClient::cached_model_names(OPENROUTER_API_URL).unwrap_or_default());
     let mut auth_url = use_signal(|| "".to_string());
 
     let set_key = move |key: String| async move {
-        let model = if let ProviderSettings::OpenRouter { model, .. } = ps() {
-            model
+        let (model, options) = if let ProviderSettings::OpenRouter { model, options, .. } = ps() {
+            (model, options)
         } else {
-            None
+            (None, Default::default())
         };
         onchange(ProviderSettings::OpenRouter {
             api_key: key,
             model,
+            options,
         });
     };
     let handle_key_change = move |e: Event<FormData>| async move {
         set_key(e.value()).await;
     };
     let set_model = move |model: Option<String>| async move {
-        let api_key = if let ProviderSettings::OpenRouter { api_key, .. } = ps() {
-            api_key
+        let (api_key, options) = if let ProviderSettings::OpenRouter {
+            api_key, options, ..
+        } = ps()
+        {
+            (api_key, options)
         } else {
-            "".to_string()
+            ("".to_string(), Default::default())
         };
-        onchange(ProviderSettings::OpenRouter { api_key, model });
+        onchange(ProviderSettings::OpenRouter {
+            api_key,
+            model,
+            options,
+        });
     };
     let get_available_models = move || async move {
         let api_key = if let ProviderSettings::OpenRouter { api_key, .. } = ps() {
@@ -831,10 +1847,8 @@ fn OpenRouterSettings(
         } else {
             "".to_string()
         };
-        let lmc = LlmClient::new("https://openrouter.ai/api/v1".to_string(), api_key);
-        let models = lmc.models().await?;
-        let names = models.data.into_iter().map(|m| m.id).collect::<Vec<_>>();
-        anyhow::Ok(names)
+        let lmc = LlmClient::new(OPENROUTER_API_URL.to_string(), api_key);
+        lmc.model_names().await
     };
     let refresh_model_list = move |_e: Event<MouseData>| async move {
         match get_available_models().await {
@@ -970,7 +1984,7 @@ fn OpenRouterSettings(
     #[cfg(target_arch = "wasm32")]
     let start_pkce_button = rsx! {};
 
-    let (api_key, model) = if let ProviderSettings::OpenRouter { api_key, model } = ps() {
+    let (api_key, model) = if let ProviderSettings::OpenRouter { api_key, model, .. } = ps() {
         (api_key, model)
     } else {
         ("".to_string(), None)
@@ -984,7 +1998,8 @@ fn OpenRouterSettings(
             flex-direction: column;
             ",
             label { style: "margin-top: 1em;", "API Key" }
-            input { value: api_key, oninput: handle_key_change }
+            input { value: api_key.clone(), oninput: handle_key_change }
+            OpenRouterKeyStatus { api_key: api_key.clone() }
             p {
                 {start_pkce_button}
             }
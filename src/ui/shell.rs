@@ -0,0 +1,105 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::{AppSettings, mcp::shell::ShellSettings};
+
+/// Settings of the shell server: whether it runs, the directory commands run
+/// in, the commands allowed and how long they may take.
+///
+/// # Arguments
+/// * `settings` - Current settings
+/// * `on_save` - Called with the settings after the shell settings are changed
+#[component]
+pub fn ShellServerSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>) -> Element {
+    let shell = settings.shell.clone();
+    let mut working_dir = use_signal(|| shell.working_dir.clone());
+    let mut allowlist = use_signal(|| shell.allowlist.join("\n"));
+    let mut timeout = use_signal(|| shell.timeout_secs.to_string());
+    let mut error = use_signal(|| None::<String>);
+
+    let save = use_callback(move |shell: ShellSettings| {
+        on_save(AppSettings {
+            shell,
+            ..settings.clone()
+        });
+    });
+
+    let enabled = shell.enabled;
+    let current = shell.clone();
+    let submit = move |_| {
+        let dir = working_dir().trim().to_string();
+        if !dir.is_empty() && !std::path::Path::new(&dir).is_dir() {
+            error.set(Some(format!("{dir} is not a directory")));
+            return;
+        }
+        let Ok(timeout_secs) = timeout().trim().parse::<u64>() else {
+            error.set(Some("The timeout must be a whole number of seconds".into()));
+            return;
+        };
+        error.set(None);
+        save(ShellSettings {
+            working_dir: dir,
+            allowlist: allowlist()
+                .lines()
+                .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|l| !l.is_empty())
+                .collect(),
+            timeout_secs: timeout_secs.max(1),
+            ..current.clone()
+        });
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Shell Commands" }
+        p { style: "color: #666; font-size: 0.9em; margin-top: 0;",
+            "Lets the model run commands in a working directory, e.g. to build and test code. "
+            "Only commands starting with an allowlist entry can run, and you approve every command before it runs."
+        }
+        label { style: "display: flex; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem;",
+            input {
+                r#type: "checkbox",
+                checked: enabled,
+                oninput: move |e: Event<FormData>| {
+                    save(ShellSettings {
+                        enabled: e.checked(),
+                        ..shell.clone()
+                    });
+                },
+            }
+            "Offer the shell server to the model"
+        }
+        div { style: "display: flex; flex-direction: column; gap: 0.5rem;",
+            label { style: "font-size: 0.9em; color: #666;", "Working directory" }
+            input {
+                placeholder: "/home/me/project",
+                value: working_dir(),
+                oninput: move |e| working_dir.set(e.value()),
+            }
+            label { style: "font-size: 0.9em; color: #666;",
+                "Allowed commands, one per line, e.g. "
+                code { "cargo" }
+                " or "
+                code { "git status" }
+            }
+            textarea {
+                style: "width: 100%; box-sizing: border-box; height: 6em; font-family: monospace;",
+                value: "{allowlist}",
+                oninput: move |e: Event<FormData>| allowlist.set(e.value()),
+            }
+            label { style: "font-size: 0.9em; color: #666;", "Timeout (seconds)" }
+            input {
+                r#type: "number",
+                min: "1",
+                value: timeout(),
+                oninput: move |e| timeout.set(e.value()),
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545;", "{e}" }
+            }
+            div {
+                button { onclick: submit, "Save Shell Settings" }
+            }
+        }
+    }
+}
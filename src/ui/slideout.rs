@@ -30,17 +30,8 @@ pub fn Slideout(mut props: SlideoutProps) -> Element {
                 props.open.set(false);
             },
 
-            // Slideout panel
-            div {
-                style: "
-                    position: fixed;
-                    top: 0;
-                    right: 0;
-                    max-width: 80%;
-                    height: 100%;
-                    background: #fff;
-                    z-index: 999;
-                ",
+            // Slideout panel; full-screen on narrow screens
+            div { class: "slideout-panel",
                 onclick: move |e: Event<MouseData>| {
                     e.stop_propagation();
                 },
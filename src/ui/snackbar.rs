@@ -0,0 +1,62 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+/// How long a snackbar stays up before dismissing itself.
+const SNACKBAR_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Transient notice at the bottom of the window with a single action, such as undo.
+///
+/// The snackbar dismisses itself after a few seconds. Give it a new `key` for
+/// each notice so the timer restarts.
+///
+/// # Arguments
+/// * `message` - Text of the notice
+/// * `action` - Label of the action button
+/// * `on_action` - Called when the action button is clicked
+/// * `on_dismiss` - Called when the notice times out or is closed
+#[component]
+pub fn Snackbar(
+    message: String,
+    action: String,
+    on_action: EventHandler<()>,
+    on_dismiss: EventHandler<()>,
+) -> Element {
+    use_future(move || async move {
+        crate::core::sleep(SNACKBAR_TIMEOUT).await;
+        on_dismiss.call(());
+    });
+
+    rsx! {
+        div { style: "
+            position: fixed;
+            bottom: 6em;
+            left: 50%;
+            transform: translateX(-50%);
+            z-index: 10;
+            display: flex;
+            align-items: center;
+            gap: 1em;
+            background-color: #323232;
+            border-radius: 4px;
+            padding: 0.6em 1em;
+            color: #fff;
+            box-shadow: 0 2px 6px rgba(0, 0, 0, 0.3);
+            ",
+            span { "{message}" }
+            button {
+                style: "border: none; background: none; color: #8ab4f8; cursor: pointer; font-weight: bold;",
+                onclick: move |_| on_action.call(()),
+                "{action}"
+            }
+            button {
+                style: "border: none; background: none; color: #ccc; cursor: pointer;",
+                title: "Dismiss",
+                onclick: move |_| on_dismiss.call(()),
+                "✕"
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::app_settings::{AppSettings, Snippet};
+
+/// Checks that an abbreviation can be typed and expanded.
+///
+/// # Arguments
+/// * `abbreviation` - The abbreviation
+/// * `others` - Abbreviations of the other snippets, which it must not repeat
+fn validate_abbreviation(abbreviation: &str, others: &[&str]) -> Result<(), String> {
+    if abbreviation.is_empty() {
+        return Err("Enter an abbreviation".into());
+    }
+    if abbreviation.contains(char::is_whitespace) {
+        return Err("Abbreviations cannot contain spaces".into());
+    }
+    if abbreviation.starts_with('/') {
+        return Err("Abbreviations cannot start with /, which starts commands".into());
+    }
+    if others.contains(&abbreviation) {
+        return Err(format!("A snippet for {abbreviation} already exists"));
+    }
+    Ok(())
+}
+
+/// List of snippets, with a form to add one. Typing a snippet's abbreviation
+/// in the message input and pressing Tab replaces it with the expansion.
+///
+/// # Arguments
+/// * `settings` - Current settings
+/// * `on_save` - Called with the settings after snippets are changed
+#[component]
+pub fn SnippetSettings(settings: AppSettings, on_save: Callback<AppSettings, ()>) -> Element {
+    let mut abbreviation = use_signal(String::new);
+    let mut expansion = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let snippets = settings.snippets.clone();
+    let save = use_callback(move |snippets: Vec<Snippet>| {
+        on_save(AppSettings {
+            snippets,
+            ..settings.clone()
+        });
+    });
+
+    let existing = snippets.clone();
+    let add = move |_| {
+        let abbr = abbreviation().trim().to_string();
+        let others: Vec<&str> = existing.iter().map(|s| s.abbreviation.as_str()).collect();
+        if let Err(e) = validate_abbreviation(&abbr, &others) {
+            error.set(Some(e));
+            return;
+        }
+        if expansion().trim().is_empty() {
+            error.set(Some("Enter the text to expand to".into()));
+            return;
+        }
+        let mut snippets = existing.clone();
+        snippets.push(Snippet {
+            abbreviation: abbr,
+            expansion: expansion(),
+        });
+        save(snippets);
+        abbreviation.set(String::new());
+        expansion.set(String::new());
+        error.set(None);
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 1rem 0;", "Snippets" }
+        p { style: "color: #666; font-size: 0.9em; margin-top: 0;",
+            "Type an abbreviation in the message input and press Tab to expand it. "
+            "Placeholders like "
+            code { "{{{{tone}}}}" }
+            " are selected one after another with Tab, ready to be typed over."
+        }
+        if snippets.is_empty() {
+            p { style: "color: #666; font-style: italic;", "No snippets" }
+        }
+        for (index , snippet) in snippets.iter().cloned().enumerate() {
+            div {
+                key: "{snippet.abbreviation}",
+                style: "display: flex; gap: 0.5rem; align-items: flex-start; border: 1px solid #ddd; border-radius: 4px; padding: 0.5rem; margin-bottom: 0.5rem;",
+                code { style: "min-width: 6em;", "{snippet.abbreviation}" }
+                textarea {
+                    style: "flex: 1; height: 3em; font-family: inherit;",
+                    value: "{snippet.expansion}",
+                    onchange: {
+                        let snippets = snippets.clone();
+                        move |e: Event<FormData>| {
+                            let mut snippets = snippets.clone();
+                            snippets[index].expansion = e.value();
+                            save(snippets);
+                        }
+                    },
+                }
+                button {
+                    title: "Remove the snippet",
+                    onclick: {
+                        let snippets = snippets.clone();
+                        move |_| {
+                            let mut snippets = snippets.clone();
+                            snippets.remove(index);
+                            save(snippets);
+                        }
+                    },
+                    "×"
+                }
+            }
+        }
+        div { style: "display: flex; gap: 0.5rem; align-items: flex-start;",
+            input {
+                style: "width: 8em;",
+                placeholder: "Abbreviation",
+                value: abbreviation(),
+                oninput: move |e| abbreviation.set(e.value()),
+            }
+            textarea {
+                style: "flex: 1; height: 3em; font-family: inherit;",
+                placeholder: "Expansion, e.g. Write in a {{{{tone}}}} tone, in the present tense.",
+                value: "{expansion}",
+                oninput: move |e: Event<FormData>| expansion.set(e.value()),
+            }
+            button { onclick: add, "+ Add Snippet" }
+        }
+        if let Some(e) = error() {
+            div { style: "color: #dc3545; margin-top: 0.5rem;", "{e}" }
+        }
+    }
+}
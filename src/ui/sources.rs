@@ -0,0 +1,121 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Sources of an answer, derived from the fetch and search tool calls made
+//! while writing it.
+//!
+//! URLs passed to fetch tools are sources as they are; search results are
+//! scanned for the URLs they list, since the model may have answered from the
+//! snippets alone.
+
+use dioxus::prelude::*;
+use serde_json::Value;
+
+use crate::{llm::Message, mcp::tool_ref::ToolRef};
+
+/// Most URLs taken from the results of a single search.
+const MAX_SEARCH_SOURCES: usize = 10;
+
+/// Collects the URLs consulted by the fetch and search tool calls in a turn.
+///
+/// # Arguments
+/// * `messages` - The messages of the turn, before the answer
+///
+/// # Returns
+/// The URLs in the order they were consulted, without duplicates
+pub fn turn_sources(messages: &[Message]) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    let mut add = |url: &str| {
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    };
+    for message in messages {
+        let Message::Assistant {
+            tool_calls: Some(calls),
+            ..
+        } = message
+        else {
+            continue;
+        };
+        for call in calls {
+            let Some(f) = &call.function else { continue };
+            let Some(tool) = ToolRef::decode(f.name.as_deref().unwrap_or_default()) else {
+                continue;
+            };
+            let name = tool.tool.to_lowercase();
+            let is_search = name.contains("search");
+            if !is_search && !name.contains("fetch") {
+                continue;
+            }
+            let args: Value = f
+                .arguments
+                .as_deref()
+                .and_then(|a| serde_json::from_str(a).ok())
+                .unwrap_or_default();
+            for key in ["url", "urls"] {
+                match args.get(key) {
+                    Some(Value::String(url)) if is_web_url(url) => add(url),
+                    Some(Value::Array(list)) => list
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .filter(|u| is_web_url(u))
+                        .for_each(&mut add),
+                    _ => {}
+                }
+            }
+            if is_search {
+                let result = messages.iter().find_map(|m| match m {
+                    Message::Tool {
+                        tool_call_id,
+                        content,
+                    } if call.id.as_ref() == Some(tool_call_id) => Some(content),
+                    _ => None,
+                });
+                result
+                    .map(|r| urls_in(r))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .take(MAX_SEARCH_SOURCES)
+                    .for_each(&mut add);
+            }
+        }
+    }
+    urls
+}
+
+/// Whether a string is an http(s) URL.
+fn is_web_url(s: &str) -> bool {
+    s.starts_with("https://") || s.starts_with("http://")
+}
+
+/// Finds the http(s) URLs mentioned in free text, Markdown or JSON.
+fn urls_in(text: &str) -> Vec<&str> {
+    text.split(|c: char| c.is_whitespace() || "\"'<>()[]{}`|".contains(c))
+        .map(|w| w.trim_end_matches(['.', ',', ';', ':', '!', '?']))
+        .filter(|w| is_web_url(w) && w.len() > "https://".len())
+        .collect()
+}
+
+/// Numbered list of links to the sources of an answer.
+#[component]
+pub fn SourcesList(urls: Vec<String>) -> Element {
+    rsx! {
+        div { class: "sources",
+            style: "margin-top: 1em; padding-top: 0.5em; border-top: 1px solid rgba(255, 255, 255, 0.2); font-size: 0.85em;",
+            div { style: "font-weight: 600; margin-bottom: 0.25em; opacity: 0.9;", "Sources" }
+            ol { style: "margin: 0; padding-left: 1.5em;",
+                for url in urls {
+                    li { key: "{url}",
+                        a {
+                            href: "{url}",
+                            target: "_blank",
+                            rel: "noopener noreferrer",
+                            style: "color: inherit; word-break: break-all;",
+                            "{url}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
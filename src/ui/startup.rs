@@ -0,0 +1,136 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Startup warm-up shown on the loading screen.
+//!
+//! Instead of spawning MCP servers when they are first needed and fetching the
+//! model list when the settings page opens, both happen concurrently while the
+//! app loads, so the first chat turn and the model pickers are ready right away.
+
+use dioxus::prelude::*;
+use futures::future::join_all;
+
+use crate::{app_settings::AppSettings, mcp::host::MCPHost};
+
+/// State of one warm-up step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepState {
+    Running,
+    /// Finished, with a short summary such as "12 tools"
+    Done(String),
+    Failed(String),
+}
+
+/// A task run during warm-up, e.g. starting one MCP server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupStep {
+    pub label: String,
+    pub state: StepState,
+}
+
+/// Starts the enabled MCP servers and prefetches the provider's model list, all at once.
+///
+/// Failures are recorded in the steps and do not stop the other tasks.
+///
+/// # Arguments
+/// * `settings` - Settings of the active profile
+/// * `host` - Global MCP host to start the servers in
+/// * `steps` - Signal receiving the progress of each task
+pub async fn warm_up(settings: &AppSettings, host: &MCPHost, steps: Signal<Vec<WarmupStep>>) {
+    let specs: Vec<_> = settings
+        .mcp_servers
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|s| s.enabled)
+        .collect();
+
+    let servers = join_all(specs.into_iter().map(|spec| async move {
+        if host.server_tools(&spec.id).await.is_some() {
+            return;
+        }
+        let step = start_step(steps, format!("Starting MCP server \"{}\"", spec.id));
+        let id = spec.id.clone();
+        let state = match host.add_server(spec).await {
+            Ok(()) => {
+                let count = host.server_tools(&id).await.unwrap_or_default().len();
+                StepState::Done(format!("{count} tools"))
+            }
+            Err(e) => StepState::Failed(e.to_string()),
+        };
+        finish_step(steps, step, state);
+    }));
+
+    let models = async {
+        if !settings.provider.is_configured() {
+            return;
+        }
+        let step = start_step(steps, "Fetching model list".to_string());
+        let client = settings.provider.client();
+        let state = match client.model_names().await {
+            Ok(names) => StepState::Done(format!("{} models", names.len())),
+            Err(e) => StepState::Failed(e.to_string()),
+        };
+        finish_step(steps, step, state);
+    };
+
+    futures::future::join(servers, models).await;
+}
+
+/// Adds a running step and returns its index.
+fn start_step(mut steps: Signal<Vec<WarmupStep>>, label: String) -> usize {
+    let mut steps = steps.write();
+    steps.push(WarmupStep {
+        label,
+        state: StepState::Running,
+    });
+    steps.len() - 1
+}
+
+/// Records the outcome of a step.
+fn finish_step(mut steps: Signal<Vec<WarmupStep>>, index: usize, state: StepState) {
+    if let Some(step) = steps.write().get_mut(index) {
+        step.state = state;
+    }
+}
+
+/// Loading screen listing the warm-up steps as they run.
+///
+/// # Arguments
+/// * `steps` - Progress of the warm-up
+/// * `on_skip` - Called when the user stops waiting; warm-up continues in the background
+#[component]
+pub fn StartupScreen(steps: Vec<WarmupStep>, on_skip: Callback<(), ()>) -> Element {
+    let has_steps = !steps.is_empty();
+    rsx! {
+        div { style: "
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            justify-content: center;
+            height: 100vh;
+            gap: 0.5em;
+            ",
+            h3 { "Loading..." }
+            for (i , step) in steps.into_iter().enumerate() {
+                {
+                    let (icon, detail, color) = match step.state {
+                        StepState::Running => ("⏳", String::new(), "#666"),
+                        StepState::Done(summary) => ("✓", format!(" ({summary})"), "#28a745"),
+                        StepState::Failed(e) => ("✗", format!(": {e}"), "#dc3545"),
+                    };
+                    rsx! {
+                        div {
+                            key: "{i}",
+                            style: "font-size: 0.9em; max-width: 40em; color: {color};",
+                            "{icon} {step.label}{detail}"
+                        }
+                    }
+                }
+            }
+            // Settings are loaded once the first step appears, so the app can start
+            if has_steps {
+                button { style: "margin-top: 1em;", onclick: move |_| on_skip(()), "Continue without waiting" }
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::{logger::tracing::warn, prelude::*};
+
+use crate::{
+    Route,
+    core::now_millis,
+    storage::{
+        Storage, StorageUsage, delete_chats_before, get_storage, strip_chat_images,
+        strip_images_before,
+    },
+};
+
+/// Chats listed by size; the rest are only counted in the total.
+const LISTED_CHATS: usize = 20;
+/// Ages offered for bulk cleanup, in days.
+const CLEANUP_AGES: [u64; 4] = [7, 30, 90, 365];
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+/// Reports the space the browser uses for the app and allows it, as
+/// `[usage, quota]` in bytes.
+const ESTIMATE_JS: &str = r#"
+const estimate = navigator.storage && navigator.storage.estimate
+    ? await navigator.storage.estimate()
+    : null;
+dioxus.send(estimate ? [estimate.usage || 0, estimate.quota || 0] : null);
+"#;
+
+/// Cleanup of old chats, waiting for confirmation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cleanup {
+    DeleteChats,
+    StripImages,
+}
+
+/// Space used by the active profile's storage and by each chat, with
+/// cleanup of old chats and their images. Measured only when opened, since
+/// it reads every chat.
+#[component]
+pub fn StorageInsights() -> Element {
+    let mut open = use_signal(|| false);
+    let mut refresh = use_signal(|| 0u32);
+    let mut age_days = use_signal(|| 90u64);
+    let mut pending: Signal<Option<Cleanup>> = use_signal(|| None);
+    let mut status: Signal<Option<String>> = use_signal(|| None);
+
+    let usage = use_resource(move || async move {
+        let _ = refresh();
+        if !open() {
+            return None;
+        }
+        let res = async { get_storage().await?.storage_usage().await }.await;
+        match res {
+            Ok(u) => Some(u),
+            Err(e) => {
+                warn!("Could not measure storage: {e:?}");
+                status.set(Some(format!("Could not measure storage: {e}")));
+                None
+            }
+        }
+    });
+    let quota = use_resource(move || async move {
+        if !open() || !cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let mut eval = document::eval(ESTIMATE_JS);
+        eval.recv::<Option<(f64, f64)>>().await.ok().flatten()
+    });
+
+    let run_cleanup = move |cleanup: Cleanup| async move {
+        pending.set(None);
+        let before = now_millis().saturating_sub(age_days() * DAY_MILLIS);
+        let res = match cleanup {
+            Cleanup::DeleteChats => delete_chats_before(before)
+                .await
+                .map(|n| format!("Deleted {n} chats")),
+            Cleanup::StripImages => strip_images_before(before)
+                .await
+                .map(|n| format!("Removed {n} images")),
+        };
+        status.set(Some(res.unwrap_or_else(|e| format!("Cleanup failed: {e}"))));
+        refresh.set(refresh() + 1);
+    };
+    let strip_one = move |id: u32| async move {
+        let res = async { strip_chat_images(&get_storage().await?, id).await }.await;
+        status.set(Some(match res {
+            Ok(n) => format!("Removed {n} images from chat #{id}"),
+            Err(e) => format!("Could not remove images: {e}"),
+        }));
+        refresh.set(refresh() + 1);
+    };
+    let delete_one = move |id: u32| async move {
+        let res = async { get_storage().await?.delete_chat(id).await }.await;
+        status.set(Some(match res {
+            Ok(()) => format!("Deleted chat #{id}"),
+            Err(e) => format!("Could not delete the chat: {e}"),
+        }));
+        refresh.set(refresh() + 1);
+    };
+
+    rsx! {
+        h4 { style: "margin: 0 0 0.5rem 0;", "Storage usage" }
+        if !open() {
+            button { onclick: move |_| open.set(true), "Measure storage" }
+        } else if let Some(Some(usage)) = usage() {
+            {
+                let now = now_millis();
+                let before = now.saturating_sub(age_days() * DAY_MILLIS);
+                let old = usage
+                    .chats
+                    .iter()
+                    .filter(|c| c.saved_at.is_some_and(|t| t < before))
+                    .collect::<Vec<_>>();
+                let old_images = old.iter().filter(|c| c.image_bytes > 0).count();
+                let confirm = pending().map(|cleanup| match cleanup {
+                    Cleanup::DeleteChats => format!("Delete {} chats for good?", old.len()),
+                    Cleanup::StripImages => {
+                        format!("Remove the images from {old_images} chats for good?")
+                    }
+                });
+                let old_count = old.len();
+                rsx! {
+                    UsageSummary { usage: usage.clone(), quota: quota().flatten() }
+                    div { style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; margin: 0.5rem 0; font-size: 0.9em;",
+                        "Chats not saved in the last"
+                        select {
+                            onchange: move |e: Event<FormData>| {
+                                if let Ok(days) = e.value().parse() {
+                                    age_days.set(days);
+                                }
+                                pending.set(None);
+                            },
+                            for days in CLEANUP_AGES {
+                                option {
+                                    value: "{days}",
+                                    selected: days == age_days(),
+                                    "{days} days"
+                                }
+                            }
+                        }
+                        ": {old_count}"
+                        button {
+                            disabled: old_images == 0,
+                            onclick: move |_| pending.set(Some(Cleanup::StripImages)),
+                            "Remove their images"
+                        }
+                        button {
+                            disabled: old_count == 0,
+                            onclick: move |_| pending.set(Some(Cleanup::DeleteChats)),
+                            "Delete them"
+                        }
+                    }
+                    if let (Some(question), Some(cleanup)) = (confirm, pending()) {
+                        div { style: "display: flex; align-items: center; gap: 0.5rem; margin-bottom: 0.5rem; padding: 0.5rem; background: #fff3cd; border-radius: 3px; font-size: 0.9em;",
+                            span { style: "flex: 1;", "{question}" }
+                            button { onclick: move |_| run_cleanup(cleanup), "Confirm" }
+                            button { onclick: move |_| pending.set(None), "Cancel" }
+                        }
+                    }
+                    for chat in usage.chats.iter().take(LISTED_CHATS).cloned() {
+                        div {
+                            key: "{chat.id}",
+                            style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.25rem; border-bottom: 1px solid #eee; font-size: 0.9em;",
+                            Link { to: Route::ChatEl { id: chat.id, anchor: String::new() }, "Chat #{chat.id}" }
+                            span { style: "color: #666;", "{chat.messages} messages" }
+                            span { style: "margin-left: auto;", "{format_bytes(chat.bytes)}" }
+                            if chat.image_bytes > 0 {
+                                span { style: "color: #666;", "(images {format_bytes(chat.image_bytes)})" }
+                            }
+                            span { style: "color: #666; min-width: 6em; text-align: right;",
+                                match chat.saved_at {
+                                    Some(t) => format_age(now.saturating_sub(t)),
+                                    None => "age unknown".to_string(),
+                                }
+                            }
+                            if chat.image_bytes > 0 {
+                                button {
+                                    title: "Replace the chat's images with a note",
+                                    onclick: move |_| strip_one(chat.id),
+                                    "Remove images"
+                                }
+                            }
+                            button { onclick: move |_| delete_one(chat.id), "Delete" }
+                        }
+                    }
+                    if usage.chats.len() > LISTED_CHATS {
+                        div { style: "font-size: 0.9em; color: #666; margin-top: 0.25rem;",
+                            "and {usage.chats.len() - LISTED_CHATS} smaller chats"
+                        }
+                    }
+                }
+            }
+        } else {
+            div { style: "font-size: 0.9em; color: #666;", "Measuring…" }
+        }
+        if let Some(msg) = status() {
+            div { style: "font-size: 0.9em; color: #666; margin-top: 0.25rem;", "{msg}" }
+        }
+    }
+}
+
+/// Total space used, and what the browser allows if known.
+#[component]
+fn UsageSummary(usage: StorageUsage, quota: Option<(f64, f64)>) -> Element {
+    let chat_bytes: u64 = usage.chats.iter().map(|c| c.bytes).sum();
+    let image_bytes: u64 = usage.chats.iter().map(|c| c.image_bytes).sum();
+    rsx! {
+        div { style: "font-size: 0.9em;",
+            "{format_bytes(usage.total_bytes)} in total, {format_bytes(chat_bytes)} of it in {usage.chats.len()} chats"
+            if image_bytes > 0 {
+                " ({format_bytes(image_bytes)} of images)"
+            }
+        }
+        if let Some((used, allowed)) = quota {
+            div { style: "font-size: 0.9em; color: #666;",
+                "The browser stores {format_bytes(used as u64)} for the app, of {format_bytes(allowed as u64)} it allows"
+            }
+        }
+    }
+}
+
+/// A size in bytes, in the largest unit that keeps it at least 1.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// How long ago something happened, roughly.
+fn format_age(millis: u64) -> String {
+    let minutes = millis / 60_000;
+    match minutes {
+        0 => "just now".to_string(),
+        m if m < 60 => format!("{m} min ago"),
+        m if m < 24 * 60 => format!("{} h ago", m / 60),
+        m => format!("{} days ago", m / (24 * 60)),
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::{path::PathBuf, sync::Arc};
+
+use dioxus::prelude::*;
+
+use crate::{
+    AppSettings,
+    mcp::host::MCPHost,
+    storage::{
+        config_dir,
+        location::{LocationKind, PORTABLE_MARKER, Relocation, data_location, relocate},
+    },
+    utils::load_active_profile,
+};
+
+/// Where settings and chats are stored, with a way to move them elsewhere.
+/// In portable mode the location is fixed, next to the executable.
+///
+/// # Arguments
+/// * `on_change` - Called after the location changed and the settings were reloaded
+#[component]
+pub fn StorageLocationSettings(on_change: Callback<(), ()>) -> Element {
+    let mut settings_ctx = use_context::<Signal<Option<AppSettings>>>();
+    let host = use_signal(consume_context::<Arc<MCPHost>>);
+    let mut location = use_signal(data_location);
+    let mut path = use_signal(String::new);
+    let mut how = use_signal(|| Relocation::Copy);
+    let mut working = use_signal(|| false);
+    let mut status: Signal<Option<Result<String, String>>> = use_signal(|| None);
+
+    let change = move |to: PathBuf, how: Relocation| async move {
+        working.set(true);
+        let res = async {
+            let copied = relocate(&to, how).await?;
+            load_active_profile(&mut settings_ctx, &host()).await?;
+            anyhow::Ok(copied)
+        }
+        .await;
+        working.set(false);
+        location.set(data_location());
+        match res {
+            Ok(copied) => {
+                path.set(String::new());
+                status.set(Some(Ok(match how {
+                    Relocation::UseExisting => format!("Now using the data in {}", to.display()),
+                    Relocation::Copy => format!("Copied {copied} files to {}", to.display()),
+                    Relocation::Move => format!("Moved {copied} files to {}", to.display()),
+                })));
+                on_change(());
+            }
+            Err(e) => status.set(Some(Err(format!("Could not change the location: {e:#}")))),
+        }
+    };
+
+    let (dir, kind) = location();
+    let options = [
+        (Relocation::Copy, "Copy the data there"),
+        (Relocation::Move, "Move the data there"),
+        (
+            Relocation::UseExisting,
+            "Use data already there, leaving this data in place",
+        ),
+    ];
+
+    rsx! {
+        h4 { style: "margin: 0 0 0.5rem 0;", "Storage location" }
+        div { style: "font-size: 0.9em; margin-bottom: 0.5rem;",
+            "Settings and chats are stored in "
+            code { "{dir.display()}" }
+            match kind {
+                LocationKind::Default => rsx! { " (default)" },
+                LocationKind::Custom => rsx! { "" },
+                LocationKind::Portable => rsx! { " (portable mode)" },
+            }
+        }
+        if kind == LocationKind::Portable {
+            p { style: "margin: 0; font-size: 0.9em; color: #666;",
+                "The '{PORTABLE_MARKER}' file next to the app keeps its data beside it. "
+                "Remove the file to store data elsewhere."
+            }
+        } else {
+            div { style: "display: flex; gap: 0.5rem;",
+                input {
+                    style: "flex: 1; padding: 0.5rem; border: 1px solid #ddd; border-radius: 3px; font-family: monospace;",
+                    placeholder: "Full path of the new location",
+                    value: path(),
+                    disabled: working(),
+                    oninput: move |e| path.set(e.value()),
+                }
+                if cfg!(any(target_os = "windows", target_os = "macos", target_os = "linux")) {
+                    button {
+                        disabled: working(),
+                        onclick: move |_| async move {
+                            if let Some(dir) = pick_folder().await {
+                                path.set(dir.display().to_string());
+                            }
+                        },
+                        "Browse…"
+                    }
+                }
+            }
+            div { style: "display: flex; flex-direction: column; gap: 0.25rem; margin: 0.5rem 0; font-size: 0.9em;",
+                for (option , label) in options {
+                    label { key: "{label}",
+                        input {
+                            r#type: "radio",
+                            name: "relocation",
+                            checked: how() == option,
+                            oninput: move |_| how.set(option),
+                        }
+                        " {label}"
+                    }
+                }
+            }
+            div { style: "display: flex; gap: 0.5rem;",
+                button {
+                    disabled: working() || path().trim().is_empty(),
+                    onclick: move |_| async move {
+                        let to = PathBuf::from(path().trim());
+                        change(to, how()).await;
+                    },
+                    if working() { "Working…" } else { "Change location" }
+                }
+                if kind == LocationKind::Custom {
+                    button {
+                        disabled: working(),
+                        title: "Store data in the config directory again",
+                        onclick: move |_| async move {
+                            change(config_dir(), how()).await;
+                        },
+                        "Use the default location"
+                    }
+                }
+            }
+        }
+        match status() {
+            Some(Ok(msg)) => rsx! {
+                div { style: "color: #28a745; font-size: 0.9em; margin-top: 0.25rem;", "{msg}" }
+            },
+            Some(Err(msg)) => rsx! {
+                div { style: "color: #dc3545; font-size: 0.9em; margin-top: 0.25rem;", "{msg}" }
+            },
+            None => rsx! {},
+        }
+    }
+}
+
+/// Asks the user for a directory.
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+async fn pick_folder() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .pick_folder()
+        .await
+        .map(|f| f.path().to_path_buf())
+}
+
+/// There is no folder dialog on this platform.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+async fn pick_folder() -> Option<PathBuf> {
+    None
+}
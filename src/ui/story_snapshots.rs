@@ -0,0 +1,129 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::{logger::tracing::warn, prelude::*};
+use serde_json::Value;
+
+use crate::{
+    app_settings::StorySnapshot,
+    jobs,
+    snapshots::{delete_snapshot, list_snapshots, local_time, snapshot_words, take_snapshot},
+};
+
+/// Snapshot browser of a story chat, for the story side panel: takes named
+/// snapshots of the story, and lists them for restoring or deleting.
+///
+/// # Arguments
+/// * `chat_id` - ID of the story chat
+/// * `story` - The story as saved; the list is reloaded when it changes
+/// * `on_restore` - Called with the snapshot to restore
+#[component]
+pub fn StorySnapshots(
+    chat_id: u32,
+    story: Value,
+    on_restore: Callback<StorySnapshot, ()>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let mut name = use_signal(String::new);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let story_to_save = story.clone();
+    let mut snapshots = use_resource(use_reactive!(|(chat_id, story)| async move {
+        // Reload after saves
+        let _ = story;
+        list_snapshots(chat_id).await.unwrap_or_else(|e| {
+            warn!("Could not load story snapshots: {e:?}");
+            vec![]
+        })
+    }));
+    // The day's automatic snapshot is taken by a background job after the save
+    use_future(move || async move {
+        let mut changes = jobs::subscribe();
+        while changes.changed().await.is_ok() {
+            snapshots.restart();
+        }
+    });
+    let list = snapshots().unwrap_or_default();
+    let next_draft = list.iter().filter(|s| !s.auto).count() + 1;
+
+    let save = move |_| {
+        let story = story_to_save.clone();
+        async move {
+            let n = name.read().trim().to_string();
+            let n = if n.is_empty() {
+                format!("Draft {next_draft}")
+            } else {
+                n
+            };
+            match take_snapshot(chat_id, &n, &story, false).await {
+                Ok(_) => {
+                    name.set(String::new());
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Could not save the snapshot: {e}"))),
+            }
+            snapshots.restart();
+        }
+    };
+
+    rsx! {
+        div { style: "margin: 0.5em 0; font-size: 0.9em; border-bottom: 1px solid #dee2e6; padding-bottom: 0.5em;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "Snapshots ({list.len()})"
+            }
+            if open() {
+                div { style: "display: flex; gap: 0.5em; margin: 0.5em 0;",
+                    input {
+                        style: "flex: 1;",
+                        placeholder: "Draft {next_draft}",
+                        value: name,
+                        oninput: move |e: Event<FormData>| name.set(e.value()),
+                    }
+                    button { onclick: save, "Save snapshot" }
+                }
+                if let Some(e) = error() {
+                    div { style: "color: #dc3545;", "{e}" }
+                }
+                if list.is_empty() {
+                    div { style: "color: #666;",
+                        "No snapshots yet. One is also taken automatically on each day of writing."
+                    }
+                }
+                for snapshot in list {
+                    div {
+                        key: "{snapshot.id}",
+                        style: "display: flex; align-items: center; gap: 0.5em; padding: 0.25em 0;",
+                        div { style: "flex: 1; min-width: 0;",
+                            div { style: if snapshot.auto { "color: #666;" } else { "" },
+                                "{snapshot.name}"
+                            }
+                            div { style: "font-size: 0.85em; color: #888;",
+                                "{local_time(snapshot.id)} · {snapshot_words(&snapshot)} words"
+                            }
+                        }
+                        button {
+                            title: "Replace the story with this version",
+                            onclick: {
+                                let snapshot = snapshot.clone();
+                                move |_| on_restore(snapshot.clone())
+                            },
+                            "Restore"
+                        }
+                        button {
+                            style: "border: none; background: none; cursor: pointer;",
+                            title: "Delete this snapshot",
+                            onclick: move |_| async move {
+                                if let Err(e) = delete_snapshot(chat_id, snapshot.id).await {
+                                    error.set(Some(format!("Could not delete the snapshot: {e}")));
+                                }
+                                snapshots.restart();
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
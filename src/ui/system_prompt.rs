@@ -0,0 +1,90 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+/// Viewer and editor for a chat's system prompt, shown at the top of the transcript.
+///
+/// The prompt is shown collapsed; editing it replaces the chat's system message.
+/// Replies already in the chat were written with the old prompt, which the
+/// editor points out.
+///
+/// # Arguments
+/// * `prompt` - The chat's current system prompt
+/// * `default_prompt` - Prompt of the chat's toolset, offered as a reset
+/// * `disabled` - Whether editing is disabled, e.g. while a reply is streaming
+/// * `on_save` - Called with the new prompt
+#[component]
+pub fn SystemPromptEditor(
+    prompt: String,
+    default_prompt: String,
+    disabled: bool,
+    on_save: Callback<String, ()>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    // Text being edited, while the editor is open for editing
+    let mut draft: Signal<Option<String>> = use_signal(|| None);
+
+    let customized = prompt != default_prompt;
+    let body = match draft() {
+        Some(text) => {
+            let changed = text != prompt;
+            let is_default = text == default_prompt;
+            rsx! {
+                textarea {
+                    style: "width: 100%; box-sizing: border-box; height: 12em; font-family: monospace;",
+                    value: "{text}",
+                    oninput: move |e: Event<FormData>| draft.set(Some(e.value())),
+                }
+                div { style: "color: #856404; margin: 0.25em 0;",
+                    "⚠ Changes affect future turns only; earlier replies were written with the previous prompt."
+                }
+                div { style: "display: flex; gap: 0.5em;",
+                    button {
+                        disabled: disabled || !changed,
+                        onclick: move |_| {
+                            if let Some(text) = draft.take() {
+                                on_save(text);
+                            }
+                        },
+                        "Save"
+                    }
+                    button { onclick: move |_| draft.set(None), "Cancel" }
+                    button {
+                        disabled: is_default,
+                        title: "Replace the text with the toolset's default prompt",
+                        onclick: move |_| draft.set(Some(default_prompt.clone())),
+                        "Reset to default"
+                    }
+                }
+            }
+        }
+        None => {
+            let el = crate::md2rsx::markdown_to_rsx(&prompt)?;
+            rsx! {
+                div { {el} }
+                button {
+                    disabled,
+                    onclick: move |_| draft.set(Some(prompt.clone())),
+                    "Edit"
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "message system-message",
+            div {
+                style: "cursor: pointer; font-weight: 600; opacity: 0.8;",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "System prompt"
+                if customized {
+                    span { style: "font-weight: normal;", " (edited)" }
+                }
+            }
+            if open() {
+                div { style: "margin-top: 0.5em;", {body} }
+            }
+        }
+    }
+}
@@ -0,0 +1,384 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Rendering of tool results: JSON as a foldable tree, diffs with changed lines
+//! highlighted, text as Markdown, and buttons to open linked resources and
+//! download binary data (see [`crate::mcp::content`]).
+
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    export::{save_download, save_file_as},
+    mcp::{
+        content::{ContentMarker, ResourceContents, find_markers, stored_blob},
+        host::MCPHost,
+        tool_ref::ToolRef,
+    },
+    ui::diff_view::{DiffView, is_unified_diff},
+};
+
+/// Nesting depth down to which JSON objects and arrays start expanded.
+const JSON_OPEN_DEPTH: usize = 2;
+
+/// Body of a tool result.
+///
+/// # Arguments
+/// * `content` - Content of the tool message
+#[component]
+pub fn ToolResultBody(content: String) -> Element {
+    if let Ok(value @ (Value::Object(_) | Value::Array(_))) =
+        serde_json::from_str::<Value>(content.trim())
+    {
+        return rsx! {
+            div { style: "font-family: 'Fira Code', 'JetBrains Mono', 'Courier New', monospace; font-size: 0.85em;",
+                JsonNode { name: None, value, depth: 0 }
+            }
+        };
+    }
+    if is_unified_diff(&content) {
+        return rsx! {
+            DiffView { diff: content }
+        };
+    }
+    let el = crate::md2rsx::markdown_to_rsx(&content)?;
+    let markers = find_markers(&content);
+    rsx! {
+        {el}
+        for (i , marker) in markers.into_iter().enumerate() {
+            match marker {
+                ContentMarker::Resource { server_id, uri, mime_type } => rsx! {
+                    ResourceLink { key: "{i}", server_id, uri, mime_type }
+                },
+                ContentMarker::Binary { id, mime_type, size } => rsx! {
+                    BinaryDownload { key: "{i}", id, mime_type, size }
+                },
+            }
+        }
+    }
+}
+
+/// A JSON value, with objects and arrays foldable.
+#[component]
+fn JsonNode(name: Option<String>, value: Value, depth: usize) -> Element {
+    let label = name.map(|n| format!("{n}: ")).unwrap_or_default();
+    let (children, summary): (Vec<(String, Value)>, String) = match value {
+        Value::Object(map) => {
+            let summary = format!("{{…}} {} keys", map.len());
+            (map.into_iter().collect(), summary)
+        }
+        Value::Array(items) => {
+            let summary = format!("[…] {} items", items.len());
+            let children = items
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect();
+            (children, summary)
+        }
+        scalar => {
+            return rsx! {
+                div { style: "white-space: pre-wrap; word-break: break-word;", "{label}{scalar}" }
+            };
+        }
+    };
+    rsx! {
+        details { open: depth < JSON_OPEN_DEPTH,
+            summary { style: "cursor: pointer;", "{label}"
+                span { style: "opacity: 0.6;", "{summary}" }
+            }
+            div { style: "padding-left: 1.2em; border-left: 1px solid rgba(255, 255, 255, 0.2);",
+                for (key , child) in children {
+                    JsonNode { key: "{key}", name: Some(key.clone()), value: child, depth: depth + 1 }
+                }
+            }
+        }
+    }
+}
+
+/// A resource linked in a tool result, read from its server on request.
+#[component]
+fn ResourceLink(server_id: String, uri: String, mime_type: Option<String>) -> Element {
+    let mut contents: Signal<Option<Result<Vec<ResourceContents>, String>>> = use_signal(|| None);
+    let mut loading = use_signal(|| false);
+    let host = use_context::<Arc<MCPHost>>();
+    let open = {
+        let (server_id, uri) = (server_id.clone(), uri.clone());
+        move |_| {
+            let (server_id, uri, host) = (server_id.clone(), uri.clone(), host.clone());
+            async move {
+                loading.set(true);
+                let res = host.read_resource(&server_id, &uri).await;
+                contents.set(Some(res.map_err(|e| e.to_string())));
+                loading.set(false);
+            }
+        }
+    };
+    let kind = mime_type.map(|m| format!(" ({m})")).unwrap_or_default();
+
+    rsx! {
+        div { class: "tool-resource", style: "margin-top: 0.5em;",
+            span { "📎 {uri}{kind} " }
+            button { disabled: loading(), onclick: open, "Open" }
+            match contents() {
+                Some(Ok(list)) => rsx! {
+                    for (i , c) in list.into_iter().enumerate() {
+                        ResourceContentsEl { key: "{i}", contents: c }
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    div { style: "color: #dc3545;", "Could not read the resource: {e}" }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Contents of a resource read from a server.
+#[component]
+fn ResourceContentsEl(contents: ResourceContents) -> Element {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    if let Some(text) = contents.text {
+        return rsx! {
+            div { style: "margin-top: 0.5em;", ToolResultBody { content: text } }
+        };
+    }
+    let Some(bytes) = contents.blob.and_then(|b| STANDARD.decode(b).ok()) else {
+        return rsx! {
+            div { "The resource is empty." }
+        };
+    };
+    let mime_type = contents
+        .mime_type
+        .unwrap_or_else(|| "application/octet-stream".into());
+    let name = contents
+        .uri
+        .rsplit('/')
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("resource")
+        .to_string();
+    rsx! {
+        DownloadButton { file_name: name, mime_type, bytes: Some(bytes) }
+    }
+}
+
+/// Binary data returned by a tool, offered as a download.
+#[component]
+fn BinaryDownload(id: String, mime_type: String, size: usize) -> Element {
+    let file_name = format!("{id}.{}", extension(&mime_type));
+    rsx! {
+        div { class: "tool-binary", style: "margin-top: 0.5em;",
+            span { "📦 {mime_type}, {size} bytes " }
+            DownloadButton { file_name, mime_type, bytes: stored_blob(&id) }
+        }
+    }
+}
+
+/// Button saving bytes to the downloads folder, reporting where they went.
+///
+/// # Arguments
+/// * `bytes` - The data, or `None` if it is no longer available
+#[component]
+fn DownloadButton(file_name: String, mime_type: String, bytes: Option<Vec<u8>>) -> Element {
+    let mut status: Signal<Option<String>> = use_signal(|| None);
+    let Some(bytes) = bytes else {
+        return rsx! {
+            span { style: "opacity: 0.7;", "(no longer available for download)" }
+        };
+    };
+    let save = move |_| {
+        let (file_name, mime_type, bytes) = (file_name.clone(), mime_type.clone(), bytes.clone());
+        async move {
+            let message = match save_download(&file_name, &mime_type, &bytes).await {
+                Ok(path) => format!("Saved to {path}"),
+                Err(e) => format!("Could not save: {e}"),
+            };
+            status.set(Some(message));
+        }
+    };
+    rsx! {
+        button { onclick: save, "Download" }
+        if let Some(s) = status() {
+            span { style: "margin-left: 0.5em; opacity: 0.8;", "{s}" }
+        }
+    }
+}
+
+/// Button saving a tool result as a file, named after the tool and its content.
+///
+/// # Arguments
+/// * `content` - Content of the tool message
+/// * `tool_name` - Name of the tool called, as the model called it
+/// * `arguments` - Arguments of the call, as JSON
+#[component]
+pub fn SaveResultButton(
+    content: String,
+    tool_name: Option<String>,
+    arguments: Option<String>,
+) -> Element {
+    let mut status: Signal<Option<String>> = use_signal(|| None);
+    let save = move |e: Event<MouseData>| {
+        e.stop_propagation();
+        let file = result_file(&content, tool_name.as_deref(), arguments.as_deref());
+        async move {
+            let message = match save_file_as(&file.name, file.mime_type, file.text.as_bytes()).await
+            {
+                Ok(Some(path)) => Some(format!("Saved to {path}")),
+                Ok(None) => None,
+                Err(e) => Some(format!("Could not save: {e}")),
+            };
+            status.set(message);
+        }
+    };
+    rsx! {
+        button {
+            style: "margin-left: auto; font-size: 0.85em; font-weight: normal;",
+            title: "Save this result as a file",
+            onclick: save,
+            "Save as file"
+        }
+        if let Some(s) = status() {
+            span { style: "font-size: 0.8em; font-weight: normal; opacity: 0.8;", "{s}" }
+        }
+    }
+}
+
+/// A tool result prepared for saving.
+struct ResultFile {
+    name: String,
+    mime_type: &'static str,
+    text: String,
+}
+
+/// Infers a file for a tool result. A path in the call's arguments gives the
+/// name; otherwise the tool's name does, with an extension from the content.
+/// A result that is a single fenced code block is saved as the code alone.
+fn result_file(content: &str, tool_name: Option<&str>, arguments: Option<&str>) -> ResultFile {
+    let trimmed = content.trim();
+    let (text, ext) =
+        if serde_json::from_str::<Value>(trimmed).is_ok_and(|v| v.is_object() || v.is_array()) {
+            (content.to_string(), "json")
+        } else if let Some((lang, code)) = single_code_block(trimmed) {
+            (code.to_string(), code_extension(lang))
+        } else if trimmed.starts_with("<!DOCTYPE html") || trimmed.starts_with("<html") {
+            (content.to_string(), "html")
+        } else if trimmed.starts_with("<?xml") {
+            (content.to_string(), "xml")
+        } else if trimmed
+            .lines()
+            .any(|l| l.starts_with("# ") || l.starts_with("## "))
+        {
+            (content.to_string(), "md")
+        } else {
+            (content.to_string(), "txt")
+        };
+
+    let from_arguments = arguments
+        .and_then(|a| serde_json::from_str::<Value>(a).ok())
+        .and_then(|args| {
+            ["path", "file_path", "filename", "file_name", "file"]
+                .iter()
+                .find_map(|k| args.get(k)?.as_str().map(str::to_string))
+        })
+        .and_then(|path| {
+            let name = path.rsplit(['/', '\\']).next()?.to_string();
+            name.contains('.').then_some(name)
+        });
+    let name = from_arguments.unwrap_or_else(|| {
+        let tool = tool_name
+            .map(|t| ToolRef::decode(t).map_or(t.to_string(), |r| r.tool))
+            .unwrap_or_default();
+        // "export_story" saves as "story", "get_weather" as "weather"
+        let base = [
+            "get_",
+            "read_",
+            "export_",
+            "fetch_",
+            "generate_",
+            "create_",
+            "write_",
+        ]
+        .iter()
+        .find_map(|p| tool.strip_prefix(p))
+        .unwrap_or(&tool);
+        let base: String = base
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let base = base.trim_matches('_');
+        let base = if base.is_empty() { "tool-result" } else { base };
+        format!("{base}.{ext}")
+    });
+    let mime_type = match name.rsplit('.').next().unwrap_or_default() {
+        "json" => "application/json",
+        "html" => "text/html",
+        "xml" => "application/xml",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        _ => "text/plain",
+    };
+    ResultFile {
+        name,
+        mime_type,
+        text,
+    }
+}
+
+/// Splits a text that is one fenced code block into its language and code.
+fn single_code_block(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("```")?;
+    let (lang, body) = rest.split_once('\n')?;
+    let code = body.strip_suffix("```")?;
+    // Another fence inside means several blocks
+    (!code.contains("\n```")).then_some((lang.trim(), code))
+}
+
+/// Picks a file extension for the language of a code block.
+fn code_extension(lang: &str) -> &str {
+    match lang.to_lowercase().as_str() {
+        "" | "text" | "plain" => "txt",
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "markdown" => "md",
+        "bash" | "shell" | "sh" => "sh",
+        "yaml" | "yml" => "yaml",
+        "c++" | "cpp" => "cpp",
+        "csharp" | "c#" => "cs",
+        "kotlin" => "kt",
+        "ruby" => "rb",
+        "golang" | "go" => "go",
+        _ if lang.chars().all(|c| c.is_ascii_alphanumeric()) => lang,
+        _ => "txt",
+    }
+}
+
+/// Picks a file extension for a MIME type.
+fn extension(mime_type: &str) -> &str {
+    match mime_type {
+        "application/pdf" => "pdf",
+        "application/json" => "json",
+        "application/zip" => "zip",
+        "text/plain" => "txt",
+        "text/csv" => "csv",
+        "audio/mpeg" => "mp3",
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        _ => mime_type
+            .split_once('/')
+            .map(|(_, sub)| sub)
+            .filter(|sub| sub.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("bin"),
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright © 2025 Nipun Kumar
+
+//! Input schemas of tools shown as a table of their parameters.
+//!
+//! Nested objects are flattened into dotted names, with `[]` for the items of
+//! arrays, so `filters[].field` is the `field` of each of the `filters`.
+
+use dioxus::prelude::*;
+use serde_json::Value;
+
+/// Deepest nesting of objects listed; deeper parameters are in the JSON.
+const MAX_DEPTH: usize = 4;
+
+/// A parameter of a tool, as listed in the table.
+#[derive(Debug, Clone, PartialEq)]
+struct SchemaParam {
+    /// Path of the parameter, e.g. `options.limit`
+    name: String,
+    /// Type, or the values allowed
+    type_name: String,
+    /// Whether the object it belongs to requires it
+    required: bool,
+    description: Option<String>,
+    /// Default value, as JSON
+    default: Option<String>,
+}
+
+/// Describes the type of a schema: its allowed values if it lists them, else
+/// its type, with the type of the items of arrays.
+fn type_name(schema: &Value) -> String {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            return variants
+                .iter()
+                .map(type_name)
+                .collect::<Vec<_>>()
+                .join(" | ");
+        }
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    if types.is_empty() {
+        return "any".to_string();
+    }
+    types
+        .into_iter()
+        .map(|t| match (t, schema.get("items")) {
+            ("array", Some(items)) => format!("array of {}", type_name(items)),
+            _ => t.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Lists the properties of an object schema, and those of objects nested in
+/// them, the required ones first.
+///
+/// # Arguments
+/// * `schema` - The object schema
+/// * `prefix` - Path of the object, empty at the top
+/// * `depth` - How deeply the object is nested
+/// * `params` - Where the parameters are added
+fn collect_params(schema: &Value, prefix: &str, depth: usize, params: &mut Vec<SchemaParam>) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let mut properties: Vec<_> = properties.iter().collect();
+    properties.sort_by_key(|(name, _)| !required.contains(&name.as_str()));
+    for (name, property) in properties {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        params.push(SchemaParam {
+            name: path.clone(),
+            type_name: type_name(property),
+            required: required.contains(&name.as_str()),
+            description: property
+                .get("description")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            default: property.get("default").map(Value::to_string),
+        });
+        if depth >= MAX_DEPTH {
+            continue;
+        }
+        collect_params(property, &path, depth + 1, params);
+        if let Some(items) = property.get("items") {
+            collect_params(items, &format!("{path}[]"), depth + 1, params);
+        }
+    }
+}
+
+/// Table of a tool's parameters: name, type, whether it is required and its
+/// description, with the raw JSON schema one click away.
+///
+/// # Arguments
+/// * `schema` - The tool's input schema
+#[component]
+pub fn SchemaTable(schema: Value) -> Element {
+    let mut show_json = use_signal(|| false);
+    let mut params = vec![];
+    collect_params(&schema, "", 0, &mut params);
+    let json = serde_json::to_string_pretty(&schema).unwrap_or_else(|_| "Invalid JSON".into());
+    let cell = "padding: 0.25rem 0.5rem; border-bottom: 1px solid #e0e0e0; vertical-align: top;";
+
+    rsx! {
+        if params.is_empty() {
+            div { style: "font-size: 0.85rem; color: #666; font-style: italic;", "Takes no arguments" }
+        } else {
+            table { style: "width: 100%; border-collapse: collapse; font-size: 0.85rem;",
+                thead {
+                    tr { style: "text-align: left; color: #34495e;",
+                        th { style: cell, "Parameter" }
+                        th { style: cell, "Type" }
+                        th { style: cell, "Description" }
+                    }
+                }
+                tbody {
+                    for param in params {
+                        tr { key: "{param.name}",
+                            td { style: "{cell} font-family: monospace; white-space: nowrap;",
+                                "{param.name}"
+                                if param.required {
+                                    span {
+                                        style: "color: #dc3545;",
+                                        title: "Required",
+                                        "*"
+                                    }
+                                }
+                            }
+                            td { style: "{cell} font-family: monospace; color: #8e44ad;",
+                                "{param.type_name}"
+                            }
+                            td { style: cell,
+                                if let Some(description) = &param.description {
+                                    "{description}"
+                                }
+                                if let Some(default) = &param.default {
+                                    div { style: "color: #7f8c8d;", "Default: {default}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        button {
+            style: "margin-top: 0.5rem; font-size: 0.75rem; cursor: pointer;",
+            onclick: move |_| show_json.toggle(),
+            if show_json() {
+                "Hide JSON schema"
+            } else {
+                "Show JSON schema"
+            }
+        }
+        if show_json() {
+            pre { style: "
+                    background: #2c3e50;
+                    color: #ecf0f1;
+                    padding: 1rem;
+                    border-radius: 4px;
+                    font-size: 0.8rem;
+                    overflow-x: auto;
+                    margin: 0.5rem 0 0 0;
+                    white-space: pre-wrap;
+                ",
+                "{json}"
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::core::TurnTiming;
+
+/// Estimated generation speed, shown under the response streaming in.
+///
+/// # Arguments
+/// * `rate` - Estimated tokens per second
+#[component]
+pub fn ThroughputLine(rate: f64) -> Element {
+    rsx! {
+        div {
+            style: "margin-top: 0.25em; font-size: 0.8em; color: #888;",
+            title: "Estimated from the text received so far",
+            "~{rate:.0} tokens/s"
+        }
+    }
+}
+
+/// Footer after a turn: how long it took, how long until the response
+/// started, the generation speed and the time spent running tools, with the
+/// tools of each round on hover.
+///
+/// # Arguments
+/// * `timing` - Timing of the turn
+#[component]
+pub fn TurnTimingLine(timing: TurnTiming) -> Element {
+    let mut parts = vec![format!("Turn {}", seconds(timing.total))];
+    if let Some(first) = timing.first_token {
+        parts.push(format!("first token {}", seconds(first)));
+    }
+    if let Some(rate) = timing.tokens_per_second() {
+        parts.push(format!("~{rate:.0} tokens/s"));
+    }
+    if !timing.tool_rounds.is_empty() {
+        parts.push(format!("tools {}", seconds(timing.tools())));
+    }
+    let rounds = timing
+        .tool_rounds
+        .iter()
+        .enumerate()
+        .map(|(i, (names, millis))| {
+            format!(
+                "Round {}: {} ({})",
+                i + 1,
+                names.join(", "),
+                seconds(*millis)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let summary = parts.join(" · ");
+
+    rsx! {
+        div {
+            style: "margin: 0 1em 0.5em 1em; font-size: 0.8em; color: #888;",
+            title: "{rounds}",
+            "{summary}"
+        }
+    }
+}
+
+/// A duration in milliseconds as seconds, such as "1.2s".
+fn seconds(millis: u64) -> String {
+    format!("{:.1}s", millis as f64 / 1000.0)
+}
@@ -0,0 +1,80 @@
+// Copyright © 2025 Nipun Kumar
+
+use dioxus::prelude::*;
+
+use crate::storage::encryption::{EncryptionInfo, unlock};
+
+/// Screen asking for the passphrase of encrypted storage at startup, shown
+/// before anything is loaded from it.
+///
+/// # Arguments
+/// * `info` - The stored salt and passphrase check
+/// * `on_unlock` - Called once the right passphrase was entered
+#[component]
+pub fn UnlockScreen(info: EncryptionInfo, on_unlock: Callback<(), ()>) -> Element {
+    let info = use_signal(|| info);
+    let mut passphrase = use_signal(String::new);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let mut working = use_signal(|| false);
+
+    let mut submit = move || {
+        if working() || passphrase().is_empty() {
+            return;
+        }
+        working.set(true);
+        spawn(async move {
+            // Deriving the key takes a moment; let the button show it first
+            crate::core::sleep(std::time::Duration::from_millis(20)).await;
+            match unlock(&info.read(), &passphrase()) {
+                Ok(()) => {
+                    passphrase.set(String::new());
+                    on_unlock(());
+                }
+                Err(e) => error.set(Some(e.to_string())),
+            }
+            working.set(false);
+        });
+    };
+
+    rsx! {
+        div { style: "
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            justify-content: center;
+            height: 100vh;
+            gap: 0.5em;
+            ",
+            h3 { "Storage is encrypted" }
+            p { style: "margin: 0; font-size: 0.9em; color: #666;",
+                "Enter the passphrase to open your settings and chats."
+            }
+            div { style: "display: flex; gap: 0.5rem;",
+                input {
+                    r#type: "password",
+                    autofocus: true,
+                    style: "padding: 0.5rem; border: 1px solid #ddd; border-radius: 3px; min-width: 18em;",
+                    placeholder: "Passphrase",
+                    value: passphrase(),
+                    oninput: move |e| {
+                        passphrase.set(e.value());
+                        error.set(None);
+                    },
+                    onkeydown: move |e| {
+                        if e.key() == Key::Enter {
+                            submit();
+                        }
+                    },
+                }
+                button {
+                    disabled: working() || passphrase().is_empty(),
+                    onclick: move |_| submit(),
+                    if working() { "Unlocking…" } else { "Unlock" }
+                }
+            }
+            if let Some(e) = error() {
+                div { style: "color: #dc3545; font-size: 0.9em;", "{e}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright © 2025 Nipun Kumar
+
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+
+use crate::{
+    core::{is_variable_name, placeholders},
+    llm::{ContentPart, Message},
+};
+
+/// Names of the placeholders in a conversation's system prompt and user
+/// messages, in order of appearance, each once.
+pub fn conversation_placeholders(messages: &[Message]) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    let texts = messages.iter().flat_map(|m| match m {
+        Message::System { content } => vec![content.as_str()],
+        Message::User { content } => content
+            .iter()
+            .filter_map(|p| match p {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    });
+    for text in texts {
+        for name in placeholders(text) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Collapsible editor of a chat's variables, kept at the top of the chat with
+/// the documents and pinned messages. Values are filled into `{{name}}`
+/// placeholders of the system prompt and the user's messages when they are
+/// sent. Placeholders without a value are listed so they can be given one.
+///
+/// # Arguments
+/// * `variables` - Values of the chat's variables, by name
+/// * `placeholders` - Names of the placeholders in the conversation
+/// * `on_change` - Called with the new values
+#[component]
+pub fn VariablesPanel(
+    variables: BTreeMap<String, String>,
+    placeholders: Vec<String>,
+    on_change: EventHandler<BTreeMap<String, String>>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let mut new_name = use_signal(String::new);
+    let missing: Vec<String> = placeholders
+        .into_iter()
+        .filter(|p| !variables.contains_key(p))
+        .collect();
+    if variables.is_empty() && missing.is_empty() {
+        return rsx! {};
+    }
+    let name_error = {
+        let name = new_name.read();
+        let name = name.trim();
+        (!name.is_empty() && !is_variable_name(name)).then_some("Use letters, digits and _ only")
+    };
+
+    let vars = variables.clone();
+    let set = move |name: String, value: String| {
+        let mut vars = vars.clone();
+        vars.insert(name, value);
+        on_change(vars);
+    };
+    let vars = variables.clone();
+    let remove = move |name: String| {
+        let mut vars = vars.clone();
+        vars.remove(&name);
+        on_change(vars);
+    };
+    let add = {
+        let set = set.clone();
+        move || {
+            let mut new_name = new_name;
+            let name = new_name.read().trim().to_string();
+            if is_variable_name(&name) {
+                set(name, String::new());
+                new_name.set(String::new());
+            }
+        }
+    };
+    let title = if missing.is_empty() {
+        format!("Variables ({})", variables.len())
+    } else {
+        format!(
+            "Variables ({}, {} without a value)",
+            variables.len(),
+            missing.len()
+        )
+    };
+
+    rsx! {
+        div { style: "margin: 0 1em; padding: 0.25em 0.5em; font-size: 0.9em; border-bottom: 1px solid #dee2e6;",
+            button {
+                style: "border: none; background: none; cursor: pointer; padding: 0; font-weight: 600;",
+                title: "Values filled into {{{{name}}}} placeholders of the system prompt and your messages when they are sent",
+                onclick: move |_| open.toggle(),
+                if open() { "▾ " } else { "▸ " }
+                "{title}"
+            }
+            if open() {
+                for (name , value) in variables.into_iter() {
+                    div {
+                        key: "{name}",
+                        style: "display: flex; align-items: center; gap: 0.5em; padding: 0.15em 0;",
+                        code { style: "min-width: 8em;", "{{{{{name}}}}}" }
+                        input {
+                            style: "flex: 1;",
+                            value: "{value}",
+                            onchange: {
+                                let set = set.clone();
+                                let name = name.clone();
+                                move |e: Event<FormData>| set(name.clone(), e.value())
+                            },
+                        }
+                        button {
+                            title: "Remove the variable; its placeholders are sent as they are",
+                            onclick: {
+                                let remove = remove.clone();
+                                let name = name.clone();
+                                move |_| remove(name.clone())
+                            },
+                            "×"
+                        }
+                    }
+                }
+                for name in missing {
+                    div {
+                        key: "missing-{name}",
+                        style: "display: flex; align-items: center; gap: 0.5em; padding: 0.15em 0; color: #856404;",
+                        code { style: "min-width: 8em;", "{{{{{name}}}}}" }
+                        input {
+                            style: "flex: 1;",
+                            placeholder: "No value; sent as it is",
+                            onchange: {
+                                let set = set.clone();
+                                let name = name.clone();
+                                move |e: Event<FormData>| set(name.clone(), e.value())
+                            },
+                        }
+                    }
+                }
+                div { style: "display: flex; align-items: center; gap: 0.5em; padding: 0.25em 0;",
+                    input {
+                        placeholder: "New variable, e.g. audience",
+                        value: "{new_name}",
+                        oninput: move |e| new_name.set(e.value()),
+                        onkeydown: {
+                            let add = add.clone();
+                            move |e: Event<KeyboardData>| {
+                                if e.key() == Key::Enter {
+                                    add();
+                                }
+                            }
+                        },
+                    }
+                    button {
+                        disabled: name_error.is_some() || new_name.read().trim().is_empty(),
+                        onclick: move |_| add(),
+                        "Add"
+                    }
+                    if let Some(error) = name_error {
+                        span { style: "color: #dc3545;", "{error}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,185 +1,262 @@
 // Copyright © 2025 Nipun Kumar
 
-//! Utility functions for handling tool calls and message conversion.
+//! Utility functions connecting the core tool loop to the UI.
 //!
-//! This module provides helper functions for converting between MCP tool descriptors
-//! and LLM tool objects, as well as executing tool calls and formatting their results
-//! for inclusion in chat conversations.
-
-use std::sync::Arc;
-
-use serde_json::Value;
-
-use crate::app_settings::Chat;
-use crate::llm::Function;
-use crate::llm::Message;
-use crate::llm::Tool;
+//! This module mirrors the state of a [`ChatSession`] from [`crate::core`] in
+//! the chat UI's signals, and handles saving chats to storage.
+
+use crate::app_settings::{
+    AppSettings, Chat, ModelChoice, ProviderSettings, ServerGroup, with_overrides,
+};
+use crate::core::{CancelHandle, ChatSession, MAX_TOOL_FAILURES, SessionEvent, TurnTiming};
+use crate::jobs::spawn_job;
 use crate::llm::ToolCallDelta;
-use crate::llm::{FunctionDelta, LlmClient};
-use crate::mcp::ToolDescriptor;
+use crate::llm::{LlmClient, ProviderCheck};
 use crate::mcp::host::MCPHost;
 use crate::storage::{Storage, get_storage};
 use crate::toolset::Toolset;
-use dioxus::logger::tracing::{info, warn};
+use crate::ui::send_status::SendStatus;
+use dioxus::core::spawn_forever;
+use dioxus::logger::tracing::warn;
 use dioxus::prelude::*;
 use dioxus_router::Navigator;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-/// Converts MCP tool descriptors to LLM tool objects.
+/// How long the MCP server states may go unsaved after a chat is saved
+const SERVER_STATES_SAVE_DELAY: Duration = Duration::from_secs(30);
+
+/// Whether a save of the MCP server states is waiting out its delay
+static SERVER_STATES_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Loads the active profile's settings and MCP server states into the app.
 ///
-/// This function transforms tool descriptors from MCP servers into the format
-/// expected by LLM APIs. Each tool is prefixed with its server ID to ensure
-/// unique naming and proper routing when the tool is called.
+/// Server states are restored before the settings are set, so servers spawned
+/// from the new settings pick up their saved state.
 ///
 /// # Arguments
-/// * `tools` - Vector of tool descriptors from MCP servers
+/// * `settings` - The app-wide settings signal
+/// * `host` - Global MCP host
 ///
 /// # Returns
-/// Vector of `Tool` objects formatted for LLM API requests
-pub fn tools_to_message_objects(tools: Vec<ToolDescriptor>) -> Vec<Tool> {
-    tools
-        .iter()
-        .map(move |t| {
-            let t = t.clone();
-            Tool {
-                r#type: "function".into(),
-                function: Function {
-                    // Prefix tool name with server ID for unique identification
-                    name: format!("{}--{}", t.server_id, t.tool.name),
-                    description: t.tool.description,
-                    parameters: Some(t.tool.input_schema),
-                    strict: Some(true), // Enable strict parameter validation
-                },
-            }
-        })
-        .collect()
+/// Result indicating whether the profile's storage could be read
+pub async fn load_active_profile(
+    settings: &mut Signal<Option<AppSettings>>,
+    host: &MCPHost,
+) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let s = storage.load_settings().await?;
+    // Restore persisted MCP server state before servers are spawned from settings
+    match storage.load_server_states().await {
+        Ok(states) => host.restore_states(states).await,
+        Err(e) => warn!("Could not load MCP server states: {e:?}"),
+    }
+    // Config file and environment variables take precedence over stored settings
+    settings.set(with_overrides(s));
+    Ok(())
 }
 
-/// Executes tool calls and converts results to chat messages.
+/// Switches the model in the stored settings and applies the change to the app.
 ///
-/// This function processes tool call deltas from the LLM, extracts the server ID
-/// and tool name, executes the tools on the appropriate MCP servers, and formats
-/// the results as tool messages that can be added to the chat conversation.
+/// The stored settings are changed rather than the ones in effect, so config
+/// file and environment overrides are not saved with them.
 ///
 /// # Arguments
-/// * `tool_calls` - Vector of tool call deltas from the LLM response
-/// * `host` - MCP host for executing tool calls
+/// * `settings` - The app-wide settings signal
+/// * `model` - ID of the model to use
 ///
 /// # Returns
-/// Vector of tool result messages to add to the conversation, or an error
-/// if any tool call fails
-pub async fn call_tools(
-    tool_calls: Vec<ToolCallDelta>,
-    host: Arc<MCPHost>,
-) -> anyhow::Result<Vec<Message>> {
-    let mut new_chat: Vec<Message> = vec![];
-
-    // Process each tool call from the LLM
-    for tc in tool_calls.into_iter() {
-        let Some(f) = tc.function.as_ref() else {
-            continue; // Skip tool calls without function information
-        };
-
-        // Parse the tool name to extract server ID and tool name
-        // Format is "server_id/tool_name"
-        let parts: Vec<_> = f.name.as_deref().unwrap_or("").split("--").collect();
-
-        if parts.len() == 2 {
-            let server_id = parts[0];
-            let tool_name = parts[1];
+/// The model now in effect, which differs from `model` if an override sets it
+pub async fn switch_model(
+    settings: &mut Signal<Option<AppSettings>>,
+    model: &str,
+) -> anyhow::Result<Option<String>> {
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        anyhow::bail!("Configure a provider in the settings first");
+    };
+    s.provider.set_model(model.to_string());
+    let provider = s.provider.clone();
+    s.remember_model(&provider);
+    storage.save_settings(&s).await?;
+    let effective = with_overrides(Some(s));
+    let model = effective.as_ref().and_then(|s| s.provider.get_model());
+    settings.set(effective);
+    Ok(model)
+}
 
-            // Parse the function arguments from JSON string
-            let params_str = f.arguments.as_deref().unwrap_or("{}");
-            let arguments: Value = serde_json::from_str(params_str)?;
+/// Switches the stored settings to a model, and to the provider serving it
+/// if that is of another kind, and applies the change to the app.
+///
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `choice` - The model and the kind of provider serving it
+///
+/// # Returns
+/// The model now in effect, which differs from the chosen one if an override sets it
+pub async fn switch_to_model(
+    settings: &mut Signal<Option<AppSettings>>,
+    choice: &ModelChoice,
+) -> anyhow::Result<Option<String>> {
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        anyhow::bail!("Configure a provider in the settings first");
+    };
+    let Some(provider) = s.provider_for(choice) else {
+        anyhow::bail!("No {} provider is configured", choice.provider);
+    };
+    // Keep the provider being left in the list, so switching back restores it
+    let current = s.provider.clone();
+    s.remember_model(&current);
+    s.provider = provider.clone();
+    s.remember_model(&provider);
+    storage.save_settings(&s).await?;
+    let effective = with_overrides(Some(s));
+    let model = effective.as_ref().and_then(|s| s.provider.get_model());
+    settings.set(effective);
+    Ok(model)
+}
 
-            // Log the tool call for debugging
-            info!("Calling {server_id}/{tool_name}({arguments:?})");
+/// Records a model as the most recently used one in the stored settings, for
+/// the model switcher. Nothing is saved if it already is.
+///
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `used` - The provider the model was used with, with its model set
+pub async fn remember_model(
+    settings: &mut Signal<Option<AppSettings>>,
+    used: &ProviderSettings,
+) -> anyhow::Result<()> {
+    let listed_first = settings
+        .peek()
+        .as_ref()
+        .and_then(|s| s.recent_models.first())
+        .is_some_and(|p| p.kind() == used.kind() && p.get_model() == used.get_model());
+    if listed_first {
+        return Ok(());
+    }
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        return Ok(());
+    };
+    if s.remember_model(used) {
+        storage.save_settings(&s).await?;
+        settings.set(with_overrides(Some(s)));
+    }
+    Ok(())
+}
 
-            // Execute the tool call on the MCP server
-            let result = host.tool_call(server_id, tool_name, arguments).await?;
-            // Convert tool result to text messages
-            // Filter for text content and combine into a single message
-            let messages: Vec<String> = result
-                .content
-                .into_iter()
-                .filter(|c| c.r#type == "text") // Only process text content
-                .map(|c| c.text.unwrap_or_default())
-                .collect();
-            let text = messages.join("\n");
+/// Records the result of a provider's connection test in the stored
+/// settings, for warnings while the provider seems down.
+///
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `provider` - The provider tested
+/// * `check` - What the test found
+pub async fn record_provider_check(
+    settings: &mut Signal<Option<AppSettings>>,
+    provider: &ProviderSettings,
+    check: ProviderCheck,
+) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        anyhow::bail!("Configure a provider in the settings first");
+    };
+    s.provider_checks.insert(provider.kind().to_string(), check);
+    storage.save_settings(&s).await?;
+    settings.set(with_overrides(Some(s)));
+    Ok(())
+}
 
-            // Create a tool message with the result
-            let tcm = Message::Tool {
-                tool_call_id: tc.id.unwrap_or_else(|| "".into()),
-                content: text,
-            };
-            new_chat.push(tcm);
-        }
+/// Switches to a server group in the stored settings: its servers are
+/// enabled and all others disabled. Setting the signal syncs the host to them.
+///
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `name` - Name of the group
+pub async fn switch_server_group(
+    settings: &mut Signal<Option<AppSettings>>,
+    name: &str,
+) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        anyhow::bail!("Configure a provider in the settings first");
+    };
+    if !s.switch_server_group(name) {
+        anyhow::bail!("There is no server group named {name}");
     }
-
-    Ok(new_chat)
+    storage.save_settings(&s).await?;
+    settings.set(with_overrides(Some(s)));
+    Ok(())
 }
 
-/// Extracts tool calls from text that uses non-standard formats.
+/// Replaces the server groups in the stored settings.
 ///
-/// Some LLM models may return tool calls in custom formats rather than the
-/// standard streaming format. This function attempts to parse these alternative
-/// formats and convert them to standard ToolCallDelta objects.
+/// # Arguments
+/// * `settings` - The app-wide settings signal
+/// * `groups` - The new groups
+pub async fn save_server_groups(
+    settings: &mut Signal<Option<AppSettings>>,
+    groups: Vec<ServerGroup>,
+) -> anyhow::Result<()> {
+    let storage = get_storage().await?;
+    let Some(mut s) = storage.load_settings().await? else {
+        anyhow::bail!("Configure a provider in the settings first");
+    };
+    s.server_groups = groups;
+    storage.save_settings(&s).await?;
+    settings.set(with_overrides(Some(s)));
+    Ok(())
+}
+
+/// Snapshots the state of the MCP servers and saves it to storage.
 ///
 /// # Arguments
-/// * `text` - The text content to parse for tool calls
+/// * `host` - Global MCP host
 ///
 /// # Returns
-/// An optional ToolCallDelta if a tool call was successfully extracted
-pub fn extract_wierd_tool_calls(text: &str) -> anyhow::Result<Option<ToolCallDelta>> {
-    if text.starts_with("[TOOL_CALLS]") {
-        let t = text.replace("[TOOL_CALLS]", "");
-        let parts: Vec<String> = t.split("<SPECIAL_32>").map(|s| s.into()).collect();
-        if parts.len() < 2 {
-            return Ok(None);
-        }
-        return Ok(Some(ToolCallDelta {
-            id: Some("...".into()),
-            kind: Some("function".into()),
-            function: Some(FunctionDelta {
-                name: Some(parts[0].to_string()),
-                arguments: Some(parts[1].clone()),
-            }),
-        }));
-    }
-
-    if let Ok(Value::Object(m)) = serde_json::from_str(text)
-        && let Some(name) = m.get("name").and_then(|x| x.as_str())
-        && let Some(args) = m.get("arguments")
-    {
-        let arguments = if let Some(s) = args.as_str() {
-            Some(s.to_string())
-        } else if let Some(m) = args.as_object() {
-            let args_str = serde_json::to_string(&Value::Object(m.clone()))?;
-            Some(args_str)
-        } else {
-            None
-        };
+/// Result indicating whether the states were saved
+pub async fn save_server_states(host: &MCPHost) -> anyhow::Result<()> {
+    let states = host.export_states().await;
+    get_storage().await?.save_server_states(&states).await
+}
 
-        return Ok(Some(ToolCallDelta {
-            id: Some("...".into()),
-            kind: Some("function".into()),
-            function: Some(FunctionDelta {
-                name: Some(name.to_string()),
-                arguments,
-            }),
-        }));
+/// Saves the state of the MCP servers once [`SERVER_STATES_SAVE_DELAY`] has
+/// passed, unless a save is already waiting.
+///
+/// Exporting asks every server for its state, which is too much work for each
+/// saved chat; chats saved in quick succession share one save instead. The
+/// states are also saved when the app closes and before switching profiles.
+///
+/// # Arguments
+/// * `host` - Global MCP host
+pub fn schedule_server_states_save(host: Arc<MCPHost>) {
+    if SERVER_STATES_SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let task = spawn_forever(async move {
+        crate::core::sleep(SERVER_STATES_SAVE_DELAY).await;
+        SERVER_STATES_SAVE_PENDING.store(false, Ordering::SeqCst);
+        spawn_job("Saving MCP server states", async move {
+            save_server_states(&host).await
+        });
+    });
+    if task.is_none() {
+        SERVER_STATES_SAVE_PENDING.store(false, Ordering::SeqCst);
     }
-
-    Ok(None)
 }
 
 /// Saves a chat to storage and updates its state.
 ///
 /// This function persists the chat to storage, updates the toolset state,
-/// and handles navigation to the saved chat if it's a new chat.
+/// schedules a save of the global MCP servers' state, and handles navigation
+/// to the saved chat if it's a new chat.
 ///
 /// # Arguments
 /// * `chat` - Mutable signal containing the chat to save
 /// * `toolset` - Reference to the current toolset
+/// * `host` - Global MCP host whose server states are persisted
 /// * `display` - Mutable signal for the markdown display
 /// * `id` - Signal containing the current chat ID
 /// * `nav` - Navigator for routing
@@ -189,6 +266,7 @@ pub fn extract_wierd_tool_calls(text: &str) -> anyhow::Result<Option<ToolCallDel
 pub async fn save_chat_to_storage(
     chat: &mut Signal<Chat>,
     toolset: &Box<dyn Toolset>,
+    host: &Arc<MCPHost>,
     display: &mut Signal<Option<String>>,
     id: Signal<Option<u32>>,
     nav: &Navigator,
@@ -201,6 +279,9 @@ pub async fn save_chat_to_storage(
         }
     };
     let mut ch = chat.cloned();
+    ch.assign_message_ids();
+    // Storage does the same; this keeps the creation time in the open chat
+    ch.touch(crate::core::now_millis());
     let value = toolset.get_state().await;
     ch.value = value;
     let md = toolset.get_markdown_repr().await;
@@ -209,30 +290,124 @@ pub async fn save_chat_to_storage(
     let new_chat_id = stg.save_chat(&ch).await;
     let new_chat_id = new_chat_id?;
     ch.id = Some(new_chat_id);
+    // The chat is saved, so there is nothing left to recover
+    if let Err(e) = stg.clear_checkpoint().await {
+        warn!("Could not clear checkpoint: {e:?}");
+    }
+    // The rest can finish after the turn
+    schedule_server_states_save(host.clone());
+    let snapshot_of = ch.clone();
+    spawn_job("Saving the daily story snapshot", async move {
+        crate::snapshots::auto_snapshot(&snapshot_of).await
+    });
 
     if id() != Some(new_chat_id) {
-        nav.push(crate::Route::ChatEl { id: new_chat_id });
+        nav.push(crate::Route::ChatEl {
+            id: new_chat_id,
+            anchor: String::new(),
+        });
     }
     chat.with_mut(|c| *c = ch);
 
     Ok(())
 }
 
-/// Main loop for handling LLM responses and tool execution.
-///
-/// This function manages the conversation flow:
-/// 1. Sends the current conversation to the LLM
-/// 2. Processes streaming responses (text and tool calls)
-/// 3. Executes any requested tools
-/// 4. Continues the loop until no more tools are called
-/// 5. Implements safety limits to prevent runaway tool execution
+/// Signals of the chat UI that mirror a [`ChatSession`]'s state.
+#[derive(Clone, Copy)]
+pub struct SessionSignals {
+    pub chat: Signal<Chat>,
+    pub streaming_msg: Signal<Option<String>>,
+    pub display: Signal<Option<String>>,
+    /// Whether a turn is running, so it can be stopped
+    pub running: Signal<bool>,
+    /// Position and delivery of the message the user sent last
+    pub send_status: Signal<Option<(usize, SendStatus)>>,
+    /// Estimated tokens per second of the response streaming in
+    pub throughput: Signal<Option<f64>>,
+    /// How long the parts of the last turn took
+    pub timing: Signal<Option<TurnTiming>>,
+}
+
+impl SessionSignals {
+    /// Applies an event of the session to the signals.
+    fn apply(mut self, event: SessionEvent) {
+        match event {
+            SessionEvent::Busy(busy) => {
+                if busy {
+                    self.timing.set(None);
+                    self.throughput.set(None);
+                }
+                self.running.set(busy);
+            }
+            SessionEvent::Streaming(text) => {
+                if text.is_some() {
+                    self.mark_sent();
+                }
+                self.streaming_msg.set(text);
+            }
+            SessionEvent::Appended {
+                messages,
+                author,
+                at,
+            } => {
+                // Messages from the model mean the request got through
+                if author.is_some() {
+                    self.mark_sent();
+                }
+                self.chat.with_mut(|c| {
+                    let from = c.messages.len();
+                    c.messages.extend(messages);
+                    if let Some(author) = author {
+                        c.set_author(from, &author);
+                    }
+                    c.stamp_messages(from, at);
+                });
+            }
+            SessionEvent::Display(md) => {
+                if *self.display.peek() != md {
+                    self.display.set(md);
+                }
+            }
+            SessionEvent::Throughput(rate) => self.throughput.set(rate),
+            SessionEvent::Timing(timing) => self.timing.set(Some(timing)),
+            // Reported by the caller, from the turn's result
+            SessionEvent::Error(_) | SessionEvent::RoundLimit | SessionEvent::Cancelled => {}
+        }
+    }
+
+    /// Marks the user's last message as sent once the response starts.
+    fn mark_sent(mut self) {
+        let pending = matches!(*self.send_status.peek(), Some((_, SendStatus::Pending)));
+        if pending {
+            self.send_status
+                .with_mut(|s| s.iter_mut().for_each(|(_, s)| *s = SendStatus::Sent));
+        }
+    }
+
+    /// Opens a session on the chat, with checkpoints, whose events update the signals.
+    fn session<'a>(
+        self,
+        toolset: &'a dyn Toolset,
+        max_failures: u8,
+        cancel: CancelHandle,
+    ) -> ChatSession<'a> {
+        ChatSession::new(self.chat.peek().clone(), toolset)
+            .with_max_failures(max_failures)
+            .with_checkpoints(true)
+            .with_cancel(cancel)
+            .on_event(move |event| self.apply(event))
+    }
+}
+
+/// Runs a turn on the chat in the signals and saves the chat when it finishes.
 ///
 /// # Arguments
 /// * `client` - LLM client for making API calls
 /// * `model` - Model name to use for the conversation
-/// * `chat` - Mutable signal containing the chat messages
+/// * `signals` - The chat and the signals showing the turn's progress
 /// * `toolset` - Reference to the current toolset for getting tools
-/// * `streaming_msg` - Signal for displaying streaming responses
+/// * `max_failures` - Consecutive failed tool calls at which the loop stops
+/// * `cancel` - Handle the user stops the turn with
 /// * `save_chat_fn` - Async closure for saving the chat
 ///
 /// # Returns
@@ -240,122 +415,53 @@ pub async fn save_chat_to_storage(
 pub async fn run_tools_loop<F, Fut>(
     client: &LlmClient,
     model: &str,
-    chat: &mut Signal<Chat>,
-    toolset: &Box<dyn Toolset>,
-    streaming_msg: &mut Signal<Option<String>>,
+    signals: SessionSignals,
+    toolset: &dyn Toolset,
+    max_failures: u8,
+    cancel: CancelHandle,
     save_chat_fn: F,
 ) -> anyhow::Result<u8>
 where
     F: Fn() -> Fut,
     Fut: std::future::Future<Output = anyhow::Result<()>>,
 {
-    // Get MCP host and available tools
-    let host = toolset.get_mcp_host();
-    let tools = host.list_tools().await;
-    let tools: Vec<Tool> = tools_to_message_objects(tools);
-
-    let mut count = 0u8; // Safety counter to prevent infinite loops
-    loop {
-        // Start streaming response from LLM
-        let mut stream = client.stream(model, &chat.read().messages, &tools).await?;
-        let mut text = "".to_string();
-        let mut tool_calls = vec![];
-        let mut current_tool_call: Option<ToolCallDelta> = None;
-
-        // Process streaming response chunks
-        while let Some(e) = stream.recv().await {
-            let Some(ch) = e.choices.first() else { break };
-
-            // Handle text content (assistant response)
-            if let Some(t) = &ch.delta.content
-                && !t.is_empty()
-            {
-                text = format!("{}{}", &text, t);
-                // Update streaming display in real-time
-                streaming_msg.set(Some(text.clone()));
-            }
-
-            // Handle tool calls
-            if let Some(tools) = &ch.delta.tool_calls {
-                info!("{:?}", tools);
-                for t in tools {
-                    if current_tool_call.is_none() {
-                        current_tool_call = Some(t.clone());
-                    } else {
-                        current_tool_call.as_mut().map(|c| {
-                            let existing = c
-                                .clone()
-                                .function
-                                .map(|fd| fd.arguments.clone().unwrap_or_default())
-                                .unwrap_or_default();
-                            let delta = t
-                                .clone()
-                                .function
-                                .map(|fd| fd.arguments.clone().unwrap_or_default())
-                                .unwrap_or_else(|| "".to_string());
-                            c.function
-                                .as_mut()
-                                .map(|fd| fd.arguments = Some(format!("{existing}{delta}")));
-                        });
-                    }
-                }
-            }
-
-            if ch.finish_reason.is_some() {
-                // flush tools
-                if let Some(tc) = current_tool_call.take() {
-                    tool_calls.push(tc);
-                }
-            }
-        }
-
-        if let Some(tc) = current_tool_call.take() {
-            tool_calls.push(tc);
-        }
-
-        // Clear streaming display once complete
-        streaming_msg.set(None);
-        text = text.trim().to_string();
-
-        // Process the final response
-        if !text.is_empty() {
-            // Handle special tool call format (fallback for some models)
-            if let Ok(Some(tcd)) = extract_wierd_tool_calls(&text) {
-                tool_calls.push(tcd);
-                text = "".to_string();
-            }
-        }
-
-        if !text.is_empty() || !tool_calls.is_empty() {
-            chat.with_mut(|c| {
-                c.messages.push(Message::Assistant {
-                    content: Some(text.to_string()),
-                    tool_calls: Some(tool_calls.clone()),
-                });
-            });
-            // save_chat_fn().await?;
-        }
-
-        // If no tools were called, we're done
-        if tool_calls.is_empty() {
-            save_chat_fn().await?;
-            warn!("No tool calls, exit loop");
-            return Ok(count);
-        }
-
-        // Execute the requested tools
-        let new_messages = call_tools(tool_calls, host.clone()).await?;
-        warn!("Got {} messages after tool call", new_messages.len());
-        chat.with_mut(|c| {
-            c.messages.extend(new_messages);
-        });
+    let mut session = signals.session(toolset, max_failures, cancel);
+    let result = session.run(client, model).await;
+    drop(session);
+    let count = result?;
+    save_chat_fn().await?;
+    Ok(count)
+}
 
-        // Safety check: prevent runaway tool execution
-        count += 1;
-        if count >= 10 {
-            save_chat_fn().await?;
-            warn!("Count exceeded, exit loop");
-            return Ok(count);
-        }
-    }
+/// Streams one response on the chat in the signals without running the tools
+/// it requests.
+///
+/// # Arguments
+/// * `client` - LLM client for making API calls
+/// * `model` - Model name to use for the conversation
+/// * `signals` - The chat and the signals showing the turn's progress
+/// * `toolset` - Reference to the current toolset for getting tools
+/// * `cancel` - Handle the user stops the turn with
+/// * `save_chat_fn` - Async closure for saving the chat
+///
+/// # Returns
+/// The tool calls proposed by the model, for the user to review
+pub async fn propose_tool_calls<F, Fut>(
+    client: &LlmClient,
+    model: &str,
+    signals: SessionSignals,
+    toolset: &dyn Toolset,
+    cancel: CancelHandle,
+    save_chat_fn: F,
+) -> anyhow::Result<Vec<ToolCallDelta>>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<()>>,
+{
+    let mut session = signals.session(toolset, MAX_TOOL_FAILURES, cancel);
+    let result = session.propose(client, model).await;
+    drop(session);
+    let calls = result?;
+    save_chat_fn().await?;
+    Ok(calls)
 }